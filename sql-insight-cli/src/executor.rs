@@ -1,33 +1,224 @@
+use clap::ValueEnum;
+use serde::Serialize;
 use sql_insight::error::Error;
+use sql_insight::report::Report;
 use sql_insight::sqlparser::dialect;
-use sql_insight::NormalizerOptions;
+use sql_insight::sqlparser::parser::Parser;
+use sql_insight::{
+    CrudSummary, CrudTables, Diagnostic, Digest, DigestAggregator, FormatterOptions, LintConfig,
+    Linter, NormalizeIdentifiers, NormalizerOptions, SarifLog, Severity, StatementAnalysis, Tables,
+};
+use std::fmt::Display;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 pub trait CliExecutable {
     fn execute(&self) -> Result<Vec<String>, Error>;
+
+    /// Execute, also telling whether the output reports problems failing the command, e.g. lint errors gating CI.
+    fn execute_with_status(&self) -> Result<(Vec<String>, bool), Error> {
+        self.execute().map(|output| (output, false))
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// One line per statement
+    #[default]
+    Plain,
+    /// A single JSON report. See `sql_insight::report` for the schema.
+    Json,
+    /// An aligned table with a row per statement
+    Table,
+    /// SARIF, for code scanning in CI. Only supported by `lint`.
+    Sarif,
+}
+
+/// Render an error failing the whole input. With JSON output, the error is rendered as a report of the error
+/// instead of being returned, as [`render`] does.
+fn render_error(error: Error, output_format: OutputFormat) -> Result<Vec<String>, Error> {
+    match output_format {
+        OutputFormat::Json => serde_json::to_string_pretty(&Report::<()>::from_error(&error))
+            .map(|json| vec![json])
+            .map_err(|e| Error::IOError(e.to_string())),
+        _ => Err(error),
+    }
+}
+
+/// Render per-statement results. With JSON output, an error failing the whole input, e.g. a parser error,
+/// is rendered as a report of the error instead of being returned.
+fn render<T: Display + Serialize>(
+    results: Result<Vec<Result<T, Error>>, Error>,
+    output_format: OutputFormat,
+) -> Result<Vec<String>, Error> {
+    match output_format {
+        OutputFormat::Plain => Ok(results?
+            .iter()
+            .map(|r| match r {
+                Ok(result) => format!("{}", result),
+                Err(e) => format!("Error: {}", e),
+            })
+            .collect()),
+        OutputFormat::Json => serde_json::to_string_pretty(&Report::from(results))
+            .map(|json| vec![json])
+            .map_err(|e| Error::IOError(e.to_string())),
+        OutputFormat::Table => Ok(render_table(
+            &["#", "result"],
+            results?
+                .iter()
+                .enumerate()
+                .map(|(index, r)| {
+                    let result = match r {
+                        Ok(result) => format!("{}", result),
+                        Err(e) => format!("Error: {}", e),
+                    };
+                    vec![(index + 1).to_string(), result]
+                })
+                .collect(),
+        )),
+        OutputFormat::Sarif => Err(Error::ArgumentError(
+            "SARIF output is only supported by lint".to_string(),
+        )),
+    }
 }
 
-fn get_dialect(dialect_name: Option<&str>) -> Result<Box<dyn dialect::Dialect>, Error> {
+/// Render rows as lines of a table with columns padded to the widest cell.
+fn render_table(headers: &[&str], rows: Vec<Vec<String>>) -> Vec<String> {
+    let mut widths = headers
+        .iter()
+        .map(|h| h.chars().count())
+        .collect::<Vec<_>>();
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let line = |cells: Vec<&str>| {
+        cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:width$}", cell, width = width))
+            .collect::<Vec<_>>()
+            .join(" | ")
+            .trim_end()
+            .to_string()
+    };
+    let mut lines = vec![
+        line(headers.to_vec()),
+        widths
+            .iter()
+            .map(|width| "-".repeat(*width))
+            .collect::<Vec<_>>()
+            .join("-+-"),
+    ];
+    lines.extend(
+        rows.iter()
+            .map(|row| line(row.iter().map(String::as_str).collect())),
+    );
+    lines
+}
+
+/// Get a dialect by name as `dialect::dialect_from_str` does, but shareable across threads of parallel jobs.
+fn get_dialect(
+    dialect_name: Option<&str>,
+) -> Result<Box<dyn dialect::Dialect + Send + Sync>, Error> {
     let dialect_name = dialect_name.unwrap_or("generic");
-    dialect::dialect_from_str(dialect_name)
-        .ok_or_else(|| Error::ArgumentError(format!("Dialect not found: {}", dialect_name)))
+    let dialect: Box<dyn dialect::Dialect + Send + Sync> =
+        match dialect_name.to_lowercase().as_str() {
+            "generic" => Box::new(dialect::GenericDialect {}),
+            "mysql" => Box::new(dialect::MySqlDialect {}),
+            "postgresql" | "postgres" => Box::new(dialect::PostgreSqlDialect {}),
+            "hive" => Box::new(dialect::HiveDialect {}),
+            "sqlite" => Box::new(dialect::SQLiteDialect {}),
+            "snowflake" => Box::new(dialect::SnowflakeDialect {}),
+            "redshift" => Box::new(dialect::RedshiftSqlDialect {}),
+            "mssql" => Box::new(dialect::MsSqlDialect {}),
+            "clickhouse" => Box::new(dialect::ClickHouseDialect {}),
+            "bigquery" => Box::new(dialect::BigQueryDialect {}),
+            "ansi" => Box::new(dialect::AnsiDialect {}),
+            "duckdb" => Box::new(dialect::DuckDbDialect {}),
+            _ => {
+                return Err(Error::ArgumentError(format!(
+                    "Dialect not found: {}",
+                    dialect_name
+                )))
+            }
+        };
+    Ok(dialect)
+}
+
+/// Run `f` on a thread pool of `jobs` threads, where 0 is the number of CPUs.
+#[cfg(feature = "rayon")]
+fn in_thread_pool<T: Send>(
+    jobs: usize,
+    f: impl FnOnce() -> Result<T, Error> + Send,
+) -> Result<T, Error> {
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(jobs)
+        .build()
+        .map_err(|e| Error::ArgumentError(format!("Failed to start {} jobs: {}", jobs, e)))?
+        .install(f)
+}
+
+#[cfg(not(feature = "rayon"))]
+fn without_rayon(jobs: usize) -> Error {
+    Error::ArgumentError(format!(
+        "Cannot run {} jobs: built without the rayon feature",
+        jobs
+    ))
 }
 
 pub struct FormatExecutor {
     sql: String,
     dialect_name: Option<String>,
+    options: FormatterOptions,
+    fix: bool,
+    output_format: OutputFormat,
 }
 
 impl FormatExecutor {
     pub fn new(sql: String, dialect_name: Option<String>) -> Self {
-        Self { sql, dialect_name }
+        Self {
+            sql,
+            dialect_name,
+            options: FormatterOptions::new(),
+            fix: false,
+            output_format: OutputFormat::default(),
+        }
+    }
+
+    pub fn with_options(mut self, options: FormatterOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Normalize the casing and quoting of identifiers before formatting.
+    pub fn with_fix(mut self, fix: bool) -> Self {
+        self.fix = fix;
+        self
+    }
+
+    pub fn with_output_format(mut self, output_format: OutputFormat) -> Self {
+        self.output_format = output_format;
+        self
     }
 }
 
 impl CliExecutable for FormatExecutor {
     fn execute(&self) -> Result<Vec<String>, Error> {
-        sql_insight::format(
-            get_dialect(self.dialect_name.as_deref())?.as_ref(),
-            self.sql.as_ref(),
+        let dialect = get_dialect(self.dialect_name.as_deref())?;
+        let sql = if self.fix {
+            let rewrite = NormalizeIdentifiers::new().with_dialect(dialect.as_ref());
+            sql_insight::rewrite(dialect.as_ref(), self.sql.as_ref(), vec![Box::new(rewrite)])
+                .map(|statements| statements.join(";\n"))
+        } else {
+            Ok(self.sql.clone())
+        };
+        let result = sql.and_then(|sql| {
+            sql_insight::format_with_options(dialect.as_ref(), sql.as_ref(), self.options.clone())
+        });
+        render(
+            result.map(|result| result.into_iter().map(Ok).collect()),
+            self.output_format,
         )
     }
 }
@@ -36,6 +227,8 @@ pub struct NormalizeExecutor {
     sql: String,
     dialect_name: Option<String>,
     options: NormalizerOptions,
+    jobs: usize,
+    output_format: OutputFormat,
 }
 
 impl NormalizeExecutor {
@@ -44,6 +237,8 @@ impl NormalizeExecutor {
             sql,
             dialect_name,
             options: NormalizerOptions::new(),
+            jobs: 1,
+            output_format: OutputFormat::default(),
         }
     }
 
@@ -51,53 +246,277 @@ impl NormalizeExecutor {
         self.options = options;
         self
     }
+
+    /// Number of statements to process in parallel, where 0 is the number of CPUs.
+    pub fn with_jobs(mut self, jobs: usize) -> Self {
+        self.jobs = jobs;
+        self
+    }
+
+    pub fn with_output_format(mut self, output_format: OutputFormat) -> Self {
+        self.output_format = output_format;
+        self
+    }
 }
 
 impl CliExecutable for NormalizeExecutor {
     fn execute(&self) -> Result<Vec<String>, Error> {
-        sql_insight::normalize_with_options(
+        let dialect = get_dialect(self.dialect_name.as_deref())?;
+        let result = if self.jobs == 1 {
+            sql_insight::normalize_with_options(
+                dialect.as_ref(),
+                self.sql.as_ref(),
+                self.options.clone(),
+            )
+        } else {
+            #[cfg(feature = "rayon")]
+            {
+                in_thread_pool(self.jobs, || {
+                    sql_insight::normalize_par(
+                        dialect.as_ref(),
+                        self.sql.as_ref(),
+                        self.options.clone(),
+                    )
+                })
+            }
+            #[cfg(not(feature = "rayon"))]
+            return Err(without_rayon(self.jobs));
+        };
+        render(
+            result.map(|result| result.into_iter().map(Ok).collect()),
+            self.output_format,
+        )
+    }
+}
+
+pub struct FingerprintExecutor {
+    sql: String,
+    dialect_name: Option<String>,
+    options: NormalizerOptions,
+    output_format: OutputFormat,
+}
+
+impl FingerprintExecutor {
+    pub fn new(sql: String, dialect_name: Option<String>) -> Self {
+        Self {
+            sql,
+            dialect_name,
+            options: NormalizerOptions::new(),
+            output_format: OutputFormat::default(),
+        }
+    }
+
+    pub fn with_options(mut self, options: NormalizerOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn with_output_format(mut self, output_format: OutputFormat) -> Self {
+        self.output_format = output_format;
+        self
+    }
+}
+
+impl CliExecutable for FingerprintExecutor {
+    fn execute(&self) -> Result<Vec<String>, Error> {
+        let result = sql_insight::fingerprint_with_options(
             get_dialect(self.dialect_name.as_deref())?.as_ref(),
             self.sql.as_ref(),
             self.options.clone(),
-        )
+        );
+        render(Ok(result), self.output_format)
     }
 }
 
 pub struct TableExtractExecutor {
     pub sql: String,
     pub dialect_name: Option<String>,
+    pub jobs: usize,
+    pub output_format: OutputFormat,
 }
 
 impl TableExtractExecutor {
     pub fn new(sql: String, dialect_name: Option<String>) -> Self {
-        Self { sql, dialect_name }
+        Self {
+            sql,
+            dialect_name,
+            jobs: 1,
+            output_format: OutputFormat::default(),
+        }
+    }
+
+    /// Number of statements to process in parallel, where 0 is the number of CPUs.
+    pub fn with_jobs(mut self, jobs: usize) -> Self {
+        self.jobs = jobs;
+        self
+    }
+
+    pub fn with_output_format(mut self, output_format: OutputFormat) -> Self {
+        self.output_format = output_format;
+        self
     }
 }
 
 impl CliExecutable for TableExtractExecutor {
     fn execute(&self) -> Result<Vec<String>, Error> {
-        let result = sql_insight::extract_tables(
-            get_dialect(self.dialect_name.as_deref())?.as_ref(),
-            self.sql.as_ref(),
-        )?;
-        Ok(result
-            .iter()
-            .map(|r| match r {
-                Ok(tables) => format!("{}", tables),
-                Err(e) => format!("Error: {}", e),
-            })
-            .collect())
+        let dialect = get_dialect(self.dialect_name.as_deref())?;
+        let result = if self.jobs == 1 {
+            sql_insight::extract_tables(dialect.as_ref(), self.sql.as_ref())
+        } else {
+            #[cfg(feature = "rayon")]
+            {
+                in_thread_pool(self.jobs, || {
+                    sql_insight::extract_tables_par(dialect.as_ref(), self.sql.as_ref())
+                })
+            }
+            #[cfg(not(feature = "rayon"))]
+            return Err(without_rayon(self.jobs));
+        };
+        render(result, self.output_format)
+    }
+}
+
+pub struct AnalyzeExecutor {
+    sql: String,
+    dialect_name: Option<String>,
+    output_format: OutputFormat,
+}
+
+impl AnalyzeExecutor {
+    pub fn new(sql: String, dialect_name: Option<String>) -> Self {
+        Self {
+            sql,
+            dialect_name,
+            output_format: OutputFormat::default(),
+        }
+    }
+
+    pub fn with_output_format(mut self, output_format: OutputFormat) -> Self {
+        self.output_format = output_format;
+        self
+    }
+}
+
+impl CliExecutable for AnalyzeExecutor {
+    fn execute(&self) -> Result<Vec<String>, Error> {
+        let dialect = get_dialect(self.dialect_name.as_deref())?;
+        let result = sql_insight::measure_complexity(dialect.as_ref(), self.sql.as_ref());
+        render(result, self.output_format)
+    }
+}
+
+/// Results of every analysis of a statement, as output by `inspect`.
+#[derive(Serialize)]
+struct Inspection {
+    formatted: String,
+    normalized: String,
+    tables: Tables,
+    crud_tables: CrudTables,
+}
+
+impl TryFrom<StatementAnalysis> for Inspection {
+    type Error = Error;
+
+    fn try_from(analysis: StatementAnalysis) -> Result<Self, Self::Error> {
+        Ok(Self {
+            formatted: analysis.formatted,
+            normalized: analysis.normalized,
+            tables: analysis.tables?,
+            crud_tables: analysis.crud_tables?,
+        })
+    }
+}
+
+impl Display for Inspection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Formatted: {}\nNormalized: {}\nTables: {}\nCRUD: {}",
+            self.formatted, self.normalized, self.tables, self.crud_tables
+        )
+    }
+}
+
+pub struct InspectExecutor {
+    sql: String,
+    dialect_name: Option<String>,
+    output_format: OutputFormat,
+}
+
+impl InspectExecutor {
+    pub fn new(sql: String, dialect_name: Option<String>) -> Self {
+        Self {
+            sql,
+            dialect_name,
+            output_format: OutputFormat::default(),
+        }
+    }
+
+    pub fn with_output_format(mut self, output_format: OutputFormat) -> Self {
+        self.output_format = output_format;
+        self
+    }
+}
+
+impl CliExecutable for InspectExecutor {
+    fn execute(&self) -> Result<Vec<String>, Error> {
+        let dialect = get_dialect(self.dialect_name.as_deref())?;
+        let results =
+            sql_insight::analyze_all(dialect.as_ref(), self.sql.as_ref()).map(|results| {
+                results
+                    .into_iter()
+                    .map(Inspection::try_from)
+                    .collect::<Vec<Result<Inspection, Error>>>()
+            });
+        match self.output_format {
+            OutputFormat::Table => Ok(render_table(
+                &["#", "formatted", "normalized", "tables", "crud"],
+                results?
+                    .iter()
+                    .enumerate()
+                    .map(|(index, r)| match r {
+                        Ok(inspection) => vec![
+                            (index + 1).to_string(),
+                            inspection.formatted.clone(),
+                            inspection.normalized.clone(),
+                            inspection.tables.to_string(),
+                            inspection.crud_tables.to_string(),
+                        ],
+                        Err(e) => vec![(index + 1).to_string(), format!("Error: {}", e)],
+                    })
+                    .collect(),
+            )),
+            _ => render(results, self.output_format),
+        }
     }
 }
 
 pub struct CrudTableExtractExecutor {
     sql: String,
     dialect_name: Option<String>,
+    summary: bool,
+    output_format: OutputFormat,
 }
 
 impl CrudTableExtractExecutor {
     pub fn new(sql: String, dialect_name: Option<String>) -> Self {
-        Self { sql, dialect_name }
+        Self {
+            sql,
+            dialect_name,
+            summary: false,
+            output_format: OutputFormat::default(),
+        }
+    }
+
+    /// Merge the results of all statements into a [`CrudSummary`] instead of a result per statement.
+    pub fn with_summary(mut self, summary: bool) -> Self {
+        self.summary = summary;
+        self
+    }
+
+    pub fn with_output_format(mut self, output_format: OutputFormat) -> Self {
+        self.output_format = output_format;
+        self
     }
 }
 
@@ -106,13 +525,272 @@ impl CliExecutable for CrudTableExtractExecutor {
         let result = sql_insight::extract_crud_tables(
             get_dialect(self.dialect_name.as_deref())?.as_ref(),
             self.sql.as_ref(),
-        )?;
-        Ok(result
+        );
+        if !self.summary {
+            return render(result, self.output_format);
+        }
+        let summary = match result {
+            Ok(result) => CrudSummary::aggregate_results(result),
+            Err(e) => return render_error(e, self.output_format),
+        };
+        match self.output_format {
+            OutputFormat::Plain => Ok(summary.tables.iter().map(|t| t.to_string()).collect()),
+            OutputFormat::Table => Ok(render_table(
+                &["table", "create", "read", "update", "delete", "ddl"],
+                summary
+                    .tables
+                    .iter()
+                    .map(|t| {
+                        vec![
+                            t.table.to_string(),
+                            t.create.to_string(),
+                            t.read.to_string(),
+                            t.update.to_string(),
+                            t.delete.to_string(),
+                            t.ddl.to_string(),
+                        ]
+                    })
+                    .collect(),
+            )),
+            OutputFormat::Json => serde_json::to_string_pretty(&summary)
+                .map(|json| vec![json])
+                .map_err(|e| Error::IOError(e.to_string())),
+            OutputFormat::Sarif => Err(Error::ArgumentError(
+                "SARIF output is only supported by lint".to_string(),
+            )),
+        }
+    }
+}
+
+pub struct LintExecutor {
+    sql: String,
+    dialect_name: Option<String>,
+    fix: bool,
+    file: Option<String>,
+    config: LintConfig,
+    output_format: OutputFormat,
+}
+
+impl LintExecutor {
+    pub fn new(sql: String, dialect_name: Option<String>) -> Self {
+        Self {
+            sql,
+            dialect_name,
+            fix: false,
+            config: LintConfig::default(),
+            file: None,
+            output_format: OutputFormat::default(),
+        }
+    }
+
+    /// Path of the file the SQL was read from, reported as the location of SARIF results.
+    pub fn with_file(mut self, file: Option<String>) -> Self {
+        self.file = file;
+        self
+    }
+
+    /// Rules to disable and severities to override.
+    pub fn with_config(mut self, config: LintConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Output the fixed statements instead of diagnostics.
+    pub fn with_fix(mut self, fix: bool) -> Self {
+        self.fix = fix;
+        self
+    }
+
+    pub fn with_output_format(mut self, output_format: OutputFormat) -> Self {
+        self.output_format = output_format;
+        self
+    }
+}
+
+impl LintExecutor {
+    /// Fail on rule ids of the configuration that no rule of the linter has, e.g. misspelled ones.
+    fn validate_rule_ids(&self, linter: &Linter) -> Result<(), Error> {
+        let rule_ids = linter
+            .rules()
             .iter()
-            .map(|r| match r {
-                Ok(crud_tables) => format!("{}", crud_tables),
-                Err(e) => format!("Error: {}", e),
-            })
-            .collect())
+            .map(|rule| rule.id())
+            .collect::<Vec<_>>();
+        let configured = self
+            .config
+            .disabled_rules
+            .iter()
+            .chain(self.config.severities.keys());
+        for rule_id in configured {
+            if !rule_ids.contains(&rule_id.as_str()) {
+                return Err(Error::ArgumentError(format!(
+                    "Rule not found: {rule_id}. Available rules: {}",
+                    rule_ids.join(", ")
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl CliExecutable for LintExecutor {
+    fn execute(&self) -> Result<Vec<String>, Error> {
+        self.execute_with_status().map(|(output, _)| output)
+    }
+
+    /// Lint errors, i.e. diagnostics of error severity, fail the command.
+    fn execute_with_status(&self) -> Result<(Vec<String>, bool), Error> {
+        let dialect = get_dialect(self.dialect_name.as_deref())?;
+        let linter = Linter::new(sql_insight::default_rules()).with_config(self.config.clone());
+        self.validate_rule_ids(&linter)?;
+        if self.fix {
+            let result = linter.fix(dialect.as_ref(), self.sql.as_ref());
+            return render(
+                result.map(|result| result.into_iter().map(Ok).collect()),
+                self.output_format,
+            )
+            .map(|output| (output, false));
+        }
+        let per_statement = linter.lint_by_statement(dialect.as_ref(), self.sql.as_ref());
+        let failed = per_statement.as_ref().is_ok_and(|per_statement| {
+            per_statement
+                .iter()
+                .flatten()
+                .any(|diagnostic| diagnostic.severity == Severity::Error)
+        });
+        let flatten = |per_statement: Result<Vec<Vec<Diagnostic>>, Error>| {
+            per_statement
+                .map(|per_statement| per_statement.into_iter().flatten().collect::<Vec<_>>())
+        };
+        let output = match self.output_format {
+            OutputFormat::Plain => Ok(flatten(per_statement)?
+                .iter()
+                .map(|d| d.to_string())
+                .collect()),
+            OutputFormat::Table => Ok(render_table(
+                &["#", "severity", "rule", "message"],
+                flatten(per_statement)?
+                    .iter()
+                    .map(|d| {
+                        vec![
+                            (d.statement_index + 1).to_string(),
+                            d.severity.to_string(),
+                            d.rule_id.clone(),
+                            d.message.clone(),
+                        ]
+                    })
+                    .collect(),
+            )),
+            OutputFormat::Sarif => {
+                let log = SarifLog::new(
+                    linter.rules(),
+                    &flatten(per_statement)?,
+                    self.file.as_deref(),
+                );
+                serde_json::to_string_pretty(&log)
+                    .map(|json| vec![json])
+                    .map_err(|e| Error::IOError(e.to_string()))
+            }
+            OutputFormat::Json => {
+                let results = per_statement
+                    .map(|per_statement| per_statement.into_iter().map(Ok).collect::<Vec<_>>());
+                serde_json::to_string_pretty(&Report::from(results))
+                    .map(|json| vec![json])
+                    .map_err(|e| Error::IOError(e.to_string()))
+            }
+        };
+        output.map(|output| (output, failed))
+    }
+}
+
+pub struct StatsExecutor {
+    sql: String,
+    dialect_name: Option<String>,
+    sqlite: Option<String>,
+    output_format: OutputFormat,
+}
+
+impl StatsExecutor {
+    pub fn new(sql: String, dialect_name: Option<String>) -> Self {
+        Self {
+            sql,
+            dialect_name,
+            sqlite: None,
+            output_format: OutputFormat::default(),
+        }
+    }
+
+    /// Path of a SQLite database to accumulate digests into across runs.
+    pub fn with_sqlite(mut self, sqlite: Option<String>) -> Self {
+        self.sqlite = sqlite;
+        self
+    }
+
+    pub fn with_output_format(mut self, output_format: OutputFormat) -> Self {
+        self.output_format = output_format;
+        self
+    }
+
+    fn digests(&self, dialect: &dyn dialect::Dialect, seen_at: u64) -> Result<Vec<Digest>, Error> {
+        let mut aggregator = DigestAggregator::new();
+        for statement in Parser::parse_sql(dialect, self.sql.as_ref())? {
+            aggregator.add_statement(&statement, seen_at)?;
+        }
+        let digests = aggregator.digests();
+        match &self.sqlite {
+            Some(path) => self.persist(path, digests),
+            None => Ok(digests),
+        }
+    }
+
+    fn persist(&self, path: &str, digests: Vec<Digest>) -> Result<Vec<Digest>, Error> {
+        #[cfg(feature = "sqlite")]
+        {
+            let mut sink = sql_insight::SqliteDigestSink::open(path)?;
+            sink.write(&digests)?;
+            sink.read()
+        }
+        #[cfg(not(feature = "sqlite"))]
+        {
+            let _ = digests;
+            Err(Error::ArgumentError(format!(
+                "Cannot write {}: built without the sqlite feature",
+                path
+            )))
+        }
+    }
+}
+
+impl CliExecutable for StatsExecutor {
+    fn execute(&self) -> Result<Vec<String>, Error> {
+        let dialect = get_dialect(self.dialect_name.as_deref())?;
+        let seen_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs());
+        let digests = match self.digests(dialect.as_ref(), seen_at) {
+            Ok(digests) => digests,
+            Err(e) => return render_error(e, self.output_format),
+        };
+        match self.output_format {
+            OutputFormat::Plain => Ok(digests.iter().map(|d| d.to_string()).collect()),
+            OutputFormat::Table => Ok(render_table(
+                &["count", "fingerprint", "normalized"],
+                digests
+                    .iter()
+                    .map(|d| {
+                        vec![
+                            d.count.to_string(),
+                            d.fingerprint.clone(),
+                            d.normalized.clone(),
+                        ]
+                    })
+                    .collect(),
+            )),
+            OutputFormat::Json => serde_json::to_string_pretty(&digests)
+                .map(|json| vec![json])
+                .map_err(|e| Error::IOError(e.to_string())),
+            OutputFormat::Sarif => Err(Error::ArgumentError(
+                "SARIF output is only supported by lint".to_string(),
+            )),
+        }
     }
 }
@@ -0,0 +1,141 @@
+use crate::executor::OutputFormat;
+use clap::ValueEnum;
+use sql_insight::error::Error;
+use std::io::{self, IsTerminal, Write};
+use std::process::{Command, Stdio};
+
+/// Number of lines of output beyond which it is paged, unless `LINES` tells the height of the terminal.
+const DEFAULT_PAGE_HEIGHT: usize = 24;
+
+pub const HELP: &str = "\
+\\format [plain|json|table]  Show or set the output format
+\\pager [on|off]             Show or toggle paging long output through $PAGER
+\\save FILE                  Write the last result to FILE
+\\help                       Show this help";
+
+/// State of an interactive session, changed by backslash commands.
+pub struct Session {
+    pub output_format: OutputFormat,
+    pub pager: bool,
+    /// Output of the last successfully executed SQL, written by `\save`.
+    pub last_result: Vec<String>,
+}
+
+impl Session {
+    pub fn new(output_format: OutputFormat) -> Self {
+        Self {
+            output_format,
+            pager: true,
+            last_result: vec![],
+        }
+    }
+
+    /// Run a backslash command, returning the message to show.
+    pub fn command(&mut self, line: &str) -> Result<String, Error> {
+        let mut words = line.split_whitespace();
+        let name = words.next().unwrap_or_default();
+        let arg = words.next();
+        match (name, arg) {
+            ("\\format", None) => Ok(format!("Output format: {}", self.output_format_name())),
+            ("\\format", Some(format)) => {
+                // SARIF is only supported by lint, so it is not available to every SQL of the session.
+                self.output_format = OutputFormat::from_str(format, true)
+                    .ok()
+                    .filter(|output_format| *output_format != OutputFormat::Sarif)
+                    .ok_or_else(|| {
+                        Error::ArgumentError(format!(
+                            "Unknown output format: {}. Available formats: plain, json, table",
+                            format
+                        ))
+                    })?;
+                Ok(format!("Output format: {}", self.output_format_name()))
+            }
+            ("\\pager", None) => Ok(format!("Pager: {}", on_off(self.pager))),
+            ("\\pager", Some(pager @ ("on" | "off"))) => {
+                self.pager = pager == "on";
+                Ok(format!("Pager: {}", on_off(self.pager)))
+            }
+            ("\\save", Some(file)) => {
+                if self.last_result.is_empty() {
+                    return Err(Error::ArgumentError("No result to save".to_string()));
+                }
+                let mut contents = self.last_result.join("\n");
+                contents.push('\n');
+                std::fs::write(file, contents)
+                    .map_err(|e| Error::IOError(format!("Failed to write file {}: {}", file, e)))?;
+                Ok(format!(
+                    "Saved {} lines to {}",
+                    line_count(&self.last_result),
+                    file
+                ))
+            }
+            ("\\help", None) => Ok(HELP.to_string()),
+            _ => Err(Error::ArgumentError(format!(
+                "Invalid command: {}. Type `\\help` for commands.",
+                line
+            ))),
+        }
+    }
+
+    /// Print output, through `$PAGER` if paging is on, stdout is a terminal and the output is longer than a page.
+    pub fn print(&self, lines: &[String]) -> Result<(), Error> {
+        if self.pager && io::stdout().is_terminal() && line_count(lines) > page_height() {
+            if let Some(pager) = std::env::var("PAGER").ok().filter(|p| !p.trim().is_empty()) {
+                if page(&pager, lines).is_ok() {
+                    return Ok(());
+                }
+            }
+        }
+        let mut stdout = io::stdout().lock();
+        for line in lines {
+            writeln!(stdout, "{}", line).map_err(|e| Error::IOError(e.to_string()))?;
+        }
+        Ok(())
+    }
+
+    fn output_format_name(&self) -> String {
+        self.output_format
+            .to_possible_value()
+            .map_or_else(String::new, |value| value.get_name().to_string())
+    }
+}
+
+fn on_off(on: bool) -> &'static str {
+    if on {
+        "on"
+    } else {
+        "off"
+    }
+}
+
+/// Number of terminal lines of output, where an element may span several lines, e.g. a JSON report.
+fn line_count(lines: &[String]) -> usize {
+    lines.iter().map(|line| line.lines().count()).sum()
+}
+
+fn page_height() -> usize {
+    std::env::var("LINES")
+        .ok()
+        .and_then(|lines| lines.parse().ok())
+        .unwrap_or(DEFAULT_PAGE_HEIGHT)
+}
+
+/// Write lines to the stdin of the pager, e.g. `less -R`, and wait for it to exit.
+fn page(pager: &str, lines: &[String]) -> io::Result<()> {
+    let mut words = pager.split_whitespace();
+    let program = words.next().unwrap_or_default();
+    let mut child = Command::new(program)
+        .args(words)
+        .stdin(Stdio::piped())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        for line in lines {
+            // The pager may exit before reading everything, e.g. when quit early.
+            if writeln!(stdin, "{}", line).is_err() {
+                break;
+            }
+        }
+    }
+    child.wait()?;
+    Ok(())
+}
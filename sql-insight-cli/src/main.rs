@@ -1,13 +1,18 @@
 mod executor;
+mod interactive;
 
 use crate::executor::{
-    CliExecutable, CrudTableExtractExecutor, FormatExecutor, NormalizeExecutor,
+    AnalyzeExecutor, CliExecutable, CrudTableExtractExecutor, FingerprintExecutor, FormatExecutor,
+    InspectExecutor, LintExecutor, NormalizeExecutor, OutputFormat, StatsExecutor,
     TableExtractExecutor,
 };
+use crate::interactive::Session;
 use clap::{ArgGroup, Parser, Subcommand};
 use sql_insight::error::Error;
-use sql_insight::NormalizerOptions;
-use std::io::{self, Write};
+use sql_insight::{
+    Encoding, FormatterOptions, KeywordCase, LintConfig, NormalizerOptions, Severity,
+};
+use std::io::{self, IsTerminal, Read, Write};
 use std::process::ExitCode;
 
 #[derive(Debug, Parser)]
@@ -22,7 +27,7 @@ struct Cli {
 }
 
 #[derive(Parser, Debug)]
-#[clap(group(ArgGroup::new("source").args(& ["sql", "file"]).required(false)))]
+#[clap(group(ArgGroup::new("source").args(& ["sql", "file", "interactive"]).required(false)))]
 struct CommonOptions {
     /// The subject SQL to operate on
     #[clap(value_parser, group = "source")]
@@ -32,9 +37,43 @@ struct CommonOptions {
     /// Default: generic.
     #[clap(short, long)]
     dialect: Option<String>,
-    /// The file containing the SQL to operate on
+    /// The file containing the SQL to operate on, or `-` for standard input.
+    /// Without SQL nor a file, SQL is read from standard input when it is not a terminal, e.g. piped from another command.
     #[clap(short, long, value_parser, group = "source")]
     file: Option<String>,
+    /// Enter interactive mode even when standard input is not a terminal.
+    #[clap(short, long, group = "source")]
+    interactive: bool,
+    /// The encoding of the file. Available encodings: utf-8, latin1, utf-16, utf-16le, utf-16be.
+    /// A byte order mark is stripped and CRLF and CR line endings are converted into LF.
+    #[clap(long, default_value = "utf-8", value_parser = parse_encoding)]
+    encoding: Encoding,
+    /// The output format. `json` emits a single report covering all statements. `sarif` is only supported by `lint`.
+    /// In interactive mode, it can be changed with `\format`.
+    #[clap(short, long, value_enum, default_value_t = OutputFormat::Plain)]
+    output: OutputFormat,
+}
+
+#[derive(Parser, Debug)]
+struct FormatCommandOptions {
+    #[clap(flatten)]
+    common_options: CommonOptions,
+    /// Break statements into lines, starting each clause on a line of its own and indenting subqueries.
+    #[clap(long)]
+    multi_line: bool,
+    /// The number of spaces of each level of indentation in multi-line output.
+    #[clap(long, default_value_t = 2)]
+    indent_width: usize,
+    /// The casing of keywords. Available keyword cases: upper, lower, preserve.
+    #[clap(long, default_value = "upper", value_parser = parse_keyword_case)]
+    keyword_case: KeywordCase,
+    /// The width beyond which lines of multi-line output are wrapped where possible.
+    #[clap(long, default_value_t = 80)]
+    max_line_width: usize,
+    /// Normalize identifiers before formatting: lowercase unquoted identifiers and remove quotes
+    /// the dialect does not need.
+    #[clap(long)]
+    fix: bool,
 }
 
 #[derive(Parser, Debug)]
@@ -47,37 +86,87 @@ struct NormalizeCommandOptions {
     /// Unify VALUES lists to a single form when all elements are literal values. For example, `VALUES (1, 2, 3), (4, 5, 6)` becomes `VALUES (...)`.
     #[clap(long)]
     unify_values: bool,
+    /// Keep placeholders already in SQL as written instead of replacing them. For example, `a = $1 AND b = 2` becomes `a = $1 AND b = ?`.
+    #[clap(long)]
+    keep_placeholders: bool,
+    /// The number of statements to parse and analyze in parallel, where 0 is the number of CPUs.
+    #[clap(short, long, default_value_t = 1)]
+    jobs: usize,
+}
+
+#[derive(Parser, Debug)]
+struct ExtractTablesCommandOptions {
+    #[clap(flatten)]
+    common_options: CommonOptions,
+    /// The number of statements to parse and analyze in parallel, where 0 is the number of CPUs.
+    #[clap(short, long, default_value_t = 1)]
+    jobs: usize,
+}
+
+#[derive(Parser, Debug)]
+struct FingerprintCommandOptions {
+    #[clap(flatten)]
+    common_options: CommonOptions,
+    /// Unify IN lists before hashing, so statements differing only in the number of elements of IN lists have the same fingerprint.
+    #[clap(long)]
+    unify_in_list: bool,
+    /// Unify VALUES lists before hashing, so statements differing only in the number of rows of VALUES lists have the same fingerprint.
+    #[clap(long)]
+    unify_values: bool,
+}
+
+#[derive(Parser, Debug)]
+struct ExtractCrudCommandOptions {
+    #[clap(flatten)]
+    common_options: CommonOptions,
+    /// Merge the results of all statements into a summary with the number of statements
+    /// creating, reading, updating, deleting and changing each table by DDL.
+    #[clap(long)]
+    summary: bool,
+}
+
+#[derive(Parser, Debug)]
+struct LintCommandOptions {
+    #[clap(flatten)]
+    common_options: CommonOptions,
+    /// Apply automatic fixes and output the fixed SQL instead of diagnostics.
+    #[clap(long)]
+    fix: bool,
+    /// Override the severity of a rule, e.g. `select-star=error`. Available severities: info, warning, error.
+    /// Can be given multiple times.
+    #[clap(long, value_name = "RULE=SEVERITY", value_parser = parse_rule_severity)]
+    severity: Vec<(String, Severity)>,
+    /// Disable a rule, e.g. `select-without-limit`. Can be given multiple times.
+    #[clap(long, value_name = "RULE")]
+    disable: Vec<String>,
+}
+
+#[derive(Parser, Debug)]
+struct StatsCommandOptions {
+    #[clap(flatten)]
+    common_options: CommonOptions,
+    /// Accumulate digests into the SQLite database at the path across runs, and output all accumulated digests.
+    /// First and last seen are Unix timestamps of the runs.
+    #[clap(long)]
+    sqlite: Option<String>,
 }
 
 enum ProcessType {
     Sql(String),
     File(String),
+    Stdin,
     Interactive,
 }
 
 impl From<&Commands> for ProcessType {
     fn from(command: &Commands) -> Self {
-        match command {
-            Commands::Format(opts)
-            | Commands::ExtractCrud(opts)
-            | Commands::ExtractTables(opts) => {
-                if opts.sql.is_some() {
-                    ProcessType::Sql(opts.sql.clone().unwrap())
-                } else if opts.file.is_some() {
-                    ProcessType::File(opts.file.clone().unwrap())
-                } else {
-                    ProcessType::Interactive
-                }
-            }
-            Commands::Normalize(opts) => {
-                if opts.common_options.sql.is_some() {
-                    ProcessType::Sql(opts.common_options.sql.clone().unwrap())
-                } else if opts.common_options.file.is_some() {
-                    ProcessType::File(opts.common_options.file.clone().unwrap())
-                } else {
-                    ProcessType::Interactive
-                }
-            }
+        let opts = command.common_options();
+        match (&opts.sql, &opts.file) {
+            (Some(sql), _) => ProcessType::Sql(sql.clone()),
+            (None, Some(file)) if file == "-" => ProcessType::Stdin,
+            (None, Some(file)) => ProcessType::File(file.clone()),
+            (None, None) if !opts.interactive && !io::stdin().is_terminal() => ProcessType::Stdin,
+            (None, None) => ProcessType::Interactive,
         }
     }
 }
@@ -85,48 +174,85 @@ impl From<&Commands> for ProcessType {
 #[derive(Subcommand, Debug)]
 enum Commands {
     /// Format SQL
-    Format(CommonOptions),
+    Format(FormatCommandOptions),
     /// Normalize SQL
     Normalize(NormalizeCommandOptions),
+    /// Fingerprint SQL with a stable hash of the normalized form of each statement
+    Fingerprint(FingerprintCommandOptions),
     /// Extract CRUD operations from SQL
-    ExtractCrud(CommonOptions),
+    ExtractCrud(ExtractCrudCommandOptions),
     /// Extract tables from SQL
-    ExtractTables(CommonOptions),
+    ExtractTables(ExtractTablesCommandOptions),
+    /// Analyze the complexity of each statement, such as the number of joins and the depth of subqueries
+    Analyze(CommonOptions),
+    /// Format, normalize and extract tables and CRUD tables from SQL at once, parsing it only once
+    Inspect(CommonOptions),
+    /// Lint SQL with the built-in rules, exiting with a non-zero status on diagnostics of error severity
+    Lint(LintCommandOptions),
+    /// Aggregate statements differing only in literal values into digests with counts
+    Stats(StatsCommandOptions),
 }
 
 impl Commands {
-    fn execute(&self) -> Result<Vec<String>, Error> {
+    /// Execute the command, returning its output and whether it reports problems failing the command.
+    fn execute(&self) -> Result<(Vec<String>, bool), Error> {
         match ProcessType::from(self) {
             ProcessType::Sql(sql) => self.execute_sql(sql),
             ProcessType::File(file) => self.execute_file(file),
+            ProcessType::Stdin => self.execute_stdin(),
             ProcessType::Interactive => self.execute_interactive(),
         }
     }
 
-    fn execute_sql(&self, sql: String) -> Result<Vec<String>, Error> {
-        self.executor(sql).execute()
+    fn common_options(&self) -> &CommonOptions {
+        match self {
+            Commands::Analyze(opts) | Commands::Inspect(opts) => opts,
+            Commands::Format(FormatCommandOptions { common_options, .. })
+            | Commands::Normalize(NormalizeCommandOptions { common_options, .. })
+            | Commands::Fingerprint(FingerprintCommandOptions { common_options, .. })
+            | Commands::ExtractTables(ExtractTablesCommandOptions { common_options, .. })
+            | Commands::ExtractCrud(ExtractCrudCommandOptions { common_options, .. })
+            | Commands::Lint(LintCommandOptions { common_options, .. })
+            | Commands::Stats(StatsCommandOptions { common_options, .. }) => common_options,
+        }
     }
 
-    fn execute_file(&self, file: String) -> Result<Vec<String>, Error> {
-        match std::fs::read_to_string(file.clone()) {
-            Ok(sql) => self.executor(sql).execute(),
-            Err(e) => Err(Error::ArgumentError(format!(
-                "Failed to read file {}: {}",
-                file, e
-            ))),
-        }
+    fn execute_sql(&self, sql: String) -> Result<(Vec<String>, bool), Error> {
+        self.executor(sql, self.common_options().output)
+            .execute_with_status()
     }
 
-    fn execute_interactive(&self) -> Result<Vec<String>, Error> {
+    fn execute_file(&self, file: String) -> Result<(Vec<String>, bool), Error> {
+        let bytes = std::fs::read(file.clone())
+            .map_err(|e| Error::ArgumentError(format!("Failed to read file {}: {}", file, e)))?;
+        let sql = sql_insight::decode_sql(&bytes, self.common_options().encoding)
+            .map_err(|e| Error::ArgumentError(format!("Failed to decode file {}: {}", file, e)))?;
+        self.executor(sql, self.common_options().output)
+            .execute_with_status()
+    }
+
+    fn execute_stdin(&self) -> Result<(Vec<String>, bool), Error> {
+        let mut bytes = vec![];
+        io::stdin()
+            .read_to_end(&mut bytes)
+            .map_err(|e| Error::IOError(format!("Failed to read standard input: {}", e)))?;
+        let sql = sql_insight::decode_sql(&bytes, self.common_options().encoding)
+            .map_err(|e| Error::ArgumentError(format!("Failed to decode standard input: {}", e)))?;
+        self.executor(sql, self.common_options().output)
+            .execute_with_status()
+    }
+
+    fn execute_interactive(&self) -> Result<(Vec<String>, bool), Error> {
         self.entering_interactive_mode()?;
-        Ok(vec![])
+        Ok((vec![], false))
     }
 
     fn entering_interactive_mode(&self) -> Result<(), Error> {
         println!(
             "Entering interactive mode. Type sql statement end with `;` to execute. \
-             Type `exit` or `quit` to exit."
+             Type `exit` or `quit` to exit, or `\\help` for commands."
         );
+        let mut session = Session::new(self.common_options().output);
         let stdin = io::stdin();
         let mut stdout = io::stdout();
         let mut input_buffer = String::new();
@@ -139,10 +265,16 @@ impl Commands {
             }
             stdout.flush().map_err(|e| Error::IOError(e.to_string()))?;
             let mut line = String::new();
-            stdin
+            let read = stdin
                 .read_line(&mut line)
                 .map_err(|e| Error::IOError(e.to_string()))?;
-            let line = line.trim();
+            // End of input, e.g. Ctrl-D or the end of a piped file.
+            if read == 0 {
+                println!();
+                break Ok(());
+            }
+            // A byte order mark may lead input piped from files.
+            let line = line.trim().trim_start_matches('\u{feff}');
             if line.is_empty() {
                 continue;
             }
@@ -150,14 +282,23 @@ impl Commands {
                 println!("Bye");
                 break Ok(());
             }
+            if new_input && line.starts_with('\\') {
+                match session.command(line) {
+                    Ok(message) => println!("{}", message),
+                    Err(e) => eprintln!("Error: {}", e),
+                }
+                continue;
+            }
             input_buffer.push_str(line);
             input_buffer.push('\n');
             if line.ends_with(';') {
-                match self.executor(input_buffer.clone()).execute() {
+                match self
+                    .executor(input_buffer.clone(), session.output_format)
+                    .execute()
+                {
                     Ok(result) => {
-                        for r in result {
-                            println!("{}", r);
-                        }
+                        session.print(&result)?;
+                        session.last_result = result;
                     }
                     Err(e) => {
                         eprintln!("Error: {}", e);
@@ -171,35 +312,104 @@ impl Commands {
         }
     }
 
-    fn executor(&self, sql: String) -> Box<dyn CliExecutable> {
+    fn executor(&self, sql: String, output_format: OutputFormat) -> Box<dyn CliExecutable> {
         match self {
-            Commands::Format(opts) => Box::new(FormatExecutor::new(sql, opts.dialect.clone())),
+            Commands::Format(opts) => Box::new(
+                FormatExecutor::new(sql, opts.common_options.dialect.clone())
+                    .with_options(
+                        FormatterOptions::new()
+                            .with_multi_line(opts.multi_line)
+                            .with_indent_width(opts.indent_width)
+                            .with_keyword_case(opts.keyword_case)
+                            .with_max_line_width(opts.max_line_width),
+                    )
+                    .with_fix(opts.fix)
+                    .with_output_format(output_format),
+            ),
             Commands::Normalize(opts) => Box::new(
-                NormalizeExecutor::new(sql, opts.common_options.dialect.clone()).with_options(
-                    NormalizerOptions::new()
-                        .with_unify_in_list(opts.unify_in_list)
-                        .with_unify_values(opts.unify_values),
-                ),
+                NormalizeExecutor::new(sql, opts.common_options.dialect.clone())
+                    .with_options(
+                        NormalizerOptions::new()
+                            .with_unify_in_list(opts.unify_in_list)
+                            .with_unify_values(opts.unify_values)
+                            .with_keep_placeholders(opts.keep_placeholders),
+                    )
+                    .with_jobs(opts.jobs)
+                    .with_output_format(output_format),
+            ),
+            Commands::Fingerprint(opts) => Box::new(
+                FingerprintExecutor::new(sql, opts.common_options.dialect.clone())
+                    .with_options(
+                        NormalizerOptions::new()
+                            .with_unify_in_list(opts.unify_in_list)
+                            .with_unify_values(opts.unify_values),
+                    )
+                    .with_output_format(output_format),
+            ),
+            Commands::ExtractCrud(opts) => Box::new(
+                CrudTableExtractExecutor::new(sql, opts.common_options.dialect.clone())
+                    .with_summary(opts.summary)
+                    .with_output_format(output_format),
+            ),
+            Commands::ExtractTables(opts) => Box::new(
+                TableExtractExecutor::new(sql, opts.common_options.dialect.clone())
+                    .with_jobs(opts.jobs)
+                    .with_output_format(output_format),
+            ),
+            Commands::Analyze(opts) => Box::new(
+                AnalyzeExecutor::new(sql, opts.dialect.clone()).with_output_format(output_format),
+            ),
+            Commands::Inspect(opts) => Box::new(
+                InspectExecutor::new(sql, opts.dialect.clone()).with_output_format(output_format),
+            ),
+            Commands::Lint(opts) => Box::new(
+                LintExecutor::new(sql, opts.common_options.dialect.clone())
+                    .with_config(opts.severity.iter().cloned().fold(
+                        LintConfig::new().with_disabled_rules(opts.disable.clone()),
+                        |config, (rule_id, severity)| config.with_severity(rule_id, severity),
+                    ))
+                    .with_fix(opts.fix)
+                    .with_file(opts.common_options.file.clone())
+                    .with_output_format(output_format),
+            ),
+            Commands::Stats(opts) => Box::new(
+                StatsExecutor::new(sql, opts.common_options.dialect.clone())
+                    .with_sqlite(opts.sqlite.clone())
+                    .with_output_format(output_format),
             ),
-            Commands::ExtractCrud(opts) => {
-                Box::new(CrudTableExtractExecutor::new(sql, opts.dialect.clone()))
-            }
-            Commands::ExtractTables(opts) => {
-                Box::new(TableExtractExecutor::new(sql, opts.dialect.clone()))
-            }
         }
     }
 }
 
+fn parse_encoding(encoding: &str) -> Result<Encoding, String> {
+    encoding.parse().map_err(|e: Error| e.to_string())
+}
+
+fn parse_rule_severity(rule_severity: &str) -> Result<(String, Severity), String> {
+    let (rule_id, severity) = rule_severity
+        .split_once('=')
+        .ok_or_else(|| format!("Expected RULE=SEVERITY: {rule_severity}"))?;
+    let severity = severity.parse().map_err(|e: Error| e.to_string())?;
+    Ok((rule_id.to_string(), severity))
+}
+
+fn parse_keyword_case(keyword_case: &str) -> Result<KeywordCase, String> {
+    keyword_case.parse().map_err(|e: Error| e.to_string())
+}
+
 fn main() -> ExitCode {
     let args = Cli::parse();
     let result = args.command.execute();
     match result {
-        Ok(result) => {
+        Ok((result, failed)) => {
             for r in result {
                 println!("{}", r);
             }
-            ExitCode::SUCCESS
+            if failed {
+                ExitCode::FAILURE
+            } else {
+                ExitCode::SUCCESS
+            }
         }
         Err(e) => {
             eprintln!("Error: {}", e);
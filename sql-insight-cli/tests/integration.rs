@@ -37,6 +37,47 @@ mod integration {
                 .stderr("");
         }
 
+        #[test]
+        fn test_format_with_options() {
+            sql_insight_cmd()
+                .arg("format")
+                .arg("--multi-line")
+                .arg("--indent-width")
+                .arg("4")
+                .arg("--keyword-case")
+                .arg("lower")
+                .arg("SELECT a FROM t1 WHERE b IN (SELECT b FROM t2);")
+                .assert()
+                .success()
+                .stdout("select a\nfrom t1\nwhere b in (\n    select b\n    from t2\n)\n")
+                .stderr("");
+        }
+
+        #[test]
+        fn test_format_with_fix() {
+            sql_insight_cmd()
+                .arg("format")
+                .arg("--dialect")
+                .arg("postgresql")
+                .arg("--fix")
+                .arg("SELECT \"name\", \"Name\", Id FROM \"Users\" AS U;")
+                .assert()
+                .success()
+                .stdout("SELECT name, \"Name\", id FROM \"Users\" AS u\n")
+                .stderr("");
+        }
+
+        #[test]
+        fn test_format_with_invalid_keyword_case() {
+            sql_insight_cmd()
+                .arg("format")
+                .arg("--keyword-case")
+                .arg("title")
+                .arg("SELECT a FROM t1;")
+                .assert()
+                .failure();
+        }
+
         #[test]
         fn test_format_from_file() {
             let mut temp_file = NamedTempFile::new().unwrap();
@@ -52,6 +93,80 @@ mod integration {
                 .stdout("SELECT * FROM t1\nINSERT INTO t2 (a) VALUES (1)\n")
                 .stderr("");
         }
+
+        #[test]
+        fn test_format_from_file_with_bom_and_crlf() {
+            let mut temp_file = NamedTempFile::new().unwrap();
+            temp_file
+                .write_all(b"\xEF\xBB\xBFselect a -- comment\rfrom t1;\r\nselect b from t2;\r\n")
+                .unwrap();
+            sql_insight_cmd()
+                .arg("format")
+                .arg("--file")
+                .arg(temp_file.path())
+                .assert()
+                .success()
+                .stdout("SELECT a FROM t1\nSELECT b FROM t2\n")
+                .stderr("");
+        }
+
+        #[test]
+        fn test_format_from_file_with_encoding() {
+            let mut temp_file = NamedTempFile::new().unwrap();
+            temp_file.write_all(b"select '\xE9' from t1;").unwrap();
+            sql_insight_cmd()
+                .arg("format")
+                .arg("--file")
+                .arg(temp_file.path())
+                .arg("--encoding")
+                .arg("latin1")
+                .assert()
+                .success()
+                .stdout("SELECT '\u{e9}' FROM t1\n")
+                .stderr("");
+            sql_insight_cmd()
+                .arg("format")
+                .arg("--file")
+                .arg(temp_file.path())
+                .assert()
+                .failure()
+                .stdout("")
+                .stderr(predicate::str::contains("invalid UTF-8 at byte 8"));
+        }
+    }
+
+    mod fingerprint {
+        use super::*;
+
+        #[test]
+        fn test_fingerprint() {
+            sql_insight_cmd()
+                .arg("fingerprint")
+                .arg("select * from t1 where a = 1; SELECT *  FROM t1 WHERE a = 'x'; selec")
+                .assert()
+                .success()
+                .stdout(predicate::str::starts_with(
+                    "fb114be00126bafe SELECT * FROM t1 WHERE a = ?\n\
+                     fb114be00126bafe SELECT * FROM t1 WHERE a = ?\n\
+                     Error: ",
+                ))
+                .stderr("");
+        }
+
+        #[test]
+        fn test_fingerprint_with_unify_values_option() {
+            sql_insight_cmd()
+                .arg("fingerprint")
+                .arg("--unify-values")
+                .arg("insert into t2 (a) values (1); insert into t2 (a) values (2), (3);")
+                .assert()
+                .success()
+                .stdout(
+                    "cf3e8fe4134e8839 INSERT INTO t2 (a) VALUES (...)\n\
+                     cf3e8fe4134e8839 INSERT INTO t2 (a) VALUES (...)\n",
+                )
+                .stderr("");
+        }
     }
 
     mod normalize {
@@ -113,6 +228,34 @@ mod integration {
                 .stderr("");
         }
 
+        #[test]
+        fn test_normalize_with_keep_placeholders_option() {
+            sql_insight_cmd()
+                .arg("normalize")
+                .arg("--keep-placeholders")
+                .arg("select * from t1 where a = $1 and b = 2 and c = :name;")
+                .assert()
+                .success()
+                .stdout("SELECT * FROM t1 WHERE a = $1 AND b = ? AND c = :name\n")
+                .stderr("");
+        }
+
+        #[test]
+        fn test_normalize_with_jobs() {
+            sql_insight_cmd()
+                .arg("normalize")
+                .arg("--jobs")
+                .arg("2")
+                .arg("--unify-values")
+                .arg("select * from t1 where a = 1; insert into t2 (a) values (4), (5); delete from t3 where b = 'x';")
+                .assert()
+                .success()
+                .stdout(
+                    "SELECT * FROM t1 WHERE a = ?\nINSERT INTO t2 (a) VALUES (...)\nDELETE FROM t3 WHERE b = ?\n",
+                )
+                .stderr("");
+        }
+
         #[test]
         fn test_normalize_with_dialect() {
             sql_insight_cmd()
@@ -163,6 +306,38 @@ mod integration {
                 .stderr("");
         }
 
+        #[test]
+        fn test_extract_crud_tables_with_summary() {
+            sql_insight_cmd()
+                .arg("extract-crud")
+                .arg("--summary")
+                .arg("create table t1 (a int); insert into t1 (a) select b from t2 as x; update t1 set a = 1; delete from t2; drop table t3;")
+                .assert()
+                .success()
+                .stdout(
+                    "t1: Create: 1, Read: 0, Update: 1, Delete: 0, DDL: 1\n\
+                     t2: Create: 0, Read: 1, Update: 0, Delete: 1, DDL: 0\n\
+                     t3: Create: 0, Read: 0, Update: 0, Delete: 0, DDL: 1\n",
+                )
+                .stderr("");
+        }
+
+        #[test]
+        fn test_extract_crud_tables_with_summary_and_json_output_of_parser_error() {
+            let output = sql_insight_cmd()
+                .arg("extract-crud")
+                .arg("--summary")
+                .arg("--output")
+                .arg("json")
+                .arg("SELEC a FROM t1;")
+                .output()
+                .unwrap();
+            assert!(output.status.success());
+            let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+            assert_eq!(report["statements"], serde_json::json!([]));
+            assert_eq!(report["error"]["kind"], "parser");
+        }
+
         #[test]
         fn test_extract_crud_tables_with_dialect() {
             sql_insight_cmd()
@@ -207,6 +382,19 @@ mod integration {
                 .stderr("");
         }
 
+        #[test]
+        fn test_extract_tables_with_jobs() {
+            sql_insight_cmd()
+                .arg("extract-tables")
+                .arg("--jobs")
+                .arg("0")
+                .arg("select * from t1 inner join t2 using(id); select * from catalog.schema.table.extra; insert into t3 (a) select b from t4;")
+                .assert()
+                .success()
+                .stdout("t1, t2\nError: Too many identifiers provided\nt3, t4\n")
+                .stderr("");
+        }
+
         #[test]
         fn test_extract_tables_with_full_identifiers_and_alis() {
             sql_insight_cmd()
@@ -232,6 +420,59 @@ mod integration {
                 .stderr("");
         }
 
+        #[test]
+        fn test_extract_tables_with_json_output_of_parser_error() {
+            let output = sql_insight_cmd()
+                .arg("extract-tables")
+                .arg("--output")
+                .arg("json")
+                .arg("SELEC a FROM t1;")
+                .output()
+                .unwrap();
+            assert!(output.status.success());
+            let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+            assert_eq!(report["statements"], serde_json::json!([]));
+            assert_eq!(report["error"]["kind"], "parser");
+        }
+
+        #[test]
+        fn test_extract_tables_with_json_output() {
+            let output = sql_insight_cmd()
+                .arg("extract-tables")
+                .arg("--output")
+                .arg("json")
+                .arg("select * from t1 as t1_alias; select * from catalog.schema.t2.extra;")
+                .output()
+                .unwrap();
+            assert!(output.status.success());
+            let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+            assert_eq!(
+                report,
+                serde_json::json!({
+                    "version": 1,
+                    "statements": [
+                        {
+                            "index": 0,
+                            "result": [{
+                                "catalog": null,
+                                "schema": null,
+                                "name": { "value": "t1", "quote_style": null },
+                                "alias": { "value": "t1_alias", "quote_style": null }
+                            }],
+                            "error": null
+                        },
+                        {
+                            "index": 1,
+                            "result": null,
+                            "error": { "kind": "analysis", "message": "Too many identifiers provided" }
+                        }
+                    ],
+                    "summary": { "statements": 2, "succeeded": 1, "failed": 1 },
+                    "error": null
+                })
+            );
+        }
+
         #[test]
         fn test_extract_tables_from_file() {
             let mut temp_file = NamedTempFile::new().unwrap();
@@ -249,6 +490,311 @@ mod integration {
         }
     }
 
+    mod analyze {
+        use super::*;
+
+        #[test]
+        fn test_analyze() {
+            sql_insight_cmd()
+                .arg("analyze")
+                .arg("SELECT * FROM t1 JOIN t2 ON t1.id = t2.id; SELECT a FROM t1 WHERE b IN (SELECT b FROM t2 WHERE c > 1);")
+                .assert()
+                .success()
+                .stdout(
+                    "Joins: 1, Tables: 2, Subqueries: 0, Subquery Depth: 0, Predicates: 1, Set Operations: 0, Select Star: true\n\
+                     Joins: 0, Tables: 2, Subqueries: 1, Subquery Depth: 1, Predicates: 2, Set Operations: 0, Select Star: false\n",
+                )
+                .stderr("");
+        }
+    }
+
+    mod inspect {
+        use super::*;
+
+        #[test]
+        fn test_inspect() {
+            sql_insight_cmd()
+                .arg("inspect")
+                .arg("select a from t1 where b = 1; select c from catalog.schema.t2.extra;")
+                .assert()
+                .success()
+                .stdout(
+                    "Formatted: SELECT a FROM t1 WHERE b = 1\n\
+                     Normalized: SELECT a FROM t1 WHERE b = ?\n\
+                     Tables: t1\n\
+                     CRUD: Create: [], Read: [t1], Update: [], Delete: []\n\
+                     Error: Too many identifiers provided\n",
+                )
+                .stderr("");
+        }
+
+        #[test]
+        fn test_inspect_with_json_output() {
+            let output = sql_insight_cmd()
+                .arg("inspect")
+                .arg("--output")
+                .arg("json")
+                .arg("delete from t1 where a = 1")
+                .output()
+                .unwrap();
+            assert!(output.status.success());
+            let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+            let result = &report["statements"][0]["result"];
+            assert_eq!(result["formatted"], "DELETE FROM t1 WHERE a = 1");
+            assert_eq!(result["normalized"], "DELETE FROM t1 WHERE a = ?");
+            assert_eq!(result["tables"][0]["name"]["value"], "t1");
+            assert_eq!(
+                result["crud_tables"]["delete_tables"][0]["name"]["value"],
+                "t1"
+            );
+        }
+    }
+
+    mod lint {
+        use super::*;
+
+        #[test]
+        fn test_lint() {
+            sql_insight_cmd()
+                .arg("lint")
+                .arg("DELETE FROM t1; -- sql-insight: allow(no-where-clause)\nDELETE FROM t2")
+                .assert()
+                .failure()
+                .stdout("2:1: error[no-where-clause]: DELETE without WHERE clause affects all rows of t2\n")
+                .stderr("");
+        }
+
+        #[test]
+        fn test_lint_with_json_output_of_parser_error() {
+            let output = sql_insight_cmd()
+                .arg("lint")
+                .arg("--output")
+                .arg("json")
+                .arg("SELEC a FROM t1;")
+                .output()
+                .unwrap();
+            assert!(output.status.success());
+            let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+            assert_eq!(report["statements"], serde_json::json!([]));
+            assert_eq!(report["error"]["kind"], "parser");
+        }
+
+        #[test]
+        fn test_lint_with_sarif_output() {
+            let mut temp_file = NamedTempFile::new().unwrap();
+            temp_file
+                .write_all(b"SELECT a FROM t1;\nDELETE FROM t2")
+                .unwrap();
+            let output = sql_insight_cmd()
+                .arg("lint")
+                .arg("--output")
+                .arg("sarif")
+                .arg("--file")
+                .arg(temp_file.path())
+                .output()
+                .unwrap();
+            assert!(!output.status.success());
+            let log: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+            assert_eq!(log["version"], "2.1.0");
+            assert_eq!(
+                log["runs"][0]["results"],
+                serde_json::json!([{
+                    "ruleId": "no-where-clause",
+                    "level": "error",
+                    "message": { "text": "DELETE without WHERE clause affects all rows of t2" },
+                    "locations": [{
+                        "physicalLocation": {
+                            "artifactLocation": { "uri": temp_file.path().to_str().unwrap() },
+                            "region": { "startLine": 2, "startColumn": 1, "endLine": 2, "endColumn": 15 }
+                        }
+                    }]
+                }])
+            );
+        }
+
+        #[test]
+        fn test_lint_with_severity_and_disable() {
+            sql_insight_cmd()
+                .arg("lint")
+                .arg("--severity")
+                .arg("select-star=error")
+                .arg("--disable")
+                .arg("no-where-clause")
+                .arg("SELECT * FROM t1; DELETE FROM t2")
+                .assert()
+                .failure()
+                .stdout("1:1: error[select-star]: Wildcard projection * is used\n")
+                .stderr("");
+            sql_insight_cmd()
+                .arg("lint")
+                .arg("--severity")
+                .arg("no-where-clause=warning")
+                .arg("SELECT a FROM t1 LIMIT 1; DELETE FROM t2")
+                .assert()
+                .success()
+                .stdout("1:27: warning[no-where-clause]: DELETE without WHERE clause affects all rows of t2\n")
+                .stderr("");
+        }
+
+        #[test]
+        fn test_lint_with_unknown_rule() {
+            for args in [
+                ["--disable", "no-where"],
+                ["--severity", "select-starr=error"],
+            ] {
+                sql_insight_cmd()
+                    .arg("lint")
+                    .args(args)
+                    .arg("SELECT a FROM t1")
+                    .assert()
+                    .failure()
+                    .stderr(predicate::str::contains("Rule not found: "));
+            }
+        }
+
+        #[test]
+        fn test_lint_with_invalid_severity() {
+            sql_insight_cmd()
+                .arg("lint")
+                .arg("--severity")
+                .arg("select-star=fatal")
+                .arg("SELECT * FROM t1")
+                .assert()
+                .failure()
+                .stderr(predicate::str::contains(
+                    "Severity not found: fatal. Available severities: info, warning, error",
+                ));
+        }
+
+        #[test]
+        fn test_lint_with_fix() {
+            sql_insight_cmd()
+                .arg("lint")
+                .arg("--fix")
+                .arg("SELECT DISTINCT a FROM t1, t2 GROUP BY a; DELETE FROM t3")
+                .assert()
+                .success()
+                .stdout("SELECT a FROM t1 CROSS JOIN t2 GROUP BY a\nDELETE FROM t3\n")
+                .stderr("");
+        }
+    }
+
+    mod stats {
+        use super::*;
+
+        #[test]
+        fn test_stats() {
+            sql_insight_cmd()
+                .arg("stats")
+                .arg("SELECT a FROM t1 WHERE b = 1; DELETE FROM t2; SELECT a FROM t1 WHERE b = 2")
+                .assert()
+                .success()
+                .stdout(
+                    predicate::str::is_match(
+                        r"^2\t[0-9a-f]{16}\tSELECT a FROM t1 WHERE b = \?\n1\t[0-9a-f]{16}\tDELETE FROM t2\n$",
+                    )
+                    .unwrap(),
+                )
+                .stderr("");
+        }
+
+        #[test]
+        fn test_stats_with_json_output_of_parser_error() {
+            let output = sql_insight_cmd()
+                .arg("stats")
+                .arg("--output")
+                .arg("json")
+                .arg("SELEC a FROM t1;")
+                .output()
+                .unwrap();
+            assert!(output.status.success());
+            let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+            assert_eq!(report["statements"], serde_json::json!([]));
+            assert_eq!(report["error"]["kind"], "parser");
+        }
+
+        #[test]
+        fn test_stats_with_sqlite() {
+            let dir = tempfile::tempdir().unwrap();
+            let db = dir.path().join("digests.db");
+            for _ in 0..2 {
+                sql_insight_cmd()
+                    .arg("stats")
+                    .arg("--sqlite")
+                    .arg(&db)
+                    .arg("SELECT a FROM t1 WHERE b = 1")
+                    .assert()
+                    .success();
+            }
+            sql_insight_cmd()
+                .arg("stats")
+                .arg("--sqlite")
+                .arg(&db)
+                .arg("DELETE FROM t2")
+                .assert()
+                .success()
+                .stdout(
+                    predicate::str::is_match(
+                        r"^2\t[0-9a-f]{16}\tSELECT a FROM t1 WHERE b = \?\n1\t[0-9a-f]{16}\tDELETE FROM t2\n$",
+                    )
+                    .unwrap(),
+                )
+                .stderr("");
+        }
+    }
+
+    mod stdin {
+        use super::*;
+
+        #[test]
+        fn test_piped_stdin() {
+            sql_insight_cmd()
+                .arg("normalize")
+                .write_stdin("select * from t1\nwhere a = 1;\nselect b from t2;\n")
+                .assert()
+                .success()
+                .stdout("SELECT * FROM t1 WHERE a = ?\nSELECT b FROM t2\n")
+                .stderr("");
+        }
+
+        #[test]
+        fn test_file_dash() {
+            sql_insight_cmd()
+                .arg("extract-tables")
+                .arg("--file")
+                .arg("-")
+                .write_stdin("\u{feff}SELECT a FROM t1 JOIN t2 ON t1.id = t2.id")
+                .assert()
+                .success()
+                .stdout("t1, t2\n")
+                .stderr("");
+        }
+
+        #[test]
+        fn test_interactive_exits_at_end_of_input() {
+            sql_insight_cmd()
+                .arg("extract-tables")
+                .arg("--interactive")
+                .write_stdin("SELECT a FROM t1;\n")
+                .timeout(std::time::Duration::from_secs(5))
+                .assert()
+                .success()
+                .stdout(predicate::str::contains("sql> t1\n"))
+                .stderr("");
+        }
+
+        #[test]
+        fn test_interactive_conflicts_with_sql() {
+            sql_insight_cmd()
+                .arg("format")
+                .arg("--interactive")
+                .arg("SELECT 1")
+                .assert()
+                .failure()
+                .stderr(predicate::str::contains("cannot be used with"));
+        }
+    }
+
     mod interactive_mode {
         use super::*;
         use std::time::Duration;
@@ -288,6 +834,7 @@ mod integration {
         async fn test_interactive() -> Result<(), Box<dyn std::error::Error>> {
             let mut child = Command::new(BIN_PATH)
                 .arg("format")
+                .arg("--interactive")
                 .stdin(process::Stdio::piped())
                 .stdout(process::Stdio::piped())
                 .stderr(process::Stdio::piped())
@@ -338,6 +885,80 @@ mod integration {
 
             Ok(())
         }
+
+        #[tokio::test]
+        async fn test_interactive_commands() -> Result<(), Box<dyn std::error::Error>> {
+            let mut child = Command::new(BIN_PATH)
+                .arg("extract-tables")
+                .arg("--interactive")
+                .stdin(process::Stdio::piped())
+                .stdout(process::Stdio::piped())
+                .stderr(process::Stdio::piped())
+                .spawn()
+                .expect("Failed to spawn child process");
+
+            let stdin = child.stdin.as_mut().expect("Failed to open stdin");
+            let stdout = child.stdout.take().expect("Failed to open stdout");
+            let stderr = child.stderr.take().expect("Failed to open stderr");
+            let mut stdout_reader = BufReader::new(stdout).lines();
+            let mut stderr_reader = BufReader::new(stderr).lines();
+            read_from_stdout(&mut stdout_reader).await?;
+
+            // Switch the output format
+            write_to_stdin(stdin, "\\format table\n").await?;
+            assert_eq!(
+                read_from_stdout(&mut stdout_reader).await?,
+                "sql> Output format: table"
+            );
+            write_to_stdin(stdin, "SELECT a FROM t1 JOIN t2 ON t1.id = t2.id;\n").await?;
+            let mut table = vec![];
+            for _ in 0..3 {
+                table.push(read_from_stdout(&mut stdout_reader).await?);
+            }
+            assert_eq!(
+                table,
+                ["sql> # | result", "--+-------", "1 | t1, t2"],
+                "Table not as expected: {table:?}"
+            );
+
+            // Save the last result
+            let dir = tempfile::tempdir()?;
+            let path = dir.path().join("result.txt");
+            write_to_stdin(stdin, &format!("\\save {}\n", path.display())).await?;
+            assert_eq!(
+                read_from_stdout(&mut stdout_reader).await?,
+                format!("sql> Saved 3 lines to {}", path.display())
+            );
+            assert_eq!(
+                std::fs::read_to_string(&path)?,
+                "# | result\n--+-------\n1 | t1, t2\n"
+            );
+
+            // Invalid commands are reported without leaving interactive mode
+            write_to_stdin(stdin, "\\format xml\n").await?;
+            let error = read_from_stderr(&mut stderr_reader).await?;
+            assert!(
+                error.contains("Error: Unknown output format: xml"),
+                "Error not as expected: {error:?}"
+            );
+            write_to_stdin(stdin, "\\format sarif\n").await?;
+            let error = read_from_stderr(&mut stderr_reader).await?;
+            assert!(
+                error.contains("Error: Unknown output format: sarif"),
+                "Error not as expected: {error:?}"
+            );
+
+            write_to_stdin(stdin, "quit\n").await?;
+            let exit_message = read_from_stdout(&mut stdout_reader).await?;
+            assert!(
+                exit_message.contains("Bye"),
+                "Exit message not as expected: {exit_message:?}"
+            );
+
+            child.wait().await?;
+
+            Ok(())
+        }
     }
 
     mod invalid_cases {
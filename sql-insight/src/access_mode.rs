@@ -0,0 +1,237 @@
+//! A classifier of statements by whether they read, write or change the schema.
+//!
+//! See [`access_mode`](crate::access_mode()) as the entry point for classifying SQL.
+
+use crate::error::Error;
+use crate::instrument;
+use sqlparser::ast::{Query, SetExpr, Statement};
+use sqlparser::dialect::Dialect;
+
+/// Convenience function to classify each statement of SQL by its [`AccessMode`].
+///
+/// Classification only looks at the kind of statements and the top-level structure of queries,
+/// without extracting tables, so it is cheap enough to run for every statement passing a proxy.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+/// use sql_insight::AccessMode;
+///
+/// let dialect = GenericDialect {};
+/// let sql = "BEGIN; SELECT a FROM t1; UPDATE t1 SET a = 1; DROP TABLE t2; COMMIT";
+/// let result = sql_insight::access_mode(&dialect, sql).unwrap();
+/// assert_eq!(result, [
+///     AccessMode::TransactionControl,
+///     AccessMode::ReadOnly,
+///     AccessMode::Write,
+///     AccessMode::Ddl,
+///     AccessMode::TransactionControl,
+/// ]);
+/// ```
+pub fn access_mode(dialect: &dyn Dialect, sql: &str) -> Result<Vec<AccessMode>, Error> {
+    let statements = instrument::parse_sql(dialect, sql)?;
+    Ok(statements.iter().map(AccessMode::of).collect())
+}
+
+/// [`AccessMode`] represents what a statement does to the database, for routing statements
+/// to replicas or rejecting them on read-only connections.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum AccessMode {
+    /// Reads data without modifying it, e.g. `SELECT`, `SHOW` and `EXPLAIN` without `ANALYZE`.
+    ReadOnly,
+    /// Modifies data, e.g. `INSERT`, `UPDATE`, `DELETE`, `MERGE`, `SELECT ... INTO`, and queries locking rows
+    /// with `FOR UPDATE` or `FOR SHARE`, which must run on the primary.
+    Write,
+    /// Changes the schema or privileges, e.g. `CREATE`, `ALTER`, `DROP`, `TRUNCATE`, `GRANT` and `REVOKE`.
+    Ddl,
+    /// Controls transactions, e.g. `BEGIN`, `COMMIT`, `ROLLBACK` and `SAVEPOINT`.
+    TransactionControl,
+    /// Not classified, e.g. `CALL`, `SET` and `EXECUTE`, whose effects depend on what they run.
+    /// Treat it as a write to be safe.
+    Unknown,
+}
+
+impl AccessMode {
+    /// Classify a statement.
+    pub fn of(statement: &Statement) -> Self {
+        match statement {
+            Statement::Query(query) => Self::of_query(query),
+            Statement::Explain {
+                analyze: true,
+                statement,
+                ..
+            } => Self::of(statement),
+            Statement::Explain { .. }
+            | Statement::ExplainTable { .. }
+            | Statement::ShowTables { .. }
+            | Statement::ShowColumns { .. }
+            | Statement::ShowCreate { .. }
+            | Statement::ShowFunctions { .. }
+            | Statement::ShowVariable { .. }
+            | Statement::ShowVariables { .. }
+            | Statement::ShowCollation { .. } => Self::ReadOnly,
+            Statement::Copy { to: true, .. } => Self::ReadOnly,
+            Statement::Insert { .. }
+            | Statement::Update { .. }
+            | Statement::Delete { .. }
+            | Statement::Merge { .. }
+            | Statement::Copy { .. } => Self::Write,
+            Statement::CreateTable { .. }
+            | Statement::CreateVirtualTable { .. }
+            | Statement::CreateView { .. }
+            | Statement::CreateIndex { .. }
+            | Statement::CreateSchema { .. }
+            | Statement::CreateDatabase { .. }
+            | Statement::CreateFunction { .. }
+            | Statement::CreateProcedure { .. }
+            | Statement::CreateSequence { .. }
+            | Statement::CreateType { .. }
+            | Statement::CreateRole { .. }
+            | Statement::AlterTable { .. }
+            | Statement::AlterIndex { .. }
+            | Statement::AlterView { .. }
+            | Statement::AlterRole { .. }
+            | Statement::Drop { .. }
+            | Statement::DropFunction { .. }
+            | Statement::Truncate { .. }
+            | Statement::Comment { .. }
+            | Statement::Grant { .. }
+            | Statement::Revoke { .. } => Self::Ddl,
+            Statement::StartTransaction { .. }
+            | Statement::SetTransaction { .. }
+            | Statement::Commit { .. }
+            | Statement::Rollback { .. }
+            | Statement::Savepoint { .. }
+            | Statement::ReleaseSavepoint { .. } => Self::TransactionControl,
+            _ => Self::Unknown,
+        }
+    }
+
+    fn of_query(query: &Query) -> Self {
+        let ctes = query.with.iter().flat_map(|with| &with.cte_tables);
+        if !query.locks.is_empty()
+            || ctes
+                .map(|cte| Self::of_query(&cte.query))
+                .any(|mode| mode != Self::ReadOnly)
+        {
+            return Self::Write;
+        }
+        Self::of_set_expr(&query.body)
+    }
+
+    fn of_set_expr(set_expr: &SetExpr) -> Self {
+        match set_expr {
+            SetExpr::Select(select) if select.into.is_some() => Self::Write,
+            SetExpr::Query(query) => Self::of_query(query),
+            SetExpr::SetOperation { left, right, .. } => {
+                match (Self::of_set_expr(left), Self::of_set_expr(right)) {
+                    (Self::ReadOnly, Self::ReadOnly) => Self::ReadOnly,
+                    _ => Self::Write,
+                }
+            }
+            SetExpr::Insert(_) | SetExpr::Update(_) => Self::Write,
+            SetExpr::Select(_) | SetExpr::Values(_) | SetExpr::Table(_) => Self::ReadOnly,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+    use sqlparser::dialect::{GenericDialect, MySqlDialect, PostgreSqlDialect};
+
+    fn assert_access_mode(sql: &str, expected: Vec<AccessMode>, dialects: Vec<Box<dyn Dialect>>) {
+        for dialect in dialects {
+            let result = access_mode(dialect.as_ref(), sql).unwrap();
+            assert_eq!(result, expected, "Failed for dialect: {dialect:?}");
+        }
+    }
+
+    #[test]
+    fn test_read_and_write() {
+        let sql = "SELECT a FROM t1 UNION SELECT b FROM t2; \
+            WITH c AS (SELECT a FROM t1) SELECT * FROM c; \
+            INSERT INTO t1 (a) VALUES (1); \
+            UPDATE t1 SET a = 1; \
+            DELETE FROM t1";
+        assert_access_mode(
+            sql,
+            vec![
+                AccessMode::ReadOnly,
+                AccessMode::ReadOnly,
+                AccessMode::Write,
+                AccessMode::Write,
+                AccessMode::Write,
+            ],
+            all_dialects(),
+        );
+    }
+
+    #[test]
+    fn test_ddl() {
+        let sql = "CREATE TABLE t1 (a INT); ALTER TABLE t1 ADD COLUMN b INT; CREATE VIEW v1 AS SELECT a FROM t1; DROP TABLE t1";
+        assert_access_mode(
+            sql,
+            vec![
+                AccessMode::Ddl,
+                AccessMode::Ddl,
+                AccessMode::Ddl,
+                AccessMode::Ddl,
+            ],
+            all_dialects(),
+        );
+    }
+
+    #[test]
+    fn test_locks_and_select_into() {
+        assert_access_mode(
+            "SELECT a FROM t1 FOR UPDATE; SELECT a INTO t2 FROM t1",
+            vec![AccessMode::Write, AccessMode::Write],
+            vec![Box::new(PostgreSqlDialect {}), Box::new(GenericDialect {})],
+        );
+    }
+
+    #[test]
+    fn test_transaction_control_and_utility() {
+        let sql =
+            "START TRANSACTION; SAVEPOINT s1; ROLLBACK; SHOW TABLES; EXPLAIN DELETE FROM t1; \
+            SET autocommit = 1; TRUNCATE TABLE t1; COMMIT";
+        assert_access_mode(
+            sql,
+            vec![
+                AccessMode::TransactionControl,
+                AccessMode::TransactionControl,
+                AccessMode::TransactionControl,
+                AccessMode::ReadOnly,
+                AccessMode::ReadOnly,
+                AccessMode::Unknown,
+                AccessMode::Ddl,
+                AccessMode::TransactionControl,
+            ],
+            vec![Box::new(MySqlDialect {}), Box::new(GenericDialect {})],
+        );
+    }
+
+    #[test]
+    fn test_data_modifying_ctes() {
+        assert_access_mode(
+            "WITH c AS (SELECT 1) UPDATE t SET a = 1; \
+            WITH d AS (UPDATE t SET a = 1 RETURNING *) SELECT * FROM d",
+            vec![AccessMode::Write, AccessMode::Write],
+            vec![Box::new(PostgreSqlDialect {}), Box::new(GenericDialect {})],
+        );
+    }
+
+    #[test]
+    fn test_explain_analyze() {
+        assert_access_mode(
+            "EXPLAIN ANALYZE DELETE FROM t1; EXPLAIN ANALYZE SELECT a FROM t1",
+            vec![AccessMode::Write, AccessMode::ReadOnly],
+            vec![Box::new(PostgreSqlDialect {}), Box::new(GenericDialect {})],
+        );
+    }
+}
@@ -0,0 +1,835 @@
+//! Collection of columns of a statement, with the clauses they appear in and the values they are compared with.
+
+use std::ops::ControlFlow;
+
+use crate::extractor::table_extractor::TableReference;
+use crate::helper;
+use crate::prepared::ParamType;
+use sqlparser::ast::{
+    BinaryOperator, Expr, GroupByExpr, Ident, JoinConstraint, Query, Select, SelectItem, SetExpr,
+    Statement, TableFactor, TableWithJoins, UnaryOperator, Visit, Visitor,
+};
+
+/// Clause a column reference appears in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum ColumnContext {
+    Projection,
+    Filter,
+    JoinKey,
+    GroupBy,
+    OrderBy,
+}
+
+/// A column reference, with the table it belongs to when it can be determined.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct ColumnReference {
+    pub(crate) table: Option<TableReference>,
+    pub(crate) column: Ident,
+    pub(crate) context: ColumnContext,
+}
+
+/// A column compared with, assigned or inserted a value, with the type of the value if it is a literal.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct ValueReference {
+    pub(crate) table: Option<TableReference>,
+    pub(crate) column: Ident,
+    pub(crate) value_type: Option<ParamType>,
+}
+
+/// Columns of a statement, including those of its subqueries.
+#[derive(Default, Debug)]
+pub(crate) struct StatementColumns {
+    /// Column references, classified by the clause they appear in.
+    pub(crate) references: Vec<ColumnReference>,
+    /// Equalities between columns used as join keys.
+    pub(crate) join_keys: Vec<(ColumnReference, ColumnReference)>,
+    /// Columns compared with values in conditions, assigned values by UPDATE, or inserted values by INSERT.
+    pub(crate) values: Vec<ValueReference>,
+    /// Columns written by INSERT with a column list, or assigned by UPDATE.
+    pub(crate) writes: Vec<(Option<TableReference>, Ident)>,
+    /// Tables whose all columns are selected by `*` or `table.*`.
+    pub(crate) wildcard_tables: Vec<Option<TableReference>>,
+    /// Tables whose all columns may be written by INSERT without a column list.
+    pub(crate) wildcard_writes: Vec<Option<TableReference>>,
+    /// Tables in FROM clauses, and tables UPDATE and INSERT write to, excluding CTEs.
+    pub(crate) tables: Vec<TableReference>,
+}
+
+/// Collect columns of a statement.
+///
+/// Qualified columns are resolved through the aliases and table names of the enclosing FROM clauses,
+/// and unqualified columns are resolved only when their SELECT reads a single table.
+/// Columns of derived tables and CTEs are not resolved to tables.
+pub(crate) fn collect_columns(statement: &Statement) -> StatementColumns {
+    let mut visitor = ColumnReferenceVisitor::default();
+    let _ = statement.visit(&mut visitor);
+    visitor.columns
+}
+
+/// A table in scope, referred to by its alias, or by its name when not aliased.
+#[derive(Clone, Debug)]
+struct ScopedTable {
+    qualifier: String,
+    table: Option<TableReference>,
+}
+
+#[derive(Default)]
+struct ColumnReferenceVisitor {
+    /// Tables in scope of enclosing queries and statements, for resolving correlated columns.
+    scopes: Vec<Vec<ScopedTable>>,
+    cte_scopes: Vec<Vec<String>>,
+    columns: StatementColumns,
+}
+
+impl Visitor for ColumnReferenceVisitor {
+    type Break = ();
+
+    fn pre_visit_statement(&mut self, statement: &Statement) -> ControlFlow<Self::Break> {
+        match statement {
+            Statement::Insert {
+                table_name,
+                columns,
+                source,
+                ..
+            } => {
+                let table = TableReference::try_from(table_name).ok();
+                self.record_tables(table.iter());
+                if columns.is_empty() {
+                    self.columns.wildcard_writes.push(table.clone());
+                }
+                let rows = match source.as_ref().map(|source| &*source.body) {
+                    Some(SetExpr::Values(values)) => values.rows.iter().collect(),
+                    _ => vec![],
+                };
+                for (i, column) in columns.iter().enumerate() {
+                    self.columns.writes.push((table.clone(), column.clone()));
+                    let mut value_types = rows
+                        .iter()
+                        .map(|row| row.get(i).and_then(literal_type))
+                        .collect::<Vec<_>>();
+                    if value_types.is_empty() {
+                        value_types.push(None);
+                    }
+                    for value_type in value_types {
+                        self.columns.values.push(ValueReference {
+                            table: table.clone(),
+                            column: column.clone(),
+                            value_type,
+                        });
+                    }
+                }
+            }
+            Statement::Update {
+                table,
+                assignments,
+                from,
+                selection,
+                ..
+            } => {
+                let mut scope = vec![];
+                self.collect_scope(table, &mut scope);
+                if let Some(from) = from {
+                    self.collect_scope(from, &mut scope);
+                }
+                self.record_tables(scope.iter().filter_map(|t| t.table.as_ref()));
+                for assignment in assignments {
+                    if let [.., column] = assignment.id.as_slice() {
+                        let qualifier = match assignment.id.as_slice() {
+                            [.., qualifier, _] => Some(qualifier),
+                            _ => None,
+                        };
+                        let table = self.resolve(&scope, qualifier);
+                        self.columns.writes.push((table.clone(), column.clone()));
+                        self.columns.values.push(ValueReference {
+                            table,
+                            column: column.clone(),
+                            value_type: literal_type(&assignment.value),
+                        });
+                    }
+                }
+                for table_with_joins in std::iter::once(table).chain(from) {
+                    self.record_join_conditions(table_with_joins, &scope);
+                }
+                if let Some(selection) = selection {
+                    self.record_condition(selection, &scope, false);
+                }
+                self.scopes.push(scope);
+            }
+            Statement::Delete {
+                from,
+                using,
+                selection,
+                ..
+            } => {
+                let mut scope = vec![];
+                let tables = from.iter().chain(using.iter().flatten());
+                for table_with_joins in tables.clone() {
+                    self.collect_scope(table_with_joins, &mut scope);
+                }
+                self.record_tables(scope.iter().filter_map(|t| t.table.as_ref()));
+                for table_with_joins in tables {
+                    self.record_join_conditions(table_with_joins, &scope);
+                }
+                if let Some(selection) = selection {
+                    self.record_condition(selection, &scope, false);
+                }
+                self.scopes.push(scope);
+            }
+            _ => {}
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn post_visit_statement(&mut self, statement: &Statement) -> ControlFlow<Self::Break> {
+        if matches!(
+            statement,
+            Statement::Update { .. } | Statement::Delete { .. }
+        ) {
+            self.scopes.pop();
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_query(&mut self, query: &Query) -> ControlFlow<Self::Break> {
+        self.cte_scopes.push(
+            query
+                .with
+                .iter()
+                .flat_map(|with| &with.cte_tables)
+                .map(|cte| cte.alias.name.value.clone())
+                .collect(),
+        );
+        let mut selects = vec![];
+        helper::collect_selects(&query.body, &mut selects);
+        let mut query_scope = vec![];
+        for select in &selects {
+            let mut scope = vec![];
+            for table_with_joins in &select.from {
+                self.collect_scope(table_with_joins, &mut scope);
+            }
+            self.record_tables(scope.iter().filter_map(|t| t.table.as_ref()));
+            self.record_select(select, &scope);
+            query_scope.extend(scope);
+        }
+        let (order_by_scope, aliases) = match &*query.body {
+            SetExpr::Select(select) => (query_scope.clone(), projection_aliases(select)),
+            _ => (vec![], vec![]),
+        };
+        for order_by in &query.order_by {
+            for (qualifier, column) in columns(&order_by.expr) {
+                if qualifier.is_none() && aliases.contains(&column.value) {
+                    continue;
+                }
+                self.record(&order_by_scope, qualifier, column, ColumnContext::OrderBy);
+            }
+        }
+        self.scopes.push(query_scope);
+        ControlFlow::Continue(())
+    }
+
+    fn post_visit_query(&mut self, _query: &Query) -> ControlFlow<Self::Break> {
+        self.cte_scopes.pop();
+        self.scopes.pop();
+        ControlFlow::Continue(())
+    }
+}
+
+impl ColumnReferenceVisitor {
+    fn collect_scope(&self, table_with_joins: &TableWithJoins, scope: &mut Vec<ScopedTable>) {
+        let factors = std::iter::once(&table_with_joins.relation)
+            .chain(table_with_joins.joins.iter().map(|join| &join.relation));
+        for factor in factors {
+            match factor {
+                TableFactor::Table { name, alias, .. } => {
+                    let is_cte = name.0.len() == 1
+                        && self
+                            .cte_scopes
+                            .iter()
+                            .flatten()
+                            .any(|cte| *cte == name.0[0].value);
+                    let table = if is_cte {
+                        None
+                    } else {
+                        TableReference::try_from(name).ok()
+                    };
+                    let qualifier = match alias {
+                        Some(alias) => alias.name.value.clone(),
+                        None => name
+                            .0
+                            .last()
+                            .map(|ident| ident.value.clone())
+                            .unwrap_or_default(),
+                    };
+                    scope.push(ScopedTable { qualifier, table });
+                }
+                TableFactor::Derived {
+                    alias: Some(alias), ..
+                } => scope.push(ScopedTable {
+                    qualifier: alias.name.value.clone(),
+                    table: None,
+                }),
+                TableFactor::NestedJoin {
+                    table_with_joins, ..
+                } => self.collect_scope(table_with_joins, scope),
+                _ => {}
+            }
+        }
+    }
+
+    fn record_select(&mut self, select: &Select, scope: &[ScopedTable]) {
+        for item in &select.projection {
+            match item {
+                SelectItem::UnnamedExpr(expr) | SelectItem::ExprWithAlias { expr, .. } => {
+                    self.record_expr(expr, scope, ColumnContext::Projection)
+                }
+                SelectItem::Wildcard(_) => self
+                    .columns
+                    .wildcard_tables
+                    .extend(scope.iter().map(|t| t.table.clone())),
+                SelectItem::QualifiedWildcard(name, _) => {
+                    let table = self.resolve(scope, name.0.last());
+                    self.columns.wildcard_tables.push(table);
+                }
+            }
+        }
+        for table_with_joins in &select.from {
+            self.record_join_conditions(table_with_joins, scope);
+        }
+        if let Some(selection) = &select.selection {
+            self.record_condition(selection, scope, false);
+        }
+        if let Some(having) = &select.having {
+            self.record_expr(having, scope, ColumnContext::Filter);
+            self.record_values(having, scope);
+        }
+        if let GroupByExpr::Expressions(exprs) = &select.group_by {
+            for expr in exprs {
+                self.record_expr(expr, scope, ColumnContext::GroupBy);
+            }
+        }
+    }
+
+    /// Record columns of join constraints. Columns of `USING` are attributed to the joined table
+    /// and the first table of the FROM item.
+    fn record_join_conditions(&mut self, table_with_joins: &TableWithJoins, scope: &[ScopedTable]) {
+        let mut conditions = vec![];
+        helper::collect_join_conditions(table_with_joins, &mut conditions);
+        for condition in conditions {
+            self.record_condition(condition, scope, true);
+        }
+        for join in &table_with_joins.joins {
+            if let Some(JoinConstraint::Using(idents)) =
+                helper::join_constraint(&join.join_operator)
+            {
+                let mut joined = vec![];
+                self.collect_scope(
+                    &TableWithJoins {
+                        relation: join.relation.clone(),
+                        joins: vec![],
+                    },
+                    &mut joined,
+                );
+                self.collect_scope(
+                    &TableWithJoins {
+                        relation: table_with_joins.relation.clone(),
+                        joins: vec![],
+                    },
+                    &mut joined,
+                );
+                for ident in idents {
+                    let references = joined
+                        .iter()
+                        .map(|table| ColumnReference {
+                            table: table.table.clone(),
+                            column: ident.clone(),
+                            context: ColumnContext::JoinKey,
+                        })
+                        .collect::<Vec<_>>();
+                    if let [left, right] = references.as_slice() {
+                        self.columns.join_keys.push((left.clone(), right.clone()));
+                    }
+                    self.columns.references.extend(references);
+                }
+            }
+        }
+    }
+
+    /// Record columns of a condition. Equalities between columns are join keys when they are
+    /// join conditions, or when they compare columns of differently qualified tables.
+    fn record_condition(&mut self, condition: &Expr, scope: &[ScopedTable], is_join: bool) {
+        let mut conjuncts = vec![];
+        split_conjunction(condition, &mut conjuncts);
+        for conjunct in conjuncts {
+            let context = match join_key_columns(conjunct) {
+                Some((left, right)) if is_join || differently_qualified(left, right) => {
+                    ColumnContext::JoinKey
+                }
+                _ => ColumnContext::Filter,
+            };
+            self.record_values(conjunct, scope);
+            let start = self.columns.references.len();
+            self.record_expr(conjunct, scope, context);
+            if context == ColumnContext::JoinKey {
+                if let [left, right] = &self.columns.references[start..] {
+                    self.columns.join_keys.push((left.clone(), right.clone()));
+                }
+            }
+        }
+    }
+
+    fn record_expr(&mut self, expr: &Expr, scope: &[ScopedTable], context: ColumnContext) {
+        for (qualifier, column) in columns(expr) {
+            self.record(scope, qualifier, column, context);
+        }
+    }
+
+    /// Record columns compared with values.
+    fn record_values(&mut self, condition: &Expr, scope: &[ScopedTable]) {
+        let mut collector = ValueComparisonCollector::default();
+        let _ = condition.visit(&mut collector);
+        for (qualifier, column, value_type) in collector.comparisons {
+            let table = self.resolve(scope, qualifier.as_ref());
+            self.columns.values.push(ValueReference {
+                table,
+                column,
+                value_type,
+            });
+        }
+    }
+
+    fn record_tables<'a>(&mut self, tables: impl Iterator<Item = &'a TableReference>) {
+        for table in tables {
+            if !self.columns.tables.contains(table) {
+                self.columns.tables.push(table.clone());
+            }
+        }
+    }
+
+    fn record(
+        &mut self,
+        scope: &[ScopedTable],
+        qualifier: Option<Ident>,
+        column: Ident,
+        context: ColumnContext,
+    ) {
+        let table = self.resolve(scope, qualifier.as_ref());
+        self.columns.references.push(ColumnReference {
+            table,
+            column,
+            context,
+        });
+    }
+
+    fn resolve(&self, scope: &[ScopedTable], qualifier: Option<&Ident>) -> Option<TableReference> {
+        match qualifier {
+            None => match scope {
+                [table] => table.table.clone(),
+                _ => None,
+            },
+            Some(qualifier) => std::iter::once(scope)
+                .chain(self.scopes.iter().rev().map(Vec::as_slice))
+                .find_map(|scope| scope.iter().find(|t| t.qualifier == qualifier.value))
+                .and_then(|t| t.table.clone()),
+        }
+    }
+}
+
+fn projection_aliases(select: &Select) -> Vec<String> {
+    select
+        .projection
+        .iter()
+        .filter_map(|item| match item {
+            SelectItem::ExprWithAlias { alias, .. } => Some(alias.value.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn split_conjunction<'a>(expr: &'a Expr, conjuncts: &mut Vec<&'a Expr>) {
+    match expr {
+        Expr::BinaryOp {
+            left,
+            op: BinaryOperator::And,
+            right,
+        } => {
+            split_conjunction(left, conjuncts);
+            split_conjunction(right, conjuncts);
+        }
+        Expr::Nested(inner)
+            if matches!(
+                **inner,
+                Expr::BinaryOp {
+                    op: BinaryOperator::And,
+                    ..
+                }
+            ) =>
+        {
+            split_conjunction(inner, conjuncts)
+        }
+        expr => conjuncts.push(expr),
+    }
+}
+
+/// Both sides of an equality between columns.
+fn join_key_columns(expr: &Expr) -> Option<(&Expr, &Expr)> {
+    match expr {
+        Expr::BinaryOp {
+            left,
+            op: BinaryOperator::Eq,
+            right,
+        } if is_column(left) && is_column(right) => Some((left, right)),
+        _ => None,
+    }
+}
+
+fn is_column(expr: &Expr) -> bool {
+    matches!(expr, Expr::Identifier(_) | Expr::CompoundIdentifier(_))
+}
+
+fn differently_qualified(left: &Expr, right: &Expr) -> bool {
+    match (columns(left).pop(), columns(right).pop()) {
+        (Some((Some(left), _)), Some((Some(right), _))) => left.value != right.value,
+        _ => false,
+    }
+}
+
+/// Columns of an expression with their qualifiers, excluding those of subqueries.
+fn columns(expr: &Expr) -> Vec<(Option<Ident>, Ident)> {
+    let mut collector = ColumnCollector::default();
+    let _ = expr.visit(&mut collector);
+    collector.columns
+}
+
+/// Type of a literal value, possibly signed, or `None` if the expression is not a literal.
+pub(crate) fn literal_type(expr: &Expr) -> Option<ParamType> {
+    match expr {
+        Expr::Value(value) => ParamType::of_value(value),
+        Expr::UnaryOp {
+            op: UnaryOperator::Minus | UnaryOperator::Plus,
+            expr,
+        }
+        | Expr::Nested(expr) => literal_type(expr),
+        _ => None,
+    }
+}
+
+pub(crate) fn as_column(expr: &Expr) -> Option<(Option<Ident>, Ident)> {
+    match expr {
+        Expr::Identifier(ident) => Some((None, ident.clone())),
+        Expr::CompoundIdentifier(idents) => match idents.as_slice() {
+            [.., qualifier, column] => Some((Some(qualifier.clone()), column.clone())),
+            [column] => Some((None, column.clone())),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Collects columns compared with values by comparison operators, `IN` lists, `BETWEEN` and `LIKE`,
+/// excluding those of subqueries.
+#[derive(Default)]
+struct ValueComparisonCollector {
+    depth: usize,
+    comparisons: Vec<(Option<Ident>, Ident, Option<ParamType>)>,
+}
+
+impl Visitor for ValueComparisonCollector {
+    type Break = ();
+
+    fn pre_visit_query(&mut self, _query: &Query) -> ControlFlow<Self::Break> {
+        self.depth += 1;
+        ControlFlow::Continue(())
+    }
+
+    fn post_visit_query(&mut self, _query: &Query) -> ControlFlow<Self::Break> {
+        self.depth -= 1;
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_expr(&mut self, expr: &Expr) -> ControlFlow<Self::Break> {
+        if self.depth > 0 {
+            return ControlFlow::Continue(());
+        }
+        let (operand, values) = match expr {
+            Expr::BinaryOp {
+                left,
+                op:
+                    BinaryOperator::Eq
+                    | BinaryOperator::NotEq
+                    | BinaryOperator::Lt
+                    | BinaryOperator::LtEq
+                    | BinaryOperator::Gt
+                    | BinaryOperator::GtEq,
+                right,
+            } => match (as_column(left), as_column(right)) {
+                (Some(_), None) => (&**left, vec![&**right]),
+                (None, Some(_)) => (&**right, vec![&**left]),
+                _ => return ControlFlow::Continue(()),
+            },
+            Expr::InList { expr, list, .. } => (&**expr, list.iter().collect()),
+            Expr::Between {
+                expr, low, high, ..
+            } => (&**expr, vec![&**low, &**high]),
+            Expr::Like { expr, pattern, .. } | Expr::ILike { expr, pattern, .. } => {
+                (&**expr, vec![&**pattern])
+            }
+            _ => return ControlFlow::Continue(()),
+        };
+        let Some((qualifier, column)) = as_column(operand) else {
+            return ControlFlow::Continue(());
+        };
+        for value_type in values.into_iter().filter_map(literal_type) {
+            self.comparisons
+                .push((qualifier.clone(), column.clone(), Some(value_type)));
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+#[derive(Default)]
+struct ColumnCollector {
+    depth: usize,
+    columns: Vec<(Option<Ident>, Ident)>,
+}
+
+impl Visitor for ColumnCollector {
+    type Break = ();
+
+    fn pre_visit_query(&mut self, _query: &Query) -> ControlFlow<Self::Break> {
+        self.depth += 1;
+        ControlFlow::Continue(())
+    }
+
+    fn post_visit_query(&mut self, _query: &Query) -> ControlFlow<Self::Break> {
+        self.depth -= 1;
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_expr(&mut self, expr: &Expr) -> ControlFlow<Self::Break> {
+        if self.depth > 0 {
+            return ControlFlow::Continue(());
+        }
+        match expr {
+            Expr::Identifier(ident) => self.columns.push((None, ident.clone())),
+            Expr::CompoundIdentifier(idents) => {
+                if let [.., qualifier, column] = idents.as_slice() {
+                    self.columns.push((Some(qualifier.clone()), column.clone()));
+                }
+            }
+            _ => {}
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+    use sqlparser::dialect::Dialect;
+    use sqlparser::parser::Parser;
+
+    fn assert_column_references(
+        sql: &str,
+        expected: Vec<(Option<&str>, &str, ColumnContext)>,
+        dialects: Vec<Box<dyn Dialect>>,
+    ) {
+        for dialect in dialects {
+            let statement = Parser::parse_sql(dialect.as_ref(), sql).unwrap().remove(0);
+            let result = collect_columns(&statement)
+                .references
+                .into_iter()
+                .map(|r| (r.table.map(|t| t.to_string()), r.column.value, r.context))
+                .collect::<Vec<_>>();
+            let expected = expected
+                .iter()
+                .map(|(table, column, context)| {
+                    (table.map(String::from), column.to_string(), *context)
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(result, expected, "Failed for dialect: {dialect:?}");
+        }
+    }
+
+    #[test]
+    fn test_select_contexts() {
+        let sql = "SELECT x.a, COUNT(t2.b) FROM t1 AS x JOIN t2 ON x.id = t2.id AND t2.c > 0 \
+            WHERE x.d = 1 GROUP BY x.a HAVING COUNT(t2.b) > 1 ORDER BY x.a";
+        assert_column_references(
+            sql,
+            vec![
+                (Some("t1"), "a", ColumnContext::Projection),
+                (Some("t2"), "b", ColumnContext::Projection),
+                (Some("t1"), "id", ColumnContext::JoinKey),
+                (Some("t2"), "id", ColumnContext::JoinKey),
+                (Some("t2"), "c", ColumnContext::Filter),
+                (Some("t1"), "d", ColumnContext::Filter),
+                (Some("t2"), "b", ColumnContext::Filter),
+                (Some("t1"), "a", ColumnContext::GroupBy),
+                (Some("t1"), "a", ColumnContext::OrderBy),
+            ],
+            all_dialects(),
+        );
+    }
+
+    #[test]
+    fn test_implicit_join_keys() {
+        let sql = "SELECT a FROM t1, t2 WHERE t1.id = t2.id AND t1.b = t1.c";
+        assert_column_references(
+            sql,
+            vec![
+                (None, "a", ColumnContext::Projection),
+                (Some("t1"), "id", ColumnContext::JoinKey),
+                (Some("t2"), "id", ColumnContext::JoinKey),
+                (Some("t1"), "b", ColumnContext::Filter),
+                (Some("t1"), "c", ColumnContext::Filter),
+            ],
+            all_dialects(),
+        );
+    }
+
+    #[test]
+    fn test_using() {
+        let sql = "SELECT a FROM t1 JOIN t2 USING (id)";
+        assert_column_references(
+            sql,
+            vec![
+                (None, "a", ColumnContext::Projection),
+                (Some("t2"), "id", ColumnContext::JoinKey),
+                (Some("t1"), "id", ColumnContext::JoinKey),
+            ],
+            all_dialects(),
+        );
+    }
+
+    #[test]
+    fn test_subqueries_and_ctes() {
+        let sql = "WITH c AS (SELECT a FROM t1) \
+            SELECT c.a, b FROM c WHERE EXISTS (SELECT 1 FROM t2 WHERE t2.a = c.a) ORDER BY b";
+        assert_column_references(
+            sql,
+            vec![
+                (None, "a", ColumnContext::Projection),
+                (None, "b", ColumnContext::Projection),
+                (None, "b", ColumnContext::OrderBy),
+                (Some("t1"), "a", ColumnContext::Projection),
+                (Some("t2"), "a", ColumnContext::JoinKey),
+                (None, "a", ColumnContext::JoinKey),
+            ],
+            all_dialects(),
+        );
+    }
+
+    #[test]
+    fn test_update_and_delete() {
+        let sql = "UPDATE t1 SET a = 1 WHERE b = 2 AND c IN (SELECT c FROM t2 WHERE t2.d = t1.d)";
+        assert_column_references(
+            sql,
+            vec![
+                (Some("t1"), "b", ColumnContext::Filter),
+                (Some("t1"), "c", ColumnContext::Filter),
+                (Some("t2"), "c", ColumnContext::Projection),
+                (Some("t2"), "d", ColumnContext::JoinKey),
+                (Some("t1"), "d", ColumnContext::JoinKey),
+            ],
+            all_dialects(),
+        );
+        let sql = "DELETE FROM t1 WHERE b = 2";
+        assert_column_references(
+            sql,
+            vec![(Some("t1"), "b", ColumnContext::Filter)],
+            all_dialects(),
+        );
+    }
+
+    #[test]
+    fn test_join_keys() {
+        let sql =
+            "SELECT a FROM t1 JOIN t2 ON t1.id = t2.t1_id AND t2.b > 0 JOIN t3 USING (id), t4 \
+            WHERE t4.c = t1.c AND t4.d = 1";
+        for dialect in all_dialects() {
+            let statement = Parser::parse_sql(dialect.as_ref(), sql).unwrap().remove(0);
+            let result = collect_columns(&statement)
+                .join_keys
+                .into_iter()
+                .map(|(left, right)| {
+                    (
+                        format!("{}.{}", left.table.unwrap(), left.column),
+                        format!("{}.{}", right.table.unwrap(), right.column),
+                    )
+                })
+                .collect::<Vec<_>>();
+            let expected = [("t1.id", "t2.t1_id"), ("t3.id", "t1.id"), ("t4.c", "t1.c")]
+                .map(|(left, right)| (left.to_string(), right.to_string()));
+            assert_eq!(result, expected, "Failed for dialect: {dialect:?}");
+        }
+    }
+
+    #[test]
+    fn test_values() {
+        let sql = "UPDATE t1 SET a = -1, b = c WHERE d IN (1.5, 'x') AND e NOT LIKE 'y%' AND 'z' <> t1.f \
+            AND g BETWEEN TRUE AND NULL AND h = i";
+        for dialect in all_dialects() {
+            let statement = Parser::parse_sql(dialect.as_ref(), sql).unwrap().remove(0);
+            let result = collect_columns(&statement)
+                .values
+                .into_iter()
+                .map(|v| (v.table.unwrap().to_string(), v.column.value, v.value_type))
+                .collect::<Vec<_>>();
+            let expected = [
+                ("a", Some(ParamType::Integer)),
+                ("b", None),
+                ("d", Some(ParamType::Float)),
+                ("d", Some(ParamType::String)),
+                ("e", Some(ParamType::String)),
+                ("f", Some(ParamType::String)),
+                ("g", Some(ParamType::Boolean)),
+                ("g", Some(ParamType::Null)),
+            ]
+            .map(|(column, value_type)| ("t1".to_string(), column.to_string(), value_type));
+            assert_eq!(result, expected, "Failed for dialect: {dialect:?}");
+        }
+    }
+
+    #[test]
+    fn test_writes_and_wildcards() {
+        let sql = "INSERT INTO t1 (a, b) SELECT x.*, t3.* FROM t2 AS x, t3";
+        for dialect in all_dialects() {
+            let statement = Parser::parse_sql(dialect.as_ref(), sql).unwrap().remove(0);
+            let columns = collect_columns(&statement);
+            let writes = columns
+                .writes
+                .into_iter()
+                .map(|(table, column)| (table.map(|t| t.to_string()), column.value))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                writes,
+                [
+                    (Some("t1".to_string()), "a".to_string()),
+                    (Some("t1".to_string()), "b".to_string())
+                ],
+                "Failed for dialect: {dialect:?}"
+            );
+            let wildcard_tables = columns
+                .wildcard_tables
+                .into_iter()
+                .map(|table| table.map(|t| t.to_string()))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                wildcard_tables,
+                [Some("t2".to_string()), Some("t3".to_string())],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_order_by_alias() {
+        let sql = "SELECT a + 1 AS b FROM t1 ORDER BY b";
+        assert_column_references(
+            sql,
+            vec![(Some("t1"), "a", ColumnContext::Projection)],
+            all_dialects(),
+        );
+    }
+}
@@ -0,0 +1,197 @@
+//! A summary of CRUD operations on each table across statements, such as those of a migration file.
+//!
+//! See [`summarize_crud_tables`](crate::summarize_crud_tables()) as the entry point for summarizing SQL,
+//! or [`CrudSummary::aggregate`] for merging [`CrudTables`] extracted beforehand.
+
+use core::fmt;
+use std::collections::HashMap;
+
+use crate::aggregator::{distinct, OperationCount};
+use crate::error::Error;
+use crate::extractor::crud_table_extractor::{CrudTableExtractor, CrudTables};
+use crate::extractor::table_extractor::TableReference;
+use sqlparser::dialect::Dialect;
+
+/// Convenience function to summarize CRUD operations on each table of SQL.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+///
+/// let dialect = GenericDialect {};
+/// let sql = "INSERT INTO t1 SELECT a FROM t2; UPDATE t1 SET a = 1; DELETE FROM t2; DROP TABLE t3";
+/// let result = sql_insight::summarize_crud_tables(&dialect, sql).unwrap();
+/// assert_eq!(result.statements, 4);
+/// assert_eq!(result.tables[0].to_string(), "t1: Create: 1, Read: 0, Update: 1, Delete: 0, DDL: 0");
+/// assert_eq!(result.tables[1].to_string(), "t2: Create: 0, Read: 1, Update: 0, Delete: 1, DDL: 0");
+/// assert_eq!(result.tables[2].to_string(), "t3: Create: 0, Read: 0, Update: 0, Delete: 0, DDL: 1");
+/// ```
+pub fn summarize_crud_tables(dialect: &dyn Dialect, sql: &str) -> Result<CrudSummary, Error> {
+    Ok(CrudSummary::aggregate_results(CrudTableExtractor::extract(
+        dialect, sql,
+    )?))
+}
+
+/// [`CrudTableSummary`] represents the operations on a table across statements.
+/// Counts are the number of statements, so a table read twice by one statement is counted once.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CrudTableSummary {
+    /// The table, without alias.
+    pub table: TableReference,
+    pub create: usize,
+    pub read: usize,
+    pub update: usize,
+    pub delete: usize,
+    /// Statements creating, altering, dropping or truncating the table.
+    pub ddl: usize,
+}
+
+impl fmt::Display for CrudTableSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: Create: {}, Read: {}, Update: {}, Delete: {}, DDL: {}",
+            self.table, self.create, self.read, self.update, self.delete, self.ddl
+        )
+    }
+}
+
+impl CrudTableSummary {
+    fn new(table: TableReference) -> Self {
+        Self {
+            table,
+            create: 0,
+            read: 0,
+            update: 0,
+            delete: 0,
+            ddl: 0,
+        }
+    }
+}
+
+/// [`CrudSummary`] represents the operations on each table merged from the [`CrudTables`] of statements.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CrudSummary {
+    /// Number of statements aggregated, including the failed ones.
+    pub statements: usize,
+    /// Number of statements whose tables could not be extracted.
+    pub errors: usize,
+    /// Operations on each table, ordered by table name.
+    pub tables: Vec<CrudTableSummary>,
+}
+
+impl CrudSummary {
+    /// Merge the [`CrudTables`] of statements.
+    pub fn aggregate<I>(crud_tables: I) -> Self
+    where
+        I: IntoIterator<Item = CrudTables>,
+    {
+        Self::aggregate_results(crud_tables.into_iter().map(Ok))
+    }
+
+    /// Merge the [`CrudTables`] of statements, counting the failed statements as errors.
+    pub fn aggregate_results<I>(results: I) -> Self
+    where
+        I: IntoIterator<Item = Result<CrudTables, Error>>,
+    {
+        let mut summary = Self::default();
+        let mut tables = HashMap::<TableReference, CrudTableSummary>::new();
+        for result in results {
+            summary.statements += 1;
+            let Ok(crud_tables) = result else {
+                summary.errors += 1;
+                continue;
+            };
+            let ddl_tables = crud_tables.ddl.tables();
+            let operations: [OperationCount<CrudTableSummary>; 5] = [
+                (&crud_tables.create_tables, |table| table.create += 1),
+                (&crud_tables.read_tables, |table| table.read += 1),
+                (&crud_tables.update_tables, |table| table.update += 1),
+                (&crud_tables.delete_tables, |table| table.delete += 1),
+                (&ddl_tables, |table| table.ddl += 1),
+            ];
+            for (references, count) in operations {
+                for table in distinct(references) {
+                    count(
+                        tables
+                            .entry(table.clone())
+                            .or_insert_with(|| CrudTableSummary::new(table)),
+                    );
+                }
+            }
+        }
+        summary.tables = tables.into_values().collect();
+        summary.tables.sort_by_key(|table| table.table.to_string());
+        summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+
+    fn table(name: &str, [create, read, update, delete, ddl]: [usize; 5]) -> CrudTableSummary {
+        CrudTableSummary {
+            create,
+            read,
+            update,
+            delete,
+            ddl,
+            ..CrudTableSummary::new(name.parse().unwrap())
+        }
+    }
+
+    #[test]
+    fn test_summarize_crud_tables() {
+        let sql = "CREATE TABLE t1 (a INT); \
+            INSERT INTO t1 (a) SELECT a FROM t2 AS x JOIN t2 AS y ON x.a = y.a; \
+            UPDATE t1 SET a = 1 WHERE a IN (SELECT a FROM t2); \
+            DELETE FROM t3; \
+            ALTER TABLE t3 ADD COLUMN b INT";
+        for dialect in all_dialects() {
+            let result = summarize_crud_tables(dialect.as_ref(), sql).unwrap();
+            assert_eq!(
+                result,
+                CrudSummary {
+                    statements: 5,
+                    errors: 0,
+                    tables: vec![
+                        table("t1", [1, 0, 1, 0, 1]),
+                        table("t2", [0, 2, 0, 0, 0]),
+                        table("t3", [0, 0, 0, 1, 1]),
+                    ],
+                },
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_aggregate_results_with_errors() {
+        for dialect in all_dialects() {
+            let results = CrudTableExtractor::extract(
+                dialect.as_ref(),
+                "SELECT a FROM t1; SELECT a FROM catalog.schema.table.extra",
+            )
+            .unwrap();
+            assert_eq!(
+                CrudSummary::aggregate_results(results),
+                CrudSummary {
+                    statements: 2,
+                    errors: 1,
+                    tables: vec![table("t1", [0, 1, 0, 0, 0])],
+                },
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_aggregate() {
+        assert_eq!(CrudSummary::aggregate(vec![]), CrudSummary::default());
+    }
+}
@@ -0,0 +1,311 @@
+//! Aggregation of statements into digests of statements differing only in literal values,
+//! optionally persisted into SQLite with the `sqlite` feature.
+//!
+//! See [`aggregate_digests`](crate::aggregate_digests()) as the entry point for aggregating SQL,
+//! or [`DigestAggregator`] for feeding statements incrementally.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::error::Error;
+use crate::export::table_names;
+use crate::extractor::table_extractor::TableExtractor;
+use crate::fingerprint::hash_normalized;
+use crate::instrument;
+use crate::normalizer::Normalizer;
+use sqlparser::ast::{Statement, VisitMut};
+use sqlparser::dialect::Dialect;
+
+/// Convenience function to aggregate the digests of SQL, where statements are seen at their positions in SQL.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+///
+/// let dialect = GenericDialect {};
+/// let sql = "SELECT a FROM t1 WHERE b = 1; DELETE FROM t2; SELECT a FROM t1 WHERE b = 2";
+/// let digests = sql_insight::aggregate_digests(&dialect, sql).unwrap();
+/// assert_eq!(digests[0].normalized, "SELECT a FROM t1 WHERE b = ?");
+/// assert_eq!((digests[0].count, digests[0].first_seen, digests[0].last_seen), (2, 0, 2));
+/// assert_eq!(digests[1].tables, ["t2"]);
+/// ```
+pub fn aggregate_digests(dialect: &dyn Dialect, sql: &str) -> Result<Vec<Digest>, Error> {
+    let statements = instrument::parse_sql(dialect, sql)?;
+    let mut aggregator = DigestAggregator::new();
+    for (index, statement) in statements.iter().enumerate() {
+        aggregator.add_statement(statement, index as u64)?;
+    }
+    Ok(aggregator.digests())
+}
+
+/// [`Digest`] represents statements with the same normalized form, i.e. differing only in literal values.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Digest {
+    /// Hexadecimal hash of the normalized statement.
+    pub fingerprint: String,
+    /// The normalized statement. See [`normalize`](crate::normalize()).
+    pub normalized: String,
+    /// Number of statements.
+    pub count: u64,
+    /// Tables of the statements, without aliases.
+    pub tables: Vec<String>,
+    /// When the first and last statements were seen, in whatever unit they were added with,
+    /// e.g. positions in the input or Unix timestamps.
+    pub first_seen: u64,
+    pub last_seen: u64,
+}
+
+impl fmt::Display for Digest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}\t{}\t{}",
+            self.count, self.fingerprint, self.normalized
+        )
+    }
+}
+
+impl Digest {
+    /// Merge another digest of the same statements into this one.
+    pub fn merge(&mut self, other: &Digest) {
+        self.count += other.count;
+        self.first_seen = self.first_seen.min(other.first_seen);
+        self.last_seen = self.last_seen.max(other.last_seen);
+    }
+}
+
+/// An aggregator of digests consuming statements one by one.
+#[derive(Default, Debug)]
+pub struct DigestAggregator {
+    digests: HashMap<String, Digest>,
+}
+
+impl DigestAggregator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a statement seen at `seen_at`.
+    pub fn add_statement(&mut self, statement: &Statement, seen_at: u64) -> Result<(), Error> {
+        let mut normalized = statement.clone();
+        let _ = normalized.visit(&mut Normalizer::new());
+        let normalized = normalized.to_string();
+        let fingerprint = hash_normalized(&normalized);
+        if let Some(digest) = self.digests.get_mut(&fingerprint) {
+            digest.count += 1;
+            digest.first_seen = digest.first_seen.min(seen_at);
+            digest.last_seen = digest.last_seen.max(seen_at);
+            return Ok(());
+        }
+        let tables = TableExtractor::extract_from_statement(statement)?;
+        self.digests.insert(
+            fingerprint.clone(),
+            Digest {
+                fingerprint,
+                normalized,
+                count: 1,
+                tables: table_names(&tables.0),
+                first_seen: seen_at,
+                last_seen: seen_at,
+            },
+        );
+        Ok(())
+    }
+
+    /// Digests aggregated so far, ordered by count descending, then by when they were first seen.
+    pub fn digests(&self) -> Vec<Digest> {
+        let mut digests = self.digests.values().cloned().collect::<Vec<_>>();
+        digests.sort_by(|a, b| {
+            b.count
+                .cmp(&a.count)
+                .then(a.first_seen.cmp(&b.first_seen))
+                .then(a.fingerprint.cmp(&b.fingerprint))
+        });
+        digests
+    }
+}
+
+#[cfg(feature = "sqlite")]
+pub use self::sqlite::*;
+
+#[cfg(feature = "sqlite")]
+mod sqlite {
+    use super::Digest;
+    use crate::error::Error;
+    use rusqlite::{params, Connection};
+
+    /// A sink persisting digests into a SQLite database, so that digests of successive runs accumulate
+    /// into a monitor of the workload.
+    ///
+    /// Digests are stored in the `digests` table, created if missing, with a row per fingerprint.
+    /// Writing a digest whose fingerprint is already stored adds up the counts and widens the first and last seen.
+    pub struct SqliteDigestSink {
+        connection: Connection,
+    }
+
+    fn sqlite_error(e: rusqlite::Error) -> Error {
+        Error::IOError(e.to_string())
+    }
+
+    impl SqliteDigestSink {
+        /// Open the database at `path`, creating it if missing.
+        pub fn open(path: impl AsRef<std::path::Path>) -> Result<Self, Error> {
+            Self::from_connection(Connection::open(path).map_err(sqlite_error)?)
+        }
+
+        pub fn open_in_memory() -> Result<Self, Error> {
+            Self::from_connection(Connection::open_in_memory().map_err(sqlite_error)?)
+        }
+
+        fn from_connection(connection: Connection) -> Result<Self, Error> {
+            connection
+                .execute_batch(
+                    "CREATE TABLE IF NOT EXISTS digests (
+                        fingerprint TEXT PRIMARY KEY,
+                        normalized TEXT NOT NULL,
+                        count INTEGER NOT NULL,
+                        tables TEXT NOT NULL,
+                        first_seen INTEGER NOT NULL,
+                        last_seen INTEGER NOT NULL
+                    )",
+                )
+                .map_err(sqlite_error)?;
+            Ok(Self { connection })
+        }
+
+        /// Upsert digests in a single transaction.
+        pub fn write(&mut self, digests: &[Digest]) -> Result<(), Error> {
+            let transaction = self.connection.transaction().map_err(sqlite_error)?;
+            {
+                let mut statement = transaction
+                    .prepare(
+                        "INSERT INTO digests (fingerprint, normalized, count, tables, first_seen, last_seen)
+                        VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+                        ON CONFLICT (fingerprint) DO UPDATE SET
+                            count = count + excluded.count,
+                            first_seen = min(first_seen, excluded.first_seen),
+                            last_seen = max(last_seen, excluded.last_seen)",
+                    )
+                    .map_err(sqlite_error)?;
+                for digest in digests {
+                    statement
+                        .execute(params![
+                            digest.fingerprint,
+                            digest.normalized,
+                            digest.count as i64,
+                            digest.tables.join("\n"),
+                            digest.first_seen as i64,
+                            digest.last_seen as i64,
+                        ])
+                        .map_err(sqlite_error)?;
+                }
+            }
+            transaction.commit().map_err(sqlite_error)
+        }
+
+        /// All stored digests, ordered by count descending, then by when they were first seen.
+        pub fn read(&self) -> Result<Vec<Digest>, Error> {
+            let mut statement = self
+                .connection
+                .prepare(
+                    "SELECT fingerprint, normalized, count, tables, first_seen, last_seen FROM digests
+                    ORDER BY count DESC, first_seen, fingerprint",
+                )
+                .map_err(sqlite_error)?;
+            let rows = statement
+                .query_map([], |row| {
+                    let tables: String = row.get(3)?;
+                    Ok(Digest {
+                        fingerprint: row.get(0)?,
+                        normalized: row.get(1)?,
+                        count: row.get::<_, i64>(2)? as u64,
+                        tables: tables
+                            .split('\n')
+                            .filter(|t| !t.is_empty())
+                            .map(String::from)
+                            .collect(),
+                        first_seen: row.get::<_, i64>(4)? as u64,
+                        last_seen: row.get::<_, i64>(5)? as u64,
+                    })
+                })
+                .map_err(sqlite_error)?;
+            rows.collect::<Result<Vec<_>, _>>().map_err(sqlite_error)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+
+    #[test]
+    fn test_aggregate_digests() {
+        let sql = "UPDATE t1 SET a = 1 WHERE b = 2; \
+            SELECT a FROM t2 AS x WHERE b IN (1, 2); \
+            UPDATE t1 SET a = 'x' WHERE b = 3; \
+            SELECT a FROM t2 AS x WHERE b IN (3, 4)";
+        for dialect in all_dialects() {
+            let digests = aggregate_digests(dialect.as_ref(), sql).unwrap();
+            let result = digests
+                .iter()
+                .map(|d| {
+                    (
+                        d.normalized.as_str(),
+                        d.count,
+                        d.tables.clone(),
+                        d.first_seen,
+                        d.last_seen,
+                    )
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(
+                result,
+                vec![
+                    (
+                        "UPDATE t1 SET a = ? WHERE b = ?",
+                        2,
+                        vec!["t1".to_string()],
+                        0,
+                        2
+                    ),
+                    (
+                        "SELECT a FROM t2 AS x WHERE b IN (?, ?)",
+                        2,
+                        vec!["t2".to_string()],
+                        1,
+                        3
+                    ),
+                ],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn test_sqlite_sink() {
+        let dialect = sqlparser::dialect::GenericDialect {};
+        let mut sink = SqliteDigestSink::open_in_memory().unwrap();
+        let first =
+            aggregate_digests(&dialect, "SELECT a FROM t1 WHERE b = 1; DELETE FROM t2").unwrap();
+        sink.write(&first).unwrap();
+        let mut second = DigestAggregator::new();
+        for statement in
+            sqlparser::parser::Parser::parse_sql(&dialect, "SELECT a FROM t1 WHERE b = 2").unwrap()
+        {
+            second.add_statement(&statement, 10).unwrap();
+        }
+        sink.write(&second.digests()).unwrap();
+        let stored = sink.read().unwrap();
+        let mut expected = first.clone();
+        expected[0].merge(&second.digests()[0]);
+        assert_eq!(stored, expected);
+        assert_eq!(
+            (stored[0].count, stored[0].first_seen, stored[0].last_seen),
+            (2, 0, 10)
+        );
+    }
+}
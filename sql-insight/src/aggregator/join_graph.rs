@@ -0,0 +1,322 @@
+//! A graph of how tables are joined across a workload of SQL statements.
+//!
+//! See [`build_join_graph`](crate::build_join_graph()) as the entry point for building a graph from SQL,
+//! or [`JoinGraphBuilder`] for feeding statements incrementally.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+use crate::aggregator::column_usage;
+use crate::error::Error;
+use crate::extractor::table_extractor::TableReference;
+use crate::instrument;
+use sqlparser::ast::{Ident, Statement};
+use sqlparser::dialect::Dialect;
+
+/// Convenience function to build a join graph of SQL.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+///
+/// let dialect = GenericDialect {};
+/// let sql = "SELECT * FROM orders AS o JOIN customers AS c ON o.customer_id = c.id; \
+///     SELECT * FROM customers, orders WHERE customers.id = orders.customer_id";
+/// let graph = sql_insight::build_join_graph(&dialect, sql).unwrap();
+/// assert_eq!(graph.edges[0].weight, 2);
+/// assert_eq!(
+///     graph.to_dot(),
+///     "graph joins {\n  \"customers\";\n  \"orders\";\n  \"customers\" -- \"orders\" [label=\"id = customer_id\", weight=2];\n}\n"
+/// );
+/// ```
+pub fn build_join_graph(dialect: &dyn Dialect, sql: &str) -> Result<JoinGraph, Error> {
+    let mut builder = JoinGraphBuilder::new();
+    builder.add_sql(dialect, sql)?;
+    Ok(builder.build())
+}
+
+/// [`JoinGraph`] represents tables as nodes, and the tables joined with each other as weighted edges.
+/// Serialize it with the `serde` feature to export it as JSON, or use [`JoinGraph::to_dot`] for Graphviz.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct JoinGraph {
+    /// Joined tables, ordered by table name.
+    pub tables: Vec<TableReference>,
+    /// Edges between joined tables, ordered by table names.
+    pub edges: Vec<JoinEdge>,
+}
+
+/// [`JoinEdge`] represents a pair of tables joined with each other.
+/// `left` is the table whose name comes first, and is the same as `right` for self joins.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct JoinEdge {
+    pub left: TableReference,
+    pub right: TableReference,
+    /// Columns the tables are joined on, ordered by column names.
+    pub keys: Vec<JoinKey>,
+    /// Number of statements joining the tables, where a statement joining them on several keys counts once.
+    pub weight: usize,
+}
+
+/// [`JoinKey`] represents an equality between a column of the left table and a column of the right table.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct JoinKey {
+    pub left_column: Ident,
+    pub right_column: Ident,
+    /// Number of times the columns are compared.
+    pub weight: usize,
+}
+
+impl JoinGraph {
+    /// Render the graph in the DOT language of Graphviz.
+    /// Edges are labeled with their keys and weighted by the number of statements joining the tables.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("graph joins {\n");
+        for table in &self.tables {
+            let _ = writeln!(dot, "  {};", quote(&table.to_string()));
+        }
+        for edge in &self.edges {
+            let label = edge
+                .keys
+                .iter()
+                .map(|key| format!("{} = {}", key.left_column, key.right_column))
+                .collect::<Vec<_>>()
+                .join("\n");
+            let _ = writeln!(
+                dot,
+                "  {} -- {} [label={}, weight={}];",
+                quote(&edge.left.to_string()),
+                quote(&edge.right.to_string()),
+                quote(&label),
+                edge.weight
+            );
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+fn quote(id: &str) -> String {
+    format!(
+        "\"{}\"",
+        id.replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+    )
+}
+
+/// A builder merging join keys of statements into a [`JoinGraph`].
+/// Join keys are equalities between columns in join conditions, between columns of different tables in WHERE,
+/// and columns of `USING`. Keys whose columns cannot be attributed to tables, e.g. unqualified columns of
+/// a SELECT reading several tables, are ignored.
+#[derive(Default, Debug)]
+pub struct JoinGraphBuilder {
+    edges: HashMap<(TableReference, TableReference), HashMap<(Ident, Ident), usize>>,
+    /// Number of statements joining each pair of tables.
+    joins: HashMap<(TableReference, TableReference), usize>,
+}
+
+impl JoinGraphBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add join keys of all statements of SQL.
+    pub fn add_sql(&mut self, dialect: &dyn Dialect, sql: &str) -> Result<(), Error> {
+        let statements = instrument::parse_sql(dialect, sql)?;
+        self.add_statements(&statements);
+        Ok(())
+    }
+
+    /// Add join keys of statements.
+    pub fn add_statements<'a, I>(&mut self, statements: I)
+    where
+        I: IntoIterator<Item = &'a Statement>,
+    {
+        for statement in statements {
+            self.add_statement(statement);
+        }
+    }
+
+    /// Add join keys of a statement.
+    pub fn add_statement(&mut self, statement: &Statement) {
+        let mut joined = HashSet::new();
+        for (left, right) in column_usage::collect_columns(statement).join_keys {
+            let (Some(left_table), Some(right_table)) = (left.table, right.table) else {
+                continue;
+            };
+            let (mut left, mut right) = ((left_table, left.column), (right_table, right.column));
+            if (left.0.to_string(), &left.1.value) > (right.0.to_string(), &right.1.value) {
+                std::mem::swap(&mut left, &mut right);
+            }
+            joined.insert((left.0.clone(), right.0.clone()));
+            *self
+                .edges
+                .entry((left.0, right.0))
+                .or_default()
+                .entry((left.1, right.1))
+                .or_default() += 1;
+        }
+        for tables in joined {
+            *self.joins.entry(tables).or_default() += 1;
+        }
+    }
+
+    /// The graph built so far.
+    pub fn build(&self) -> JoinGraph {
+        let mut tables = Vec::<TableReference>::new();
+        let mut edges = self
+            .edges
+            .iter()
+            .map(|((left, right), keys)| {
+                for table in [left, right] {
+                    if !tables.contains(table) {
+                        tables.push(table.clone());
+                    }
+                }
+                let mut keys = keys
+                    .iter()
+                    .map(|((left_column, right_column), weight)| JoinKey {
+                        left_column: left_column.clone(),
+                        right_column: right_column.clone(),
+                        weight: *weight,
+                    })
+                    .collect::<Vec<_>>();
+                keys.sort_by_key(|key| {
+                    (
+                        key.left_column.value.clone(),
+                        key.right_column.value.clone(),
+                    )
+                });
+                JoinEdge {
+                    weight: self.joins[&(left.clone(), right.clone())],
+                    left: left.clone(),
+                    right: right.clone(),
+                    keys,
+                }
+            })
+            .collect::<Vec<_>>();
+        tables.sort_by_key(|table| table.to_string());
+        edges.sort_by_key(|edge| (edge.left.to_string(), edge.right.to_string()));
+        JoinGraph { tables, edges }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+
+    fn table(name: &str) -> TableReference {
+        TableReference {
+            catalog: None,
+            schema: None,
+            name: Ident::new(name),
+            alias: None,
+        }
+    }
+
+    fn key(left_column: &str, right_column: &str, weight: usize) -> JoinKey {
+        JoinKey {
+            left_column: Ident::new(left_column),
+            right_column: Ident::new(right_column),
+            weight,
+        }
+    }
+
+    #[test]
+    fn test_build_join_graph() {
+        let sql = "SELECT * FROM orders AS o JOIN customers AS c ON o.customer_id = c.id; \
+            SELECT * FROM orders JOIN items USING (order_id) JOIN customers ON customers.id = orders.customer_id; \
+            SELECT * FROM orders AS a JOIN orders AS b ON a.parent_id = b.id; \
+            SELECT * FROM orders, items WHERE order_id = id";
+        for dialect in all_dialects() {
+            let graph = build_join_graph(dialect.as_ref(), sql).unwrap();
+            assert_eq!(
+                graph,
+                JoinGraph {
+                    tables: vec![table("customers"), table("items"), table("orders")],
+                    edges: vec![
+                        JoinEdge {
+                            left: table("customers"),
+                            right: table("orders"),
+                            keys: vec![key("id", "customer_id", 2)],
+                            weight: 2,
+                        },
+                        JoinEdge {
+                            left: table("items"),
+                            right: table("orders"),
+                            keys: vec![key("order_id", "order_id", 1)],
+                            weight: 1,
+                        },
+                        JoinEdge {
+                            left: table("orders"),
+                            right: table("orders"),
+                            keys: vec![key("id", "parent_id", 1)],
+                            weight: 1,
+                        },
+                    ],
+                },
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_weight_counts_statements() {
+        let sql = "SELECT * FROM t1 JOIN t2 ON t1.a = t2.a AND t1.b = t2.b; \
+            SELECT * FROM t1 JOIN t2 ON t1.a = t2.a";
+        for dialect in all_dialects() {
+            let graph = build_join_graph(dialect.as_ref(), sql).unwrap();
+            assert_eq!(
+                graph.edges,
+                vec![JoinEdge {
+                    left: table("t1"),
+                    right: table("t2"),
+                    keys: vec![key("a", "a", 2), key("b", "b", 1)],
+                    weight: 2,
+                }],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let graph = JoinGraph {
+            tables: vec![table("t1"), table("t\"2")],
+            edges: vec![JoinEdge {
+                left: table("t1"),
+                right: table("t\"2"),
+                keys: vec![key("a", "a", 1), key("b", "c", 2)],
+                weight: 3,
+            }],
+        };
+        assert_eq!(
+            graph.to_dot(),
+            "graph joins {\n  \"t1\";\n  \"t\\\"2\";\n  \"t1\" -- \"t\\\"2\" [label=\"a = a\\nb = c\", weight=3];\n}\n"
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_join_graph_serialization() {
+        let graph = JoinGraph {
+            tables: vec![table("t1"), table("t2")],
+            edges: vec![JoinEdge {
+                left: table("t1"),
+                right: table("t2"),
+                keys: vec![key("id", "t1_id", 1)],
+                weight: 1,
+            }],
+        };
+        let json = serde_json::to_value(&graph).unwrap();
+        assert_eq!(json["edges"][0]["weight"], 1);
+        assert_eq!(json["edges"][0]["keys"][0]["left_column"]["value"], "id");
+        let deserialized: JoinGraph = serde_json::from_value(json).unwrap();
+        assert_eq!(deserialized, graph);
+    }
+}
@@ -0,0 +1,472 @@
+//! A aggregator that summarizes how tables and columns are used across a workload of SQL statements.
+//!
+//! See [`aggregate_table_usage`](crate::aggregate_table_usage()) as the entry point for aggregating SQL,
+//! or [`WorkloadAggregator`] for feeding statements incrementally.
+//! See the [`crud_summary`] module for counts of CRUD operations on each table merged from extracted [`CrudTables`](crate::CrudTables),
+//! the [`digest`] module for digests of statements differing only in literal values,
+//! the [`join_graph`] module for the graph of how tables are joined,
+//! the [`schema_inference`] module for the schema inferred from the workload,
+//! the [`sensitive_columns`] module for access to sensitive columns,
+//! the [`table_access`] module for statements touching a table,
+//! and the [`similarity`] module for clustering near-duplicate statements.
+
+pub(crate) mod column_usage;
+pub mod crud_summary;
+pub mod digest;
+pub mod join_graph;
+pub mod schema_inference;
+pub mod sensitive_columns;
+pub mod similarity;
+pub mod table_access;
+
+pub use crud_summary::*;
+pub use digest::*;
+pub use join_graph::*;
+pub use schema_inference::*;
+pub use sensitive_columns::*;
+pub use similarity::*;
+pub use table_access::*;
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::aggregator::column_usage::ColumnContext;
+use crate::error::Error;
+use crate::extractor::crud_table_extractor::CrudTableExtractor;
+use crate::extractor::table_extractor::TableReference;
+use crate::instrument;
+use sqlparser::ast::{Ident, Statement};
+use sqlparser::dialect::Dialect;
+
+/// Convenience function to aggregate table and column usage of SQL.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+///
+/// let dialect = GenericDialect {};
+/// let sql = "SELECT a FROM t1; INSERT INTO t2 SELECT a FROM t1 AS x; DELETE FROM t2";
+/// let result = sql_insight::aggregate_table_usage(&dialect, sql).unwrap();
+/// assert_eq!(result.statements, 3);
+/// let t1 = &result.tables[0];
+/// assert_eq!(t1.table.to_string(), "t1");
+/// assert_eq!((t1.read, t1.first_seen, t1.last_seen), (2, 0, 1));
+/// assert_eq!(t1.co_occurrences["t2"], 1);
+/// let t2 = &result.tables[1];
+/// assert_eq!((t2.create, t2.delete, t2.statements), (1, 1, 2));
+/// let a = &result.columns[0];
+/// assert_eq!((a.column.value.as_str(), a.projection), ("a", 2));
+/// ```
+pub fn aggregate_table_usage(dialect: &dyn Dialect, sql: &str) -> Result<WorkloadUsage, Error> {
+    let mut aggregator = WorkloadAggregator::new();
+    aggregator.add_sql(dialect, sql)?;
+    Ok(aggregator.summary())
+}
+
+/// [`TableUsage`] represents how a table is used across a workload.
+/// Counts are the number of statements, so a table read twice by one statement is counted once.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TableUsage {
+    /// The table, without alias.
+    pub table: TableReference,
+    pub create: usize,
+    pub read: usize,
+    pub update: usize,
+    pub delete: usize,
+    /// Statements creating, altering, dropping or truncating the table.
+    pub ddl: usize,
+    /// Number of statements referencing the table in any operation.
+    pub statements: usize,
+    /// Index of the first statement referencing the table.
+    pub first_seen: usize,
+    /// Index of the last statement referencing the table.
+    pub last_seen: usize,
+    /// Number of statements referencing both the table and the other table, keyed by the other table.
+    pub co_occurrences: BTreeMap<String, usize>,
+}
+
+impl TableUsage {
+    fn new(table: TableReference, index: usize) -> Self {
+        Self {
+            table,
+            create: 0,
+            read: 0,
+            update: 0,
+            delete: 0,
+            ddl: 0,
+            statements: 0,
+            first_seen: index,
+            last_seen: index,
+            co_occurrences: BTreeMap::new(),
+        }
+    }
+}
+
+/// [`ColumnUsage`] represents how a column is used across a workload.
+/// Counts are the number of references, so a column filtered twice by one statement is counted twice.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ColumnUsage {
+    /// The table of the column, or `None` if it cannot be determined,
+    /// e.g. for unqualified columns of a SELECT reading several tables, or columns of derived tables and CTEs.
+    pub table: Option<TableReference>,
+    pub column: Ident,
+    /// References in the SELECT list.
+    pub projection: usize,
+    /// References in WHERE and HAVING, and in join conditions other than column equalities.
+    pub filter: usize,
+    /// References in column equalities of join conditions or between tables in WHERE, and in USING.
+    pub join_key: usize,
+    /// References in GROUP BY.
+    pub group_by: usize,
+    /// References in ORDER BY.
+    pub order_by: usize,
+}
+
+impl ColumnUsage {
+    fn new(table: Option<TableReference>, column: Ident) -> Self {
+        Self {
+            table,
+            column,
+            projection: 0,
+            filter: 0,
+            join_key: 0,
+            group_by: 0,
+            order_by: 0,
+        }
+    }
+}
+
+/// [`WorkloadUsage`] represents the aggregated table and column usage of a workload.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WorkloadUsage {
+    /// Number of statements aggregated, including the failed ones.
+    pub statements: usize,
+    /// Number of statements whose tables could not be extracted.
+    pub errors: usize,
+    /// Usage of each table, ordered by table name.
+    pub tables: Vec<TableUsage>,
+    /// Usage of each column, ordered by table name and column name.
+    pub columns: Vec<ColumnUsage>,
+}
+
+/// An aggregator consuming statements one by one, in the order they appear in the workload.
+/// Each statement takes the next index, which is what `first_seen` and `last_seen` refer to.
+#[derive(Default, Debug)]
+pub struct WorkloadAggregator {
+    statements: usize,
+    errors: usize,
+    tables: HashMap<TableReference, TableUsage>,
+    columns: HashMap<(Option<TableReference>, Ident), ColumnUsage>,
+}
+
+impl WorkloadAggregator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Aggregate all statements of SQL.
+    /// Statements whose tables cannot be extracted are counted as errors and otherwise skipped.
+    pub fn add_sql(&mut self, dialect: &dyn Dialect, sql: &str) -> Result<(), Error> {
+        let statements = instrument::parse_sql(dialect, sql)?;
+        self.add_statements(&statements);
+        Ok(())
+    }
+
+    /// Aggregate statements in order.
+    /// Statements whose tables cannot be extracted are counted as errors and otherwise skipped.
+    pub fn add_statements<'a, I>(&mut self, statements: I)
+    where
+        I: IntoIterator<Item = &'a Statement>,
+    {
+        for statement in statements {
+            let _ = self.add_statement(statement);
+        }
+    }
+
+    /// Aggregate a statement, returning the error if its tables cannot be extracted.
+    /// The failed statement still takes an index and is counted as an error.
+    pub fn add_statement(&mut self, statement: &Statement) -> Result<(), Error> {
+        let index = self.statements;
+        self.statements += 1;
+        let crud_tables = match CrudTableExtractor::extract_from_statement(statement) {
+            Ok(crud_tables) => crud_tables,
+            Err(e) => {
+                self.errors += 1;
+                return Err(e);
+            }
+        };
+        let ddl_tables = crud_tables.ddl.tables();
+        let operations: [OperationCount<TableUsage>; 5] = [
+            (&crud_tables.create_tables, |usage| usage.create += 1),
+            (&crud_tables.read_tables, |usage| usage.read += 1),
+            (&crud_tables.update_tables, |usage| usage.update += 1),
+            (&crud_tables.delete_tables, |usage| usage.delete += 1),
+            (&ddl_tables, |usage| usage.ddl += 1),
+        ];
+        let mut referenced = Vec::<TableReference>::new();
+        for (tables, count) in operations {
+            let tables = distinct(tables);
+            for table in &tables {
+                count(self.usage_mut(table, index));
+            }
+            for table in tables {
+                if !referenced.contains(&table) {
+                    referenced.push(table);
+                }
+            }
+        }
+        let names = referenced
+            .iter()
+            .map(|table| table.to_string())
+            .collect::<Vec<_>>();
+        for table in &referenced {
+            let usage = self.usage_mut(table, index);
+            usage.statements += 1;
+            usage.last_seen = index;
+            let name = table.to_string();
+            for other in names.iter().filter(|other| **other != name) {
+                *usage.co_occurrences.entry(other.clone()).or_default() += 1;
+            }
+        }
+        for reference in column_usage::collect_columns(statement).references {
+            let usage = self
+                .columns
+                .entry((reference.table.clone(), reference.column.clone()))
+                .or_insert_with(|| ColumnUsage::new(reference.table, reference.column));
+            match reference.context {
+                ColumnContext::Projection => usage.projection += 1,
+                ColumnContext::Filter => usage.filter += 1,
+                ColumnContext::JoinKey => usage.join_key += 1,
+                ColumnContext::GroupBy => usage.group_by += 1,
+                ColumnContext::OrderBy => usage.order_by += 1,
+            }
+        }
+        Ok(())
+    }
+
+    /// The usage aggregated so far.
+    pub fn summary(&self) -> WorkloadUsage {
+        let mut tables = self.tables.values().cloned().collect::<Vec<_>>();
+        tables.sort_by_key(|usage| usage.table.to_string());
+        let mut columns = self.columns.values().cloned().collect::<Vec<_>>();
+        columns.sort_by_key(|usage| {
+            (
+                usage.table.as_ref().map(|table| table.to_string()),
+                usage.column.value.clone(),
+            )
+        });
+        WorkloadUsage {
+            statements: self.statements,
+            errors: self.errors,
+            tables,
+            columns,
+        }
+    }
+
+    fn usage_mut(&mut self, table: &TableReference, index: usize) -> &mut TableUsage {
+        self.tables
+            .entry(table.clone())
+            .or_insert_with(|| TableUsage::new(table.clone(), index))
+    }
+}
+
+/// Tables referenced by an operation, and how to count a reference to one of them.
+type OperationCount<'a, T> = (&'a [TableReference], fn(&mut T));
+
+/// Tables without aliases, deduplicated.
+fn distinct(tables: &[TableReference]) -> Vec<TableReference> {
+    let mut distinct = Vec::<TableReference>::new();
+    for table in tables {
+        let table = TableReference {
+            alias: None,
+            ..table.clone()
+        };
+        if !distinct.contains(&table) {
+            distinct.push(table);
+        }
+    }
+    distinct
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+
+    fn table(name: &str) -> TableReference {
+        TableReference {
+            catalog: None,
+            schema: None,
+            name: Ident::new(name),
+            alias: None,
+        }
+    }
+
+    fn usage(
+        name: &str,
+        [create, read, update, delete, ddl]: [usize; 5],
+        statements: usize,
+        (first_seen, last_seen): (usize, usize),
+        co_occurrences: &[(&str, usize)],
+    ) -> TableUsage {
+        TableUsage {
+            table: table(name),
+            create,
+            read,
+            update,
+            delete,
+            ddl,
+            statements,
+            first_seen,
+            last_seen,
+            co_occurrences: co_occurrences
+                .iter()
+                .map(|(other, count)| (other.to_string(), *count))
+                .collect(),
+        }
+    }
+
+    fn column(
+        table_name: Option<&str>,
+        name: &str,
+        [projection, filter, join_key, group_by, order_by]: [usize; 5],
+    ) -> ColumnUsage {
+        ColumnUsage {
+            table: table_name.map(table),
+            column: Ident::new(name),
+            projection,
+            filter,
+            join_key,
+            group_by,
+            order_by,
+        }
+    }
+
+    #[test]
+    fn test_aggregate_table_usage() {
+        let sql = "SELECT a FROM t1 AS x JOIN t2 ON x.id = t2.id; \
+            INSERT INTO t3 SELECT a FROM t1; \
+            UPDATE t2 SET a = 1; \
+            DELETE FROM t3 WHERE a IN (SELECT a FROM t1 WHERE t1.a = t1.b)";
+        for dialect in all_dialects() {
+            let result = aggregate_table_usage(dialect.as_ref(), sql).unwrap();
+            assert_eq!(
+                result,
+                WorkloadUsage {
+                    statements: 4,
+                    errors: 0,
+                    tables: vec![
+                        usage("t1", [0, 3, 0, 0, 0], 3, (0, 3), &[("t2", 1), ("t3", 2)]),
+                        usage("t2", [0, 1, 1, 0, 0], 2, (0, 2), &[("t1", 1)]),
+                        usage("t3", [1, 0, 0, 1, 0], 2, (1, 3), &[("t1", 2)]),
+                    ],
+                    columns: vec![
+                        column(None, "a", [1, 0, 0, 0, 0]),
+                        column(Some("t1"), "a", [2, 1, 0, 0, 0]),
+                        column(Some("t1"), "b", [0, 1, 0, 0, 0]),
+                        column(Some("t1"), "id", [0, 0, 1, 0, 0]),
+                        column(Some("t2"), "id", [0, 0, 1, 0, 0]),
+                        column(Some("t3"), "a", [0, 1, 0, 0, 0]),
+                    ],
+                },
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_aggregate_ddl() {
+        let sql = "CREATE TABLE t1 (a INT); ALTER TABLE t1 ADD COLUMN b INT; SELECT a FROM t1; DROP TABLE t1, t2";
+        for dialect in all_dialects() {
+            let result = aggregate_table_usage(dialect.as_ref(), sql).unwrap();
+            assert_eq!(
+                result.tables,
+                vec![
+                    usage("t1", [0, 1, 0, 0, 3], 4, (0, 3), &[("t2", 1)]),
+                    usage("t2", [0, 0, 0, 0, 1], 1, (3, 3), &[("t1", 1)]),
+                ],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_aggregate_table_usage_with_qualified_tables() {
+        let sql = "SELECT a FROM s.t1; SELECT a FROM t1";
+        for dialect in all_dialects() {
+            let result = aggregate_table_usage(dialect.as_ref(), sql).unwrap();
+            let tables = result
+                .tables
+                .iter()
+                .map(|usage| (usage.table.to_string(), usage.read))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                tables,
+                [("s.t1".to_string(), 1), ("t1".to_string(), 1)],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_aggregate_column_usage() {
+        let sql = "SELECT c.name, SUM(o.total) FROM customers AS c JOIN orders AS o ON c.id = o.customer_id \
+            WHERE o.status = 'paid' GROUP BY c.name ORDER BY c.name; \
+            SELECT total FROM orders WHERE status = 'open' ORDER BY created_at";
+        for dialect in all_dialects() {
+            let result = aggregate_table_usage(dialect.as_ref(), sql).unwrap();
+            assert_eq!(
+                result.columns,
+                vec![
+                    column(Some("customers"), "id", [0, 0, 1, 0, 0]),
+                    column(Some("customers"), "name", [1, 0, 0, 1, 1]),
+                    column(Some("orders"), "created_at", [0, 0, 0, 0, 1]),
+                    column(Some("orders"), "customer_id", [0, 0, 1, 0, 0]),
+                    column(Some("orders"), "status", [0, 2, 0, 0, 0]),
+                    column(Some("orders"), "total", [2, 0, 0, 0, 0]),
+                ],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_workload_aggregator_incrementally() {
+        for dialect in all_dialects() {
+            let mut aggregator = WorkloadAggregator::new();
+            aggregator
+                .add_sql(dialect.as_ref(), "SELECT a FROM t1")
+                .unwrap();
+            aggregator
+                .add_sql(dialect.as_ref(), "SELECT 1; SELECT a FROM t1")
+                .unwrap();
+            assert_eq!(
+                aggregator.summary(),
+                WorkloadUsage {
+                    statements: 3,
+                    errors: 0,
+                    tables: vec![usage("t1", [0, 2, 0, 0, 0], 2, (0, 2), &[])],
+                    columns: vec![column(Some("t1"), "a", [2, 0, 0, 0, 0])],
+                },
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_workload_aggregator_with_parse_error() {
+        for dialect in all_dialects() {
+            let mut aggregator = WorkloadAggregator::new();
+            assert!(
+                aggregator
+                    .add_sql(dialect.as_ref(), "SELECT a FROM")
+                    .is_err(),
+                "Failed for dialect: {dialect:?}"
+            );
+            assert_eq!(aggregator.summary(), WorkloadUsage::default());
+        }
+    }
+}
@@ -0,0 +1,254 @@
+//! A best-effort inference of the schema a workload of SQL statements runs against.
+//!
+//! See [`infer_schema`](crate::infer_schema()) as the entry point for inferring a schema from SQL,
+//! or [`SchemaInferrer`] for feeding statements incrementally.
+
+use std::collections::HashMap;
+
+use crate::aggregator::column_usage::{self, StatementColumns};
+use crate::error::Error;
+use crate::extractor::table_extractor::TableReference;
+use crate::instrument;
+use crate::prepared::ParamType;
+use sqlparser::ast::{Ident, Statement};
+use sqlparser::dialect::Dialect;
+
+/// Convenience function to infer a schema from SQL.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+/// use sql_insight::ParamType;
+///
+/// let dialect = GenericDialect {};
+/// let sql = "SELECT name FROM users WHERE age > 30; INSERT INTO users (name, score) VALUES ('x', 1.5)";
+/// let schema = sql_insight::infer_schema(&dialect, sql).unwrap();
+/// let users = &schema.tables[0];
+/// assert_eq!(users.table.to_string(), "users");
+/// let columns = users
+///     .columns
+///     .iter()
+///     .map(|c| (c.name.value.as_str(), c.type_hints.clone()))
+///     .collect::<Vec<_>>();
+/// assert_eq!(columns, [
+///     ("age", vec![ParamType::Integer]),
+///     ("name", vec![ParamType::String]),
+///     ("score", vec![ParamType::Float]),
+/// ]);
+/// ```
+pub fn infer_schema(dialect: &dyn Dialect, sql: &str) -> Result<InferredSchema, Error> {
+    let mut inferrer = SchemaInferrer::new();
+    inferrer.add_sql(dialect, sql)?;
+    Ok(inferrer.infer())
+}
+
+/// [`InferredSchema`] represents the tables and columns observed in a workload.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InferredSchema {
+    /// Observed tables, ordered by table name.
+    pub tables: Vec<InferredTable>,
+    /// Columns whose tables cannot be determined, e.g. unqualified columns of a SELECT reading several tables,
+    /// ordered by column name.
+    pub unresolved_columns: Vec<InferredColumn>,
+}
+
+/// [`InferredTable`] represents a table and the columns referenced on it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InferredTable {
+    pub table: TableReference,
+    /// Referenced columns, ordered by column name.
+    pub columns: Vec<InferredColumn>,
+}
+
+/// [`InferredColumn`] represents a column with the types of the literal values it is compared with,
+/// assigned by UPDATE, or inserted by INSERT.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InferredColumn {
+    pub name: Ident,
+    /// Distinct types of the observed values, in the order they are first observed.
+    /// Empty when the column is never compared with literal values.
+    pub type_hints: Vec<ParamType>,
+}
+
+/// An inferrer consuming statements one by one.
+#[derive(Default, Debug)]
+pub struct SchemaInferrer {
+    tables: HashMap<TableReference, HashMap<Ident, Vec<ParamType>>>,
+    unresolved_columns: HashMap<Ident, Vec<ParamType>>,
+}
+
+impl SchemaInferrer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Observe all statements of SQL.
+    pub fn add_sql(&mut self, dialect: &dyn Dialect, sql: &str) -> Result<(), Error> {
+        let statements = instrument::parse_sql(dialect, sql)?;
+        self.add_statements(&statements);
+        Ok(())
+    }
+
+    /// Observe statements.
+    pub fn add_statements<'a, I>(&mut self, statements: I)
+    where
+        I: IntoIterator<Item = &'a Statement>,
+    {
+        for statement in statements {
+            self.add_statement(statement);
+        }
+    }
+
+    /// Observe a statement.
+    pub fn add_statement(&mut self, statement: &Statement) {
+        let StatementColumns {
+            references,
+            values,
+            tables,
+            ..
+        } = column_usage::collect_columns(statement);
+        for table in tables {
+            self.tables.entry(table).or_default();
+        }
+        for reference in references {
+            self.column_mut(reference.table, reference.column);
+        }
+        for value in values {
+            let type_hints = self.column_mut(value.table, value.column);
+            if let Some(value_type) = value.value_type {
+                if !type_hints.contains(&value_type) {
+                    type_hints.push(value_type);
+                }
+            }
+        }
+    }
+
+    /// The schema inferred so far.
+    pub fn infer(&self) -> InferredSchema {
+        let mut tables = self
+            .tables
+            .iter()
+            .map(|(table, columns)| InferredTable {
+                table: table.clone(),
+                columns: inferred_columns(columns),
+            })
+            .collect::<Vec<_>>();
+        tables.sort_by_key(|table| table.table.to_string());
+        InferredSchema {
+            tables,
+            unresolved_columns: inferred_columns(&self.unresolved_columns),
+        }
+    }
+
+    fn column_mut(&mut self, table: Option<TableReference>, column: Ident) -> &mut Vec<ParamType> {
+        let columns = match table {
+            Some(table) => self.tables.entry(table).or_default(),
+            None => &mut self.unresolved_columns,
+        };
+        columns.entry(column).or_default()
+    }
+}
+
+fn inferred_columns(columns: &HashMap<Ident, Vec<ParamType>>) -> Vec<InferredColumn> {
+    let mut columns = columns
+        .iter()
+        .map(|(name, type_hints)| InferredColumn {
+            name: name.clone(),
+            type_hints: type_hints.clone(),
+        })
+        .collect::<Vec<_>>();
+    columns.sort_by_key(|column| column.name.value.clone());
+    columns
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+
+    fn table(name: &str, columns: Vec<InferredColumn>) -> InferredTable {
+        InferredTable {
+            table: TableReference {
+                catalog: None,
+                schema: None,
+                name: Ident::new(name),
+                alias: None,
+            },
+            columns,
+        }
+    }
+
+    fn column(name: &str, type_hints: &[ParamType]) -> InferredColumn {
+        InferredColumn {
+            name: Ident::new(name),
+            type_hints: type_hints.to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_infer_schema() {
+        let sql = "SELECT id, name FROM users WHERE age > 30 AND name LIKE 'a%'; \
+            INSERT INTO users (id, name, score) VALUES (1, 'x', 1.5), (2, NULL, -2); \
+            UPDATE orders SET status = 'paid' WHERE id IN (1, 2); \
+            SELECT o.total, kind FROM orders AS o JOIN users AS u ON o.user_id = u.id \
+            WHERE o.created_at BETWEEN '2024-01-01' AND '2024-12-31'; \
+            SELECT a FROM logs";
+        for dialect in all_dialects() {
+            let result = infer_schema(dialect.as_ref(), sql).unwrap();
+            assert_eq!(
+                result,
+                InferredSchema {
+                    tables: vec![
+                        table("logs", vec![column("a", &[])]),
+                        table(
+                            "orders",
+                            vec![
+                                column("created_at", &[ParamType::String]),
+                                column("id", &[ParamType::Integer]),
+                                column("status", &[ParamType::String]),
+                                column("total", &[]),
+                                column("user_id", &[]),
+                            ]
+                        ),
+                        table(
+                            "users",
+                            vec![
+                                column("age", &[ParamType::Integer]),
+                                column("id", &[ParamType::Integer]),
+                                column("name", &[ParamType::String, ParamType::Null]),
+                                column("score", &[ParamType::Float, ParamType::Integer]),
+                            ]
+                        ),
+                    ],
+                    unresolved_columns: vec![column("kind", &[])],
+                },
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_infer_schema_without_columns() {
+        let sql = "SELECT * FROM t1 WHERE EXISTS (SELECT 1 FROM t2); \
+            WITH c AS (SELECT * FROM t3) SELECT * FROM c";
+        for dialect in all_dialects() {
+            let result = infer_schema(dialect.as_ref(), sql).unwrap();
+            assert_eq!(
+                result,
+                InferredSchema {
+                    tables: vec![
+                        table("t1", vec![]),
+                        table("t2", vec![]),
+                        table("t3", vec![])
+                    ],
+                    unresolved_columns: vec![],
+                },
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,487 @@
+//! A report of statements reading or writing sensitive columns, such as personal or secret data.
+//!
+//! See [`find_sensitive_column_access`](crate::find_sensitive_column_access()) as the entry point for finding access in SQL,
+//! or [`SensitiveColumnFinder`] for finding access in parsed statements.
+
+use std::fmt;
+
+use crate::aggregator::column_usage::{self, StatementColumns};
+use crate::error::Error;
+use crate::extractor::table_extractor::TableReference;
+use crate::instrument;
+use sqlparser::ast::{Ident, Statement};
+use sqlparser::dialect::Dialect;
+
+/// Convenience function to find access to sensitive columns in SQL, for each statement in input order.
+/// See [`SensitiveColumnPattern::parse`] for the syntax of patterns.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+/// use sql_insight::ColumnAccess;
+///
+/// let dialect = GenericDialect {};
+/// let sql = "SELECT u.email AS contact FROM users AS u; \
+///     UPDATE customers SET ssn = NULL; \
+///     SELECT name FROM users";
+/// let result = sql_insight::find_sensitive_column_access(&dialect, sql, &["users.email", "*.ssn"]).unwrap();
+/// let accesses: Vec<Vec<_>> = result
+///     .iter()
+///     .map(|a| a.iter().map(|a| (a.pattern.to_string(), a.access)).collect())
+///     .collect();
+/// assert_eq!(accesses, [
+///     vec![("users.email".to_string(), ColumnAccess::Read)],
+///     vec![("*.ssn".to_string(), ColumnAccess::Write)],
+///     vec![],
+/// ]);
+/// ```
+pub fn find_sensitive_column_access(
+    dialect: &dyn Dialect,
+    sql: &str,
+    patterns: &[&str],
+) -> Result<Vec<Vec<SensitiveColumnAccess>>, Error> {
+    let patterns = patterns
+        .iter()
+        .map(|pattern| SensitiveColumnPattern::parse(pattern))
+        .collect::<Result<Vec<_>, _>>()?;
+    let finder = SensitiveColumnFinder::new(patterns);
+    let statements = instrument::parse_sql(dialect, sql)?;
+    Ok(statements
+        .iter()
+        .map(|statement| finder.find_from_statement(statement))
+        .collect())
+}
+
+/// [`SensitiveColumnPattern`] represents a configured sensitive column, e.g. `users.email` or `*.ssn`.
+/// Names are compared case-insensitively.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SensitiveColumnPattern {
+    pub catalog: Option<String>,
+    pub schema: Option<String>,
+    /// The table name, or `None` for any table.
+    pub table: Option<String>,
+    pub column: String,
+}
+
+impl SensitiveColumnPattern {
+    /// Parse a pattern of the form `[[catalog.]schema.]table.column`, where `table` may be `*` to match any table.
+    /// A bare `column` is the same as `*.column`.
+    ///
+    /// A table qualified by schema or catalog in the pattern matches a table in SQL with the same qualifiers,
+    /// or without qualifiers since it may resolve to the qualified one.
+    pub fn parse(pattern: &str) -> Result<Self, Error> {
+        let parts = pattern.split('.').map(str::trim).collect::<Vec<_>>();
+        let invalid =
+            || Error::ArgumentError(format!("Invalid sensitive column pattern: {pattern}"));
+        if parts.iter().any(|part| part.is_empty()) || parts.last() == Some(&"*") {
+            return Err(invalid());
+        }
+        let name = |part: &str| (part != "*").then(|| part.to_string());
+        let (catalog, schema, table, column) = match parts.as_slice() {
+            [column] => (None, None, None, column),
+            [table, column] => (None, None, name(table), column),
+            [schema, table, column] => (None, name(schema), name(table), column),
+            [catalog, schema, table, column] => (name(catalog), name(schema), name(table), column),
+            _ => return Err(invalid()),
+        };
+        Ok(Self {
+            catalog,
+            schema,
+            table,
+            column: column.to_string(),
+        })
+    }
+
+    fn matches_table(&self, table: &TableReference) -> bool {
+        let matches = |pattern: &Option<String>, ident: &Option<Ident>| match (pattern, ident) {
+            (Some(pattern), Some(ident)) => pattern.eq_ignore_ascii_case(&ident.value),
+            _ => true,
+        };
+        matches(&self.catalog, &table.catalog)
+            && matches(&self.schema, &table.schema)
+            && matches(&self.table, &Some(table.name.clone()))
+    }
+
+    fn matches_column(&self, column: &Ident) -> bool {
+        self.column.eq_ignore_ascii_case(&column.value)
+    }
+}
+
+impl fmt::Display for SensitiveColumnPattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(catalog) = &self.catalog {
+            write!(f, "{}.", catalog)?;
+        }
+        if self.catalog.is_some() || self.schema.is_some() {
+            write!(f, "{}.", self.schema.as_deref().unwrap_or("*"))?;
+        }
+        write!(
+            f,
+            "{}.{}",
+            self.table.as_deref().unwrap_or("*"),
+            self.column
+        )
+    }
+}
+
+/// [`ColumnAccess`] represents whether a column is read or written.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ColumnAccess {
+    /// Selected, or referenced in conditions, joins, grouping or ordering.
+    Read,
+    /// Inserted by INSERT, including INSERT without a column list, or assigned by UPDATE.
+    Write,
+}
+
+/// [`SensitiveColumnAccess`] represents a statement reading or writing a sensitive column.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SensitiveColumnAccess {
+    /// The pattern the column matches.
+    pub pattern: SensitiveColumnPattern,
+    /// The table of the column, without alias, or `None` if it cannot be determined.
+    pub table: Option<TableReference>,
+    pub column: Ident,
+    pub access: ColumnAccess,
+    /// Whether the access may not actually be to the sensitive column: when the column is selected by a wildcard
+    /// or inserted by INSERT without a column list, or when an unqualified column of a statement reading several tables cannot be attributed to a table.
+    pub ambiguous: bool,
+}
+
+/// A finder of access to sensitive columns in statements.
+///
+/// Columns are attributed to tables through aliases and table names, so aliased access such as
+/// `SELECT u.email AS contact FROM users AS u` is found. Columns of derived tables and CTEs are found
+/// where they are read from the underlying tables.
+#[derive(Clone, Debug, Default)]
+pub struct SensitiveColumnFinder {
+    patterns: Vec<SensitiveColumnPattern>,
+}
+
+impl SensitiveColumnFinder {
+    pub fn new(patterns: Vec<SensitiveColumnPattern>) -> Self {
+        Self { patterns }
+    }
+
+    /// Find access to sensitive columns in a statement, in order of appearance, each access reported once.
+    pub fn find_from_statement(&self, statement: &Statement) -> Vec<SensitiveColumnAccess> {
+        let StatementColumns {
+            references,
+            writes,
+            wildcard_tables,
+            wildcard_writes,
+            tables,
+            ..
+        } = column_usage::collect_columns(statement);
+        let columns = references
+            .into_iter()
+            .map(|reference| (reference.table, reference.column, ColumnAccess::Read))
+            .chain(
+                writes
+                    .into_iter()
+                    .map(|(table, column)| (table, column, ColumnAccess::Write)),
+            );
+        let mut accesses = vec![];
+        for (table, column, access) in columns {
+            for pattern in &self.patterns {
+                if !pattern.matches_column(&column) {
+                    continue;
+                }
+                let ambiguous = match &table {
+                    Some(table) if pattern.matches_table(table) => false,
+                    Some(_) => continue,
+                    None if pattern.table.is_none()
+                        || tables.iter().any(|table| pattern.matches_table(table)) =>
+                    {
+                        true
+                    }
+                    None => continue,
+                };
+                push_unique(
+                    &mut accesses,
+                    SensitiveColumnAccess {
+                        pattern: pattern.clone(),
+                        table: table.clone(),
+                        column: column.clone(),
+                        access,
+                        ambiguous,
+                    },
+                );
+            }
+        }
+        let wildcards = wildcard_tables
+            .into_iter()
+            .map(|table| (table, ColumnAccess::Read))
+            .chain(
+                wildcard_writes
+                    .into_iter()
+                    .map(|table| (table, ColumnAccess::Write)),
+            );
+        for (table, access) in wildcards {
+            let Some(table) = table else {
+                continue;
+            };
+            for pattern in &self.patterns {
+                if pattern.matches_table(&table) {
+                    push_unique(
+                        &mut accesses,
+                        SensitiveColumnAccess {
+                            pattern: pattern.clone(),
+                            table: Some(table.clone()),
+                            column: Ident::new(&pattern.column),
+                            access,
+                            ambiguous: true,
+                        },
+                    );
+                }
+            }
+        }
+        accesses
+    }
+}
+
+fn push_unique(accesses: &mut Vec<SensitiveColumnAccess>, access: SensitiveColumnAccess) {
+    if !accesses.contains(&access) {
+        accesses.push(access);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+
+    /// Pattern, table, column, access and whether the table is ambiguous.
+    type ExpectedAccess<'a> = (&'a str, Option<&'a str>, &'a str, ColumnAccess, bool);
+
+    fn assert_sensitive_column_access(
+        sql: &str,
+        patterns: &[&str],
+        expected: Vec<Vec<ExpectedAccess>>,
+        dialects: Vec<Box<dyn Dialect>>,
+    ) {
+        for dialect in dialects {
+            let result = find_sensitive_column_access(dialect.as_ref(), sql, patterns)
+                .unwrap()
+                .into_iter()
+                .map(|accesses| {
+                    accesses
+                        .into_iter()
+                        .map(|a| {
+                            (
+                                a.pattern.to_string(),
+                                a.table.map(|t| t.to_string()),
+                                a.column.value,
+                                a.access,
+                                a.ambiguous,
+                            )
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>();
+            let expected = expected
+                .iter()
+                .map(|accesses| {
+                    accesses
+                        .iter()
+                        .map(|(pattern, table, column, access, ambiguous)| {
+                            (
+                                pattern.to_string(),
+                                table.map(String::from),
+                                column.to_string(),
+                                *access,
+                                *ambiguous,
+                            )
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(result, expected, "Failed for dialect: {dialect:?}");
+        }
+    }
+
+    #[test]
+    fn test_aliased_and_nested_access() {
+        let sql = "SELECT x.EMAIL AS e FROM users AS x WHERE x.id IN (SELECT user_id FROM orders WHERE ssn = '1'); \
+            SELECT o.id FROM orders AS o JOIN users AS u ON o.email = u.email";
+        assert_sensitive_column_access(
+            sql,
+            &["users.email", "*.ssn"],
+            vec![
+                vec![
+                    (
+                        "users.email",
+                        Some("users"),
+                        "EMAIL",
+                        ColumnAccess::Read,
+                        false,
+                    ),
+                    ("*.ssn", Some("orders"), "ssn", ColumnAccess::Read, false),
+                ],
+                vec![(
+                    "users.email",
+                    Some("users"),
+                    "email",
+                    ColumnAccess::Read,
+                    false,
+                )],
+            ],
+            all_dialects(),
+        );
+    }
+
+    #[test]
+    fn test_writes() {
+        let sql = "INSERT INTO users (name, email) VALUES ('a', 'b'); \
+            UPDATE users AS u SET u.email = NULL WHERE email LIKE '%@example.com'; \
+            DELETE FROM users WHERE email = 'c'";
+        assert_sensitive_column_access(
+            sql,
+            &["users.email"],
+            vec![
+                vec![(
+                    "users.email",
+                    Some("users"),
+                    "email",
+                    ColumnAccess::Write,
+                    false,
+                )],
+                vec![
+                    (
+                        "users.email",
+                        Some("users"),
+                        "email",
+                        ColumnAccess::Read,
+                        false,
+                    ),
+                    (
+                        "users.email",
+                        Some("users"),
+                        "email",
+                        ColumnAccess::Write,
+                        false,
+                    ),
+                ],
+                vec![(
+                    "users.email",
+                    Some("users"),
+                    "email",
+                    ColumnAccess::Read,
+                    false,
+                )],
+            ],
+            all_dialects(),
+        );
+    }
+
+    #[test]
+    fn test_ambiguous_access() {
+        let sql = "SELECT * FROM users; \
+            SELECT email FROM users, orders; \
+            SELECT email FROM accounts, orders; \
+            SELECT o.* FROM users AS u, orders AS o";
+        assert_sensitive_column_access(
+            sql,
+            &["users.email"],
+            vec![
+                vec![(
+                    "users.email",
+                    Some("users"),
+                    "email",
+                    ColumnAccess::Read,
+                    true,
+                )],
+                vec![("users.email", None, "email", ColumnAccess::Read, true)],
+                vec![],
+                vec![],
+            ],
+            all_dialects(),
+        );
+    }
+
+    #[test]
+    fn test_wildcards_with_any_table_patterns() {
+        let sql = "SELECT * FROM customers; \
+            SELECT c.* FROM customers AS c; \
+            INSERT INTO users VALUES ('a', 'b', 'c')";
+        assert_sensitive_column_access(
+            sql,
+            &["*.ssn", "users.email"],
+            vec![
+                vec![("*.ssn", Some("customers"), "ssn", ColumnAccess::Read, true)],
+                vec![("*.ssn", Some("customers"), "ssn", ColumnAccess::Read, true)],
+                vec![
+                    ("*.ssn", Some("users"), "ssn", ColumnAccess::Write, true),
+                    (
+                        "users.email",
+                        Some("users"),
+                        "email",
+                        ColumnAccess::Write,
+                        true,
+                    ),
+                ],
+            ],
+            all_dialects(),
+        );
+    }
+
+    #[test]
+    fn test_qualified_patterns() {
+        let sql = "SELECT a.users.email FROM a.users; \
+            SELECT b.users.email FROM b.users; \
+            SELECT users.email FROM users";
+        assert_sensitive_column_access(
+            sql,
+            &["a.users.email"],
+            vec![
+                vec![(
+                    "a.users.email",
+                    Some("a.users"),
+                    "email",
+                    ColumnAccess::Read,
+                    false,
+                )],
+                vec![],
+                vec![(
+                    "a.users.email",
+                    Some("users"),
+                    "email",
+                    ColumnAccess::Read,
+                    false,
+                )],
+            ],
+            all_dialects(),
+        );
+    }
+
+    #[test]
+    fn test_parse_pattern() {
+        assert_eq!(
+            SensitiveColumnPattern::parse("ssn").unwrap(),
+            SensitiveColumnPattern {
+                catalog: None,
+                schema: None,
+                table: None,
+                column: "ssn".into(),
+            }
+        );
+        assert_eq!(
+            SensitiveColumnPattern::parse("c.*.users.email")
+                .unwrap()
+                .to_string(),
+            "c.*.users.email"
+        );
+        for pattern in ["users.*", "a..b", "", "a.b.c.d.e"] {
+            assert_eq!(
+                SensitiveColumnPattern::parse(pattern),
+                Err(Error::ArgumentError(format!(
+                    "Invalid sensitive column pattern: {pattern}"
+                )))
+            );
+        }
+    }
+}
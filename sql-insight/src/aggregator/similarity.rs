@@ -0,0 +1,324 @@
+//! Similarity of statements by their structure, and clustering of near-duplicate statements.
+//!
+//! See [`similarity`](crate::similarity()) for scoring a pair of statements,
+//! and [`cluster_similar_statements`](crate::cluster_similar_statements()) as the entry point for clustering SQL.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::ControlFlow;
+
+use crate::error::Error;
+use crate::helper;
+use crate::instrument;
+use sqlparser::ast::{
+    Expr, GroupByExpr, JoinOperator, ObjectName, Query, SetExpr, Statement, Visit, Visitor,
+};
+use sqlparser::dialect::Dialect;
+
+/// Score the similarity of two statements, from `0.0` for unrelated statements to `1.0` for statements
+/// of the same structure. See [`QueryShape`] for what the structure consists of.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+/// use sql_insight::sqlparser::parser::Parser;
+///
+/// let dialect = GenericDialect {};
+/// let a = &Parser::parse_sql(&dialect, "SELECT a, b FROM t1 WHERE c = 1").unwrap()[0];
+/// let b = &Parser::parse_sql(&dialect, "SELECT d FROM t1 WHERE e > 2 AND f < 3").unwrap()[0];
+/// let c = &Parser::parse_sql(&dialect, "SELECT a FROM t2 GROUP BY a").unwrap()[0];
+/// assert_eq!(sql_insight::similarity(a, b), 1.0);
+/// assert!(sql_insight::similarity(a, c) < 0.5);
+/// ```
+pub fn similarity(a: &Statement, b: &Statement) -> f64 {
+    QueryShape::from_statement(a).similarity(&QueryShape::from_statement(b))
+}
+
+/// Convenience function to cluster statements of SQL whose similarity to the first statement of
+/// the cluster is at least `threshold`, which must be between `0.0` and `1.0`.
+/// Clusters are lists of statement indices, in order of their first statements.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+///
+/// let dialect = GenericDialect {};
+/// let sql = "SELECT a FROM t1 WHERE b = 1; \
+///     SELECT x FROM t2 GROUP BY x; \
+///     SELECT a, c FROM t1 WHERE d IN (1, 2)";
+/// let clusters = sql_insight::cluster_similar_statements(&dialect, sql, 0.9).unwrap();
+/// assert_eq!(clusters, [vec![0, 2], vec![1]]);
+/// ```
+pub fn cluster_similar_statements(
+    dialect: &dyn Dialect,
+    sql: &str,
+    threshold: f64,
+) -> Result<Vec<Vec<usize>>, Error> {
+    if !(0.0..=1.0).contains(&threshold) {
+        return Err(Error::ArgumentError(format!(
+            "Threshold must be between 0.0 and 1.0, got {threshold}"
+        )));
+    }
+    let statements = instrument::parse_sql(dialect, sql)?;
+    Ok(cluster_statements(&statements, threshold))
+}
+
+/// Cluster statements whose similarity to the first statement of the cluster is at least `threshold`.
+/// Each statement joins the first cluster it is similar enough to, or starts a new cluster.
+pub fn cluster_statements(statements: &[Statement], threshold: f64) -> Vec<Vec<usize>> {
+    let mut clusters: Vec<(QueryShape, Vec<usize>)> = vec![];
+    for (index, statement) in statements.iter().enumerate() {
+        let shape = QueryShape::from_statement(statement);
+        match clusters
+            .iter_mut()
+            .find(|(leader, _)| leader.similarity(&shape) >= threshold)
+        {
+            Some((_, members)) => members.push(index),
+            None => clusters.push((shape, vec![index])),
+        }
+    }
+    clusters.into_iter().map(|(_, members)| members).collect()
+}
+
+/// [`QueryShape`] represents the structure of a statement: its kind, the tables it references,
+/// and the clauses, joins, set operations and subqueries its queries consist of.
+/// Selected columns, predicates and literal values are not part of the shape,
+/// so that statements differing only in them have the same shape.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct QueryShape {
+    features: BTreeMap<String, usize>,
+}
+
+impl QueryShape {
+    pub fn from_statement(statement: &Statement) -> Self {
+        let mut visitor = QueryShapeVisitor::default();
+        let _ = statement.visit(&mut visitor);
+        visitor.shape
+    }
+
+    /// Weighted Jaccard similarity of the features of the shapes.
+    pub fn similarity(&self, other: &QueryShape) -> f64 {
+        let features = self
+            .features
+            .keys()
+            .chain(other.features.keys())
+            .collect::<BTreeSet<_>>();
+        let (mut intersection, mut union) = (0, 0);
+        for feature in features {
+            let (a, b) = (
+                self.features.get(feature).copied().unwrap_or_default(),
+                other.features.get(feature).copied().unwrap_or_default(),
+            );
+            intersection += a.min(b);
+            union += a.max(b);
+        }
+        if union == 0 {
+            return 1.0;
+        }
+        intersection as f64 / union as f64
+    }
+
+    fn add(&mut self, feature: impl Into<String>) {
+        *self.features.entry(feature.into()).or_default() += 1;
+    }
+}
+
+#[derive(Default)]
+struct QueryShapeVisitor {
+    shape: QueryShape,
+    depth: usize,
+}
+
+impl Visitor for QueryShapeVisitor {
+    type Break = ();
+
+    fn pre_visit_statement(&mut self, statement: &Statement) -> ControlFlow<Self::Break> {
+        let kind = match statement {
+            Statement::Query(_) => "query",
+            Statement::Insert { .. } => "insert",
+            Statement::Update { selection, .. } => {
+                self.add_where(selection);
+                "update"
+            }
+            Statement::Delete { selection, .. } => {
+                self.add_where(selection);
+                "delete"
+            }
+            Statement::Merge { .. } => "merge",
+            _ => "other",
+        };
+        self.shape.add(format!("statement:{kind}"));
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_relation(&mut self, relation: &ObjectName) -> ControlFlow<Self::Break> {
+        self.shape.add(format!("table:{relation}"));
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_query(&mut self, query: &Query) -> ControlFlow<Self::Break> {
+        if self.depth > 0 {
+            self.shape.add("subquery");
+        }
+        self.depth += 1;
+        if let Some(with) = &query.with {
+            for _ in &with.cte_tables {
+                self.shape.add("cte");
+            }
+        }
+        if !query.order_by.is_empty() {
+            self.shape.add("order_by");
+        }
+        if query.limit.is_some() || query.fetch.is_some() {
+            self.shape.add("limit");
+        }
+        if query.offset.is_some() {
+            self.shape.add("offset");
+        }
+        self.add_set_operations(&query.body);
+        let mut selects = vec![];
+        helper::collect_selects(&query.body, &mut selects);
+        for select in selects {
+            self.shape.add("select");
+            if select.distinct.is_some() {
+                self.shape.add("distinct");
+            }
+            if select.top.is_some() {
+                self.shape.add("limit");
+            }
+            for table_with_joins in &select.from {
+                self.shape.add("from");
+                for join in &table_with_joins.joins {
+                    self.shape
+                        .add(format!("join:{}", join_kind(&join.join_operator)));
+                }
+            }
+            self.add_where(&select.selection);
+            if !matches!(&select.group_by, GroupByExpr::Expressions(exprs) if exprs.is_empty()) {
+                self.shape.add("group_by");
+            }
+            if select.having.is_some() {
+                self.shape.add("having");
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn post_visit_query(&mut self, _query: &Query) -> ControlFlow<Self::Break> {
+        self.depth -= 1;
+        ControlFlow::Continue(())
+    }
+}
+
+impl QueryShapeVisitor {
+    fn add_where(&mut self, selection: &Option<Expr>) {
+        if selection.is_some() {
+            self.shape.add("where");
+        }
+    }
+
+    fn add_set_operations(&mut self, set_expr: &SetExpr) {
+        if let SetExpr::SetOperation {
+            op, left, right, ..
+        } = set_expr
+        {
+            self.shape.add(format!("set:{op}"));
+            self.add_set_operations(left);
+            self.add_set_operations(right);
+        }
+    }
+}
+
+fn join_kind(join_operator: &JoinOperator) -> &'static str {
+    match join_operator {
+        JoinOperator::Inner(_) => "inner",
+        JoinOperator::LeftOuter(_) => "left",
+        JoinOperator::RightOuter(_) => "right",
+        JoinOperator::FullOuter(_) => "full",
+        JoinOperator::CrossJoin => "cross",
+        JoinOperator::LeftSemi(_) | JoinOperator::RightSemi(_) => "semi",
+        JoinOperator::LeftAnti(_) | JoinOperator::RightAnti(_) => "anti",
+        JoinOperator::CrossApply | JoinOperator::OuterApply => "apply",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+    use sqlparser::parser::Parser;
+
+    fn assert_similarity(a: &str, b: &str, expected: f64, dialects: Vec<Box<dyn Dialect>>) {
+        for dialect in dialects {
+            let a = Parser::parse_sql(dialect.as_ref(), a).unwrap().remove(0);
+            let b = Parser::parse_sql(dialect.as_ref(), b).unwrap().remove(0);
+            assert_eq!(
+                similarity(&a, &b),
+                expected,
+                "Failed for dialect: {dialect:?}"
+            );
+            assert_eq!(
+                similarity(&b, &a),
+                expected,
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_same_shape() {
+        assert_similarity(
+            "SELECT a FROM t1 AS x JOIN t2 ON x.id = t2.id WHERE x.b = 1 ORDER BY a",
+            "SELECT c, d FROM t1 JOIN t2 ON t1.k = t2.k WHERE t2.e IN (1, 2) AND t1.f > 0 ORDER BY c DESC",
+            1.0,
+            all_dialects(),
+        );
+    }
+
+    #[test]
+    fn test_different_shape() {
+        // statement:query, select, from, table:t1 are shared; where and group_by are not.
+        assert_similarity(
+            "SELECT a FROM t1 WHERE b = 1",
+            "SELECT a FROM t1 GROUP BY a",
+            4.0 / 6.0,
+            all_dialects(),
+        );
+        assert_similarity("SELECT a FROM t1", "DELETE FROM t2", 0.0, all_dialects());
+    }
+
+    #[test]
+    fn test_cluster_similar_statements() {
+        let sql = "SELECT a FROM t1 WHERE b = 1; \
+            SELECT a FROM t1 UNION SELECT a FROM t2; \
+            SELECT c FROM t1 WHERE d = 'x'; \
+            UPDATE t1 SET a = 1 WHERE b = 2; \
+            SELECT b FROM t2 UNION SELECT c FROM t1; \
+            UPDATE t1 SET c = 3 WHERE d = 4";
+        for dialect in all_dialects() {
+            let clusters = cluster_similar_statements(dialect.as_ref(), sql, 1.0).unwrap();
+            assert_eq!(
+                clusters,
+                [vec![0, 2], vec![1, 4], vec![3, 5]],
+                "Failed for dialect: {dialect:?}"
+            );
+            let clusters = cluster_similar_statements(dialect.as_ref(), sql, 0.0).unwrap();
+            assert_eq!(
+                clusters,
+                [vec![0, 1, 2, 3, 4, 5]],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_cluster_similar_statements_with_invalid_threshold() {
+        assert_eq!(
+            cluster_similar_statements(&sqlparser::dialect::GenericDialect {}, "SELECT 1", 1.5),
+            Err(Error::ArgumentError(
+                "Threshold must be between 0.0 and 1.0, got 1.5".into()
+            ))
+        );
+    }
+}
@@ -0,0 +1,239 @@
+//! An audit of which statements of a workload read or write a given table.
+//!
+//! See [`find_statements_touching`](crate::find_statements_touching()) as the entry point for auditing SQL,
+//! or [`statements_touching`](crate::statements_touching()) for auditing parsed statements.
+
+use crate::error::Error;
+use crate::extractor::crud_table_extractor::CrudTableExtractor;
+use crate::extractor::table_extractor::TableReference;
+use crate::instrument;
+use sqlparser::ast::{Ident, Statement};
+use sqlparser::dialect::Dialect;
+use sqlparser::parser::Parser;
+
+/// Convenience function to find statements of SQL reading or writing `table`,
+/// where `table` is a possibly qualified table name in the syntax of the dialect, e.g. `app.users`.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+/// use sql_insight::CrudOperation;
+///
+/// let dialect = GenericDialect {};
+/// let sql = "SELECT * FROM Users; \
+///     SELECT * FROM orders; \
+///     INSERT INTO archive SELECT * FROM app.users AS u; \
+///     DELETE FROM other.users";
+/// let result = sql_insight::find_statements_touching(&dialect, sql, "app.users").unwrap();
+/// let accesses: Vec<_> = result.iter().map(|a| (a.statement, a.operations.clone())).collect();
+/// assert_eq!(accesses, [
+///     (0, vec![CrudOperation::Read]),
+///     (2, vec![CrudOperation::Read]),
+/// ]);
+/// ```
+pub fn find_statements_touching(
+    dialect: &dyn Dialect,
+    sql: &str,
+    table: &str,
+) -> Result<Vec<TableAccess>, Error> {
+    let name = Parser::new(dialect)
+        .try_with_sql(table)?
+        .parse_object_name(false)?;
+    let table = TableReference::try_from(&name)?;
+    let statements = instrument::parse_sql(dialect, sql)?;
+    statements_touching(&table, &statements)
+}
+
+/// Find statements reading or writing `table`, returning their indices and the operations on the table,
+/// or the first error of statements whose tables cannot be extracted.
+///
+/// Identifiers are compared case-insensitively unless either of them is quoted.
+/// Qualifiers are compared only when both `table` and the table in a statement have them,
+/// since an unqualified table may resolve to a qualified one through the search path, so `app.users` matches
+/// `users` but not `other.users`, and `users` matches all of them. Aliases are ignored.
+pub fn statements_touching(
+    table: &TableReference,
+    statements: &[Statement],
+) -> Result<Vec<TableAccess>, Error> {
+    let mut accesses = vec![];
+    for (index, statement) in statements.iter().enumerate() {
+        let crud_tables = CrudTableExtractor::extract_from_statement(statement)?;
+        let ddl_tables = crud_tables.ddl.tables();
+        let operations = [
+            (CrudOperation::Create, &crud_tables.create_tables),
+            (CrudOperation::Read, &crud_tables.read_tables),
+            (CrudOperation::Update, &crud_tables.update_tables),
+            (CrudOperation::Delete, &crud_tables.delete_tables),
+            (CrudOperation::Ddl, &ddl_tables),
+        ]
+        .into_iter()
+        .filter(|(_, tables)| tables.iter().any(|t| matches_table(table, t)))
+        .map(|(operation, _)| operation)
+        .collect::<Vec<_>>();
+        if !operations.is_empty() {
+            accesses.push(TableAccess {
+                statement: index,
+                operations,
+            });
+        }
+    }
+    Ok(accesses)
+}
+
+/// [`CrudOperation`] represents an operation of a statement on a table.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum CrudOperation {
+    Create,
+    Read,
+    Update,
+    Delete,
+    /// Creating, altering, dropping or truncating the table.
+    Ddl,
+}
+
+/// [`TableAccess`] represents a statement reading or writing a table.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TableAccess {
+    /// Index of the statement.
+    pub statement: usize,
+    /// Operations of the statement on the table, in the order of create, read, update, delete and DDL.
+    pub operations: Vec<CrudOperation>,
+}
+
+fn matches_table(target: &TableReference, table: &TableReference) -> bool {
+    let matches_qualifier = |a: &Option<Ident>, b: &Option<Ident>| match (a, b) {
+        (Some(a), Some(b)) => matches_ident(a, b),
+        _ => true,
+    };
+    matches_ident(&target.name, &table.name)
+        && matches_qualifier(&target.schema, &table.schema)
+        && matches_qualifier(&target.catalog, &table.catalog)
+}
+
+fn matches_ident(a: &Ident, b: &Ident) -> bool {
+    match (a.quote_style, b.quote_style) {
+        (None, None) => a.value.eq_ignore_ascii_case(&b.value),
+        _ => a.value == b.value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+
+    fn assert_statements_touching(
+        sql: &str,
+        table: &str,
+        expected: Vec<(usize, Vec<CrudOperation>)>,
+        dialects: Vec<Box<dyn Dialect>>,
+    ) {
+        for dialect in dialects {
+            let result = find_statements_touching(dialect.as_ref(), sql, table)
+                .unwrap()
+                .into_iter()
+                .map(|access| (access.statement, access.operations))
+                .collect::<Vec<_>>();
+            assert_eq!(result, expected, "Failed for dialect: {dialect:?}");
+        }
+    }
+
+    #[test]
+    fn test_crud_operations() {
+        let sql = "INSERT INTO t1 (a) SELECT a FROM t1 AS x; \
+            UPDATE t2 SET a = 1 WHERE b IN (SELECT b FROM T1); \
+            DELETE FROM t1 WHERE a = 1; \
+            SELECT a FROM t2";
+        assert_statements_touching(
+            sql,
+            "t1",
+            vec![
+                (0, vec![CrudOperation::Create, CrudOperation::Read]),
+                (1, vec![CrudOperation::Read]),
+                (2, vec![CrudOperation::Delete]),
+            ],
+            all_dialects(),
+        );
+    }
+
+    #[test]
+    fn test_ddl() {
+        let sql = "CREATE TABLE app.users (id INT); \
+            ALTER TABLE app.users ADD COLUMN name TEXT; \
+            SELECT * FROM users; \
+            TRUNCATE TABLE app.users; \
+            DROP TABLE app.users";
+        assert_statements_touching(
+            sql,
+            "app.users",
+            vec![
+                (0, vec![CrudOperation::Ddl]),
+                (1, vec![CrudOperation::Ddl]),
+                (2, vec![CrudOperation::Read]),
+                (3, vec![CrudOperation::Ddl]),
+                (4, vec![CrudOperation::Ddl]),
+            ],
+            vec![
+                Box::new(sqlparser::dialect::PostgreSqlDialect {}),
+                Box::new(sqlparser::dialect::GenericDialect {}),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_qualifiers() {
+        let sql =
+            "SELECT a FROM s1.t1; SELECT a FROM s2.t1; SELECT a FROM t1; SELECT a FROM c1.s1.t1";
+        assert_statements_touching(
+            sql,
+            "s1.t1",
+            vec![
+                (0, vec![CrudOperation::Read]),
+                (2, vec![CrudOperation::Read]),
+                (3, vec![CrudOperation::Read]),
+            ],
+            all_dialects(),
+        );
+        assert_statements_touching(
+            sql,
+            "t1",
+            vec![
+                (0, vec![CrudOperation::Read]),
+                (1, vec![CrudOperation::Read]),
+                (2, vec![CrudOperation::Read]),
+                (3, vec![CrudOperation::Read]),
+            ],
+            all_dialects(),
+        );
+    }
+
+    #[test]
+    fn test_quoted_identifiers() {
+        let table = |name: &str, quote_style| TableReference {
+            catalog: None,
+            schema: None,
+            name: Ident {
+                value: name.into(),
+                quote_style,
+            },
+            alias: None,
+        };
+        let dialect = sqlparser::dialect::PostgreSqlDialect {};
+        let statements =
+            Parser::parse_sql(&dialect, "SELECT a FROM Users; SELECT a FROM \"Users\"").unwrap();
+        let touching = |target| {
+            statements_touching(&target, &statements)
+                .unwrap()
+                .into_iter()
+                .map(|access| access.statement)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(touching(table("users", None)), [0]);
+        assert_eq!(touching(table("Users", Some('"'))), [0, 1]);
+        assert_eq!(touching(table("users", Some('"'))), Vec::<usize>::new());
+    }
+}
@@ -0,0 +1,239 @@
+//! An Analyzer that parses SQL once and runs several analyses on the same statements.
+//!
+//! See [`Analyzer`] for details, or [`analyze_all`](crate::analyze_all()) as the entry point for running
+//! every analysis at once.
+
+use crate::error::Error;
+use crate::extractor::crud_table_extractor::{CrudTableExtractor, CrudTables};
+use crate::extractor::table_extractor::{TableExtractor, Tables};
+use crate::formatter::{Formatter, FormatterOptions};
+use crate::instrument;
+use crate::normalizer::{Normalizer, NormalizerOptions};
+use sqlparser::ast::{Statement, VisitMut};
+use sqlparser::dialect::Dialect;
+
+/// Convenience function to format, normalize and extract tables and CRUD tables from SQL with default options,
+/// parsing the SQL only once.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+///
+/// let dialect = GenericDialect {};
+/// let result = sql_insight::analyze_all(&dialect, "select a from t1 where b = 1").unwrap();
+/// assert_eq!(result[0].formatted, "SELECT a FROM t1 WHERE b = 1");
+/// assert_eq!(result[0].normalized, "SELECT a FROM t1 WHERE b = ?");
+/// assert_eq!(result[0].tables.as_ref().unwrap().to_string(), "t1");
+/// assert_eq!(result[0].crud_tables.as_ref().unwrap().to_string(), "Create: [], Read: [t1], Update: [], Delete: []");
+/// ```
+pub fn analyze_all(dialect: &dyn Dialect, sql: &str) -> Result<Vec<StatementAnalysis>, Error> {
+    Ok(Analyzer::new(dialect, sql)?.analyze_all())
+}
+
+/// [`StatementAnalysis`] represents the results of every analysis of a statement.
+#[derive(Debug, PartialEq)]
+pub struct StatementAnalysis {
+    pub formatted: String,
+    pub normalized: String,
+    pub tables: Result<Tables, Error>,
+    pub crud_tables: Result<CrudTables, Error>,
+}
+
+/// [`Analyzer`] parses SQL once and runs analyses on the parsed statements, instead of each entry point
+/// such as [`format`](crate::format()) and [`extract_tables`](crate::extract_tables()) parsing the SQL again.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+/// use sql_insight::{Analyzer, NormalizerOptions};
+///
+/// let dialect = GenericDialect {};
+/// let analyzer = Analyzer::new(&dialect, "INSERT INTO t1 (a) SELECT a FROM t2 WHERE b IN (1, 2)").unwrap();
+/// let normalized = analyzer.normalize(NormalizerOptions::new().with_unify_in_list(true));
+/// assert_eq!(normalized, ["INSERT INTO t1 (a) SELECT a FROM t2 WHERE b IN (...)"]);
+/// let tables = analyzer.extract_tables();
+/// assert_eq!(tables[0].as_ref().unwrap().to_string(), "t1, t2");
+/// ```
+pub struct Analyzer<'a> {
+    dialect: &'a dyn Dialect,
+    sql: &'a str,
+    statements: Vec<Statement>,
+}
+
+impl<'a> Analyzer<'a> {
+    /// Parse SQL. Fails if the SQL fails to parse, like the other entry points.
+    pub fn new(dialect: &'a dyn Dialect, sql: &'a str) -> Result<Self, Error> {
+        Ok(Self {
+            dialect,
+            sql,
+            statements: instrument::parse_sql(dialect, sql)?,
+        })
+    }
+
+    /// The parsed statements, for analyses not offered by the analyzer.
+    pub fn statements(&self) -> &[Statement] {
+        &self.statements
+    }
+
+    /// Format the statements. See [`format`](crate::format()).
+    pub fn format(&self) -> Vec<String> {
+        self.statements
+            .iter()
+            .map(|statement| instrument::analyze("format", || statement.to_string()))
+            .collect()
+    }
+
+    /// Format the statements with options. See [`format_with_options`](crate::format_with_options()).
+    pub fn format_with_options(&self, options: FormatterOptions) -> Vec<String> {
+        Formatter::format_statements(self.dialect, self.sql, &self.statements, &options)
+    }
+
+    /// Normalize the statements with options. See [`normalize_with_options`](crate::normalize_with_options()).
+    /// The parsed statements are left intact.
+    pub fn normalize(&self, options: NormalizerOptions) -> Vec<String> {
+        let mut normalizer = Normalizer::new().with_options(options);
+        self.statements
+            .iter()
+            .map(|statement| {
+                instrument::analyze("normalize", || {
+                    let mut statement = statement.clone();
+                    let _ = statement.visit(&mut normalizer);
+                    statement.to_string()
+                })
+            })
+            .collect()
+    }
+
+    /// Extract tables from the statements. See [`extract_tables`](crate::extract_tables()).
+    pub fn extract_tables(&self) -> Vec<Result<Tables, Error>> {
+        self.statements
+            .iter()
+            .map(TableExtractor::extract_from_statement)
+            .collect()
+    }
+
+    /// Extract CRUD tables from the statements. See [`extract_crud_tables`](crate::extract_crud_tables()).
+    pub fn extract_crud_tables(&self) -> Vec<Result<CrudTables, Error>> {
+        self.statements
+            .iter()
+            .map(CrudTableExtractor::extract_from_statement)
+            .collect()
+    }
+
+    /// Run every analysis with default options, combining the results of each statement.
+    pub fn analyze_all(&self) -> Vec<StatementAnalysis> {
+        let formatted = self.format();
+        let normalized = self.normalize(NormalizerOptions::new());
+        let tables = self.extract_tables();
+        let crud_tables = self.extract_crud_tables();
+        formatted
+            .into_iter()
+            .zip(normalized)
+            .zip(tables)
+            .zip(crud_tables)
+            .map(
+                |(((formatted, normalized), tables), crud_tables)| StatementAnalysis {
+                    formatted,
+                    normalized,
+                    tables,
+                    crud_tables,
+                },
+            )
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+    use crate::KeywordCase;
+
+    #[test]
+    fn test_same_results_as_entry_points() {
+        let sql = "select a from t1 where b = 1; insert into t2 (a) select a from t3 as x; delete from t4 where c in (2, 3)";
+        for dialect in all_dialects() {
+            let dialect = dialect.as_ref();
+            let analyzer = Analyzer::new(dialect, sql).unwrap();
+            assert_eq!(
+                analyzer.format(),
+                crate::format(dialect, sql).unwrap(),
+                "Failed for dialect: {dialect:?}"
+            );
+            let options = FormatterOptions::new()
+                .with_multi_line(true)
+                .with_keyword_case(KeywordCase::Preserve);
+            assert_eq!(
+                analyzer.format_with_options(options.clone()),
+                crate::format_with_options(dialect, sql, options).unwrap(),
+                "Failed for dialect: {dialect:?}"
+            );
+            let options = NormalizerOptions::new().with_unify_in_list(true);
+            assert_eq!(
+                analyzer.normalize(options.clone()),
+                crate::normalize_with_options(dialect, sql, options).unwrap(),
+                "Failed for dialect: {dialect:?}"
+            );
+            assert_eq!(
+                analyzer.extract_tables(),
+                crate::extract_tables(dialect, sql).unwrap(),
+                "Failed for dialect: {dialect:?}"
+            );
+            assert_eq!(
+                analyzer.extract_crud_tables(),
+                crate::extract_crud_tables(dialect, sql).unwrap(),
+                "Failed for dialect: {dialect:?}"
+            );
+            // Normalizing does not alter the parsed statements.
+            assert_eq!(
+                analyzer.format(),
+                crate::format(dialect, sql).unwrap(),
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_analyze_all() {
+        for dialect in all_dialects() {
+            let result = analyze_all(
+                dialect.as_ref(),
+                "UPDATE t1 SET a = 1 WHERE b = 2; SELECT a FROM a.b.c.d",
+            )
+            .unwrap();
+            assert_eq!(result.len(), 2, "Failed for dialect: {dialect:?}");
+            assert_eq!(
+                result[0].normalized, "UPDATE t1 SET a = ? WHERE b = ?",
+                "Failed for dialect: {dialect:?}"
+            );
+            assert_eq!(
+                result[0].crud_tables.as_ref().unwrap().update_tables[0].to_string(),
+                "t1",
+                "Failed for dialect: {dialect:?}"
+            );
+            assert_eq!(
+                result[1].formatted, "SELECT a FROM a.b.c.d",
+                "Failed for dialect: {dialect:?}"
+            );
+            assert_eq!(
+                result[1].tables,
+                Err(Error::AnalysisError(
+                    "Too many identifiers provided".to_string()
+                )),
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_error() {
+        for dialect in all_dialects() {
+            assert!(
+                Analyzer::new(dialect.as_ref(), "SELECT * FROM (").is_err(),
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,528 @@
+//! An Anonymizer that replaces literal values of SQL queries with fake values or hashes, keeping queries valid.
+//!
+//! Unlike the [`normalizer`](crate::normalizer), literals remain literals of the same kind,
+//! so anonymized queries can still be run and shared, e.g. with vendors, without leaking personal data.
+//!
+//! See [`anonymize`](crate::anonymize()) as the entry point for anonymizing SQL.
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::ops::ControlFlow;
+
+use crate::error::Error;
+use crate::instrument;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use sqlparser::ast::{DataType, Expr, Statement, Value, VisitMut, VisitorMut};
+use sqlparser::dialect::Dialect;
+
+/// Convenience function to anonymize SQL with default options.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+///
+/// let dialect = GenericDialect {};
+/// let sql = "SELECT a FROM users WHERE email = 'alice@example.com' OR email = 'alice@example.com'";
+/// let result = sql_insight::anonymize(&dialect, sql).unwrap();
+/// assert_ne!(result[0], sql);
+/// // The same values are replaced with the same fake values of the same shape.
+/// let fake = result[0].split('\'').nth(1).unwrap();
+/// assert_eq!(result[0].matches(fake).count(), 2);
+/// assert_eq!(fake.len(), "alice@example.com".len());
+/// assert_eq!(fake.find('@'), Some(5));
+/// ```
+pub fn anonymize(dialect: &dyn Dialect, sql: &str) -> Result<Vec<String>, Error> {
+    Anonymizer::anonymize(dialect, sql, AnonymizerOptions::new())
+}
+
+/// Convenience function to anonymize SQL with options.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+/// use sql_insight::{AnonymizerOptions, Redaction};
+///
+/// let dialect = GenericDialect {};
+/// let sql = "SELECT a FROM users WHERE name = 'alice' LIMIT 10";
+/// let options = AnonymizerOptions::new()
+///     .with_strings(Redaction::Hash)
+///     .with_numbers(Redaction::Keep)
+///     .with_salt("secret");
+/// let result = sql_insight::anonymize_with_options(&dialect, sql, options).unwrap();
+/// assert_eq!(result, ["SELECT a FROM users WHERE name = '4360c67bc8102511' LIMIT 10"]);
+/// ```
+pub fn anonymize_with_options(
+    dialect: &dyn Dialect,
+    sql: &str,
+    options: AnonymizerOptions,
+) -> Result<Vec<String>, Error> {
+    Anonymizer::anonymize(dialect, sql, options)
+}
+
+/// [`Redaction`] represents how literal values of a type are replaced.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Redaction {
+    /// Keep values as they are.
+    Keep,
+    /// Replace values with fake values of the same shape: each letter with a letter of the same case and each digit
+    /// with a digit, keeping other characters such as `@`, `-` and spaces. Numbers keep their sign, decimal point,
+    /// exponent and whether the first digit is zero.
+    #[default]
+    Fake,
+    /// Replace strings with 16 hex characters of their hash, and numbers with their hash as an integer.
+    Hash,
+}
+
+/// Options for anonymizing SQL.
+///
+/// Replacements are deterministic, so the same value is replaced with the same value within and across statements,
+/// keeping equalities and joins on literal values intact. Replacements are derived from an HMAC-SHA256 of the value
+/// keyed by the salt. A known salt lets anyone guess values by anonymizing candidates, e.g. phone numbers or IDs,
+/// so [`new`](Self::new) generates a random salt. Set a secret salt with [`with_salt`](Self::with_salt)
+/// to replace values the same way across runs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AnonymizerOptions {
+    /// Redaction of string literals, including national, escaped, raw, byte, hex and dollar-quoted strings,
+    /// and typed strings. Dates, times and timestamps such as `DATE '2024-01-01'` are replaced with fake values
+    /// valid for their type unless kept, as hashes would not be.
+    pub strings: Redaction,
+    /// Redaction of numeric literals.
+    pub numbers: Redaction,
+    /// Secret key of the HMAC deriving fake values and hashes. Random unless set.
+    pub salt: String,
+}
+
+impl Default for AnonymizerOptions {
+    fn default() -> Self {
+        Self {
+            strings: Redaction::default(),
+            numbers: Redaction::default(),
+            salt: random_salt(),
+        }
+    }
+}
+
+impl AnonymizerOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_strings(mut self, strings: Redaction) -> Self {
+        self.strings = strings;
+        self
+    }
+
+    pub fn with_numbers(mut self, numbers: Redaction) -> Self {
+        self.numbers = numbers;
+        self
+    }
+
+    pub fn with_salt(mut self, salt: impl Into<String>) -> Self {
+        self.salt = salt.into();
+        self
+    }
+}
+
+/// 32 hex characters from the randomly keyed hashers of the standard library, seeded by the operating system.
+fn random_salt() -> String {
+    (0..2)
+        .map(|_| format!("{:016x}", RandomState::new().build_hasher().finish()))
+        .collect()
+}
+
+/// A visitor for SQL AST nodes that anonymizes literal values.
+#[derive(Default)]
+pub struct Anonymizer {
+    pub options: AnonymizerOptions,
+}
+
+impl VisitorMut for Anonymizer {
+    type Break = ();
+
+    fn pre_visit_expr(&mut self, expr: &mut Expr) -> ControlFlow<Self::Break> {
+        match expr {
+            Expr::Value(value) => self.anonymize_value(value),
+            Expr::TypedString { data_type, value } => {
+                let replaced = match data_type {
+                    DataType::Date | DataType::Datetime(_) | DataType::Timestamp(..) => {
+                        self.replace_temporal(value, 0)
+                    }
+                    // Time values start from the hour field.
+                    DataType::Time(..) => self.replace_temporal(value, 3),
+                    _ => self.replace_string(value, false),
+                };
+                if let Some(replaced) = replaced {
+                    *value = replaced;
+                }
+            }
+            _ => {}
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+impl Anonymizer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_options(mut self, options: AnonymizerOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Anonymize SQL.
+    pub fn anonymize(
+        dialect: &dyn Dialect,
+        sql: &str,
+        options: AnonymizerOptions,
+    ) -> Result<Vec<String>, Error> {
+        let mut statements = instrument::parse_sql(dialect, sql)?;
+        let mut anonymizer = Self::new().with_options(options);
+        Ok(statements
+            .iter_mut()
+            .map(|statement| anonymizer.anonymize_statement(statement))
+            .collect())
+    }
+
+    /// Anonymize a statement in place, returning its SQL.
+    pub fn anonymize_statement(&mut self, statement: &mut Statement) -> String {
+        instrument::analyze("anonymize", || {
+            let _ = statement.visit(self);
+            statement.to_string()
+        })
+    }
+
+    fn anonymize_value(&self, value: &mut Value) {
+        match value {
+            Value::Number(number, _) => {
+                if let Some(replaced) = self.replace_number(number) {
+                    *number = replaced;
+                }
+            }
+            Value::HexStringLiteral(hex) => {
+                if let Some(replaced) = self.replace_string(hex, true) {
+                    *hex = replaced;
+                }
+            }
+            Value::SingleQuotedString(s)
+            | Value::DoubleQuotedString(s)
+            | Value::EscapedStringLiteral(s)
+            | Value::NationalStringLiteral(s)
+            | Value::RawStringLiteral(s)
+            | Value::SingleQuotedByteStringLiteral(s)
+            | Value::DoubleQuotedByteStringLiteral(s) => {
+                if let Some(replaced) = self.replace_string(s, false) {
+                    *s = replaced;
+                }
+            }
+            Value::DollarQuotedString(s) => {
+                if let Some(replaced) = self.replace_string(&s.value, false) {
+                    s.value = replaced;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn replace_string(&self, s: &str, hex: bool) -> Option<String> {
+        let seed = self.hash(s);
+        match self.options.strings {
+            Redaction::Keep => None,
+            Redaction::Hash => Some(format!("{:016x}", seed)),
+            Redaction::Fake if hex => {
+                let mut rng = Rng(seed);
+                Some(s.chars().map(|_| rng.pick("0123456789ABCDEF")).collect())
+            }
+            Redaction::Fake => {
+                let mut rng = Rng(seed);
+                Some(
+                    s.chars()
+                        .map(|c| match c {
+                            '0'..='9' => rng.pick("0123456789"),
+                            'A'..='Z' => rng.pick("ABCDEFGHIJKLMNOPQRSTUVWXYZ"),
+                            // Non-ASCII letters are replaced with ASCII ones, not to leak their script.
+                            c if c.is_alphabetic() => rng.pick("abcdefghijklmnopqrstuvwxyz"),
+                            c => c,
+                        })
+                        .collect(),
+                )
+            }
+        }
+    }
+
+    /// Replace each run of digits of a date, time or timestamp with a value in the range of its field,
+    /// taking the runs as year, month, day, hour, minute, second and fraction from `first_field`.
+    /// Runs after the fraction, such as time zone offsets, are kept.
+    fn replace_temporal(&self, value: &str, first_field: usize) -> Option<String> {
+        if self.options.strings == Redaction::Keep {
+            return None;
+        }
+        let mut rng = Rng(self.hash(value));
+        let mut replaced = String::with_capacity(value.len());
+        let mut field = first_field;
+        let mut rest = value;
+        while let Some(c) = rest.chars().next() {
+            if !c.is_ascii_digit() {
+                replaced.push(c);
+                rest = &rest[c.len_utf8()..];
+                continue;
+            }
+            let width = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            let range = match field {
+                0 => Some((1970, 2069)),
+                1 => Some((1, 12)),
+                2 => Some((1, 28)),
+                3 => Some((0, 23)),
+                4 | 5 => Some((0, 59)),
+                _ => None,
+            };
+            match range {
+                Some((min, max)) => {
+                    let n = min + rng.next() % (max - min + 1);
+                    replaced.push_str(&format!("{n:0width$}"));
+                }
+                None if field == 6 => {
+                    replaced.extend((0..width).map(|_| rng.pick("0123456789")));
+                }
+                None => replaced.push_str(&rest[..width]),
+            }
+            rest = &rest[width..];
+            field += 1;
+        }
+        Some(replaced)
+    }
+
+    fn replace_number(&self, number: &str) -> Option<String> {
+        let seed = self.hash(number);
+        match self.options.numbers {
+            Redaction::Keep => None,
+            Redaction::Hash => Some((seed >> 1).to_string()),
+            Redaction::Fake => {
+                let mut rng = Rng(seed);
+                let (mantissa, exponent) = number
+                    .find(['e', 'E'])
+                    .map_or((number, ""), |i| number.split_at(i));
+                let mut leading = true;
+                let mantissa = mantissa
+                    .chars()
+                    .map(|c| match c {
+                        // Keep leading zeros so that e.g. `0.5` stays below 1.
+                        '0' if leading => '0',
+                        '0'..='9' if leading => {
+                            leading = false;
+                            rng.pick("123456789")
+                        }
+                        '0'..='9' => rng.pick("0123456789"),
+                        c => c,
+                    })
+                    .collect::<String>();
+                Some(mantissa + exponent)
+            }
+        }
+    }
+
+    fn hash(&self, value: &str) -> u64 {
+        let mut mac = Hmac::<Sha256>::new_from_slice(self.options.salt.as_bytes())
+            .expect("HMAC takes keys of any length");
+        mac.update(value.as_bytes());
+        let digest = mac.finalize().into_bytes();
+        u64::from_be_bytes(digest[..8].try_into().expect("digest is 32 bytes"))
+    }
+}
+
+/// A SplitMix64 generator of fake characters, seeded by the hash of the replaced value.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    fn pick(&mut self, chars: &str) -> char {
+        let chars = chars.as_bytes();
+        chars[(self.next() % chars.len() as u64) as usize] as char
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+
+    /// The shape of a value, where letters and digits are replaced with their classes.
+    fn shape(s: &str) -> String {
+        s.chars()
+            .map(|c| match c {
+                '0'..='9' => '9',
+                'A'..='Z' => 'A',
+                c if c.is_alphabetic() => 'a',
+                c => c,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_anonymize() {
+        let sql = "SELECT a FROM t1 WHERE b = 'Alice Smith' AND c = 'alice@example.com' \
+            AND d IN (12345, 0.25, -3) AND e = 'Alice Smith' AND f IS NULL AND g = TRUE";
+        for dialect in all_dialects() {
+            let result = anonymize(dialect.as_ref(), sql).unwrap();
+            assert_eq!(
+                instrument::parse_sql(dialect.as_ref(), &result[0])
+                    .unwrap()
+                    .iter()
+                    .map(|s| s.to_string())
+                    .collect::<Vec<_>>(),
+                result,
+                "Failed for dialect: {dialect:?}"
+            );
+            let strings = result[0].split('\'').skip(1).step_by(2).collect::<Vec<_>>();
+            assert_eq!(strings.len(), 3, "Failed for dialect: {dialect:?}");
+            assert_ne!(strings[0], "Alice Smith", "Failed for dialect: {dialect:?}");
+            assert_eq!(strings[0], strings[2], "Failed for dialect: {dialect:?}");
+            assert_eq!(
+                shape(strings[0]),
+                "Aaaaa Aaaaa",
+                "Failed for dialect: {dialect:?}"
+            );
+            assert_eq!(
+                shape(strings[1]),
+                "aaaaa@aaaaaaa.aaa",
+                "Failed for dialect: {dialect:?}"
+            );
+            assert!(
+                result[0].ends_with("IS NULL AND g = true"),
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_replace_number() {
+        let anonymizer = Anonymizer::new();
+        for number in ["12345", "0.25", "1.5e10", "007"] {
+            let replaced = anonymizer.replace_number(number).unwrap();
+            assert_eq!(
+                shape(&replaced),
+                shape(number),
+                "Failed for number: {number}"
+            );
+            assert_eq!(replaced, anonymizer.replace_number(number).unwrap());
+        }
+        assert!(anonymizer.replace_number("0.25").unwrap().starts_with("0."));
+        assert!(!anonymizer.replace_number("12345").unwrap().starts_with('0'));
+        assert!(anonymizer
+            .replace_number("1.5e10")
+            .unwrap()
+            .ends_with("e10"));
+    }
+
+    #[test]
+    fn test_redaction_options() {
+        let sql = "INSERT INTO t1 (a, b) VALUES ('alice', 42)";
+        for dialect in all_dialects() {
+            let keep = AnonymizerOptions::new()
+                .with_strings(Redaction::Keep)
+                .with_numbers(Redaction::Keep);
+            assert_eq!(
+                anonymize_with_options(dialect.as_ref(), sql, keep).unwrap(),
+                [sql],
+                "Failed for dialect: {dialect:?}"
+            );
+            let hash = AnonymizerOptions::new()
+                .with_strings(Redaction::Hash)
+                .with_numbers(Redaction::Hash)
+                .with_salt("secret");
+            assert_eq!(
+                anonymize_with_options(dialect.as_ref(), sql, hash).unwrap(),
+                ["INSERT INTO t1 (a, b) VALUES ('4360c67bc8102511', 5323414136479137039)"],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_salt() {
+        let sql = "SELECT a FROM t1 WHERE b = 'alice'";
+        let dialect = sqlparser::dialect::GenericDialect {};
+        // Salts are random unless set, so values cannot be guessed by anonymizing candidates with default options.
+        assert_ne!(AnonymizerOptions::new().salt, AnonymizerOptions::new().salt);
+        assert_ne!(
+            anonymize(&dialect, sql).unwrap(),
+            anonymize(&dialect, sql).unwrap()
+        );
+        let salted =
+            anonymize_with_options(&dialect, sql, AnonymizerOptions::new().with_salt("secret"))
+                .unwrap();
+        assert_ne!(
+            salted,
+            anonymize_with_options(&dialect, sql, AnonymizerOptions::new().with_salt("other"))
+                .unwrap()
+        );
+        assert_eq!(
+            salted,
+            anonymize_with_options(&dialect, sql, AnonymizerOptions::new().with_salt("secret"))
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_salt_is_hmac_key() {
+        let anonymizer = Anonymizer::new().with_options(
+            AnonymizerOptions::new()
+                .with_strings(Redaction::Hash)
+                .with_salt("k"),
+        );
+        // HMAC-SHA256 of `alice` keyed by `k`, truncated to 64 bits.
+        assert_eq!(
+            anonymizer.replace_string("alice", false).unwrap(),
+            "ec631a55bb81f5b9"
+        );
+    }
+
+    #[test]
+    fn test_typed_strings() {
+        let sql = "SELECT a FROM t1 WHERE b = DATE '2024-03-15' \
+            AND c < TIMESTAMP '2024-03-15 13:45:30.123456' AND d = TIME '13:45:30'";
+        let dialect = sqlparser::dialect::PostgreSqlDialect {};
+        for options in [
+            AnonymizerOptions::new(),
+            AnonymizerOptions::new().with_strings(Redaction::Hash),
+        ] {
+            let result = anonymize_with_options(&dialect, sql, options).unwrap();
+            let values = result[0].split('\'').skip(1).step_by(2).collect::<Vec<_>>();
+            assert_eq!(
+                values.iter().map(|v| shape(v)).collect::<Vec<_>>(),
+                ["9999-99-99", "9999-99-99 99:99:99.999999", "99:99:99"]
+            );
+            assert!(!result[0].contains("2024"));
+            let date = values[0]
+                .split('-')
+                .map(|v| v.parse::<u32>().unwrap())
+                .collect::<Vec<_>>();
+            assert!((1970..=2069).contains(&date[0]));
+            assert!((1..=12).contains(&date[1]));
+            assert!((1..=28).contains(&date[2]));
+            let time = values[2]
+                .split(':')
+                .map(|v| v.parse::<u32>().unwrap())
+                .collect::<Vec<_>>();
+            assert!(time[0] < 24 && time[1] < 60 && time[2] < 60);
+        }
+        let keep = AnonymizerOptions::new().with_strings(Redaction::Keep);
+        assert_eq!(anonymize_with_options(&dialect, sql, keep).unwrap(), [sql]);
+    }
+}
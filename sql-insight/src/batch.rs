@@ -0,0 +1,432 @@
+//! Batch input of SQL split into statements and parsed one at a time.
+//!
+//! See [`BatchInput`] for details, or [`parse_raw_statements`](crate::parse_raw_statements()) for parsing SQL
+//! into statements paired with their original text.
+
+use std::collections::VecDeque;
+
+use crate::cancellation::CancellationToken;
+use crate::error::Error;
+use crate::instrument;
+use crate::span::Span;
+use crate::splitter::{split_statements, StatementSplitter};
+use sqlparser::ast::Statement;
+use sqlparser::dialect::Dialect;
+
+/// Convenience function to parse SQL into statements paired with their original text and span,
+/// so that several analyses can run on the same parse instead of each entry point parsing the SQL again.
+///
+/// The SQL is split on semicolons before parsing, so a statement failing to parse is an entry of its own
+/// and the statements following it are still parsed.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+/// use sql_insight::{Linter, TableExtractor};
+///
+/// let dialect = GenericDialect {};
+/// let statements = sql_insight::parse_raw_statements(&dialect, "SELECT * FROM t1;\nSELEC a;\nDELETE FROM t2");
+/// assert_eq!(statements[0].sql, "SELECT * FROM t1");
+/// let statement = statements[0].statement.as_ref().unwrap();
+/// let tables = TableExtractor::extract_from_statement(statement).unwrap();
+/// let diagnostics = Linter::new(sql_insight::default_rules()).check_statement(statement);
+/// assert_eq!(tables.to_string(), "t1");
+/// assert_eq!(diagnostics[0].rule_id, "select-star");
+/// assert!(statements[1].statement.is_err());
+/// assert_eq!(statements[2].span.start.line, 3);
+/// ```
+pub fn parse_raw_statements(dialect: &dyn Dialect, sql: &str) -> Vec<RawStatement> {
+    let mut raw_statements = vec![];
+    for (sql, span) in split_statements(dialect, sql) {
+        match instrument::parse_sql(dialect, &sql) {
+            Ok(statements) => {
+                raw_statements.extend(statements.into_iter().map(|statement| RawStatement {
+                    sql: sql.clone(),
+                    span,
+                    statement: Ok(statement),
+                }))
+            }
+            Err(e) => raw_statements.push(RawStatement {
+                sql,
+                span,
+                statement: Err(e),
+            }),
+        }
+    }
+    raw_statements
+}
+
+/// [`RawStatement`] represents a statement parsed from SQL together with its original text.
+#[derive(Debug, PartialEq)]
+pub struct RawStatement {
+    /// The original text of the statement, without the delimiter and surrounding whitespace.
+    /// Statements parsed from the same text share it.
+    pub sql: String,
+    /// Span of the text in the input.
+    pub span: Span,
+    /// The parsed statement, or the error parsing the text.
+    pub statement: Result<Statement, Error>,
+}
+
+/// [`BatchInput`] holds SQL to process in batch, such as a dump or a query log, and parses it statement by statement.
+///
+/// With the `mmap` feature, `BatchInput::from_path` memory-maps a file instead of reading it into a `String`,
+/// so that the file is read from the page cache as statements are parsed instead of being held on the heap. Combined with processing statements as they are parsed,
+/// memory usage stays flat regardless of the size of the file.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+/// use sql_insight::{BatchInput, TableExtractor};
+///
+/// let dialect = GenericDialect {};
+/// let input = BatchInput::from_string("SELECT a FROM t1; SELEC b FROM t2; SELECT c FROM t3".into());
+/// let tables = input
+///     .statements(&dialect)
+///     .map(|statement| Ok(TableExtractor::extract_from_statement(&statement?)?.to_string()))
+///     .collect::<Vec<Result<_, sql_insight::error::Error>>>();
+/// assert_eq!(tables[0], Ok("t1".to_string()));
+/// assert!(tables[1].is_err());
+/// assert_eq!(tables[2], Ok("t3".to_string()));
+/// ```
+pub struct BatchInput {
+    source: Source,
+}
+
+enum Source {
+    Owned(String),
+    /// A memory-mapped file, which the caller of `BatchInput::from_path` guarantees to be UTF-8.
+    #[cfg(feature = "mmap")]
+    Mapped(memmap2::Mmap),
+}
+
+impl BatchInput {
+    pub fn from_string(sql: String) -> Self {
+        Self {
+            source: Source::Owned(sql),
+        }
+    }
+
+    /// Memory-map the file at `path`.
+    ///
+    /// The file is not validated as UTF-8 up front, which would read every page of it,
+    /// so pages are only read as statements are parsed.
+    ///
+    /// # Safety
+    ///
+    /// The file must be valid UTF-8, and must not be modified or truncated while the returned
+    /// [`BatchInput`] is alive, by this or any other process.
+    #[cfg(feature = "mmap")]
+    pub unsafe fn from_path(path: impl AsRef<std::path::Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let io_error = |e: std::io::Error| Error::IOError(format!("{}: {}", path.display(), e));
+        let file = std::fs::File::open(path).map_err(io_error)?;
+        // Mapping an empty file fails on some platforms.
+        if file.metadata().map_err(io_error)?.len() == 0 {
+            return Ok(Self::from_string(String::new()));
+        }
+        // SAFETY: the caller guarantees the file is not modified while mapped.
+        let mmap = unsafe { memmap2::Mmap::map(&file) }.map_err(io_error)?;
+        // Checked once here in debug builds only, as `as_str` relies on the caller's guarantee.
+        debug_assert!(
+            std::str::from_utf8(&mmap).is_ok(),
+            "{} is not valid UTF-8",
+            path.display()
+        );
+        Ok(Self {
+            source: Source::Mapped(mmap),
+        })
+    }
+
+    /// The whole SQL.
+    pub fn as_str(&self) -> &str {
+        match &self.source {
+            Source::Owned(sql) => sql,
+            // SAFETY: the caller of `BatchInput::from_path` guarantees the file is UTF-8.
+            #[cfg(feature = "mmap")]
+            Source::Mapped(mmap) => unsafe { std::str::from_utf8_unchecked(mmap) },
+        }
+    }
+
+    /// Statements of the SQL, parsed one at a time as the iterator advances.
+    ///
+    /// The SQL is split on semicolons before parsing, so a statement failing to parse is an error of its own
+    /// and the statements following it are still parsed.
+    pub fn statements<'a>(&'a self, dialect: &'a dyn Dialect) -> Statements<'a> {
+        Statements {
+            dialect,
+            splitter: StatementSplitter::new(dialect, self.as_str()),
+            parsed: VecDeque::new(),
+            token: None,
+        }
+    }
+
+    /// Like [`BatchInput::statements`], but a procedural statement that fails to parse, such as a stored procedure
+    /// with a `BEGIN ... END` body or a function with a dollar-quoted body, is captured as an opaque
+    /// [`BatchEntry::Procedural`] instead of being an error. Procedural statements the dialect can parse are
+    /// yielded as statements.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use sql_insight::sqlparser::dialect::MySqlDialect;
+    /// use sql_insight::{BatchEntry, BatchInput};
+    ///
+    /// let dialect = MySqlDialect {};
+    /// let input = BatchInput::from_string(
+    ///     "CREATE TABLE t1 (a INT); CREATE PROCEDURE p() BEGIN SELECT a FROM t1; END; DROP TABLE t1".into(),
+    /// );
+    /// let entries = input.entries(&dialect).collect::<Result<Vec<_>, _>>().unwrap();
+    /// assert_eq!(entries.len(), 3);
+    /// assert_eq!(
+    ///     entries[1],
+    ///     BatchEntry::Procedural("CREATE PROCEDURE p() BEGIN SELECT a FROM t1; END".into())
+    /// );
+    /// ```
+    pub fn entries<'a>(&'a self, dialect: &'a dyn Dialect) -> Entries<'a> {
+        Entries {
+            dialect,
+            splitter: StatementSplitter::new(dialect, self.as_str()),
+            parsed: VecDeque::new(),
+            token: None,
+        }
+    }
+}
+
+/// An iterator over the statements of a [`BatchInput`].
+pub struct Statements<'a> {
+    dialect: &'a dyn Dialect,
+    splitter: StatementSplitter<'a>,
+    /// Statements parsed from the current piece of SQL but not yet yielded.
+    parsed: VecDeque<Statement>,
+    token: Option<&'a CancellationToken>,
+}
+
+impl<'a> Statements<'a> {
+    /// End the iteration when `token` is cancelled, leaving the rest of the input unparsed.
+    /// Whether the statements yielded so far are all of the input can be told by [`CancellationToken::is_cancelled`].
+    pub fn with_cancellation(mut self, token: &'a CancellationToken) -> Self {
+        self.token = Some(token);
+        self
+    }
+}
+
+impl Iterator for Statements<'_> {
+    type Item = Result<Statement, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.token.is_some_and(CancellationToken::is_cancelled) {
+            return None;
+        }
+        loop {
+            if let Some(statement) = self.parsed.pop_front() {
+                return Some(Ok(statement));
+            }
+            let (_, sql) = self.splitter.next()?;
+            match instrument::parse_sql(self.dialect, sql) {
+                Ok(statements) => self.parsed.extend(statements),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+/// [`BatchEntry`] represents an entry of a [`BatchInput`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum BatchEntry {
+    Statement(Box<Statement>),
+    /// The text of a procedural statement the dialect can't parse.
+    Procedural(String),
+}
+
+/// An iterator over the entries of a [`BatchInput`].
+pub struct Entries<'a> {
+    dialect: &'a dyn Dialect,
+    splitter: StatementSplitter<'a>,
+    /// Statements parsed from the current piece of SQL but not yet yielded.
+    parsed: VecDeque<Statement>,
+    token: Option<&'a CancellationToken>,
+}
+
+impl<'a> Entries<'a> {
+    /// End the iteration when `token` is cancelled, as [`Statements::with_cancellation`] does.
+    pub fn with_cancellation(mut self, token: &'a CancellationToken) -> Self {
+        self.token = Some(token);
+        self
+    }
+}
+
+impl Iterator for Entries<'_> {
+    type Item = Result<BatchEntry, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.token.is_some_and(CancellationToken::is_cancelled) {
+            return None;
+        }
+        loop {
+            if let Some(statement) = self.parsed.pop_front() {
+                return Some(Ok(BatchEntry::Statement(Box::new(statement))));
+            }
+            let (_, sql) = self.splitter.next()?;
+            match instrument::parse_sql(self.dialect, sql) {
+                Ok(statements) => self.parsed.extend(statements),
+                Err(_) if self.splitter.is_procedural() => {
+                    return Some(Ok(BatchEntry::Procedural(sql.to_string())))
+                }
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::span::Location;
+    use crate::test_utils::all_dialects;
+
+    fn statements(input: &BatchInput, dialect: &dyn Dialect) -> Vec<Result<String, ()>> {
+        input
+            .statements(dialect)
+            .map(|statement| statement.map(|s| s.to_string()).map_err(|_| ()))
+            .collect()
+    }
+
+    #[test]
+    fn test_statements() {
+        let input = BatchInput::from_string(
+            "SELECT a FROM t1; INSERT INTO t2 (a) VALUES ('x;y'); SELEC c; DELETE FROM t3".into(),
+        );
+        for dialect in all_dialects() {
+            assert_eq!(
+                statements(&input, dialect.as_ref()),
+                vec![
+                    Ok("SELECT a FROM t1".into()),
+                    Ok("INSERT INTO t2 (a) VALUES ('x;y')".into()),
+                    Err(()),
+                    Ok("DELETE FROM t3".into()),
+                ],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_entries() {
+        let procedure = "CREATE PROCEDURE p() BEGIN DECLARE x INT; SELECT a INTO x FROM t1; END";
+        let input = BatchInput::from_string(format!(
+            "SELECT a FROM t1; {procedure}; SELEC b; DELETE FROM t2"
+        ));
+        for dialect in all_dialects() {
+            let entries = input
+                .entries(dialect.as_ref())
+                .map(|entry| match entry {
+                    Ok(BatchEntry::Statement(statement)) => Ok(statement.to_string()),
+                    Ok(BatchEntry::Procedural(sql)) => Ok(format!("procedural: {sql}")),
+                    Err(_) => Err(()),
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(
+                entries,
+                vec![
+                    Ok("SELECT a FROM t1".into()),
+                    Ok(format!("procedural: {procedure}")),
+                    Err(()),
+                    Ok("DELETE FROM t2".into()),
+                ],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_cancellation() {
+        let input =
+            BatchInput::from_string("SELECT a FROM t1; SELECT b FROM t2; SELECT c FROM t3".into());
+        for dialect in all_dialects() {
+            let token = CancellationToken::new();
+            let statements = input
+                .statements(dialect.as_ref())
+                .with_cancellation(&token)
+                .map(|statement| {
+                    token.cancel();
+                    statement.unwrap().to_string()
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(
+                statements,
+                vec!["SELECT a FROM t1"],
+                "Failed for dialect: {dialect:?}"
+            );
+
+            let token = CancellationToken::new();
+            token.cancel();
+            let entries = input.entries(dialect.as_ref()).with_cancellation(&token);
+            assert_eq!(entries.count(), 0, "Failed for dialect: {dialect:?}");
+        }
+    }
+
+    #[test]
+    fn test_parse_raw_statements() {
+        let sql =
+            "SELECT a FROM t1;\n  INSERT INTO t2 (a) VALUES ('x;y');\nSELEC c; DELETE FROM t3";
+        for dialect in all_dialects() {
+            let statements = parse_raw_statements(dialect.as_ref(), sql)
+                .into_iter()
+                .map(|raw| {
+                    (
+                        raw.sql,
+                        raw.span.start,
+                        raw.statement.map(|s| s.to_string()).map_err(|_| ()),
+                    )
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(
+                statements,
+                vec![
+                    (
+                        "SELECT a FROM t1".to_string(),
+                        Location::new(1, 1),
+                        Ok("SELECT a FROM t1".to_string())
+                    ),
+                    (
+                        "INSERT INTO t2 (a) VALUES ('x;y')".to_string(),
+                        Location::new(2, 3),
+                        Ok("INSERT INTO t2 (a) VALUES ('x;y')".to_string())
+                    ),
+                    ("SELEC c".to_string(), Location::new(3, 1), Err(())),
+                    (
+                        "DELETE FROM t3".to_string(),
+                        Location::new(3, 10),
+                        Ok("DELETE FROM t3".to_string())
+                    ),
+                ],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_from_path() {
+        let dir = std::env::temp_dir().join(format!("sql-insight-batch-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("input.sql");
+        std::fs::write(&path, "SELECT a FROM t1;\nSELECT b FROM t2;\n").unwrap();
+        // SAFETY: the file is UTF-8 and is not modified until the input is dropped.
+        let input = unsafe { BatchInput::from_path(&path) }.unwrap();
+        let dialect = sqlparser::dialect::GenericDialect {};
+        assert_eq!(
+            statements(&input, &dialect),
+            vec![Ok("SELECT a FROM t1".into()), Ok("SELECT b FROM t2".into())]
+        );
+        drop(input);
+        std::fs::write(&path, "").unwrap();
+        // SAFETY: the file is empty.
+        let input = unsafe { BatchInput::from_path(&path) }.unwrap();
+        assert_eq!(input.as_str(), "");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,201 @@
+//! Cancellation of long-running analysis.
+//!
+//! See [`analyze_cancellable`](crate::analyze_cancellable()) as the entry point for analyzing SQL that can be cancelled midway.
+//! Streaming and batch input can be cancelled with [`process_stream_cancellable`](crate::process_stream_cancellable())
+//! and [`Statements::with_cancellation`](crate::Statements::with_cancellation()).
+
+use std::ops::ControlFlow;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::error::Error;
+use crate::parsing;
+use sqlparser::ast::Statement;
+use sqlparser::dialect::Dialect;
+use sqlparser::parser::Parser;
+
+/// Analyze SQL statement by statement with `analyze`, stopping when `token` is cancelled.
+/// Statements are parsed incrementally, so the rest of the input is neither parsed nor analyzed once cancelled.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+/// use sql_insight::{CancellationToken, TableExtractor};
+///
+/// let dialect = GenericDialect {};
+/// let token = CancellationToken::new();
+/// let sql = "SELECT a FROM t1; SELECT b FROM t2";
+/// let result = sql_insight::analyze_cancellable(&dialect, sql, &token, |statement| {
+///     token.cancel();
+///     TableExtractor::extract_from_statement(statement)
+/// })
+/// .unwrap();
+/// assert!(result.cancelled);
+/// assert_eq!(result.results.len(), 1);
+/// assert_eq!(result.results[0].as_ref().unwrap().to_string(), "t1");
+/// ```
+pub fn analyze_cancellable<T, F>(
+    dialect: &dyn Dialect,
+    sql: &str,
+    token: &CancellationToken,
+    mut analyze: F,
+) -> Result<Partial<Result<T, Error>>, Error>
+where
+    F: FnMut(&Statement) -> Result<T, Error>,
+{
+    let mut parser = Parser::new(dialect).try_with_sql(sql)?;
+    let mut partial = Partial::default();
+    parsing::parse_statements(&mut parser, |statement| {
+        if token.is_cancelled() {
+            partial.cancelled = true;
+            return Ok(ControlFlow::Break(()));
+        }
+        partial.results.push(analyze(&statement));
+        Ok(ControlFlow::Continue(()))
+    })?;
+    Ok(partial)
+}
+
+/// [`CancellationToken`] signals that an analysis should stop.
+/// Clones share the same state, so a token can be cancelled from another thread.
+/// A token with a deadline is regarded as cancelled once the deadline has passed.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        self.with_deadline(Instant::now() + timeout)
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+            || self
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+}
+
+/// [`Partial`] represents results of an analysis that may have been cancelled before reaching the end of the input.
+#[derive(Debug, PartialEq)]
+pub struct Partial<T> {
+    /// Results of the statements analyzed before cancellation, in input order.
+    pub results: Vec<T>,
+    /// Whether the analysis was cancelled before reaching the end of the input.
+    pub cancelled: bool,
+}
+
+impl<T> Default for Partial<T> {
+    fn default() -> Self {
+        Self {
+            results: Vec::new(),
+            cancelled: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+    use crate::{TableExtractor, TableReference, Tables};
+
+    const SQL: &str = "SELECT a FROM t1; SELECT b FROM t2; SELECT c FROM t3";
+
+    fn table(name: &str) -> Tables {
+        Tables(vec![TableReference {
+            catalog: None,
+            schema: None,
+            name: name.into(),
+            alias: None,
+        }])
+    }
+
+    #[test]
+    fn test_not_cancelled() {
+        for dialect in all_dialects() {
+            let token = CancellationToken::new();
+            let result = analyze_cancellable(
+                dialect.as_ref(),
+                SQL,
+                &token,
+                TableExtractor::extract_from_statement,
+            )
+            .unwrap();
+            assert_eq!(
+                result,
+                Partial {
+                    results: vec![Ok(table("t1")), Ok(table("t2")), Ok(table("t3"))],
+                    cancelled: false,
+                },
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_cancelled_midway() {
+        for dialect in all_dialects() {
+            let token = CancellationToken::new();
+            let mut analyzed = 0;
+            let result = analyze_cancellable(dialect.as_ref(), SQL, &token, |statement| {
+                analyzed += 1;
+                if analyzed == 2 {
+                    token.cancel();
+                }
+                TableExtractor::extract_from_statement(statement)
+            })
+            .unwrap();
+            assert_eq!(
+                result,
+                Partial {
+                    results: vec![Ok(table("t1")), Ok(table("t2"))],
+                    cancelled: true,
+                },
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_deadline_passed() {
+        let token = CancellationToken::new().with_deadline(Instant::now());
+        assert!(token.is_cancelled());
+        for dialect in all_dialects() {
+            let result = analyze_cancellable(
+                dialect.as_ref(),
+                SQL,
+                &token,
+                TableExtractor::extract_from_statement,
+            )
+            .unwrap();
+            assert!(result.results.is_empty());
+            assert!(result.cancelled);
+        }
+    }
+
+    #[test]
+    fn test_cancel_from_clone() {
+        let token = CancellationToken::new().with_timeout(Duration::from_secs(3600));
+        assert!(!token.is_cancelled());
+        token.clone().cancel();
+        assert!(token.is_cancelled());
+    }
+}
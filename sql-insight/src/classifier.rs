@@ -0,0 +1,230 @@
+//! A classifier of statements by their kind.
+//!
+//! See [`classify`](crate::classify()) as the entry point for classifying SQL.
+
+use crate::error::Error;
+use crate::instrument;
+use sqlparser::ast::{Query, SetExpr, Statement};
+use sqlparser::dialect::Dialect;
+
+/// Convenience function to classify each statement of SQL by its [`StatementKind`].
+///
+/// Classification only looks at the kind of statements and the body of queries,
+/// without extracting tables, so it is cheap enough to run for every statement passing a proxy.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+/// use sql_insight::{DdlKind, StatementKind};
+///
+/// let dialect = GenericDialect {};
+/// let sql = "BEGIN; SELECT a FROM t1; UPDATE t1 SET a = 1; CREATE TABLE t2 (a INT); COMMIT";
+/// let result = sql_insight::classify(&dialect, sql).unwrap();
+/// assert_eq!(result, [
+///     StatementKind::Transaction,
+///     StatementKind::Select,
+///     StatementKind::Update,
+///     StatementKind::Ddl(DdlKind::CreateTable),
+///     StatementKind::Transaction,
+/// ]);
+/// ```
+pub fn classify(dialect: &dyn Dialect, sql: &str) -> Result<Vec<StatementKind>, Error> {
+    let statements = instrument::parse_sql(dialect, sql)?;
+    Ok(statements.iter().map(StatementKind::of).collect())
+}
+
+/// [`StatementKind`] represents the kind of a statement.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum StatementKind {
+    /// Queries, including set operations and `VALUES`.
+    Select,
+    Insert,
+    Update,
+    Delete,
+    Merge,
+    /// Changes to the schema or privileges.
+    Ddl(DdlKind),
+    /// Transaction control, e.g. `BEGIN`, `COMMIT`, `ROLLBACK` and `SAVEPOINT`.
+    Transaction,
+    /// Other statements, e.g. `SHOW`, `EXPLAIN`, `SET`, `USE`, `CALL` and `COPY`.
+    Utility,
+}
+
+/// [`DdlKind`] represents the kind of a statement changing the schema or privileges.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum DdlKind {
+    /// `CREATE TABLE`, including `CREATE VIRTUAL TABLE`.
+    CreateTable,
+    AlterTable,
+    CreateView,
+    AlterView,
+    CreateIndex,
+    AlterIndex,
+    /// `CREATE SCHEMA` and `CREATE DATABASE`.
+    CreateSchema,
+    /// `CREATE FUNCTION` and `CREATE PROCEDURE`.
+    CreateFunction,
+    CreateSequence,
+    CreateType,
+    CreateRole,
+    AlterRole,
+    /// `DROP` of any object.
+    Drop,
+    Truncate,
+    Comment,
+    /// `GRANT` and `REVOKE`.
+    Privilege,
+}
+
+impl StatementKind {
+    /// Classify a statement.
+    pub fn of(statement: &Statement) -> Self {
+        match statement {
+            Statement::Query(query) => Self::of_query(query),
+            Statement::Insert { .. } => Self::Insert,
+            Statement::Update { .. } => Self::Update,
+            Statement::Delete { .. } => Self::Delete,
+            Statement::Merge { .. } => Self::Merge,
+            Statement::CreateTable { .. } | Statement::CreateVirtualTable { .. } => {
+                Self::Ddl(DdlKind::CreateTable)
+            }
+            Statement::AlterTable { .. } => Self::Ddl(DdlKind::AlterTable),
+            Statement::CreateView { .. } => Self::Ddl(DdlKind::CreateView),
+            Statement::AlterView { .. } => Self::Ddl(DdlKind::AlterView),
+            Statement::CreateIndex { .. } => Self::Ddl(DdlKind::CreateIndex),
+            Statement::AlterIndex { .. } => Self::Ddl(DdlKind::AlterIndex),
+            Statement::CreateSchema { .. } | Statement::CreateDatabase { .. } => {
+                Self::Ddl(DdlKind::CreateSchema)
+            }
+            Statement::CreateFunction { .. } | Statement::CreateProcedure { .. } => {
+                Self::Ddl(DdlKind::CreateFunction)
+            }
+            Statement::CreateSequence { .. } => Self::Ddl(DdlKind::CreateSequence),
+            Statement::CreateType { .. } => Self::Ddl(DdlKind::CreateType),
+            Statement::CreateRole { .. } => Self::Ddl(DdlKind::CreateRole),
+            Statement::AlterRole { .. } => Self::Ddl(DdlKind::AlterRole),
+            Statement::Drop { .. } | Statement::DropFunction { .. } => Self::Ddl(DdlKind::Drop),
+            Statement::Truncate { .. } => Self::Ddl(DdlKind::Truncate),
+            Statement::Comment { .. } => Self::Ddl(DdlKind::Comment),
+            Statement::Grant { .. } | Statement::Revoke { .. } => Self::Ddl(DdlKind::Privilege),
+            Statement::StartTransaction { .. }
+            | Statement::SetTransaction { .. }
+            | Statement::Commit { .. }
+            | Statement::Rollback { .. }
+            | Statement::Savepoint { .. }
+            | Statement::ReleaseSavepoint { .. } => Self::Transaction,
+            _ => Self::Utility,
+        }
+    }
+
+    /// Kind of a query by its body, so that data-modifying statements with CTEs, such as
+    /// `WITH c AS (...) INSERT ...`, are classified by the statement they wrap.
+    fn of_query(query: &Query) -> Self {
+        match query.body.as_ref() {
+            SetExpr::Insert(statement) | SetExpr::Update(statement) => Self::of(statement),
+            SetExpr::Query(query) => Self::of_query(query),
+            _ => Self::Select,
+        }
+    }
+
+    /// Whether the statement is a query or modifies data.
+    pub fn is_dml(&self) -> bool {
+        matches!(
+            self,
+            Self::Select | Self::Insert | Self::Update | Self::Delete | Self::Merge
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+    use sqlparser::dialect::{GenericDialect, MySqlDialect, PostgreSqlDialect};
+
+    fn assert_classify(sql: &str, expected: Vec<StatementKind>, dialects: Vec<Box<dyn Dialect>>) {
+        for dialect in dialects {
+            let result = classify(dialect.as_ref(), sql).unwrap();
+            assert_eq!(result, expected, "Failed for dialect: {dialect:?}");
+        }
+    }
+
+    #[test]
+    fn test_dml() {
+        let sql = "SELECT a FROM t1 UNION SELECT b FROM t2; \
+            WITH c AS (SELECT a FROM t1) SELECT * FROM c; \
+            INSERT INTO t1 (a) VALUES (1); \
+            UPDATE t1 SET a = 1; \
+            DELETE FROM t1";
+        assert_classify(
+            sql,
+            vec![
+                StatementKind::Select,
+                StatementKind::Select,
+                StatementKind::Insert,
+                StatementKind::Update,
+                StatementKind::Delete,
+            ],
+            all_dialects(),
+        );
+    }
+
+    #[test]
+    fn test_ddl() {
+        let sql = "CREATE TABLE t1 (a INT); ALTER TABLE t1 ADD COLUMN b INT; CREATE VIEW v1 AS SELECT a FROM t1; \
+            CREATE INDEX i1 ON t1 (a); DROP TABLE t1";
+        assert_classify(
+            sql,
+            vec![
+                StatementKind::Ddl(DdlKind::CreateTable),
+                StatementKind::Ddl(DdlKind::AlterTable),
+                StatementKind::Ddl(DdlKind::CreateView),
+                StatementKind::Ddl(DdlKind::CreateIndex),
+                StatementKind::Ddl(DdlKind::Drop),
+            ],
+            all_dialects(),
+        );
+    }
+
+    #[test]
+    fn test_transaction_and_utility() {
+        let sql =
+            "START TRANSACTION; SAVEPOINT s1; ROLLBACK; SHOW TABLES; EXPLAIN DELETE FROM t1; \
+            SET autocommit = 1; TRUNCATE TABLE t1; COMMIT";
+        assert_classify(
+            sql,
+            vec![
+                StatementKind::Transaction,
+                StatementKind::Transaction,
+                StatementKind::Transaction,
+                StatementKind::Utility,
+                StatementKind::Utility,
+                StatementKind::Utility,
+                StatementKind::Ddl(DdlKind::Truncate),
+                StatementKind::Transaction,
+            ],
+            vec![Box::new(MySqlDialect {}), Box::new(GenericDialect {})],
+        );
+    }
+
+    #[test]
+    fn test_data_modifying_cte() {
+        assert_classify(
+            "WITH c AS (SELECT a FROM t1) INSERT INTO t2 SELECT a FROM c",
+            vec![StatementKind::Insert],
+            vec![Box::new(PostgreSqlDialect {}), Box::new(GenericDialect {})],
+        );
+    }
+
+    #[test]
+    fn test_is_dml() {
+        assert!(StatementKind::Merge.is_dml());
+        assert!(!StatementKind::Ddl(DdlKind::Drop).is_dml());
+        assert!(!StatementKind::Utility.is_dml());
+    }
+}
@@ -0,0 +1,312 @@
+//! A checker of whether SQL parses under each of several dialects, and a comparison of analysis results across them.
+//!
+//! See [`check_compatibility`](crate::check_compatibility()) as the entry point for checking SQL,
+//! and [`compare_across_dialects`](crate::compare_across_dialects()) for comparing analysis results.
+
+use std::fmt;
+
+use crate::error::Error;
+use crate::extractor::crud_table_extractor::CrudTableExtractor;
+use crate::extractor::table_extractor::TableExtractor;
+use crate::formatter::Formatter;
+use crate::normalizer::{Normalizer, NormalizerOptions};
+use crate::report::ErrorRecord;
+use sqlparser::dialect::{dialect_from_str, Dialect};
+use sqlparser::parser::Parser;
+
+/// Convenience function to check whether SQL parses under each of the named dialects.
+/// Dialect names are those accepted by [`dialect_from_str`], e.g. `mysql` or `postgres`,
+/// and results are in the order of the names.
+///
+/// ## Example
+///
+/// ```rust
+/// let result = sql_insight::check_compatibility("SELECT a FROM t1 LIMIT 1, 2", &["mysql", "postgres"]).unwrap();
+/// assert!(result[0].is_compatible());
+/// assert!(!result[1].is_compatible());
+/// ```
+pub fn check_compatibility(
+    sql: &str,
+    dialect_names: &[&str],
+) -> Result<Vec<DialectCompatibility>, Error> {
+    dialect_names
+        .iter()
+        .map(|name| {
+            let dialect = dialect_from_str(name)
+                .ok_or_else(|| Error::ArgumentError(format!("Dialect not found: {name}")))?;
+            let error = Parser::parse_sql(dialect.as_ref(), sql)
+                .err()
+                .map(|e| ErrorRecord::from(&Error::from(e)));
+            Ok(DialectCompatibility {
+                dialect: name.to_string(),
+                error,
+            })
+        })
+        .collect()
+}
+
+/// [`DialectCompatibility`] represents whether SQL parses under a dialect, with the parse error if it doesn't.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DialectCompatibility {
+    /// The dialect name as requested.
+    pub dialect: String,
+    pub error: Option<ErrorRecord>,
+}
+
+impl DialectCompatibility {
+    pub fn is_compatible(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Convenience function to run an analysis of SQL under each of the named dialects and compare the results,
+/// to audit how sensitive the analysis is to the dialect. Dialect names are those accepted by [`dialect_from_str`],
+/// and results are in the order of the names.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::DialectAnalysis;
+///
+/// let sql = "SELECT a FROM t1 WHERE b = 1; SELECT c FROM t2 LIMIT 1, 2";
+/// let comparison =
+///     sql_insight::compare_across_dialects(sql, &["mysql", "postgres"], DialectAnalysis::ExtractTables).unwrap();
+/// assert!(!comparison.is_consistent());
+/// assert_eq!(comparison.failed_dialects(), ["postgres"]);
+/// assert_eq!(comparison.results[0].output, Some(vec!["t1".to_string(), "t2".to_string()]));
+/// ```
+pub fn compare_across_dialects(
+    sql: &str,
+    dialect_names: &[&str],
+    analysis: DialectAnalysis,
+) -> Result<DialectComparison, Error> {
+    let results = dialect_names
+        .iter()
+        .map(|name| {
+            let dialect = dialect_from_str(name)
+                .ok_or_else(|| Error::ArgumentError(format!("Dialect not found: {name}")))?;
+            let (output, error) = match analysis.run(dialect.as_ref(), sql) {
+                Ok(output) => (Some(output), None),
+                Err(e) => (None, Some(ErrorRecord::from(&e))),
+            };
+            Ok(DialectResult {
+                dialect: name.to_string(),
+                output,
+                error,
+            })
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    Ok(DialectComparison { analysis, results })
+}
+
+/// [`DialectAnalysis`] represents an analysis compared across dialects.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum DialectAnalysis {
+    /// Parsing only, comparing the formatted statements.
+    Format,
+    Normalize,
+    ExtractTables,
+    ExtractCrudTables,
+}
+
+impl fmt::Display for DialectAnalysis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            DialectAnalysis::Format => "format",
+            DialectAnalysis::Normalize => "normalize",
+            DialectAnalysis::ExtractTables => "extract_tables",
+            DialectAnalysis::ExtractCrudTables => "extract_crud_tables",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl DialectAnalysis {
+    /// Run the analysis, rendering the result of each statement as a string.
+    /// The first error of analyzing any statement is the error of the whole SQL.
+    fn run(&self, dialect: &dyn Dialect, sql: &str) -> Result<Vec<String>, Error> {
+        match self {
+            DialectAnalysis::Format => Formatter::format(dialect, sql),
+            DialectAnalysis::Normalize => {
+                Normalizer::normalize(dialect, sql, NormalizerOptions::new())
+            }
+            DialectAnalysis::ExtractTables => TableExtractor::extract(dialect, sql)?
+                .into_iter()
+                .map(|tables| Ok(tables?.to_string()))
+                .collect(),
+            DialectAnalysis::ExtractCrudTables => CrudTableExtractor::extract(dialect, sql)?
+                .into_iter()
+                .map(|crud_tables| Ok(crud_tables?.to_string()))
+                .collect(),
+        }
+    }
+}
+
+/// [`DialectComparison`] represents the results of an analysis under each of several dialects.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DialectComparison {
+    pub analysis: DialectAnalysis,
+    pub results: Vec<DialectResult>,
+}
+
+/// [`DialectResult`] represents the result of an analysis under a dialect.
+/// Exactly one of `output` and `error` is set.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DialectResult {
+    /// The dialect name as requested.
+    pub dialect: String,
+    /// The result of each statement, rendered as a string.
+    pub output: Option<Vec<String>>,
+    pub error: Option<ErrorRecord>,
+}
+
+impl DialectComparison {
+    /// Whether the analysis succeeds with the same results under all dialects.
+    pub fn is_consistent(&self) -> bool {
+        self.results
+            .iter()
+            .all(|result| result.output.is_some() && result.output == self.results[0].output)
+    }
+
+    /// Dialects under which the analysis fails, e.g. the SQL fails to parse.
+    pub fn failed_dialects(&self) -> Vec<&str> {
+        self.results
+            .iter()
+            .filter(|result| result.error.is_some())
+            .map(|result| result.dialect.as_str())
+            .collect()
+    }
+
+    /// Positions of statements whose results differ among the dialects under which the analysis succeeds,
+    /// including statements missing under some of them.
+    pub fn differing_statements(&self) -> Vec<usize> {
+        let outputs = self
+            .results
+            .iter()
+            .filter_map(|result| result.output.as_ref())
+            .collect::<Vec<_>>();
+        let statements = outputs.iter().map(|output| output.len()).max().unwrap_or(0);
+        (0..statements)
+            .filter(|index| {
+                outputs
+                    .iter()
+                    .any(|output| output.get(*index) != outputs[0].get(*index))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::ErrorKind;
+
+    #[test]
+    fn test_check_compatibility() {
+        let sql = "SELECT a FROM t1 WHERE b = 1 LIMIT 10";
+        let result = check_compatibility(sql, &["mysql", "postgres", "sqlite"]).unwrap();
+        assert_eq!(
+            result
+                .iter()
+                .map(|r| (r.dialect.as_str(), r.is_compatible()))
+                .collect::<Vec<_>>(),
+            [("mysql", true), ("postgres", true), ("sqlite", true)]
+        );
+    }
+
+    #[test]
+    fn test_check_compatibility_with_parse_error() {
+        let result =
+            check_compatibility("SELECT a FROM t1 LIMIT 1, 2", &["mysql", "postgres"]).unwrap();
+        assert_eq!(result[0].error, None);
+        assert_eq!(result[1].dialect, "postgres");
+        assert_eq!(
+            result[1].error.as_ref().map(|e| e.kind),
+            Some(ErrorKind::Parser)
+        );
+    }
+
+    #[test]
+    fn test_check_compatibility_with_unknown_dialect() {
+        assert_eq!(
+            check_compatibility("SELECT 1", &["mysql", "oracle"]),
+            Err(Error::ArgumentError("Dialect not found: oracle".into()))
+        );
+    }
+
+    #[test]
+    fn test_compare_across_dialects() {
+        let sql = "SELECT a FROM t1 AS x WHERE b = 1; DELETE FROM t2";
+        for analysis in [
+            DialectAnalysis::Format,
+            DialectAnalysis::Normalize,
+            DialectAnalysis::ExtractTables,
+            DialectAnalysis::ExtractCrudTables,
+        ] {
+            let comparison =
+                compare_across_dialects(sql, &["generic", "mysql", "postgres"], analysis).unwrap();
+            assert!(
+                comparison.is_consistent(),
+                "Failed for analysis: {analysis}"
+            );
+            assert_eq!(comparison.differing_statements(), Vec::<usize>::new());
+        }
+        let comparison =
+            compare_across_dialects(sql, &["mysql"], DialectAnalysis::ExtractCrudTables).unwrap();
+        assert_eq!(
+            comparison.results[0].output,
+            Some(vec![
+                "Create: [], Read: [t1 AS x], Update: [], Delete: []".to_string(),
+                "Create: [], Read: [], Update: [], Delete: [t2]".to_string(),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_compare_across_dialects_with_differences() {
+        // Double quotes delimit a string literal in MySQL but an identifier in PostgreSQL.
+        let sql = "SELECT \"a\" FROM t1; SELECT 1";
+        let comparison =
+            compare_across_dialects(sql, &["mysql", "postgres"], DialectAnalysis::Normalize)
+                .unwrap();
+        assert!(!comparison.is_consistent());
+        assert_eq!(comparison.failed_dialects(), Vec::<&str>::new());
+        assert_eq!(comparison.differing_statements(), [0]);
+        assert_eq!(
+            comparison.results[0].output,
+            Some(vec!["SELECT ? FROM t1".to_string(), "SELECT ?".to_string()])
+        );
+        assert_eq!(
+            comparison.results[1].output,
+            Some(vec![
+                "SELECT \"a\" FROM t1".to_string(),
+                "SELECT ?".to_string()
+            ])
+        );
+        let comparison = compare_across_dialects(
+            "SELECT a FROM t1 LIMIT 1, 2",
+            &["mysql", "postgres"],
+            DialectAnalysis::Normalize,
+        )
+        .unwrap();
+        assert_eq!(comparison.failed_dialects(), ["postgres"]);
+        assert_eq!(
+            comparison.results[1].error.as_ref().map(|e| e.kind),
+            Some(ErrorKind::Parser)
+        );
+        assert_eq!(comparison.differing_statements(), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn test_compare_across_dialects_with_unknown_dialect() {
+        assert_eq!(
+            compare_across_dialects("SELECT 1", &["oracle"], DialectAnalysis::Format),
+            Err(Error::ArgumentError("Dialect not found: oracle".into()))
+        );
+    }
+}
@@ -0,0 +1,335 @@
+//! A measure of the complexity of statements, such as the number of joins and the nesting depth of subqueries.
+//!
+//! See [`measure_complexity`](crate::measure_complexity()) as the entry point for measuring SQL.
+
+use core::fmt;
+use std::collections::HashSet;
+use std::ops::ControlFlow;
+
+use crate::error::Error;
+use crate::extractor::join_extractor::JoinExtractor;
+use crate::extractor::table_extractor::{TableExtractor, TableReference};
+use crate::helper;
+use crate::instrument;
+use sqlparser::ast::{
+    BinaryOperator, Expr, Query, SelectItem, SetExpr, Statement, TableFactor, Visit, Visitor,
+};
+use sqlparser::dialect::Dialect;
+
+/// Convenience function to measure the complexity of each statement of SQL.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+///
+/// let dialect = GenericDialect {};
+/// let sql = "SELECT * FROM t1 JOIN t2 ON t1.id = t2.id WHERE t1.a IN (SELECT a FROM t3 WHERE b > 1)";
+/// let result = sql_insight::measure_complexity(&dialect, sql).unwrap();
+/// let complexity = result[0].as_ref().unwrap();
+/// assert_eq!((complexity.joins, complexity.tables, complexity.subquery_depth), (1, 3, 1));
+/// assert_eq!(complexity.predicates, 3);
+/// assert!(complexity.select_star);
+/// ```
+pub fn measure_complexity(
+    dialect: &dyn Dialect,
+    sql: &str,
+) -> Result<Vec<Result<Complexity, Error>>, Error> {
+    ComplexityAnalyzer::analyze(dialect, sql)
+}
+
+/// [`Complexity`] represents complexity measures of a statement.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Complexity {
+    /// Number of joins, including tables separated by commas in `FROM`.
+    pub joins: usize,
+    /// Number of distinct tables, regardless of aliases. CTEs are not tables.
+    pub tables: usize,
+    /// Number of subqueries, i.e. queries in expressions, derived tables and CTEs.
+    pub subqueries: usize,
+    /// Maximum nesting depth of subqueries, which is 0 without subqueries.
+    pub subquery_depth: usize,
+    /// Number of predicates, i.e. comparisons, `IN`, `BETWEEN`, `LIKE`, `IS` tests and `EXISTS`.
+    pub predicates: usize,
+    /// Number of set operations, e.g. `UNION` and `EXCEPT`.
+    pub set_operations: usize,
+    /// Whether any SELECT has a wildcard, e.g. `SELECT *` or `SELECT t1.*`.
+    pub select_star: bool,
+}
+
+impl fmt::Display for Complexity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Joins: {}, Tables: {}, Subqueries: {}, Subquery Depth: {}, Predicates: {}, Set Operations: {}, Select Star: {}",
+            self.joins,
+            self.tables,
+            self.subqueries,
+            self.subquery_depth,
+            self.predicates,
+            self.set_operations,
+            self.select_star
+        )
+    }
+}
+
+/// A visitor to measure the complexity of a statement. Joins and tables are counted by
+/// [`JoinExtractor`] and [`TableExtractor`] respectively.
+#[derive(Default, Debug)]
+pub struct ComplexityAnalyzer {
+    complexity: Complexity,
+    /// Number of queries to be visited next that are subqueries, announced by the expression, derived table
+    /// or query with CTEs containing them.
+    pending_subqueries: usize,
+    /// Whether each query being visited is a subquery.
+    query_stack: Vec<bool>,
+    /// Names of CTEs, which are not counted as tables.
+    cte_names: HashSet<String>,
+}
+
+impl Visitor for ComplexityAnalyzer {
+    type Break = ();
+
+    fn pre_visit_query(&mut self, query: &Query) -> ControlFlow<Self::Break> {
+        let is_subquery = self.pending_subqueries > 0;
+        if is_subquery {
+            self.pending_subqueries -= 1;
+            self.complexity.subqueries += 1;
+        }
+        self.query_stack.push(is_subquery);
+        let depth = self.query_stack.iter().filter(|&&sub| sub).count();
+        self.complexity.subquery_depth = self.complexity.subquery_depth.max(depth);
+        if let Some(with) = &query.with {
+            self.pending_subqueries += with.cte_tables.len();
+            self.cte_names.extend(
+                with.cte_tables
+                    .iter()
+                    .map(|cte| cte.alias.name.value.clone()),
+            );
+        }
+        self.complexity.set_operations += count_set_operations(&query.body);
+        let mut selects = vec![];
+        helper::collect_selects(&query.body, &mut selects);
+        if selects
+            .iter()
+            .flat_map(|select| &select.projection)
+            .any(|item| {
+                matches!(
+                    item,
+                    SelectItem::Wildcard(_) | SelectItem::QualifiedWildcard(_, _)
+                )
+            })
+        {
+            self.complexity.select_star = true;
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn post_visit_query(&mut self, _query: &Query) -> ControlFlow<Self::Break> {
+        self.query_stack.pop();
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_table_factor(&mut self, table_factor: &TableFactor) -> ControlFlow<Self::Break> {
+        if let TableFactor::Derived { .. } = table_factor {
+            self.pending_subqueries += 1;
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_expr(&mut self, expr: &Expr) -> ControlFlow<Self::Break> {
+        match expr {
+            Expr::Subquery(_) => self.pending_subqueries += 1,
+            Expr::InSubquery { .. } | Expr::Exists { .. } => {
+                self.pending_subqueries += 1;
+                self.complexity.predicates += 1;
+            }
+            Expr::BinaryOp { op, .. } if is_comparison(op) => self.complexity.predicates += 1,
+            Expr::InList { .. }
+            | Expr::InUnnest { .. }
+            | Expr::Between { .. }
+            | Expr::Like { .. }
+            | Expr::ILike { .. }
+            | Expr::SimilarTo { .. }
+            | Expr::IsNull(_)
+            | Expr::IsNotNull(_)
+            | Expr::IsTrue(_)
+            | Expr::IsNotTrue(_)
+            | Expr::IsFalse(_)
+            | Expr::IsNotFalse(_)
+            | Expr::IsUnknown(_)
+            | Expr::IsNotUnknown(_)
+            | Expr::IsDistinctFrom(_, _)
+            | Expr::IsNotDistinctFrom(_, _) => self.complexity.predicates += 1,
+            _ => {}
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+impl ComplexityAnalyzer {
+    /// Measure the complexity of each statement of SQL.
+    pub fn analyze(
+        dialect: &dyn Dialect,
+        sql: &str,
+    ) -> Result<Vec<Result<Complexity, Error>>, Error> {
+        let statements = instrument::parse_sql(dialect, sql)?;
+        Ok(statements.iter().map(Self::analyze_statement).collect())
+    }
+
+    /// Measure the complexity of a statement, failing if its tables cannot be extracted.
+    pub fn analyze_statement(statement: &Statement) -> Result<Complexity, Error> {
+        instrument::try_analyze("measure_complexity", || {
+            let mut visitor = ComplexityAnalyzer::default();
+            let _ = statement.visit(&mut visitor);
+            let tables = TableExtractor::extract_from_statement(statement)?
+                .0
+                .into_iter()
+                .filter(|table| {
+                    table.catalog.is_some()
+                        || table.schema.is_some()
+                        || !visitor.cte_names.contains(&table.name.value)
+                })
+                .map(|table| TableReference {
+                    alias: None,
+                    ..table
+                })
+                .collect::<HashSet<_>>();
+            Ok(Complexity {
+                joins: JoinExtractor::extract_from_statement(statement)?.0.len(),
+                tables: tables.len(),
+                ..visitor.complexity
+            })
+        })
+    }
+}
+
+fn is_comparison(op: &BinaryOperator) -> bool {
+    matches!(
+        op,
+        BinaryOperator::Eq
+            | BinaryOperator::NotEq
+            | BinaryOperator::Lt
+            | BinaryOperator::LtEq
+            | BinaryOperator::Gt
+            | BinaryOperator::GtEq
+            | BinaryOperator::Spaceship
+    )
+}
+
+fn count_set_operations(set_expr: &SetExpr) -> usize {
+    match set_expr {
+        SetExpr::SetOperation { left, right, .. } => {
+            1 + count_set_operations(left) + count_set_operations(right)
+        }
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+
+    fn assert_complexity(sql: &str, expected: Vec<Complexity>) {
+        for dialect in all_dialects() {
+            let result = ComplexityAnalyzer::analyze(dialect.as_ref(), sql)
+                .unwrap()
+                .into_iter()
+                .map(Result::unwrap)
+                .collect::<Vec<_>>();
+            assert_eq!(result, expected, "Failed for dialect: {dialect:?}");
+        }
+    }
+
+    #[test]
+    fn test_simple_statements() {
+        let sql = "SELECT a FROM t1; INSERT INTO t1 (a) SELECT a FROM t2 AS x; DELETE FROM t1 WHERE a = 1";
+        assert_complexity(
+            sql,
+            vec![
+                Complexity {
+                    tables: 1,
+                    ..Default::default()
+                },
+                Complexity {
+                    tables: 2,
+                    ..Default::default()
+                },
+                Complexity {
+                    tables: 1,
+                    predicates: 1,
+                    ..Default::default()
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn test_nested_subqueries() {
+        let sql = "SELECT t1.* FROM t1, (SELECT a FROM t2) AS d \
+            WHERE t1.a = d.a AND EXISTS (SELECT 1 FROM t3 WHERE t3.b IN (SELECT b FROM t4 WHERE c BETWEEN 1 AND 2)) \
+            AND t1.c IS NOT NULL AND t1.d LIKE 'x%'";
+        assert_complexity(
+            sql,
+            vec![Complexity {
+                joins: 1,
+                tables: 4,
+                subqueries: 3,
+                subquery_depth: 2,
+                predicates: 6,
+                set_operations: 0,
+                select_star: true,
+            }],
+        );
+    }
+
+    #[test]
+    fn test_ctes_and_set_operations() {
+        let sql = "WITH c1 AS (SELECT a FROM t1 UNION SELECT a FROM t2), c2 AS (SELECT a FROM c1) \
+            SELECT * FROM c2 JOIN t1 ON c2.a = t1.a UNION ALL SELECT a FROM t3 EXCEPT SELECT a FROM t4";
+        assert_complexity(
+            sql,
+            vec![Complexity {
+                joins: 1,
+                tables: 4,
+                subqueries: 2,
+                subquery_depth: 1,
+                predicates: 1,
+                set_operations: 3,
+                select_star: true,
+            }],
+        );
+    }
+
+    #[test]
+    fn test_update_with_subquery() {
+        let sql = "UPDATE t1 SET a = (SELECT MAX(a) FROM t2) WHERE b IN (1, 2)";
+        assert_complexity(
+            sql,
+            vec![Complexity {
+                tables: 2,
+                subqueries: 1,
+                subquery_depth: 1,
+                predicates: 1,
+                ..Default::default()
+            }],
+        );
+    }
+
+    #[test]
+    fn test_too_many_identifiers() {
+        for dialect in all_dialects() {
+            let result =
+                ComplexityAnalyzer::analyze(dialect.as_ref(), "SELECT a FROM a.b.c.d").unwrap();
+            assert_eq!(
+                result,
+                vec![Err(Error::AnalysisError(
+                    "Too many identifiers provided".to_string()
+                ))],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,480 @@
+//! Dependency analysis of DDL statements.
+//!
+//! See [`order_ddl`](crate::order_ddl()) as the entry point for ordering DDL statements,
+//! or [`extract_view_dependencies`](crate::extract_view_dependencies()) for extracting the tables views read from.
+
+use core::fmt;
+use std::collections::HashMap;
+use std::ops::ControlFlow;
+
+use crate::error::Error;
+use crate::extractor::crud_table_extractor::CrudTableExtractor;
+use crate::extractor::options::ExtractorOptions;
+use crate::extractor::table_extractor::TableReference;
+use crate::helper;
+use crate::instrument;
+use sqlparser::ast::{
+    AlterTableOperation, ColumnOption, ObjectName, Statement, TableConstraint, Visit, Visitor,
+};
+use sqlparser::dialect::Dialect;
+
+/// Convenience function to order statements of SQL so that each statement comes after the statements
+/// creating the tables and views it depends on.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+///
+/// let dialect = GenericDialect {};
+/// let sql = "CREATE VIEW v1 AS SELECT * FROM orders; \
+///     CREATE TABLE orders (id INT, user_id INT REFERENCES users (id)); \
+///     CREATE TABLE users (id INT PRIMARY KEY)";
+/// let result = sql_insight::order_ddl(&dialect, sql).unwrap();
+/// assert_eq!(result.order, [2, 1, 0]);
+/// assert!(result.cycles.is_empty());
+/// ```
+pub fn order_ddl(dialect: &dyn Dialect, sql: &str) -> Result<DdlOrder, Error> {
+    let statements = instrument::parse_sql(dialect, sql)?;
+    Ok(DdlOrder::from_statements(&statements))
+}
+
+/// [`DdlOrder`] represents an order of statements respecting their dependencies.
+///
+/// A statement depends on the statements creating the tables and views it references, through foreign keys,
+/// queries of views and `CREATE TABLE ... AS SELECT`, `LIKE` clauses, or as the subject of `CREATE INDEX` and `ALTER TABLE`.
+/// Names are compared case-insensitively unless quoted.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DdlOrder {
+    /// Indices of all statements in dependency order. Among statements whose dependencies are placed,
+    /// the one appearing first in the input is placed first.
+    /// Cycles are broken at their first statement, so statements in cycles are ordered on a best-effort basis.
+    pub order: Vec<usize>,
+    /// Indices of statements depending on each other in a cycle, e.g. tables referencing each other by foreign keys.
+    pub cycles: Vec<Vec<usize>>,
+}
+
+impl DdlOrder {
+    pub fn from_statements(statements: &[Statement]) -> Self {
+        let mut creators = HashMap::new();
+        for (index, statement) in statements.iter().enumerate() {
+            if let Some(name) = created_object(statement) {
+                creators.entry(object_key(name)).or_insert(index);
+            }
+        }
+        let dependencies = statements
+            .iter()
+            .enumerate()
+            .map(|(index, statement)| {
+                let mut dependencies = referenced_objects(statement)
+                    .iter()
+                    .filter_map(|name| creators.get(&object_key(name)).copied())
+                    .filter(|creator| *creator != index)
+                    .collect::<Vec<_>>();
+                dependencies.sort_unstable();
+                dependencies.dedup();
+                dependencies
+            })
+            .collect::<Vec<_>>();
+        let cycles = cycles(&dependencies);
+        Self {
+            order: topological_order(&dependencies, &cycles),
+            cycles,
+        }
+    }
+}
+
+/// Convenience function to extract the views and tables created from queries by `CREATE VIEW`,
+/// `CREATE MATERIALIZED VIEW` and `CREATE TABLE ... AS SELECT`, with the tables they read from.
+/// Other statements have `None` as their result.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+/// use sql_insight::ViewKind;
+///
+/// let dialect = GenericDialect {};
+/// let sql = "CREATE VIEW v1 AS SELECT * FROM t1 JOIN t2 ON t1.id = t2.id; \
+///     CREATE TABLE t3 AS SELECT * FROM v1 WHERE a IN (SELECT a FROM t1); \
+///     INSERT INTO t4 SELECT * FROM t3";
+/// let result = sql_insight::extract_view_dependencies(&dialect, sql).unwrap();
+/// let dependency = result[0].as_ref().unwrap().as_ref().unwrap();
+/// assert_eq!(dependency.kind, ViewKind::View);
+/// assert_eq!(dependency.to_string(), "v1: t1, t2");
+/// assert_eq!(result[1].as_ref().unwrap().as_ref().unwrap().to_string(), "t3: v1, t1");
+/// assert_eq!(result[2], Ok(None));
+/// ```
+pub fn extract_view_dependencies(
+    dialect: &dyn Dialect,
+    sql: &str,
+) -> Result<Vec<Result<Option<ViewDependency>, Error>>, Error> {
+    let statements = instrument::parse_sql(dialect, sql)?;
+    Ok(statements
+        .iter()
+        .map(ViewDependency::from_statement)
+        .collect())
+}
+
+/// Kind of object created from a query.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ViewKind {
+    View,
+    MaterializedView,
+    TableAsSelect,
+}
+
+/// [`ViewDependency`] represents an object created from a query and the tables the query reads from,
+/// i.e. the edges of a DAG of views.
+///
+/// Sources are deduplicated and in order of appearance. References to CTEs are not sources,
+/// and sources are views as well as tables, since views cannot be told apart from tables in a statement alone.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ViewDependency {
+    pub kind: ViewKind,
+    pub object: TableReference,
+    pub sources: Vec<TableReference>,
+}
+
+impl fmt::Display for ViewDependency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sources = self
+            .sources
+            .iter()
+            .map(|t| t.to_string())
+            .collect::<Vec<String>>()
+            .join(", ");
+        write!(f, "{}: {}", self.object, sources)
+    }
+}
+
+impl ViewDependency {
+    /// Extract the dependency of a statement creating a view or a table from a query, or `None` for other statements.
+    pub fn from_statement(statement: &Statement) -> Result<Option<Self>, Error> {
+        let (kind, name) = match statement {
+            Statement::CreateView {
+                name, materialized, ..
+            } => {
+                let kind = if *materialized {
+                    ViewKind::MaterializedView
+                } else {
+                    ViewKind::View
+                };
+                (kind, name)
+            }
+            Statement::CreateTable {
+                name,
+                query: Some(_),
+                ..
+            } => (ViewKind::TableAsSelect, name),
+            _ => return Ok(None),
+        };
+        let object = TableReference::try_from(name)?;
+        let read_tables = CrudTableExtractor::extract_from_statement(statement)?.read_tables;
+        let sources = helper::calc_difference_of_tables(read_tables, vec![object.clone()])
+            .into_iter()
+            .map(|table| TableReference {
+                alias: None,
+                ..table
+            })
+            .collect();
+        Ok(Some(Self {
+            kind,
+            object,
+            sources: ExtractorOptions::new()
+                .with_deduplicate(true)
+                .apply(sources),
+        }))
+    }
+}
+
+fn created_object(statement: &Statement) -> Option<&ObjectName> {
+    match statement {
+        Statement::CreateTable { name, .. } | Statement::CreateView { name, .. } => Some(name),
+        _ => None,
+    }
+}
+
+/// Tables and views a statement references, including the one it creates.
+fn referenced_objects(statement: &Statement) -> Vec<ObjectName> {
+    let mut objects = vec![];
+    let mut constraints = vec![];
+    match statement {
+        Statement::CreateTable {
+            columns,
+            constraints: table_constraints,
+            like,
+            clone,
+            ..
+        } => {
+            for column in columns {
+                for option in &column.options {
+                    if let ColumnOption::ForeignKey { foreign_table, .. } = &option.option {
+                        objects.push(foreign_table);
+                    }
+                }
+            }
+            constraints.extend(table_constraints);
+            objects.extend(like);
+            objects.extend(clone);
+        }
+        Statement::CreateIndex { table_name, .. } => objects.push(table_name),
+        Statement::AlterTable {
+            name, operations, ..
+        } => {
+            objects.push(name);
+            for operation in operations {
+                if let AlterTableOperation::AddConstraint(constraint) = operation {
+                    constraints.push(constraint);
+                }
+            }
+        }
+        _ => {}
+    }
+    for constraint in constraints {
+        if let TableConstraint::ForeignKey { foreign_table, .. } = constraint {
+            objects.push(foreign_table);
+        }
+    }
+    let mut collector = RelationCollector::default();
+    let _ = statement.visit(&mut collector);
+    objects
+        .into_iter()
+        .cloned()
+        .chain(collector.relations)
+        .collect()
+}
+
+/// Key of an object name, lowercasing unquoted identifiers.
+fn object_key(name: &ObjectName) -> String {
+    name.0
+        .iter()
+        .map(|ident| match ident.quote_style {
+            Some(_) => ident.value.clone(),
+            None => ident.value.to_lowercase(),
+        })
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+#[derive(Default)]
+struct RelationCollector {
+    relations: Vec<ObjectName>,
+}
+
+impl Visitor for RelationCollector {
+    type Break = ();
+
+    fn pre_visit_relation(&mut self, relation: &ObjectName) -> ControlFlow<Self::Break> {
+        self.relations.push(relation.clone());
+        ControlFlow::Continue(())
+    }
+}
+
+/// Order nodes so that each node comes after its dependencies, picking the smallest ready node first.
+/// When no node is ready because of cycles, the smallest remaining node in a cycle is picked.
+fn topological_order(dependencies: &[Vec<usize>], cycles: &[Vec<usize>]) -> Vec<usize> {
+    let mut emitted = vec![false; dependencies.len()];
+    let mut order = Vec::with_capacity(dependencies.len());
+    while order.len() < dependencies.len() {
+        let next = (0..dependencies.len())
+            .find(|node| !emitted[*node] && dependencies[*node].iter().all(|dep| emitted[*dep]))
+            .or_else(|| {
+                cycles
+                    .iter()
+                    .flatten()
+                    .filter(|node| !emitted[**node])
+                    .min()
+                    .copied()
+            })
+            .expect("Remaining nodes without ready ones should be in a cycle");
+        emitted[next] = true;
+        order.push(next);
+    }
+    order
+}
+
+/// Strongly connected components with more than one node, found by Tarjan's algorithm.
+fn cycles(dependencies: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    struct Tarjan<'a> {
+        dependencies: &'a [Vec<usize>],
+        index: usize,
+        indices: Vec<Option<usize>>,
+        low_links: Vec<usize>,
+        stack: Vec<usize>,
+        on_stack: Vec<bool>,
+        components: Vec<Vec<usize>>,
+    }
+
+    impl Tarjan<'_> {
+        fn connect(&mut self, node: usize) {
+            self.indices[node] = Some(self.index);
+            self.low_links[node] = self.index;
+            self.index += 1;
+            self.stack.push(node);
+            self.on_stack[node] = true;
+            let dependencies = self.dependencies;
+            for &dep in &dependencies[node] {
+                match self.indices[dep] {
+                    None => {
+                        self.connect(dep);
+                        self.low_links[node] = self.low_links[node].min(self.low_links[dep]);
+                    }
+                    Some(index) if self.on_stack[dep] => {
+                        self.low_links[node] = self.low_links[node].min(index);
+                    }
+                    _ => {}
+                }
+            }
+            if Some(self.low_links[node]) == self.indices[node] {
+                let mut component = vec![];
+                while let Some(member) = self.stack.pop() {
+                    self.on_stack[member] = false;
+                    component.push(member);
+                    if member == node {
+                        break;
+                    }
+                }
+                if component.len() > 1 {
+                    component.sort_unstable();
+                    self.components.push(component);
+                }
+            }
+        }
+    }
+
+    let mut tarjan = Tarjan {
+        dependencies,
+        index: 0,
+        indices: vec![None; dependencies.len()],
+        low_links: vec![0; dependencies.len()],
+        stack: vec![],
+        on_stack: vec![false; dependencies.len()],
+        components: vec![],
+    };
+    for node in 0..dependencies.len() {
+        if tarjan.indices[node].is_none() {
+            tarjan.connect(node);
+        }
+    }
+    tarjan.components.sort();
+    tarjan.components
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+
+    fn assert_ddl_order(sql: &str, expected: DdlOrder, dialects: Vec<Box<dyn Dialect>>) {
+        for dialect in dialects {
+            let result = order_ddl(dialect.as_ref(), sql).unwrap();
+            assert_eq!(result, expected, "Failed for dialect: {dialect:?}");
+        }
+    }
+
+    #[test]
+    fn test_order_by_foreign_keys_and_views() {
+        let sql =
+            "CREATE VIEW v1 AS SELECT * FROM orders JOIN users ON orders.user_id = users.id; \
+            CREATE TABLE orders (id INT, user_id INT REFERENCES users (id)); \
+            CREATE INDEX i1 ON orders (user_id); \
+            CREATE TABLE users (id INT PRIMARY KEY)";
+        assert_ddl_order(
+            sql,
+            DdlOrder {
+                order: vec![3, 1, 0, 2],
+                cycles: vec![],
+            },
+            all_dialects(),
+        );
+    }
+
+    #[test]
+    fn test_order_with_table_constraints_and_case() {
+        let sql = "CREATE TABLE items (id INT, order_id INT, FOREIGN KEY (order_id) REFERENCES Orders (id)); \
+            CREATE TABLE t1 AS SELECT * FROM items; \
+            CREATE TABLE orders (id INT)";
+        assert_ddl_order(
+            sql,
+            DdlOrder {
+                order: vec![2, 0, 1],
+                cycles: vec![],
+            },
+            all_dialects(),
+        );
+    }
+
+    #[test]
+    fn test_cycles() {
+        let sql = "CREATE TABLE c (a_id INT REFERENCES a (id)); \
+            CREATE TABLE a (id INT, b_id INT, FOREIGN KEY (b_id) REFERENCES b (id)); \
+            CREATE TABLE b (id INT, a_id INT, FOREIGN KEY (a_id) REFERENCES a (id)); \
+            CREATE TABLE d (id INT, parent_id INT REFERENCES d (id))";
+        assert_ddl_order(
+            sql,
+            DdlOrder {
+                order: vec![3, 1, 0, 2],
+                cycles: vec![vec![1, 2]],
+            },
+            all_dialects(),
+        );
+    }
+
+    #[test]
+    fn test_view_dependencies() {
+        let sql = "CREATE VIEW s1.v1 (a) AS SELECT x.a FROM t1 AS x JOIN s2.t2 ON x.id = t2.id; \
+            CREATE MATERIALIZED VIEW v2 AS WITH c AS (SELECT a FROM t1) SELECT a FROM c UNION SELECT a FROM t3; \
+            CREATE TABLE t4 AS SELECT * FROM t1 WHERE a IN (SELECT a FROM t1 AS y); \
+            CREATE TABLE t5 (a INT); \
+            SELECT * FROM v2";
+        let table = |name: &str| name.parse::<TableReference>().unwrap();
+        for dialect in all_dialects() {
+            let result = extract_view_dependencies(dialect.as_ref(), sql).unwrap();
+            assert_eq!(
+                result,
+                vec![
+                    Ok(Some(ViewDependency {
+                        kind: ViewKind::View,
+                        object: table("s1.v1"),
+                        sources: vec![table("t1"), table("s2.t2")],
+                    })),
+                    Ok(Some(ViewDependency {
+                        kind: ViewKind::MaterializedView,
+                        object: table("v2"),
+                        sources: vec![table("t1"), table("t3")],
+                    })),
+                    Ok(Some(ViewDependency {
+                        kind: ViewKind::TableAsSelect,
+                        object: table("t4"),
+                        sources: vec![table("t1")],
+                    })),
+                    Ok(None),
+                    Ok(None),
+                ],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_view_dependencies_with_too_many_identifiers() {
+        for dialect in all_dialects() {
+            let result = extract_view_dependencies(
+                dialect.as_ref(),
+                "CREATE VIEW v1 AS SELECT * FROM a.b.c.d",
+            )
+            .unwrap();
+            assert_eq!(
+                result,
+                vec![Err(Error::AnalysisError(
+                    "Too many identifiers provided".to_string()
+                ))],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+}
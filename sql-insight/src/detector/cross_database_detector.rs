@@ -0,0 +1,249 @@
+//! A Detector that finds statements referencing tables of more than one catalog or schema.
+//!
+//! See [`detect_cross_database_references`](crate::detect_cross_database_references()) as the entry point
+//! for detecting cross-database references in SQL.
+
+use core::fmt;
+
+use crate::error::Error;
+use crate::extractor::table_extractor::{TableExtractor, TableReference};
+use crate::instrument;
+use sqlparser::ast::{Ident, Statement};
+use sqlparser::dialect::Dialect;
+
+/// Convenience function to detect cross-database references in SQL, for each statement in input order.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+///
+/// let dialect = GenericDialect {};
+/// let sql = "SELECT * FROM db1.users AS u JOIN db2.orders AS o ON u.id = o.user_id; \
+///     SELECT * FROM db1.users JOIN db1.orders ON users.id = orders.user_id";
+/// let result = sql_insight::detect_cross_database_references(&dialect, sql).unwrap();
+/// let reference = result[0].as_ref().unwrap();
+/// assert_eq!(reference.pairs[0].0.to_string(), "db1");
+/// assert_eq!(reference.pairs[0].1.to_string(), "db2");
+/// assert_eq!(reference.namespaces[1].tables[0].to_string(), "db2.orders");
+/// assert_eq!(result[1], None);
+/// ```
+pub fn detect_cross_database_references(
+    dialect: &dyn Dialect,
+    sql: &str,
+) -> Result<Vec<Option<CrossDatabaseReference>>, Error> {
+    let statements = instrument::parse_sql(dialect, sql)?;
+    statements
+        .iter()
+        .map(CrossDatabaseDetector::detect_from_statement)
+        .collect()
+}
+
+/// [`Namespace`] represents the catalog and schema qualifying a table, which is a database in dialects such as MySQL.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Namespace {
+    pub catalog: Option<Ident>,
+    pub schema: Ident,
+}
+
+impl fmt::Display for Namespace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.catalog {
+            Some(catalog) => write!(f, "{}.{}", catalog, self.schema),
+            None => write!(f, "{}", self.schema),
+        }
+    }
+}
+
+/// [`NamespaceTables`] represents a namespace and the tables of a statement in it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NamespaceTables {
+    pub namespace: Namespace,
+    /// Tables without aliases, in order of first reference.
+    pub tables: Vec<TableReference>,
+}
+
+/// [`CrossDatabaseReference`] represents a statement referencing tables of more than one namespace.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CrossDatabaseReference {
+    /// Referenced namespaces, in order of first reference.
+    pub namespaces: Vec<NamespaceTables>,
+    /// Every pair of distinct referenced namespaces, in order of first reference.
+    pub pairs: Vec<(Namespace, Namespace)>,
+    /// Tables without qualifiers, which are in the default namespace of the connection and may or may not be
+    /// one of the referenced namespaces.
+    pub unqualified_tables: Vec<TableReference>,
+}
+
+/// A detector of statements referencing tables of more than one catalog or schema, e.g. `db1.t JOIN db2.t`,
+/// which cannot run once the databases are split onto separate clusters.
+///
+/// Namespaces are compared case-insensitively unless quoted, and catalogs are compared only when both tables have them,
+/// so `c1.s1.t1` and `s1.t2` are in the same namespace. Tables without qualifiers are not attributed to any namespace,
+/// since the default namespace is unknown from SQL alone.
+#[derive(Default, Debug)]
+pub struct CrossDatabaseDetector;
+
+impl CrossDatabaseDetector {
+    /// Detect a cross-database reference in a statement, or `None` if it references at most one namespace.
+    pub fn detect_from_statement(
+        statement: &Statement,
+    ) -> Result<Option<CrossDatabaseReference>, Error> {
+        let tables = TableExtractor::extract_from_statement(statement)?.0;
+        let mut namespaces: Vec<NamespaceTables> = vec![];
+        let mut unqualified_tables = vec![];
+        for table in tables {
+            let table = TableReference {
+                alias: None,
+                ..table
+            };
+            let Some(schema) = table.schema.clone() else {
+                if !unqualified_tables.contains(&table) {
+                    unqualified_tables.push(table);
+                }
+                continue;
+            };
+            let namespace = Namespace {
+                catalog: table.catalog.clone(),
+                schema,
+            };
+            match namespaces
+                .iter_mut()
+                .find(|n| same_namespace(&n.namespace, &namespace))
+            {
+                Some(n) if n.tables.contains(&table) => {}
+                Some(n) => n.tables.push(table),
+                None => namespaces.push(NamespaceTables {
+                    namespace,
+                    tables: vec![table],
+                }),
+            }
+        }
+        if namespaces.len() < 2 {
+            return Ok(None);
+        }
+        let mut pairs = vec![];
+        for (i, left) in namespaces.iter().enumerate() {
+            for right in &namespaces[i + 1..] {
+                pairs.push((left.namespace.clone(), right.namespace.clone()));
+            }
+        }
+        Ok(Some(CrossDatabaseReference {
+            namespaces,
+            pairs,
+            unqualified_tables,
+        }))
+    }
+}
+
+fn same_namespace(a: &Namespace, b: &Namespace) -> bool {
+    let catalogs_match = match (&a.catalog, &b.catalog) {
+        (Some(a), Some(b)) => same_ident(a, b),
+        _ => true,
+    };
+    catalogs_match && same_ident(&a.schema, &b.schema)
+}
+
+fn same_ident(a: &Ident, b: &Ident) -> bool {
+    match (a.quote_style, b.quote_style) {
+        (None, None) => a.value.eq_ignore_ascii_case(&b.value),
+        _ => a.value == b.value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+
+    fn assert_cross_database_pairs(
+        sql: &str,
+        expected: Vec<Vec<(&str, &str)>>,
+        dialects: Vec<Box<dyn Dialect>>,
+    ) {
+        for dialect in dialects {
+            let result = detect_cross_database_references(dialect.as_ref(), sql)
+                .unwrap()
+                .into_iter()
+                .map(|reference| {
+                    reference
+                        .map(|r| {
+                            r.pairs
+                                .iter()
+                                .map(|(a, b)| (a.to_string(), b.to_string()))
+                                .collect::<Vec<_>>()
+                        })
+                        .unwrap_or_default()
+                })
+                .collect::<Vec<_>>();
+            let expected = expected
+                .iter()
+                .map(|pairs| {
+                    pairs
+                        .iter()
+                        .map(|(a, b)| (a.to_string(), b.to_string()))
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(result, expected, "Failed for dialect: {dialect:?}");
+        }
+    }
+
+    #[test]
+    fn test_cross_database_pairs() {
+        let sql = "SELECT * FROM db1.t1 JOIN db2.t2 ON t1.id = t2.id; \
+            INSERT INTO db3.t1 SELECT * FROM db1.t1 WHERE a IN (SELECT a FROM db2.t2); \
+            SELECT * FROM db1.t1 JOIN DB1.t2 ON t1.id = t2.id; \
+            SELECT * FROM db1.t1 JOIN t2 ON t1.id = t2.id";
+        assert_cross_database_pairs(
+            sql,
+            vec![
+                vec![("db1", "db2")],
+                vec![("db3", "db1"), ("db3", "db2"), ("db1", "db2")],
+                vec![],
+                vec![],
+            ],
+            all_dialects(),
+        );
+    }
+
+    #[test]
+    fn test_catalogs_and_unqualified_tables() {
+        let sql = "SELECT * FROM c1.s1.t1 JOIN s1.t2 ON t1.id = t2.id JOIN c2.s1.t3 ON t1.id = t3.id JOIN t4 ON t1.id = t4.id";
+        for dialect in all_dialects() {
+            let result = detect_cross_database_references(dialect.as_ref(), sql)
+                .unwrap()
+                .remove(0)
+                .unwrap();
+            let namespaces = result
+                .namespaces
+                .iter()
+                .map(|n| {
+                    (
+                        n.namespace.to_string(),
+                        n.tables.iter().map(|t| t.to_string()).collect::<Vec<_>>(),
+                    )
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(
+                namespaces,
+                vec![
+                    (
+                        "c1.s1".to_string(),
+                        vec!["c1.s1.t1".to_string(), "s1.t2".to_string()]
+                    ),
+                    ("c2.s1".to_string(), vec!["c2.s1.t3".to_string()]),
+                ],
+                "Failed for dialect: {dialect:?}"
+            );
+            assert_eq!(
+                result.unqualified_tables[0].to_string(),
+                "t4",
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,546 @@
+//! A Detector that finds destructive changes in migrations, such as dropped tables and narrowed column types.
+//!
+//! See [`detect_destructive_changes`](crate::detect_destructive_changes()) as the entry point for detecting destructive changes in SQL.
+
+use std::collections::HashMap;
+
+use crate::error::Error;
+use crate::instrument;
+use sqlparser::ast::{
+    AlterColumnOperation, AlterTableOperation, ColumnDef, ColumnOption, DataType, Ident,
+    ObjectName, ObjectType, Statement,
+};
+use sqlparser::dialect::Dialect;
+
+/// Convenience function to detect destructive changes in SQL, for each statement in input order.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+/// use sql_insight::DestructiveChangeKind;
+///
+/// let dialect = GenericDialect {};
+/// let sql = "CREATE TABLE t1 (a VARCHAR(255)); \
+///     ALTER TABLE t1 ALTER COLUMN a SET DATA TYPE VARCHAR(10); \
+///     ALTER TABLE t1 ADD COLUMN b INT NOT NULL; \
+///     DROP TABLE t2";
+/// let changes = sql_insight::detect_destructive_changes(&dialect, sql).unwrap();
+/// let kinds: Vec<Vec<_>> = changes.iter().map(|c| c.iter().map(|c| c.kind).collect()).collect();
+/// assert_eq!(kinds, [
+///     vec![],
+///     vec![DestructiveChangeKind::NarrowingTypeChange],
+///     vec![DestructiveChangeKind::NotNullWithoutDefault],
+///     vec![DestructiveChangeKind::DropTable],
+/// ]);
+/// ```
+pub fn detect_destructive_changes(
+    dialect: &dyn Dialect,
+    sql: &str,
+) -> Result<Vec<Vec<DestructiveChange>>, Error> {
+    let statements = instrument::parse_sql(dialect, sql)?;
+    let mut detector = DestructiveChangeDetector::new();
+    Ok(statements
+        .iter()
+        .map(|statement| detector.detect_from_statement(statement))
+        .collect())
+}
+
+/// [`DestructiveChangeKind`] represents the kind of a [`DestructiveChange`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum DestructiveChangeKind {
+    /// `DROP TABLE`, deleting the table with its data.
+    DropTable,
+    /// `ALTER TABLE ... DROP COLUMN`, deleting the column with its data.
+    DropColumn,
+    /// A column type change to a narrower type, e.g. `VARCHAR(255)` to `VARCHAR(10)`, `BIGINT` to `INT`,
+    /// or `INT` to a type too small for every integer it holds, such as `DECIMAL(5,0)`, `VARCHAR(5)` or `REAL`,
+    /// which may truncate, round or reject existing values.
+    NarrowingTypeChange,
+    /// `TRUNCATE`, deleting all rows of the table.
+    Truncate,
+    /// A column added as `NOT NULL` without `DEFAULT`, or an existing column set `NOT NULL`,
+    /// which fails on tables having rows, or rows with NULL.
+    NotNullWithoutDefault,
+}
+
+/// [`DestructiveChange`] represents a destructive change found in a statement.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DestructiveChange {
+    pub kind: DestructiveChangeKind,
+    pub table: ObjectName,
+    /// The column changed, for changes of columns.
+    pub column: Option<Ident>,
+    pub description: String,
+}
+
+/// A detector of destructive changes in statements, fed in the order they are applied.
+///
+/// Whether a type change narrows the type can only be told when the previous type of the column is known.
+/// The detector tracks column types defined by `CREATE TABLE` and `ALTER TABLE` of the statements it has seen,
+/// so feed the statements defining the current schema to [`DestructiveChangeDetector::observe`] before detecting
+/// changes of a migration that doesn't create the tables it alters.
+#[derive(Default, Debug)]
+pub struct DestructiveChangeDetector {
+    column_types: HashMap<String, HashMap<String, DataType>>,
+}
+
+impl DestructiveChangeDetector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Track the column types a statement defines, without detecting changes.
+    pub fn observe(&mut self, statement: &Statement) {
+        let _ = self.detect_from_statement(statement);
+    }
+
+    /// Detect destructive changes in a statement, and track the column types it defines.
+    pub fn detect_from_statement(&mut self, statement: &Statement) -> Vec<DestructiveChange> {
+        let mut changes = vec![];
+        match statement {
+            Statement::CreateTable { name, columns, .. } => {
+                let column_types = self.column_types.entry(key(&name.0)).or_default();
+                column_types.clear();
+                for column in columns {
+                    column_types.insert(
+                        key(std::slice::from_ref(&column.name)),
+                        column.data_type.clone(),
+                    );
+                }
+            }
+            Statement::Drop {
+                object_type: ObjectType::Table,
+                names,
+                ..
+            } => {
+                for name in names {
+                    self.column_types.remove(&key(&name.0));
+                    changes.push(DestructiveChange {
+                        kind: DestructiveChangeKind::DropTable,
+                        table: name.clone(),
+                        column: None,
+                        description: format!("DROP TABLE deletes table {} and its data", name),
+                    });
+                }
+            }
+            Statement::Truncate { table_name, .. } => changes.push(DestructiveChange {
+                kind: DestructiveChangeKind::Truncate,
+                table: table_name.clone(),
+                column: None,
+                description: format!("TRUNCATE deletes all rows of table {}", table_name),
+            }),
+            Statement::AlterTable {
+                name, operations, ..
+            } => {
+                for operation in operations {
+                    self.inspect_alter_table(name, operation, &mut changes);
+                }
+            }
+            _ => {}
+        }
+        changes
+    }
+
+    fn inspect_alter_table(
+        &mut self,
+        table: &ObjectName,
+        operation: &AlterTableOperation,
+        changes: &mut Vec<DestructiveChange>,
+    ) {
+        let column_types = self.column_types.entry(key(&table.0)).or_default();
+        let change = |kind, column: &Ident, description| DestructiveChange {
+            kind,
+            table: table.clone(),
+            column: Some(column.clone()),
+            description,
+        };
+        match operation {
+            AlterTableOperation::DropColumn { column_name, .. } => {
+                column_types.remove(&key(std::slice::from_ref(column_name)));
+                changes.push(change(
+                    DestructiveChangeKind::DropColumn,
+                    column_name,
+                    format!(
+                        "DROP COLUMN deletes column {}.{} and its data",
+                        table, column_name
+                    ),
+                ));
+            }
+            AlterTableOperation::AddColumn { column_def, .. } => {
+                column_types.insert(
+                    key(std::slice::from_ref(&column_def.name)),
+                    column_def.data_type.clone(),
+                );
+                if is_not_null_without_default(column_def) {
+                    changes.push(change(
+                        DestructiveChangeKind::NotNullWithoutDefault,
+                        &column_def.name,
+                        format!(
+                            "Column {}.{} is added as NOT NULL without DEFAULT, which fails if the table has rows",
+                            table, column_def.name
+                        ),
+                    ));
+                }
+            }
+            AlterTableOperation::AlterColumn { column_name, op } => match op {
+                AlterColumnOperation::SetDataType { data_type, .. } => {
+                    let previous = column_types
+                        .insert(key(std::slice::from_ref(column_name)), data_type.clone());
+                    if let Some(previous) = previous.filter(|previous| narrows(previous, data_type))
+                    {
+                        changes.push(change(
+                            DestructiveChangeKind::NarrowingTypeChange,
+                            column_name,
+                            format!(
+                                "Type of column {}.{} is narrowed from {} to {}, which may truncate or reject existing values",
+                                table, column_name, previous, data_type
+                            ),
+                        ));
+                    }
+                }
+                AlterColumnOperation::SetNotNull => changes.push(change(
+                    DestructiveChangeKind::NotNullWithoutDefault,
+                    column_name,
+                    format!(
+                        "Column {}.{} is set NOT NULL, which fails if it has NULL values",
+                        table, column_name
+                    ),
+                )),
+                _ => {}
+            },
+            AlterTableOperation::ChangeColumn {
+                old_name,
+                new_name,
+                data_type,
+                ..
+            } => {
+                let previous = column_types.remove(&key(std::slice::from_ref(old_name)));
+                column_types.insert(key(std::slice::from_ref(new_name)), data_type.clone());
+                if let Some(previous) = previous.filter(|previous| narrows(previous, data_type)) {
+                    changes.push(change(
+                        DestructiveChangeKind::NarrowingTypeChange,
+                        old_name,
+                        format!(
+                            "Type of column {}.{} is narrowed from {} to {}, which may truncate or reject existing values",
+                            table, old_name, previous, data_type
+                        ),
+                    ));
+                }
+            }
+            AlterTableOperation::RenameColumn {
+                old_column_name,
+                new_column_name,
+            } => {
+                if let Some(data_type) =
+                    column_types.remove(&key(std::slice::from_ref(old_column_name)))
+                {
+                    column_types.insert(key(std::slice::from_ref(new_column_name)), data_type);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Key of a name, lowercasing unquoted identifiers.
+fn key(idents: &[Ident]) -> String {
+    idents
+        .iter()
+        .map(|ident| match ident.quote_style {
+            Some(_) => ident.value.clone(),
+            None => ident.value.to_lowercase(),
+        })
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+fn is_not_null_without_default(column_def: &ColumnDef) -> bool {
+    let options = || column_def.options.iter().map(|option| &option.option);
+    options().any(|option| matches!(option, ColumnOption::NotNull))
+        && !options().any(|option| matches!(option, ColumnOption::Default(_)))
+}
+
+/// Class of a data type, telling which values it can hold.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum TypeClass {
+    /// Integer of the size in bytes.
+    Integer(u64),
+    /// Floating point number of the size in bytes.
+    Float(u64),
+    Decimal {
+        precision: Option<u64>,
+        scale: u64,
+    },
+    /// Character string of the maximum length, or `None` if unbounded.
+    Character(Option<u64>),
+}
+
+/// Number of decimal digits of the largest integer of the size in bytes.
+fn digits(bytes: u64) -> u64 {
+    match bytes {
+        1 => 3,
+        2 => 5,
+        3 => 7,
+        4 => 10,
+        _ => 19,
+    }
+}
+
+/// Number of bits of the significand of a floating point number of the size in bytes,
+/// i.e. the size of integers it holds exactly.
+fn mantissa_bits(bytes: u64) -> u64 {
+    if bytes > 4 {
+        53
+    } else {
+        24
+    }
+}
+
+/// Classify a data type by its SQL representation, e.g. `VARCHAR(255)` or `DECIMAL(10,2)`.
+/// Types not telling which values they hold, such as `DATE` or `JSON`, are not classified.
+fn classify(data_type: &DataType) -> Option<TypeClass> {
+    let data_type = data_type.to_string().to_uppercase();
+    let (name, args) = match data_type.split_once('(') {
+        Some((name, rest)) => (name.trim(), rest.split(')').next().unwrap_or_default()),
+        None => (data_type.as_str(), ""),
+    };
+    let name = name.trim_end_matches(" UNSIGNED");
+    let args = args
+        .split(',')
+        .map(|arg| arg.trim().split(' ').next().unwrap_or_default())
+        .collect::<Vec<_>>();
+    let number = |i: usize| args.get(i).and_then(|arg| arg.parse::<u64>().ok());
+    let class = match name {
+        "TINYINT" => TypeClass::Integer(1),
+        "SMALLINT" | "INT2" => TypeClass::Integer(2),
+        "MEDIUMINT" => TypeClass::Integer(3),
+        "INT" | "INTEGER" | "INT4" => TypeClass::Integer(4),
+        "BIGINT" | "INT8" | "INT64" => TypeClass::Integer(8),
+        "REAL" | "FLOAT4" => TypeClass::Float(4),
+        "FLOAT" => TypeClass::Float(if number(0).is_some_and(|p| p > 24) {
+            8
+        } else {
+            4
+        }),
+        "DOUBLE" | "DOUBLE PRECISION" | "FLOAT8" | "FLOAT64" => TypeClass::Float(8),
+        "DECIMAL" | "DEC" | "NUMERIC" => TypeClass::Decimal {
+            precision: number(0),
+            scale: number(1).unwrap_or_default(),
+        },
+        "CHAR" | "CHARACTER" | "NCHAR" => TypeClass::Character(number(0).or(Some(1))),
+        "VARCHAR" | "CHARACTER VARYING" | "NVARCHAR" | "STRING" => TypeClass::Character(number(0)),
+        "TEXT" | "CLOB" | "TINYTEXT" | "MEDIUMTEXT" | "LONGTEXT" => TypeClass::Character(None),
+        _ => return None,
+    };
+    Some(class)
+}
+
+/// Whether changing a column from the type `from` to the type `to` may lose or reject values.
+fn narrows(from: &DataType, to: &DataType) -> bool {
+    let (Some(from), Some(to)) = (classify(from), classify(to)) else {
+        return false;
+    };
+    match (from, to) {
+        (TypeClass::Integer(from), TypeClass::Integer(to))
+        | (TypeClass::Float(from), TypeClass::Float(to)) => to < from,
+        (
+            TypeClass::Decimal {
+                precision: from_precision,
+                scale: from_scale,
+            },
+            TypeClass::Decimal {
+                precision: to_precision,
+                scale: to_scale,
+            },
+        ) => {
+            let precision_narrowed = match (from_precision, to_precision) {
+                (Some(from), Some(to)) => to - to_scale.min(to) < from - from_scale.min(from),
+                (None, Some(_)) => true,
+                _ => false,
+            };
+            to_scale < from_scale || precision_narrowed
+        }
+        (TypeClass::Float(_), TypeClass::Integer(_)) => true,
+        (TypeClass::Integer(bytes), TypeClass::Float(to)) => bytes * 8 - 1 > mantissa_bits(to),
+        (TypeClass::Integer(bytes), TypeClass::Decimal { precision, scale }) => {
+            precision.is_some_and(|precision| precision.saturating_sub(scale) < digits(bytes))
+        }
+        (TypeClass::Integer(bytes), TypeClass::Character(length)) => {
+            // One more character for the sign.
+            length.is_some_and(|length| length < digits(bytes) + 1)
+        }
+        (TypeClass::Decimal { scale, .. }, TypeClass::Integer(_)) => scale > 0,
+        (TypeClass::Character(from), TypeClass::Character(to)) => match (from, to) {
+            (Some(from), Some(to)) => to < from,
+            (None, Some(_)) => true,
+            _ => false,
+        },
+        (TypeClass::Character(_), _) => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+    use sqlparser::parser::Parser;
+
+    fn assert_destructive_changes(
+        sql: &str,
+        expected: Vec<Vec<(DestructiveChangeKind, &str, Option<&str>)>>,
+        dialects: Vec<Box<dyn Dialect>>,
+    ) {
+        for dialect in dialects {
+            let result = detect_destructive_changes(dialect.as_ref(), sql)
+                .unwrap()
+                .into_iter()
+                .map(|changes| {
+                    changes
+                        .into_iter()
+                        .map(|c| (c.kind, c.table.to_string(), c.column.map(|c| c.value)))
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>();
+            let expected = expected
+                .iter()
+                .map(|changes| {
+                    changes
+                        .iter()
+                        .map(|(kind, table, column)| {
+                            (*kind, table.to_string(), column.map(String::from))
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(result, expected, "Failed for dialect: {dialect:?}");
+        }
+    }
+
+    #[test]
+    fn test_drops_and_truncate() {
+        let sql =
+            "DROP TABLE t1, t2; DROP VIEW v1; TRUNCATE TABLE t3; ALTER TABLE t4 DROP COLUMN a";
+        assert_destructive_changes(
+            sql,
+            vec![
+                vec![
+                    (DestructiveChangeKind::DropTable, "t1", None),
+                    (DestructiveChangeKind::DropTable, "t2", None),
+                ],
+                vec![],
+                vec![(DestructiveChangeKind::Truncate, "t3", None)],
+                vec![(DestructiveChangeKind::DropColumn, "t4", Some("a"))],
+            ],
+            all_dialects(),
+        );
+    }
+
+    #[test]
+    fn test_not_null_without_default() {
+        let sql = "ALTER TABLE t1 ADD COLUMN a INT NOT NULL; \
+            ALTER TABLE t1 ADD COLUMN b INT NOT NULL DEFAULT 0; \
+            ALTER TABLE t1 ADD COLUMN c INT; \
+            ALTER TABLE t1 ALTER COLUMN c SET NOT NULL";
+        assert_destructive_changes(
+            sql,
+            vec![
+                vec![(
+                    DestructiveChangeKind::NotNullWithoutDefault,
+                    "t1",
+                    Some("a"),
+                )],
+                vec![],
+                vec![],
+                vec![(
+                    DestructiveChangeKind::NotNullWithoutDefault,
+                    "t1",
+                    Some("c"),
+                )],
+            ],
+            all_dialects(),
+        );
+    }
+
+    #[test]
+    fn test_narrowing_type_change() {
+        let sql = "CREATE TABLE t1 (a VARCHAR(255), b BIGINT, c DECIMAL(10,2), d INT, e TEXT); \
+            ALTER TABLE T1 ALTER COLUMN a SET DATA TYPE VARCHAR(10); \
+            ALTER TABLE t1 ALTER COLUMN a SET DATA TYPE VARCHAR(20); \
+            ALTER TABLE t1 ALTER COLUMN b SET DATA TYPE INT; \
+            ALTER TABLE t1 ALTER COLUMN c SET DATA TYPE DECIMAL(12,2); \
+            ALTER TABLE t1 ALTER COLUMN c SET DATA TYPE DECIMAL(12,1); \
+            ALTER TABLE t1 ALTER COLUMN d SET DATA TYPE TEXT; \
+            ALTER TABLE t1 ALTER COLUMN e SET DATA TYPE INT; \
+            ALTER TABLE t2 ALTER COLUMN a SET DATA TYPE INT";
+        assert_destructive_changes(
+            sql,
+            vec![
+                vec![],
+                vec![(DestructiveChangeKind::NarrowingTypeChange, "T1", Some("a"))],
+                vec![],
+                vec![(DestructiveChangeKind::NarrowingTypeChange, "t1", Some("b"))],
+                vec![],
+                vec![(DestructiveChangeKind::NarrowingTypeChange, "t1", Some("c"))],
+                vec![],
+                vec![(DestructiveChangeKind::NarrowingTypeChange, "t1", Some("e"))],
+                vec![],
+            ],
+            all_dialects(),
+        );
+    }
+
+    #[test]
+    fn test_narrowing_type_change_of_integer() {
+        let sql = "CREATE TABLE t1 (a INT, b INT, c INT, d INT, e BIGINT, f BIGINT, g SMALLINT); \
+            ALTER TABLE t1 ALTER COLUMN a SET DATA TYPE DECIMAL(5,0); \
+            ALTER TABLE t1 ALTER COLUMN b SET DATA TYPE DECIMAL(12,2); \
+            ALTER TABLE t1 ALTER COLUMN c SET DATA TYPE VARCHAR(5); \
+            ALTER TABLE t1 ALTER COLUMN d SET DATA TYPE VARCHAR(11); \
+            ALTER TABLE t1 ALTER COLUMN e SET DATA TYPE FLOAT(4); \
+            ALTER TABLE t1 ALTER COLUMN f SET DATA TYPE DOUBLE PRECISION; \
+            ALTER TABLE t1 ALTER COLUMN g SET DATA TYPE REAL";
+        assert_destructive_changes(
+            sql,
+            vec![
+                vec![],
+                vec![(DestructiveChangeKind::NarrowingTypeChange, "t1", Some("a"))],
+                vec![],
+                vec![(DestructiveChangeKind::NarrowingTypeChange, "t1", Some("c"))],
+                vec![],
+                vec![(DestructiveChangeKind::NarrowingTypeChange, "t1", Some("e"))],
+                vec![(DestructiveChangeKind::NarrowingTypeChange, "t1", Some("f"))],
+                vec![],
+            ],
+            all_dialects(),
+        );
+    }
+
+    #[test]
+    fn test_observe_schema() {
+        let dialect = sqlparser::dialect::GenericDialect {};
+        let mut detector = DestructiveChangeDetector::new();
+        for statement in Parser::parse_sql(&dialect, "CREATE TABLE t1 (a SMALLINT)").unwrap() {
+            detector.observe(&statement);
+        }
+        let statement = Parser::parse_sql(
+            &dialect,
+            "ALTER TABLE t1 ALTER COLUMN a SET DATA TYPE TINYINT",
+        )
+        .unwrap()
+        .remove(0);
+        assert_eq!(
+            detector.detect_from_statement(&statement),
+            vec![DestructiveChange {
+                kind: DestructiveChangeKind::NarrowingTypeChange,
+                table: ObjectName(vec![Ident::new("t1")]),
+                column: Some(Ident::new("a")),
+                description: "Type of column t1.a is narrowed from SMALLINT to TINYINT, which may truncate or reject existing values".into(),
+            }]
+        );
+    }
+}
@@ -0,0 +1,329 @@
+//! A Detector that finds UPDATE and DELETE statements likely to write every row of their tables.
+//!
+//! See [`detect_full_table_writes`](crate::detect_full_table_writes()) as the entry point for detecting full-table writes in SQL.
+
+use std::ops::ControlFlow;
+
+use crate::error::Error;
+use crate::extractor::crud_table_extractor::CrudTableExtractor;
+use crate::extractor::table_extractor::{TableExtractor, TableReference};
+use crate::helper;
+use crate::instrument;
+use sqlparser::ast::{Expr, Ident, Query, Statement, TableWithJoins, Visit, Visitor};
+use sqlparser::dialect::Dialect;
+
+/// Convenience function to detect full-table writes in SQL, for each statement in input order.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+/// use sql_insight::FullTableWriteReason;
+///
+/// let dialect = GenericDialect {};
+/// let sql = "DELETE FROM t1; \
+///     UPDATE t1 SET a = 1 WHERE EXISTS (SELECT 1 FROM t2 WHERE t2.b = 0); \
+///     UPDATE t1 SET a = 1 WHERE id = 1";
+/// let result = sql_insight::detect_full_table_writes(&dialect, sql).unwrap();
+/// let reasons: Vec<Vec<_>> = result.iter().map(|w| w.iter().map(|w| w.reason).collect()).collect();
+/// assert_eq!(reasons, [
+///     vec![FullTableWriteReason::NoPredicate],
+///     vec![FullTableWriteReason::PredicateNotOnTable],
+///     vec![],
+/// ]);
+/// ```
+pub fn detect_full_table_writes(
+    dialect: &dyn Dialect,
+    sql: &str,
+) -> Result<Vec<Vec<FullTableWrite>>, Error> {
+    let statements = instrument::parse_sql(dialect, sql)?;
+    statements
+        .iter()
+        .map(FullTableWriteDetector::detect_from_statement)
+        .collect()
+}
+
+/// [`WriteOperation`] represents the operation of a [`FullTableWrite`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum WriteOperation {
+    Update,
+    Delete,
+}
+
+/// [`FullTableWriteReason`] represents why a write is considered to affect every row of the table.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum FullTableWriteReason {
+    /// The statement has neither a WHERE clause nor join conditions.
+    NoPredicate,
+    /// The WHERE clause and join conditions reference no column of the table, e.g. `WHERE 1 = 1`
+    /// or an uncorrelated `WHERE EXISTS (...)`, so it holds for all rows or none.
+    PredicateNotOnTable,
+}
+
+/// [`FullTableWrite`] represents a table an UPDATE or DELETE statement likely writes every row of.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FullTableWrite {
+    pub operation: WriteOperation,
+    /// The written table, with its alias if any.
+    pub table: TableReference,
+    pub reason: FullTableWriteReason,
+}
+
+/// A detector of UPDATE and DELETE statements whose WHERE clause and join conditions are missing
+/// or don't restrict the written table.
+///
+/// A column restricts a table when it is qualified by the table's alias or name, at any depth of subqueries
+/// so that correlated subqueries count, or when it is unqualified outside subqueries of a statement
+/// reading no other table. Unqualified columns of statements reading several tables are assumed to restrict
+/// all of them, since they cannot be attributed without the schema.
+/// Unlike the `no-where-clause` lint rule, this returns structured findings for programmatic gating.
+#[derive(Default, Debug)]
+pub struct FullTableWriteDetector;
+
+impl FullTableWriteDetector {
+    /// Detect full-table writes in a statement, one for each written table.
+    pub fn detect_from_statement(statement: &Statement) -> Result<Vec<FullTableWrite>, Error> {
+        let (operation, selection, scope) = match statement {
+            Statement::Update {
+                table,
+                from,
+                selection,
+                ..
+            } => (
+                WriteOperation::Update,
+                selection,
+                std::iter::once(table).chain(from).collect::<Vec<_>>(),
+            ),
+            Statement::Delete {
+                from,
+                using,
+                selection,
+                ..
+            } => (
+                WriteOperation::Delete,
+                selection,
+                from.iter().chain(using.iter().flatten()).collect(),
+            ),
+            _ => return Ok(vec![]),
+        };
+        let crud_tables = CrudTableExtractor::extract_from_statement(statement)?;
+        let targets = match operation {
+            WriteOperation::Update => crud_tables.update_tables,
+            WriteOperation::Delete => crud_tables.delete_tables,
+        };
+        let mut predicates = vec![];
+        for table_with_joins in &scope {
+            helper::collect_join_conditions(table_with_joins, &mut predicates);
+        }
+        predicates.extend(selection);
+        if predicates.is_empty() {
+            return Ok(targets
+                .into_iter()
+                .map(|table| FullTableWrite {
+                    operation,
+                    table,
+                    reason: FullTableWriteReason::NoPredicate,
+                })
+                .collect());
+        }
+        let scope = scope_tables(&scope)?;
+        let mut collector = ColumnQualifierCollector::default();
+        for predicate in predicates {
+            let _ = predicate.visit(&mut collector);
+        }
+        if collector.has_unqualified {
+            return Ok(vec![]);
+        }
+        Ok(targets
+            .into_iter()
+            .filter(|target| {
+                !collector
+                    .qualifiers
+                    .iter()
+                    .any(|qualifier| qualifies(qualifier, target, &scope))
+            })
+            .map(|table| FullTableWrite {
+                operation,
+                table,
+                reason: FullTableWriteReason::PredicateNotOnTable,
+            })
+            .collect())
+    }
+}
+
+fn scope_tables(tables_with_joins: &[&TableWithJoins]) -> Result<Vec<TableReference>, Error> {
+    let mut tables = vec![];
+    for table_with_joins in tables_with_joins {
+        tables.extend(TableExtractor::extract_from_table_node(table_with_joins)?.0);
+    }
+    Ok(tables)
+}
+
+/// Whether `qualifier` refers to `target`, by the alias or name of the target, or of a table in scope
+/// with the same name as the target.
+fn qualifies(qualifier: &Ident, target: &TableReference, scope: &[TableReference]) -> bool {
+    std::iter::once(target)
+        .chain(scope.iter().filter(|table| {
+            table.name == target.name && (target.schema.is_none() || table.schema == target.schema)
+        }))
+        .any(|table| {
+            table.alias.as_ref().unwrap_or(&table.name).value == qualifier.value
+                || table.name.value == qualifier.value
+        })
+}
+
+/// Collects qualifiers of columns at any depth, and whether any column outside subqueries is unqualified.
+#[derive(Default)]
+struct ColumnQualifierCollector {
+    depth: usize,
+    qualifiers: Vec<Ident>,
+    has_unqualified: bool,
+}
+
+impl Visitor for ColumnQualifierCollector {
+    type Break = ();
+
+    fn pre_visit_query(&mut self, _query: &Query) -> ControlFlow<Self::Break> {
+        self.depth += 1;
+        ControlFlow::Continue(())
+    }
+
+    fn post_visit_query(&mut self, _query: &Query) -> ControlFlow<Self::Break> {
+        self.depth -= 1;
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_expr(&mut self, expr: &Expr) -> ControlFlow<Self::Break> {
+        match expr {
+            Expr::Identifier(_) if self.depth == 0 => self.has_unqualified = true,
+            Expr::CompoundIdentifier(idents) => {
+                if let [.., qualifier, _] = idents.as_slice() {
+                    self.qualifiers.push(qualifier.clone());
+                }
+            }
+            _ => {}
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+    use sqlparser::dialect::{MySqlDialect, PostgreSqlDialect};
+
+    fn assert_full_table_writes(
+        sql: &str,
+        expected: Vec<Vec<(WriteOperation, &str, FullTableWriteReason)>>,
+        dialects: Vec<Box<dyn Dialect>>,
+    ) {
+        for dialect in dialects {
+            let result = detect_full_table_writes(dialect.as_ref(), sql)
+                .unwrap()
+                .into_iter()
+                .map(|writes| {
+                    writes
+                        .into_iter()
+                        .map(|w| (w.operation, w.table.name.value, w.reason))
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>();
+            let expected = expected
+                .iter()
+                .map(|writes| {
+                    writes
+                        .iter()
+                        .map(|(operation, table, reason)| (*operation, table.to_string(), *reason))
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(result, expected, "Failed for dialect: {dialect:?}");
+        }
+    }
+
+    #[test]
+    fn test_no_predicate() {
+        let sql = "UPDATE t1 SET a = 1; DELETE FROM t2; SELECT a FROM t1";
+        assert_full_table_writes(
+            sql,
+            vec![
+                vec![(
+                    WriteOperation::Update,
+                    "t1",
+                    FullTableWriteReason::NoPredicate,
+                )],
+                vec![(
+                    WriteOperation::Delete,
+                    "t2",
+                    FullTableWriteReason::NoPredicate,
+                )],
+                vec![],
+            ],
+            all_dialects(),
+        );
+    }
+
+    #[test]
+    fn test_predicate_not_on_table() {
+        let sql = "DELETE FROM t1 WHERE 1 = 1; \
+            UPDATE t1 SET a = 1 WHERE EXISTS (SELECT 1 FROM t2 WHERE b = 0); \
+            UPDATE t1 SET a = 1 WHERE EXISTS (SELECT 1 FROM t2 WHERE t2.b = t1.b); \
+            DELETE FROM t1 WHERE b IN (SELECT b FROM t2); \
+            UPDATE t1 AS x SET a = 1 WHERE x.b > 0";
+        assert_full_table_writes(
+            sql,
+            vec![
+                vec![(
+                    WriteOperation::Delete,
+                    "t1",
+                    FullTableWriteReason::PredicateNotOnTable,
+                )],
+                vec![(
+                    WriteOperation::Update,
+                    "t1",
+                    FullTableWriteReason::PredicateNotOnTable,
+                )],
+                vec![],
+                vec![],
+                vec![],
+            ],
+            all_dialects(),
+        );
+    }
+
+    #[test]
+    fn test_multiple_tables() {
+        assert_full_table_writes(
+            "UPDATE t1 SET a = t2.a FROM t2 WHERE t2.b = 0; \
+            UPDATE t1 SET a = t2.a FROM t2 WHERE t1.id = t2.id",
+            vec![
+                vec![(
+                    WriteOperation::Update,
+                    "t1",
+                    FullTableWriteReason::PredicateNotOnTable,
+                )],
+                vec![],
+            ],
+            vec![Box::new(PostgreSqlDialect {})],
+        );
+        assert_full_table_writes(
+            "DELETE t1 FROM t1 JOIN t2 ON t1.id = t2.id WHERE t2.b = 0; \
+            DELETE t1 FROM t1 JOIN t2 ON t2.c = 1 WHERE t2.b = 0",
+            vec![
+                vec![],
+                vec![(
+                    WriteOperation::Delete,
+                    "t1",
+                    FullTableWriteReason::PredicateNotOnTable,
+                )],
+            ],
+            vec![Box::new(MySqlDialect {})],
+        );
+    }
+}
@@ -0,0 +1,13 @@
+pub mod cross_database_detector;
+pub mod destructive_change_detector;
+pub mod full_table_write_detector;
+pub mod non_sargable_detector;
+pub mod suspicious_pattern_detector;
+pub mod wildcard_detector;
+
+pub use cross_database_detector::*;
+pub use destructive_change_detector::*;
+pub use full_table_write_detector::*;
+pub use non_sargable_detector::*;
+pub use suspicious_pattern_detector::*;
+pub use wildcard_detector::*;
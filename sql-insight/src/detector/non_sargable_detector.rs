@@ -0,0 +1,244 @@
+//! A Detector that finds non-sargable predicates in SQL queries.
+//!
+//! See [`NonSargableDetector`] for details.
+
+use std::ops::ControlFlow;
+
+use crate::helper;
+use sqlparser::ast::{BinaryOperator, Expr, Query, Statement, Visit, Visitor};
+
+/// [`ColumnWrapper`] represents what a column is wrapped in.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ColumnWrapper {
+    /// A function call such as `LOWER(col)`. Holds the function name.
+    Function(String),
+    /// Arithmetic such as `col + 1`. Holds the operator.
+    Arithmetic(BinaryOperator),
+    /// A cast such as `CAST(col AS DATE)`.
+    Cast,
+}
+
+/// [`WrappedColumnPredicate`] represents a non-sargable predicate, whose column is wrapped in a function, arithmetic or cast
+/// before comparison, which commonly prevents the use of an index on the column.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WrappedColumnPredicate {
+    /// The predicate, e.g. `LOWER(name) = 'foo'`.
+    pub predicate: Expr,
+    /// The wrapped column, e.g. `name`.
+    pub column: Expr,
+    pub wrapper: ColumnWrapper,
+}
+
+/// A visitor to detect non-sargable predicates in WHERE, HAVING and join conditions,
+/// including those of subqueries and of UPDATE and DELETE statements.
+#[derive(Default, Debug)]
+pub struct NonSargableDetector {
+    predicates: Vec<WrappedColumnPredicate>,
+}
+
+impl Visitor for NonSargableDetector {
+    type Break = ();
+
+    fn pre_visit_query(&mut self, query: &Query) -> ControlFlow<Self::Break> {
+        let mut conditions = Vec::new();
+        helper::collect_conditions(&query.body, &mut conditions);
+        for condition in conditions {
+            self.inspect_condition(condition);
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_statement(&mut self, statement: &Statement) -> ControlFlow<Self::Break> {
+        let mut conditions = Vec::new();
+        helper::collect_statement_conditions(statement, &mut conditions);
+        for condition in conditions {
+            self.inspect_condition(condition);
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+impl NonSargableDetector {
+    /// Detect non-sargable predicates in a statement.
+    pub fn detect_from_statement(statement: &Statement) -> Vec<WrappedColumnPredicate> {
+        let mut visitor = NonSargableDetector::default();
+        let _ = statement.visit(&mut visitor);
+        visitor.predicates
+    }
+
+    fn inspect_condition(&mut self, expr: &Expr) {
+        match expr {
+            Expr::BinaryOp {
+                left,
+                op: BinaryOperator::And | BinaryOperator::Or,
+                right,
+            } => {
+                self.inspect_condition(left);
+                self.inspect_condition(right);
+            }
+            Expr::Nested(inner) | Expr::UnaryOp { expr: inner, .. } => {
+                self.inspect_condition(inner)
+            }
+            Expr::BinaryOp {
+                left,
+                op:
+                    BinaryOperator::Eq
+                    | BinaryOperator::NotEq
+                    | BinaryOperator::Lt
+                    | BinaryOperator::LtEq
+                    | BinaryOperator::Gt
+                    | BinaryOperator::GtEq,
+                right,
+            } => {
+                self.inspect_operand(expr, left);
+                self.inspect_operand(expr, right);
+            }
+            Expr::Between { expr: operand, .. }
+            | Expr::InList { expr: operand, .. }
+            | Expr::InSubquery { expr: operand, .. }
+            | Expr::Like { expr: operand, .. }
+            | Expr::ILike { expr: operand, .. }
+            | Expr::IsNull(operand)
+            | Expr::IsNotNull(operand) => self.inspect_operand(expr, operand),
+            _ => {}
+        }
+    }
+
+    fn inspect_operand(&mut self, predicate: &Expr, operand: &Expr) {
+        if let Some((wrapper, column)) = wrapped_column(operand) {
+            self.predicates.push(WrappedColumnPredicate {
+                predicate: predicate.clone(),
+                column,
+                wrapper,
+            });
+        }
+    }
+}
+
+fn wrapped_column(operand: &Expr) -> Option<(ColumnWrapper, Expr)> {
+    let wrapper = match operand {
+        Expr::Nested(inner) => return wrapped_column(inner),
+        Expr::Function(function) => ColumnWrapper::Function(function.name.to_string()),
+        Expr::Substring { .. } => ColumnWrapper::Function("SUBSTRING".into()),
+        Expr::Trim { .. } => ColumnWrapper::Function("TRIM".into()),
+        Expr::Extract { .. } => ColumnWrapper::Function("EXTRACT".into()),
+        Expr::Ceil { .. } => ColumnWrapper::Function("CEIL".into()),
+        Expr::Floor { .. } => ColumnWrapper::Function("FLOOR".into()),
+        Expr::BinaryOp {
+            op:
+                op @ (BinaryOperator::Plus
+                | BinaryOperator::Minus
+                | BinaryOperator::Multiply
+                | BinaryOperator::Divide
+                | BinaryOperator::Modulo
+                | BinaryOperator::StringConcat),
+            ..
+        } => ColumnWrapper::Arithmetic(op.clone()),
+        Expr::Cast { .. } | Expr::TryCast { .. } | Expr::SafeCast { .. } => ColumnWrapper::Cast,
+        _ => return None,
+    };
+    first_column(operand).map(|column| (wrapper, column))
+}
+
+fn first_column(expr: &Expr) -> Option<Expr> {
+    struct ColumnFinder;
+
+    impl Visitor for ColumnFinder {
+        type Break = Expr;
+
+        fn pre_visit_expr(&mut self, expr: &Expr) -> ControlFlow<Self::Break> {
+            match expr {
+                Expr::Identifier(_) | Expr::CompoundIdentifier(_) => {
+                    ControlFlow::Break(expr.clone())
+                }
+                _ => ControlFlow::Continue(()),
+            }
+        }
+    }
+
+    match expr.visit(&mut ColumnFinder) {
+        ControlFlow::Break(column) => Some(column),
+        ControlFlow::Continue(()) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+    use sqlparser::dialect::Dialect;
+    use sqlparser::parser::Parser;
+
+    fn detect(dialect: &dyn Dialect, sql: &str) -> Vec<(String, String, ColumnWrapper)> {
+        Parser::parse_sql(dialect, sql)
+            .unwrap()
+            .iter()
+            .flat_map(NonSargableDetector::detect_from_statement)
+            .map(|p| (p.predicate.to_string(), p.column.to_string(), p.wrapper))
+            .collect()
+    }
+
+    #[test]
+    fn test_function_on_column_in_where() {
+        let sql = "SELECT a FROM t1 WHERE LOWER(name) = 'foo' AND (b + 1 > 2 OR c = 3)";
+        for dialect in all_dialects() {
+            assert_eq!(
+                detect(dialect.as_ref(), sql),
+                vec![
+                    (
+                        "LOWER(name) = 'foo'".to_string(),
+                        "name".to_string(),
+                        ColumnWrapper::Function("LOWER".into())
+                    ),
+                    (
+                        "b + 1 > 2".to_string(),
+                        "b".to_string(),
+                        ColumnWrapper::Arithmetic(BinaryOperator::Plus)
+                    ),
+                ],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_cast_in_join_condition() {
+        let sql = "SELECT a FROM t1 INNER JOIN t2 ON CAST(t1.id AS INT) = t2.id";
+        for dialect in all_dialects() {
+            assert_eq!(
+                detect(dialect.as_ref(), sql),
+                vec![(
+                    "CAST(t1.id AS INT) = t2.id".to_string(),
+                    "t1.id".to_string(),
+                    ColumnWrapper::Cast
+                )],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_update_and_subquery() {
+        let sql = "UPDATE t1 SET a = 1 WHERE b IN (SELECT b FROM t2 WHERE UPPER(c) = 'X')";
+        for dialect in all_dialects() {
+            assert_eq!(
+                detect(dialect.as_ref(), sql),
+                vec![(
+                    "UPPER(c) = 'X'".to_string(),
+                    "c".to_string(),
+                    ColumnWrapper::Function("UPPER".into())
+                )],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_sargable_predicates() {
+        let sql =
+            "SELECT LOWER(a) FROM t1 WHERE b = LOWER('FOO') AND c > 1 + 2 AND d BETWEEN 1 AND 2";
+        for dialect in all_dialects() {
+            assert!(detect(dialect.as_ref(), sql).is_empty());
+        }
+    }
+}
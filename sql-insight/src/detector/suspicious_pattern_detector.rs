@@ -0,0 +1,392 @@
+//! A Detector that finds suspicious patterns in SQL, such as always-true predicates and UNION SELECT probes.
+//!
+//! See [`detect_suspicious_patterns`](crate::detect_suspicious_patterns()) as the entry point for detecting suspicious patterns in SQL.
+
+use std::ops::ControlFlow;
+
+use crate::error::Error;
+use crate::helper;
+use crate::instrument;
+use sqlparser::ast::{
+    BinaryOperator, Expr, Query, SelectItem, SetExpr, SetOperator, Statement, TableFactor,
+    UnaryOperator, Value, Visit, Visitor,
+};
+use sqlparser::dialect::Dialect;
+use sqlparser::parser::ParserError;
+use sqlparser::tokenizer::{Token, Tokenizer, Whitespace};
+
+/// Convenience function to detect suspicious patterns in SQL, for each statement in input order.
+///
+/// Besides the patterns found by [`SuspiciousPatternDetector`], the last statement is reported as
+/// [`SuspiciousPatternKind::CommentTerminated`] when the input ends with a single-line comment on the same line,
+/// as seen in injected input like `WHERE name = 'admin'--' AND password = ''`.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+/// use sql_insight::SuspiciousPatternKind;
+///
+/// let dialect = GenericDialect {};
+/// let sql = "SELECT * FROM users WHERE id = '' OR 1=1 --";
+/// let patterns = sql_insight::detect_suspicious_patterns(&dialect, sql).unwrap();
+/// let kinds: Vec<_> = patterns[0].iter().map(|p| p.kind).collect();
+/// assert_eq!(kinds, vec![SuspiciousPatternKind::Tautology, SuspiciousPatternKind::CommentTerminated]);
+/// ```
+pub fn detect_suspicious_patterns(
+    dialect: &dyn Dialect,
+    sql: &str,
+) -> Result<Vec<Vec<SuspiciousPattern>>, Error> {
+    let statements = instrument::parse_sql(dialect, sql)?;
+    let mut patterns: Vec<Vec<SuspiciousPattern>> = statements
+        .iter()
+        .map(SuspiciousPatternDetector::detect_from_statement)
+        .collect();
+    if let (Some(comment), Some(last)) = (terminating_comment(dialect, sql)?, patterns.last_mut()) {
+        last.push(SuspiciousPattern {
+            kind: SuspiciousPatternKind::CommentTerminated,
+            description: format!("Statement is terminated by comment {}", comment),
+        });
+    }
+    Ok(patterns)
+}
+
+/// [`SuspiciousPatternKind`] represents the kind of a [`SuspiciousPattern`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SuspiciousPatternKind {
+    /// A predicate that is always true, e.g. `OR 1=1`.
+    Tautology,
+    /// A predicate that is always false, e.g. `WHERE 1=0`.
+    Contradiction,
+    /// A UNION branch selecting only constants or reading system catalogs, e.g. `UNION SELECT NULL, NULL`.
+    UnionSelect,
+    /// A statement whose rest is commented out by a trailing single-line comment.
+    CommentTerminated,
+}
+
+/// [`SuspiciousPattern`] represents a suspicious pattern found in a statement.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SuspiciousPattern {
+    pub kind: SuspiciousPatternKind,
+    pub description: String,
+}
+
+/// A visitor to detect suspicious patterns in statements.
+///
+/// Tautologies and contradictions are detected in WHERE, HAVING and join conditions.
+/// A tautology combined with other predicates by AND, as in `WHERE 1=1 AND ...`, and a contradiction combined by OR,
+/// as in `WHERE 1=0 OR ...`, have no effect and are common idioms of query builders, so they are not reported.
+#[derive(Default, Debug)]
+pub struct SuspiciousPatternDetector {
+    patterns: Vec<SuspiciousPattern>,
+}
+
+impl Visitor for SuspiciousPatternDetector {
+    type Break = ();
+
+    fn pre_visit_query(&mut self, query: &Query) -> ControlFlow<Self::Break> {
+        let mut conditions = Vec::new();
+        helper::collect_conditions(&query.body, &mut conditions);
+        for condition in conditions {
+            self.inspect_condition(condition, true, true);
+        }
+        if let SetExpr::SetOperation {
+            op: SetOperator::Union,
+            ..
+        } = query.body.as_ref()
+        {
+            self.inspect_union(&query.body);
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_statement(&mut self, statement: &Statement) -> ControlFlow<Self::Break> {
+        let mut conditions = Vec::new();
+        helper::collect_statement_conditions(statement, &mut conditions);
+        for condition in conditions {
+            self.inspect_condition(condition, true, true);
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+impl SuspiciousPatternDetector {
+    /// Detect suspicious patterns in a statement.
+    pub fn detect_from_statement(statement: &Statement) -> Vec<SuspiciousPattern> {
+        let mut visitor = SuspiciousPatternDetector::default();
+        let _ = statement.visit(&mut visitor);
+        visitor.patterns
+    }
+
+    fn inspect_condition(&mut self, expr: &Expr, report_true: bool, report_false: bool) {
+        match constant_truth(expr) {
+            Some(true) if report_true => self.push(
+                SuspiciousPatternKind::Tautology,
+                format!("Predicate {} is always true", expr),
+            ),
+            Some(false) if report_false => self.push(
+                SuspiciousPatternKind::Contradiction,
+                format!("Predicate {} is always false", expr),
+            ),
+            Some(_) => {}
+            None => match expr {
+                Expr::BinaryOp {
+                    left,
+                    op: BinaryOperator::And,
+                    right,
+                } => {
+                    self.inspect_condition(left, false, report_false);
+                    self.inspect_condition(right, false, report_false);
+                }
+                Expr::BinaryOp {
+                    left,
+                    op: BinaryOperator::Or,
+                    right,
+                } => {
+                    self.inspect_condition(left, report_true, false);
+                    self.inspect_condition(right, report_true, false);
+                }
+                Expr::UnaryOp {
+                    op: UnaryOperator::Not,
+                    expr,
+                } => self.inspect_condition(expr, report_false, report_true),
+                Expr::Nested(expr) => self.inspect_condition(expr, report_true, report_false),
+                _ => {}
+            },
+        }
+    }
+
+    fn inspect_union(&mut self, set_expr: &SetExpr) {
+        let mut selects = Vec::new();
+        helper::collect_selects(set_expr, &mut selects);
+        for select in selects {
+            let selects_constants = select.from.is_empty()
+                && select.projection.iter().all(|item| match item {
+                    SelectItem::UnnamedExpr(expr) | SelectItem::ExprWithAlias { expr, .. } => {
+                        matches!(expr, Expr::Value(_))
+                    }
+                    _ => false,
+                });
+            if selects_constants {
+                self.push(
+                    SuspiciousPatternKind::UnionSelect,
+                    format!("UNION branch {} selects only constants", select),
+                );
+            }
+            let catalogs = select
+                .from
+                .iter()
+                .flat_map(|table_with_joins| {
+                    std::iter::once(&table_with_joins.relation)
+                        .chain(table_with_joins.joins.iter().map(|join| &join.relation))
+                })
+                .filter_map(|relation| match relation {
+                    TableFactor::Table { name, .. } if is_system_catalog(&name.0) => Some(name),
+                    _ => None,
+                });
+            for catalog in catalogs {
+                self.push(
+                    SuspiciousPatternKind::UnionSelect,
+                    format!("UNION branch reads system catalog {}", catalog),
+                );
+            }
+        }
+    }
+
+    fn push(&mut self, kind: SuspiciousPatternKind, description: String) {
+        self.patterns.push(SuspiciousPattern { kind, description });
+    }
+}
+
+/// Truth value of a predicate when it does not depend on any row, ignoring NULLs.
+fn constant_truth(expr: &Expr) -> Option<bool> {
+    match expr {
+        Expr::Value(Value::Boolean(value)) => Some(*value),
+        Expr::Nested(expr) => constant_truth(expr),
+        Expr::UnaryOp {
+            op: UnaryOperator::Not,
+            expr,
+        } => constant_truth(expr).map(|value| !value),
+        Expr::BinaryOp {
+            left,
+            op: BinaryOperator::And,
+            right,
+        } => match (constant_truth(left), constant_truth(right)) {
+            (Some(false), _) | (_, Some(false)) => Some(false),
+            (Some(true), Some(true)) => Some(true),
+            _ => None,
+        },
+        Expr::BinaryOp {
+            left,
+            op: BinaryOperator::Or,
+            right,
+        } => match (constant_truth(left), constant_truth(right)) {
+            (Some(true), _) | (_, Some(true)) => Some(true),
+            (Some(false), Some(false)) => Some(false),
+            _ => None,
+        },
+        Expr::BinaryOp { left, op, right } => compare(unnest(left), op, unnest(right)),
+        _ => None,
+    }
+}
+
+fn unnest(expr: &Expr) -> &Expr {
+    match expr {
+        Expr::Nested(expr) => unnest(expr),
+        _ => expr,
+    }
+}
+
+fn compare(left: &Expr, op: &BinaryOperator, right: &Expr) -> Option<bool> {
+    let ordering = match (left, right) {
+        (Expr::Value(Value::Number(l, _)), Expr::Value(Value::Number(r, _))) => l
+            .parse::<f64>()
+            .ok()?
+            .partial_cmp(&r.parse::<f64>().ok()?)?,
+        (Expr::Value(Value::SingleQuotedString(l)), Expr::Value(Value::SingleQuotedString(r))) => {
+            l.cmp(r)
+        }
+        (Expr::Value(Value::Boolean(l)), Expr::Value(Value::Boolean(r))) => l.cmp(r),
+        (
+            Expr::Identifier(_) | Expr::CompoundIdentifier(_),
+            Expr::Identifier(_) | Expr::CompoundIdentifier(_),
+        ) if left == right => std::cmp::Ordering::Equal,
+        _ => return None,
+    };
+    match op {
+        BinaryOperator::Eq => Some(ordering.is_eq()),
+        BinaryOperator::NotEq => Some(ordering.is_ne()),
+        BinaryOperator::Lt => Some(ordering.is_lt()),
+        BinaryOperator::LtEq => Some(ordering.is_le()),
+        BinaryOperator::Gt => Some(ordering.is_gt()),
+        BinaryOperator::GtEq => Some(ordering.is_ge()),
+        _ => None,
+    }
+}
+
+fn is_system_catalog(idents: &[sqlparser::ast::Ident]) -> bool {
+    const SCHEMAS: [&str; 4] = ["information_schema", "pg_catalog", "mysql", "sys"];
+    const TABLES: [&str; 4] = ["sqlite_master", "sqlite_schema", "pg_user", "pg_shadow"];
+    let Some((table, qualifiers)) = idents.split_last() else {
+        return false;
+    };
+    qualifiers
+        .iter()
+        .any(|ident| SCHEMAS.contains(&ident.value.to_lowercase().as_str()))
+        || TABLES.contains(&table.value.to_lowercase().as_str())
+}
+
+/// Single-line comment ending the input on the same line as the last statement, if any.
+fn terminating_comment(dialect: &dyn Dialect, sql: &str) -> Result<Option<String>, Error> {
+    let tokens = Tokenizer::new(dialect, sql)
+        .tokenize()
+        .map_err(ParserError::from)?;
+    let mut significant = tokens
+        .iter()
+        .rev()
+        .filter(|token| !matches!(token, Token::EOF | Token::Whitespace(Whitespace::Space)));
+    match (significant.next(), significant.next()) {
+        (
+            Some(Token::Whitespace(Whitespace::SingleLineComment { comment, prefix })),
+            Some(previous),
+        ) if !comment.ends_with('\n') && !matches!(previous, Token::Whitespace(_)) => {
+            Ok(Some(format!("{}{}", prefix, comment)))
+        }
+        _ => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+
+    fn detect(dialect: &dyn Dialect, sql: &str) -> Vec<(SuspiciousPatternKind, String)> {
+        detect_suspicious_patterns(dialect, sql)
+            .unwrap()
+            .into_iter()
+            .flatten()
+            .map(|p| (p.kind, p.description))
+            .collect()
+    }
+
+    #[test]
+    fn test_tautology_and_contradiction() {
+        let sql = "SELECT a FROM t1 WHERE id = 1 OR 1 = 1; \
+            SELECT a FROM t1 WHERE 'a' = 'a'; \
+            DELETE FROM t1 WHERE id = 1 AND 2 < 1; \
+            SELECT a FROM t1 INNER JOIN t2 ON t1.id = t1.id";
+        for dialect in all_dialects() {
+            assert_eq!(
+                detect(dialect.as_ref(), sql),
+                vec![
+                    (
+                        SuspiciousPatternKind::Tautology,
+                        "Predicate id = 1 OR 1 = 1 is always true".to_string()
+                    ),
+                    (
+                        SuspiciousPatternKind::Tautology,
+                        "Predicate 'a' = 'a' is always true".to_string()
+                    ),
+                    (
+                        SuspiciousPatternKind::Contradiction,
+                        "Predicate id = 1 AND 2 < 1 is always false".to_string()
+                    ),
+                    (
+                        SuspiciousPatternKind::Tautology,
+                        "Predicate t1.id = t1.id is always true".to_string()
+                    ),
+                ],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_query_builder_idioms_are_not_reported() {
+        let sql = "SELECT a FROM t1 WHERE 1 = 1 AND b = 2; SELECT a FROM t1 WHERE 1 = 0 OR b = 2";
+        for dialect in all_dialects() {
+            assert!(detect(dialect.as_ref(), sql).is_empty());
+        }
+    }
+
+    #[test]
+    fn test_union_select() {
+        let sql = "SELECT name FROM users WHERE id = 1 UNION SELECT NULL; \
+            SELECT name FROM users UNION SELECT table_name FROM information_schema.tables";
+        for dialect in all_dialects() {
+            assert_eq!(
+                detect(dialect.as_ref(), sql),
+                vec![
+                    (
+                        SuspiciousPatternKind::UnionSelect,
+                        "UNION branch SELECT NULL selects only constants".to_string()
+                    ),
+                    (
+                        SuspiciousPatternKind::UnionSelect,
+                        "UNION branch reads system catalog information_schema.tables".to_string()
+                    ),
+                ],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_comment_terminated() {
+        for dialect in all_dialects() {
+            assert_eq!(
+                detect(dialect.as_ref(), "SELECT a FROM t1 WHERE name = 'admin'--"),
+                vec![(
+                    SuspiciousPatternKind::CommentTerminated,
+                    "Statement is terminated by comment --".to_string()
+                )],
+                "Failed for dialect: {dialect:?}"
+            );
+            assert!(
+                detect(dialect.as_ref(), "SELECT a FROM t1 -- comment\n").is_empty(),
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+}
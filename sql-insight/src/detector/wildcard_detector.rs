@@ -0,0 +1,131 @@
+//! A Detector that finds wildcard projections in SQL queries.
+//!
+//! See [`WildcardDetector`] for details.
+
+use std::ops::ControlFlow;
+
+use crate::helper;
+use sqlparser::ast::{ObjectName, Query, SelectItem, Statement, Visit, Visitor};
+
+/// [`WildcardProjection`] represents a wildcard found in the projection of a SELECT.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WildcardProjection {
+    /// Qualifier of the wildcard, e.g. `t1` for `t1.*`. `None` for a bare `*`.
+    pub qualifier: Option<ObjectName>,
+    /// Number of tables the SELECT reads from, counting joined tables.
+    pub table_count: usize,
+}
+
+impl WildcardProjection {
+    pub fn is_qualified(&self) -> bool {
+        self.qualifier.is_some()
+    }
+}
+
+/// A visitor to detect wildcard projections (`SELECT *` and `SELECT t1.*`) in SQL,
+/// including those in subqueries, derived tables and CTEs.
+/// Wildcards used as function arguments such as `COUNT(*)` are not projections and are not detected.
+#[derive(Default, Debug)]
+pub struct WildcardDetector {
+    wildcards: Vec<WildcardProjection>,
+}
+
+impl Visitor for WildcardDetector {
+    type Break = ();
+
+    fn pre_visit_query(&mut self, query: &Query) -> ControlFlow<Self::Break> {
+        let mut selects = Vec::new();
+        helper::collect_selects(&query.body, &mut selects);
+        for select in selects {
+            let table_count = select
+                .from
+                .iter()
+                .map(|table_with_joins| 1 + table_with_joins.joins.len())
+                .sum();
+            for item in &select.projection {
+                let qualifier = match item {
+                    SelectItem::Wildcard(_) => None,
+                    SelectItem::QualifiedWildcard(name, _) => Some(name.clone()),
+                    _ => continue,
+                };
+                self.wildcards.push(WildcardProjection {
+                    qualifier,
+                    table_count,
+                });
+            }
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+impl WildcardDetector {
+    /// Detect wildcard projections in a statement, in order of appearance of the enclosing queries.
+    pub fn detect_from_statement(statement: &Statement) -> Vec<WildcardProjection> {
+        let mut visitor = WildcardDetector::default();
+        let _ = statement.visit(&mut visitor);
+        visitor.wildcards
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+    use sqlparser::ast::Ident;
+    use sqlparser::dialect::Dialect;
+    use sqlparser::parser::Parser;
+
+    fn assert_wildcard_detection(
+        sql: &str,
+        expected: Vec<WildcardProjection>,
+        dialects: Vec<Box<dyn Dialect>>,
+    ) {
+        for dialect in dialects {
+            let statements = Parser::parse_sql(dialect.as_ref(), sql).unwrap();
+            let result = WildcardDetector::detect_from_statement(&statements[0]);
+            assert_eq!(result, expected, "Failed for dialect: {dialect:?}")
+        }
+    }
+
+    #[test]
+    fn test_wildcard() {
+        let sql = "SELECT * FROM t1";
+        let expected = vec![WildcardProjection {
+            qualifier: None,
+            table_count: 1,
+        }];
+        assert_wildcard_detection(sql, expected, all_dialects());
+    }
+
+    #[test]
+    fn test_qualified_wildcard_with_join() {
+        let sql = "SELECT t1.*, t2.a FROM t1 INNER JOIN t2 ON t1.id = t2.id, t3";
+        let expected = vec![WildcardProjection {
+            qualifier: Some(ObjectName(vec![Ident::new("t1")])),
+            table_count: 3,
+        }];
+        assert_wildcard_detection(sql, expected, all_dialects());
+    }
+
+    #[test]
+    fn test_wildcard_in_subquery_and_union() {
+        let sql = "SELECT a FROM (SELECT * FROM t1) AS d WHERE b IN (SELECT b FROM t2 UNION SELECT * FROM t3)";
+        let expected = vec![
+            WildcardProjection {
+                qualifier: None,
+                table_count: 1,
+            },
+            WildcardProjection {
+                qualifier: None,
+                table_count: 1,
+            },
+        ];
+        assert_wildcard_detection(sql, expected, all_dialects());
+    }
+
+    #[test]
+    fn test_wildcard_in_function_argument_is_not_detected() {
+        let sql = "SELECT COUNT(*) FROM t1";
+        assert_wildcard_detection(sql, vec![], all_dialects());
+    }
+}
@@ -0,0 +1,218 @@
+//! Decoding of SQL input from bytes, such as files and stdin.
+//!
+//! See [`decode_sql`](crate::decode_sql()) as the entry point for decoding input.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::Error;
+
+/// Convenience function to decode input into SQL ready for parsing.
+///
+/// - A byte order mark is stripped.
+/// - CRLF and lone CR line endings are converted into LF, since lone CRs do not end `--` comments for the parser.
+/// - Decode errors are reported with the byte offset of the invalid input.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::Encoding;
+///
+/// let sql = sql_insight::decode_sql(b"\xEF\xBB\xBFSELECT a -- b\rFROM t1\r\n", Encoding::Utf8).unwrap();
+/// assert_eq!(sql, "SELECT a -- b\nFROM t1\n");
+/// let sql = sql_insight::decode_sql(b"SELECT '\xE9'", Encoding::Latin1).unwrap();
+/// assert_eq!(sql, "SELECT '\u{e9}'");
+/// assert!(sql_insight::decode_sql(b"SELECT '\xE9'", Encoding::Utf8).is_err());
+/// ```
+pub fn decode_sql(bytes: &[u8], encoding: Encoding) -> Result<String, Error> {
+    const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+    const UTF16_LE_BOM: &[u8] = b"\xFF\xFE";
+    const UTF16_BE_BOM: &[u8] = b"\xFE\xFF";
+    let decoded = match encoding {
+        Encoding::Utf8 => decode_utf8(strip_bom(bytes, UTF8_BOM))?,
+        Encoding::Latin1 => bytes.iter().map(|byte| char::from(*byte)).collect(),
+        Encoding::Utf16 if bytes.starts_with(UTF16_BE_BOM) => {
+            decode_utf16(strip_bom(bytes, UTF16_BE_BOM), u16::from_be_bytes)?
+        }
+        Encoding::Utf16 | Encoding::Utf16Le => {
+            decode_utf16(strip_bom(bytes, UTF16_LE_BOM), u16::from_le_bytes)?
+        }
+        Encoding::Utf16Be => decode_utf16(strip_bom(bytes, UTF16_BE_BOM), u16::from_be_bytes)?,
+    };
+    Ok(normalize_line_endings(&decoded))
+}
+
+/// Input without the byte order mark, together with its byte offset in the input.
+fn strip_bom<'a>(bytes: &'a [u8], bom: &[u8]) -> (&'a [u8], usize) {
+    match bytes.strip_prefix(bom) {
+        Some(rest) => (rest, bom.len()),
+        None => (bytes, 0),
+    }
+}
+
+/// [`Encoding`] represents the character encoding of input.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Encoding {
+    #[default]
+    Utf8,
+    /// ISO-8859-1, where each byte is the character of the same code point.
+    Latin1,
+    /// UTF-16 with the byte order given by its byte order mark, little endian without one.
+    Utf16,
+    Utf16Le,
+    Utf16Be,
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Encoding::Utf8 => "utf-8",
+            Encoding::Latin1 => "latin1",
+            Encoding::Utf16 => "utf-16",
+            Encoding::Utf16Le => "utf-16le",
+            Encoding::Utf16Be => "utf-16be",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Encoding {
+    type Err = Error;
+
+    /// Parse an encoding name case-insensitively, e.g. `utf-8`, `latin1`, `iso-8859-1` or `utf-16le`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().replace('_', "-").as_str() {
+            "utf-8" | "utf8" => Ok(Encoding::Utf8),
+            "latin1" | "latin-1" | "iso-8859-1" => Ok(Encoding::Latin1),
+            "utf-16" | "utf16" => Ok(Encoding::Utf16),
+            "utf-16le" | "utf16le" => Ok(Encoding::Utf16Le),
+            "utf-16be" | "utf16be" => Ok(Encoding::Utf16Be),
+            _ => Err(Error::ArgumentError(format!(
+                "Encoding not found: {s}. Available encodings: utf-8, latin1, utf-16, utf-16le, utf-16be"
+            ))),
+        }
+    }
+}
+
+/// Decode UTF-8, where `offset` is the byte offset of `bytes` in the input.
+fn decode_utf8((bytes, offset): (&[u8], usize)) -> Result<String, Error> {
+    match std::str::from_utf8(bytes) {
+        Ok(s) => Ok(s.to_string()),
+        Err(e) => Err(Error::IOError(format!(
+            "invalid UTF-8 at byte {}",
+            offset + e.valid_up_to()
+        ))),
+    }
+}
+
+/// Decode UTF-16 code units read with `read`, where `offset` is the byte offset of `bytes` in the input.
+fn decode_utf16(
+    (bytes, offset): (&[u8], usize),
+    read: fn([u8; 2]) -> u16,
+) -> Result<String, Error> {
+    if bytes.len() % 2 != 0 {
+        return Err(Error::IOError(format!(
+            "truncated UTF-16 at byte {}",
+            offset + bytes.len() - 1
+        )));
+    }
+    let units = bytes.chunks_exact(2).map(|unit| read([unit[0], unit[1]]));
+    let mut decoded = String::with_capacity(bytes.len() / 2);
+    let mut position = offset;
+    for c in char::decode_utf16(units) {
+        match c {
+            Ok(c) => {
+                decoded.push(c);
+                position += c.len_utf16() * 2;
+            }
+            Err(_) => {
+                return Err(Error::IOError(format!(
+                    "invalid UTF-16 at byte {}",
+                    position
+                )))
+            }
+        }
+    }
+    Ok(decoded)
+}
+
+fn normalize_line_endings(s: &str) -> String {
+    s.replace("\r\n", "\n").replace('\r', "\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_utf8() {
+        assert_eq!(
+            decode_sql(
+                b"\xEF\xBB\xBFSELECT 1;\r\nSELECT 2;\rSELECT 3",
+                Encoding::Utf8
+            ),
+            Ok("SELECT 1;\nSELECT 2;\nSELECT 3".to_string())
+        );
+        assert_eq!(
+            decode_sql(b"SELECT '\xC3\xA9'", Encoding::Utf8),
+            Ok("SELECT '\u{e9}'".to_string())
+        );
+        assert_eq!(
+            decode_sql(b"SELECT '\xE9'", Encoding::Utf8),
+            Err(Error::IOError("invalid UTF-8 at byte 8".into()))
+        );
+        assert_eq!(
+            decode_sql(b"\xEF\xBB\xBFSELECT '\xE9'", Encoding::Utf8),
+            Err(Error::IOError("invalid UTF-8 at byte 11".into()))
+        );
+    }
+
+    #[test]
+    fn test_decode_latin1() {
+        assert_eq!(
+            decode_sql(b"SELECT '\xE9\xFF'\r\n", Encoding::Latin1),
+            Ok("SELECT '\u{e9}\u{ff}'\n".to_string())
+        );
+    }
+
+    #[test]
+    fn test_decode_utf16() {
+        let utf16 = |s: &str, to_bytes: fn(u16) -> [u8; 2]| -> Vec<u8> {
+            s.encode_utf16().flat_map(to_bytes).collect()
+        };
+        let le = utf16("\u{feff}SELECT 'é'\r\n", u16::to_le_bytes);
+        let be = utf16("\u{feff}SELECT 'é'\r\n", u16::to_be_bytes);
+        for (bytes, encoding) in [
+            (&le, Encoding::Utf16),
+            (&be, Encoding::Utf16),
+            (&le, Encoding::Utf16Le),
+            (&be, Encoding::Utf16Be),
+            (&le[2..].to_vec(), Encoding::Utf16),
+        ] {
+            assert_eq!(
+                decode_sql(bytes, encoding),
+                Ok("SELECT 'é'\n".to_string()),
+                "Failed for encoding: {encoding}"
+            );
+        }
+        assert_eq!(
+            decode_sql(&le[..5], Encoding::Utf16),
+            Err(Error::IOError("truncated UTF-16 at byte 4".into()))
+        );
+        // An unpaired high surrogate after the byte order mark and `S`.
+        let mut invalid = utf16("\u{feff}S", u16::to_le_bytes);
+        invalid.extend([0x00, 0xD8, 0x41, 0x00]);
+        assert_eq!(
+            decode_sql(&invalid, Encoding::Utf16),
+            Err(Error::IOError("invalid UTF-16 at byte 4".into()))
+        );
+    }
+
+    #[test]
+    fn test_encoding_from_str() {
+        assert_eq!("UTF-8".parse(), Ok(Encoding::Utf8));
+        assert_eq!("iso-8859-1".parse(), Ok(Encoding::Latin1));
+        assert_eq!("utf_16le".parse(), Ok(Encoding::Utf16Le));
+        assert!("ebcdic".parse::<Encoding>().is_err());
+    }
+}
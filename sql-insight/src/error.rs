@@ -1,4 +1,12 @@
+use core::fmt;
+
+use crate::rewriter::Untranslatable;
+use crate::span::{self, Location, Span};
+use regex::Regex;
+use sqlparser::dialect::Dialect;
 use sqlparser::parser::ParserError;
+use sqlparser::tokenizer::{TokenWithLocation, Tokenizer};
+use std::sync::OnceLock;
 
 #[allow(clippy::enum_variant_names)]
 #[derive(Debug, Eq, thiserror::Error, PartialEq)]
@@ -11,4 +19,215 @@ pub enum Error {
     AnalysisError(String),
     #[error("{0}")]
     IOError(String),
+    #[error("{0}")]
+    LimitExceeded(String),
+    #[error("{0}")]
+    Untranslatable(#[from] Untranslatable),
+}
+
+/// Convenience function to attach the location in SQL to errors returned by analyzing the SQL,
+/// e.g. by [`extract_tables`](crate::extract_tables()).
+///
+/// An error failing the whole input, i.e. a parser error, is located at the token reported by the parser,
+/// and an error of a statement is located at the statement.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+///
+/// let dialect = GenericDialect {};
+/// let sql = "SELECT a FROM t1;\nSELECT b FROM a.b.c.d";
+/// let result = sql_insight::extract_tables(&dialect, sql);
+/// let results = sql_insight::error::locate_errors(&dialect, sql, result).unwrap();
+/// let error = results[1].as_ref().unwrap_err();
+/// assert_eq!(error.location.statement_index, Some(1));
+/// assert_eq!(error.location.offset, Some(18));
+/// assert_eq!(error.to_string(), "2:1: Too many identifiers provided");
+/// ```
+pub fn locate_errors<T>(
+    dialect: &dyn Dialect,
+    sql: &str,
+    result: Result<Vec<Result<T, Error>>, Error>,
+) -> Result<Vec<Result<T, LocatedError>>, LocatedError> {
+    let locator = Locator::new(dialect, sql);
+    match result {
+        Ok(results) => Ok(results
+            .into_iter()
+            .enumerate()
+            .map(|(index, result)| result.map_err(|e| locator.locate(e, Some(index))))
+            .collect()),
+        Err(e) => Err(locator.locate(e, None)),
+    }
+}
+
+/// [`ErrorLocation`] represents where in SQL an [`Error`] was caused. Every field is best effort.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ErrorLocation {
+    /// Zero-based position of the statement causing the error.
+    pub statement_index: Option<usize>,
+    /// Span of the offending SQL, i.e. the token reported by the parser, or the statement failing to be analyzed.
+    pub span: Option<Span>,
+    /// Byte offset of the start of `span` in SQL.
+    pub offset: Option<usize>,
+}
+
+/// [`LocatedError`] represents an [`Error`] together with its location in SQL.
+/// It displays as `line:column: message` when its span is known, like [`Diagnostic`](crate::Diagnostic).
+#[derive(Debug, PartialEq, Eq)]
+pub struct LocatedError {
+    pub error: Error,
+    pub location: ErrorLocation,
+}
+
+impl fmt::Display for LocatedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(span) = &self.location.span {
+            write!(f, "{}:{}: ", span.start.line, span.start.column)?;
+        }
+        write!(f, "{}", self.error)
+    }
+}
+
+impl std::error::Error for LocatedError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl LocatedError {
+    /// Locate an error caused by SQL. `statement_index` is the position of the statement the error was returned for,
+    /// or `None` when it failed the whole input, in which case the statement is inferred from the position reported
+    /// by the parser.
+    pub fn locate(
+        dialect: &dyn Dialect,
+        sql: &str,
+        error: Error,
+        statement_index: Option<usize>,
+    ) -> Self {
+        Locator::new(dialect, sql).locate(error, statement_index)
+    }
+}
+
+/// Tokens and statement spans of SQL, computed once to locate many errors.
+struct Locator<'a> {
+    sql: &'a str,
+    tokens: Vec<TokenWithLocation>,
+    statement_spans: Vec<Span>,
+}
+
+impl<'a> Locator<'a> {
+    fn new(dialect: &dyn Dialect, sql: &'a str) -> Self {
+        Self {
+            sql,
+            tokens: Tokenizer::new(dialect, sql)
+                .tokenize_with_location()
+                .unwrap_or_default(),
+            statement_spans: span::statement_spans(dialect, sql),
+        }
+    }
+
+    fn locate(&self, error: Error, statement_index: Option<usize>) -> LocatedError {
+        let span = match &error {
+            Error::ParserError(e) => reported_location(e).map(|start| self.token_span(start)),
+            _ => None,
+        };
+        let statement_index = statement_index.or_else(|| {
+            let start = span?.start;
+            self.statement_spans
+                .iter()
+                .position(|statement| start < statement.end)
+        });
+        let span = span.or_else(|| self.statement_spans.get(statement_index?).copied());
+        LocatedError {
+            error,
+            location: ErrorLocation {
+                statement_index,
+                span,
+                offset: span.and_then(|span| span.start.offset_in(self.sql)),
+            },
+        }
+    }
+
+    /// Span of the token starting at `start`, or an empty span when no token starts there.
+    fn token_span(&self, start: Location) -> Span {
+        let end = self
+            .tokens
+            .iter()
+            .find(|token| Location::from(token.location) == start)
+            .map(|token| start.advance(&token.token.to_string()))
+            .unwrap_or(start);
+        Span::new(start, end)
+    }
+}
+
+/// Location reported in the message of a parser error, e.g. `Expected ..., found: x at Line: 1, Column 8`.
+fn reported_location(error: &ParserError) -> Option<Location> {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    let pattern =
+        PATTERN.get_or_init(|| Regex::new(r"at Line: (\d+), Column:? (\d+)").expect("valid regex"));
+    let message = error.to_string();
+    let captures = pattern.captures_iter(&message).last()?;
+    let location = Location::new(captures[1].parse().ok()?, captures[2].parse().ok()?);
+    (location.line > 0).then_some(location)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+    use sqlparser::dialect::GenericDialect;
+
+    #[test]
+    fn test_locate_statement_errors() {
+        let sql = "SELECT a FROM t1;\n  SELECT b FROM a.b.c.d";
+        for dialect in all_dialects() {
+            let result = crate::extract_tables(dialect.as_ref(), sql);
+            let results = locate_errors(dialect.as_ref(), sql, result).unwrap();
+            assert!(results[0].is_ok(), "Failed for dialect: {dialect:?}");
+            assert_eq!(
+                results[1],
+                Err(LocatedError {
+                    error: Error::AnalysisError("Too many identifiers provided".to_string()),
+                    location: ErrorLocation {
+                        statement_index: Some(1),
+                        span: Some(Span::new(Location::new(2, 3), Location::new(2, 24))),
+                        offset: Some(20),
+                    },
+                }),
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_locate_parser_error() {
+        let dialect = GenericDialect {};
+        let sql = "SELECT a FROM t1;\nSELECT b FROM t2 WHERE a = )";
+        let result = crate::extract_tables(&dialect, sql);
+        let error = locate_errors(&dialect, sql, result).unwrap_err();
+        assert!(matches!(error.error, Error::ParserError(_)));
+        assert_eq!(
+            error.location,
+            ErrorLocation {
+                statement_index: Some(1),
+                span: Some(Span::new(Location::new(2, 28), Location::new(2, 29))),
+                offset: Some(45),
+            }
+        );
+        assert!(error.to_string().starts_with("2:28: "));
+    }
+
+    #[test]
+    fn test_locate_without_location() {
+        let error = LocatedError::locate(
+            &GenericDialect {},
+            "SELECT a FROM t1",
+            Error::ArgumentError("invalid".to_string()),
+            None,
+        );
+        assert_eq!(error.location, ErrorLocation::default());
+        assert_eq!(error.to_string(), "invalid");
+    }
 }
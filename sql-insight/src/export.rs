@@ -0,0 +1,290 @@
+//! Export of batch analysis results as flat records, and as Arrow record batches or Parquet files
+//! with the `arrow` feature.
+//!
+//! See [`export_records`](crate::export_records()) as the entry point for building records from SQL.
+
+use crate::batch::BatchInput;
+use crate::extractor::crud_table_extractor::CrudTableExtractor;
+use crate::extractor::table_extractor::{TableExtractor, TableReference};
+use crate::fingerprint::hash_normalized;
+use crate::normalizer::Normalizer;
+use sqlparser::ast::{Statement, VisitMut};
+use sqlparser::dialect::Dialect;
+
+/// Convenience function to build a record for each statement of SQL read from `file`, if any.
+/// Statements are parsed one by one, so a statement failing to parse becomes a record with an error.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+///
+/// let dialect = GenericDialect {};
+/// let sql = "INSERT INTO t1 SELECT * FROM t2 WHERE a = 1; SELEC";
+/// let records = sql_insight::export_records(&dialect, sql, Some("queries.sql"));
+/// assert_eq!(records[0].normalized.as_deref(), Some("INSERT INTO t1 SELECT * FROM t2 WHERE a = ?"));
+/// assert_eq!(records[0].tables, ["t1", "t2"]);
+/// assert_eq!(records[0].create_tables, ["t1"]);
+/// assert_eq!(records[0].read_tables, ["t2"]);
+/// assert!(records[1].error.is_some());
+/// ```
+pub fn export_records(
+    dialect: &dyn Dialect,
+    sql: &str,
+    file: Option<&str>,
+) -> Vec<StatementRecord> {
+    let input = BatchInput::from_string(sql.to_string());
+    input
+        .statements(dialect)
+        .enumerate()
+        .map(|(index, statement)| match statement {
+            Ok(statement) => StatementRecord::from_statement(file, index, &statement),
+            Err(e) => StatementRecord {
+                file: file.map(String::from),
+                statement: index,
+                error: Some(e.to_string()),
+                ..Default::default()
+            },
+        })
+        .collect()
+}
+
+/// [`StatementRecord`] represents the analysis results of a statement as a flat row, one column per field.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StatementRecord {
+    /// File the statement was read from, if any.
+    pub file: Option<String>,
+    /// Zero-based position of the statement in its input.
+    pub statement: usize,
+    /// The statement as formatted by the parser, or `None` if it failed to parse.
+    pub sql: Option<String>,
+    /// The normalized statement. See [`normalize`](crate::normalize()).
+    pub normalized: Option<String>,
+    /// Hexadecimal hash of the normalized statement, equal for statements differing only in literal values.
+    pub fingerprint: Option<String>,
+    /// Tables of the statement, without aliases.
+    pub tables: Vec<String>,
+    pub create_tables: Vec<String>,
+    pub read_tables: Vec<String>,
+    pub update_tables: Vec<String>,
+    pub delete_tables: Vec<String>,
+    /// The first error of analyzing the statement, if any.
+    pub error: Option<String>,
+}
+
+impl StatementRecord {
+    /// Build the record of a statement.
+    /// Fields of analyses that fail are left empty and the first error is recorded.
+    pub fn from_statement(file: Option<&str>, index: usize, statement: &Statement) -> Self {
+        let mut normalized = statement.clone();
+        let _ = normalized.visit(&mut Normalizer::new());
+        let normalized = normalized.to_string();
+        let mut record = StatementRecord {
+            file: file.map(String::from),
+            statement: index,
+            sql: Some(statement.to_string()),
+            fingerprint: Some(hash_normalized(&normalized)),
+            normalized: Some(normalized),
+            ..Default::default()
+        };
+        match TableExtractor::extract_from_statement(statement) {
+            Ok(tables) => record.tables = table_names(&tables.0),
+            Err(e) => record.error = Some(e.to_string()),
+        }
+        match CrudTableExtractor::extract_from_statement(statement) {
+            Ok(crud_tables) => {
+                record.create_tables = table_names(&crud_tables.create_tables);
+                record.read_tables = table_names(&crud_tables.read_tables);
+                record.update_tables = table_names(&crud_tables.update_tables);
+                record.delete_tables = table_names(&crud_tables.delete_tables);
+            }
+            Err(e) => {
+                record.error.get_or_insert(e.to_string());
+            }
+        }
+        record
+    }
+}
+
+/// Names of tables without aliases, deduplicated in order of appearance.
+pub(crate) fn table_names(tables: &[TableReference]) -> Vec<String> {
+    let mut names = vec![];
+    for table in tables {
+        let name = TableReference {
+            alias: None,
+            ..table.clone()
+        }
+        .to_string();
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    names
+}
+
+#[cfg(feature = "arrow")]
+pub use self::arrow_export::*;
+
+#[cfg(feature = "arrow")]
+mod arrow_export {
+    use std::sync::Arc;
+
+    use super::StatementRecord;
+    use crate::error::Error;
+    use arrow::array::{ArrayRef, ListBuilder, StringArray, StringBuilder, UInt64Array};
+    use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+    use arrow::record_batch::RecordBatch;
+    use parquet::arrow::ArrowWriter;
+
+    const LIST_COLUMNS: [&str; 5] = [
+        "tables",
+        "create_tables",
+        "read_tables",
+        "update_tables",
+        "delete_tables",
+    ];
+
+    /// Arrow schema of [`StatementRecord`]s, with a column for each field of the same name.
+    /// Table columns are lists of strings, and the others are nullable strings except `statement`.
+    pub fn record_schema() -> SchemaRef {
+        let string = |name: &str| Field::new(name, DataType::Utf8, true);
+        let list = |name: &str| {
+            Field::new(
+                name,
+                DataType::List(Arc::new(Field::new("item", DataType::Utf8, true))),
+                false,
+            )
+        };
+        let mut fields = vec![
+            string("file"),
+            Field::new("statement", DataType::UInt64, false),
+            string("sql"),
+            string("normalized"),
+            string("fingerprint"),
+        ];
+        fields.extend(LIST_COLUMNS.map(list));
+        fields.push(string("error"));
+        Arc::new(Schema::new(fields))
+    }
+
+    /// Convert records into an Arrow record batch of [`record_schema`].
+    pub fn to_record_batch(records: &[StatementRecord]) -> Result<RecordBatch, Error> {
+        let strings = |field: fn(&StatementRecord) -> Option<&str>| -> ArrayRef {
+            Arc::new(records.iter().map(field).collect::<StringArray>())
+        };
+        let list = |field: fn(&StatementRecord) -> &Vec<String>| -> ArrayRef {
+            let mut builder = ListBuilder::new(StringBuilder::new());
+            for record in records {
+                for value in field(record) {
+                    builder.values().append_value(value);
+                }
+                builder.append(true);
+            }
+            Arc::new(builder.finish())
+        };
+        let columns = vec![
+            strings(|r| r.file.as_deref()),
+            Arc::new(
+                records
+                    .iter()
+                    .map(|r| r.statement as u64)
+                    .collect::<UInt64Array>(),
+            ),
+            strings(|r| r.sql.as_deref()),
+            strings(|r| r.normalized.as_deref()),
+            strings(|r| r.fingerprint.as_deref()),
+            list(|r| &r.tables),
+            list(|r| &r.create_tables),
+            list(|r| &r.read_tables),
+            list(|r| &r.update_tables),
+            list(|r| &r.delete_tables),
+            strings(|r| r.error.as_deref()),
+        ];
+        RecordBatch::try_new(record_schema(), columns).map_err(|e| Error::IOError(e.to_string()))
+    }
+
+    /// Write records to `writer` as a Parquet file of [`record_schema`].
+    pub fn write_parquet<W: std::io::Write + Send>(
+        records: &[StatementRecord],
+        writer: W,
+    ) -> Result<(), Error> {
+        let batch = to_record_batch(records)?;
+        let mut writer = ArrowWriter::try_new(writer, batch.schema(), None)
+            .map_err(|e| Error::IOError(e.to_string()))?;
+        writer
+            .write(&batch)
+            .map_err(|e| Error::IOError(e.to_string()))?;
+        writer.close().map_err(|e| Error::IOError(e.to_string()))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fingerprint::fnv1a;
+    use crate::test_utils::all_dialects;
+
+    #[test]
+    fn test_export_records() {
+        let sql = "SELECT a FROM t1 AS x WHERE b = 1; SELECT a FROM t1 WHERE b = 2; DELETE FROM t2";
+        for dialect in all_dialects() {
+            let records = export_records(dialect.as_ref(), sql, None);
+            assert_eq!(
+                records[0].normalized.as_deref(),
+                Some("SELECT a FROM t1 AS x WHERE b = ?"),
+                "Failed for dialect: {dialect:?}"
+            );
+            assert_eq!(records[0].tables, ["t1"], "Failed for dialect: {dialect:?}");
+            assert_ne!(
+                records[0].fingerprint, records[1].fingerprint,
+                "Failed for dialect: {dialect:?}"
+            );
+            assert_eq!(
+                records[2],
+                StatementRecord {
+                    file: None,
+                    statement: 2,
+                    sql: Some("DELETE FROM t2".into()),
+                    normalized: Some("DELETE FROM t2".into()),
+                    fingerprint: Some(format!("{:016x}", fnv1a(b"DELETE FROM t2"))),
+                    tables: vec!["t2".into()],
+                    delete_tables: vec!["t2".into()],
+                    ..Default::default()
+                },
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        let dialect = sqlparser::dialect::GenericDialect {};
+        let records = export_records(
+            &dialect,
+            "SELECT a FROM t1 WHERE b = 1; SELECT a FROM t1 WHERE b = 'x'",
+            None,
+        );
+        assert_eq!(records[0].fingerprint, records[1].fingerprint);
+        assert_eq!(fnv1a(b""), 0xcbf29ce484222325);
+        assert_eq!(fnv1a(b"a"), 0xaf63dc4c8601ec8c);
+    }
+
+    #[cfg(feature = "arrow")]
+    #[test]
+    fn test_arrow_and_parquet() {
+        let dialect = sqlparser::dialect::GenericDialect {};
+        let records = export_records(
+            &dialect,
+            "SELECT a FROM t1 JOIN t2 ON t1.id = t2.id; SELEC",
+            Some("q.sql"),
+        );
+        let batch = to_record_batch(&records).unwrap();
+        assert_eq!(batch.num_rows(), 2);
+        assert_eq!(batch.schema(), record_schema());
+        let mut buf = vec![];
+        write_parquet(&records, &mut buf).unwrap();
+        assert!(buf.starts_with(b"PAR1") && buf.ends_with(b"PAR1"));
+    }
+}
@@ -0,0 +1,394 @@
+//! A Extractor that extracts columns from SQL queries.
+//!
+//! See [`extract_columns`](crate::extract_columns()) as the entry point for extracting columns from SQL.
+
+use core::fmt;
+use std::collections::HashSet;
+use std::ops::ControlFlow;
+
+use crate::error::Error;
+use crate::helper;
+use crate::instrument;
+use sqlparser::ast::{
+    Expr, GroupByExpr, Ident, JoinConstraint, Query, SelectItem, Statement, Visit, Visitor,
+};
+use sqlparser::dialect::Dialect;
+
+/// Convenience function to extract columns from SQL.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+///
+/// let dialect = GenericDialect {};
+/// let sql = "SELECT a, t2.b FROM t1 AS x INNER JOIN t2 ON x.id = t2.id WHERE c = 1";
+/// let result = sql_insight::extract_columns(&dialect, sql).unwrap();
+/// println!("{:#?}", result);
+/// assert_eq!(result[0].as_ref().unwrap().to_string(), "a, t2.b, x.id, t2.id, c");
+/// ```
+pub fn extract_columns(
+    dialect: &dyn Dialect,
+    sql: &str,
+) -> Result<Vec<Result<Columns, Error>>, Error> {
+    ColumnExtractor::extract(dialect, sql)
+}
+
+/// [`ColumnReference`] represents a column with the qualifiers it is written with.
+/// `table` is the table name or alias qualifying the column, which is not resolved to a table.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ColumnReference {
+    pub catalog: Option<Ident>,
+    pub schema: Option<Ident>,
+    pub table: Option<Ident>,
+    pub name: Ident,
+}
+
+impl ColumnReference {
+    pub fn has_qualifiers(&self) -> bool {
+        self.catalog.is_some() || self.schema.is_some() || self.table.is_some()
+    }
+}
+
+impl fmt::Display for ColumnReference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parts = [&self.catalog, &self.schema, &self.table]
+            .into_iter()
+            .flatten()
+            .chain(std::iter::once(&self.name))
+            .map(|ident| ident.to_string())
+            .collect::<Vec<String>>();
+        write!(f, "{}", parts.join("."))
+    }
+}
+
+impl TryFrom<&[Ident]> for ColumnReference {
+    type Error = Error;
+
+    fn try_from(idents: &[Ident]) -> Result<Self, Self::Error> {
+        match idents {
+            [] => unreachable!("Parser should not allow empty identifiers"),
+            [name] => Ok(ColumnReference {
+                catalog: None,
+                schema: None,
+                table: None,
+                name: name.clone(),
+            }),
+            [table, name] => Ok(ColumnReference {
+                catalog: None,
+                schema: None,
+                table: Some(table.clone()),
+                name: name.clone(),
+            }),
+            [schema, table, name] => Ok(ColumnReference {
+                catalog: None,
+                schema: Some(schema.clone()),
+                table: Some(table.clone()),
+                name: name.clone(),
+            }),
+            [catalog, schema, table, name] => Ok(ColumnReference {
+                catalog: Some(catalog.clone()),
+                schema: Some(schema.clone()),
+                table: Some(table.clone()),
+                name: name.clone(),
+            }),
+            _ => Err(Error::AnalysisError(
+                "Too many identifiers provided".to_string(),
+            )),
+        }
+    }
+}
+
+/// [`Columns`] represents a list of [`ColumnReference`] that found in SQL.
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Columns(pub Vec<ColumnReference>);
+
+impl fmt::Display for Columns {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let columns = self
+            .0
+            .iter()
+            .map(|c| c.to_string())
+            .collect::<Vec<String>>()
+            .join(", ");
+        write!(f, "{}", columns)
+    }
+}
+
+/// A visitor to extract columns from SQL.
+///
+/// Columns are collected from expressions, column lists of INSERT, targets of UPDATE assignments
+/// and `USING` clauses of joins, in the order of appearance without duplicates.
+/// Wildcards are not columns, nor are unqualified identifiers of `ORDER BY`, `GROUP BY` and `HAVING`
+/// naming a projection alias, e.g. `b` of `SELECT a AS b FROM t1 ORDER BY b`.
+#[derive(Default, Debug)]
+pub struct ColumnExtractor {
+    columns: Vec<ColumnReference>,
+    // Addresses of identifier expressions referring to projection aliases.
+    alias_references: HashSet<usize>,
+}
+
+impl Visitor for ColumnExtractor {
+    type Break = Error;
+
+    fn pre_visit_statement(&mut self, statement: &Statement) -> ControlFlow<Self::Break> {
+        match statement {
+            Statement::Insert { columns, .. } => {
+                for column in columns {
+                    self.push(std::slice::from_ref(column))?;
+                }
+            }
+            Statement::Update { assignments, .. } => {
+                for assignment in assignments {
+                    self.push(&assignment.id)?;
+                }
+            }
+            _ => {}
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_query(&mut self, query: &Query) -> ControlFlow<Self::Break> {
+        let mut selects = vec![];
+        helper::collect_selects(&query.body, &mut selects);
+        let order_by_aliases = selects
+            .iter()
+            .flat_map(|select| projection_aliases(&select.projection))
+            .collect::<Vec<_>>();
+        for order_by in &query.order_by {
+            self.collect_alias_references(&order_by.expr, &order_by_aliases);
+        }
+        for select in &selects {
+            let aliases = projection_aliases(&select.projection).collect::<Vec<_>>();
+            if let GroupByExpr::Expressions(exprs) = &select.group_by {
+                for expr in exprs {
+                    self.collect_alias_references(expr, &aliases);
+                }
+            }
+            if let Some(having) = &select.having {
+                self.collect_alias_references(having, &aliases);
+            }
+        }
+        let joins = selects
+            .iter()
+            .flat_map(|select| &select.from)
+            .flat_map(|table_with_joins| &table_with_joins.joins);
+        for join in joins {
+            if let Some(JoinConstraint::Using(idents)) =
+                helper::join_constraint(&join.join_operator)
+            {
+                for ident in idents {
+                    self.push(std::slice::from_ref(ident))?;
+                }
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_expr(&mut self, expr: &Expr) -> ControlFlow<Self::Break> {
+        match expr {
+            Expr::Identifier(_) if self.alias_references.contains(&address(expr)) => {}
+            Expr::Identifier(ident) => self.push(std::slice::from_ref(ident))?,
+            Expr::CompoundIdentifier(idents) => self.push(idents)?,
+            _ => {}
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+impl ColumnExtractor {
+    /// Extract columns from SQL.
+    pub fn extract(dialect: &dyn Dialect, sql: &str) -> Result<Vec<Result<Columns, Error>>, Error> {
+        let statements = instrument::parse_sql(dialect, sql)?;
+        let results = statements
+            .iter()
+            .map(Self::extract_from_statement)
+            .collect::<Vec<Result<Columns, Error>>>();
+        Ok(results)
+    }
+
+    pub fn extract_from_statement(statement: &Statement) -> Result<Columns, Error> {
+        instrument::try_analyze("extract_columns", || {
+            let mut visitor = ColumnExtractor::default();
+            match statement.visit(&mut visitor) {
+                ControlFlow::Break(e) => Err(e),
+                ControlFlow::Continue(()) => Ok(visitor.into_columns()),
+            }
+        })
+    }
+
+    /// Columns found so far.
+    /// Useful when the extractor is driven by an external traversal such as [`VisitorSet`](crate::VisitorSet).
+    pub fn into_columns(self) -> Columns {
+        Columns(self.columns)
+    }
+
+    fn collect_alias_references(&mut self, expr: &Expr, aliases: &[&Ident]) {
+        if aliases.is_empty() {
+            return;
+        }
+        let mut visitor = AliasReferenceCollector {
+            aliases,
+            depth: 0,
+            references: &mut self.alias_references,
+        };
+        let _ = expr.visit(&mut visitor);
+    }
+
+    fn push(&mut self, idents: &[Ident]) -> ControlFlow<Error> {
+        match ColumnReference::try_from(idents) {
+            Ok(column) => {
+                if !self.columns.contains(&column) {
+                    self.columns.push(column);
+                }
+                ControlFlow::Continue(())
+            }
+            Err(e) => ControlFlow::Break(e),
+        }
+    }
+}
+
+fn projection_aliases(projection: &[SelectItem]) -> impl Iterator<Item = &Ident> {
+    projection.iter().filter_map(|item| match item {
+        SelectItem::ExprWithAlias { alias, .. } => Some(alias),
+        _ => None,
+    })
+}
+
+fn address(expr: &Expr) -> usize {
+    expr as *const Expr as usize
+}
+
+/// A visitor collecting unqualified identifiers naming one of the aliases, outside of subqueries.
+struct AliasReferenceCollector<'a> {
+    aliases: &'a [&'a Ident],
+    depth: usize,
+    references: &'a mut HashSet<usize>,
+}
+
+impl Visitor for AliasReferenceCollector<'_> {
+    type Break = ();
+
+    fn pre_visit_query(&mut self, _query: &Query) -> ControlFlow<Self::Break> {
+        self.depth += 1;
+        ControlFlow::Continue(())
+    }
+
+    fn post_visit_query(&mut self, _query: &Query) -> ControlFlow<Self::Break> {
+        self.depth -= 1;
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_expr(&mut self, expr: &Expr) -> ControlFlow<Self::Break> {
+        if let (0, Expr::Identifier(ident)) = (self.depth, expr) {
+            let is_alias =
+                self.aliases
+                    .iter()
+                    .any(|alias| match (alias.quote_style, ident.quote_style) {
+                        (None, None) => alias.value.eq_ignore_ascii_case(&ident.value),
+                        _ => alias.value == ident.value,
+                    });
+            if is_alias {
+                self.references.insert(address(expr));
+            }
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+
+    fn assert_column_extraction(
+        sql: &str,
+        expected: Vec<Result<Columns, Error>>,
+        dialects: Vec<Box<dyn Dialect>>,
+    ) {
+        for dialect in dialects {
+            let result = ColumnExtractor::extract(dialect.as_ref(), sql).unwrap();
+            assert_eq!(result, expected, "Failed for dialect: {dialect:?}")
+        }
+    }
+
+    fn column(idents: &[&str]) -> ColumnReference {
+        let idents = idents.iter().map(|&s| Ident::new(s)).collect::<Vec<_>>();
+        ColumnReference::try_from(idents.as_slice()).unwrap()
+    }
+
+    #[test]
+    fn test_select_statement() {
+        let sql = "SELECT a, x.b, s1.t2.c FROM t1 AS x JOIN s1.t2 ON x.id = t2.id WHERE d = 1 AND a > 0 GROUP BY e ORDER BY f";
+        let expected = vec![Ok(Columns(vec![
+            column(&["a"]),
+            column(&["x", "b"]),
+            column(&["s1", "t2", "c"]),
+            column(&["x", "id"]),
+            column(&["t2", "id"]),
+            column(&["d"]),
+            column(&["e"]),
+            column(&["f"]),
+        ]))];
+        assert_column_extraction(sql, expected, all_dialects());
+    }
+
+    #[test]
+    fn test_subquery_and_using() {
+        let sql =
+            "SELECT * FROM t1 JOIN t2 USING (id) WHERE a IN (SELECT b FROM t3 WHERE t3.c = t1.c)";
+        let expected = vec![Ok(Columns(vec![
+            column(&["id"]),
+            column(&["a"]),
+            column(&["b"]),
+            column(&["t3", "c"]),
+            column(&["t1", "c"]),
+        ]))];
+        assert_column_extraction(sql, expected, all_dialects());
+    }
+
+    #[test]
+    fn test_insert_and_update_statements() {
+        let sql = "INSERT INTO t1 (a, b) SELECT c, d FROM t2; UPDATE t1 SET a = b + 1 WHERE c = 2";
+        let expected = vec![
+            Ok(Columns(vec![
+                column(&["a"]),
+                column(&["b"]),
+                column(&["c"]),
+                column(&["d"]),
+            ])),
+            Ok(Columns(vec![
+                column(&["a"]),
+                column(&["b"]),
+                column(&["c"]),
+            ])),
+        ];
+        assert_column_extraction(sql, expected, all_dialects());
+    }
+
+    #[test]
+    fn test_projection_alias_references() {
+        let sql = "SELECT a AS b, c + 1 AS d FROM t1 GROUP BY B, e HAVING d > 1 \
+            ORDER BY b, t1.d, f, (SELECT b FROM t2)";
+        let expected = vec![Ok(Columns(vec![
+            column(&["a"]),
+            column(&["c"]),
+            column(&["e"]),
+            column(&["t1", "d"]),
+            column(&["f"]),
+            column(&["b"]),
+        ]))];
+        assert_column_extraction(sql, expected, all_dialects());
+    }
+
+    #[test]
+    fn test_too_many_identifiers() {
+        let sql = "SELECT a.b.c.d.e FROM t1";
+        let expected = vec![Err(Error::AnalysisError(
+            "Too many identifiers provided".to_string(),
+        ))];
+        assert_column_extraction(sql, expected, all_dialects());
+    }
+}
@@ -6,11 +6,14 @@ use std::fmt;
 use std::ops::ControlFlow;
 
 use crate::error::Error;
+use crate::extractor::options::ExtractorOptions;
 use crate::extractor::table_extractor::TableReference;
+use crate::instrument;
 use crate::{helper, TableExtractor};
-use sqlparser::ast::{MergeClause, Statement, Visit, Visitor};
+use sqlparser::ast::{
+    MergeClause, ObjectName, ObjectType, Query, Statement, TableFactor, Visit, Visitor,
+};
 use sqlparser::dialect::Dialect;
-use sqlparser::parser::Parser;
 
 /// Convenience function to extract CRUD tables from SQL.
 ///
@@ -32,13 +35,97 @@ pub fn extract_crud_tables(
     CrudTableExtractor::extract(dialect, sql)
 }
 
+/// Convenience function to extract CRUD tables from SQL with options, which apply to each category of tables.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+/// use sql_insight::ExtractorOptions;
+///
+/// let dialect = GenericDialect {};
+/// let sql = "INSERT INTO t1 (a) SELECT a FROM t3 UNION SELECT a FROM t2 UNION SELECT a FROM t3";
+/// let options = ExtractorOptions::new().with_deduplicate(true).with_sort(true);
+/// let result = sql_insight::extract_crud_tables_with_options(&dialect, sql, options).unwrap();
+/// assert_eq!(result[0].as_ref().unwrap().to_string(), "Create: [t1], Read: [t2, t3], Update: [], Delete: []");
+/// ```
+pub fn extract_crud_tables_with_options(
+    dialect: &dyn Dialect,
+    sql: &str,
+    options: ExtractorOptions,
+) -> Result<Vec<Result<CrudTables, Error>>, Error> {
+    CrudTableExtractor::extract_with_options(dialect, sql, options)
+}
+
+/// Convenience function to extract CRUD tables from SQL, calling `on_unhandled` for each statement
+/// that is not classified into CRUD operations, such as `CALL`, `SET` or `SHOW`.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+///
+/// let dialect = GenericDialect {};
+/// let sql = "SELECT a FROM t1; CALL my_procedure()";
+/// let mut unhandled = vec![];
+/// let result = sql_insight::extract_crud_tables_with_unhandled(&dialect, sql, |statement| {
+///     unhandled.push(statement.to_string())
+/// })
+/// .unwrap();
+/// assert_eq!(result.len(), 2);
+/// assert_eq!(unhandled, ["CALL my_procedure()"]);
+/// ```
+pub fn extract_crud_tables_with_unhandled<F>(
+    dialect: &dyn Dialect,
+    sql: &str,
+    on_unhandled: F,
+) -> Result<Vec<Result<CrudTables, Error>>, Error>
+where
+    F: FnMut(&Statement),
+{
+    CrudTableExtractor::extract_with_unhandled(dialect, sql, on_unhandled)
+}
+
 /// [`CrudTables`] represents the tables involved in CRUD operations.
 #[derive(Default, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CrudTables {
     pub create_tables: Vec<TableReference>,
     pub read_tables: Vec<TableReference>,
     pub update_tables: Vec<TableReference>,
     pub delete_tables: Vec<TableReference>,
+    /// Tables whose definition is changed by DDL, which are not reported as read.
+    pub ddl: DdlTables,
+}
+
+/// [`DdlTables`] represents the tables created, altered, dropped or truncated by DDL.
+#[derive(Default, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DdlTables {
+    pub create_tables: Vec<TableReference>,
+    pub alter_tables: Vec<TableReference>,
+    pub drop_tables: Vec<TableReference>,
+    pub truncate_tables: Vec<TableReference>,
+}
+
+impl DdlTables {
+    pub fn is_empty(&self) -> bool {
+        self.create_tables.is_empty()
+            && self.alter_tables.is_empty()
+            && self.drop_tables.is_empty()
+            && self.truncate_tables.is_empty()
+    }
+
+    /// All tables changed by DDL, in the order of create, alter, drop and truncate.
+    pub fn tables(&self) -> Vec<TableReference> {
+        [
+            self.create_tables.as_slice(),
+            &self.alter_tables,
+            &self.drop_tables,
+            &self.truncate_tables,
+        ]
+        .concat()
+    }
 }
 
 impl fmt::Display for CrudTables {
@@ -51,11 +138,39 @@ impl fmt::Display for CrudTables {
             f,
             "Create: [{}], Read: [{}], Update: [{}], Delete: [{}]",
             create_tables, read_tables, update_tables, delete_tables
-        )
+        )?;
+        // DDL categories are only shown when present, so output for DML stays the same.
+        for (label, tables) in [
+            ("Create Table", &self.ddl.create_tables),
+            ("Alter Table", &self.ddl.alter_tables),
+            ("Drop Table", &self.ddl.drop_tables),
+            ("Truncate Table", &self.ddl.truncate_tables),
+        ] {
+            if !tables.is_empty() {
+                write!(f, ", {}: [{}]", label, self.format_tables(tables))?;
+            }
+        }
+        Ok(())
     }
 }
 
 impl CrudTables {
+    /// Apply options to each category of tables.
+    fn with_options(self, options: &ExtractorOptions) -> Self {
+        Self {
+            create_tables: options.apply(self.create_tables),
+            read_tables: options.apply(self.read_tables),
+            update_tables: options.apply(self.update_tables),
+            delete_tables: options.apply(self.delete_tables),
+            ddl: DdlTables {
+                create_tables: options.apply(self.ddl.create_tables),
+                alter_tables: options.apply(self.ddl.alter_tables),
+                drop_tables: options.apply(self.ddl.drop_tables),
+                truncate_tables: options.apply(self.ddl.truncate_tables),
+            },
+        }
+    }
+
     fn format_tables(&self, tables: &[TableReference]) -> String {
         tables
             .iter()
@@ -66,6 +181,10 @@ impl CrudTables {
 }
 
 /// A visitor to extract CRUD tables from SQL.
+///
+/// References to CTEs are not tables, so they are excluded from read tables.
+/// A CTE is visible in the query of the WITH clause defining it, including subqueries, and shadows tables of the same name.
+/// Each CTE sees only the CTEs preceding it, and itself too if recursive. Unquoted names are compared case-insensitively.
 #[derive(Default, Debug)]
 pub struct CrudTableExtractor {
     create_tables: Vec<TableReference>,
@@ -73,11 +192,38 @@ pub struct CrudTableExtractor {
     update_tables: Vec<TableReference>,
     delete_tables: Vec<TableReference>,
     possibly_aliased_delete_tables: Vec<TableReference>,
+    ddl: DdlTables,
+    // CTEs in scope of the queries being visited.
+    cte_scopes: helper::CteScopes,
+    // References to CTEs found in the SQL, to be excluded from read tables.
+    cte_references: Vec<TableReference>,
 }
 
 impl Visitor for CrudTableExtractor {
     type Break = Error;
 
+    fn pre_visit_query(&mut self, query: &Query) -> ControlFlow<Self::Break> {
+        self.cte_scopes.enter(query);
+        ControlFlow::Continue(())
+    }
+
+    fn post_visit_query(&mut self, _query: &Query) -> ControlFlow<Self::Break> {
+        self.cte_scopes.exit();
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_table_factor(&mut self, table_factor: &TableFactor) -> ControlFlow<Self::Break> {
+        if let TableFactor::Table { name, .. } = table_factor {
+            if self.cte_scopes.contains(name) {
+                match TableReference::try_from(table_factor) {
+                    Ok(table) => self.cte_references.push(table),
+                    Err(e) => return ControlFlow::Break(e),
+                }
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
     fn pre_visit_statement(&mut self, statement: &Statement) -> ControlFlow<Self::Break> {
         match statement {
             Statement::Insert { table_name, .. } => {
@@ -156,6 +302,26 @@ impl Visitor for CrudTableExtractor {
                 self.read_tables =
                     helper::calc_difference_of_tables(self.read_tables.clone(), vec![target_table]);
             }
+            Statement::CreateTable { name, .. } | Statement::CreateVirtualTable { name, .. } => {
+                return self
+                    .push_ddl_tables(std::slice::from_ref(name), |ddl| &mut ddl.create_tables);
+            }
+            Statement::AlterTable { name, .. } => {
+                return self
+                    .push_ddl_tables(std::slice::from_ref(name), |ddl| &mut ddl.alter_tables);
+            }
+            Statement::Drop {
+                object_type: ObjectType::Table,
+                names,
+                ..
+            } => {
+                return self.push_ddl_tables(names, |ddl| &mut ddl.drop_tables);
+            }
+            Statement::Truncate { table_name, .. } => {
+                return self.push_ddl_tables(std::slice::from_ref(table_name), |ddl| {
+                    &mut ddl.truncate_tables
+                });
+            }
             _ => {}
         }
         ControlFlow::Continue(())
@@ -168,7 +334,7 @@ impl CrudTableExtractor {
         dialect: &dyn Dialect,
         sql: &str,
     ) -> Result<Vec<Result<CrudTables, Error>>, Error> {
-        let statements = Parser::parse_sql(dialect, sql)?;
+        let statements = instrument::parse_sql(dialect, sql)?;
         let results = statements
             .iter()
             .map(Self::extract_from_statement)
@@ -176,20 +342,105 @@ impl CrudTableExtractor {
         Ok(results)
     }
 
-    fn extract_from_statement(statement: &Statement) -> Result<CrudTables, Error> {
-        let mut visitor = CrudTableExtractor {
-            read_tables: TableExtractor::extract_from_statement(statement)?.0,
-            ..Default::default()
-        };
-        match statement.visit(&mut visitor) {
-            ControlFlow::Break(e) => Err(e),
-            ControlFlow::Continue(()) => Ok(CrudTables {
-                create_tables: visitor.create_tables,
-                read_tables: visitor.read_tables,
-                update_tables: visitor.update_tables,
-                delete_tables: visitor.delete_tables,
-            }),
+    /// Extract CRUD tables from SQL with options.
+    pub fn extract_with_options(
+        dialect: &dyn Dialect,
+        sql: &str,
+        options: ExtractorOptions,
+    ) -> Result<Vec<Result<CrudTables, Error>>, Error> {
+        let statements = instrument::parse_sql(dialect, sql)?;
+        let results = statements
+            .iter()
+            .map(|statement| {
+                Self::extract_from_statement(statement)
+                    .map(|crud_tables| crud_tables.with_options(&options))
+            })
+            .collect::<Vec<Result<CrudTables, Error>>>();
+        Ok(results)
+    }
+
+    /// Extract CRUD tables from SQL, calling `on_unhandled` for each statement that is not classified into CRUD operations.
+    /// Unhandled statements still have their (possibly empty) entry in the results.
+    pub fn extract_with_unhandled<F>(
+        dialect: &dyn Dialect,
+        sql: &str,
+        mut on_unhandled: F,
+    ) -> Result<Vec<Result<CrudTables, Error>>, Error>
+    where
+        F: FnMut(&Statement),
+    {
+        let statements = instrument::parse_sql(dialect, sql)?;
+        let results = statements
+            .iter()
+            .map(|statement| {
+                if !Self::is_handled(statement) {
+                    on_unhandled(statement);
+                }
+                Self::extract_from_statement(statement)
+            })
+            .collect::<Vec<Result<CrudTables, Error>>>();
+        Ok(results)
+    }
+
+    /// Whether the statement is classified into CRUD operations by this extractor.
+    pub fn is_handled(statement: &Statement) -> bool {
+        matches!(
+            statement,
+            Statement::Query(_)
+                | Statement::Insert { .. }
+                | Statement::Update { .. }
+                | Statement::Delete { .. }
+                | Statement::Merge { .. }
+                | Statement::CreateTable { .. }
+                | Statement::CreateVirtualTable { .. }
+                | Statement::AlterTable { .. }
+                | Statement::Drop {
+                    object_type: ObjectType::Table,
+                    ..
+                }
+                | Statement::Truncate { .. }
+        )
+    }
+
+    /// Record tables changed by DDL into the category selected by `category`, and exclude them from read tables.
+    fn push_ddl_tables(
+        &mut self,
+        names: &[ObjectName],
+        category: fn(&mut DdlTables) -> &mut Vec<TableReference>,
+    ) -> ControlFlow<Error> {
+        for name in names {
+            match TableReference::try_from(name) {
+                Ok(table) => category(&mut self.ddl).push(table),
+                Err(e) => return ControlFlow::Break(e),
+            }
         }
+        self.read_tables = helper::calc_difference_of_tables(
+            self.read_tables.clone(),
+            category(&mut self.ddl).clone(),
+        );
+        ControlFlow::Continue(())
+    }
+
+    pub fn extract_from_statement(statement: &Statement) -> Result<CrudTables, Error> {
+        instrument::try_analyze("extract_crud_tables", || {
+            let mut visitor = CrudTableExtractor {
+                read_tables: TableExtractor::extract_from_statement(statement)?.0,
+                ..Default::default()
+            };
+            match statement.visit(&mut visitor) {
+                ControlFlow::Break(e) => Err(e),
+                ControlFlow::Continue(()) => Ok(CrudTables {
+                    create_tables: visitor.create_tables,
+                    read_tables: helper::calc_difference_of_tables(
+                        visitor.read_tables,
+                        visitor.cte_references,
+                    ),
+                    update_tables: visitor.update_tables,
+                    delete_tables: visitor.delete_tables,
+                    ddl: visitor.ddl,
+                }),
+            }
+        })
     }
 }
 
@@ -223,6 +474,7 @@ mod tests {
             }],
             update_tables: vec![],
             delete_tables: vec![],
+            ddl: DdlTables::default(),
         })];
         assert_crud_table_extraction(sql, expected, all_dialects());
     }
@@ -241,6 +493,7 @@ mod tests {
                 }],
                 update_tables: vec![],
                 delete_tables: vec![],
+                ddl: DdlTables::default(),
             }),
             Ok(CrudTables {
                 create_tables: vec![],
@@ -252,6 +505,7 @@ mod tests {
                 }],
                 update_tables: vec![],
                 delete_tables: vec![],
+                ddl: DdlTables::default(),
             }),
         ];
         assert_crud_table_extraction(sql, expected, all_dialects());
@@ -270,6 +524,7 @@ mod tests {
             }],
             update_tables: vec![],
             delete_tables: vec![],
+            ddl: DdlTables::default(),
         })];
         assert_crud_table_extraction(sql, expected, all_dialects());
     }
@@ -287,6 +542,7 @@ mod tests {
             }],
             update_tables: vec![],
             delete_tables: vec![],
+            ddl: DdlTables::default(),
         })];
         assert_crud_table_extraction(sql, expected, all_dialects());
     }
@@ -304,6 +560,7 @@ mod tests {
             }],
             update_tables: vec![],
             delete_tables: vec![],
+            ddl: DdlTables::default(),
         })];
         assert_crud_table_extraction(sql, expected, all_dialects());
     }
@@ -333,6 +590,7 @@ mod tests {
                     name: "t1".into(),
                     alias: None,
                 }],
+                ddl: DdlTables::default(),
             })];
             assert_crud_table_extraction(sql, expected, all_dialects());
         }
@@ -350,6 +608,7 @@ mod tests {
                     name: "t1".into(),
                     alias: None,
                 }],
+                ddl: DdlTables::default(),
             })];
             assert_crud_table_extraction(sql, expected, all_dialects());
         }
@@ -367,6 +626,7 @@ mod tests {
                     name: "t1".into(),
                     alias: Some("t1_alias".into()),
                 }],
+                ddl: DdlTables::default(),
             })];
             assert_crud_table_extraction(sql, expected, all_dialects());
         }
@@ -411,6 +671,7 @@ mod tests {
                         alias: None,
                     },
                 ],
+                ddl: DdlTables::default(),
             })];
             assert_crud_table_extraction(sql, expected, all_dialects());
         }
@@ -456,6 +717,7 @@ mod tests {
                         alias: Some("t2_alias".into()),
                     },
                 ],
+                ddl: DdlTables::default(),
             })];
             assert_crud_table_extraction(sql, expected, all_dialects());
         }
@@ -500,6 +762,7 @@ mod tests {
                         alias: None,
                     },
                 ],
+                ddl: DdlTables::default(),
             })];
             assert_crud_table_extraction(sql, expected, all_dialects());
         }
@@ -544,6 +807,7 @@ mod tests {
                         alias: Some("t2_alias".into()),
                     },
                 ],
+                ddl: DdlTables::default(),
             })];
             assert_crud_table_extraction(sql, expected, all_dialects());
         }
@@ -565,6 +829,7 @@ mod tests {
                 read_tables: vec![],
                 update_tables: vec![],
                 delete_tables: vec![],
+                ddl: DdlTables::default(),
             })];
             assert_crud_table_extraction(sql, expected, all_dialects());
         }
@@ -595,6 +860,7 @@ mod tests {
                 ],
                 update_tables: vec![],
                 delete_tables: vec![],
+                ddl: DdlTables::default(),
             })];
             assert_crud_table_extraction(sql, expected, all_dialects());
         }
@@ -619,6 +885,7 @@ mod tests {
                         alias: None,
                     }],
                     delete_tables: vec![],
+                    ddl: DdlTables::default(),
                 }),]
             )
         }
@@ -649,6 +916,7 @@ mod tests {
                     },
                 ],
                 delete_tables: vec![],
+                ddl: DdlTables::default(),
             })];
             assert_crud_table_extraction(sql, expected, all_dialects());
         }
@@ -685,24 +953,64 @@ mod tests {
                 name: "t1".into(),
                 alias: Some("t1_alias".into()),
             }],
+            ddl: DdlTables::default(),
         })];
         assert_crud_table_extraction(sql, expected, all_dialects());
     }
 
+    #[test]
+    fn test_unhandled_statements() {
+        let sql = "SELECT a FROM t1; CALL my_procedure(); SET a = 1; DELETE FROM t2";
+        for dialect in all_dialects() {
+            let mut unhandled = vec![];
+            let result =
+                CrudTableExtractor::extract_with_unhandled(dialect.as_ref(), sql, |statement| {
+                    unhandled.push(statement.to_string())
+                })
+                .unwrap();
+            assert_eq!(result.len(), 4, "Failed for dialect: {dialect:?}");
+            assert_eq!(
+                result[1],
+                Ok(CrudTables::default()),
+                "Failed for dialect: {dialect:?}"
+            );
+            assert_eq!(
+                unhandled,
+                vec!["CALL my_procedure()", "SET a = 1"],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    fn table(name: &str) -> TableReference {
+        TableReference {
+            catalog: None,
+            schema: None,
+            name: name.into(),
+            alias: None,
+        }
+    }
+
     #[test]
     fn test_create_table_statement() {
-        let sql = "CREATE TABLE t1 (a INT)";
-        let expected = vec![Ok(CrudTables {
-            create_tables: vec![],
-            read_tables: vec![TableReference {
-                catalog: None,
-                schema: None,
-                name: "t1".into(),
-                alias: None,
-            }],
-            update_tables: vec![],
-            delete_tables: vec![],
-        })];
+        let sql = "CREATE TABLE t1 (a INT); CREATE TABLE t2 AS SELECT a FROM t1";
+        let expected = vec![
+            Ok(CrudTables {
+                ddl: DdlTables {
+                    create_tables: vec![table("t1")],
+                    ..Default::default()
+                },
+                ..Default::default()
+            }),
+            Ok(CrudTables {
+                read_tables: vec![table("t1")],
+                ddl: DdlTables {
+                    create_tables: vec![table("t2")],
+                    ..Default::default()
+                },
+                ..Default::default()
+            }),
+        ];
         assert_crud_table_extraction(sql, expected, all_dialects());
     }
 
@@ -710,16 +1018,178 @@ mod tests {
     fn test_alters_table_statement() {
         let sql = "ALTER TABLE t1 ADD COLUMN a INT";
         let expected = vec![Ok(CrudTables {
-            create_tables: vec![],
-            read_tables: vec![TableReference {
-                catalog: None,
-                schema: None,
-                name: "t1".into(),
+            ddl: DdlTables {
+                alter_tables: vec![table("t1")],
+                ..Default::default()
+            },
+            ..Default::default()
+        })];
+        assert_crud_table_extraction(sql, expected, all_dialects());
+    }
+
+    #[test]
+    fn test_drop_and_truncate_table_statements() {
+        let sql = "DROP TABLE t1, t2; TRUNCATE TABLE t3";
+        let expected = vec![
+            Ok(CrudTables {
+                ddl: DdlTables {
+                    drop_tables: vec![table("t1"), table("t2")],
+                    ..Default::default()
+                },
+                ..Default::default()
+            }),
+            Ok(CrudTables {
+                ddl: DdlTables {
+                    truncate_tables: vec![table("t3")],
+                    ..Default::default()
+                },
+                ..Default::default()
+            }),
+        ];
+        assert_crud_table_extraction(
+            sql,
+            expected,
+            vec![
+                Box::new(sqlparser::dialect::GenericDialect {}),
+                Box::new(sqlparser::dialect::MySqlDialect {}),
+                Box::new(sqlparser::dialect::PostgreSqlDialect {}),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_ddl_display() {
+        let crud_tables = CrudTables {
+            read_tables: vec![table("t1")],
+            ddl: DdlTables {
+                create_tables: vec![table("t2")],
+                drop_tables: vec![table("t3")],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert_eq!(
+            crud_tables.to_string(),
+            "Create: [], Read: [t1], Update: [], Delete: [], Create Table: [t2], Drop Table: [t3]"
+        );
+        assert!(!crud_tables.ddl.is_empty());
+    }
+
+    #[test]
+    fn test_statement_with_ctes() {
+        let sql = "WITH c1 AS (SELECT a FROM t1), c2 AS (SELECT a FROM c1 JOIN t2 ON c1.a = t2.a) \
+            SELECT * FROM c2 AS x WHERE x.a IN (SELECT a FROM c1); \
+            INSERT INTO t3 (a) WITH c1 AS (SELECT a FROM t1) SELECT a FROM c1";
+        let expected = vec![
+            Ok(CrudTables {
+                read_tables: vec![table("t1"), table("t2")],
+                ..Default::default()
+            }),
+            Ok(CrudTables {
+                create_tables: vec![table("t3")],
+                read_tables: vec![table("t1")],
+                ..Default::default()
+            }),
+        ];
+        assert_crud_table_extraction(sql, expected, all_dialects());
+    }
+
+    #[test]
+    fn test_cte_scope() {
+        // A CTE of a subquery is not visible outside it, where the same name refers to a table.
+        let sql = "SELECT * FROM c1 WHERE a IN (WITH c1 AS (SELECT a FROM t1) SELECT a FROM c1)";
+        let expected = vec![Ok(CrudTables {
+            read_tables: vec![table("t1"), table("c1")],
+            ..Default::default()
+        })];
+        assert_crud_table_extraction(sql, expected, all_dialects());
+    }
+
+    #[test]
+    fn test_cte_shadowing_table_of_same_name() {
+        // A non-recursive CTE doesn't see itself, so the same name in its body refers to the table.
+        let sql = "WITH t1 AS (SELECT * FROM t1) SELECT * FROM t1";
+        let expected = vec![Ok(CrudTables {
+            read_tables: vec![table("t1")],
+            ..Default::default()
+        })];
+        assert_crud_table_extraction(sql, expected, all_dialects());
+        let sql = "WITH c1 AS (SELECT a FROM c2), c2 AS (SELECT a FROM t1) SELECT * FROM c1";
+        let expected = vec![Ok(CrudTables {
+            read_tables: vec![table("c2"), table("t1")],
+            ..Default::default()
+        })];
+        assert_crud_table_extraction(sql, expected, all_dialects());
+    }
+
+    #[test]
+    fn test_cte_names_are_case_insensitive() {
+        let sql = "WITH Cte AS (SELECT a FROM t1) SELECT * FROM cte JOIN CTE AS x ON cte.a = x.a";
+        let expected = vec![Ok(CrudTables {
+            read_tables: vec![table("t1")],
+            ..Default::default()
+        })];
+        assert_crud_table_extraction(sql, expected, all_dialects());
+    }
+
+    #[test]
+    fn test_extract_with_options() {
+        let sql = "UPDATE t2 SET a = 1 WHERE b IN (SELECT b FROM t3) AND c IN (SELECT c FROM t1 UNION SELECT c FROM t3)";
+        let expected = vec![Ok(CrudTables {
+            read_tables: vec![table("t1"), table("t3")],
+            update_tables: vec![table("t2")],
+            ..Default::default()
+        })];
+        for dialect in all_dialects() {
+            let result = CrudTableExtractor::extract_with_options(
+                dialect.as_ref(),
+                sql,
+                ExtractorOptions::new()
+                    .with_deduplicate(true)
+                    .with_sort(true),
+            )
+            .unwrap();
+            assert_eq!(result, expected, "Failed for dialect: {dialect:?}");
+        }
+    }
+
+    #[test]
+    fn test_extract_with_default_schema() {
+        let sql = "INSERT INTO s1.t2 (a) WITH c AS (SELECT a FROM t1) SELECT a FROM c JOIN t3 ON c.a = t3.a";
+        let expected = vec![Ok(CrudTables {
+            create_tables: vec![TableReference {
+                catalog: Some("app".into()),
+                schema: Some("s1".into()),
+                name: "t2".into(),
                 alias: None,
             }],
-            update_tables: vec![],
-            delete_tables: vec![],
+            read_tables: vec![
+                TableReference {
+                    catalog: Some("app".into()),
+                    schema: Some("public".into()),
+                    name: "t1".into(),
+                    alias: None,
+                },
+                TableReference {
+                    catalog: Some("app".into()),
+                    schema: Some("public".into()),
+                    name: "t3".into(),
+                    alias: None,
+                },
+            ],
+            ..Default::default()
         })];
-        assert_crud_table_extraction(sql, expected, all_dialects());
+        for dialect in all_dialects() {
+            let result = CrudTableExtractor::extract_with_options(
+                dialect.as_ref(),
+                sql,
+                ExtractorOptions::new()
+                    .with_default_schema("public")
+                    .with_default_catalog("app")
+                    .with_sort(true),
+            )
+            .unwrap();
+            assert_eq!(result, expected, "Failed for dialect: {dialect:?}");
+        }
     }
 }
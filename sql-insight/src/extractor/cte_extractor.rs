@@ -0,0 +1,344 @@
+//! A Extractor that extracts the dependency graph among CTEs of SQL queries.
+//!
+//! See [`extract_cte_graphs`](crate::extract_cte_graphs()) as the entry point for extracting CTE graphs from SQL.
+
+use std::ops::ControlFlow;
+
+use crate::error::Error;
+use crate::extractor::table_extractor::TableReference;
+use crate::instrument;
+use sqlparser::ast::{Ident, ObjectName, Query, Statement, Visit, Visitor};
+use sqlparser::dialect::Dialect;
+
+/// Convenience function to extract CTE graphs from SQL.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+///
+/// let dialect = GenericDialect {};
+/// let sql = "WITH a AS (SELECT * FROM t1), b AS (SELECT * FROM a JOIN t2 ON a.id = t2.id) SELECT * FROM b";
+/// let result = sql_insight::extract_cte_graphs(&dialect, sql).unwrap();
+/// let graph = result[0].as_ref().unwrap();
+/// assert_eq!(graph.ctes[1].name.value, "b");
+/// assert_eq!(graph.ctes[1].dependencies.to_string(), "CTEs: [a], Tables: [t2]");
+/// assert_eq!(graph.final_query.to_string(), "CTEs: [b], Tables: []");
+/// ```
+pub fn extract_cte_graphs(
+    dialect: &dyn Dialect,
+    sql: &str,
+) -> Result<Vec<Result<CteGraph, Error>>, Error> {
+    CteExtractor::extract(dialect, sql)
+}
+
+/// [`CteGraph`] represents the CTEs of the outermost WITH clause of a statement and the final query using them.
+/// CTEs and their dependencies form a graph where each CTE is a node, which is acyclic unless the WITH is `RECURSIVE`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CteGraph {
+    /// Whether the WITH clause is `RECURSIVE`.
+    pub recursive: bool,
+    /// CTEs in order of definition.
+    pub ctes: Vec<CteNode>,
+    /// Dependencies of the query following the WITH clause.
+    pub final_query: QueryDependencies,
+}
+
+/// [`CteNode`] represents a CTE and what its query depends on.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CteNode {
+    pub name: Ident,
+    pub dependencies: QueryDependencies,
+}
+
+/// [`QueryDependencies`] represents the CTEs and physical tables a query reads, including in subqueries.
+/// Tables defined by WITH clauses nested in the query are neither CTEs nor tables of it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct QueryDependencies {
+    /// CTEs of the graph, in order of first reference.
+    pub ctes: Vec<Ident>,
+    /// Physical tables, without aliases, in order of first reference.
+    pub tables: Vec<TableReference>,
+}
+
+impl std::fmt::Display for QueryDependencies {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let ctes = self
+            .ctes
+            .iter()
+            .map(|cte| cte.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let tables = self
+            .tables
+            .iter()
+            .map(|table| table.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        write!(f, "CTEs: [{}], Tables: [{}]", ctes, tables)
+    }
+}
+
+/// An extractor of CTE graphs.
+#[derive(Default, Debug)]
+pub struct CteExtractor;
+
+impl CteExtractor {
+    /// Extract CTE graphs from SQL.
+    pub fn extract(
+        dialect: &dyn Dialect,
+        sql: &str,
+    ) -> Result<Vec<Result<CteGraph, Error>>, Error> {
+        let statements = instrument::parse_sql(dialect, sql)?;
+        Ok(statements
+            .iter()
+            .map(Self::extract_from_statement)
+            .collect())
+    }
+
+    /// Extract the CTE graph of the outermost query of a statement, i.e. the query of SELECT, the source of INSERT,
+    /// or the query of CREATE VIEW and CREATE TABLE ... AS SELECT. The graph is empty for other statements.
+    pub fn extract_from_statement(statement: &Statement) -> Result<CteGraph, Error> {
+        let query = match statement {
+            Statement::Query(query) => Some(query),
+            Statement::Insert { source, .. } => source.as_ref(),
+            Statement::CreateView { query, .. } => Some(query),
+            Statement::CreateTable { query, .. } => query.as_ref(),
+            _ => None,
+        };
+        match query {
+            Some(query) => Self::extract_from_query(query),
+            None => Ok(CteGraph::default()),
+        }
+    }
+
+    /// Extract the CTE graph of a query.
+    pub fn extract_from_query(query: &Query) -> Result<CteGraph, Error> {
+        let Some(with) = &query.with else {
+            let mut visitor = DependencyVisitor::new(&[]);
+            visit_final_query(query, &mut visitor)?;
+            return Ok(CteGraph {
+                final_query: visitor.dependencies,
+                ..Default::default()
+            });
+        };
+        let names = with
+            .cte_tables
+            .iter()
+            .map(|cte| cte.alias.name.clone())
+            .collect::<Vec<_>>();
+        let mut ctes = vec![];
+        for (index, cte) in with.cte_tables.iter().enumerate() {
+            // A CTE can refer to the CTEs defined before it, and any CTE if recursive.
+            let visible = if with.recursive {
+                &names[..]
+            } else {
+                &names[..index]
+            };
+            let mut visitor = DependencyVisitor::new(visible);
+            if let ControlFlow::Break(e) = cte.query.visit(&mut visitor) {
+                return Err(e);
+            }
+            ctes.push(CteNode {
+                name: cte.alias.name.clone(),
+                dependencies: visitor.dependencies,
+            });
+        }
+        let mut visitor = DependencyVisitor::new(&names);
+        visit_final_query(query, &mut visitor)?;
+        Ok(CteGraph {
+            recursive: with.recursive,
+            ctes,
+            final_query: visitor.dependencies,
+        })
+    }
+}
+
+/// Visit a query except its WITH clause.
+fn visit_final_query(query: &Query, visitor: &mut DependencyVisitor) -> Result<(), Error> {
+    if let ControlFlow::Break(e) = query.body.visit(visitor) {
+        return Err(e);
+    }
+    for order_by in &query.order_by {
+        if let ControlFlow::Break(e) = order_by.visit(visitor) {
+            return Err(e);
+        }
+    }
+    Ok(())
+}
+
+struct DependencyVisitor<'a> {
+    ctes: &'a [Ident],
+    /// Names of CTEs defined by WITH clauses nested in the visited query, shadowing the CTEs of the graph.
+    nested_scopes: Vec<Vec<String>>,
+    dependencies: QueryDependencies,
+}
+
+impl<'a> DependencyVisitor<'a> {
+    fn new(ctes: &'a [Ident]) -> Self {
+        Self {
+            ctes,
+            nested_scopes: vec![],
+            dependencies: QueryDependencies::default(),
+        }
+    }
+}
+
+impl Visitor for DependencyVisitor<'_> {
+    type Break = Error;
+
+    fn pre_visit_query(&mut self, query: &Query) -> ControlFlow<Self::Break> {
+        self.nested_scopes.push(
+            query
+                .with
+                .iter()
+                .flat_map(|with| &with.cte_tables)
+                .map(|cte| cte.alias.name.value.clone())
+                .collect(),
+        );
+        ControlFlow::Continue(())
+    }
+
+    fn post_visit_query(&mut self, _query: &Query) -> ControlFlow<Self::Break> {
+        self.nested_scopes.pop();
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_relation(&mut self, relation: &ObjectName) -> ControlFlow<Self::Break> {
+        if let [name] = relation.0.as_slice() {
+            if self
+                .nested_scopes
+                .iter()
+                .flatten()
+                .any(|n| *n == name.value)
+            {
+                return ControlFlow::Continue(());
+            }
+            if let Some(cte) = self.ctes.iter().find(|cte| cte.value == name.value) {
+                if !self.dependencies.ctes.contains(cte) {
+                    self.dependencies.ctes.push(cte.clone());
+                }
+                return ControlFlow::Continue(());
+            }
+        }
+        match TableReference::try_from(relation) {
+            Ok(table) => {
+                if !self.dependencies.tables.contains(&table) {
+                    self.dependencies.tables.push(table);
+                }
+                ControlFlow::Continue(())
+            }
+            Err(e) => ControlFlow::Break(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+
+    fn dependencies(ctes: &[&str], tables: &[&str]) -> QueryDependencies {
+        QueryDependencies {
+            ctes: ctes.iter().map(|cte| Ident::new(*cte)).collect(),
+            tables: tables
+                .iter()
+                .map(|table| TableReference {
+                    catalog: None,
+                    schema: None,
+                    name: Ident::new(*table),
+                    alias: None,
+                })
+                .collect(),
+        }
+    }
+
+    fn node(name: &str, ctes: &[&str], tables: &[&str]) -> CteNode {
+        CteNode {
+            name: Ident::new(name),
+            dependencies: dependencies(ctes, tables),
+        }
+    }
+
+    fn assert_cte_graph(sql: &str, expected: CteGraph, dialects: Vec<Box<dyn Dialect>>) {
+        for dialect in dialects {
+            let result = extract_cte_graphs(dialect.as_ref(), sql).unwrap().remove(0);
+            assert_eq!(
+                result,
+                Ok(expected.clone()),
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_cte_graph() {
+        let sql = "WITH a AS (SELECT id FROM t1), \
+            b AS (SELECT id FROM t2 WHERE id IN (SELECT id FROM a)), \
+            c AS (SELECT * FROM a JOIN b ON a.id = b.id JOIN t1 ON t1.id = a.id) \
+            SELECT * FROM c WHERE EXISTS (SELECT 1 FROM t3)";
+        assert_cte_graph(
+            sql,
+            CteGraph {
+                recursive: false,
+                ctes: vec![
+                    node("a", &[], &["t1"]),
+                    node("b", &["a"], &["t2"]),
+                    node("c", &["a", "b"], &["t1"]),
+                ],
+                final_query: dependencies(&["c"], &["t3"]),
+            },
+            all_dialects(),
+        );
+    }
+
+    #[test]
+    fn test_forward_reference_and_nested_with() {
+        let sql = "WITH a AS (SELECT * FROM b), \
+            b AS (WITH a AS (SELECT * FROM t1) SELECT * FROM a) \
+            SELECT * FROM a";
+        assert_cte_graph(
+            sql,
+            CteGraph {
+                recursive: false,
+                ctes: vec![node("a", &[], &["b"]), node("b", &[], &["t1"])],
+                final_query: dependencies(&["a"], &[]),
+            },
+            all_dialects(),
+        );
+    }
+
+    #[test]
+    fn test_without_with() {
+        assert_cte_graph(
+            "INSERT INTO t1 SELECT * FROM t2",
+            CteGraph {
+                final_query: dependencies(&[], &["t2"]),
+                ..Default::default()
+            },
+            all_dialects(),
+        );
+        assert_cte_graph("DROP TABLE t1", CteGraph::default(), all_dialects());
+    }
+
+    #[test]
+    fn test_recursive() {
+        let sql = "WITH RECURSIVE r AS (SELECT id FROM t1 UNION ALL SELECT t1.id FROM t1 JOIN r ON t1.parent_id = r.id) \
+            SELECT * FROM r";
+        assert_cte_graph(
+            sql,
+            CteGraph {
+                recursive: true,
+                ctes: vec![node("r", &["r"], &["t1"])],
+                final_query: dependencies(&["r"], &[]),
+            },
+            vec![
+                Box::new(sqlparser::dialect::PostgreSqlDialect {}),
+                Box::new(sqlparser::dialect::GenericDialect {}),
+            ],
+        );
+    }
+}
@@ -0,0 +1,469 @@
+//! A Extractor that extracts objects touched by DDL and the kind of changes made to them.
+//!
+//! See [`extract_ddl_objects`](crate::extract_ddl_objects()) as the entry point for extracting DDL objects from SQL.
+
+use core::fmt;
+
+use crate::error::Error;
+use crate::instrument;
+use sqlparser::ast::{
+    AlterIndexOperation, AlterTableOperation, Ident, ObjectName, ObjectType, SchemaName, Statement,
+    TableConstraint,
+};
+use sqlparser::dialect::Dialect;
+
+/// Convenience function to extract objects touched by DDL from SQL, for each statement in input order.
+/// Statements other than DDL have no objects.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+/// use sql_insight::{DdlAction, DdlObjectKind};
+///
+/// let dialect = GenericDialect {};
+/// let sql = "CREATE INDEX i1 ON t1 (a); \
+///     ALTER TABLE t1 ADD COLUMN b INT, DROP CONSTRAINT fk1; \
+///     DROP TABLE t2, t3";
+/// let result = sql_insight::extract_ddl_objects(&dialect, sql).unwrap();
+/// assert_eq!(result[0][0].kind, DdlObjectKind::Index);
+/// assert_eq!(result[0][0].action, DdlAction::Create);
+/// assert_eq!(result[0][0].to_string(), "CREATE INDEX i1 ON t1");
+/// assert_eq!(result[1][0].to_string(), "ALTER TABLE t1 (ADD COLUMN b, DROP CONSTRAINT fk1)");
+/// assert_eq!(result[2].len(), 2);
+/// ```
+pub fn extract_ddl_objects(dialect: &dyn Dialect, sql: &str) -> Result<Vec<Vec<DdlObject>>, Error> {
+    DdlExtractor::extract(dialect, sql)
+}
+
+/// [`DdlObjectKind`] represents the kind of object touched by DDL.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum DdlObjectKind {
+    /// Tables, including virtual tables.
+    Table,
+    /// Views, including materialized views.
+    View,
+    Index,
+    Sequence,
+    Schema,
+    Database,
+}
+
+impl fmt::Display for DdlObjectKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            DdlObjectKind::Table => "TABLE",
+            DdlObjectKind::View => "VIEW",
+            DdlObjectKind::Index => "INDEX",
+            DdlObjectKind::Sequence => "SEQUENCE",
+            DdlObjectKind::Schema => "SCHEMA",
+            DdlObjectKind::Database => "DATABASE",
+        })
+    }
+}
+
+/// [`DdlAction`] represents what DDL does to an object.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum DdlAction {
+    Create,
+    Alter,
+    Drop,
+    /// `TRUNCATE`, deleting all rows of a table without changing its definition.
+    Truncate,
+}
+
+impl fmt::Display for DdlAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            DdlAction::Create => "CREATE",
+            DdlAction::Alter => "ALTER",
+            DdlAction::Drop => "DROP",
+            DdlAction::Truncate => "TRUNCATE",
+        })
+    }
+}
+
+/// [`DdlChange`] represents a change made to an object by `ALTER`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum DdlChange {
+    AddColumn {
+        column: Ident,
+    },
+    DropColumn {
+        column: Ident,
+    },
+    /// A change of the type, default or nullability of a column.
+    AlterColumn {
+        column: Ident,
+    },
+    RenameColumn {
+        old_name: Ident,
+        new_name: Ident,
+    },
+    /// MySQL's `CHANGE COLUMN`, redefining a column with possibly a new name.
+    ChangeColumn {
+        old_name: Ident,
+        new_name: Ident,
+    },
+    /// A constraint added, with its name if named.
+    AddConstraint {
+        name: Option<Ident>,
+    },
+    DropConstraint {
+        name: Ident,
+    },
+    RenameConstraint {
+        old_name: Ident,
+        new_name: Ident,
+    },
+    DropPrimaryKey,
+    /// A rename of the object itself.
+    Rename {
+        new_name: ObjectName,
+    },
+    /// A redefinition of the query of a view.
+    AlterQuery,
+    /// Other changes, with the SQL of the operation.
+    Other(String),
+}
+
+impl fmt::Display for DdlChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DdlChange::AddColumn { column } => write!(f, "ADD COLUMN {}", column),
+            DdlChange::DropColumn { column } => write!(f, "DROP COLUMN {}", column),
+            DdlChange::AlterColumn { column } => write!(f, "ALTER COLUMN {}", column),
+            DdlChange::RenameColumn { old_name, new_name } => {
+                write!(f, "RENAME COLUMN {} TO {}", old_name, new_name)
+            }
+            DdlChange::ChangeColumn { old_name, new_name } => {
+                write!(f, "CHANGE COLUMN {} {}", old_name, new_name)
+            }
+            DdlChange::AddConstraint { name: Some(name) } => {
+                write!(f, "ADD CONSTRAINT {}", name)
+            }
+            DdlChange::AddConstraint { name: None } => write!(f, "ADD CONSTRAINT"),
+            DdlChange::DropConstraint { name } => write!(f, "DROP CONSTRAINT {}", name),
+            DdlChange::RenameConstraint { old_name, new_name } => {
+                write!(f, "RENAME CONSTRAINT {} TO {}", old_name, new_name)
+            }
+            DdlChange::DropPrimaryKey => write!(f, "DROP PRIMARY KEY"),
+            DdlChange::Rename { new_name } => write!(f, "RENAME TO {}", new_name),
+            DdlChange::AlterQuery => write!(f, "AS query"),
+            DdlChange::Other(operation) => write!(f, "{}", operation),
+        }
+    }
+}
+
+/// [`DdlObject`] represents an object touched by DDL, with the changes made to it by `ALTER`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DdlObject {
+    pub kind: DdlObjectKind,
+    pub action: DdlAction,
+    /// Name of the object, which is `None` for indexes created without a name.
+    pub name: Option<ObjectName>,
+    /// Table of an index created, for `CREATE INDEX`.
+    pub table: Option<ObjectName>,
+    /// Changes made by `ALTER`, in order of appearance. Empty for other actions.
+    pub changes: Vec<DdlChange>,
+}
+
+impl fmt::Display for DdlObject {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.action, self.kind)?;
+        if let Some(name) = &self.name {
+            write!(f, " {}", name)?;
+        }
+        if let Some(table) = &self.table {
+            write!(f, " ON {}", table)?;
+        }
+        if !self.changes.is_empty() {
+            let changes = self
+                .changes
+                .iter()
+                .map(|c| c.to_string())
+                .collect::<Vec<String>>()
+                .join(", ");
+            write!(f, " ({})", changes)?;
+        }
+        Ok(())
+    }
+}
+
+impl DdlObject {
+    fn new(kind: DdlObjectKind, action: DdlAction, name: &ObjectName) -> Self {
+        Self {
+            kind,
+            action,
+            name: Some(name.clone()),
+            table: None,
+            changes: vec![],
+        }
+    }
+
+    fn with_changes(mut self, changes: Vec<DdlChange>) -> Self {
+        self.changes = changes;
+        self
+    }
+}
+
+/// An extractor of objects touched by DDL.
+#[derive(Default, Debug)]
+pub struct DdlExtractor;
+
+impl DdlExtractor {
+    /// Extract objects touched by DDL from SQL.
+    pub fn extract(dialect: &dyn Dialect, sql: &str) -> Result<Vec<Vec<DdlObject>>, Error> {
+        let statements = instrument::parse_sql(dialect, sql)?;
+        Ok(statements
+            .iter()
+            .map(Self::extract_from_statement)
+            .collect())
+    }
+
+    /// Extract objects touched by a statement, which are empty for statements other than DDL.
+    pub fn extract_from_statement(statement: &Statement) -> Vec<DdlObject> {
+        instrument::analyze("extract_ddl_objects", || match statement {
+            Statement::CreateTable { name, .. } | Statement::CreateVirtualTable { name, .. } => {
+                vec![DdlObject::new(
+                    DdlObjectKind::Table,
+                    DdlAction::Create,
+                    name,
+                )]
+            }
+            Statement::AlterTable {
+                name, operations, ..
+            } => vec![DdlObject::new(DdlObjectKind::Table, DdlAction::Alter, name)
+                .with_changes(operations.iter().map(Self::table_change).collect())],
+            Statement::Truncate { table_name, .. } => {
+                vec![DdlObject::new(
+                    DdlObjectKind::Table,
+                    DdlAction::Truncate,
+                    table_name,
+                )]
+            }
+            Statement::CreateView { name, .. } => {
+                vec![DdlObject::new(DdlObjectKind::View, DdlAction::Create, name)]
+            }
+            Statement::AlterView { name, .. } => {
+                vec![DdlObject::new(DdlObjectKind::View, DdlAction::Alter, name)
+                    .with_changes(vec![DdlChange::AlterQuery])]
+            }
+            Statement::CreateIndex {
+                name, table_name, ..
+            } => vec![DdlObject {
+                kind: DdlObjectKind::Index,
+                action: DdlAction::Create,
+                name: name.clone(),
+                table: Some(table_name.clone()),
+                changes: vec![],
+            }],
+            Statement::AlterIndex { name, operation } => {
+                let change = match operation {
+                    AlterIndexOperation::RenameIndex { index_name } => DdlChange::Rename {
+                        new_name: index_name.clone(),
+                    },
+                };
+                vec![DdlObject::new(DdlObjectKind::Index, DdlAction::Alter, name)
+                    .with_changes(vec![change])]
+            }
+            Statement::CreateSequence { name, .. } => {
+                vec![DdlObject::new(
+                    DdlObjectKind::Sequence,
+                    DdlAction::Create,
+                    name,
+                )]
+            }
+            Statement::CreateSchema { schema_name, .. } => {
+                let name = match schema_name {
+                    SchemaName::Simple(name) | SchemaName::NamedAuthorization(name, _) => {
+                        name.clone()
+                    }
+                    SchemaName::UnnamedAuthorization(authorization) => {
+                        ObjectName(vec![authorization.clone()])
+                    }
+                };
+                vec![DdlObject::new(
+                    DdlObjectKind::Schema,
+                    DdlAction::Create,
+                    &name,
+                )]
+            }
+            Statement::CreateDatabase { db_name, .. } => {
+                vec![DdlObject::new(
+                    DdlObjectKind::Database,
+                    DdlAction::Create,
+                    db_name,
+                )]
+            }
+            Statement::Drop {
+                object_type, names, ..
+            } => {
+                let kind = match object_type {
+                    ObjectType::Table => DdlObjectKind::Table,
+                    ObjectType::View => DdlObjectKind::View,
+                    ObjectType::Index => DdlObjectKind::Index,
+                    ObjectType::Sequence => DdlObjectKind::Sequence,
+                    ObjectType::Schema => DdlObjectKind::Schema,
+                    _ => return vec![],
+                };
+                names
+                    .iter()
+                    .map(|name| DdlObject::new(kind, DdlAction::Drop, name))
+                    .collect()
+            }
+            _ => vec![],
+        })
+    }
+
+    fn table_change(operation: &AlterTableOperation) -> DdlChange {
+        match operation {
+            AlterTableOperation::AddColumn { column_def, .. } => DdlChange::AddColumn {
+                column: column_def.name.clone(),
+            },
+            AlterTableOperation::DropColumn { column_name, .. } => DdlChange::DropColumn {
+                column: column_name.clone(),
+            },
+            AlterTableOperation::AlterColumn { column_name, .. } => DdlChange::AlterColumn {
+                column: column_name.clone(),
+            },
+            AlterTableOperation::RenameColumn {
+                old_column_name,
+                new_column_name,
+            } => DdlChange::RenameColumn {
+                old_name: old_column_name.clone(),
+                new_name: new_column_name.clone(),
+            },
+            AlterTableOperation::ChangeColumn {
+                old_name, new_name, ..
+            } => DdlChange::ChangeColumn {
+                old_name: old_name.clone(),
+                new_name: new_name.clone(),
+            },
+            AlterTableOperation::AddConstraint(constraint) => DdlChange::AddConstraint {
+                name: match constraint {
+                    TableConstraint::Unique { name, .. }
+                    | TableConstraint::ForeignKey { name, .. }
+                    | TableConstraint::Check { name, .. } => name.clone(),
+                    _ => None,
+                },
+            },
+            AlterTableOperation::DropConstraint { name, .. } => {
+                DdlChange::DropConstraint { name: name.clone() }
+            }
+            AlterTableOperation::RenameConstraint { old_name, new_name } => {
+                DdlChange::RenameConstraint {
+                    old_name: old_name.clone(),
+                    new_name: new_name.clone(),
+                }
+            }
+            AlterTableOperation::DropPrimaryKey => DdlChange::DropPrimaryKey,
+            AlterTableOperation::RenameTable { table_name } => DdlChange::Rename {
+                new_name: table_name.clone(),
+            },
+            operation => DdlChange::Other(operation.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+
+    fn assert_ddl_extraction(sql: &str, expected: Vec<Vec<&str>>, dialects: Vec<Box<dyn Dialect>>) {
+        for dialect in dialects {
+            let result = extract_ddl_objects(dialect.as_ref(), sql).unwrap();
+            let result = result
+                .iter()
+                .map(|objects| objects.iter().map(|o| o.to_string()).collect::<Vec<_>>())
+                .collect::<Vec<_>>();
+            assert_eq!(result, expected, "Failed for dialect: {dialect:?}");
+        }
+    }
+
+    #[test]
+    fn test_create_and_drop() {
+        let sql = "CREATE TABLE s1.t1 (a INT); CREATE VIEW v1 AS SELECT a FROM t1; \
+            CREATE INDEX i1 ON t1 (a); DROP TABLE t1, t2; DROP VIEW v1; DROP INDEX i1; \
+            TRUNCATE TABLE t1; SELECT a FROM t1";
+        assert_ddl_extraction(
+            sql,
+            vec![
+                vec!["CREATE TABLE s1.t1"],
+                vec!["CREATE VIEW v1"],
+                vec!["CREATE INDEX i1 ON t1"],
+                vec!["DROP TABLE t1", "DROP TABLE t2"],
+                vec!["DROP VIEW v1"],
+                vec!["DROP INDEX i1"],
+                vec!["TRUNCATE TABLE t1"],
+                vec![],
+            ],
+            all_dialects(),
+        );
+    }
+
+    #[test]
+    fn test_alter_table() {
+        let sql = "ALTER TABLE t1 ADD COLUMN a INT; ALTER TABLE t1 DROP COLUMN a; \
+            ALTER TABLE t1 RENAME COLUMN a TO b; ALTER TABLE t1 ADD CONSTRAINT fk1 FOREIGN KEY (a) REFERENCES t2 (id); \
+            ALTER TABLE t1 DROP CONSTRAINT fk1; ALTER TABLE t1 RENAME TO t3";
+        assert_ddl_extraction(
+            sql,
+            vec![
+                vec!["ALTER TABLE t1 (ADD COLUMN a)"],
+                vec!["ALTER TABLE t1 (DROP COLUMN a)"],
+                vec!["ALTER TABLE t1 (RENAME COLUMN a TO b)"],
+                vec!["ALTER TABLE t1 (ADD CONSTRAINT fk1)"],
+                vec!["ALTER TABLE t1 (DROP CONSTRAINT fk1)"],
+                vec!["ALTER TABLE t1 (RENAME TO t3)"],
+            ],
+            all_dialects(),
+        );
+    }
+
+    #[test]
+    fn test_changes() {
+        for dialect in all_dialects() {
+            let result = extract_ddl_objects(
+                dialect.as_ref(),
+                "ALTER TABLE t1 ADD COLUMN a INT, ALTER COLUMN b SET NOT NULL",
+            )
+            .unwrap();
+            assert_eq!(
+                result[0][0].changes,
+                vec![
+                    DdlChange::AddColumn {
+                        column: Ident::new("a")
+                    },
+                    DdlChange::AlterColumn {
+                        column: Ident::new("b")
+                    },
+                ],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_sequences_and_schemas() {
+        let sql = "CREATE SEQUENCE seq1; CREATE SCHEMA s1; DROP SEQUENCE seq1; DROP SCHEMA s1";
+        assert_ddl_extraction(
+            sql,
+            vec![
+                vec!["CREATE SEQUENCE seq1"],
+                vec!["CREATE SCHEMA s1"],
+                vec!["DROP SEQUENCE seq1"],
+                vec!["DROP SCHEMA s1"],
+            ],
+            vec![Box::new(sqlparser::dialect::PostgreSqlDialect {})],
+        );
+    }
+}
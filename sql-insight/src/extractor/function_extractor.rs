@@ -0,0 +1,328 @@
+//! A Extractor that extracts function calls from SQL queries.
+//!
+//! See [`extract_functions`](crate::extract_functions()) as the entry point for extracting functions from SQL.
+
+use core::fmt;
+use std::ops::ControlFlow;
+
+use crate::error::Error;
+use crate::instrument;
+use sqlparser::ast::{Expr, Ident, ObjectName, Statement, TableFactor, Visit, Visitor};
+use sqlparser::dialect::Dialect;
+
+/// Names of aggregate functions across dialects, in upper case.
+/// `ARRAY_AGG` and `LISTAGG` are not listed since they are parsed into expressions of their own.
+const AGGREGATE_FUNCTIONS: &[&str] = &[
+    "ANY_VALUE",
+    "APPROX_COUNT_DISTINCT",
+    "AVG",
+    "BIT_AND",
+    "BIT_OR",
+    "BIT_XOR",
+    "BOOL_AND",
+    "BOOL_OR",
+    "CORR",
+    "COUNT",
+    "COUNT_IF",
+    "COVAR_POP",
+    "COVAR_SAMP",
+    "EVERY",
+    "GROUP_CONCAT",
+    "JSON_AGG",
+    "JSON_ARRAYAGG",
+    "JSON_OBJECT_AGG",
+    "JSON_OBJECTAGG",
+    "JSONB_AGG",
+    "JSONB_OBJECT_AGG",
+    "MAX",
+    "MEDIAN",
+    "MIN",
+    "MODE",
+    "PERCENTILE_CONT",
+    "PERCENTILE_DISC",
+    "STDDEV",
+    "STDDEV_POP",
+    "STDDEV_SAMP",
+    "STRING_AGG",
+    "SUM",
+    "VAR_POP",
+    "VAR_SAMP",
+    "VARIANCE",
+];
+
+/// Convenience function to extract function calls from SQL.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+///
+/// let dialect = GenericDialect {};
+/// let sql = "SELECT COUNT(*), ROW_NUMBER() OVER (ORDER BY a), NVL(b, 0) FROM t1 WHERE c > NOW()";
+/// let result = sql_insight::extract_functions(&dialect, sql).unwrap();
+/// assert_eq!(result[0].to_string(), "COUNT/1 (aggregate), ROW_NUMBER/0 (window), NVL/2, NOW/0");
+/// ```
+pub fn extract_functions(dialect: &dyn Dialect, sql: &str) -> Result<Vec<Functions>, Error> {
+    FunctionExtractor::extract(dialect, sql)
+}
+
+/// [`FunctionCall`] represents a call of a function.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FunctionCall {
+    /// The name of the function as written, including its schema if qualified.
+    pub name: ObjectName,
+    /// Number of arguments, where a wildcard as in `COUNT(*)` is an argument.
+    pub arg_count: usize,
+    /// Whether the function is an aggregate function, known by its name or called with `DISTINCT` or `FILTER`.
+    pub aggregate: bool,
+    /// Whether the function is called as a window function with `OVER`.
+    pub window: bool,
+}
+
+impl fmt::Display for FunctionCall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.name, self.arg_count)?;
+        match (self.aggregate, self.window) {
+            (true, true) => write!(f, " (aggregate, window)"),
+            (true, false) => write!(f, " (aggregate)"),
+            (false, true) => write!(f, " (window)"),
+            (false, false) => Ok(()),
+        }
+    }
+}
+
+/// [`Functions`] represents a list of [`FunctionCall`] that found in SQL.
+#[derive(Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Functions(pub Vec<FunctionCall>);
+
+impl fmt::Display for Functions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let functions = self
+            .0
+            .iter()
+            .map(|function| function.to_string())
+            .collect::<Vec<String>>()
+            .join(", ");
+        write!(f, "{}", functions)
+    }
+}
+
+/// A visitor to extract function calls from SQL.
+///
+/// Functions are collected from expressions and table-valued functions in `FROM`, in the order of appearance,
+/// with every call of the same function. Syntax with dedicated expressions, such as `CAST`, `EXTRACT`,
+/// `TRIM` and `SUBSTRING`, is not a function call, except `ARRAY_AGG` and `LISTAGG`, which are aggregate functions
+/// named in upper case since the parser does not keep their names as written.
+#[derive(Default, Debug)]
+pub struct FunctionExtractor {
+    functions: Vec<FunctionCall>,
+}
+
+impl Visitor for FunctionExtractor {
+    type Break = ();
+
+    fn pre_visit_table_factor(&mut self, table_factor: &TableFactor) -> ControlFlow<Self::Break> {
+        match table_factor {
+            TableFactor::Table {
+                name,
+                args: Some(args),
+                ..
+            }
+            | TableFactor::Function { name, args, .. } => {
+                self.push(name.clone(), args.len(), false, false)
+            }
+            _ => {}
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_expr(&mut self, expr: &Expr) -> ControlFlow<Self::Break> {
+        match expr {
+            Expr::Function(function) => {
+                let aggregate = function.distinct
+                    || function.filter.is_some()
+                    || function.name.0.last().is_some_and(|ident| {
+                        AGGREGATE_FUNCTIONS.contains(&ident.value.to_uppercase().as_str())
+                    });
+                self.push(
+                    function.name.clone(),
+                    function.args.len(),
+                    aggregate,
+                    function.over.is_some(),
+                );
+            }
+            Expr::ArrayAgg(_) => {
+                self.push(ObjectName(vec![Ident::new("ARRAY_AGG")]), 1, true, false)
+            }
+            Expr::ListAgg(list_agg) => self.push(
+                ObjectName(vec![Ident::new("LISTAGG")]),
+                1 + usize::from(list_agg.separator.is_some()),
+                true,
+                false,
+            ),
+            _ => {}
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+impl FunctionExtractor {
+    /// Extract function calls from SQL.
+    pub fn extract(dialect: &dyn Dialect, sql: &str) -> Result<Vec<Functions>, Error> {
+        let statements = instrument::parse_sql(dialect, sql)?;
+        Ok(statements
+            .iter()
+            .map(Self::extract_from_statement)
+            .collect())
+    }
+
+    pub fn extract_from_statement(statement: &Statement) -> Functions {
+        instrument::analyze("extract_functions", || {
+            let mut visitor = FunctionExtractor::default();
+            let _ = statement.visit(&mut visitor);
+            visitor.into_functions()
+        })
+    }
+
+    /// Functions found so far.
+    /// Useful when the extractor is driven by an external traversal such as [`VisitorSet`](crate::VisitorSet).
+    pub fn into_functions(self) -> Functions {
+        Functions(self.functions)
+    }
+
+    fn push(&mut self, name: ObjectName, arg_count: usize, aggregate: bool, window: bool) {
+        self.functions.push(FunctionCall {
+            name,
+            arg_count,
+            aggregate,
+            window,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+    use sqlparser::dialect::{GenericDialect, PostgreSqlDialect, SnowflakeDialect};
+
+    fn assert_function_extraction(
+        sql: &str,
+        expected: Vec<Functions>,
+        dialects: Vec<Box<dyn Dialect>>,
+    ) {
+        for dialect in dialects {
+            let result = FunctionExtractor::extract(dialect.as_ref(), sql).unwrap();
+            assert_eq!(result, expected, "Failed for dialect: {dialect:?}")
+        }
+    }
+
+    fn function(name: &[&str], arg_count: usize, aggregate: bool, window: bool) -> FunctionCall {
+        FunctionCall {
+            name: ObjectName(name.iter().map(|&s| Ident::new(s)).collect()),
+            arg_count,
+            aggregate,
+            window,
+        }
+    }
+
+    #[test]
+    fn test_select_statement() {
+        let sql = "SELECT COUNT(*), sum(a), UPPER(TRIM(b)), s1.f(c, d, 1) FROM t1 \
+            WHERE e > COALESCE(f, 0) GROUP BY g HAVING MAX(h) > 1";
+        let expected = vec![Functions(vec![
+            function(&["COUNT"], 1, true, false),
+            function(&["sum"], 1, true, false),
+            function(&["UPPER"], 1, false, false),
+            function(&["s1", "f"], 3, false, false),
+            function(&["COALESCE"], 2, false, false),
+            function(&["MAX"], 1, true, false),
+        ])];
+        assert_function_extraction(sql, expected, all_dialects());
+    }
+
+    #[test]
+    fn test_nested_functions_and_subqueries() {
+        let sql = "INSERT INTO t1 (a) SELECT ROUND(ABS(b), 2) FROM t2 WHERE c IN (SELECT MIN(c) FROM t3); \
+            UPDATE t1 SET a = LOWER(a); \
+            DELETE FROM t1";
+        let expected = vec![
+            Functions(vec![
+                function(&["ROUND"], 2, false, false),
+                function(&["ABS"], 1, false, false),
+                function(&["MIN"], 1, true, false),
+            ]),
+            Functions(vec![function(&["LOWER"], 1, false, false)]),
+            Functions(vec![]),
+        ];
+        assert_function_extraction(sql, expected, all_dialects());
+    }
+
+    #[test]
+    fn test_window_functions() {
+        let sql = "SELECT ROW_NUMBER() OVER (PARTITION BY a ORDER BY b), SUM(c) OVER (), COUNT(DISTINCT d) FROM t1";
+        let expected = vec![Functions(vec![
+            function(&["ROW_NUMBER"], 0, false, true),
+            function(&["SUM"], 1, true, true),
+            function(&["COUNT"], 1, true, false),
+        ])];
+        assert_function_extraction(
+            sql,
+            expected,
+            vec![Box::new(PostgreSqlDialect {}), Box::new(GenericDialect {})],
+        );
+    }
+
+    #[test]
+    fn test_table_valued_function() {
+        let sql = "SELECT * FROM generate_series(1, 10) AS s";
+        let expected = vec![Functions(vec![function(
+            &["generate_series"],
+            2,
+            false,
+            false,
+        )])];
+        assert_function_extraction(
+            sql,
+            expected,
+            vec![Box::new(PostgreSqlDialect {}), Box::new(GenericDialect {})],
+        );
+    }
+
+    #[test]
+    fn test_array_agg_and_listagg() {
+        let sql = "SELECT ARRAY_AGG(a ORDER BY b), array_agg(DISTINCT c) FROM t1";
+        let expected = vec![Functions(vec![
+            function(&["ARRAY_AGG"], 1, true, false),
+            function(&["ARRAY_AGG"], 1, true, false),
+        ])];
+        assert_function_extraction(
+            sql,
+            expected,
+            vec![Box::new(PostgreSqlDialect {}), Box::new(GenericDialect {})],
+        );
+
+        let sql = "SELECT LISTAGG(a, ', ') WITHIN GROUP (ORDER BY UPPER(a)), LISTAGG(b) FROM t1";
+        let expected = vec![Functions(vec![
+            function(&["LISTAGG"], 2, true, false),
+            function(&["UPPER"], 1, false, false),
+            function(&["LISTAGG"], 1, true, false),
+        ])];
+        assert_function_extraction(
+            sql,
+            expected,
+            vec![Box::new(SnowflakeDialect {}), Box::new(GenericDialect {})],
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        let functions = Functions(vec![
+            function(&["SUM"], 1, true, true),
+            function(&["s1", "f"], 0, false, false),
+        ]);
+        assert_eq!(functions.to_string(), "SUM/1 (aggregate, window), s1.f/0");
+    }
+}
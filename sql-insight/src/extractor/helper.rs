@@ -1,6 +1,188 @@
 use crate::TableReference;
+use sqlparser::ast::{
+    Expr, Ident, JoinConstraint, JoinOperator, ObjectName, Query, Select, SetExpr, Statement,
+    TableFactor, TableWithJoins,
+};
 use std::collections::HashMap;
 
+/// Collect SELECTs directly composing a query body, descending into set operations.
+/// Nested queries (`SetExpr::Query`) are not descended into since visitors visit them as queries of their own.
+pub(crate) fn collect_selects<'a>(set_expr: &'a SetExpr, selects: &mut Vec<&'a Select>) {
+    match set_expr {
+        SetExpr::Select(select) => selects.push(select),
+        SetExpr::SetOperation { left, right, .. } => {
+            collect_selects(left, selects);
+            collect_selects(right, selects);
+        }
+        _ => {}
+    }
+}
+
+/// Mutable counterpart of [`collect_selects`].
+pub(crate) fn collect_selects_mut<'a>(
+    set_expr: &'a mut SetExpr,
+    selects: &mut Vec<&'a mut Select>,
+) {
+    match set_expr {
+        SetExpr::Select(select) => selects.push(select),
+        SetExpr::SetOperation { left, right, .. } => {
+            collect_selects_mut(left, selects);
+            collect_selects_mut(right, selects);
+        }
+        _ => {}
+    }
+}
+
+/// Join constraint of a join operator, if the operator takes one.
+pub(crate) fn join_constraint(join_operator: &JoinOperator) -> Option<&JoinConstraint> {
+    match join_operator {
+        JoinOperator::Inner(constraint)
+        | JoinOperator::LeftOuter(constraint)
+        | JoinOperator::RightOuter(constraint)
+        | JoinOperator::FullOuter(constraint)
+        | JoinOperator::LeftSemi(constraint)
+        | JoinOperator::RightSemi(constraint)
+        | JoinOperator::LeftAnti(constraint)
+        | JoinOperator::RightAnti(constraint) => Some(constraint),
+        _ => None,
+    }
+}
+
+/// Collect filter conditions, i.e. WHERE, HAVING and join conditions, of the SELECTs directly composing a query body.
+pub(crate) fn collect_conditions<'a>(set_expr: &'a SetExpr, conditions: &mut Vec<&'a Expr>) {
+    let mut selects = Vec::new();
+    collect_selects(set_expr, &mut selects);
+    for select in selects {
+        for table_with_joins in &select.from {
+            collect_join_conditions(table_with_joins, conditions);
+        }
+        conditions.extend(&select.selection);
+        conditions.extend(&select.having);
+    }
+}
+
+/// Collect filter conditions of UPDATE and DELETE statements. Those of queries are collected by [`collect_conditions`].
+pub(crate) fn collect_statement_conditions<'a>(
+    statement: &'a Statement,
+    conditions: &mut Vec<&'a Expr>,
+) {
+    match statement {
+        Statement::Update {
+            table,
+            from,
+            selection,
+            ..
+        } => {
+            collect_join_conditions(table, conditions);
+            if let Some(from) = from {
+                collect_join_conditions(from, conditions);
+            }
+            conditions.extend(selection);
+        }
+        Statement::Delete {
+            from, selection, ..
+        } => {
+            for table_with_joins in from {
+                collect_join_conditions(table_with_joins, conditions);
+            }
+            conditions.extend(selection);
+        }
+        _ => {}
+    }
+}
+
+/// Collect ON conditions of joins, descending into nested joins.
+pub(crate) fn collect_join_conditions<'a>(
+    table_with_joins: &'a TableWithJoins,
+    conditions: &mut Vec<&'a Expr>,
+) {
+    let relations = std::iter::once(&table_with_joins.relation)
+        .chain(table_with_joins.joins.iter().map(|join| &join.relation));
+    for relation in relations {
+        if let TableFactor::NestedJoin {
+            table_with_joins, ..
+        } = relation
+        {
+            collect_join_conditions(table_with_joins, conditions);
+        }
+    }
+    for join in &table_with_joins.joins {
+        if let Some(JoinConstraint::On(expr)) = join_constraint(&join.join_operator) {
+            conditions.push(expr);
+        }
+    }
+}
+
+/// Scopes of CTEs of the queries being visited, telling whether a relation refers to a CTE rather than a table.
+///
+/// [`enter`](Self::enter) and [`exit`](Self::exit) are called from `pre_visit_query` and `post_visit_query`.
+/// A CTE is visible in the body of the query defining it, including subqueries, and shadows tables of the same name.
+/// Each CTE sees only the CTEs preceding it in the WITH clause, and itself too if recursive.
+#[derive(Default, Debug)]
+pub(crate) struct CteScopes {
+    scopes: Vec<CteScope>,
+}
+
+#[derive(Debug)]
+struct CteScope {
+    /// CTEs defined by the WITH clause of the query, with the address of their query to tell when it is entered.
+    ctes: Vec<(usize, Ident)>,
+    recursive: bool,
+    /// Number of CTEs visible from the query, growing as the CTEs of the WITH clause are visited.
+    visible: usize,
+    /// Index of the CTE of the enclosing query this query is the body of.
+    cte_index: Option<usize>,
+}
+
+impl CteScopes {
+    pub(crate) fn enter(&mut self, query: &Query) {
+        let address = query as *const Query as usize;
+        let cte_index = self.scopes.last_mut().and_then(|scope| {
+            let index = scope.ctes.iter().position(|(cte, _)| *cte == address)?;
+            scope.visible = if scope.recursive { index + 1 } else { index };
+            Some(index)
+        });
+        let with = query.with.as_ref();
+        self.scopes.push(CteScope {
+            ctes: with
+                .iter()
+                .flat_map(|with| &with.cte_tables)
+                .map(|cte| {
+                    (
+                        cte.query.as_ref() as *const Query as usize,
+                        cte.alias.name.clone(),
+                    )
+                })
+                .collect(),
+            recursive: with.is_some_and(|with| with.recursive),
+            visible: 0,
+            cte_index,
+        });
+    }
+
+    pub(crate) fn exit(&mut self) {
+        if let Some(index) = self.scopes.pop().and_then(|scope| scope.cte_index) {
+            if let Some(scope) = self.scopes.last_mut() {
+                scope.visible = index + 1;
+            }
+        }
+    }
+
+    /// Whether the relation refers to a CTE in scope. Unquoted names are compared case-insensitively.
+    pub(crate) fn contains(&self, relation: &ObjectName) -> bool {
+        let [name] = relation.0.as_slice() else {
+            return false;
+        };
+        self.scopes
+            .iter()
+            .flat_map(|scope| &scope.ctes[..scope.visible])
+            .any(|(_, cte)| match (cte.quote_style, name.quote_style) {
+                (None, None) => cte.value.eq_ignore_ascii_case(&name.value),
+                _ => cte.value == name.value,
+            })
+    }
+}
+
 pub(crate) fn resolve_aliased_tables(
     possibly_aliased_tables: Vec<TableReference>,
     original_tables: Vec<TableReference>,
@@ -0,0 +1,360 @@
+//! A Extractor that extracts index hints and optimizer hints from SQL queries.
+//!
+//! See [`extract_hints`](crate::extract_hints()) as the entry point for extracting hints from SQL.
+
+use core::fmt;
+use std::iter::Peekable;
+
+use crate::error::Error;
+use crate::extractor::table_extractor::TableReference;
+use crate::rewriter::hints::{hint_ranges, is_hint_comment};
+use crate::splitter::split_statements;
+use sqlparser::ast::{Ident, ObjectName};
+use sqlparser::dialect::Dialect;
+use sqlparser::parser::ParserError;
+use sqlparser::tokenizer::{Token, TokenWithLocation, Tokenizer, Whitespace};
+
+/// Convenience function to extract hints from SQL, for each statement in input order.
+///
+/// Like [`strip_hints`](crate::strip_hints()), hints are found in the source text since they are comments or,
+/// in the case of index hints, not carried by the AST for every dialect. Statements are split without parsing them,
+/// as [`split_statements`](crate::split_statements()) does.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::MySqlDialect;
+/// use sql_insight::{Hint, IndexHintAction};
+///
+/// let dialect = MySqlDialect {};
+/// let sql = "SELECT /*+ NO_ICP(t1) */ a FROM db.t1 AS x FORCE INDEX (i1, i2) WHERE b = 1; SELECT a FROM t2";
+/// let result = sql_insight::extract_hints(&dialect, sql).unwrap();
+/// assert_eq!(result[0][0], Hint::Optimizer("NO_ICP(t1)".to_string()));
+/// let Hint::Index(hint) = &result[0][1] else { panic!() };
+/// assert_eq!(hint.action, IndexHintAction::Force);
+/// assert_eq!(hint.table.as_ref().unwrap().to_string(), "db.t1 AS x");
+/// assert_eq!(hint.to_string(), "FORCE INDEX (i1, i2) ON db.t1 AS x");
+/// assert!(result[1].is_empty());
+/// ```
+pub fn extract_hints(dialect: &dyn Dialect, sql: &str) -> Result<Vec<Vec<Hint>>, Error> {
+    HintExtractor::extract(dialect, sql)
+}
+
+/// [`Hint`] represents a hint found in a statement.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Hint {
+    /// MySQL's `USE`, `FORCE` and `IGNORE` `INDEX`/`KEY` clauses.
+    Index(IndexHint),
+    /// Comments starting with `+`, i.e. `/*+ ... */` and `--+ ...`, as used by MySQL, Oracle and `pg_hint_plan`.
+    /// The text of the hint is without the `+` and surrounding whitespace.
+    Optimizer(String),
+}
+
+impl fmt::Display for Hint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Hint::Index(hint) => write!(f, "{}", hint),
+            Hint::Optimizer(hint) => write!(f, "/*+ {} */", hint),
+        }
+    }
+}
+
+/// [`IndexHintAction`] represents whether an index hint uses, forces or ignores indexes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum IndexHintAction {
+    Use,
+    Force,
+    Ignore,
+}
+
+/// [`IndexHintScope`] represents the part of query processing an index hint is limited to with `FOR`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum IndexHintScope {
+    Join,
+    OrderBy,
+    GroupBy,
+}
+
+/// [`IndexHint`] represents an index hint with the table it applies to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IndexHint {
+    pub action: IndexHintAction,
+    /// The scope given with `FOR`, or `None` for hints applying to every part.
+    pub scope: Option<IndexHintScope>,
+    /// Names of indexes, which are empty for `USE INDEX ()`, meaning no index is used.
+    pub indexes: Vec<Ident>,
+    /// The table preceding the hint with its alias, or `None` if it cannot be told from the tokens before the hint.
+    pub table: Option<TableReference>,
+}
+
+impl fmt::Display for IndexHint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let action = match self.action {
+            IndexHintAction::Use => "USE",
+            IndexHintAction::Force => "FORCE",
+            IndexHintAction::Ignore => "IGNORE",
+        };
+        write!(f, "{} INDEX", action)?;
+        match self.scope {
+            Some(IndexHintScope::Join) => write!(f, " FOR JOIN")?,
+            Some(IndexHintScope::OrderBy) => write!(f, " FOR ORDER BY")?,
+            Some(IndexHintScope::GroupBy) => write!(f, " FOR GROUP BY")?,
+            None => {}
+        }
+        let indexes = self
+            .indexes
+            .iter()
+            .map(|i| i.to_string())
+            .collect::<Vec<String>>()
+            .join(", ");
+        write!(f, " ({})", indexes)?;
+        if let Some(table) = &self.table {
+            write!(f, " ON {}", table)?;
+        }
+        Ok(())
+    }
+}
+
+/// An extractor of hints from the source text of statements.
+#[derive(Default, Debug)]
+pub struct HintExtractor;
+
+impl HintExtractor {
+    /// Extract hints from SQL, for each statement in input order.
+    pub fn extract(dialect: &dyn Dialect, sql: &str) -> Result<Vec<Vec<Hint>>, Error> {
+        split_statements(dialect, sql)
+            .iter()
+            .map(|(statement, _)| Self::extract_from_sql(dialect, statement))
+            .collect()
+    }
+
+    /// Extract hints from the source text of a single statement.
+    pub fn extract_from_sql(dialect: &dyn Dialect, sql: &str) -> Result<Vec<Hint>, Error> {
+        let tokens: Vec<TokenWithLocation> = Tokenizer::new(dialect, sql)
+            .tokenize_with_location()
+            .map_err(ParserError::from)?
+            .into_iter()
+            .filter(|token| token.token != Token::EOF)
+            .collect();
+        Ok(hint_ranges(&tokens)
+            .into_iter()
+            .map(|range| match &tokens[range.start].token {
+                Token::Whitespace(Whitespace::MultiLineComment(comment))
+                | Token::Whitespace(Whitespace::SingleLineComment { comment, .. })
+                    if is_hint_comment(&tokens[range.start].token) =>
+                {
+                    Hint::Optimizer(comment.trim_start_matches('+').trim().to_string())
+                }
+                _ => Hint::Index(Self::index_hint(&tokens, range.start, range.end)),
+            })
+            .collect())
+    }
+
+    /// Build an index hint from its tokens, which are known to form an index hint.
+    fn index_hint(tokens: &[TokenWithLocation], start: usize, end: usize) -> IndexHint {
+        let words = tokens[start..end]
+            .iter()
+            .filter_map(|token| match &token.token {
+                Token::Word(word) => Some(word.value.to_uppercase()),
+                Token::LParen => Some("(".to_string()),
+                _ => None,
+            })
+            .take_while(|word| word != "(")
+            .collect::<Vec<_>>();
+        let action = match words[0].as_str() {
+            "USE" => IndexHintAction::Use,
+            "FORCE" => IndexHintAction::Force,
+            _ => IndexHintAction::Ignore,
+        };
+        let scope = match words.get(3).map(String::as_str) {
+            Some("JOIN") => Some(IndexHintScope::Join),
+            Some("ORDER") => Some(IndexHintScope::OrderBy),
+            Some("GROUP") => Some(IndexHintScope::GroupBy),
+            _ => None,
+        };
+        let indexes = tokens[start..end]
+            .iter()
+            .skip_while(|token| token.token != Token::LParen)
+            .filter_map(|token| match &token.token {
+                Token::Word(word) => Some(Ident {
+                    value: word.value.clone(),
+                    quote_style: word.quote_style,
+                }),
+                _ => None,
+            })
+            .collect();
+        IndexHint {
+            action,
+            scope,
+            indexes,
+            table: Self::table_before(&tokens[..start]),
+        }
+    }
+
+    /// The table, with its alias, whose reference in `FROM` or `JOIN` ends the tokens, e.g. `db.t1 AS x`
+    /// before an index hint.
+    fn table_before(tokens: &[TokenWithLocation]) -> Option<TableReference> {
+        let mut tokens = tokens
+            .iter()
+            .rev()
+            .map(|token| &token.token)
+            .filter(|token| !matches!(token, Token::Whitespace(_)))
+            .peekable();
+        let is_word = |token: &Token, words: &[&str]| match token {
+            Token::Word(word) if word.quote_style.is_none() => {
+                words.iter().any(|w| word.value.eq_ignore_ascii_case(w))
+            }
+            _ => false,
+        };
+        let is_table_start = |token: &Token| {
+            *token == Token::Comma || is_word(token, &["FROM", "JOIN", "STRAIGHT_JOIN"])
+        };
+        // Skip index hints stacked before this one on the same table, e.g. `USE INDEX (i1)` of
+        // `t1 USE INDEX (i1) IGNORE INDEX (i2)`.
+        while tokens.peek() == Some(&&Token::RParen) {
+            tokens.find(|token| **token == Token::LParen)?;
+            loop {
+                let token = tokens.next()?;
+                if is_word(token, &["USE", "FORCE", "IGNORE"]) {
+                    break;
+                }
+                if !is_word(
+                    token,
+                    &["INDEX", "KEY", "FOR", "JOIN", "ORDER", "GROUP", "BY"],
+                ) {
+                    return None;
+                }
+            }
+        }
+        let idents = Self::name_before(&mut tokens)?;
+        let (name, alias) = match tokens.peek().copied() {
+            Some(token) if is_table_start(token) => (idents, None),
+            Some(Token::Word(_)) if idents.len() == 1 => {
+                if tokens
+                    .peek()
+                    .copied()
+                    .is_some_and(|token| is_word(token, &["AS"]))
+                {
+                    tokens.next();
+                }
+                (Self::name_before(&mut tokens)?, idents.into_iter().next())
+            }
+            _ => return None,
+        };
+        if !tokens.next().is_some_and(is_table_start) {
+            return None;
+        }
+        Some(TableReference {
+            alias,
+            ..TableReference::try_from(&ObjectName(name)).ok()?
+        })
+    }
+
+    /// The possibly qualified name ending the tokens, which are in reverse order.
+    fn name_before<'a>(
+        tokens: &mut Peekable<impl Iterator<Item = &'a Token>>,
+    ) -> Option<Vec<Ident>> {
+        let mut idents = vec![];
+        loop {
+            match tokens.next() {
+                Some(Token::Word(word)) => idents.push(Ident {
+                    value: word.value.clone(),
+                    quote_style: word.quote_style,
+                }),
+                _ => return None,
+            }
+            if tokens.peek() != Some(&&Token::Period) {
+                break;
+            }
+            tokens.next();
+        }
+        idents.reverse();
+        Some(idents)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+
+    fn assert_hint_extraction(sql: &str, expected: Vec<Vec<&str>>) {
+        for dialect in all_dialects() {
+            let result = extract_hints(dialect.as_ref(), sql).unwrap();
+            let result = result
+                .iter()
+                .map(|hints| hints.iter().map(|h| h.to_string()).collect::<Vec<_>>())
+                .collect::<Vec<_>>();
+            assert_eq!(result, expected, "Failed for dialect: {dialect:?}");
+        }
+    }
+
+    #[test]
+    fn test_index_hints() {
+        let sql = "SELECT * FROM t1 USE INDEX (i1, i2) JOIN s1.t2 x FORCE KEY FOR JOIN (i3) \
+            IGNORE INDEX FOR GROUP BY (i4) ON t1.a = x.a GROUP BY t1.b; \
+            SELECT * FROM t3 AS y USE INDEX () ORDER BY a; \
+            SELECT * FROM t4, t5 IGNORE INDEX FOR ORDER BY (PRIMARY)";
+        assert_hint_extraction(
+            sql,
+            vec![
+                vec![
+                    "USE INDEX (i1, i2) ON t1",
+                    "FORCE INDEX FOR JOIN (i3) ON s1.t2 AS x",
+                    "IGNORE INDEX FOR GROUP BY (i4) ON s1.t2 AS x",
+                ],
+                vec!["USE INDEX () ON t3 AS y"],
+                vec!["IGNORE INDEX FOR ORDER BY (PRIMARY) ON t5"],
+            ],
+        );
+    }
+
+    #[test]
+    fn test_optimizer_hints() {
+        let sql = "/*+ SeqScan(t1) */ SELECT a --+ FULL(t1)\nFROM t1 /* note */ WHERE b = 'USE INDEX (i1)'; \
+            /* comment only */; UPDATE /*+ NO_MERGE */ t2 SET a = 1";
+        assert_hint_extraction(
+            sql,
+            vec![
+                vec!["/*+ SeqScan(t1) */", "/*+ FULL(t1) */"],
+                vec!["/*+ NO_MERGE */"],
+            ],
+        );
+    }
+
+    #[test]
+    fn test_table_of_index_hint() {
+        for dialect in all_dialects() {
+            let result = extract_hints(
+                dialect.as_ref(),
+                "SELECT * FROM db.t1 AS x USE INDEX (i1) IGNORE INDEX (i2) \
+                    JOIN t2 FORCE INDEX (a) FORCE INDEX FOR JOIN (b) ON x.a = t2.a",
+            )
+            .unwrap();
+            let tables = result[0]
+                .iter()
+                .map(|hint| match hint {
+                    Hint::Index(hint) => hint.table.clone(),
+                    Hint::Optimizer(_) => None,
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(
+                tables,
+                vec![
+                    Some("db.t1 AS x".parse().unwrap()),
+                    Some("db.t1 AS x".parse().unwrap()),
+                    Some("t2".parse().unwrap()),
+                    Some("t2".parse().unwrap())
+                ],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,474 @@
+//! A Extractor that extracts joins from SQL queries.
+//!
+//! See [`extract_joins`](crate::extract_joins()) as the entry point for extracting joins from SQL.
+
+use core::fmt;
+use std::ops::ControlFlow;
+
+use crate::error::Error;
+use crate::extractor::table_extractor::TableReference;
+use crate::helper;
+use crate::instrument;
+use sqlparser::ast::{
+    JoinConstraint, JoinOperator, Query, Statement, TableFactor, TableWithJoins, Visit, Visitor,
+};
+use sqlparser::dialect::Dialect;
+
+/// Convenience function to extract joins from SQL.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+///
+/// let dialect = GenericDialect {};
+/// let sql = "SELECT * FROM t1 LEFT JOIN t2 AS x ON t1.id = x.id, t3";
+/// let result = sql_insight::extract_joins(&dialect, sql).unwrap();
+/// let joins = result[0].as_ref().unwrap();
+/// assert_eq!(joins.to_string(), "t1 LEFT OUTER JOIN t2 AS x ON t1.id = x.id; (t1, t2 AS x) , t3");
+/// assert!(!joins.0[0].is_cross_join());
+/// assert!(joins.0[1].is_cross_join());
+/// ```
+pub fn extract_joins(dialect: &dyn Dialect, sql: &str) -> Result<Vec<Result<Joins, Error>>, Error> {
+    JoinExtractor::extract(dialect, sql)
+}
+
+/// [`JoinType`] represents the type of a join.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum JoinType {
+    Inner,
+    LeftOuter,
+    RightOuter,
+    FullOuter,
+    Cross,
+    LeftSemi,
+    RightSemi,
+    LeftAnti,
+    RightAnti,
+    CrossApply,
+    OuterApply,
+    /// Tables separated by commas in `FROM`, whose conditions, if any, are in `WHERE`.
+    Implicit,
+}
+
+impl fmt::Display for JoinType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let join_type = match self {
+            JoinType::Inner => "INNER JOIN",
+            JoinType::LeftOuter => "LEFT OUTER JOIN",
+            JoinType::RightOuter => "RIGHT OUTER JOIN",
+            JoinType::FullOuter => "FULL OUTER JOIN",
+            JoinType::Cross => "CROSS JOIN",
+            JoinType::LeftSemi => "LEFT SEMI JOIN",
+            JoinType::RightSemi => "RIGHT SEMI JOIN",
+            JoinType::LeftAnti => "LEFT ANTI JOIN",
+            JoinType::RightAnti => "RIGHT ANTI JOIN",
+            JoinType::CrossApply => "CROSS APPLY",
+            JoinType::OuterApply => "OUTER APPLY",
+            JoinType::Implicit => ",",
+        };
+        write!(f, "{}", join_type)
+    }
+}
+
+impl From<&JoinOperator> for JoinType {
+    fn from(join_operator: &JoinOperator) -> Self {
+        match join_operator {
+            JoinOperator::Inner(_) => JoinType::Inner,
+            JoinOperator::LeftOuter(_) => JoinType::LeftOuter,
+            JoinOperator::RightOuter(_) => JoinType::RightOuter,
+            JoinOperator::FullOuter(_) => JoinType::FullOuter,
+            JoinOperator::CrossJoin => JoinType::Cross,
+            JoinOperator::LeftSemi(_) => JoinType::LeftSemi,
+            JoinOperator::RightSemi(_) => JoinType::RightSemi,
+            JoinOperator::LeftAnti(_) => JoinType::LeftAnti,
+            JoinOperator::RightAnti(_) => JoinType::RightAnti,
+            JoinOperator::CrossApply => JoinType::CrossApply,
+            JoinOperator::OuterApply => JoinType::OuterApply,
+        }
+    }
+}
+
+/// [`TableJoin`] represents a join of a table to the tables preceding it in a `FROM` item.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TableJoin {
+    /// Tables preceding the join, including those of nested joins.
+    pub left: Vec<TableReference>,
+    /// The joined table, or `None` if the joined relation is not a table, e.g. a derived table or a nested join.
+    pub right: Option<TableReference>,
+    pub join_type: JoinType,
+    /// The condition as written, e.g. `ON t1.id = t2.id`, `USING (id)` or `NATURAL`.
+    pub condition: Option<String>,
+}
+
+impl TableJoin {
+    /// Whether the join yields the cartesian product of its sides, i.e. a `CROSS JOIN`, a join without condition,
+    /// or tables separated by commas. Conditions of the latter in `WHERE` are not taken into account.
+    pub fn is_cross_join(&self) -> bool {
+        match self.join_type {
+            JoinType::Cross | JoinType::Implicit => true,
+            JoinType::Inner => self.condition.is_none(),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for TableJoin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let left = self
+            .left
+            .iter()
+            .map(|table| table.to_string())
+            .collect::<Vec<_>>();
+        match left.as_slice() {
+            [table] => write!(f, "{}", table)?,
+            _ => write!(f, "({})", left.join(", "))?,
+        }
+        write!(f, " {} ", self.join_type)?;
+        match &self.right {
+            Some(table) => write!(f, "{}", table)?,
+            None => write!(f, "(...)")?,
+        }
+        if let Some(condition) = &self.condition {
+            write!(f, " {}", condition)?;
+        }
+        Ok(())
+    }
+}
+
+/// [`Joins`] represents a list of [`TableJoin`] that found in SQL.
+#[derive(Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Joins(pub Vec<TableJoin>);
+
+impl fmt::Display for Joins {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let joins = self
+            .0
+            .iter()
+            .map(|join| join.to_string())
+            .collect::<Vec<String>>()
+            .join("; ");
+        write!(f, "{}", joins)
+    }
+}
+
+/// A visitor to extract joins from SQL.
+///
+/// Joins are collected from `FROM` of SELECTs, including those of subqueries and CTEs, and from tables of UPDATE
+/// and DELETE, including `USING` of DELETE, in the order of appearance. Joins of nested joins precede the join of the nested join itself.
+#[derive(Default, Debug)]
+pub struct JoinExtractor {
+    joins: Vec<TableJoin>,
+}
+
+impl Visitor for JoinExtractor {
+    type Break = Error;
+
+    fn pre_visit_statement(&mut self, statement: &Statement) -> ControlFlow<Self::Break> {
+        let result = match statement {
+            Statement::Update { table, from, .. } => std::iter::once(table)
+                .chain(from)
+                .try_for_each(|table_with_joins| self.push_joins(table_with_joins)),
+            Statement::Delete { from, using, .. } => std::iter::once(from.as_slice())
+                .chain(using.as_deref())
+                .try_for_each(|from| self.push_from(from)),
+            _ => Ok(()),
+        };
+        match result {
+            Ok(()) => ControlFlow::Continue(()),
+            Err(e) => ControlFlow::Break(e),
+        }
+    }
+
+    fn pre_visit_query(&mut self, query: &Query) -> ControlFlow<Self::Break> {
+        let mut selects = vec![];
+        helper::collect_selects(&query.body, &mut selects);
+        for select in selects {
+            if let Err(e) = self.push_from(&select.from) {
+                return ControlFlow::Break(e);
+            }
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+impl JoinExtractor {
+    /// Extract joins from SQL.
+    pub fn extract(dialect: &dyn Dialect, sql: &str) -> Result<Vec<Result<Joins, Error>>, Error> {
+        let statements = instrument::parse_sql(dialect, sql)?;
+        let results = statements
+            .iter()
+            .map(Self::extract_from_statement)
+            .collect::<Vec<Result<Joins, Error>>>();
+        Ok(results)
+    }
+
+    pub fn extract_from_statement(statement: &Statement) -> Result<Joins, Error> {
+        instrument::try_analyze("extract_joins", || {
+            let mut visitor = JoinExtractor::default();
+            match statement.visit(&mut visitor) {
+                ControlFlow::Break(e) => Err(e),
+                ControlFlow::Continue(()) => Ok(visitor.into_joins()),
+            }
+        })
+    }
+
+    /// Joins found so far.
+    /// Useful when the extractor is driven by an external traversal such as [`VisitorSet`](crate::VisitorSet).
+    pub fn into_joins(self) -> Joins {
+        Joins(self.joins)
+    }
+
+    /// Push joins of `FROM` items, where each item after the first is implicitly joined to the preceding ones.
+    fn push_from(&mut self, from: &[TableWithJoins]) -> Result<(), Error> {
+        let mut left = Vec::new();
+        for (i, table_with_joins) in from.iter().enumerate() {
+            if i > 0 {
+                self.joins.push(TableJoin {
+                    left: left.clone(),
+                    right: table_of(&table_with_joins.relation)?,
+                    join_type: JoinType::Implicit,
+                    condition: None,
+                });
+            }
+            self.push_joins(table_with_joins)?;
+            left.extend(tables_of(&table_with_joins.relation)?);
+            for join in &table_with_joins.joins {
+                left.extend(tables_of(&join.relation)?);
+            }
+        }
+        Ok(())
+    }
+
+    fn push_joins(&mut self, table_with_joins: &TableWithJoins) -> Result<(), Error> {
+        self.push_nested_joins(&table_with_joins.relation)?;
+        let mut left = tables_of(&table_with_joins.relation)?;
+        for join in &table_with_joins.joins {
+            self.push_nested_joins(&join.relation)?;
+            let condition = match helper::join_constraint(&join.join_operator) {
+                Some(JoinConstraint::On(expr)) => Some(format!("ON {}", expr)),
+                Some(JoinConstraint::Using(idents)) => Some(format!(
+                    "USING ({})",
+                    idents
+                        .iter()
+                        .map(|ident| ident.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )),
+                Some(JoinConstraint::Natural) => Some("NATURAL".to_string()),
+                Some(JoinConstraint::None) | None => None,
+            };
+            self.joins.push(TableJoin {
+                left: left.clone(),
+                right: table_of(&join.relation)?,
+                join_type: JoinType::from(&join.join_operator),
+                condition,
+            });
+            left.extend(tables_of(&join.relation)?);
+        }
+        Ok(())
+    }
+
+    fn push_nested_joins(&mut self, relation: &TableFactor) -> Result<(), Error> {
+        match relation {
+            TableFactor::NestedJoin {
+                table_with_joins, ..
+            } => self.push_joins(table_with_joins),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The table of a relation, if it is a table.
+fn table_of(relation: &TableFactor) -> Result<Option<TableReference>, Error> {
+    match relation {
+        TableFactor::Table { .. } => TableReference::try_from(relation).map(Some),
+        _ => Ok(None),
+    }
+}
+
+/// Tables of a relation, descending into nested joins.
+fn tables_of(relation: &TableFactor) -> Result<Vec<TableReference>, Error> {
+    match relation {
+        TableFactor::NestedJoin {
+            table_with_joins, ..
+        } => {
+            let mut tables = tables_of(&table_with_joins.relation)?;
+            for join in &table_with_joins.joins {
+                tables.extend(tables_of(&join.relation)?);
+            }
+            Ok(tables)
+        }
+        _ => Ok(table_of(relation)?.into_iter().collect()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+    use sqlparser::dialect::{GenericDialect, MySqlDialect, PostgreSqlDialect};
+
+    fn assert_join_extraction(
+        sql: &str,
+        expected: Vec<Result<Joins, Error>>,
+        dialects: Vec<Box<dyn Dialect>>,
+    ) {
+        for dialect in dialects {
+            let result = JoinExtractor::extract(dialect.as_ref(), sql).unwrap();
+            assert_eq!(result, expected, "Failed for dialect: {dialect:?}")
+        }
+    }
+
+    fn join(
+        left: &[&str],
+        right: Option<&str>,
+        join_type: JoinType,
+        condition: Option<&str>,
+    ) -> TableJoin {
+        TableJoin {
+            left: left.iter().map(|name| name.parse().unwrap()).collect(),
+            right: right.map(|name| name.parse().unwrap()),
+            join_type,
+            condition: condition.map(String::from),
+        }
+    }
+
+    #[test]
+    fn test_select_statement() {
+        let sql =
+            "SELECT * FROM t1 INNER JOIN t2 AS x ON t1.id = x.id LEFT JOIN s1.t3 USING (id, a) \
+            CROSS JOIN t4 WHERE t1.b IN (SELECT b FROM t5 RIGHT JOIN t6 ON t5.c = t6.c)";
+        let expected = vec![Ok(Joins(vec![
+            join(
+                &["t1"],
+                Some("t2 AS x"),
+                JoinType::Inner,
+                Some("ON t1.id = x.id"),
+            ),
+            join(
+                &["t1", "t2 AS x"],
+                Some("s1.t3"),
+                JoinType::LeftOuter,
+                Some("USING (id, a)"),
+            ),
+            join(
+                &["t1", "t2 AS x", "s1.t3"],
+                Some("t4"),
+                JoinType::Cross,
+                None,
+            ),
+            join(
+                &["t5"],
+                Some("t6"),
+                JoinType::RightOuter,
+                Some("ON t5.c = t6.c"),
+            ),
+        ]))];
+        assert_join_extraction(sql, expected, all_dialects());
+    }
+
+    #[test]
+    fn test_implicit_and_derived_joins() {
+        let sql = "SELECT * FROM t1, (SELECT a FROM t2) AS d, t3 JOIN t4 ON t3.id = t4.id; \
+            SELECT * FROM t1";
+        let expected = vec![
+            Ok(Joins(vec![
+                join(&["t1"], None, JoinType::Implicit, None),
+                join(&["t1"], Some("t3"), JoinType::Implicit, None),
+                join(
+                    &["t3"],
+                    Some("t4"),
+                    JoinType::Inner,
+                    Some("ON t3.id = t4.id"),
+                ),
+            ])),
+            Ok(Joins(vec![])),
+        ];
+        assert_join_extraction(sql, expected, all_dialects());
+    }
+
+    #[test]
+    fn test_nested_join() {
+        let sql = "SELECT * FROM t1 LEFT JOIN (t2 JOIN t3 ON t2.id = t3.id) ON t1.id = t2.id";
+        let expected = vec![Ok(Joins(vec![
+            join(
+                &["t2"],
+                Some("t3"),
+                JoinType::Inner,
+                Some("ON t2.id = t3.id"),
+            ),
+            join(&["t1"], None, JoinType::LeftOuter, Some("ON t1.id = t2.id")),
+        ]))];
+        assert_join_extraction(
+            sql,
+            expected,
+            vec![Box::new(PostgreSqlDialect {}), Box::new(GenericDialect {})],
+        );
+    }
+
+    #[test]
+    fn test_update_and_delete_statements() {
+        let sql = "UPDATE t1 JOIN t2 ON t1.id = t2.id SET t1.a = t2.a; \
+            DELETE t1 FROM t1 NATURAL JOIN t2";
+        let expected = vec![
+            Ok(Joins(vec![join(
+                &["t1"],
+                Some("t2"),
+                JoinType::Inner,
+                Some("ON t1.id = t2.id"),
+            )])),
+            Ok(Joins(vec![join(
+                &["t1"],
+                Some("t2"),
+                JoinType::Inner,
+                Some("NATURAL"),
+            )])),
+        ];
+        assert_join_extraction(sql, expected, vec![Box::new(MySqlDialect {})]);
+    }
+
+    #[test]
+    fn test_delete_using() {
+        let sql = "DELETE FROM t1 USING t2 JOIN t3 ON t2.id = t3.id WHERE t1.id = t2.id";
+        let expected = vec![Ok(Joins(vec![join(
+            &["t2"],
+            Some("t3"),
+            JoinType::Inner,
+            Some("ON t2.id = t3.id"),
+        )]))];
+        assert_join_extraction(
+            sql,
+            expected,
+            vec![Box::new(PostgreSqlDialect {}), Box::new(GenericDialect {})],
+        );
+    }
+
+    #[test]
+    fn test_too_many_identifiers() {
+        let sql = "SELECT * FROM t1 JOIN a.b.c.d ON t1.id = d.id";
+        let expected = vec![Err(Error::AnalysisError(
+            "Too many identifiers provided".to_string(),
+        ))];
+        assert_join_extraction(sql, expected, all_dialects());
+    }
+
+    #[test]
+    fn test_is_cross_join() {
+        assert!(join(&["t1"], Some("t2"), JoinType::Cross, None).is_cross_join());
+        assert!(join(&["t1"], Some("t2"), JoinType::Implicit, None).is_cross_join());
+        assert!(join(&["t1"], Some("t2"), JoinType::Inner, None).is_cross_join());
+        assert!(!join(&["t1"], Some("t2"), JoinType::Inner, Some("NATURAL")).is_cross_join());
+        assert!(!join(
+            &["t1"],
+            Some("t2"),
+            JoinType::LeftOuter,
+            Some("ON t1.a = t2.a")
+        )
+        .is_cross_join());
+    }
+}
@@ -1,6 +1,22 @@
+pub mod column_extractor;
 pub mod crud_table_extractor;
+pub mod cte_extractor;
+pub mod ddl_extractor;
+pub mod function_extractor;
 pub mod helper;
+pub mod hint_extractor;
+pub mod join_extractor;
+pub mod options;
+pub mod predicate_extractor;
 pub mod table_extractor;
 
+pub use column_extractor::*;
 pub use crud_table_extractor::*;
+pub use cte_extractor::*;
+pub use ddl_extractor::*;
+pub use function_extractor::*;
+pub use hint_extractor::*;
+pub use join_extractor::*;
+pub use options::*;
+pub use predicate_extractor::*;
 pub use table_extractor::*;
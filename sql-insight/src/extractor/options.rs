@@ -0,0 +1,228 @@
+//! Options shared by extractors for post-processing the tables they extract.
+
+use std::collections::HashSet;
+
+use crate::extractor::table_extractor::{IdentifierRules, TableReference};
+use sqlparser::ast::Ident;
+use sqlparser::dialect::Dialect;
+
+/// Options for extracting tables.
+///
+/// By default, tables are returned as found, in order of appearance and with duplicates.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+/// use sql_insight::ExtractorOptions;
+///
+/// let dialect = GenericDialect {};
+/// let sql = "SELECT * FROM t2 JOIN t1 ON t2.id = t1.id WHERE t2.a IN (SELECT a FROM t1)";
+/// let options = ExtractorOptions::new().with_deduplicate(true).with_sort(true);
+/// let result = sql_insight::extract_tables_with_options(&dialect, sql, options).unwrap();
+/// assert_eq!(result[0].as_ref().unwrap().to_string(), "t1, t2");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExtractorOptions {
+    /// Remove tables found more than once, keeping the first occurrence.
+    /// Tables with different aliases or qualifiers are different tables.
+    pub deduplicate: bool,
+    /// Sort tables by catalog, schema, name and alias, where unqualified tables come first.
+    pub sort: bool,
+    /// Normalize the casing and quoting of identifiers by the rules before deduplicating and sorting,
+    /// so that references to the same table written differently become equal.
+    pub identifier_rules: Option<IdentifierRules>,
+    /// Schema to qualify tables lacking a schema with.
+    /// References to CTEs, which [`TableExtractor`](crate::TableExtractor) reports as tables, are not qualified.
+    pub default_schema: Option<Ident>,
+    /// Catalog to qualify tables lacking a catalog with, once qualified with a schema.
+    pub default_catalog: Option<Ident>,
+}
+
+impl ExtractorOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_deduplicate(mut self, deduplicate: bool) -> Self {
+        self.deduplicate = deduplicate;
+        self
+    }
+
+    pub fn with_sort(mut self, sort: bool) -> Self {
+        self.sort = sort;
+        self
+    }
+
+    /// Normalize identifiers by the rules of the given dialect.
+    /// See [`TableReference::normalized`] for details.
+    pub fn with_normalize_identifiers(mut self, dialect: &dyn Dialect) -> Self {
+        self.identifier_rules = Some(IdentifierRules::of(dialect));
+        self
+    }
+
+    /// Qualify tables lacking a schema with the given schema, e.g. `public`,
+    /// so that tables can be compared by their fully-qualified names.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use sql_insight::sqlparser::dialect::PostgreSqlDialect;
+    /// use sql_insight::ExtractorOptions;
+    ///
+    /// let dialect = PostgreSqlDialect {};
+    /// let sql = "SELECT * FROM users JOIN auth.sessions AS s ON users.id = s.user_id";
+    /// let options = ExtractorOptions::new().with_default_schema("public").with_default_catalog("app");
+    /// let result = sql_insight::extract_tables_with_options(&dialect, sql, options).unwrap();
+    /// assert_eq!(result[0].as_ref().unwrap().to_string(), "app.public.users, app.auth.sessions AS s");
+    /// ```
+    pub fn with_default_schema(mut self, schema: &str) -> Self {
+        self.default_schema = Some(schema.into());
+        self
+    }
+
+    /// Qualify tables lacking a catalog with the given catalog.
+    /// Tables lacking a schema are only qualified when a default schema is set as well.
+    pub fn with_default_catalog(mut self, catalog: &str) -> Self {
+        self.default_catalog = Some(catalog.into());
+        self
+    }
+
+    fn qualify(&self, mut table: TableReference) -> TableReference {
+        if table.schema.is_none() {
+            table.schema = self.default_schema.clone();
+        }
+        if table.schema.is_some() && table.catalog.is_none() {
+            table.catalog = self.default_catalog.clone();
+        }
+        table
+    }
+
+    /// Apply the options to tables.
+    pub(crate) fn apply(&self, tables: Vec<TableReference>) -> Vec<TableReference> {
+        self.apply_with_ctes(tables, &[])
+    }
+
+    /// Apply the options to tables, where `ctes` are the positions of references to CTEs among them,
+    /// which are not qualified.
+    pub(crate) fn apply_with_ctes(
+        &self,
+        mut tables: Vec<TableReference>,
+        ctes: &[usize],
+    ) -> Vec<TableReference> {
+        if self.default_schema.is_some() || self.default_catalog.is_some() {
+            tables = tables
+                .into_iter()
+                .enumerate()
+                .map(|(position, table)| {
+                    if ctes.contains(&position) {
+                        table
+                    } else {
+                        self.qualify(table)
+                    }
+                })
+                .collect();
+        }
+        if let Some(rules) = &self.identifier_rules {
+            tables = tables
+                .iter()
+                .map(|table| table.normalized_with(rules))
+                .collect();
+        }
+        if self.deduplicate {
+            let mut seen = HashSet::new();
+            tables.retain(|table| seen.insert(table.clone()));
+        }
+        if self.sort {
+            tables.sort();
+        }
+        tables
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlparser::dialect::{MsSqlDialect, MySqlDialect, PostgreSqlDialect};
+
+    fn tables(names: &[&str]) -> Vec<TableReference> {
+        names.iter().map(|name| name.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_apply() {
+        let input = tables(&["t2", "s1.t1", "t1 AS x", "t2", "t1"]);
+        assert_eq!(ExtractorOptions::new().apply(input.clone()), input);
+        assert_eq!(
+            ExtractorOptions::new()
+                .with_deduplicate(true)
+                .apply(input.clone()),
+            tables(&["t2", "s1.t1", "t1 AS x", "t1"])
+        );
+        assert_eq!(
+            ExtractorOptions::new().with_sort(true).apply(input.clone()),
+            tables(&["t1", "t1 AS x", "t2", "t2", "s1.t1"])
+        );
+        assert_eq!(
+            ExtractorOptions::new()
+                .with_deduplicate(true)
+                .with_sort(true)
+                .apply(input),
+            tables(&["t1", "t1 AS x", "t2", "s1.t1"])
+        );
+    }
+
+    #[test]
+    fn test_apply_with_normalize_identifiers() {
+        let input = tables(&[
+            "Users",
+            "\"users\"",
+            "`users`",
+            "\"Users\"",
+            "S1.users AS U",
+        ]);
+        let options = |dialect: &dyn Dialect| {
+            ExtractorOptions::new()
+                .with_normalize_identifiers(dialect)
+                .with_deduplicate(true)
+        };
+        assert_eq!(
+            options(&PostgreSqlDialect {}).apply(input.clone()),
+            tables(&["users", "\"Users\"", "s1.users AS u"])
+        );
+        assert_eq!(
+            options(&MySqlDialect {}).apply(input.clone()),
+            tables(&["Users", "users", "S1.users AS U"])
+        );
+        assert_eq!(
+            options(&MsSqlDialect {}).apply(input),
+            tables(&["users", "s1.users AS u"])
+        );
+    }
+
+    #[test]
+    fn test_apply_with_default_schema() {
+        let input = tables(&["t1", "s1.t1 AS x", "c1.s1.t1", "public.t1"]);
+        assert_eq!(
+            ExtractorOptions::new()
+                .with_default_schema("public")
+                .apply(input.clone()),
+            tables(&["public.t1", "s1.t1 AS x", "c1.s1.t1", "public.t1"])
+        );
+        assert_eq!(
+            ExtractorOptions::new()
+                .with_default_catalog("app")
+                .apply(input.clone()),
+            tables(&["t1", "app.s1.t1 AS x", "c1.s1.t1", "app.public.t1"])
+        );
+        assert_eq!(
+            ExtractorOptions::new()
+                .with_default_schema("public")
+                .with_default_catalog("app")
+                .with_deduplicate(true)
+                .apply(input),
+            tables(&["app.public.t1", "app.s1.t1 AS x", "c1.s1.t1"])
+        );
+    }
+}
@@ -0,0 +1,549 @@
+//! A Extractor that extracts predicates of WHERE, HAVING and ON clauses from SQL queries.
+//!
+//! See [`extract_predicates`](crate::extract_predicates()) as the entry point for extracting predicates from SQL.
+
+use core::fmt;
+use std::ops::ControlFlow;
+
+use crate::error::Error;
+use crate::extractor::column_extractor::ColumnReference;
+use crate::helper;
+use crate::instrument;
+use sqlparser::ast::{
+    BinaryOperator, Expr, Query, Statement, UnaryOperator, Value, Visit, Visitor,
+};
+use sqlparser::dialect::Dialect;
+
+/// Convenience function to extract predicates from SQL.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+/// use sql_insight::{PredicateOperator, PredicateValue};
+///
+/// let dialect = GenericDialect {};
+/// let sql = "SELECT * FROM orders WHERE tenant_id = ? AND created_at > '2024-01-01'";
+/// let result = sql_insight::extract_predicates(&dialect, sql).unwrap();
+/// let predicates = result[0].as_ref().unwrap();
+/// assert_eq!(predicates.to_string(), "WHERE tenant_id = ?; WHERE created_at > '2024-01-01'");
+/// let shard_key = predicates
+///     .0
+///     .iter()
+///     .find(|p| p.column.name.value == "tenant_id" && p.operator == PredicateOperator::Eq)
+///     .unwrap();
+/// assert_eq!(shard_key.values, vec![PredicateValue::Placeholder("?".to_string())]);
+/// ```
+pub fn extract_predicates(
+    dialect: &dyn Dialect,
+    sql: &str,
+) -> Result<Vec<Result<Predicates, Error>>, Error> {
+    PredicateExtractor::extract(dialect, sql)
+}
+
+/// [`PredicateClause`] represents the clause a [`Predicate`] is found in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum PredicateClause {
+    Where,
+    Having,
+    On,
+}
+
+impl fmt::Display for PredicateClause {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PredicateClause::Where => write!(f, "WHERE"),
+            PredicateClause::Having => write!(f, "HAVING"),
+            PredicateClause::On => write!(f, "ON"),
+        }
+    }
+}
+
+/// [`PredicateOperator`] represents how a column is compared with values.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum PredicateOperator {
+    Eq,
+    NotEq,
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+    In,
+    NotIn,
+    Between,
+    NotBetween,
+    Like,
+    NotLike,
+    IsNull,
+    IsNotNull,
+}
+
+impl fmt::Display for PredicateOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PredicateOperator::Eq => write!(f, "="),
+            PredicateOperator::NotEq => write!(f, "<>"),
+            PredicateOperator::Lt => write!(f, "<"),
+            PredicateOperator::LtEq => write!(f, "<="),
+            PredicateOperator::Gt => write!(f, ">"),
+            PredicateOperator::GtEq => write!(f, ">="),
+            PredicateOperator::In => write!(f, "IN"),
+            PredicateOperator::NotIn => write!(f, "NOT IN"),
+            PredicateOperator::Between => write!(f, "BETWEEN"),
+            PredicateOperator::NotBetween => write!(f, "NOT BETWEEN"),
+            PredicateOperator::Like => write!(f, "LIKE"),
+            PredicateOperator::NotLike => write!(f, "NOT LIKE"),
+            PredicateOperator::IsNull => write!(f, "IS NULL"),
+            PredicateOperator::IsNotNull => write!(f, "IS NOT NULL"),
+        }
+    }
+}
+
+impl PredicateOperator {
+    fn from_binary_operator(op: &BinaryOperator) -> Option<Self> {
+        match op {
+            BinaryOperator::Eq => Some(PredicateOperator::Eq),
+            BinaryOperator::NotEq => Some(PredicateOperator::NotEq),
+            BinaryOperator::Lt => Some(PredicateOperator::Lt),
+            BinaryOperator::LtEq => Some(PredicateOperator::LtEq),
+            BinaryOperator::Gt => Some(PredicateOperator::Gt),
+            BinaryOperator::GtEq => Some(PredicateOperator::GtEq),
+            _ => None,
+        }
+    }
+
+    /// The operator comparing the operands swapped, e.g. `>` for `1 < a` written as `a > 1`.
+    fn swapped(self) -> Self {
+        match self {
+            PredicateOperator::Lt => PredicateOperator::Gt,
+            PredicateOperator::LtEq => PredicateOperator::GtEq,
+            PredicateOperator::Gt => PredicateOperator::Lt,
+            PredicateOperator::GtEq => PredicateOperator::LtEq,
+            op => op,
+        }
+    }
+}
+
+/// [`PredicateValue`] represents a value a column is compared with.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum PredicateValue {
+    /// A literal value, e.g. `1` or `'a'`. A negated number such as `-1` is a literal.
+    Literal(Value),
+    /// A placeholder, e.g. `?` or `$1`.
+    Placeholder(String),
+    /// Another column, e.g. in a join condition.
+    Column(ColumnReference),
+    /// Any other expression, e.g. a function call or arithmetic.
+    Expression(Expr),
+}
+
+impl fmt::Display for PredicateValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PredicateValue::Literal(value) => write!(f, "{}", value),
+            PredicateValue::Placeholder(placeholder) => write!(f, "{}", placeholder),
+            PredicateValue::Column(column) => write!(f, "{}", column),
+            PredicateValue::Expression(expr) => write!(f, "{}", expr),
+        }
+    }
+}
+
+impl TryFrom<&Expr> for PredicateValue {
+    type Error = Error;
+
+    fn try_from(expr: &Expr) -> Result<Self, Self::Error> {
+        match expr {
+            Expr::Identifier(ident) => Ok(PredicateValue::Column(ColumnReference::try_from(
+                std::slice::from_ref(ident),
+            )?)),
+            Expr::CompoundIdentifier(idents) => Ok(PredicateValue::Column(
+                ColumnReference::try_from(idents.as_slice())?,
+            )),
+            Expr::Value(Value::Placeholder(placeholder)) => {
+                Ok(PredicateValue::Placeholder(placeholder.clone()))
+            }
+            Expr::Value(value) => Ok(PredicateValue::Literal(value.clone())),
+            Expr::UnaryOp {
+                op: UnaryOperator::Minus,
+                expr,
+            } => match expr.as_ref() {
+                Expr::Value(Value::Number(number, long)) => Ok(PredicateValue::Literal(
+                    Value::Number(format!("-{number}"), *long),
+                )),
+                _ => Ok(PredicateValue::Expression(Expr::UnaryOp {
+                    op: UnaryOperator::Minus,
+                    expr: expr.clone(),
+                })),
+            },
+            Expr::Nested(expr) => PredicateValue::try_from(expr.as_ref()),
+            _ => Ok(PredicateValue::Expression(expr.clone())),
+        }
+    }
+}
+
+/// [`Predicate`] represents a column compared with values, e.g. `a = 1` or `a IN (?, ?)`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Predicate {
+    pub clause: PredicateClause,
+    /// The column compared, written on either side of the operator.
+    /// When both sides are columns, it is the left one.
+    pub column: ColumnReference,
+    /// The operator, as if the column were written on the left side.
+    pub operator: PredicateOperator,
+    /// Values compared with, i.e. one for comparisons and `LIKE`, the list for `IN`,
+    /// the bounds for `BETWEEN` and none for `IS NULL`.
+    pub values: Vec<PredicateValue>,
+}
+
+impl fmt::Display for Predicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}", self.clause, self.column, self.operator)?;
+        let values = self
+            .values
+            .iter()
+            .map(|value| value.to_string())
+            .collect::<Vec<String>>();
+        match self.operator {
+            PredicateOperator::IsNull | PredicateOperator::IsNotNull => Ok(()),
+            PredicateOperator::In | PredicateOperator::NotIn => {
+                write!(f, " ({})", values.join(", "))
+            }
+            PredicateOperator::Between | PredicateOperator::NotBetween => {
+                write!(f, " {}", values.join(" AND "))
+            }
+            _ => write!(f, " {}", values.join(", ")),
+        }
+    }
+}
+
+/// [`Predicates`] represents a list of [`Predicate`] that found in SQL.
+#[derive(Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Predicates(pub Vec<Predicate>);
+
+impl fmt::Display for Predicates {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let predicates = self
+            .0
+            .iter()
+            .map(|predicate| predicate.to_string())
+            .collect::<Vec<String>>()
+            .join("; ");
+        write!(f, "{}", predicates)
+    }
+}
+
+/// A visitor to extract predicates from SQL.
+///
+/// Predicates are the conditions combined with `AND` at the top level of WHERE, HAVING and ON clauses,
+/// including those of subqueries, in the order of ON, WHERE and HAVING of each SELECT.
+/// Conditions under `OR` or `NOT` do not restrict every row, so they are not extracted, nor are
+/// conditions not comparing a column, e.g. `1 = 1` or `a + 1 = 2`.
+#[derive(Default, Debug)]
+pub struct PredicateExtractor {
+    predicates: Vec<Predicate>,
+}
+
+impl Visitor for PredicateExtractor {
+    type Break = Error;
+
+    fn pre_visit_statement(&mut self, statement: &Statement) -> ControlFlow<Self::Break> {
+        let mut join_conditions = vec![];
+        let selection = match statement {
+            Statement::Update {
+                table,
+                from,
+                selection,
+                ..
+            } => {
+                helper::collect_join_conditions(table, &mut join_conditions);
+                if let Some(from) = from {
+                    helper::collect_join_conditions(from, &mut join_conditions);
+                }
+                selection
+            }
+            Statement::Delete {
+                from, selection, ..
+            } => {
+                for table_with_joins in from {
+                    helper::collect_join_conditions(table_with_joins, &mut join_conditions);
+                }
+                selection
+            }
+            _ => return ControlFlow::Continue(()),
+        };
+        for condition in join_conditions {
+            self.push_conjuncts(condition, PredicateClause::On)?;
+        }
+        if let Some(selection) = selection {
+            self.push_conjuncts(selection, PredicateClause::Where)?;
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_query(&mut self, query: &Query) -> ControlFlow<Self::Break> {
+        let mut selects = vec![];
+        helper::collect_selects(&query.body, &mut selects);
+        for select in selects {
+            let mut join_conditions = vec![];
+            for table_with_joins in &select.from {
+                helper::collect_join_conditions(table_with_joins, &mut join_conditions);
+            }
+            for condition in join_conditions {
+                self.push_conjuncts(condition, PredicateClause::On)?;
+            }
+            if let Some(selection) = &select.selection {
+                self.push_conjuncts(selection, PredicateClause::Where)?;
+            }
+            if let Some(having) = &select.having {
+                self.push_conjuncts(having, PredicateClause::Having)?;
+            }
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+impl PredicateExtractor {
+    /// Extract predicates from SQL.
+    pub fn extract(
+        dialect: &dyn Dialect,
+        sql: &str,
+    ) -> Result<Vec<Result<Predicates, Error>>, Error> {
+        let statements = instrument::parse_sql(dialect, sql)?;
+        let results = statements
+            .iter()
+            .map(Self::extract_from_statement)
+            .collect::<Vec<Result<Predicates, Error>>>();
+        Ok(results)
+    }
+
+    pub fn extract_from_statement(statement: &Statement) -> Result<Predicates, Error> {
+        instrument::try_analyze("extract_predicates", || {
+            let mut visitor = PredicateExtractor::default();
+            match statement.visit(&mut visitor) {
+                ControlFlow::Break(e) => Err(e),
+                ControlFlow::Continue(()) => Ok(visitor.into_predicates()),
+            }
+        })
+    }
+
+    /// Predicates found so far.
+    /// Useful when the extractor is driven by an external traversal such as [`VisitorSet`](crate::VisitorSet).
+    pub fn into_predicates(self) -> Predicates {
+        Predicates(self.predicates)
+    }
+
+    fn push_conjuncts(&mut self, expr: &Expr, clause: PredicateClause) -> ControlFlow<Error> {
+        match expr {
+            Expr::BinaryOp {
+                left,
+                op: BinaryOperator::And,
+                right,
+            } => {
+                self.push_conjuncts(left, clause)?;
+                self.push_conjuncts(right, clause)
+            }
+            Expr::Nested(expr) => self.push_conjuncts(expr, clause),
+            _ => match Self::predicate(expr, clause) {
+                Ok(Some(predicate)) => {
+                    self.predicates.push(predicate);
+                    ControlFlow::Continue(())
+                }
+                Ok(None) => ControlFlow::Continue(()),
+                Err(e) => ControlFlow::Break(e),
+            },
+        }
+    }
+
+    fn predicate(expr: &Expr, clause: PredicateClause) -> Result<Option<Predicate>, Error> {
+        let (subject, operator, values) = match expr {
+            Expr::BinaryOp { left, op, right } => {
+                let Some(operator) = PredicateOperator::from_binary_operator(op) else {
+                    return Ok(None);
+                };
+                let (left, right) = (
+                    PredicateValue::try_from(left.as_ref())?,
+                    PredicateValue::try_from(right.as_ref())?,
+                );
+                let (column, operator, value) = match (left, right) {
+                    (PredicateValue::Column(column), value) => (column, operator, value),
+                    (value, PredicateValue::Column(column)) => (column, operator.swapped(), value),
+                    _ => return Ok(None),
+                };
+                return Ok(Some(Predicate {
+                    clause,
+                    column,
+                    operator,
+                    values: vec![value],
+                }));
+            }
+            Expr::InList {
+                expr,
+                list,
+                negated,
+            } => (
+                expr,
+                match negated {
+                    true => PredicateOperator::NotIn,
+                    false => PredicateOperator::In,
+                },
+                list.iter()
+                    .map(PredicateValue::try_from)
+                    .collect::<Result<Vec<_>, _>>()?,
+            ),
+            Expr::Between {
+                expr,
+                negated,
+                low,
+                high,
+            } => (
+                expr,
+                match negated {
+                    true => PredicateOperator::NotBetween,
+                    false => PredicateOperator::Between,
+                },
+                vec![
+                    PredicateValue::try_from(low.as_ref())?,
+                    PredicateValue::try_from(high.as_ref())?,
+                ],
+            ),
+            Expr::Like {
+                negated,
+                expr,
+                pattern,
+                ..
+            } => (
+                expr,
+                match negated {
+                    true => PredicateOperator::NotLike,
+                    false => PredicateOperator::Like,
+                },
+                vec![PredicateValue::try_from(pattern.as_ref())?],
+            ),
+            Expr::IsNull(expr) => (expr, PredicateOperator::IsNull, vec![]),
+            Expr::IsNotNull(expr) => (expr, PredicateOperator::IsNotNull, vec![]),
+            _ => return Ok(None),
+        };
+        match PredicateValue::try_from(subject.as_ref())? {
+            PredicateValue::Column(column) => Ok(Some(Predicate {
+                clause,
+                column,
+                operator,
+                values,
+            })),
+            _ => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+    use sqlparser::ast::Ident;
+    use sqlparser::dialect::{GenericDialect, MySqlDialect, PostgreSqlDialect};
+
+    fn assert_predicate_extraction(
+        sql: &str,
+        expected: Vec<Result<String, Error>>,
+        dialects: Vec<Box<dyn Dialect>>,
+    ) {
+        for dialect in dialects {
+            let result = PredicateExtractor::extract(dialect.as_ref(), sql)
+                .unwrap()
+                .into_iter()
+                .map(|result| result.map(|predicates| predicates.to_string()))
+                .collect::<Vec<_>>();
+            assert_eq!(result, expected, "Failed for dialect: {dialect:?}")
+        }
+    }
+
+    fn column(idents: &[&str]) -> ColumnReference {
+        let idents = idents.iter().map(|&s| Ident::new(s)).collect::<Vec<_>>();
+        ColumnReference::try_from(idents.as_slice()).unwrap()
+    }
+
+    #[test]
+    fn test_select_statement() {
+        let sql = "SELECT a FROM t1 JOIN t2 ON t1.id = t2.id AND t2.b > 1 \
+            WHERE t1.c = 'x' AND (t1.d IN (1, 2) OR t1.e = 3) AND 10 <= t1.f \
+            GROUP BY a HAVING COUNT(*) > 1 AND a IS NOT NULL";
+        let expected = vec![Ok(
+            "ON t1.id = t2.id; ON t2.b > 1; WHERE t1.c = 'x'; WHERE t1.f >= 10; HAVING a IS NOT NULL"
+                .to_string(),
+        )];
+        assert_predicate_extraction(sql, expected, all_dialects());
+    }
+
+    #[test]
+    fn test_in_between_like_and_subqueries() {
+        let sql = "SELECT a FROM t1 WHERE a NOT IN (1, -2) AND b BETWEEN 1 AND 10 AND c LIKE 'x%' \
+            AND d IS NULL AND e IN (SELECT e FROM t2 WHERE f <> 0)";
+        let expected = vec![Ok(
+            "WHERE a NOT IN (1, -2); WHERE b BETWEEN 1 AND 10; WHERE c LIKE 'x%'; WHERE d IS NULL; WHERE f <> 0"
+                .to_string(),
+        )];
+        assert_predicate_extraction(sql, expected, all_dialects());
+    }
+
+    #[test]
+    fn test_update_and_delete_statements() {
+        let sql = "UPDATE t1 SET a = 1 WHERE id = 2 AND b = c; DELETE FROM t1 WHERE id IN (3, 4)";
+        let expected = vec![
+            Ok("WHERE id = 2; WHERE b = c".to_string()),
+            Ok("WHERE id IN (3, 4)".to_string()),
+        ];
+        assert_predicate_extraction(sql, expected, all_dialects());
+    }
+
+    #[test]
+    fn test_placeholders() {
+        let sql = "SELECT a FROM t1 WHERE tenant_id = ? AND id IN (?, ?)";
+        for dialect in [
+            Box::new(GenericDialect {}) as Box<dyn Dialect>,
+            Box::new(MySqlDialect {}),
+        ] {
+            let result = PredicateExtractor::extract(dialect.as_ref(), sql).unwrap();
+            assert_eq!(
+                result,
+                vec![Ok(Predicates(vec![
+                    Predicate {
+                        clause: PredicateClause::Where,
+                        column: column(&["tenant_id"]),
+                        operator: PredicateOperator::Eq,
+                        values: vec![PredicateValue::Placeholder("?".to_string())],
+                    },
+                    Predicate {
+                        clause: PredicateClause::Where,
+                        column: column(&["id"]),
+                        operator: PredicateOperator::In,
+                        values: vec![
+                            PredicateValue::Placeholder("?".to_string()),
+                            PredicateValue::Placeholder("?".to_string()),
+                        ],
+                    },
+                ]))],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+        let sql = "SELECT a FROM t1 WHERE $1 = t1.tenant_id";
+        let expected = vec![Ok("WHERE t1.tenant_id = $1".to_string())];
+        assert_predicate_extraction(sql, expected, vec![Box::new(PostgreSqlDialect {})]);
+    }
+
+    #[test]
+    fn test_too_many_identifiers() {
+        let sql = "SELECT a FROM t1 WHERE a.b.c.d.e = 1";
+        let expected = vec![Err(Error::AnalysisError(
+            "Too many identifiers provided".to_string(),
+        ))];
+        assert_predicate_extraction(sql, expected, all_dialects());
+    }
+}
@@ -4,12 +4,24 @@
 
 use core::fmt;
 use std::ops::ControlFlow;
+use std::str::FromStr;
 
 use crate::error::Error;
+use crate::extractor::options::ExtractorOptions;
 use crate::helper;
-use sqlparser::ast::{Ident, ObjectName, Statement, TableFactor, TableWithJoins, Visit, Visitor};
-use sqlparser::dialect::Dialect;
+use crate::instrument;
+use crate::linter::KeywordDialect;
+use crate::rewriter::IdentifierQuotes;
+use sqlparser::ast::{
+    Ident, ObjectName, Query, Statement, TableFactor, TableWithJoins, Visit, Visitor,
+};
+use sqlparser::dialect::{
+    AnsiDialect, BigQueryDialect, ClickHouseDialect, Dialect, DuckDbDialect, GenericDialect,
+    HiveDialect, MsSqlDialect, MySqlDialect, SQLiteDialect, SnowflakeDialect,
+};
+use sqlparser::keywords;
 use sqlparser::parser::Parser;
+use sqlparser::tokenizer::Token;
 
 /// Convenience function to extract tables from SQL.
 ///
@@ -31,10 +43,38 @@ pub fn extract_tables(
     TableExtractor::extract(dialect, sql)
 }
 
+/// Convenience function to extract tables from SQL with options.
+/// See [`ExtractorOptions`] for an example.
+pub fn extract_tables_with_options(
+    dialect: &dyn Dialect,
+    sql: &str,
+    options: ExtractorOptions,
+) -> Result<Vec<Result<Tables, Error>>, Error> {
+    TableExtractor::extract_with_options(dialect, sql, options)
+}
+
 /// [`TableReference`] represents a qualified table with alias.
 /// In this crate, this is the canonical representation of a table.
 /// Tables found during analyzing an AST are stored as `TableReference`.
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+///
+/// A `TableReference` can be parsed from a string such as `catalog.schema.table AS alias`,
+/// and its string representation parses back into the same `TableReference`, including quote styles.
+/// With the `serde` feature, it serializes into a struct of identifiers with their quote styles,
+/// and deserializes from either that struct or a string, which is handy for configuration files.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::TableReference;
+///
+/// let table: TableReference = "app.\"User Table\" AS u".parse().unwrap();
+/// assert_eq!(table.schema.as_ref().unwrap().value, "app");
+/// assert_eq!(table.name.value, "User Table");
+/// assert_eq!(table.name.quote_style, Some('"'));
+/// assert_eq!(table.to_string(), "app.\"User Table\" AS u");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct TableReference {
     pub catalog: Option<Ident>,
     pub schema: Option<Ident>,
@@ -49,6 +89,140 @@ impl TableReference {
     pub fn has_qualifiers(&self) -> bool {
         self.catalog.is_some() || self.schema.is_some()
     }
+
+    /// Normalize the casing and quoting of identifiers by the rules of the given dialect,
+    /// so that references to the same table become equal. See [`IdentifierRules`] for details.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use sql_insight::sqlparser::dialect::{MySqlDialect, PostgreSqlDialect};
+    /// use sql_insight::TableReference;
+    ///
+    /// let table = |s: &str| s.parse::<TableReference>().unwrap();
+    /// let postgres = PostgreSqlDialect {};
+    /// assert_eq!(table("Public.Users").normalized(&postgres), table("public.users"));
+    /// assert_eq!(table("\"users\"").normalized(&postgres), table("users"));
+    /// assert_eq!(table("\"Users\"").normalized(&postgres).to_string(), "\"Users\"");
+    /// assert_eq!(table("`users`").normalized(&MySqlDialect {}), table("users"));
+    /// ```
+    pub fn normalized(&self, dialect: &dyn Dialect) -> TableReference {
+        self.normalized_with(&IdentifierRules::of(dialect))
+    }
+
+    /// Normalize the casing and quoting of identifiers by the given rules.
+    pub fn normalized_with(&self, rules: &IdentifierRules) -> TableReference {
+        TableReference {
+            catalog: self.catalog.as_ref().map(|ident| rules.normalize(ident)),
+            schema: self.schema.as_ref().map(|ident| rules.normalize(ident)),
+            name: rules.normalize(&self.name),
+            alias: self.alias.as_ref().map(|ident| rules.normalize(ident)),
+        }
+    }
+
+    /// Whether both references refer to the same table by the rules of the given dialect, ignoring aliases.
+    /// Qualifiers are compared as written, so `s1.t1` and `t1` are different tables.
+    pub fn is_same_table(&self, other: &TableReference, dialect: &dyn Dialect) -> bool {
+        let rules = IdentifierRules::of(dialect);
+        let normalize = |ident: &Option<Ident>| ident.as_ref().map(|ident| rules.normalize(ident));
+        normalize(&self.catalog) == normalize(&other.catalog)
+            && normalize(&self.schema) == normalize(&other.schema)
+            && rules.normalize(&self.name) == rules.normalize(&other.name)
+    }
+}
+
+/// [`IdentifierFolding`] represents how a dialect resolves the casing of identifiers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum IdentifierFolding {
+    /// Unquoted identifiers are folded to lowercase and quoted identifiers are case-sensitive, as in PostgreSQL.
+    Lower,
+    /// Unquoted identifiers are folded to uppercase and quoted identifiers are case-sensitive,
+    /// as in the SQL standard and Snowflake.
+    Upper,
+    /// Identifiers are case-insensitive whether quoted or not, as in MsSQL and SQLite.
+    CaseInsensitive,
+    /// Identifiers are case-sensitive whether quoted or not, as in BigQuery and MySQL on case-sensitive file systems.
+    CaseSensitive,
+}
+
+/// [`IdentifierRules`] represents the rules of a dialect for resolving identifiers,
+/// used to normalize identifiers so that identifiers referring to the same object are equal.
+///
+/// A normalized identifier has its value folded as the dialect resolves it, and is quoted with the quote of the dialect
+/// only when quotes are needed to keep its meaning, i.e. when it is a reserved keyword, contains characters other than
+/// letters, digits and underscores, or its casing would be folded without quotes. A [`TableReference`] normalized
+/// with any dialect's quote, including MsSQL's brackets, parses back from its string representation.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::ast::Ident;
+/// use sql_insight::sqlparser::dialect::MsSqlDialect;
+/// use sql_insight::IdentifierRules;
+///
+/// let rules = IdentifierRules::of(&MsSqlDialect {});
+/// assert_eq!(rules.normalize(&Ident::with_quote('"', "Users")), Ident::new("users"));
+/// assert_eq!(rules.normalize(&Ident::new("User Table")), Ident::with_quote('[', "user table"));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IdentifierRules {
+    pub folding: IdentifierFolding,
+    /// Quote for identifiers that need quotes.
+    pub quote: char,
+    /// Reserved keywords, which need quotes.
+    pub keywords: KeywordDialect,
+}
+
+impl IdentifierRules {
+    /// Rules of the given dialect. Unquoted identifiers are folded to lowercase for the generic dialect.
+    pub fn of(dialect: &dyn Dialect) -> Self {
+        let folding = if dialect.is::<SnowflakeDialect>() || dialect.is::<AnsiDialect>() {
+            IdentifierFolding::Upper
+        } else if dialect.is::<MsSqlDialect>()
+            || dialect.is::<SQLiteDialect>()
+            || dialect.is::<HiveDialect>()
+            || dialect.is::<DuckDbDialect>()
+        {
+            IdentifierFolding::CaseInsensitive
+        } else if dialect.is::<MySqlDialect>()
+            || dialect.is::<BigQueryDialect>()
+            || dialect.is::<ClickHouseDialect>()
+        {
+            IdentifierFolding::CaseSensitive
+        } else {
+            IdentifierFolding::Lower
+        };
+        Self {
+            folding,
+            quote: IdentifierQuotes::for_dialect(dialect).quote(),
+            keywords: KeywordDialect::of(dialect),
+        }
+    }
+
+    /// Normalize the casing and quoting of an identifier.
+    pub fn normalize(&self, ident: &Ident) -> Ident {
+        let quoted = ident.quote_style.is_some();
+        let value = match self.folding {
+            IdentifierFolding::Lower if !quoted => ident.value.to_lowercase(),
+            IdentifierFolding::Upper if !quoted => ident.value.to_uppercase(),
+            IdentifierFolding::CaseInsensitive => ident.value.to_lowercase(),
+            _ => ident.value.clone(),
+        };
+        let is_plain = value.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && value.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        let is_folded = match self.folding {
+            IdentifierFolding::Lower => value == value.to_lowercase(),
+            IdentifierFolding::Upper => value == value.to_uppercase(),
+            IdentifierFolding::CaseInsensitive | IdentifierFolding::CaseSensitive => true,
+        };
+        if is_plain && is_folded && !self.keywords.is_reserved(&value) {
+            Ident::new(value)
+        } else {
+            Ident::with_quote(self.quote, value)
+        }
+    }
 }
 
 impl fmt::Display for TableReference {
@@ -70,6 +244,83 @@ impl fmt::Display for TableReference {
     }
 }
 
+impl FromStr for TableReference {
+    type Err = Error;
+
+    /// Parse a table reference with up to three identifiers and an optional alias, e.g. `catalog.schema.table AS alias`.
+    /// Identifiers can be quoted with double quotes, backticks or square brackets.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let dialect = TableReferenceDialect;
+        let mut parser = Parser::new(&dialect).try_with_sql(s)?;
+        let name = parser.parse_object_name(false)?;
+        let alias = parser.parse_optional_table_alias(keywords::RESERVED_FOR_TABLE_ALIAS)?;
+        parser.expect_token(&Token::EOF)?;
+        if alias
+            .as_ref()
+            .is_some_and(|alias| !alias.columns.is_empty())
+        {
+            return Err(Error::ArgumentError(format!(
+                "Column aliases are not allowed in a table reference: {}",
+                s
+            )));
+        }
+        Ok(TableReference {
+            alias: alias.map(|alias| alias.name),
+            ..TableReference::try_from(&name)?
+        })
+    }
+}
+
+/// Dialect of table references parsed from strings, accepting identifiers quoted with the quote of any dialect,
+/// so that a reference written with [`Display`](fmt::Display) parses back whichever dialect it was extracted with.
+#[derive(Debug)]
+struct TableReferenceDialect;
+
+impl Dialect for TableReferenceDialect {
+    fn is_delimited_identifier_start(&self, ch: char) -> bool {
+        matches!(ch, '"' | '`' | '[')
+    }
+
+    fn is_identifier_start(&self, ch: char) -> bool {
+        GenericDialect {}.is_identifier_start(ch)
+    }
+
+    fn is_identifier_part(&self, ch: char) -> bool {
+        GenericDialect {}.is_identifier_part(ch)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for TableReference {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(serde::Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            String(String),
+            Struct {
+                catalog: Option<Ident>,
+                schema: Option<Ident>,
+                name: Ident,
+                alias: Option<Ident>,
+            },
+        }
+        match Repr::deserialize(deserializer)? {
+            Repr::String(s) => s.parse().map_err(serde::de::Error::custom),
+            Repr::Struct {
+                catalog,
+                schema,
+                name,
+                alias,
+            } => Ok(TableReference {
+                catalog,
+                schema,
+                name,
+                alias,
+            }),
+        }
+    }
+}
+
 impl TryFrom<&TableFactor> for TableReference {
     type Error = Error;
 
@@ -137,6 +388,7 @@ impl TryFrom<&ObjectName> for TableReference {
 
 /// [`Tables`] represents a list of [`TableReference`] that found in SQL.
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Tables(pub Vec<TableReference>);
 
 impl fmt::Display for Tables {
@@ -160,11 +412,25 @@ pub struct TableExtractor {
     original_tables: Vec<TableReference>,
     // Flag to indicate if the current relation is part of a `TableFactor::Table`
     relation_of_table: bool,
+    // CTEs in scope of the queries being visited.
+    cte_scopes: helper::CteScopes,
+    // Positions of references to CTEs in `all_tables`, which options don't qualify.
+    cte_positions: Vec<usize>,
 }
 
 impl Visitor for TableExtractor {
     type Break = Error;
 
+    fn pre_visit_query(&mut self, query: &Query) -> ControlFlow<Self::Break> {
+        self.cte_scopes.enter(query);
+        ControlFlow::Continue(())
+    }
+
+    fn post_visit_query(&mut self, _query: &Query) -> ControlFlow<Self::Break> {
+        self.cte_scopes.exit();
+        ControlFlow::Continue(())
+    }
+
     fn pre_visit_relation(&mut self, relation: &ObjectName) -> ControlFlow<Self::Break> {
         // Skip if relation is part of a TableFactor::Table
         if self.relation_of_table {
@@ -182,10 +448,14 @@ impl Visitor for TableExtractor {
     }
 
     fn pre_visit_table_factor(&mut self, table_factor: &TableFactor) -> ControlFlow<Self::Break> {
-        if let TableFactor::Table { .. } = table_factor {
+        if let TableFactor::Table { name, .. } = table_factor {
             self.relation_of_table = true;
+            let is_cte = self.cte_scopes.contains(name);
             match TableReference::try_from(table_factor) {
                 Ok(table) => {
+                    if is_cte {
+                        self.cte_positions.push(self.all_tables.len());
+                    }
                     self.all_tables.push(table.clone());
                     self.original_tables.push(table)
                 }
@@ -212,7 +482,7 @@ impl Visitor for TableExtractor {
 impl TableExtractor {
     /// Extract tables from SQL.
     pub fn extract(dialect: &dyn Dialect, sql: &str) -> Result<Vec<Result<Tables, Error>>, Error> {
-        let statements = Parser::parse_sql(dialect, sql)?;
+        let statements = instrument::parse_sql(dialect, sql)?;
         let results = statements
             .iter()
             .map(Self::extract_from_statement)
@@ -220,15 +490,39 @@ impl TableExtractor {
         Ok(results)
     }
 
+    /// Extract tables from SQL with options.
+    pub fn extract_with_options(
+        dialect: &dyn Dialect,
+        sql: &str,
+        options: ExtractorOptions,
+    ) -> Result<Vec<Result<Tables, Error>>, Error> {
+        let statements = instrument::parse_sql(dialect, sql)?;
+        let results = statements
+            .iter()
+            .map(|statement| {
+                Self::extract_with_cte_positions(statement)
+                    .map(|(tables, ctes)| Tables(options.apply_with_ctes(tables.0, &ctes)))
+            })
+            .collect::<Vec<Result<Tables, Error>>>();
+        Ok(results)
+    }
+
     pub fn extract_from_statement(statement: &Statement) -> Result<Tables, Error> {
-        let mut visitor = TableExtractor::default();
-        match statement.visit(&mut visitor) {
-            ControlFlow::Break(e) => Err(e),
-            ControlFlow::Continue(()) => Ok(Tables(helper::resolve_aliased_tables(
-                visitor.all_tables,
-                visitor.original_tables,
-            ))),
-        }
+        Self::extract_with_cte_positions(statement).map(|(tables, _)| tables)
+    }
+
+    /// Extract tables from a statement, together with the positions of references to CTEs among them.
+    fn extract_with_cte_positions(statement: &Statement) -> Result<(Tables, Vec<usize>), Error> {
+        instrument::try_analyze("extract_tables", || {
+            let mut visitor = TableExtractor::default();
+            match statement.visit(&mut visitor) {
+                ControlFlow::Break(e) => Err(e),
+                ControlFlow::Continue(()) => {
+                    let ctes = std::mem::take(&mut visitor.cte_positions);
+                    Ok((visitor.into_tables(), ctes))
+                }
+            }
+        })
     }
 
     // `Visit` trait object cannot be used since method `visit` has generic type parameters.
@@ -237,18 +531,25 @@ impl TableExtractor {
         let mut visitor = TableExtractor::default();
         match table.visit(&mut visitor) {
             ControlFlow::Break(e) => Err(e),
-            ControlFlow::Continue(()) => Ok(Tables(helper::resolve_aliased_tables(
-                visitor.all_tables,
-                visitor.original_tables,
-            ))),
+            ControlFlow::Continue(()) => Ok(visitor.into_tables()),
         }
     }
+
+    /// Tables found so far with aliases resolved.
+    /// Useful when the extractor is driven by an external traversal such as [`VisitorSet`](crate::VisitorSet).
+    pub fn into_tables(self) -> Tables {
+        Tables(helper::resolve_aliased_tables(
+            self.all_tables,
+            self.original_tables,
+        ))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::test_utils::all_dialects;
+    use sqlparser::dialect::PostgreSqlDialect;
 
     fn assert_table_extraction(
         sql: &str,
@@ -683,4 +984,221 @@ mod tests {
         }]))];
         assert_table_extraction(sql, expected, all_dialects());
     }
+
+    #[test]
+    fn test_table_reference_from_str() {
+        assert_eq!(
+            "c1.s1.t1 AS a".parse::<TableReference>(),
+            Ok(TableReference {
+                catalog: Some("c1".into()),
+                schema: Some("s1".into()),
+                name: "t1".into(),
+                alias: Some("a".into()),
+            })
+        );
+        assert_eq!(
+            "`s 1`.\"t\"\"1\" a".parse::<TableReference>(),
+            Ok(TableReference {
+                catalog: None,
+                schema: Some(Ident::with_quote('`', "s 1")),
+                name: Ident::with_quote('"', "t\"1"),
+                alias: Some("a".into()),
+            })
+        );
+        for s in ["", "t1 AS", "t1 AS a b", "t1 AS a (x)", "a.b.c.d", "t1; t2"] {
+            assert!(s.parse::<TableReference>().is_err(), "Failed for: {s}");
+        }
+    }
+
+    #[test]
+    fn test_table_reference_display_round_trip() {
+        for s in [
+            "t1",
+            "s1.t1",
+            "c1.s1.t1 AS a",
+            "\"S 1\".`t``1` AS \"A\"",
+            "[dbo].[User Table] AS [u]",
+        ] {
+            let table = s.parse::<TableReference>().unwrap();
+            assert_eq!(table.to_string(), s);
+            assert_eq!(table.to_string().parse::<TableReference>(), Ok(table));
+        }
+    }
+
+    #[test]
+    fn test_table_reference_display_round_trip_of_extracted_tables() {
+        for dialect in all_dialects() {
+            let (open, close) = match IdentifierQuotes::for_dialect(dialect.as_ref()).quote() {
+                '[' => ('[', ']'),
+                quote => (quote, quote),
+            };
+            let sql = format!(
+                "SELECT a FROM {open}dbo{close}.{open}User Table{close} AS {open}u{close} JOIN t2 ON u.a = t2.a"
+            );
+            let tables = TableExtractor::extract(dialect.as_ref(), &sql).unwrap();
+            let tables = tables[0].as_ref().unwrap();
+            assert_eq!(tables.0.len(), 2, "Failed for dialect: {dialect:?}");
+            for table in &tables.0 {
+                assert_eq!(
+                    table.to_string().parse::<TableReference>().as_ref(),
+                    Ok(table),
+                    "Failed for dialect: {dialect:?}"
+                );
+            }
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_table_reference_serde() {
+        let table = "s1.\"T 1\" AS a".parse::<TableReference>().unwrap();
+        let json = serde_json::to_value(&table).unwrap();
+        assert_eq!(
+            json["name"],
+            serde_json::json!({ "value": "T 1", "quote_style": '"' })
+        );
+        assert_eq!(
+            serde_json::from_value::<TableReference>(json).unwrap(),
+            table
+        );
+        assert_eq!(
+            serde_json::from_str::<TableReference>("\"s1.\\\"T 1\\\" AS a\"").unwrap(),
+            table
+        );
+        assert!(serde_json::from_str::<TableReference>("\"a.b.c.d\"").is_err());
+    }
+
+    #[test]
+    fn test_is_same_table() {
+        let table = |name: &str| name.parse::<TableReference>().unwrap();
+        for dialect in all_dialects() {
+            let dialect = dialect.as_ref();
+            assert!(
+                table("users").is_same_table(&table("users AS u"), dialect),
+                "Failed for dialect: {dialect:?}"
+            );
+            assert!(
+                !table("s1.users").is_same_table(&table("users"), dialect),
+                "Failed for dialect: {dialect:?}"
+            );
+            let normalized = table("S1.\"User Table\" AS U").normalized(dialect);
+            assert_eq!(
+                normalized.normalized(dialect),
+                normalized,
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+        let postgres = PostgreSqlDialect {};
+        assert!(table("Users").is_same_table(&table("users"), &postgres));
+        assert!(!table("\"Users\"").is_same_table(&table("users"), &postgres));
+        assert!(table("`users`").is_same_table(&table("\"users\""), &postgres));
+        let snowflake = SnowflakeDialect {};
+        assert!(table("users").is_same_table(&table("\"USERS\""), &snowflake));
+        assert!(!table("users").is_same_table(&table("\"users\""), &snowflake));
+        let mysql = MySqlDialect {};
+        assert!(!table("Users").is_same_table(&table("users"), &mysql));
+        assert!(table("`users`").is_same_table(&table("users"), &mysql));
+        let mssql = MsSqlDialect {};
+        assert!(table("\"Users\"").is_same_table(&table("users"), &mssql));
+        assert_eq!(
+            table("\"User Table\"").normalized(&mssql).to_string(),
+            "[user table]"
+        );
+        assert_eq!(
+            table("\"user\"").normalized(&postgres).to_string(),
+            "\"user\""
+        );
+    }
+
+    #[test]
+    fn test_normalized_table_reference_round_trip() {
+        let table = |name: &str| name.parse::<TableReference>().unwrap();
+        for dialect in all_dialects() {
+            let normalized = table("S1.\"User Table\" AS \"Select\"").normalized(dialect.as_ref());
+            assert_eq!(
+                normalized.to_string().parse::<TableReference>(),
+                Ok(normalized),
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_extract_with_options() {
+        let sql = "SELECT * FROM t2 JOIN t1 ON t2.id = t1.id WHERE t2.a IN (SELECT a FROM t1 AS x JOIN t1)";
+        let table = |name: &str| name.parse::<TableReference>().unwrap();
+        for dialect in all_dialects() {
+            let result = TableExtractor::extract_with_options(
+                dialect.as_ref(),
+                sql,
+                ExtractorOptions::new().with_deduplicate(true),
+            )
+            .unwrap();
+            assert_eq!(
+                result,
+                vec![Ok(Tables(vec![table("t2"), table("t1"), table("t1 AS x")]))],
+                "Failed for dialect: {dialect:?}"
+            );
+            let result = TableExtractor::extract_with_options(
+                dialect.as_ref(),
+                sql,
+                ExtractorOptions::new()
+                    .with_deduplicate(true)
+                    .with_sort(true),
+            )
+            .unwrap();
+            assert_eq!(
+                result,
+                vec![Ok(Tables(vec![table("t1"), table("t1 AS x"), table("t2")]))],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_extract_with_default_schema_skips_ctes() {
+        let sql = "WITH c AS (SELECT a FROM t1) SELECT * FROM c JOIN t2 AS x ON c.a = x.a";
+        let table = |name: &str| name.parse::<TableReference>().unwrap();
+        for dialect in all_dialects() {
+            let result = TableExtractor::extract_with_options(
+                dialect.as_ref(),
+                sql,
+                ExtractorOptions::new().with_default_schema("s"),
+            )
+            .unwrap();
+            assert_eq!(
+                result,
+                vec![Ok(Tables(vec![
+                    table("s.t1"),
+                    table("c"),
+                    table("s.t2 AS x")
+                ]))],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_extract_with_default_schema_qualifies_tables_shadowed_by_ctes() {
+        let sql = "WITH t AS (SELECT a FROM t), u AS (SELECT a FROM T) SELECT * FROM U JOIN t ON U.a = t.a";
+        let table = |name: &str| name.parse::<TableReference>().unwrap();
+        for dialect in all_dialects() {
+            let result = TableExtractor::extract_with_options(
+                dialect.as_ref(),
+                sql,
+                ExtractorOptions::new().with_default_schema("s"),
+            )
+            .unwrap();
+            assert_eq!(
+                result,
+                vec![Ok(Tables(vec![
+                    table("s.t"),
+                    table("T"),
+                    table("U"),
+                    table("t")
+                ]))],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
 }
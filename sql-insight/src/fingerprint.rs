@@ -0,0 +1,158 @@
+//! A Fingerprinter that identifies statements differing only in literal values with a stable hash,
+//! similar to `pt-fingerprint` or the `queryid` of `pg_stat_statements`.
+//!
+//! See [`fingerprint`](crate::fingerprint()) as the entry point for fingerprinting SQL.
+
+use core::fmt;
+
+use crate::error::Error;
+use crate::lossy::normalize_lossy;
+use crate::normalizer::NormalizerOptions;
+use sqlparser::dialect::Dialect;
+
+/// Convenience function to fingerprint SQL.
+/// Each statement is normalized with [`normalize`](crate::normalize()) and hashed,
+/// so statements differing only in literal values, whitespace or comments have the same fingerprint.
+/// A statement failing to parse becomes an error of its own, and the remaining statements are still fingerprinted.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+///
+/// let dialect = GenericDialect {};
+/// let sql = "SELECT a FROM t1 WHERE b = 1; select a from t1 -- comment\n where b = 'x'";
+/// let result = sql_insight::fingerprint(&dialect, sql);
+/// let fingerprints = result.into_iter().map(Result::unwrap).collect::<Vec<_>>();
+/// assert_eq!(fingerprints[0], fingerprints[1]);
+/// assert_eq!(fingerprints[0].normalized, "SELECT a FROM t1 WHERE b = ?");
+/// assert_eq!(fingerprints[0].hash.len(), 16);
+/// ```
+pub fn fingerprint(dialect: &dyn Dialect, sql: &str) -> Vec<Result<Fingerprint, Error>> {
+    fingerprint_with_options(dialect, sql, NormalizerOptions::new())
+}
+
+/// Convenience function to fingerprint SQL with options of normalization.
+/// For example, unifying IN lists gives statements differing only in the number of elements the same fingerprint.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+/// use sql_insight::NormalizerOptions;
+///
+/// let dialect = GenericDialect {};
+/// let sql = "SELECT a FROM t1 WHERE b IN (1, 2); SELECT a FROM t1 WHERE b IN (3, 4, 5)";
+/// let result = sql_insight::fingerprint_with_options(&dialect, sql, NormalizerOptions::new().with_unify_in_list(true));
+/// assert_eq!(result[0], result[1]);
+/// ```
+pub fn fingerprint_with_options(
+    dialect: &dyn Dialect,
+    sql: &str,
+    options: NormalizerOptions,
+) -> Vec<Result<Fingerprint, Error>> {
+    normalize_lossy(dialect, sql, options)
+        .into_iter()
+        .map(|entry| entry.result.map(Fingerprint::from_normalized))
+        .collect()
+}
+
+/// [`Fingerprint`] represents the hash of a normalized statement together with the normalized statement.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Fingerprint {
+    /// Hexadecimal 64-bit FNV-1a hash of the normalized statement, stable across platforms and releases.
+    pub hash: String,
+    /// The normalized statement.
+    pub normalized: String,
+}
+
+impl Fingerprint {
+    /// Fingerprint of a statement already normalized.
+    pub fn from_normalized(normalized: String) -> Self {
+        Self {
+            hash: hash_normalized(&normalized),
+            normalized,
+        }
+    }
+}
+
+impl fmt::Display for Fingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.hash, self.normalized)
+    }
+}
+
+/// Hash of a normalized statement.
+pub(crate) fn hash_normalized(normalized: &str) -> String {
+    format!("{:016x}", fnv1a(normalized.as_bytes()))
+}
+
+/// 64-bit FNV-1a hash, which is stable across platforms and releases unlike the hasher of the standard library.
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+
+    #[test]
+    fn test_fingerprint() {
+        let sql = "SELECT a FROM t1 WHERE b = 1;\nSELECT  a FROM t1 WHERE b = 'x';\nSELECT a FROM t1 WHERE c = 1";
+        for dialect in all_dialects() {
+            let result = fingerprint(dialect.as_ref(), sql);
+            assert_eq!(result.len(), 3, "Failed for dialect: {dialect:?}");
+            assert_eq!(
+                result[0],
+                Ok(Fingerprint {
+                    hash: format!("{:016x}", fnv1a(b"SELECT a FROM t1 WHERE b = ?")),
+                    normalized: "SELECT a FROM t1 WHERE b = ?".into(),
+                }),
+                "Failed for dialect: {dialect:?}"
+            );
+            assert_eq!(result[0], result[1], "Failed for dialect: {dialect:?}");
+            assert_ne!(result[0], result[2], "Failed for dialect: {dialect:?}");
+        }
+    }
+
+    #[test]
+    fn test_fingerprint_with_options() {
+        let sql = "INSERT INTO t1 (a) VALUES (1); INSERT INTO t1 (a) VALUES (2), (3)";
+        for dialect in all_dialects() {
+            let result = fingerprint(dialect.as_ref(), sql);
+            assert_ne!(result[0], result[1], "Failed for dialect: {dialect:?}");
+            let result = fingerprint_with_options(
+                dialect.as_ref(),
+                sql,
+                NormalizerOptions::new().with_unify_values(true),
+            );
+            assert_eq!(result[0], result[1], "Failed for dialect: {dialect:?}");
+        }
+    }
+
+    #[test]
+    fn test_fingerprint_with_parse_error() {
+        for dialect in all_dialects() {
+            let result = fingerprint(dialect.as_ref(), "SELEC a FROM t1; DELETE FROM t2");
+            assert!(
+                matches!(result[0], Err(Error::ParserError(_))),
+                "Failed for dialect: {dialect:?}"
+            );
+            assert_eq!(
+                result[1],
+                Ok(Fingerprint::from_normalized("DELETE FROM t2".into())),
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_fnv1a() {
+        assert_eq!(fnv1a(b""), 0xcbf29ce484222325);
+        assert_eq!(fnv1a(b"a"), 0xaf63dc4c8601ec8c);
+    }
+}
@@ -2,9 +2,17 @@
 //!
 //! See [`format`](crate::format()) as the entry point for formatting SQL.
 
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
+
 use crate::error::Error;
+use crate::instrument;
+use crate::span::Location;
+use sqlparser::ast::Statement;
 use sqlparser::dialect::Dialect;
-use sqlparser::parser::Parser;
+use sqlparser::keywords::Keyword;
+use sqlparser::tokenizer::{Token, TokenWithLocation, Tokenizer};
 
 /// Convenience function to format SQL.
 ///
@@ -22,6 +30,119 @@ pub fn format(dialect: &dyn Dialect, sql: &str) -> Result<Vec<String>, Error> {
     Formatter::format(dialect, sql)
 }
 
+/// Convenience function to format SQL with options.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+/// use sql_insight::{FormatterOptions, KeywordCase};
+///
+/// let dialect = GenericDialect {};
+/// let sql = "select a, b from t1 join t2 on t1.id = t2.id where c in (select c from t3) and d = 1";
+/// let options = FormatterOptions::new().with_multi_line(true).with_keyword_case(KeywordCase::Lower);
+/// let result = sql_insight::format_with_options(&dialect, sql, options).unwrap();
+/// assert_eq!(
+///     result[0],
+///     "select a, b\nfrom t1\njoin t2 on t1.id = t2.id\nwhere c in (\n  select c\n  from t3\n) and d = 1"
+/// );
+/// ```
+pub fn format_with_options(
+    dialect: &dyn Dialect,
+    sql: &str,
+    options: FormatterOptions,
+) -> Result<Vec<String>, Error> {
+    Formatter::format_with_options(dialect, sql, options)
+}
+
+/// Options for formatting SQL.
+#[derive(Clone, Debug)]
+pub struct FormatterOptions {
+    /// Break statements into lines, starting each clause such as `FROM` and `WHERE` on a line of its own
+    /// and indenting subqueries. Otherwise, each statement is formatted into a single line.
+    pub multi_line: bool,
+    /// Number of spaces of each level of indentation in multi-line output.
+    pub indent_width: usize,
+    pub keyword_case: KeywordCase,
+    /// Width beyond which lines of multi-line output are wrapped at commas and before `AND` and `OR`
+    /// where possible. Wrapped lines are indented one level deeper than the clause.
+    pub max_line_width: usize,
+}
+
+impl Default for FormatterOptions {
+    fn default() -> Self {
+        Self {
+            multi_line: false,
+            indent_width: 2,
+            keyword_case: KeywordCase::default(),
+            max_line_width: 80,
+        }
+    }
+}
+
+impl FormatterOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_multi_line(mut self, multi_line: bool) -> Self {
+        self.multi_line = multi_line;
+        self
+    }
+
+    pub fn with_indent_width(mut self, indent_width: usize) -> Self {
+        self.indent_width = indent_width;
+        self
+    }
+
+    pub fn with_keyword_case(mut self, keyword_case: KeywordCase) -> Self {
+        self.keyword_case = keyword_case;
+        self
+    }
+
+    pub fn with_max_line_width(mut self, max_line_width: usize) -> Self {
+        self.max_line_width = max_line_width;
+        self
+    }
+}
+
+/// [`KeywordCase`] represents the casing of keywords in formatted SQL.
+/// Only keywords are cased, while identifiers spelled like keywords, e.g. `name` or `Status`, are never changed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum KeywordCase {
+    #[default]
+    Upper,
+    Lower,
+    /// The casing keywords are written with in the input, or upper case for keywords not in the input.
+    Preserve,
+}
+
+impl fmt::Display for KeywordCase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            KeywordCase::Upper => "upper",
+            KeywordCase::Lower => "lower",
+            KeywordCase::Preserve => "preserve",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for KeywordCase {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "upper" => Ok(KeywordCase::Upper),
+            "lower" => Ok(KeywordCase::Lower),
+            "preserve" => Ok(KeywordCase::Preserve),
+            _ => Err(Error::ArgumentError(format!(
+                "Keyword case not found: {s}. Available keyword cases: upper, lower, preserve"
+            ))),
+        }
+    }
+}
+
 /// Formatter for SQL.
 #[derive(Debug, Default)]
 pub struct Formatter;
@@ -29,18 +150,384 @@ pub struct Formatter;
 impl Formatter {
     /// Format SQL.
     pub fn format(dialect: &dyn Dialect, sql: &str) -> Result<Vec<String>, Error> {
-        let statements = Parser::parse_sql(dialect, sql)?;
+        let statements = instrument::parse_sql(dialect, sql)?;
         Ok(statements
             .into_iter()
-            .map(|statement| statement.to_string())
+            .map(|statement| instrument::analyze("format", || statement.to_string()))
             .collect::<Vec<String>>())
     }
+
+    /// Format SQL with options.
+    pub fn format_with_options(
+        dialect: &dyn Dialect,
+        sql: &str,
+        options: FormatterOptions,
+    ) -> Result<Vec<String>, Error> {
+        let statements = instrument::parse_sql(dialect, sql)?;
+        Ok(Self::format_statements(dialect, sql, &statements, &options))
+    }
+
+    /// Format statements parsed from `sql` with options. `sql` is consulted for the spelling of keywords.
+    pub(crate) fn format_statements(
+        dialect: &dyn Dialect,
+        sql: &str,
+        statements: &[Statement],
+        options: &FormatterOptions,
+    ) -> Vec<String> {
+        let spellings = match options.keyword_case {
+            KeywordCase::Preserve => {
+                let identifiers = statements.iter().flat_map(unquoted_identifiers).collect();
+                keyword_spellings(dialect, sql, &identifiers)
+            }
+            _ => HashMap::new(),
+        };
+        statements
+            .iter()
+            .map(|statement| {
+                instrument::analyze("format", || {
+                    let formatted = statement.to_string();
+                    if !options.multi_line && options.keyword_case == KeywordCase::Upper {
+                        return formatted;
+                    }
+                    let identifiers = match options.keyword_case {
+                        KeywordCase::Upper => HashSet::new(),
+                        _ => unquoted_identifiers(statement),
+                    };
+                    // The formatted statement always tokenizes, but fall back to it just in case.
+                    match Layout::new(dialect, &formatted, options, &spellings, &identifiers) {
+                        Some(layout) => layout.render(),
+                        None => formatted,
+                    }
+                })
+            })
+            .collect::<Vec<String>>()
+    }
+}
+
+/// Spelling of each keyword as first written in SQL, keyed by the keyword in upper case.
+/// Words among `identifiers` are not keywords.
+fn keyword_spellings(
+    dialect: &dyn Dialect,
+    sql: &str,
+    identifiers: &HashSet<String>,
+) -> HashMap<String, String> {
+    let mut spellings = HashMap::new();
+    for token in Tokenizer::new(dialect, sql).tokenize().unwrap_or_default() {
+        if let Some(word) = keyword_word(&token, identifiers) {
+            spellings
+                .entry(word.to_uppercase())
+                .or_insert_with(|| word.to_string());
+        }
+    }
+    spellings
+}
+
+/// The text of an unquoted keyword, unless it is one of `identifiers` of the statement, written as debug strings.
+fn keyword_word<'a>(token: &'a Token, identifiers: &HashSet<String>) -> Option<&'a str> {
+    match token {
+        Token::Word(word)
+            if word.quote_style.is_none()
+                && word.keyword != Keyword::NoKeyword
+                && !identifiers.contains(&format!("{:?}", word.value)) =>
+        {
+            Some(&word.value)
+        }
+        _ => None,
+    }
+}
+
+/// Values of the unquoted identifiers of a statement, such as names of tables, columns, aliases and functions,
+/// as debug strings.
+///
+/// The AST has no visitor hook for identifiers, so they are read from its debug representation,
+/// where each is written as `Ident { value: "...", quote_style: None }`.
+fn unquoted_identifiers(statement: &Statement) -> HashSet<String> {
+    const PREFIX: &str = "Ident { value: ";
+    const SUFFIX: &str = ", quote_style: None }";
+    let debug = format!("{statement:?}");
+    let mut identifiers = HashSet::new();
+    let mut rest = debug.as_str();
+    while let Some(start) = rest.find(PREFIX) {
+        rest = &rest[start + PREFIX.len()..];
+        // The value is a debug string, ending at the first quote not escaped by a backslash.
+        let mut escaped = false;
+        let end = rest.char_indices().skip(1).find_map(|(i, c)| match c {
+            _ if escaped => {
+                escaped = false;
+                None
+            }
+            '\\' => {
+                escaped = true;
+                None
+            }
+            '"' => Some(i + 1),
+            _ => None,
+        });
+        let Some(end) = end else {
+            break;
+        };
+        if rest[end..].starts_with(SUFFIX) {
+            identifiers.insert(rest[..end].to_string());
+        }
+        rest = &rest[end..];
+    }
+    identifiers
+}
+
+fn keyword(token: &Token) -> Option<Keyword> {
+    match token {
+        Token::Word(word) if word.quote_style.is_none() => Some(word.keyword),
+        _ => None,
+    }
+}
+
+/// Where a line may or must break before a token of multi-line output, with the indentation level of the new line.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Break {
+    None,
+    /// A clause, or a subquery opening or closing, starts a new line.
+    Line(usize),
+    /// The line wraps here if it would be too long otherwise.
+    Wrap(usize),
+}
+
+/// A token of a formatted statement with how it is laid out.
+struct LaidToken {
+    text: String,
+    space_before: bool,
+    line_break: Break,
+}
+
+/// Layout of a formatted statement, which is a single line of tokens separated by single spaces or nothing.
+struct Layout<'a> {
+    tokens: Vec<LaidToken>,
+    options: &'a FormatterOptions,
+}
+
+impl<'a> Layout<'a> {
+    fn new(
+        dialect: &dyn Dialect,
+        formatted: &str,
+        options: &'a FormatterOptions,
+        spellings: &HashMap<String, String>,
+        identifiers: &HashSet<String>,
+    ) -> Option<Self> {
+        let tokens = Tokenizer::new(dialect, formatted)
+            .tokenize_with_location()
+            .ok()?
+            .into_iter()
+            .filter(|token| token.token != Token::EOF)
+            .collect::<Vec<_>>();
+        let offsets = token_offsets(formatted, &tokens);
+        let mut words = vec![];
+        let mut space_before = false;
+        for (i, TokenWithLocation { token, .. }) in tokens.iter().enumerate() {
+            if let Token::Whitespace(_) = token {
+                space_before = true;
+                continue;
+            }
+            let end = offsets.get(i + 1).copied().unwrap_or(formatted.len());
+            words.push((token, &formatted[offsets[i]..end], space_before));
+            space_before = false;
+        }
+
+        let first = words.first().and_then(|(token, ..)| keyword(token));
+        let mut laid = Vec::with_capacity(words.len());
+        // Whether each open parenthesis encloses a subquery.
+        let mut parens: Vec<bool> = vec![];
+        let mut level = 0;
+        let mut in_between = false;
+        let mut wrap_next = false;
+        for (i, (token, text, space_before)) in words.iter().enumerate() {
+            let previous = i.checked_sub(1).map(|i| words[i].0);
+            let next = words.get(i + 1).map(|(token, ..)| *token);
+            let clause_level = parens.last().is_none_or(|subquery| *subquery);
+            let mut line_break = if std::mem::take(&mut wrap_next) {
+                Break::Wrap(level + 1)
+            } else {
+                Break::None
+            };
+            match token {
+                Token::LParen => {
+                    let subquery = matches!(
+                        next.and_then(keyword),
+                        Some(Keyword::SELECT | Keyword::WITH)
+                    );
+                    parens.push(subquery);
+                    if subquery {
+                        level += 1;
+                    }
+                }
+                Token::RParen => {
+                    let subquery = parens.pop() == Some(true);
+                    if subquery {
+                        level -= 1;
+                        line_break = Break::Line(level);
+                    }
+                }
+                Token::Comma if clause_level => wrap_next = true,
+                _ if clause_level && i > 0 => match keyword(token) {
+                    Some(Keyword::AND | Keyword::OR) if !in_between => {
+                        line_break = Break::Wrap(level + 1)
+                    }
+                    Some(Keyword::AND) => in_between = false,
+                    Some(Keyword::BETWEEN) => in_between = true,
+                    Some(keyword) if starts_clause(keyword, first, previous, next) => {
+                        in_between = false;
+                        line_break = Break::Line(level);
+                    }
+                    _ => {}
+                },
+                _ => {}
+            }
+            let text = match keyword_word(token, identifiers) {
+                Some(word) => match options.keyword_case {
+                    KeywordCase::Upper => word.to_string(),
+                    KeywordCase::Lower => word.to_lowercase(),
+                    KeywordCase::Preserve => spellings
+                        .get(&word.to_uppercase())
+                        .cloned()
+                        .unwrap_or_else(|| word.to_string()),
+                },
+                None => text.to_string(),
+            };
+            laid.push(LaidToken {
+                text,
+                space_before: *space_before,
+                line_break: if options.multi_line {
+                    line_break
+                } else {
+                    Break::None
+                },
+            });
+        }
+        Some(Self {
+            tokens: laid,
+            options,
+        })
+    }
+
+    fn render(&self) -> String {
+        let mut rendered = String::new();
+        let mut line_width = 0;
+        for (i, token) in self.tokens.iter().enumerate() {
+            let new_line = match token.line_break {
+                Break::Line(level) if i > 0 => Some(level),
+                Break::Wrap(level)
+                    if line_width + 1 + self.segment_width(i) > self.options.max_line_width =>
+                {
+                    Some(level)
+                }
+                _ => None,
+            };
+            if let Some(level) = new_line {
+                let indent = " ".repeat(level * self.options.indent_width);
+                rendered.push('\n');
+                rendered.push_str(&indent);
+                line_width = indent.len();
+            } else if token.space_before {
+                rendered.push(' ');
+                line_width += 1;
+            }
+            rendered.push_str(&token.text);
+            line_width += token.text.chars().count();
+        }
+        rendered
+    }
+
+    /// Width of the tokens from `start` up to the next possible line break.
+    fn segment_width(&self, start: usize) -> usize {
+        let mut width = self.tokens[start].text.chars().count();
+        for token in self.tokens[start + 1..]
+            .iter()
+            .take_while(|token| token.line_break == Break::None)
+        {
+            width += token.text.chars().count() + usize::from(token.space_before);
+        }
+        width
+    }
+}
+
+/// Whether `keyword` starts a clause of a query or a statement starting with `first`.
+fn starts_clause(
+    keyword: Keyword,
+    first: Option<Keyword>,
+    previous: Option<&Token>,
+    next: Option<&Token>,
+) -> bool {
+    let previous_keyword = previous.and_then(self::keyword);
+    let next_keyword = next.and_then(self::keyword);
+    match keyword {
+        Keyword::SELECT
+        | Keyword::WHERE
+        | Keyword::HAVING
+        | Keyword::QUALIFY
+        | Keyword::LIMIT
+        | Keyword::OFFSET
+        | Keyword::UNION
+        | Keyword::EXCEPT
+        | Keyword::INTERSECT
+        | Keyword::RETURNING => true,
+        // `DELETE FROM` and `IS DISTINCT FROM` stay on a line.
+        Keyword::FROM => !matches!(previous_keyword, Some(Keyword::DELETE | Keyword::DISTINCT)),
+        Keyword::GROUP | Keyword::ORDER => next_keyword == Some(Keyword::BY),
+        // Not `VALUES(a)` of `ON DUPLICATE KEY UPDATE`.
+        Keyword::VALUES => {
+            first == Some(Keyword::INSERT)
+                && matches!(previous, Some(Token::RParen | Token::Word(_)))
+        }
+        Keyword::SET => first == Some(Keyword::UPDATE),
+        Keyword::JOIN => !matches!(
+            previous_keyword,
+            Some(
+                Keyword::INNER
+                    | Keyword::LEFT
+                    | Keyword::RIGHT
+                    | Keyword::FULL
+                    | Keyword::OUTER
+                    | Keyword::CROSS
+                    | Keyword::NATURAL
+                    | Keyword::SEMI
+                    | Keyword::ANTI
+            )
+        ),
+        // Not functions such as `LEFT(a, 1)`.
+        Keyword::INNER
+        | Keyword::LEFT
+        | Keyword::RIGHT
+        | Keyword::FULL
+        | Keyword::CROSS
+        | Keyword::NATURAL => matches!(
+            next_keyword,
+            Some(Keyword::JOIN | Keyword::OUTER | Keyword::SEMI | Keyword::ANTI)
+        ),
+        _ => false,
+    }
+}
+
+/// Byte offset of each token in `text`.
+fn token_offsets(text: &str, tokens: &[TokenWithLocation]) -> Vec<usize> {
+    let mut offsets = Vec::with_capacity(tokens.len());
+    let mut chars = text.char_indices().peekable();
+    let mut location = Location::new(1, 1);
+    for token in tokens {
+        let target = Location::from(token.location);
+        while location < target {
+            match chars.next() {
+                Some((_, c)) => location = location.advance(c.encode_utf8(&mut [0; 4])),
+                None => break,
+            }
+        }
+        offsets.push(chars.peek().map_or(text.len(), |(offset, _)| *offset));
+    }
+    offsets
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::test_utils::all_dialects;
+    use sqlparser::dialect::{ClickHouseDialect, GenericDialect};
 
     fn assert_format(sql: &str, expected: Vec<String>, dialects: Vec<Box<dyn Dialect>>) {
         for dialect in dialects {
@@ -70,6 +557,125 @@ mod tests {
         assert_format(sql, expected, all_dialects());
     }
 
+    fn assert_format_with_options(
+        sql: &str,
+        options: FormatterOptions,
+        expected: Vec<String>,
+        dialects: Vec<Box<dyn Dialect>>,
+    ) {
+        for dialect in dialects {
+            let result =
+                Formatter::format_with_options(dialect.as_ref(), sql, options.clone()).unwrap();
+            assert_eq!(result, expected, "Failed for dialect: {dialect:?}")
+        }
+    }
+
+    #[test]
+    fn test_default_options() {
+        let sql = "SELECT a from t1   WHERE b=1; DELETE \n FROM t3   WHERE c = 3";
+        for dialect in all_dialects() {
+            assert_eq!(
+                Formatter::format_with_options(dialect.as_ref(), sql, FormatterOptions::new()),
+                Formatter::format(dialect.as_ref(), sql),
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_multi_line() {
+        let sql = "WITH x AS (SELECT a, b FROM t1 WHERE c BETWEEN 1 AND 2) \
+            SELECT x.a, COUNT(*) FROM x LEFT OUTER JOIN t2 ON x.b = t2.b \
+            WHERE x.a IN (SELECT a FROM t3) AND t2.c IS DISTINCT FROM 1 \
+            GROUP BY x.a HAVING COUNT(*) > 1 ORDER BY x.a LIMIT 10";
+        let expected = vec![[
+            "WITH x AS (",
+            "    SELECT a, b",
+            "    FROM t1",
+            "    WHERE c BETWEEN 1 AND 2",
+            ")",
+            "SELECT x.a, COUNT(*)",
+            "FROM x",
+            "LEFT JOIN t2 ON x.b = t2.b",
+            "WHERE x.a IN (",
+            "    SELECT a",
+            "    FROM t3",
+            ") AND t2.c IS DISTINCT FROM 1",
+            "GROUP BY x.a",
+            "HAVING COUNT(*) > 1",
+            "ORDER BY x.a",
+            "LIMIT 10",
+        ]
+        .join("\n")];
+        let options = FormatterOptions::new()
+            .with_multi_line(true)
+            .with_indent_width(4);
+        assert_format_with_options(sql, options, expected, all_dialects());
+    }
+
+    #[test]
+    fn test_multi_line_dml() {
+        let sql = "INSERT INTO t1 (a, b) VALUES (1, 'x'), (2, 'y'); UPDATE t1 SET a = 1 WHERE b = 2; DELETE FROM t1 WHERE a = 1";
+        let expected = vec![
+            "INSERT INTO t1 (a, b)\nVALUES (1, 'x'), (2, 'y')".into(),
+            "UPDATE t1\nSET a = 1\nWHERE b = 2".into(),
+            "DELETE FROM t1\nWHERE a = 1".into(),
+        ];
+        let options = FormatterOptions::new().with_multi_line(true);
+        assert_format_with_options(sql, options, expected, all_dialects());
+    }
+
+    #[test]
+    fn test_max_line_width() {
+        let sql = "SELECT aaaa, bbbb, cccc, dddd FROM t1 WHERE aaaa = 1 AND bbbb = 2 OR cccc = 3";
+        let expected = vec![[
+            "SELECT aaaa, bbbb,",
+            "  cccc, dddd",
+            "FROM t1",
+            "WHERE aaaa = 1",
+            "  AND bbbb = 2",
+            "  OR cccc = 3",
+        ]
+        .join("\n")];
+        let options = FormatterOptions::new()
+            .with_multi_line(true)
+            .with_max_line_width(18);
+        assert_format_with_options(sql, options, expected, all_dialects());
+    }
+
+    #[test]
+    fn test_keyword_case() {
+        let sql = "Select \"Select\", name FROM t1 where a = 'SELECT'";
+        assert_format_with_options(
+            sql,
+            FormatterOptions::new().with_keyword_case(KeywordCase::Lower),
+            vec!["select \"Select\", name from t1 where a = 'SELECT'".into()],
+            all_dialects(),
+        );
+        assert_format_with_options(
+            "Select a From t1 AS x where b = 1",
+            FormatterOptions::new().with_keyword_case(KeywordCase::Preserve),
+            vec!["Select a From t1 AS x where b = 1".into()],
+            all_dialects(),
+        );
+        assert_format_with_options(
+            "SELECT Name, Status, Type, count(*) FROM Events WHERE Value > 1 GROUP BY 1",
+            FormatterOptions::new().with_keyword_case(KeywordCase::Lower),
+            vec![
+                "select Name, Status, Type, count(*) from Events where Value > 1 group by 1".into(),
+            ],
+            vec![Box::new(ClickHouseDialect {}), Box::new(GenericDialect {})],
+        );
+        assert_format_with_options(
+            "select name, Status from t1 WHERE status = 1",
+            FormatterOptions::new().with_keyword_case(KeywordCase::Preserve),
+            vec!["select name, Status from t1 WHERE status = 1".into()],
+            all_dialects(),
+        );
+        assert_eq!("Lower".parse(), Ok(KeywordCase::Lower));
+        assert!("title".parse::<KeywordCase>().is_err());
+    }
+
     #[test]
     fn test_sql_with_comments() {
         let sql = "SELECT a FROM t1 WHERE b = 1; -- comment\nSELECT b FROM t2 WHERE c =  2  /* comment */";
@@ -0,0 +1,81 @@
+//! Instrumentation of parsing and analyses with `tracing` spans and events, enabled by the `tracing` feature.
+//! Without the feature, these are plain calls.
+//!
+//! - Parsing runs in a `parse` span with the dialect and the length of SQL, and emits a `DEBUG` event with
+//!   the number of parsed statements, or a `WARN` event with the error.
+//! - Analyzing a statement runs in an `analyze` span named by the analyzer, which subscribers can time,
+//!   and emits a `WARN` event with the error if it fails.
+
+use crate::error::Error;
+use sqlparser::ast::Statement;
+use sqlparser::dialect::Dialect;
+use sqlparser::parser::Parser;
+
+/// Parse SQL in a `parse` span.
+pub(crate) fn parse_sql(dialect: &dyn Dialect, sql: &str) -> Result<Vec<Statement>, Error> {
+    #[cfg(feature = "tracing")]
+    let _span = tracing::debug_span!("parse", dialect = ?dialect, sql_len = sql.len()).entered();
+    let result = Parser::parse_sql(dialect, sql).map_err(Error::from);
+    #[cfg(feature = "tracing")]
+    match &result {
+        Ok(statements) => tracing::debug!(statements = statements.len(), "parsed"),
+        Err(e) => tracing::warn!(error = %e, "failed to parse"),
+    }
+    result
+}
+
+/// Run an analysis of a statement in an `analyze` span of `analyzer`.
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+pub(crate) fn analyze<T>(analyzer: &'static str, f: impl FnOnce() -> T) -> T {
+    #[cfg(feature = "tracing")]
+    let _span = tracing::debug_span!("analyze", analyzer).entered();
+    f()
+}
+
+/// Run a fallible analysis of a statement in an `analyze` span of `analyzer`, reporting its error if any.
+pub(crate) fn try_analyze<T>(
+    analyzer: &'static str,
+    f: impl FnOnce() -> Result<T, Error>,
+) -> Result<T, Error> {
+    analyze(analyzer, || {
+        let result = f();
+        #[cfg(feature = "tracing")]
+        if let Err(e) = &result {
+            tracing::warn!(analyzer, error = %e, "failed to analyze");
+        }
+        result
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+
+    #[test]
+    fn test_parse_sql() {
+        for dialect in all_dialects() {
+            assert_eq!(
+                parse_sql(dialect.as_ref(), "SELECT a FROM t1").unwrap(),
+                Parser::parse_sql(dialect.as_ref(), "SELECT a FROM t1").unwrap(),
+                "Failed for dialect: {dialect:?}"
+            );
+            assert!(
+                matches!(
+                    parse_sql(dialect.as_ref(), "SELEC"),
+                    Err(Error::ParserError(_))
+                ),
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_try_analyze() {
+        assert_eq!(try_analyze("ok", || Ok(1)), Ok(1));
+        assert_eq!(
+            try_analyze::<()>("err", || Err(Error::AnalysisError("x".into()))),
+            Err(Error::AnalysisError("x".into()))
+        );
+    }
+}
@@ -4,10 +4,43 @@
 //!
 //! ## Main Functionalities
 //!
+//! - **Input Decoding**: Decode input bytes into SQL, stripping byte order marks, normalizing line endings and transcoding from Latin-1 or UTF-16. See the [`encoding`] module for more information.
+//! - **Statement Spans**: Attach the source span of each statement to per-statement results, to map them back to positions in large SQL files. See the [`span`] module for more information.
+//! - **Error Locations**: Locate errors at the statement or token causing them, with line and column spans and byte offsets, for editors and CI annotations. See [`error::locate_errors`] for more information.
+//! - **Statement Splitting**: Split SQL into statements with their spans without parsing, so a statement failing to parse can be isolated. See the [`splitter`] module for more information.
+//! - **Unified Analysis**: Parse SQL once and format, normalize and extract tables and CRUD tables from the same statements. See the [`analyzer`] module for more information.
+//! - **Raw Statements**: Parse SQL into statements paired with their original text and span, to run several analyses on the same parse. See [`parse_raw_statements`] for more information.
+//! - **Streaming Processing**: Parse SQL read from a reader statement by statement, buffering only the statement being read, for inputs too large to load into memory. See the [`stream`] module for more information.
+//! - **Parallel Processing**: Parse and analyze statements concurrently with `rayon` behind the `rayon` feature. See the `parallel` module for more information.
+//! - **Error-Recovering Analysis**: Analyze or normalize SQL statement by statement, recording statements failing to parse with their raw SQL instead of failing the whole input. See the [`lossy`] module for more information.
 //! - **SQL Formatting**: Format SQL queries into a standardized format. See the [`formatter`] module for more information.
 //! - **SQL Normalization**: Normalize SQL queries by abstracting literals. See the [`normalizer`] module for more information.
+//! - **SQL Anonymization**: Replace literal values with fake values or hashes, keeping queries valid for sharing without leaking personal data. See the [`anonymizer`] module for more information.
+//! - **Query Fingerprinting**: Identify statements differing only in literal values with a stable hash of their normalized form. See the [`fingerprint`](mod@fingerprint) module for more information.
+//! - **Prepared Statement Generation**: Turn literal values into numbered placeholders and typed parameters. See the [`prepared`] module for more information.
+//! - **Parameter Inference**: Infer the number and likely types of placeholders from their context for binding. See the [`param_inference`] module for more information.
 //! - **Table Extraction**: Extract tables within SQL queries. See the [`table_extractor`] module for more information.
 //! - **CRUD Table Extraction**: Extract CRUD tables from SQL queries. See the [`crud_table_extractor`] module for more information.
+//! - **Column Extraction**: Extract columns within SQL queries with the qualifiers they are written with. See the [`column_extractor`] module for more information.
+//! - **Function Extraction**: Extract function calls within SQL queries with their number of arguments and whether they are aggregate or window functions. See the [`function_extractor`] module for more information.
+//! - **Join Extraction**: Extract joins within SQL queries with the tables on each side, the join type and the join condition, to detect cross joins. See the [`join_extractor`] module for more information.
+//! - **Hint Extraction**: Extract MySQL's index hints with the tables they apply to and optimizer hint comments, e.g. to audit queries pinning indexes before dropping them. See the [`hint_extractor`] module for more information.
+//! - **Complexity Metrics**: Measure the complexity of statements, such as the number of joins, tables and predicates and the nesting depth of subqueries. See the [`complexity`] module for more information.
+//! - **Predicate Extraction**: Extract predicates of WHERE, HAVING and ON clauses as columns compared with literals, placeholders or other columns, e.g. to route queries by a shard key. See the [`predicate_extractor`] module for more information.
+//! - **DDL Object Extraction**: Extract tables, views, indexes, sequences and schemas created, altered or dropped by DDL, with the kind of each change such as added columns and dropped constraints. See the [`ddl_extractor`] module for more information.
+//! - **CTE Graph Extraction**: Extract the dependencies among CTEs and the tables each CTE reads. See the [`cte_extractor`] module for more information.
+//! - **SQL Rewriting**: Transform SQL queries with composable rewrites. See the [`rewriter`] module for more information.
+//! - **Workload Aggregation**: Aggregate table and column usage, join graphs, inferred schemas and access to sensitive columns, and cluster near-duplicate statements across many statements. See the [`aggregator`] module for more information.
+//! - **Access Mode Classification**: Classify statements as read-only, write, DDL or transaction control for routing and gating. See the [`access_mode`] module for more information.
+//! - **Statement Classification**: Classify statements by their kind, such as SELECT, INSERT, the kind of DDL or transaction control, without extracting tables. See the [`classifier`] module for more information.
+//! - **Dialect Compatibility Check**: Check whether SQL parses under each of several dialects, and compare analysis results across them. See the [`compatibility`] module for more information.
+//! - **DDL Ordering**: Order DDL statements by their dependencies and detect cycles. See the [`dependency`] module for more information.
+//! - **View Dependency Extraction**: Extract the views and tables created by `CREATE VIEW`, `CREATE MATERIALIZED VIEW` and `CREATE TABLE ... AS SELECT` with the tables they read from, to build a DAG of views. See [`extract_view_dependencies`] for more information.
+//! - **Round-Trip Verification**: Verify that formatted statements reparse into the same AST and that normalization is idempotent. See the [`roundtrip`] module for more information.
+//! - **Linting**: Check SQL queries against a set of rules. See the [`linter`] module for more information.
+//! - **Cross-Database Reference Detection**: Detect statements referencing tables of more than one catalog or schema. See the [`cross_database_detector`] module for more information.
+//! - **Destructive Change Detection**: Detect dropped tables and columns, narrowed column types, truncations and NOT NULL additions without defaults in migrations. See the [`destructive_change_detector`] module for more information.
+//! - **Suspicious Pattern Detection**: Detect always-true predicates, UNION SELECT probes and comment-terminated statements. See the [`suspicious_pattern_detector`] module for more information.
 //!
 //! ## Quick Start
 //!
@@ -21,17 +54,92 @@
 //! assert_eq!(normalized_sql, ["SELECT * FROM users WHERE id = 1"]);
 //! ```
 //!
+//! ## Features
+//!
+//! - `arrow`: Enables writing [`StatementRecord`]s of the [`export`] module as Arrow record batches or Parquet files.
+//! - `metrics`: Enables the [`metrics`] module, counting parsed statements, parse failures per dialect and latencies
+//!   of analyzers, and serving them at a Prometheus `/metrics` endpoint for services built on this crate.
+//! - `mmap`: Enables memory-mapping input files with `BatchInput::from_path` for processing large files in batch.
+//! - `rayon`: Enables the `parallel` module, extracting tables and normalizing with statements parsed and analyzed
+//!   concurrently on a `rayon` thread pool.
+//! - `serde`: Enables serialization of analysis results, including the typed reports in the [`report`] module
+//!   and SARIF output of lint diagnostics in the `linter::sarif` module.
+//! - `tracing`: Instruments parsing and each analysis of a statement with `tracing` spans and events,
+//!   so that timing and errors show up in the observability stack of embedders.
+//! - `sqlite`: Enables persisting digests of the [`digest`] module into SQLite with `SqliteDigestSink`.
+//!
 //! For more comprehensive examples and usage, refer to [crates.io](https://crates.io/crates/sql-insight) or the documentation of each module.
 
+pub mod access_mode;
+pub mod aggregator;
+pub mod analyzer;
+pub mod anonymizer;
+pub mod batch;
+pub mod cancellation;
+pub mod classifier;
+pub mod compatibility;
+pub mod complexity;
+pub mod dependency;
+pub mod detector;
+pub mod encoding;
 pub mod error;
+pub mod export;
 pub mod extractor;
+pub mod fingerprint;
 pub mod formatter;
+pub mod limits;
+pub mod linter;
+pub mod lossy;
+#[cfg(feature = "metrics")]
+pub mod metrics;
 pub mod normalizer;
+#[cfg(feature = "rayon")]
+pub mod parallel;
+pub mod param_inference;
+pub mod prepared;
+pub mod report;
+pub mod rewriter;
+pub mod roundtrip;
+pub mod span;
+pub mod splitter;
+pub mod stream;
+pub mod visitor;
+
+mod instrument;
+mod parsing;
 
+pub use access_mode::*;
+pub use aggregator::*;
+pub use analyzer::*;
+pub use anonymizer::*;
+pub use batch::*;
+pub use cancellation::*;
+pub use classifier::*;
+pub use compatibility::*;
+pub use complexity::*;
+pub use dependency::*;
+pub use detector::*;
+pub use encoding::*;
+pub use export::*;
 pub use extractor::*;
+pub use fingerprint::*;
 pub use formatter::*;
+pub use limits::*;
+pub use linter::*;
+pub use lossy::*;
+#[cfg(feature = "metrics")]
+pub use metrics::*;
 pub use normalizer::*;
+#[cfg(feature = "rayon")]
+pub use parallel::*;
+pub use param_inference::*;
+pub use prepared::*;
+pub use rewriter::*;
+pub use roundtrip::*;
+pub use splitter::*;
 pub use sqlparser;
+pub use stream::*;
+pub use visitor::*;
 
 #[doc(hidden)]
 // Internal module for testing. Made public for use in integration tests.
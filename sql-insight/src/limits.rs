@@ -0,0 +1,302 @@
+//! Limits that guard analysis against oversized or pathological input.
+//!
+//! See [`Limits`] for details.
+
+use std::ops::ControlFlow;
+
+use crate::error::Error;
+use crate::parsing;
+use sqlparser::ast::{Expr, Query, Statement, Visit, Visitor};
+use sqlparser::dialect::Dialect;
+use sqlparser::parser::{Parser, ParserError};
+
+/// [`Limits`] bounds the size and complexity of the input accepted for analysis.
+/// Violations are reported as [`Error::LimitExceeded`]. Every limit is unbounded unless set.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::error::Error;
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+/// use sql_insight::Limits;
+///
+/// let dialect = GenericDialect {};
+/// let limits = Limits::new().with_max_statements(1);
+/// assert_eq!(limits.parse(&dialect, "SELECT a FROM t1").unwrap().len(), 1);
+/// assert!(matches!(
+///     limits.parse(&dialect, "SELECT a FROM t1; SELECT b FROM t2"),
+///     Err(Error::LimitExceeded(_))
+/// ));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Limits {
+    /// Maximum size of the input in bytes. Checked before parsing.
+    pub max_input_bytes: Option<usize>,
+    /// Maximum number of statements. Checked while parsing, so the rest of the input is not parsed once exceeded.
+    pub max_statements: Option<usize>,
+    /// Maximum nesting depth of expressions and queries. Passed to the parser as its recursion limit,
+    /// and checked while visiting statements by [`Limits::check_statement`].
+    pub max_expr_depth: Option<usize>,
+}
+
+impl Limits {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_max_input_bytes(mut self, max_input_bytes: usize) -> Self {
+        self.max_input_bytes = Some(max_input_bytes);
+        self
+    }
+
+    pub fn with_max_statements(mut self, max_statements: usize) -> Self {
+        self.max_statements = Some(max_statements);
+        self
+    }
+
+    pub fn with_max_expr_depth(mut self, max_expr_depth: usize) -> Self {
+        self.max_expr_depth = Some(max_expr_depth);
+        self
+    }
+
+    /// Check the size of the input.
+    pub fn check_input(&self, sql: &str) -> Result<(), Error> {
+        match self.max_input_bytes {
+            Some(max_input_bytes) if sql.len() > max_input_bytes => {
+                Err(Error::LimitExceeded(format!(
+                    "Input size of {} bytes exceeds the limit of {} bytes",
+                    sql.len(),
+                    max_input_bytes
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Check the nesting depth of expressions and queries of a statement,
+    /// e.g. one built programmatically or parsed without the recursion limit of [`Limits::parser`].
+    /// Visiting stops as soon as the limit is exceeded, so the check itself stays within the limit.
+    pub fn check_statement(&self, statement: &Statement) -> Result<(), Error> {
+        let Some(max_expr_depth) = self.max_expr_depth else {
+            return Ok(());
+        };
+        let mut guard = DepthGuard {
+            depth: 0,
+            max_depth: max_expr_depth,
+        };
+        match statement.visit(&mut guard) {
+            ControlFlow::Break(e) => Err(e),
+            ControlFlow::Continue(()) => Ok(()),
+        }
+    }
+
+    /// Parser for the input with the recursion limit applied.
+    pub fn parser<'a>(&self, dialect: &'a dyn Dialect, sql: &str) -> Result<Parser<'a>, Error> {
+        self.check_input(sql)?;
+        let parser = match self.max_expr_depth {
+            Some(max_expr_depth) => Parser::new(dialect).with_recursion_limit(max_expr_depth),
+            None => Parser::new(dialect),
+        };
+        Ok(parser.try_with_sql(sql)?)
+    }
+
+    /// Parse SQL while enforcing the limits.
+    pub fn parse(&self, dialect: &dyn Dialect, sql: &str) -> Result<Vec<Statement>, Error> {
+        let mut parser = self.parser(dialect, sql)?;
+        let mut statements = Vec::new();
+        parsing::parse_statements(&mut parser, |statement| {
+            if let Some(max_statements) = self.max_statements {
+                if statements.len() >= max_statements {
+                    return Err(Error::LimitExceeded(format!(
+                        "Number of statements exceeds the limit of {}",
+                        max_statements
+                    )));
+                }
+            }
+            self.check_statement(&statement)?;
+            statements.push(statement);
+            Ok(ControlFlow::Continue(()))
+        })
+        .map_err(|e| self.map_recursion_error(e))?;
+        Ok(statements)
+    }
+
+    pub(crate) fn map_recursion_error(&self, error: Error) -> Error {
+        match (error, self.max_expr_depth) {
+            (Error::ParserError(ParserError::RecursionLimitExceeded), Some(max_expr_depth)) => {
+                depth_exceeded(max_expr_depth)
+            }
+            (e, _) => e,
+        }
+    }
+}
+
+fn depth_exceeded(max_expr_depth: usize) -> Error {
+    Error::LimitExceeded(format!(
+        "Nesting depth exceeds the limit of {}",
+        max_expr_depth
+    ))
+}
+
+/// A visitor breaking once the nesting depth of expressions and queries exceeds the limit.
+struct DepthGuard {
+    depth: usize,
+    max_depth: usize,
+}
+
+impl DepthGuard {
+    fn enter(&mut self) -> ControlFlow<Error> {
+        self.depth += 1;
+        if self.depth > self.max_depth {
+            return ControlFlow::Break(depth_exceeded(self.max_depth));
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+impl Visitor for DepthGuard {
+    type Break = Error;
+
+    fn pre_visit_query(&mut self, _query: &Query) -> ControlFlow<Self::Break> {
+        self.enter()
+    }
+
+    fn post_visit_query(&mut self, _query: &Query) -> ControlFlow<Self::Break> {
+        self.depth -= 1;
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_expr(&mut self, _expr: &Expr) -> ControlFlow<Self::Break> {
+        self.enter()
+    }
+
+    fn post_visit_expr(&mut self, _expr: &Expr) -> ControlFlow<Self::Break> {
+        self.depth -= 1;
+        ControlFlow::Continue(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+
+    #[test]
+    fn test_no_limits() {
+        let sql = "SELECT a FROM t1 WHERE b = ((1 + 2) * 3); SELECT b FROM t2";
+        for dialect in all_dialects() {
+            let statements = Limits::new().parse(dialect.as_ref(), sql).unwrap();
+            assert_eq!(statements.len(), 2, "Failed for dialect: {dialect:?}");
+        }
+    }
+
+    #[test]
+    fn test_max_input_bytes() {
+        let sql = "SELECT a FROM t1";
+        for dialect in all_dialects() {
+            let limits = Limits::new().with_max_input_bytes(sql.len());
+            assert!(limits.parse(dialect.as_ref(), sql).is_ok());
+            let limits = Limits::new().with_max_input_bytes(sql.len() - 1);
+            assert_eq!(
+                limits.parse(dialect.as_ref(), sql),
+                Err(Error::LimitExceeded(
+                    "Input size of 16 bytes exceeds the limit of 15 bytes".into()
+                )),
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_max_statements() {
+        let sql = "SELECT a FROM t1; SELECT b FROM t2;; SELECT c FROM t3";
+        for dialect in all_dialects() {
+            let limits = Limits::new().with_max_statements(3);
+            assert_eq!(limits.parse(dialect.as_ref(), sql).unwrap().len(), 3);
+            let limits = Limits::new().with_max_statements(2);
+            assert_eq!(
+                limits.parse(dialect.as_ref(), sql),
+                Err(Error::LimitExceeded(
+                    "Number of statements exceeds the limit of 2".into()
+                )),
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_max_expr_depth() {
+        let sql = format!("SELECT {}1{}", "(".repeat(20), ")".repeat(20));
+        for dialect in all_dialects() {
+            let limits = Limits::new().with_max_expr_depth(100);
+            assert!(limits.parse(dialect.as_ref(), &sql).is_ok());
+            let limits = Limits::new().with_max_expr_depth(10);
+            assert_eq!(
+                limits.parse(dialect.as_ref(), &sql),
+                Err(Error::LimitExceeded(
+                    "Nesting depth exceeds the limit of 10".into()
+                )),
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_check_statement() {
+        let sql = format!("SELECT {}1 FROM t1", "NOT ".repeat(30));
+        let subqueries = format!(
+            "SELECT a FROM t1 WHERE b IN {}(SELECT c FROM t2){}",
+            "(SELECT c FROM t2 WHERE d IN ".repeat(3),
+            ")".repeat(3)
+        );
+        for dialect in all_dialects() {
+            // Parsed without the recursion limit of `Limits::parser`.
+            let statement = Parser::parse_sql(dialect.as_ref(), &sql).unwrap().remove(0);
+            assert_eq!(
+                Limits::new().check_statement(&statement),
+                Ok(()),
+                "Failed for dialect: {dialect:?}"
+            );
+            // The query and 31 nested expressions: 30 NOTs and the literal.
+            assert_eq!(
+                Limits::new()
+                    .with_max_expr_depth(32)
+                    .check_statement(&statement),
+                Ok(()),
+                "Failed for dialect: {dialect:?}"
+            );
+            assert_eq!(
+                Limits::new()
+                    .with_max_expr_depth(31)
+                    .check_statement(&statement),
+                Err(Error::LimitExceeded(
+                    "Nesting depth exceeds the limit of 31".into()
+                )),
+                "Failed for dialect: {dialect:?}"
+            );
+            let statement = Parser::parse_sql(dialect.as_ref(), &subqueries)
+                .unwrap()
+                .remove(0);
+            assert!(
+                matches!(
+                    Limits::new()
+                        .with_max_expr_depth(8)
+                        .check_statement(&statement),
+                    Err(Error::LimitExceeded(_))
+                ),
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_error_is_preserved() {
+        for dialect in all_dialects() {
+            let result = Limits::new().parse(dialect.as_ref(), "SELECT a FROM t1 t2 t3");
+            assert!(
+                matches!(result, Err(Error::ParserError(_))),
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,288 @@
+//! Configuration of a [`Linter`](crate::Linter), and inline suppression comments.
+//!
+//! See [`LintConfig`] for details.
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use crate::error::Error;
+use crate::linter::{Diagnostic, Severity};
+use crate::TableReference;
+use regex::Regex;
+use sqlparser::ast::Ident;
+use sqlparser::dialect::Dialect;
+use sqlparser::parser::ParserError;
+use sqlparser::tokenizer::{Token, Tokenizer, Whitespace};
+
+/// [`LintConfig`] configures which rules a [`Linter`](crate::Linter) runs, their severities, and allowlists.
+///
+/// Independently of the configuration, diagnostics are suppressed per statement by comments of the form
+/// `-- sql-insight: allow(no-where-clause, select-star)`. A suppression comment applies to the statement it precedes
+/// or is inside of, or to the previous statement when it follows that statement's semicolon on the same line.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+/// use sql_insight::{default_rules, AllowlistEntry, LintConfig, Linter, Severity};
+///
+/// let config = LintConfig::new()
+///     .with_disabled_rules(vec!["select-star".into()])
+///     .with_severity("implicit-cross-join", Severity::Error)
+///     .with_allowlist("no-where-clause", vec![AllowlistEntry::schema("tmp")]);
+/// let linter = Linter::new(default_rules()).with_config(config);
+/// let sql = "DELETE FROM tmp.t1;\nDELETE FROM t2; -- sql-insight: allow(no-where-clause)\nDELETE FROM t3";
+/// let diagnostics = linter.lint(&GenericDialect {}, sql).unwrap();
+/// assert_eq!(diagnostics.len(), 1);
+/// assert_eq!(diagnostics[0].statement_index, 2);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct LintConfig {
+    /// Ids of the rules to run. Every rule of the linter runs when `None`.
+    pub enabled_rules: Option<Vec<String>>,
+    /// Ids of the rules not to run. Takes precedence over `enabled_rules`.
+    pub disabled_rules: Vec<String>,
+    /// Severities overriding the default severity of rules, by rule id.
+    pub severities: HashMap<String, Severity>,
+    /// Tables and schemas whose diagnostics are suppressed, by rule id.
+    /// Diagnostics are suppressed only when they are about tables and all of them are allowlisted.
+    pub allowlists: HashMap<String, Vec<AllowlistEntry>>,
+}
+
+/// [`AllowlistEntry`] represents a table or a whole schema in an allowlist of [`LintConfig`].
+/// Qualifiers are only compared when specified, and aliases are ignored.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum AllowlistEntry {
+    Table(TableReference),
+    Schema {
+        catalog: Option<Ident>,
+        schema: Ident,
+    },
+}
+
+impl AllowlistEntry {
+    /// Entry for a table without qualifiers.
+    pub fn table(name: &str) -> Self {
+        AllowlistEntry::Table(TableReference {
+            catalog: None,
+            schema: None,
+            name: name.into(),
+            alias: None,
+        })
+    }
+
+    /// Entry for every table in a schema.
+    pub fn schema(schema: &str) -> Self {
+        AllowlistEntry::Schema {
+            catalog: None,
+            schema: schema.into(),
+        }
+    }
+
+    pub fn matches(&self, table: &TableReference) -> bool {
+        match self {
+            AllowlistEntry::Table(allowed) => {
+                allowed.name == table.name
+                    && (allowed.schema.is_none() || allowed.schema == table.schema)
+                    && (allowed.catalog.is_none() || allowed.catalog == table.catalog)
+            }
+            AllowlistEntry::Schema { catalog, schema } => {
+                table.schema.as_ref() == Some(schema)
+                    && (catalog.is_none() || *catalog == table.catalog)
+            }
+        }
+    }
+}
+
+impl LintConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_enabled_rules(mut self, enabled_rules: Vec<String>) -> Self {
+        self.enabled_rules = Some(enabled_rules);
+        self
+    }
+
+    pub fn with_disabled_rules(mut self, disabled_rules: Vec<String>) -> Self {
+        self.disabled_rules = disabled_rules;
+        self
+    }
+
+    pub fn with_severity(mut self, rule_id: impl Into<String>, severity: Severity) -> Self {
+        self.severities.insert(rule_id.into(), severity);
+        self
+    }
+
+    pub fn with_allowlist(
+        mut self,
+        rule_id: impl Into<String>,
+        allowlist: Vec<AllowlistEntry>,
+    ) -> Self {
+        self.allowlists.insert(rule_id.into(), allowlist);
+        self
+    }
+
+    /// Whether the rule runs under this configuration.
+    pub fn is_enabled(&self, rule_id: &str) -> bool {
+        if self.disabled_rules.iter().any(|id| id == rule_id) {
+            return false;
+        }
+        match &self.enabled_rules {
+            Some(enabled_rules) => enabled_rules.iter().any(|id| id == rule_id),
+            None => true,
+        }
+    }
+
+    /// Whether the diagnostic is suppressed by the allowlist of its rule.
+    pub fn is_allowlisted(&self, diagnostic: &Diagnostic) -> bool {
+        let Some(allowlist) = self.allowlists.get(&diagnostic.rule_id) else {
+            return false;
+        };
+        !diagnostic.tables.is_empty()
+            && diagnostic
+                .tables
+                .iter()
+                .all(|table| allowlist.iter().any(|entry| entry.matches(table)))
+    }
+
+    /// Apply severity overrides and allowlists to diagnostics of a statement.
+    pub(crate) fn apply(&self, diagnostics: Vec<Diagnostic>) -> Vec<Diagnostic> {
+        diagnostics
+            .into_iter()
+            .filter(|diagnostic| !self.is_allowlisted(diagnostic))
+            .map(
+                |diagnostic| match self.severities.get(&diagnostic.rule_id) {
+                    Some(severity) => Diagnostic {
+                        severity: *severity,
+                        ..diagnostic
+                    },
+                    None => diagnostic,
+                },
+            )
+            .collect()
+    }
+}
+
+/// Rule ids suppressed by inline comments, for each statement in input order.
+/// Statements are delimited by semicolons, the same way the parser delimits them, and a comment on the line
+/// a statement ends belongs to that statement.
+pub(crate) fn suppressions(dialect: &dyn Dialect, sql: &str) -> Result<Vec<Vec<String>>, Error> {
+    let tokens = Tokenizer::new(dialect, sql)
+        .tokenize()
+        .map_err(ParserError::from)?;
+    let mut suppressions: Vec<Vec<String>> = Vec::new();
+    let mut pending = Vec::new();
+    let mut in_statement = false;
+    // Whether the last statement ended on the current line, so that a comment here belongs to it.
+    let mut trailing = false;
+    for token in tokens {
+        match token {
+            Token::SemiColon | Token::EOF => {
+                if in_statement {
+                    suppressions.push(std::mem::take(&mut pending));
+                    in_statement = false;
+                    trailing = true;
+                }
+            }
+            Token::Whitespace(Whitespace::Newline) => trailing = false,
+            Token::Whitespace(Whitespace::SingleLineComment { comment, .. })
+            | Token::Whitespace(Whitespace::MultiLineComment(comment)) => {
+                let rule_ids = parse_suppression(&comment);
+                match suppressions.last_mut() {
+                    Some(last) if trailing && !in_statement => last.extend(rule_ids),
+                    _ => pending.extend(rule_ids),
+                }
+                if comment.ends_with('\n') {
+                    trailing = false;
+                }
+            }
+            Token::Whitespace(_) => {}
+            _ => {
+                in_statement = true;
+                trailing = false;
+            }
+        }
+    }
+    if in_statement {
+        suppressions.push(pending);
+    }
+    Ok(suppressions)
+}
+
+fn parse_suppression(comment: &str) -> Vec<String> {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    let pattern = PATTERN
+        .get_or_init(|| Regex::new(r"sql-insight:\s*allow\(([^)]*)\)").expect("valid regex"));
+    pattern
+        .captures_iter(comment)
+        .flat_map(|captures| {
+            captures[1]
+                .split(',')
+                .map(|rule_id| rule_id.trim().to_string())
+                .filter(|rule_id| !rule_id.is_empty())
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+
+    #[test]
+    fn test_suppressions() {
+        let sql = "-- sql-insight: allow(no-where-clause)\nDELETE FROM t1;\n\
+            UPDATE t2 SET a = 1; /* sql-insight: allow(a, b) */\n\
+            SELECT * FROM t3 -- sql-insight: allow(select-star)\n;\n\
+            SELECT 1";
+        for dialect in all_dialects() {
+            assert_eq!(
+                suppressions(dialect.as_ref(), sql).unwrap(),
+                vec![
+                    vec!["no-where-clause".to_string()],
+                    vec!["a".to_string(), "b".to_string()],
+                    vec!["select-star".to_string()],
+                    vec![],
+                ],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_is_enabled() {
+        let config = LintConfig::new()
+            .with_enabled_rules(vec!["a".into(), "b".into()])
+            .with_disabled_rules(vec!["b".into()]);
+        assert!(config.is_enabled("a"));
+        assert!(!config.is_enabled("b"));
+        assert!(!config.is_enabled("c"));
+        assert!(LintConfig::new().is_enabled("c"));
+    }
+
+    #[test]
+    fn test_allowlist_entry_matches() {
+        let table = TableReference {
+            catalog: None,
+            schema: Some("s1".into()),
+            name: "t1".into(),
+            alias: Some("a".into()),
+        };
+        assert!(AllowlistEntry::table("t1").matches(&table));
+        assert!(AllowlistEntry::schema("s1").matches(&table));
+        assert!(!AllowlistEntry::schema("s2").matches(&table));
+        assert!(!AllowlistEntry::Table(TableReference {
+            catalog: None,
+            schema: Some("s2".into()),
+            name: "t1".into(),
+            alias: None,
+        })
+        .matches(&table));
+    }
+}
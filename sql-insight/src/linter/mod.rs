@@ -0,0 +1,430 @@
+//! A Linter that checks SQL against a set of rules.
+//!
+//! Rules implement the [`Rule`] trait and report [`Diagnostic`]s. A [`Linter`] runs its rules over parsed statements.
+//! Built-in rules are found in the [`rules`] module.
+//!
+//! See [`lint`](crate::lint()) as the entry point for linting SQL with the default rules.
+
+pub mod config;
+pub mod keywords;
+pub mod rule;
+pub mod rules;
+#[cfg(feature = "serde")]
+pub mod sarif;
+
+pub use config::*;
+pub use keywords::*;
+pub use rule::*;
+pub use rules::*;
+#[cfg(feature = "serde")]
+pub use sarif::*;
+
+use crate::error::Error;
+use crate::instrument;
+use crate::span;
+use sqlparser::ast::Statement;
+use sqlparser::dialect::Dialect;
+
+/// Convenience function to lint SQL with the default rules.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+///
+/// let dialect = GenericDialect {};
+/// let diagnostics = sql_insight::lint(&dialect, "DELETE FROM users").unwrap();
+/// assert_eq!(
+///     diagnostics[0].to_string(),
+///     "1:1: error[no-where-clause]: DELETE without WHERE clause affects all rows of users"
+/// );
+/// ```
+pub fn lint(dialect: &dyn Dialect, sql: &str) -> Result<Vec<Diagnostic>, Error> {
+    Linter::new(default_rules()).lint(dialect, sql)
+}
+
+/// [`Linter`] runs a set of [`Rule`]s over statements, as configured by a [`LintConfig`].
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::ast::Statement;
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+/// use sql_insight::{Diagnostic, Linter, Rule};
+///
+/// struct NoTruncate;
+///
+/// impl Rule for NoTruncate {
+///     fn id(&self) -> &str {
+///         "no-truncate"
+///     }
+///
+///     fn check(&self, statement: &Statement) -> Vec<Diagnostic> {
+///         match statement {
+///             Statement::Truncate { .. } => vec![Diagnostic::new(self, "TRUNCATE is not allowed")],
+///             _ => vec![],
+///         }
+///     }
+/// }
+///
+/// let linter = Linter::new(vec![Box::new(NoTruncate)]);
+/// let diagnostics = linter.lint(&GenericDialect {}, "SELECT a FROM t1;\nTRUNCATE TABLE t1").unwrap();
+/// assert_eq!(diagnostics.len(), 1);
+/// assert_eq!(diagnostics[0].to_string(), "2:1: warning[no-truncate]: TRUNCATE is not allowed");
+/// ```
+#[derive(Default)]
+pub struct Linter {
+    rules: Vec<Box<dyn Rule>>,
+    config: LintConfig,
+}
+
+impl Linter {
+    pub fn new(rules: Vec<Box<dyn Rule>>) -> Self {
+        Self {
+            rules,
+            config: LintConfig::default(),
+        }
+    }
+
+    /// Register a rule, e.g. a custom rule implementing an internal policy.
+    /// A rule already registered with the same id is replaced.
+    pub fn with_rule(mut self, rule: Box<dyn Rule>) -> Self {
+        self.add_rule(rule);
+        self
+    }
+
+    /// Register a rule. A rule already registered with the same id is replaced.
+    pub fn add_rule(&mut self, rule: Box<dyn Rule>) {
+        match self.rules.iter_mut().find(|r| r.id() == rule.id()) {
+            Some(registered) => *registered = rule,
+            None => self.rules.push(rule),
+        }
+    }
+
+    pub fn with_config(mut self, config: LintConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub fn config(&self) -> &LintConfig {
+        &self.config
+    }
+
+    /// Rules run by this linter, in order.
+    pub fn rules(&self) -> &[Box<dyn Rule>] {
+        &self.rules
+    }
+
+    /// Lint SQL. Diagnostics carry the span of the statement they were reported for,
+    /// and diagnostics suppressed by inline comments are dropped.
+    pub fn lint(&self, dialect: &dyn Dialect, sql: &str) -> Result<Vec<Diagnostic>, Error> {
+        self.lint_statements(dialect, sql)
+            .map(|(_, diagnostics)| diagnostics)
+    }
+
+    /// Lint SQL like [`lint`](Self::lint), grouping diagnostics by statement.
+    /// The result has an entry for every statement, including statements without diagnostics.
+    pub fn lint_by_statement(
+        &self,
+        dialect: &dyn Dialect,
+        sql: &str,
+    ) -> Result<Vec<Vec<Diagnostic>>, Error> {
+        let (statement_count, diagnostics) = self.lint_statements(dialect, sql)?;
+        let mut per_statement = vec![Vec::new(); statement_count];
+        for diagnostic in diagnostics {
+            if let Some(diagnostics) = per_statement.get_mut(diagnostic.statement_index) {
+                diagnostics.push(diagnostic);
+            }
+        }
+        Ok(per_statement)
+    }
+
+    // Returns the number of parsed statements along with the diagnostics.
+    fn lint_statements(
+        &self,
+        dialect: &dyn Dialect,
+        sql: &str,
+    ) -> Result<(usize, Vec<Diagnostic>), Error> {
+        let statements = instrument::parse_sql(dialect, sql)?;
+        let mut diagnostics = self.check_statements(&statements);
+        if let Ok(suppressions) = config::suppressions(dialect, sql) {
+            if suppressions.len() == statements.len() {
+                diagnostics.retain(|diagnostic| {
+                    !suppressions[diagnostic.statement_index].contains(&diagnostic.rule_id)
+                });
+            }
+        }
+        // Spans are best effort: they are only attached when the input splits into the same statements.
+        let spans = span::statement_spans(dialect, sql);
+        if spans.len() == statements.len() {
+            for diagnostic in diagnostics.iter_mut() {
+                diagnostic.span = Some(spans[diagnostic.statement_index]);
+            }
+        }
+        Ok((statements.len(), diagnostics))
+    }
+
+    /// Lint parsed statements.
+    pub fn check_statements(&self, statements: &[Statement]) -> Vec<Diagnostic> {
+        statements
+            .iter()
+            .enumerate()
+            .flat_map(|(index, statement)| {
+                self.check_statement(statement)
+                    .into_iter()
+                    .map(move |diagnostic| Diagnostic {
+                        statement_index: index,
+                        ..diagnostic
+                    })
+            })
+            .collect()
+    }
+
+    /// Lint a parsed statement. Inline suppression comments are not available here since they are not part of the AST.
+    pub fn check_statement(&self, statement: &Statement) -> Vec<Diagnostic> {
+        instrument::analyze("lint", || {
+            self.enabled_rules()
+                .flat_map(|rule| {
+                    let mut diagnostics = self.config.apply(rule.check(statement));
+                    if !diagnostics.is_empty() && rule.fix(statement).is_some() {
+                        for diagnostic in diagnostics.iter_mut() {
+                            diagnostic.fixable = true;
+                        }
+                    }
+                    diagnostics
+                })
+                .collect()
+        })
+    }
+
+    /// Lint SQL and apply the fixes of rules reporting problems, returning the fixed statements.
+    /// Diagnostics suppressed by inline comments are not fixed.
+    pub fn fix(&self, dialect: &dyn Dialect, sql: &str) -> Result<Vec<String>, Error> {
+        let mut statements = instrument::parse_sql(dialect, sql)?;
+        let suppressions = config::suppressions(dialect, sql)
+            .ok()
+            .filter(|suppressions| suppressions.len() == statements.len());
+        Ok(statements
+            .iter_mut()
+            .enumerate()
+            .map(|(index, statement)| {
+                let suppressed = suppressions
+                    .as_ref()
+                    .map(|suppressions| suppressions[index].as_slice())
+                    .unwrap_or_default();
+                self.fix_statement(statement, suppressed);
+                statement.to_string()
+            })
+            .collect())
+    }
+
+    /// Apply the fixes of rules reporting problems to parsed statements. Returns the number of fixes applied.
+    pub fn apply_fixes(&self, statements: &mut [Statement]) -> usize {
+        statements
+            .iter_mut()
+            .map(|statement| self.fix_statement(statement, &[]))
+            .sum()
+    }
+
+    // Rules are applied in order, so each rule checks the statement as fixed by the previous ones.
+    fn fix_statement(&self, statement: &mut Statement, suppressed: &[String]) -> usize {
+        let mut applied = 0;
+        for rule in self.enabled_rules() {
+            if suppressed.iter().any(|id| id == rule.id())
+                || self.config.apply(rule.check(statement)).is_empty()
+            {
+                continue;
+            }
+            if let Some(fix) = rule.fix(statement) {
+                fix.apply(statement);
+                applied += 1;
+            }
+        }
+        applied
+    }
+
+    fn enabled_rules(&self) -> impl Iterator<Item = &dyn Rule> {
+        self.rules
+            .iter()
+            .map(|rule| rule.as_ref())
+            .filter(|rule| self.config.is_enabled(rule.id()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::span::{Location, Span};
+    use crate::test_utils::all_dialects;
+    use sqlparser::parser::Parser;
+
+    struct NoDelete;
+
+    impl Rule for NoDelete {
+        fn id(&self) -> &str {
+            "no-delete"
+        }
+
+        fn severity(&self) -> Severity {
+            Severity::Error
+        }
+
+        fn check(&self, statement: &Statement) -> Vec<Diagnostic> {
+            match statement {
+                Statement::Delete { .. } => vec![Diagnostic::new(self, "DELETE is not allowed")],
+                _ => vec![],
+            }
+        }
+    }
+
+    struct NoSelect;
+
+    impl Rule for NoSelect {
+        fn id(&self) -> &str {
+            "no-select"
+        }
+
+        fn check(&self, statement: &Statement) -> Vec<Diagnostic> {
+            match statement {
+                Statement::Query(_) => vec![Diagnostic::new(self, "SELECT is not allowed")],
+                _ => vec![],
+            }
+        }
+    }
+
+    #[test]
+    fn test_lint() {
+        let sql = "SELECT a FROM t1;\n  DELETE FROM t2;\nUPDATE t3 SET a = 1";
+        let linter = Linter::new(vec![Box::new(NoDelete), Box::new(NoSelect)]);
+        for dialect in all_dialects() {
+            let diagnostics = linter.lint(dialect.as_ref(), sql).unwrap();
+            assert_eq!(
+                diagnostics,
+                vec![
+                    Diagnostic {
+                        rule_id: "no-select".into(),
+                        severity: Severity::Warning,
+                        message: "SELECT is not allowed".into(),
+                        statement_index: 0,
+                        span: Some(Span::new(Location::new(1, 1), Location::new(1, 17))),
+                        tables: vec![],
+                        fixable: false,
+                    },
+                    Diagnostic {
+                        rule_id: "no-delete".into(),
+                        severity: Severity::Error,
+                        message: "DELETE is not allowed".into(),
+                        statement_index: 1,
+                        span: Some(Span::new(Location::new(2, 3), Location::new(2, 17))),
+                        tables: vec![],
+                        fixable: false,
+                    },
+                ],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_lint_by_statement() {
+        let sql = "SELECT a FROM t1;\n  DELETE FROM t2;\nUPDATE t3 SET a = 1";
+        let linter = Linter::new(vec![Box::new(NoDelete)]);
+        for dialect in all_dialects() {
+            let per_statement = linter.lint_by_statement(dialect.as_ref(), sql).unwrap();
+            assert_eq!(
+                per_statement
+                    .iter()
+                    .map(|diagnostics| diagnostics
+                        .iter()
+                        .map(|diagnostic| diagnostic.rule_id.as_str())
+                        .collect::<Vec<_>>())
+                    .collect::<Vec<_>>(),
+                vec![vec![], vec!["no-delete"], vec![]],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_check_statements() {
+        let statements =
+            Parser::parse_sql(&sqlparser::dialect::GenericDialect {}, "DELETE FROM t1").unwrap();
+        let diagnostics = Linter::new(vec![Box::new(NoDelete)]).check_statements(&statements);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].span, None);
+        assert_eq!(
+            diagnostics[0].to_string(),
+            "statement 1: error[no-delete]: DELETE is not allowed"
+        );
+    }
+
+    #[test]
+    fn test_lint_without_rules() {
+        for dialect in all_dialects() {
+            let diagnostics = Linter::default()
+                .lint(dialect.as_ref(), "DELETE FROM t1")
+                .unwrap();
+            assert!(diagnostics.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_lint_with_config() {
+        let sql =
+            "SELECT a FROM t1;\nDELETE FROM t2; -- sql-insight: allow(no-delete)\nDELETE FROM t3";
+        let config = LintConfig::new()
+            .with_disabled_rules(vec!["no-select".into()])
+            .with_severity("no-delete", Severity::Info);
+        let linter = Linter::new(vec![Box::new(NoDelete), Box::new(NoSelect)]).with_config(config);
+        for dialect in all_dialects() {
+            let diagnostics = linter.lint(dialect.as_ref(), sql).unwrap();
+            assert_eq!(
+                diagnostics
+                    .iter()
+                    .map(|d| d.to_string())
+                    .collect::<Vec<_>>(),
+                vec!["3:1: info[no-delete]: DELETE is not allowed"],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_with_rule() {
+        let linter = Linter::new(vec![Box::new(NoDelete), Box::new(NoSelect)])
+            .with_rule(Box::new(FnRule::new(
+                "no-select",
+                |statement: &Statement| match statement {
+                    Statement::Query(_) => vec!["replaced".to_string()],
+                    _ => vec![],
+                },
+            )))
+            .with_rule(Box::new(
+                FnRule::new("no-update", |statement: &Statement| match statement {
+                    Statement::Update { .. } => vec!["UPDATE is not allowed".to_string()],
+                    _ => vec![],
+                })
+                .with_severity(Severity::Error),
+            ));
+        assert_eq!(
+            linter.rules().iter().map(|r| r.id()).collect::<Vec<_>>(),
+            vec!["no-delete", "no-select", "no-update"]
+        );
+        for dialect in all_dialects() {
+            let diagnostics = linter
+                .lint(dialect.as_ref(), "SELECT a FROM t1; UPDATE t1 SET a = 1")
+                .unwrap();
+            assert_eq!(
+                diagnostics
+                    .iter()
+                    .map(|d| d.to_string())
+                    .collect::<Vec<_>>(),
+                vec![
+                    "1:1: warning[no-select]: replaced",
+                    "1:19: error[no-update]: UPDATE is not allowed"
+                ],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,215 @@
+//! The [`Rule`] trait implemented by every lint rule, and the [`Diagnostic`]s that rules report.
+
+use core::fmt;
+use std::str::FromStr;
+
+use crate::error::Error;
+use crate::span::Span;
+use crate::{TableReference, VisitorMutSet};
+use sqlparser::ast::{Statement, VisitMut, VisitorMut};
+
+/// [`Rule`] checks a single statement and reports a [`Diagnostic`] for each problem found.
+pub trait Rule {
+    /// Identifier of the rule in kebab-case, e.g. `no-where-clause`. Must be unique within a [`Linter`](crate::Linter).
+    fn id(&self) -> &str;
+
+    /// Severity of diagnostics reported by the rule unless configured otherwise.
+    fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    /// Check a statement.
+    fn check(&self, statement: &Statement) -> Vec<Diagnostic>;
+
+    /// Fix resolving the problems reported for a statement, if the rule can fix them automatically.
+    fn fix(&self, _statement: &Statement) -> Option<Fix> {
+        None
+    }
+}
+
+/// [`Fix`] is an AST rewrite resolving the problems a [`Rule`] reported for a statement.
+pub struct Fix {
+    visitor: Box<dyn VisitorMut<Break = ()>>,
+}
+
+impl Fix {
+    /// Fix rewriting statements with a [`VisitorMut`].
+    pub fn new(visitor: impl VisitorMut<Break = ()> + 'static) -> Self {
+        Self {
+            visitor: Box::new(visitor),
+        }
+    }
+
+    pub fn apply(mut self, statement: &mut Statement) {
+        let mut visitor_set = VisitorMutSet::new().with_visitor(self.visitor.as_mut());
+        let _ = statement.visit(&mut visitor_set);
+    }
+}
+
+/// [`FnRule`] is a [`Rule`] defined by a closure returning the messages of the problems found in a statement.
+/// It allows registering custom policies at runtime without defining a type.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::ast::Statement;
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+/// use sql_insight::{FnRule, Linter, Severity};
+///
+/// let tenant_filter = FnRule::new("tenant-filter", |statement: &Statement| {
+///     match statement {
+///         Statement::Query(query) if !query.to_string().contains("tenant_id") => {
+///             vec!["Query does not filter on tenant_id".to_string()]
+///         }
+///         _ => vec![],
+///     }
+/// })
+/// .with_severity(Severity::Error);
+/// let linter = Linter::default().with_rule(Box::new(tenant_filter));
+/// let diagnostics = linter.lint(&GenericDialect {}, "SELECT a FROM t1 WHERE tenant_id = 1; SELECT a FROM t1").unwrap();
+/// assert_eq!(diagnostics.len(), 1);
+/// assert_eq!(diagnostics[0].to_string(), "1:39: error[tenant-filter]: Query does not filter on tenant_id");
+/// ```
+pub struct FnRule<F> {
+    id: String,
+    severity: Severity,
+    check: F,
+}
+
+impl<F> FnRule<F>
+where
+    F: Fn(&Statement) -> Vec<String>,
+{
+    pub fn new(id: impl Into<String>, check: F) -> Self {
+        Self {
+            id: id.into(),
+            severity: Severity::Warning,
+            check,
+        }
+    }
+
+    pub fn with_severity(mut self, severity: Severity) -> Self {
+        self.severity = severity;
+        self
+    }
+}
+
+impl<F> Rule for FnRule<F>
+where
+    F: Fn(&Statement) -> Vec<String>,
+{
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    fn check(&self, statement: &Statement) -> Vec<Diagnostic> {
+        (self.check)(statement)
+            .into_iter()
+            .map(|message| Diagnostic::new(self, message))
+            .collect()
+    }
+}
+
+/// [`Severity`] represents how serious a [`Diagnostic`] is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Info => write!(f, "info"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+impl FromStr for Severity {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "info" => Ok(Severity::Info),
+            "warning" => Ok(Severity::Warning),
+            "error" => Ok(Severity::Error),
+            _ => Err(Error::ArgumentError(format!(
+                "Severity not found: {s}. Available severities: info, warning, error"
+            ))),
+        }
+    }
+}
+
+/// [`Diagnostic`] represents a problem found by a [`Rule`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Diagnostic {
+    pub rule_id: String,
+    pub severity: Severity,
+    pub message: String,
+    /// Zero-based position of the statement in the input. Set by the [`Linter`](crate::Linter).
+    pub statement_index: usize,
+    /// Span of the statement in the input. Set by the [`Linter`](crate::Linter) when linting source text.
+    pub span: Option<Span>,
+    /// Tables the problem is about, if any.
+    pub tables: Vec<TableReference>,
+    /// Whether the rule can fix the problem automatically. Set by the [`Linter`](crate::Linter).
+    pub fixable: bool,
+}
+
+impl Diagnostic {
+    /// Diagnostic reported by `rule` with its default severity.
+    pub fn new(rule: &(impl Rule + ?Sized), message: impl Into<String>) -> Self {
+        Self {
+            rule_id: rule.id().to_string(),
+            severity: rule.severity(),
+            message: message.into(),
+            statement_index: 0,
+            span: None,
+            tables: vec![],
+            fixable: false,
+        }
+    }
+
+    pub fn with_tables(mut self, tables: Vec<TableReference>) -> Self {
+        self.tables = tables;
+        self
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.span {
+            Some(span) => write!(f, "{}:{}: ", span.start.line, span.start.column)?,
+            None => write!(f, "statement {}: ", self.statement_index + 1)?,
+        }
+        write!(f, "{}[{}]: {}", self.severity, self.rule_id, self.message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_severity_from_str() {
+        assert_eq!("info".parse::<Severity>().unwrap(), Severity::Info);
+        assert_eq!("Warning".parse::<Severity>().unwrap(), Severity::Warning);
+        assert_eq!("ERROR".parse::<Severity>().unwrap(), Severity::Error);
+        assert_eq!(
+            "fatal".parse::<Severity>(),
+            Err(Error::ArgumentError(
+                "Severity not found: fatal. Available severities: info, warning, error".to_string()
+            ))
+        );
+    }
+}
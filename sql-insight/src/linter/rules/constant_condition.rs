@@ -0,0 +1,62 @@
+//! Rule that flags predicates which are always true or always false.
+
+use crate::linter::{Diagnostic, Rule};
+use crate::{SuspiciousPatternDetector, SuspiciousPatternKind};
+use sqlparser::ast::Statement;
+
+/// [`ConstantCondition`] flags predicates in WHERE, HAVING and join conditions which are always true or always false,
+/// such as `OR 1=1` and `WHERE 'a' = 'a'`. See [`SuspiciousPatternDetector`] for the predicates regarded as idioms.
+#[derive(Clone, Debug, Default)]
+pub struct ConstantCondition;
+
+impl ConstantCondition {
+    pub const ID: &'static str = "constant-condition";
+
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Rule for ConstantCondition {
+    fn id(&self) -> &str {
+        Self::ID
+    }
+
+    fn check(&self, statement: &Statement) -> Vec<Diagnostic> {
+        SuspiciousPatternDetector::detect_from_statement(statement)
+            .into_iter()
+            .filter(|pattern| {
+                matches!(
+                    pattern.kind,
+                    SuspiciousPatternKind::Tautology | SuspiciousPatternKind::Contradiction
+                )
+            })
+            .map(|pattern| Diagnostic::new(self, pattern.description))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+    use sqlparser::parser::Parser;
+
+    #[test]
+    fn test_constant_condition() {
+        let sql = "SELECT a FROM t1 WHERE NOT (1 = 1); SELECT a FROM t1 UNION SELECT 1";
+        for dialect in all_dialects() {
+            let messages: Vec<String> = Parser::parse_sql(dialect.as_ref(), sql)
+                .unwrap()
+                .iter()
+                .flat_map(|statement| ConstantCondition::new().check(statement))
+                .map(|diagnostic| diagnostic.message)
+                .collect();
+            assert_eq!(
+                messages,
+                vec!["Predicate NOT (1 = 1) is always false"],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+}
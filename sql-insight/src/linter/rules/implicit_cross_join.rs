@@ -0,0 +1,271 @@
+//! Rule that flags joins without a join condition.
+
+use std::ops::ControlFlow;
+
+use crate::helper;
+use crate::linter::{Diagnostic, Fix, Rule};
+use crate::TableReference;
+use sqlparser::ast::{
+    Join, JoinConstraint, JoinOperator, Query, Select, Statement, TableFactor, Visit, Visitor,
+    VisitorMut,
+};
+
+/// [`ImplicitCrossJoin`] flags comma joins (`FROM t1, t2`) and JOINs without ON or USING,
+/// which produce a cartesian product unless a condition is given elsewhere.
+/// Explicit `CROSS JOIN` and `NATURAL JOIN` are regarded as intentional and not flagged.
+///
+/// The fix makes the cartesian products explicit: comma joins and inner JOINs without a condition become `CROSS JOIN`.
+/// Outer JOINs without a condition are left as they are since they are not equivalent to a `CROSS JOIN`.
+#[derive(Clone, Debug, Default)]
+pub struct ImplicitCrossJoin;
+
+impl ImplicitCrossJoin {
+    pub const ID: &'static str = "implicit-cross-join";
+
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Rule for ImplicitCrossJoin {
+    fn id(&self) -> &str {
+        Self::ID
+    }
+
+    fn check(&self, statement: &Statement) -> Vec<Diagnostic> {
+        let mut visitor = ImplicitCrossJoinVisitor {
+            rule: self,
+            diagnostics: vec![],
+        };
+        let _ = statement.visit(&mut visitor);
+        visitor.diagnostics
+    }
+
+    fn fix(&self, _statement: &Statement) -> Option<Fix> {
+        Some(Fix::new(ExplicitCrossJoinFixer))
+    }
+}
+
+struct ExplicitCrossJoinFixer;
+
+impl VisitorMut for ExplicitCrossJoinFixer {
+    type Break = ();
+
+    fn pre_visit_query(&mut self, query: &mut Query) -> ControlFlow<Self::Break> {
+        let mut selects = Vec::new();
+        helper::collect_selects_mut(&mut query.body, &mut selects);
+        for select in selects {
+            make_cross_joins_explicit(select);
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+fn make_cross_joins_explicit(select: &mut Select) {
+    for table_with_joins in select.from.iter_mut() {
+        for join in table_with_joins.joins.iter_mut() {
+            if join.join_operator == JoinOperator::Inner(JoinConstraint::None) {
+                join.join_operator = JoinOperator::CrossJoin;
+            }
+        }
+    }
+    if select.from.len() < 2 {
+        return;
+    }
+    let mut from = std::mem::take(&mut select.from).into_iter();
+    let Some(mut first) = from.next() else {
+        return;
+    };
+    for table_with_joins in from {
+        // Joins following a comma bind tighter than the comma, so they are kept together in a nested join.
+        let relation = if table_with_joins.joins.is_empty() {
+            table_with_joins.relation
+        } else {
+            TableFactor::NestedJoin {
+                table_with_joins: Box::new(table_with_joins),
+                alias: None,
+            }
+        };
+        first.joins.push(Join {
+            relation,
+            join_operator: JoinOperator::CrossJoin,
+        });
+    }
+    select.from = vec![first];
+}
+
+struct ImplicitCrossJoinVisitor<'a> {
+    rule: &'a ImplicitCrossJoin,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl ImplicitCrossJoinVisitor<'_> {
+    fn report(&mut self, kind: &str, left: &TableFactor, right: &TableFactor) {
+        let tables = [left, right]
+            .into_iter()
+            .filter(|factor| matches!(factor, TableFactor::Table { .. }))
+            .filter_map(|factor| TableReference::try_from(factor).ok())
+            .collect();
+        self.diagnostics.push(
+            Diagnostic::new(
+                self.rule,
+                format!(
+                    "{} between {} and {}",
+                    kind,
+                    describe(left),
+                    describe(right)
+                ),
+            )
+            .with_tables(tables),
+        );
+    }
+}
+
+impl Visitor for ImplicitCrossJoinVisitor<'_> {
+    type Break = ();
+
+    fn pre_visit_query(&mut self, query: &Query) -> ControlFlow<Self::Break> {
+        let mut selects = Vec::new();
+        helper::collect_selects(&query.body, &mut selects);
+        for select in selects {
+            let mut previous: Option<&TableFactor> = None;
+            for table_with_joins in &select.from {
+                if let Some(left) = previous {
+                    self.report("Comma join", left, &table_with_joins.relation);
+                }
+                let mut left = &table_with_joins.relation;
+                for join in &table_with_joins.joins {
+                    if has_no_condition(&join.join_operator) {
+                        self.report("Join without condition", left, &join.relation);
+                    }
+                    left = &join.relation;
+                }
+                previous = Some(left);
+            }
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+fn has_no_condition(join_operator: &JoinOperator) -> bool {
+    matches!(
+        join_operator,
+        JoinOperator::Inner(JoinConstraint::None)
+            | JoinOperator::LeftOuter(JoinConstraint::None)
+            | JoinOperator::RightOuter(JoinConstraint::None)
+            | JoinOperator::FullOuter(JoinConstraint::None)
+    )
+}
+
+fn describe(factor: &TableFactor) -> String {
+    match factor {
+        TableFactor::Table { .. } => TableReference::try_from(factor)
+            .map(|table| table.to_string())
+            .unwrap_or_else(|_| "table".to_string()),
+        TableFactor::Derived {
+            alias: Some(alias), ..
+        } => format!("subquery {}", alias.name),
+        _ => "subquery".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+    use crate::Linter;
+    use sqlparser::dialect::{Dialect, GenericDialect, MySqlDialect};
+    use sqlparser::parser::Parser;
+
+    fn check(dialect: &dyn Dialect, sql: &str) -> Vec<Diagnostic> {
+        Parser::parse_sql(dialect, sql)
+            .unwrap()
+            .iter()
+            .flat_map(|statement| ImplicitCrossJoin::new().check(statement))
+            .collect()
+    }
+
+    fn table(name: &str, alias: Option<&str>) -> TableReference {
+        TableReference {
+            catalog: None,
+            schema: None,
+            name: name.into(),
+            alias: alias.map(|a| a.into()),
+        }
+    }
+
+    #[test]
+    fn test_comma_join() {
+        let sql = "SELECT a FROM t1 AS x, t2 WHERE x.id = t2.id";
+        for dialect in all_dialects() {
+            let diagnostics = check(dialect.as_ref(), sql);
+            assert_eq!(diagnostics.len(), 1, "Failed for dialect: {dialect:?}");
+            assert_eq!(diagnostics[0].message, "Comma join between t1 AS x and t2");
+            assert_eq!(
+                diagnostics[0].tables,
+                vec![table("t1", Some("x")), table("t2", None)]
+            );
+        }
+    }
+
+    #[test]
+    fn test_comma_join_after_explicit_join() {
+        let sql = "SELECT a FROM t1 INNER JOIN t2 ON t1.id = t2.id, (SELECT b FROM t3) AS d";
+        for dialect in all_dialects() {
+            let diagnostics = check(dialect.as_ref(), sql);
+            assert_eq!(diagnostics.len(), 1, "Failed for dialect: {dialect:?}");
+            assert_eq!(
+                diagnostics[0].message,
+                "Comma join between t2 and subquery d"
+            );
+            assert_eq!(diagnostics[0].tables, vec![table("t2", None)]);
+        }
+    }
+
+    #[test]
+    fn test_join_without_condition() {
+        let sql = "SELECT a FROM t1 JOIN t2 JOIN t3 ON t2.id = t3.id";
+        let diagnostics = check(&MySqlDialect {}, sql);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].message,
+            "Join without condition between t1 and t2"
+        );
+    }
+
+    #[test]
+    fn test_joins_with_condition_or_explicit_cross_join() {
+        let sql = "SELECT a FROM t1 JOIN t2 USING (id) LEFT JOIN t3 ON t2.id = t3.id CROSS JOIN t4 NATURAL JOIN t5";
+        for dialect in all_dialects() {
+            assert!(check(dialect.as_ref(), sql).is_empty());
+        }
+    }
+
+    #[test]
+    fn test_comma_join_in_subquery() {
+        let sql = "SELECT a FROM t1 WHERE b IN (SELECT b FROM t2, t3)";
+        for dialect in all_dialects() {
+            let diagnostics = check(dialect.as_ref(), sql);
+            assert_eq!(diagnostics.len(), 1, "Failed for dialect: {dialect:?}");
+            assert_eq!(diagnostics[0].message, "Comma join between t2 and t3");
+        }
+    }
+
+    fn fix(sql: &str) -> Vec<String> {
+        Linter::new(vec![Box::new(ImplicitCrossJoin::new())])
+            .fix(&GenericDialect {}, sql)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_fix() {
+        assert_eq!(
+            fix("SELECT a FROM t1, t2 JOIN t3 ON t2.id = t3.id, t4 WHERE t1.id = t2.id; \
+                SELECT a FROM t1 JOIN t2 LEFT JOIN t3 ON t2.id = t3.id"),
+            vec![
+                "SELECT a FROM t1 CROSS JOIN (t2 JOIN t3 ON t2.id = t3.id) CROSS JOIN t4 WHERE t1.id = t2.id",
+                "SELECT a FROM t1 CROSS JOIN t2 LEFT JOIN t3 ON t2.id = t3.id",
+            ]
+        );
+    }
+}
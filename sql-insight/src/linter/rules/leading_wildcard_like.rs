@@ -0,0 +1,157 @@
+//! Rule that flags LIKE patterns starting with a wildcard.
+
+use std::ops::ControlFlow;
+
+use crate::error::Error;
+use crate::linter::{Diagnostic, Rule};
+use regex::Regex;
+use sqlparser::ast::{Expr, Statement, Value, Visit, Visitor};
+
+/// [`LeadingWildcardLike`] flags `LIKE` and `ILIKE` patterns starting with a wildcard such as `LIKE '%foo'`,
+/// which cannot use an index on the column.
+#[derive(Clone, Debug, Default)]
+pub struct LeadingWildcardLike {
+    ignore_columns: Option<Regex>,
+}
+
+impl LeadingWildcardLike {
+    pub const ID: &'static str = "leading-wildcard-like";
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ignore columns whose unqualified name matches `pattern`, e.g. `_search$`.
+    pub fn with_ignore_columns(mut self, pattern: &str) -> Result<Self, Error> {
+        let regex = Regex::new(pattern).map_err(|e| {
+            Error::ArgumentError(format!("Invalid column pattern {}: {}", pattern, e))
+        })?;
+        self.ignore_columns = Some(regex);
+        Ok(self)
+    }
+
+    fn is_ignored(&self, expr: &Expr) -> bool {
+        let Some(ignore_columns) = &self.ignore_columns else {
+            return false;
+        };
+        match expr {
+            Expr::Identifier(ident) => ignore_columns.is_match(&ident.value),
+            Expr::CompoundIdentifier(idents) => idents
+                .last()
+                .is_some_and(|ident| ignore_columns.is_match(&ident.value)),
+            _ => false,
+        }
+    }
+}
+
+impl Rule for LeadingWildcardLike {
+    fn id(&self) -> &str {
+        Self::ID
+    }
+
+    fn check(&self, statement: &Statement) -> Vec<Diagnostic> {
+        let mut visitor = LeadingWildcardLikeVisitor {
+            rule: self,
+            diagnostics: vec![],
+        };
+        let _ = statement.visit(&mut visitor);
+        visitor.diagnostics
+    }
+}
+
+struct LeadingWildcardLikeVisitor<'a> {
+    rule: &'a LeadingWildcardLike,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Visitor for LeadingWildcardLikeVisitor<'_> {
+    type Break = ();
+
+    fn pre_visit_expr(&mut self, expr: &Expr) -> ControlFlow<Self::Break> {
+        let (operator, column, pattern) = match expr {
+            Expr::Like { expr, pattern, .. } => ("LIKE", expr, pattern),
+            Expr::ILike { expr, pattern, .. } => ("ILIKE", expr, pattern),
+            _ => return ControlFlow::Continue(()),
+        };
+        let pattern = match pattern.as_ref() {
+            Expr::Value(Value::SingleQuotedString(s))
+            | Expr::Value(Value::DoubleQuotedString(s)) => s,
+            _ => return ControlFlow::Continue(()),
+        };
+        if (pattern.starts_with('%') || pattern.starts_with('_')) && !self.rule.is_ignored(column) {
+            self.diagnostics.push(Diagnostic::new(
+                self.rule,
+                format!(
+                    "{} pattern '{}' on {} starts with a wildcard and cannot use an index",
+                    operator, pattern, column
+                ),
+            ));
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+    use sqlparser::dialect::{Dialect, PostgreSqlDialect};
+    use sqlparser::parser::Parser;
+
+    fn check(rule: &LeadingWildcardLike, dialect: &dyn Dialect, sql: &str) -> Vec<String> {
+        Parser::parse_sql(dialect, sql)
+            .unwrap()
+            .iter()
+            .flat_map(|statement| rule.check(statement))
+            .map(|diagnostic| diagnostic.message)
+            .collect()
+    }
+
+    #[test]
+    fn test_leading_wildcard() {
+        let sql =
+            "SELECT a FROM t1 WHERE b LIKE '%foo' AND t1.c NOT LIKE '_bar%' AND d LIKE 'baz%'";
+        for dialect in all_dialects() {
+            assert_eq!(
+                check(&LeadingWildcardLike::new(), dialect.as_ref(), sql),
+                vec![
+                    "LIKE pattern '%foo' on b starts with a wildcard and cannot use an index",
+                    "LIKE pattern '_bar%' on t1.c starts with a wildcard and cannot use an index",
+                ],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_leading_wildcard_ilike() {
+        let sql = "UPDATE t1 SET a = 1 WHERE b ILIKE '%foo%'";
+        assert_eq!(
+            check(&LeadingWildcardLike::new(), &PostgreSqlDialect {}, sql),
+            vec!["ILIKE pattern '%foo%' on b starts with a wildcard and cannot use an index"]
+        );
+    }
+
+    #[test]
+    fn test_ignore_columns() {
+        let sql = "SELECT a FROM t1 WHERE name_search LIKE '%foo' AND t1.body_search LIKE '%bar' AND name LIKE '%baz'";
+        let rule = LeadingWildcardLike::new()
+            .with_ignore_columns("_search$")
+            .unwrap();
+        for dialect in all_dialects() {
+            assert_eq!(
+                check(&rule, dialect.as_ref(), sql),
+                vec!["LIKE pattern '%baz' on name starts with a wildcard and cannot use an index"],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_invalid_ignore_columns_pattern() {
+        assert!(matches!(
+            LeadingWildcardLike::new().with_ignore_columns("("),
+            Err(Error::ArgumentError(_))
+        ));
+    }
+}
@@ -0,0 +1,39 @@
+//! Built-in lint rules.
+
+pub mod constant_condition;
+pub mod implicit_cross_join;
+pub mod leading_wildcard_like;
+pub mod no_where_clause;
+pub mod non_sargable_predicate;
+pub mod positional_reference;
+pub mod redundant_distinct;
+pub mod reserved_keyword_identifier;
+pub mod select_star;
+pub mod select_without_limit;
+
+pub use constant_condition::*;
+pub use implicit_cross_join::*;
+pub use leading_wildcard_like::*;
+pub use no_where_clause::*;
+pub use non_sargable_predicate::*;
+pub use positional_reference::*;
+pub use redundant_distinct::*;
+pub use reserved_keyword_identifier::*;
+pub use select_star::*;
+pub use select_without_limit::*;
+
+use crate::linter::Rule;
+
+/// Rules enabled by default.
+pub fn default_rules() -> Vec<Box<dyn Rule>> {
+    vec![
+        Box::new(NoWhereClause::new()),
+        Box::new(SelectStar::new()),
+        Box::new(ImplicitCrossJoin::new()),
+        Box::new(LeadingWildcardLike::new()),
+        Box::new(NonSargablePredicate::new()),
+        Box::new(PositionalReference::new()),
+        Box::new(RedundantDistinct::new()),
+        Box::new(ConstantCondition::new()),
+    ]
+}
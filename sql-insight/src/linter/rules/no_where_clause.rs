@@ -0,0 +1,158 @@
+//! Rule that flags UPDATE and DELETE statements without a WHERE clause.
+
+use crate::linter::{AllowlistEntry, Diagnostic, Rule, Severity};
+use crate::{CrudTableExtractor, TableReference};
+use sqlparser::ast::Statement;
+
+/// [`NoWhereClause`] flags UPDATE and DELETE statements lacking a WHERE clause, which affect every row of the table.
+/// A diagnostic is reported for each affected table, unless the table is in the allowlist.
+#[derive(Clone, Debug, Default)]
+pub struct NoWhereClause {
+    allowlist: Vec<AllowlistEntry>,
+}
+
+impl NoWhereClause {
+    pub const ID: &'static str = "no-where-clause";
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Tables and schemas that may be updated or deleted without a WHERE clause,
+    /// matched like the allowlists of [`LintConfig`](crate::LintConfig).
+    pub fn with_allowlist(mut self, allowlist: Vec<AllowlistEntry>) -> Self {
+        self.allowlist = allowlist;
+        self
+    }
+
+    fn is_allowed(&self, table: &TableReference) -> bool {
+        self.allowlist.iter().any(|entry| entry.matches(table))
+    }
+}
+
+impl Rule for NoWhereClause {
+    fn id(&self) -> &str {
+        Self::ID
+    }
+
+    fn severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, statement: &Statement) -> Vec<Diagnostic> {
+        let (operation, selection) = match statement {
+            Statement::Update { selection, .. } => ("UPDATE", selection),
+            Statement::Delete { selection, .. } => ("DELETE", selection),
+            _ => return vec![],
+        };
+        if selection.is_some() {
+            return vec![];
+        }
+        let Ok(crud_tables) = CrudTableExtractor::extract_from_statement(statement) else {
+            return vec![];
+        };
+        let tables = match statement {
+            Statement::Update { .. } => crud_tables.update_tables,
+            _ => crud_tables.delete_tables,
+        };
+        tables
+            .into_iter()
+            .filter(|table| !self.is_allowed(table))
+            .map(|table| {
+                Diagnostic::new(
+                    self,
+                    format!(
+                        "{} without WHERE clause affects all rows of {}",
+                        operation, table
+                    ),
+                )
+                .with_tables(vec![table])
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+    use sqlparser::dialect::Dialect;
+    use sqlparser::parser::Parser;
+
+    fn check(rule: &NoWhereClause, dialect: &dyn Dialect, sql: &str) -> Vec<Diagnostic> {
+        Parser::parse_sql(dialect, sql)
+            .unwrap()
+            .iter()
+            .flat_map(|statement| rule.check(statement))
+            .collect()
+    }
+
+    fn table(name: &str) -> TableReference {
+        TableReference {
+            catalog: None,
+            schema: None,
+            name: name.into(),
+            alias: None,
+        }
+    }
+
+    #[test]
+    fn test_update_and_delete_without_where() {
+        let sql = "UPDATE t1 SET a = 1; DELETE FROM t2";
+        for dialect in all_dialects() {
+            let diagnostics = check(&NoWhereClause::new(), dialect.as_ref(), sql);
+            assert_eq!(
+                diagnostics,
+                vec![
+                    Diagnostic {
+                        rule_id: "no-where-clause".into(),
+                        severity: Severity::Error,
+                        message: "UPDATE without WHERE clause affects all rows of t1".into(),
+                        statement_index: 0,
+                        span: None,
+                        tables: vec![table("t1")],
+                        fixable: false,
+                    },
+                    Diagnostic {
+                        rule_id: "no-where-clause".into(),
+                        severity: Severity::Error,
+                        message: "DELETE without WHERE clause affects all rows of t2".into(),
+                        statement_index: 0,
+                        span: None,
+                        tables: vec![table("t2")],
+                        fixable: false,
+                    },
+                ],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_statements_with_where() {
+        let sql = "UPDATE t1 SET a = 1 WHERE b = 2; DELETE FROM t2 WHERE c = 3; SELECT a FROM t3";
+        for dialect in all_dialects() {
+            assert!(check(&NoWhereClause::new(), dialect.as_ref(), sql).is_empty());
+        }
+    }
+
+    #[test]
+    fn test_allowlist() {
+        let sql = "DELETE FROM t1; DELETE FROM s1.t2; DELETE FROM s2.t2; UPDATE tmp.t3 SET a = 1";
+        let mut qualified = table("t2");
+        qualified.schema = Some("s1".into());
+        let rule = NoWhereClause::new().with_allowlist(vec![
+            AllowlistEntry::table("t1"),
+            AllowlistEntry::Table(qualified),
+            AllowlistEntry::schema("tmp"),
+        ]);
+        for dialect in all_dialects() {
+            let diagnostics = check(&rule, dialect.as_ref(), sql);
+            assert_eq!(diagnostics.len(), 1, "Failed for dialect: {dialect:?}");
+            assert_eq!(
+                diagnostics[0].message,
+                "DELETE without WHERE clause affects all rows of s2.t2"
+            );
+        }
+    }
+}
@@ -0,0 +1,72 @@
+//! Rule that flags columns wrapped in a function or arithmetic in filter conditions.
+
+use crate::linter::{Diagnostic, Rule};
+use crate::{ColumnWrapper, NonSargableDetector};
+use sqlparser::ast::Statement;
+
+/// [`NonSargablePredicate`] flags predicates in WHERE, HAVING and join conditions where a column is wrapped in
+/// a function, arithmetic or cast before comparison, e.g. `WHERE LOWER(email) = 'foo'`, since it commonly prevents index use.
+#[derive(Clone, Debug, Default)]
+pub struct NonSargablePredicate;
+
+impl NonSargablePredicate {
+    pub const ID: &'static str = "non-sargable-predicate";
+
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Rule for NonSargablePredicate {
+    fn id(&self) -> &str {
+        Self::ID
+    }
+
+    fn check(&self, statement: &Statement) -> Vec<Diagnostic> {
+        NonSargableDetector::detect_from_statement(statement)
+            .into_iter()
+            .map(|predicate| {
+                let wrapper = match predicate.wrapper {
+                    ColumnWrapper::Function(name) => format!("function {}", name),
+                    ColumnWrapper::Arithmetic(op) => format!("arithmetic {}", op),
+                    ColumnWrapper::Cast => "a cast".to_string(),
+                };
+                Diagnostic::new(
+                    self,
+                    format!(
+                        "Column {} is wrapped in {} in predicate {}, which prevents index usage",
+                        predicate.column, wrapper, predicate.predicate
+                    ),
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+    use sqlparser::parser::Parser;
+
+    #[test]
+    fn test_non_sargable_predicate() {
+        let sql = "SELECT a FROM t1 WHERE LOWER(name) = 'foo' AND b * 2 < 10 AND c = 1";
+        for dialect in all_dialects() {
+            let messages: Vec<String> = Parser::parse_sql(dialect.as_ref(), sql)
+                .unwrap()
+                .iter()
+                .flat_map(|statement| NonSargablePredicate::new().check(statement))
+                .map(|diagnostic| diagnostic.message)
+                .collect();
+            assert_eq!(
+                messages,
+                vec![
+                    "Column name is wrapped in function LOWER in predicate LOWER(name) = 'foo', which prevents index usage",
+                    "Column b is wrapped in arithmetic * in predicate b * 2 < 10, which prevents index usage",
+                ],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,155 @@
+//! Rule that flags positional references in ORDER BY and GROUP BY.
+
+use std::ops::ControlFlow;
+
+use crate::helper;
+use crate::linter::{Diagnostic, Rule};
+use sqlparser::ast::{
+    Expr, GroupByExpr, Query, Select, SelectItem, Statement, Value, Visit, Visitor,
+};
+
+/// [`PositionalReference`] flags ordinals in ORDER BY and GROUP BY such as `ORDER BY 2`,
+/// which silently change meaning when the select list is edited.
+/// The diagnostic message contains the column name the ordinal resolves to, when it can be resolved.
+#[derive(Clone, Debug, Default)]
+pub struct PositionalReference;
+
+impl PositionalReference {
+    pub const ID: &'static str = "positional-reference";
+
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Rule for PositionalReference {
+    fn id(&self) -> &str {
+        Self::ID
+    }
+
+    fn check(&self, statement: &Statement) -> Vec<Diagnostic> {
+        let mut visitor = PositionalReferenceVisitor::default();
+        let _ = statement.visit(&mut visitor);
+        visitor
+            .references
+            .into_iter()
+            .map(|(clause, position, column)| {
+                let message = match column {
+                    Some(column) => format!(
+                        "{} {} refers to {} by position; use the column name instead",
+                        clause, position, column
+                    ),
+                    None => format!(
+                        "{} {} refers to a column by position; use the column name instead",
+                        clause, position
+                    ),
+                };
+                Diagnostic::new(self, message)
+            })
+            .collect()
+    }
+}
+
+#[derive(Default)]
+struct PositionalReferenceVisitor {
+    references: Vec<(&'static str, usize, Option<String>)>,
+}
+
+impl Visitor for PositionalReferenceVisitor {
+    type Break = ();
+
+    fn pre_visit_query(&mut self, query: &Query) -> ControlFlow<Self::Break> {
+        let mut selects = Vec::new();
+        helper::collect_selects(&query.body, &mut selects);
+        for select in &selects {
+            if let GroupByExpr::Expressions(exprs) = &select.group_by {
+                for expr in exprs {
+                    self.inspect("GROUP BY", expr, Some(select));
+                }
+            }
+        }
+        // The result columns of a set operation are named after its leftmost SELECT.
+        for order_by in &query.order_by {
+            self.inspect("ORDER BY", &order_by.expr, selects.first().copied());
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+impl PositionalReferenceVisitor {
+    fn inspect(&mut self, clause: &'static str, expr: &Expr, select: Option<&Select>) {
+        let Some(position) = ordinal(expr) else {
+            return;
+        };
+        let column = select
+            .and_then(|select| select.projection.get(position.checked_sub(1)?))
+            .and_then(|item| match item {
+                SelectItem::UnnamedExpr(expr) => Some(expr.to_string()),
+                SelectItem::ExprWithAlias { alias, .. } => Some(alias.to_string()),
+                _ => None,
+            });
+        self.references.push((clause, position, column));
+    }
+}
+
+fn ordinal(expr: &Expr) -> Option<usize> {
+    match expr {
+        Expr::Value(Value::Number(n, _)) => n.parse().ok(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+    use sqlparser::dialect::Dialect;
+    use sqlparser::parser::Parser;
+
+    fn check(dialect: &dyn Dialect, sql: &str) -> Vec<String> {
+        Parser::parse_sql(dialect, sql)
+            .unwrap()
+            .iter()
+            .flat_map(|statement| PositionalReference::new().check(statement))
+            .map(|diagnostic| diagnostic.message)
+            .collect()
+    }
+
+    #[test]
+    fn test_positional_reference() {
+        let sql = "SELECT a, COUNT(*) AS cnt FROM t1 GROUP BY 1 ORDER BY 2 DESC, a";
+        for dialect in all_dialects() {
+            assert_eq!(
+                check(dialect.as_ref(), sql),
+                vec![
+                    "GROUP BY 1 refers to a by position; use the column name instead",
+                    "ORDER BY 2 refers to cnt by position; use the column name instead",
+                ],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_unresolved_positional_reference() {
+        let sql = "SELECT * FROM t1 ORDER BY 1; SELECT a FROM t1 UNION SELECT b FROM t2 ORDER BY 3";
+        for dialect in all_dialects() {
+            assert_eq!(
+                check(dialect.as_ref(), sql),
+                vec![
+                    "ORDER BY 1 refers to a column by position; use the column name instead",
+                    "ORDER BY 3 refers to a column by position; use the column name instead",
+                ],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_no_positional_reference() {
+        let sql = "SELECT a FROM t1 GROUP BY a ORDER BY a + 1";
+        for dialect in all_dialects() {
+            assert!(check(dialect.as_ref(), sql).is_empty());
+        }
+    }
+}
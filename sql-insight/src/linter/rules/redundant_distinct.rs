@@ -0,0 +1,190 @@
+//! Rule that flags DISTINCT which has no effect on the result.
+
+use std::ops::ControlFlow;
+
+use crate::helper;
+use crate::linter::{Diagnostic, Fix, Rule};
+use sqlparser::ast::{
+    Distinct, Expr, GroupByExpr, Query, Select, SelectItem, SetExpr, Statement, Visit, Visitor,
+    VisitorMut,
+};
+
+/// [`RedundantDistinct`] flags DISTINCT which does not change the result, typically generated by ORMs:
+/// - `SELECT DISTINCT` whose GROUP BY expressions are all selected, since the groups are already distinct.
+/// - `SELECT DISTINCT` inside an EXISTS subquery, since EXISTS only checks whether any row exists.
+///
+/// The fix removes the redundant DISTINCT.
+#[derive(Clone, Debug, Default)]
+pub struct RedundantDistinct;
+
+impl RedundantDistinct {
+    pub const ID: &'static str = "redundant-distinct";
+
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Rule for RedundantDistinct {
+    fn id(&self) -> &str {
+        Self::ID
+    }
+
+    fn check(&self, statement: &Statement) -> Vec<Diagnostic> {
+        let mut visitor = RedundantDistinctVisitor::default();
+        let _ = statement.visit(&mut visitor);
+        visitor
+            .messages
+            .into_iter()
+            .map(|message| Diagnostic::new(self, message))
+            .collect()
+    }
+
+    fn fix(&self, _statement: &Statement) -> Option<Fix> {
+        Some(Fix::new(RedundantDistinctFixer))
+    }
+}
+
+struct RedundantDistinctFixer;
+
+impl VisitorMut for RedundantDistinctFixer {
+    type Break = ();
+
+    fn pre_visit_query(&mut self, query: &mut Query) -> ControlFlow<Self::Break> {
+        let mut selects = Vec::new();
+        helper::collect_selects_mut(&mut query.body, &mut selects);
+        for select in selects {
+            if matches!(select.distinct, Some(Distinct::Distinct)) && groups_are_selected(select) {
+                select.distinct = None;
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_expr(&mut self, expr: &mut Expr) -> ControlFlow<Self::Break> {
+        if let Expr::Exists { subquery, .. } = expr {
+            if let SetExpr::Select(select) = subquery.body.as_mut() {
+                select.distinct = None;
+            }
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+#[derive(Default)]
+struct RedundantDistinctVisitor {
+    messages: Vec<&'static str>,
+}
+
+impl Visitor for RedundantDistinctVisitor {
+    type Break = ();
+
+    fn pre_visit_query(&mut self, query: &Query) -> ControlFlow<Self::Break> {
+        let mut selects = Vec::new();
+        helper::collect_selects(&query.body, &mut selects);
+        for select in selects {
+            if matches!(select.distinct, Some(Distinct::Distinct)) && groups_are_selected(select) {
+                self.messages
+                    .push("DISTINCT is redundant since all GROUP BY expressions are selected");
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_expr(&mut self, expr: &Expr) -> ControlFlow<Self::Break> {
+        if let Expr::Exists { subquery, .. } = expr {
+            if let SetExpr::Select(select) = subquery.body.as_ref() {
+                if select.distinct.is_some() {
+                    self.messages
+                        .push("DISTINCT is redundant inside an EXISTS subquery");
+                }
+            }
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+fn groups_are_selected(select: &Select) -> bool {
+    let GroupByExpr::Expressions(group_by) = &select.group_by else {
+        return false;
+    };
+    !group_by.is_empty()
+        && group_by.iter().all(|group| {
+            select.projection.iter().any(|item| match item {
+                SelectItem::UnnamedExpr(expr) => expr == group,
+                SelectItem::ExprWithAlias { expr, alias } => {
+                    expr == group || matches!(group, Expr::Identifier(ident) if ident == alias)
+                }
+                _ => false,
+            })
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+    use crate::Linter;
+    use sqlparser::dialect::Dialect;
+    use sqlparser::parser::Parser;
+
+    fn check(dialect: &dyn Dialect, sql: &str) -> Vec<String> {
+        Parser::parse_sql(dialect, sql)
+            .unwrap()
+            .iter()
+            .flat_map(|statement| RedundantDistinct::new().check(statement))
+            .map(|diagnostic| diagnostic.message)
+            .collect()
+    }
+
+    #[test]
+    fn test_distinct_with_group_by() {
+        let sql = "SELECT DISTINCT a, b AS c, COUNT(*) FROM t1 GROUP BY a, c; \
+            SELECT DISTINCT a FROM t1 GROUP BY a, b";
+        for dialect in all_dialects() {
+            assert_eq!(
+                check(dialect.as_ref(), sql),
+                vec!["DISTINCT is redundant since all GROUP BY expressions are selected"],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_distinct_in_exists() {
+        let sql = "SELECT a FROM t1 WHERE EXISTS (SELECT DISTINCT b FROM t2 WHERE t2.a = t1.a)";
+        for dialect in all_dialects() {
+            assert_eq!(
+                check(dialect.as_ref(), sql),
+                vec!["DISTINCT is redundant inside an EXISTS subquery"],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_necessary_distinct() {
+        let sql = "SELECT DISTINCT a FROM t1 WHERE b IN (SELECT DISTINCT b FROM t2)";
+        for dialect in all_dialects() {
+            assert!(check(dialect.as_ref(), sql).is_empty());
+        }
+    }
+
+    #[test]
+    fn test_fix() {
+        let sql = "SELECT DISTINCT a, COUNT(*) FROM t1 WHERE EXISTS (SELECT DISTINCT b FROM t2) GROUP BY a; \
+            SELECT DISTINCT a FROM t1";
+        for dialect in all_dialects() {
+            assert_eq!(
+                Linter::new(vec![Box::new(RedundantDistinct::new())])
+                    .fix(dialect.as_ref(), sql)
+                    .unwrap(),
+                vec![
+                    "SELECT a, COUNT(*) FROM t1 WHERE EXISTS (SELECT b FROM t2) GROUP BY a",
+                    "SELECT DISTINCT a FROM t1",
+                ],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,197 @@
+//! Rule that flags unquoted identifiers which are reserved keywords.
+
+use std::ops::ControlFlow;
+
+use crate::helper;
+use crate::linter::{Diagnostic, KeywordDialect, Rule};
+use sqlparser::ast::{
+    Expr, Ident, ObjectName, Query, SelectItem, Statement, TableFactor, Visit, Visitor,
+};
+use sqlparser::dialect::Dialect;
+
+/// [`ReservedKeywordIdentifier`] flags unquoted identifiers, i.e. column, table and alias names,
+/// which are reserved keywords in any of the configured dialects. Such queries break when ported to those dialects.
+///
+/// This rule is not part of [`default_rules`](crate::default_rules) since it depends on the dialect.
+#[derive(Clone, Debug)]
+pub struct ReservedKeywordIdentifier {
+    dialects: Vec<KeywordDialect>,
+}
+
+impl Default for ReservedKeywordIdentifier {
+    fn default() -> Self {
+        Self {
+            dialects: vec![KeywordDialect::Ansi],
+        }
+    }
+}
+
+impl ReservedKeywordIdentifier {
+    pub const ID: &'static str = "reserved-keyword-identifier";
+
+    /// Check against the reserved keywords of the given dialect.
+    pub fn new(dialect: &dyn Dialect) -> Self {
+        Self {
+            dialects: vec![KeywordDialect::of(dialect)],
+        }
+    }
+
+    /// Also check against the reserved keywords of other dialects the queries are meant to be ported to.
+    pub fn with_dialects(mut self, dialects: Vec<KeywordDialect>) -> Self {
+        for dialect in dialects {
+            if !self.dialects.contains(&dialect) {
+                self.dialects.push(dialect);
+            }
+        }
+        self
+    }
+}
+
+impl Rule for ReservedKeywordIdentifier {
+    fn id(&self) -> &str {
+        Self::ID
+    }
+
+    fn check(&self, statement: &Statement) -> Vec<Diagnostic> {
+        let mut collector = IdentCollector::default();
+        let _ = statement.visit(&mut collector);
+        let mut seen: Vec<String> = Vec::new();
+        let mut diagnostics = Vec::new();
+        for ident in collector.idents {
+            if ident.quote_style.is_some() || seen.contains(&ident.value) {
+                continue;
+            }
+            let dialects: Vec<String> = self
+                .dialects
+                .iter()
+                .filter(|dialect| dialect.is_reserved(&ident.value))
+                .map(|dialect| dialect.to_string())
+                .collect();
+            if !dialects.is_empty() {
+                diagnostics.push(Diagnostic::new(
+                    self,
+                    format!(
+                        "Identifier {} is a reserved keyword in {}",
+                        ident.value,
+                        dialects.join(", ")
+                    ),
+                ));
+            }
+            seen.push(ident.value);
+        }
+        diagnostics
+    }
+}
+
+#[derive(Default)]
+struct IdentCollector {
+    idents: Vec<Ident>,
+}
+
+impl IdentCollector {
+    fn push_object_name(&mut self, name: &ObjectName) {
+        self.idents.extend(name.0.iter().cloned());
+    }
+}
+
+impl Visitor for IdentCollector {
+    type Break = ();
+
+    fn pre_visit_query(&mut self, query: &Query) -> ControlFlow<Self::Break> {
+        let mut selects = Vec::new();
+        helper::collect_selects(&query.body, &mut selects);
+        for select in selects {
+            for item in &select.projection {
+                if let SelectItem::ExprWithAlias { alias, .. } = item {
+                    self.idents.push(alias.clone());
+                }
+            }
+        }
+        if let Some(with) = &query.with {
+            for cte in &with.cte_tables {
+                self.idents.push(cte.alias.name.clone());
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_relation(&mut self, relation: &ObjectName) -> ControlFlow<Self::Break> {
+        self.push_object_name(relation);
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_table_factor(&mut self, table_factor: &TableFactor) -> ControlFlow<Self::Break> {
+        if let TableFactor::Table {
+            alias: Some(alias), ..
+        }
+        | TableFactor::Derived {
+            alias: Some(alias), ..
+        } = table_factor
+        {
+            self.idents.push(alias.name.clone());
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_expr(&mut self, expr: &Expr) -> ControlFlow<Self::Break> {
+        match expr {
+            Expr::Identifier(ident) => self.idents.push(ident.clone()),
+            Expr::CompoundIdentifier(idents) => self.idents.extend(idents.iter().cloned()),
+            _ => {}
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+    use sqlparser::dialect::{GenericDialect, MySqlDialect};
+    use sqlparser::parser::Parser;
+
+    fn check(rule: &ReservedKeywordIdentifier, dialect: &dyn Dialect, sql: &str) -> Vec<String> {
+        Parser::parse_sql(dialect, sql)
+            .unwrap()
+            .iter()
+            .flat_map(|statement| rule.check(statement))
+            .map(|diagnostic| diagnostic.message)
+            .collect()
+    }
+
+    #[test]
+    fn test_reserved_keyword_identifier() {
+        let sql = "SELECT rank, t1.key, name FROM t1 WHERE rank > 1";
+        let rule = ReservedKeywordIdentifier::new(&MySqlDialect {});
+        for dialect in all_dialects() {
+            assert_eq!(
+                check(&rule, dialect.as_ref(), sql),
+                vec![
+                    "Identifier rank is a reserved keyword in MySQL",
+                    "Identifier key is a reserved keyword in MySQL"
+                ],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_reserved_keyword_identifier_with_dialects() {
+        let sql = "SELECT a AS rank FROM t1 AS placing";
+        let rule = ReservedKeywordIdentifier::new(&GenericDialect {})
+            .with_dialects(vec![KeywordDialect::MySql, KeywordDialect::PostgreSql]);
+        assert_eq!(
+            check(&rule, &GenericDialect {}, sql),
+            vec![
+                "Identifier rank is a reserved keyword in ANSI, MySQL",
+                "Identifier placing is a reserved keyword in PostgreSQL",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_quoted_identifier_is_not_flagged() {
+        let rule = ReservedKeywordIdentifier::new(&MySqlDialect {});
+        assert!(check(&rule, &GenericDialect {}, r#"SELECT "rank" FROM "key""#).is_empty());
+    }
+}
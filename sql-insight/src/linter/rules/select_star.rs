@@ -0,0 +1,91 @@
+//! Rule that flags wildcard projections.
+
+use crate::linter::{Diagnostic, Rule};
+use crate::WildcardDetector;
+use sqlparser::ast::Statement;
+
+/// [`SelectStar`] flags wildcard projections such as `SELECT *` and `SELECT t1.*`,
+/// which silently change their result when the underlying tables change.
+#[derive(Clone, Debug, Default)]
+pub struct SelectStar {
+    only_with_joins: bool,
+}
+
+impl SelectStar {
+    pub const ID: &'static str = "select-star";
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only flag wildcards in SELECTs reading from multiple tables, where the result columns are hardest to follow.
+    pub fn with_only_with_joins(mut self, only_with_joins: bool) -> Self {
+        self.only_with_joins = only_with_joins;
+        self
+    }
+}
+
+impl Rule for SelectStar {
+    fn id(&self) -> &str {
+        Self::ID
+    }
+
+    fn check(&self, statement: &Statement) -> Vec<Diagnostic> {
+        WildcardDetector::detect_from_statement(statement)
+            .into_iter()
+            .filter(|wildcard| !self.only_with_joins || wildcard.table_count > 1)
+            .map(|wildcard| match wildcard.qualifier {
+                Some(qualifier) => {
+                    Diagnostic::new(self, format!("Wildcard projection {}.* is used", qualifier))
+                }
+                None => Diagnostic::new(self, "Wildcard projection * is used"),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+    use sqlparser::dialect::Dialect;
+    use sqlparser::parser::Parser;
+
+    fn check(rule: &SelectStar, dialect: &dyn Dialect, sql: &str) -> Vec<String> {
+        Parser::parse_sql(dialect, sql)
+            .unwrap()
+            .iter()
+            .flat_map(|statement| rule.check(statement))
+            .map(|diagnostic| diagnostic.message)
+            .collect()
+    }
+
+    #[test]
+    fn test_select_star() {
+        let sql =
+            "SELECT * FROM t1; SELECT t1.* FROM t1 JOIN t2 ON t1.id = t2.id; SELECT a FROM t3";
+        for dialect in all_dialects() {
+            assert_eq!(
+                check(&SelectStar::new(), dialect.as_ref(), sql),
+                vec![
+                    "Wildcard projection * is used",
+                    "Wildcard projection t1.* is used"
+                ],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_select_star_only_with_joins() {
+        let sql = "SELECT * FROM t1; SELECT * FROM t1, t2; SELECT COUNT(*) FROM t1 JOIN t2 ON t1.id = t2.id";
+        let rule = SelectStar::new().with_only_with_joins(true);
+        for dialect in all_dialects() {
+            assert_eq!(
+                check(&rule, dialect.as_ref(), sql),
+                vec!["Wildcard projection * is used"],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,109 @@
+//! Rule that flags top-level SELECTs without a row limit.
+
+use crate::linter::{Diagnostic, Rule};
+use sqlparser::ast::{Query, SetExpr, Statement};
+
+/// [`SelectWithoutLimit`] flags top-level SELECTs with no `LIMIT`, `FETCH` or `TOP`,
+/// which is useful for gating ad-hoc queries that may return unbounded result sets.
+///
+/// This rule is not part of [`default_rules`](crate::default_rules) and has to be added explicitly.
+/// SELECTs without a FROM clause, e.g. `SELECT 1`, are not flagged.
+#[derive(Clone, Debug, Default)]
+pub struct SelectWithoutLimit;
+
+impl SelectWithoutLimit {
+    pub const ID: &'static str = "select-without-limit";
+
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Rule for SelectWithoutLimit {
+    fn id(&self) -> &str {
+        Self::ID
+    }
+
+    fn check(&self, statement: &Statement) -> Vec<Diagnostic> {
+        match statement {
+            Statement::Query(query) if reads_tables(&query.body) && !is_limited(query) => {
+                vec![Diagnostic::new(
+                    self,
+                    "SELECT without LIMIT may return unbounded rows",
+                )]
+            }
+            _ => vec![],
+        }
+    }
+}
+
+pub(crate) fn is_limited(query: &Query) -> bool {
+    query.limit.is_some()
+        || query.fetch.is_some()
+        || match query.body.as_ref() {
+            SetExpr::Query(query) => is_limited(query),
+            SetExpr::Select(select) => select.top.is_some(),
+            _ => false,
+        }
+}
+
+pub(crate) fn reads_tables(set_expr: &SetExpr) -> bool {
+    match set_expr {
+        SetExpr::Select(select) => !select.from.is_empty(),
+        SetExpr::Query(query) => reads_tables(&query.body),
+        SetExpr::SetOperation { left, right, .. } => reads_tables(left) || reads_tables(right),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+    use sqlparser::dialect::{Dialect, MsSqlDialect};
+    use sqlparser::parser::Parser;
+
+    fn check(dialect: &dyn Dialect, sql: &str) -> Vec<String> {
+        Parser::parse_sql(dialect, sql)
+            .unwrap()
+            .iter()
+            .flat_map(|statement| SelectWithoutLimit::new().check(statement))
+            .map(|diagnostic| diagnostic.message)
+            .collect()
+    }
+
+    #[test]
+    fn test_select_without_limit() {
+        let sql = "SELECT a FROM t1; SELECT a FROM t1 UNION SELECT a FROM t2; SELECT 1";
+        for dialect in all_dialects() {
+            assert_eq!(
+                check(dialect.as_ref(), sql),
+                vec![
+                    "SELECT without LIMIT may return unbounded rows",
+                    "SELECT without LIMIT may return unbounded rows"
+                ],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_select_with_limit() {
+        let sql = "SELECT a FROM t1 LIMIT 10; \
+            SELECT a FROM t1 UNION SELECT a FROM t2 LIMIT 10; \
+            SELECT a FROM t1 WHERE b IN (SELECT b FROM t2); \
+            INSERT INTO t1 SELECT a FROM t2";
+        for dialect in all_dialects() {
+            assert_eq!(
+                check(dialect.as_ref(), sql),
+                vec!["SELECT without LIMIT may return unbounded rows"],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_select_with_top() {
+        assert!(check(&MsSqlDialect {}, "SELECT TOP 10 a FROM t1").is_empty());
+    }
+}
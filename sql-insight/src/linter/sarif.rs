@@ -0,0 +1,203 @@
+//! SARIF output of lint diagnostics.
+//!
+//! [SARIF](https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html) is the format read by GitHub code scanning
+//! and other CI systems to show findings inline. Only the subset of the format needed to report diagnostics is modeled.
+
+use crate::linter::{Diagnostic, Rule, Severity};
+
+pub const SARIF_VERSION: &str = "2.1.0";
+pub const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// [`SarifLog`] is the root object of a SARIF file.
+#[derive(Clone, Debug, PartialEq, serde::Serialize)]
+pub struct SarifLog {
+    #[serde(rename = "$schema")]
+    pub schema: String,
+    pub version: String,
+    pub runs: Vec<SarifRun>,
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Serialize)]
+pub struct SarifRun {
+    pub tool: SarifTool,
+    pub results: Vec<SarifResult>,
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Serialize)]
+pub struct SarifTool {
+    pub driver: SarifDriver,
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifDriver {
+    pub name: String,
+    pub version: String,
+    pub information_uri: String,
+    pub rules: Vec<SarifRule>,
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifRule {
+    pub id: String,
+    pub default_configuration: SarifConfiguration,
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Serialize)]
+pub struct SarifConfiguration {
+    pub level: String,
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifResult {
+    pub rule_id: String,
+    pub level: String,
+    pub message: SarifMessage,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub locations: Vec<SarifLocation>,
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Serialize)]
+pub struct SarifMessage {
+    pub text: String,
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifLocation {
+    pub physical_location: SarifPhysicalLocation,
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifPhysicalLocation {
+    pub artifact_location: SarifArtifactLocation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub region: Option<SarifRegion>,
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Serialize)]
+pub struct SarifArtifactLocation {
+    pub uri: String,
+}
+
+/// Region of a result. Columns are one-based and `end_column` is exclusive, the same as [`Span`](crate::span::Span).
+#[derive(Clone, Debug, PartialEq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifRegion {
+    pub start_line: u64,
+    pub start_column: u64,
+    pub end_line: u64,
+    pub end_column: u64,
+}
+
+impl SarifLog {
+    /// Build a SARIF log from diagnostics reported by `rules` for the file at `uri`.
+    /// Diagnostics without a span are reported at the file level when `uri` is given, and without a location otherwise.
+    pub fn new(rules: &[Box<dyn Rule>], diagnostics: &[Diagnostic], uri: Option<&str>) -> Self {
+        let rules = rules
+            .iter()
+            .map(|rule| SarifRule {
+                id: rule.id().to_string(),
+                default_configuration: SarifConfiguration {
+                    level: level(rule.severity()).to_string(),
+                },
+            })
+            .collect();
+        let results = diagnostics
+            .iter()
+            .map(|diagnostic| SarifResult {
+                rule_id: diagnostic.rule_id.clone(),
+                level: level(diagnostic.severity).to_string(),
+                message: SarifMessage {
+                    text: diagnostic.message.clone(),
+                },
+                locations: uri
+                    .map(|uri| SarifLocation {
+                        physical_location: SarifPhysicalLocation {
+                            artifact_location: SarifArtifactLocation {
+                                uri: uri.to_string(),
+                            },
+                            region: diagnostic.span.map(|span| SarifRegion {
+                                start_line: span.start.line,
+                                start_column: span.start.column,
+                                end_line: span.end.line,
+                                end_column: span.end.column,
+                            }),
+                        },
+                    })
+                    .into_iter()
+                    .collect(),
+            })
+            .collect();
+        SarifLog {
+            schema: SARIF_SCHEMA.to_string(),
+            version: SARIF_VERSION.to_string(),
+            runs: vec![SarifRun {
+                tool: SarifTool {
+                    driver: SarifDriver {
+                        name: env!("CARGO_PKG_NAME").to_string(),
+                        version: env!("CARGO_PKG_VERSION").to_string(),
+                        information_uri: env!("CARGO_PKG_REPOSITORY").to_string(),
+                        rules,
+                    },
+                },
+                results,
+            }],
+        }
+    }
+}
+
+fn level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Info => "note",
+        Severity::Warning => "warning",
+        Severity::Error => "error",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Linter, NoWhereClause};
+    use sqlparser::dialect::GenericDialect;
+
+    #[test]
+    fn test_sarif_log() {
+        let linter = Linter::new(vec![Box::new(NoWhereClause::new())]);
+        let diagnostics = linter
+            .lint(&GenericDialect {}, "SELECT a FROM t1;\nDELETE FROM t2")
+            .unwrap();
+        let log = SarifLog::new(linter.rules(), &diagnostics, Some("queries/cleanup.sql"));
+        assert_eq!(
+            serde_json::to_value(&log).unwrap(),
+            serde_json::json!({
+                "$schema": SARIF_SCHEMA,
+                "version": "2.1.0",
+                "runs": [{
+                    "tool": {
+                        "driver": {
+                            "name": "sql-insight",
+                            "version": env!("CARGO_PKG_VERSION"),
+                            "informationUri": env!("CARGO_PKG_REPOSITORY"),
+                            "rules": [{ "id": "no-where-clause", "defaultConfiguration": { "level": "error" } }]
+                        }
+                    },
+                    "results": [{
+                        "ruleId": "no-where-clause",
+                        "level": "error",
+                        "message": { "text": "DELETE without WHERE clause affects all rows of t2" },
+                        "locations": [{
+                            "physicalLocation": {
+                                "artifactLocation": { "uri": "queries/cleanup.sql" },
+                                "region": { "startLine": 2, "startColumn": 1, "endLine": 2, "endColumn": 15 }
+                            }
+                        }]
+                    }]
+                }]
+            })
+        );
+    }
+}
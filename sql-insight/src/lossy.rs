@@ -0,0 +1,192 @@
+//! Error-recovering analysis that keeps going past statements failing to parse.
+//!
+//! See [`analyze_lossy`](crate::analyze_lossy()) as the entry point for analyzing SQL with partial results.
+
+use crate::error::Error;
+use crate::instrument;
+use crate::normalizer::{Normalizer, NormalizerOptions};
+use crate::report::Report;
+use crate::span::Span;
+use crate::splitter::split_statements;
+use sqlparser::ast::{Statement, VisitMut};
+use sqlparser::dialect::Dialect;
+
+/// Convenience function to analyze SQL statement by statement with `analyze`, recovering from errors.
+///
+/// Unlike the other entry points, which fail as a whole when any statement fails to parse, the SQL is split
+/// into statements before parsing, so a statement failing to parse becomes an entry of its own with the error
+/// and its raw SQL, and the remaining statements are still analyzed.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+/// use sql_insight::TableExtractor;
+///
+/// let dialect = GenericDialect {};
+/// let sql = "SELECT a FROM t1;\nSELEC garbage;\nSELECT b FROM t2";
+/// let entries = sql_insight::analyze_lossy(&dialect, sql, |statement| {
+///     Ok(TableExtractor::extract_from_statement(statement)?.to_string())
+/// });
+/// assert_eq!(entries[0].result, Ok("t1".to_string()));
+/// assert_eq!(entries[1].sql, "SELEC garbage");
+/// assert_eq!(entries[1].span.start.line, 2);
+/// assert!(entries[1].result.is_err());
+/// assert_eq!(entries[2].result, Ok("t2".to_string()));
+/// ```
+pub fn analyze_lossy<T, F>(dialect: &dyn Dialect, sql: &str, mut analyze: F) -> Vec<LossyEntry<T>>
+where
+    F: FnMut(&Statement) -> Result<T, Error>,
+{
+    let mut entries = vec![];
+    for (sql, span) in split_statements(dialect, sql) {
+        match instrument::parse_sql(dialect, &sql) {
+            Ok(statements) => entries.extend(statements.iter().map(|statement| LossyEntry {
+                sql: sql.clone(),
+                span,
+                result: analyze(statement),
+            })),
+            Err(e) => entries.push(LossyEntry {
+                sql,
+                span,
+                result: Err(e),
+            }),
+        }
+    }
+    entries
+}
+
+/// Convenience function to normalize SQL with options, recovering from errors.
+/// See [`analyze_lossy`] for how errors are recovered from.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+/// use sql_insight::NormalizerOptions;
+///
+/// let dialect = GenericDialect {};
+/// let sql = "SELECT a FROM t1 WHERE b = 1; SELEC garbage; DELETE FROM t2 WHERE c = 'x'";
+/// let entries = sql_insight::normalize_lossy(&dialect, sql, NormalizerOptions::new());
+/// assert_eq!(entries[0].result, Ok("SELECT a FROM t1 WHERE b = ?".to_string()));
+/// assert!(entries[1].result.is_err());
+/// assert_eq!(entries[2].result, Ok("DELETE FROM t2 WHERE c = ?".to_string()));
+/// ```
+pub fn normalize_lossy(
+    dialect: &dyn Dialect,
+    sql: &str,
+    options: NormalizerOptions,
+) -> Vec<LossyEntry<String>> {
+    let mut normalizer = Normalizer::new().with_options(options);
+    analyze_lossy(dialect, sql, |statement| {
+        instrument::analyze("normalize", || {
+            let mut statement = statement.clone();
+            let _ = statement.visit(&mut normalizer);
+            Ok(statement.to_string())
+        })
+    })
+}
+
+/// [`LossyEntry`] represents the result of analyzing a statement, or the error of the statement failing to parse,
+/// together with its raw SQL and span in the input.
+#[derive(Debug, PartialEq)]
+pub struct LossyEntry<T> {
+    /// The raw SQL of the statement as split from the input.
+    pub sql: String,
+    pub span: Span,
+    pub result: Result<T, Error>,
+}
+
+impl<T> From<Vec<LossyEntry<T>>> for Report<T> {
+    fn from(entries: Vec<LossyEntry<T>>) -> Self {
+        Report::from_results(entries.into_iter().map(|entry| entry.result).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::span::Location;
+    use crate::test_utils::all_dialects;
+
+    #[test]
+    fn test_analyze_lossy() {
+        let sql = "SELECT a FROM t1;\nSELEC b FROM t2;\nDELETE FROM t3";
+        for dialect in all_dialects() {
+            let entries =
+                analyze_lossy(dialect.as_ref(), sql, |statement| Ok(statement.to_string()));
+            assert_eq!(entries.len(), 3, "Failed for dialect: {dialect:?}");
+            assert_eq!(
+                entries[0],
+                LossyEntry {
+                    sql: "SELECT a FROM t1".into(),
+                    span: Span::new(Location::new(1, 1), Location::new(1, 17)),
+                    result: Ok("SELECT a FROM t1".into()),
+                },
+                "Failed for dialect: {dialect:?}"
+            );
+            assert_eq!(
+                entries[1].sql, "SELEC b FROM t2",
+                "Failed for dialect: {dialect:?}"
+            );
+            assert_eq!(
+                entries[1].span,
+                Span::new(Location::new(2, 1), Location::new(2, 16)),
+                "Failed for dialect: {dialect:?}"
+            );
+            assert!(
+                matches!(entries[1].result, Err(Error::ParserError(_))),
+                "Failed for dialect: {dialect:?}"
+            );
+            assert_eq!(
+                entries[2].result,
+                Ok("DELETE FROM t3".into()),
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_analyze_lossy_analysis_error() {
+        for dialect in all_dialects() {
+            let entries = analyze_lossy(dialect.as_ref(), "SELECT 1; SELECT 2", |statement| {
+                if statement.to_string() == "SELECT 1" {
+                    Err(Error::AnalysisError("failed".into()))
+                } else {
+                    Ok(())
+                }
+            });
+            let report = Report::from(entries);
+            assert_eq!(
+                report.summary.succeeded, 1,
+                "Failed for dialect: {dialect:?}"
+            );
+            assert_eq!(report.summary.failed, 1, "Failed for dialect: {dialect:?}");
+        }
+    }
+
+    #[test]
+    fn test_normalize_lossy() {
+        let sql = "SELECT a FROM t1 WHERE b IN (1, 2); SELEC b FROM t3; INSERT INTO t2 (a) VALUES (1, 'x')";
+        for dialect in all_dialects() {
+            let entries = normalize_lossy(
+                dialect.as_ref(),
+                sql,
+                NormalizerOptions::new().with_unify_in_list(true),
+            );
+            let results = entries
+                .into_iter()
+                .map(|entry| entry.result.map_err(|_| entry.sql))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                results,
+                vec![
+                    Ok("SELECT a FROM t1 WHERE b IN (...)".into()),
+                    Err("SELEC b FROM t3".into()),
+                    Ok("INSERT INTO t2 (a) VALUES (?, ?)".into()),
+                ],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+}
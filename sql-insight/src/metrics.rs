@@ -0,0 +1,369 @@
+//! Metrics of analysis for services built on this crate, exposed in the Prometheus text format.
+//!
+//! See [`Metrics`] for recording metrics and [`serve_metrics`](crate::serve_metrics()) for the `/metrics` endpoint.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use crate::error::Error;
+use sqlparser::ast::Statement;
+use sqlparser::dialect::{
+    AnsiDialect, BigQueryDialect, ClickHouseDialect, Dialect, DuckDbDialect, GenericDialect,
+    HiveDialect, MsSqlDialect, MySqlDialect, PostgreSqlDialect, RedshiftSqlDialect, SQLiteDialect,
+    SnowflakeDialect,
+};
+use sqlparser::parser::Parser;
+
+/// Upper bounds of the buckets of latency histograms in seconds.
+const LATENCY_BUCKETS: [f64; 10] = [
+    0.0001, 0.00025, 0.0005, 0.001, 0.0025, 0.005, 0.01, 0.05, 0.1, 1.0,
+];
+
+/// Timeout of reading a request from and writing a response to a connection of [`serve_metrics`].
+const CONNECTION_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Maximum length in bytes of a request line read by [`serve_metrics`], beyond which the request is not found.
+const MAX_REQUEST_LINE_LENGTH: u64 = 8192;
+
+/// [`Metrics`] records counters of parsed and analyzed statements and latencies of analyzers.
+/// It is shared across threads, e.g. with an [`Arc`], by request handlers and [`serve_metrics`].
+///
+/// - `sql_insight_statements_parsed_total`: statements parsed successfully.
+/// - `sql_insight_parse_failures_total{dialect}`: inputs failing to parse, per dialect named as in
+///   [`check_compatibility`](crate::check_compatibility()), e.g. `mysql`.
+/// - `sql_insight_statements_analyzed_total{analyzer}`: statements analyzed, per analyzer.
+/// - `sql_insight_analyzer_duration_seconds{analyzer}`: histogram of the latency of analyzing a statement, per analyzer.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+/// use sql_insight::{Metrics, TableExtractor};
+///
+/// let metrics = Metrics::new();
+/// let statements = metrics.parse(&GenericDialect {}, "SELECT a FROM t1").unwrap();
+/// for statement in &statements {
+///     metrics.analyze("extract_tables", statement, TableExtractor::extract_from_statement).unwrap();
+/// }
+/// assert!(metrics.parse(&GenericDialect {}, "SELEC").is_err());
+/// let text = metrics.render();
+/// assert!(text.contains("sql_insight_statements_parsed_total 1\n"));
+/// assert!(text.contains("sql_insight_parse_failures_total{dialect=\"generic\"} 1\n"));
+/// assert!(text.contains("sql_insight_analyzer_duration_seconds_count{analyzer=\"extract_tables\"} 1\n"));
+/// ```
+#[derive(Debug, Default)]
+pub struct Metrics {
+    state: Mutex<MetricsState>,
+}
+
+#[derive(Debug, Default)]
+struct MetricsState {
+    statements_parsed: u64,
+    parse_failures: BTreeMap<String, u64>,
+    latencies: BTreeMap<String, Histogram>,
+}
+
+#[derive(Debug, Default)]
+struct Histogram {
+    /// Counts of observations in each bucket, not cumulative.
+    buckets: [u64; LATENCY_BUCKETS.len()],
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    fn observe(&mut self, seconds: f64) {
+        if let Some(index) = LATENCY_BUCKETS.iter().position(|bound| seconds <= *bound) {
+            self.buckets[index] += 1;
+        }
+        self.sum += seconds;
+        self.count += 1;
+    }
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse SQL, counting the parsed statements, or a failure of the dialect if it fails to parse.
+    pub fn parse(&self, dialect: &dyn Dialect, sql: &str) -> Result<Vec<Statement>, Error> {
+        match Parser::parse_sql(dialect, sql) {
+            Ok(statements) => {
+                self.state().statements_parsed += statements.len() as u64;
+                Ok(statements)
+            }
+            Err(e) => {
+                self.record_parse_failure(&dialect_name(dialect));
+                Err(e.into())
+            }
+        }
+    }
+
+    /// Analyze a statement with `analyze`, recording its latency under the name of the analyzer.
+    pub fn analyze<T, F>(&self, analyzer: &str, statement: &Statement, analyze: F) -> T
+    where
+        F: FnOnce(&Statement) -> T,
+    {
+        let start = Instant::now();
+        let result = analyze(statement);
+        self.record_latency(analyzer, start.elapsed());
+        result
+    }
+
+    pub fn record_parse_failure(&self, dialect_name: &str) {
+        *self
+            .state()
+            .parse_failures
+            .entry(dialect_name.to_string())
+            .or_default() += 1;
+    }
+
+    pub fn record_latency(&self, analyzer: &str, latency: Duration) {
+        self.state()
+            .latencies
+            .entry(analyzer.to_string())
+            .or_default()
+            .observe(latency.as_secs_f64());
+    }
+
+    /// Metrics in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let state = self.state();
+        let mut text = String::new();
+        // Writing into a String never fails.
+        let _ = writeln!(
+            text,
+            "# HELP sql_insight_statements_parsed_total Statements parsed successfully.\n\
+             # TYPE sql_insight_statements_parsed_total counter\n\
+             sql_insight_statements_parsed_total {}",
+            state.statements_parsed
+        );
+        let _ = writeln!(
+            text,
+            "# HELP sql_insight_parse_failures_total Inputs failing to parse.\n\
+             # TYPE sql_insight_parse_failures_total counter"
+        );
+        for (dialect, count) in &state.parse_failures {
+            let _ = writeln!(
+                text,
+                "sql_insight_parse_failures_total{{dialect=\"{}\"}} {}",
+                escape_label(dialect),
+                count
+            );
+        }
+        let _ = writeln!(
+            text,
+            "# HELP sql_insight_statements_analyzed_total Statements analyzed.\n\
+             # TYPE sql_insight_statements_analyzed_total counter"
+        );
+        for (analyzer, histogram) in &state.latencies {
+            let _ = writeln!(
+                text,
+                "sql_insight_statements_analyzed_total{{analyzer=\"{}\"}} {}",
+                escape_label(analyzer),
+                histogram.count
+            );
+        }
+        let _ = writeln!(
+            text,
+            "# HELP sql_insight_analyzer_duration_seconds Latency of analyzing a statement.\n\
+             # TYPE sql_insight_analyzer_duration_seconds histogram"
+        );
+        for (analyzer, histogram) in &state.latencies {
+            let analyzer = escape_label(analyzer);
+            let mut cumulative = 0;
+            for (bound, count) in LATENCY_BUCKETS.iter().zip(histogram.buckets) {
+                cumulative += count;
+                let _ = writeln!(
+                    text,
+                    "sql_insight_analyzer_duration_seconds_bucket{{analyzer=\"{}\",le=\"{}\"}} {}",
+                    analyzer, bound, cumulative
+                );
+            }
+            let _ = writeln!(
+                text,
+                "sql_insight_analyzer_duration_seconds_bucket{{analyzer=\"{}\",le=\"+Inf\"}} {}\n\
+                 sql_insight_analyzer_duration_seconds_sum{{analyzer=\"{}\"}} {}\n\
+                 sql_insight_analyzer_duration_seconds_count{{analyzer=\"{}\"}} {}",
+                analyzer, histogram.count, analyzer, histogram.sum, analyzer, histogram.count
+            );
+        }
+        text
+    }
+
+    fn state(&self) -> MutexGuard<'_, MetricsState> {
+        // Metrics stay usable even if a thread panicked while recording.
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// The name of a dialect accepted by [`dialect_from_str`](sqlparser::dialect::dialect_from_str),
+/// or its debug representation for a dialect of its own.
+fn dialect_name(dialect: &dyn Dialect) -> String {
+    let names = [
+        ("generic", dialect.is::<GenericDialect>()),
+        ("mysql", dialect.is::<MySqlDialect>()),
+        ("postgres", dialect.is::<PostgreSqlDialect>()),
+        ("hive", dialect.is::<HiveDialect>()),
+        ("sqlite", dialect.is::<SQLiteDialect>()),
+        ("snowflake", dialect.is::<SnowflakeDialect>()),
+        ("redshift", dialect.is::<RedshiftSqlDialect>()),
+        ("mssql", dialect.is::<MsSqlDialect>()),
+        ("clickhouse", dialect.is::<ClickHouseDialect>()),
+        ("bigquery", dialect.is::<BigQueryDialect>()),
+        ("ansi", dialect.is::<AnsiDialect>()),
+        ("duckdb", dialect.is::<DuckDbDialect>()),
+    ];
+    names
+        .iter()
+        .find(|(_, is)| *is)
+        .map_or_else(|| format!("{dialect:?}"), |(name, _)| name.to_string())
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Serve `GET /metrics` with the metrics in the Prometheus text format on connections accepted by `listener`,
+/// responding 404 to other requests. Each connection is handled on a thread of its own with a timeout of reading
+/// and writing and a bounded length of the request line, so that a stalled client doesn't block the others.
+/// Connections failing to be accepted are skipped, and this blocks serving forever, so run it on a thread of its own.
+///
+/// ## Example
+///
+/// ```rust,no_run
+/// use std::net::TcpListener;
+/// use std::sync::Arc;
+/// use sql_insight::Metrics;
+///
+/// let metrics = Arc::new(Metrics::new());
+/// let listener = TcpListener::bind("127.0.0.1:9090").unwrap();
+/// let served = Arc::clone(&metrics);
+/// std::thread::spawn(move || sql_insight::serve_metrics(listener, served));
+/// ```
+pub fn serve_metrics(listener: TcpListener, metrics: Arc<Metrics>) -> Result<(), Error> {
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else {
+            continue;
+        };
+        let metrics = Arc::clone(&metrics);
+        std::thread::spawn(move || serve_connection(stream, &metrics));
+    }
+    Ok(())
+}
+
+fn serve_connection(mut stream: TcpStream, metrics: &Metrics) {
+    if stream.set_read_timeout(Some(CONNECTION_TIMEOUT)).is_err()
+        || stream.set_write_timeout(Some(CONNECTION_TIMEOUT)).is_err()
+    {
+        return;
+    }
+    let mut request_line = String::new();
+    if BufReader::new((&stream).take(MAX_REQUEST_LINE_LENGTH))
+        .read_line(&mut request_line)
+        .is_err()
+    {
+        return;
+    }
+    let mut parts = request_line.split_whitespace();
+    let response = match (parts.next(), parts.next()) {
+        (Some("GET"), Some("/metrics")) => {
+            let body = metrics.render();
+            format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+        }
+        _ => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string(),
+    };
+    // A client disconnecting early doesn't stop serving others.
+    let _ = stream.write_all(response.as_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+
+    #[test]
+    fn test_render() {
+        let metrics = Metrics::new();
+        for dialect in all_dialects() {
+            metrics
+                .parse(dialect.as_ref(), "SELECT a FROM t1; SELECT b FROM t2")
+                .unwrap();
+        }
+        for dialect in all_dialects() {
+            assert!(metrics.parse(dialect.as_ref(), "SELEC").is_err());
+        }
+        metrics.record_parse_failure("mysql");
+        metrics.record_latency("normalize", Duration::from_micros(200));
+        metrics.record_latency("normalize", Duration::from_secs(2));
+        let text = metrics.render();
+        let statements = 2 * all_dialects().len();
+        assert!(text.contains(&format!(
+            "sql_insight_statements_parsed_total {statements}\n"
+        )));
+        for line in [
+            "sql_insight_parse_failures_total{dialect=\"mysql\"} 2\n",
+            "sql_insight_parse_failures_total{dialect=\"postgres\"} 1\n",
+            "sql_insight_parse_failures_total{dialect=\"duckdb\"} 1\n",
+            "sql_insight_statements_analyzed_total{analyzer=\"normalize\"} 2\n",
+            "sql_insight_analyzer_duration_seconds_bucket{analyzer=\"normalize\",le=\"0.0001\"} 0\n",
+            "sql_insight_analyzer_duration_seconds_bucket{analyzer=\"normalize\",le=\"0.00025\"} 1\n",
+            "sql_insight_analyzer_duration_seconds_bucket{analyzer=\"normalize\",le=\"1\"} 1\n",
+            "sql_insight_analyzer_duration_seconds_bucket{analyzer=\"normalize\",le=\"+Inf\"} 2\n",
+            "sql_insight_analyzer_duration_seconds_count{analyzer=\"normalize\"} 2\n",
+        ] {
+            assert!(text.contains(line), "{line} not in {text}");
+        }
+    }
+
+    #[test]
+    fn test_serve_metrics() {
+        let metrics = Arc::new(Metrics::new());
+        metrics.record_parse_failure("generic");
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let served = Arc::clone(&metrics);
+        std::thread::spawn(move || serve_metrics(listener, served));
+        let request = |path: &str| {
+            let mut stream = TcpStream::connect(address).unwrap();
+            write!(stream, "GET {path} HTTP/1.1\r\nHost: localhost\r\n\r\n").unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        };
+        let response = request("/metrics");
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.contains("sql_insight_parse_failures_total{dialect=\"generic\"} 1\n"));
+        assert!(request("/other").starts_with("HTTP/1.1 404 Not Found\r\n"));
+    }
+
+    #[test]
+    fn test_serve_metrics_with_stalled_client() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let metrics = Arc::new(Metrics::new());
+        std::thread::spawn(move || serve_metrics(listener, metrics));
+        let mut stalled = TcpStream::connect(address).unwrap();
+        write!(stalled, "GET /met").unwrap();
+        let started = Instant::now();
+        let mut stream = TcpStream::connect(address).unwrap();
+        write!(stream, "GET /metrics HTTP/1.1\r\n\r\n").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(started.elapsed() < CONNECTION_TIMEOUT);
+        drop(stalled);
+    }
+}
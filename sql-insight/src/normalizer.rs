@@ -2,13 +2,16 @@
 //!
 //! See [`normalize`](crate::normalize()) as the entry point for normalizing SQL.
 
-use std::ops::ControlFlow;
+use std::fmt::Write;
+use std::ops::{ControlFlow, Range};
 
 use crate::error::Error;
-use sqlparser::ast::{Expr, VisitMut, VisitorMut};
-use sqlparser::ast::{Query, SetExpr, Value};
+use crate::instrument;
+use crate::prepared::ParamType;
+use crate::rewriter::PlaceholderStyle;
+use sqlparser::ast::{visit_expressions, Expr, VisitMut, VisitorMut};
+use sqlparser::ast::{Query, SetExpr, Statement, Value};
 use sqlparser::dialect::Dialect;
-use sqlparser::parser::Parser;
 use std::ops::DerefMut;
 
 /// Convenience function to normalize SQL with default options.
@@ -48,6 +51,44 @@ pub fn normalize_with_options(
     Normalizer::normalize(dialect, sql, options)
 }
 
+/// Convenience function to normalize SQL with options, collecting the literal values replaced in each statement.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+/// use sql_insight::{NormalizerOptions, ParamType};
+///
+/// let dialect = GenericDialect {};
+/// let sql = "SELECT a FROM t1 WHERE b = 1 AND c = 'x'";
+/// let result = sql_insight::normalize_with_literals(&dialect, sql, NormalizerOptions::new()).unwrap();
+/// let (normalized, literals) = &result[0];
+/// assert_eq!(normalized, "SELECT a FROM t1 WHERE b = ? AND c = ?");
+/// assert_eq!(literals[1].position, 2);
+/// assert_eq!(literals[1].literal_type, ParamType::String);
+/// assert_eq!(literals[1].text, "'x'");
+/// ```
+pub fn normalize_with_literals(
+    dialect: &dyn Dialect,
+    sql: &str,
+    options: NormalizerOptions,
+) -> Result<Vec<(String, Vec<ExtractedLiteral>)>, Error> {
+    Normalizer::normalize_with_literals(dialect, sql, options)
+}
+
+/// [`ExtractedLiteral`] represents a literal value replaced by the [`Normalizer`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExtractedLiteral {
+    /// One-based position of the literal among the literals of the statement, in order of appearance.
+    /// Literals of unified IN and VALUES lists are included, so it may differ from the number of the placeholder.
+    pub position: usize,
+    /// Type of the literal, inferred from its value.
+    pub literal_type: ParamType,
+    /// The literal as written in SQL, e.g. `'x'` or `1.5`.
+    pub text: String,
+}
+
 /// Options for normalizing SQL.
 #[derive(Default, Clone)]
 pub struct NormalizerOptions {
@@ -57,6 +98,14 @@ pub struct NormalizerOptions {
     /// Unify VALUES lists to a single form when all elements are literal values.
     /// For example, `VALUES (1, 2, 3), (4, 5, 6)` becomes `VALUES (...)`.
     pub unify_values: bool,
+    /// Style of placeholders literal values are replaced with. Numbered and named placeholders are numbered
+    /// in order of appearance within each statement, e.g. `$1, $2` or `:p1, :p2`. Defaults to `?`.
+    pub placeholder_style: PlaceholderStyle,
+    /// Keep placeholders already in SQL, e.g. `?`, `$1` or `:name`, as written instead of replacing them,
+    /// so already-parameterized queries normalize to the same shape as written. Numbered and named placeholders
+    /// literal values are replaced with are then numbered after the highest kept `$n` or `:pN`,
+    /// e.g. `a = 1 AND b = $1` becomes `a = $2 AND b = $1`, so that they never collide with the kept ones.
+    pub keep_placeholders: bool,
 }
 
 impl NormalizerOptions {
@@ -73,18 +122,73 @@ impl NormalizerOptions {
         self.unify_values = unify_values;
         self
     }
+
+    pub fn with_placeholder_style(mut self, placeholder_style: PlaceholderStyle) -> Self {
+        self.placeholder_style = placeholder_style;
+        self
+    }
+
+    pub fn with_keep_placeholders(mut self, keep_placeholders: bool) -> Self {
+        self.keep_placeholders = keep_placeholders;
+        self
+    }
 }
 
 /// A visitor for SQL AST nodes that normalizes SQL queries.
 #[derive(Default)]
 pub struct Normalizer {
     pub options: NormalizerOptions,
+    /// Number of placeholders literal values are replaced with in the current statement so far.
+    position: usize,
+    /// Highest number of the kept placeholders of the current statement, after which replaced ones are numbered.
+    offset: usize,
+    /// Literals replaced in the current statement, if collected.
+    literals: Option<Vec<ExtractedLiteral>>,
+    /// Number of values to be replaced in each IN and VALUES list being visited, counted before replacing them.
+    replaced: Vec<usize>,
 }
 
 impl VisitorMut for Normalizer {
     type Break = ();
 
+    fn pre_visit_statement(&mut self, statement: &mut Statement) -> ControlFlow<Self::Break> {
+        self.position = 0;
+        self.offset = 0;
+        if self.options.keep_placeholders {
+            let _ = visit_expressions(statement, |expr| {
+                if let Expr::Value(Value::Placeholder(placeholder)) = expr {
+                    let number = placeholder
+                        .strip_prefix('$')
+                        .or_else(|| placeholder.strip_prefix(":p"))
+                        .and_then(|n| n.parse::<usize>().ok());
+                    self.offset = self.offset.max(number.unwrap_or(0));
+                }
+                ControlFlow::<()>::Continue(())
+            });
+        }
+        if let Some(literals) = &mut self.literals {
+            literals.clear();
+        }
+        self.replaced.clear();
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_query(&mut self, query: &mut Query) -> ControlFlow<Self::Break> {
+        let replaced = match query.body.deref_mut() {
+            SetExpr::Values(values) => values
+                .rows
+                .iter()
+                .flatten()
+                .filter(|expr| self.is_replaced(expr))
+                .count(),
+            _ => 0,
+        };
+        self.replaced.push(replaced);
+        ControlFlow::Continue(())
+    }
+
     fn post_visit_query(&mut self, query: &mut Query) -> ControlFlow<Self::Break> {
+        let replaced = self.replaced.pop().unwrap_or(0);
         if let SetExpr::Values(values) = query.body.deref_mut() {
             if self.options.unify_values {
                 let rows = &mut values.rows;
@@ -93,6 +197,8 @@ impl VisitorMut for Normalizer {
                         row.is_empty() || row.iter().all(|expr| matches!(expr, Expr::Value(_)))
                     })
                 {
+                    // Numbers of the unified values are reused by the following placeholders.
+                    self.position = self.position.saturating_sub(replaced);
                     *rows = vec![vec![Expr::Value(Value::Placeholder("...".into()))]];
                 }
             }
@@ -101,20 +207,38 @@ impl VisitorMut for Normalizer {
     }
 
     fn pre_visit_expr(&mut self, expr: &mut Expr) -> ControlFlow<Self::Break> {
+        if let Expr::InList { list, .. } = expr {
+            let replaced = list.iter().filter(|expr| self.is_replaced(expr)).count();
+            self.replaced.push(replaced);
+        }
         if let Expr::Value(value) = expr {
-            *value = Value::Placeholder("?".into());
+            if self.options.keep_placeholders && matches!(value, Value::Placeholder(_)) {
+                return ControlFlow::Continue(());
+            }
+            self.position += 1;
+            if let (Some(literals), Some(literal_type)) =
+                (&mut self.literals, ParamType::of_value(value))
+            {
+                literals.push(ExtractedLiteral {
+                    position: literals.len() + 1,
+                    literal_type,
+                    text: value.to_string(),
+                });
+            }
+            *value = Value::Placeholder(self.placeholder());
         }
         ControlFlow::Continue(())
     }
 
     fn post_visit_expr(&mut self, expr: &mut Expr) -> ControlFlow<Self::Break> {
-        match expr {
-            Expr::InList { list, .. } if self.options.unify_in_list => {
-                if list.is_empty() || list.iter().all(|expr| matches!(expr, Expr::Value(_))) {
-                    *list = vec![Expr::Value(Value::Placeholder("...".into()))];
-                }
+        if let Expr::InList { list, .. } = expr {
+            let replaced = self.replaced.pop().unwrap_or(0);
+            if self.options.unify_in_list
+                && (list.is_empty() || list.iter().all(|expr| matches!(expr, Expr::Value(_))))
+            {
+                self.position = self.position.saturating_sub(replaced);
+                *list = vec![Expr::Value(Value::Placeholder("...".into()))];
             }
-            _ => {}
         }
         ControlFlow::Continue(())
     }
@@ -125,6 +249,25 @@ impl Normalizer {
         Self::default()
     }
 
+    fn placeholder(&self) -> String {
+        let number = self.offset + self.position;
+        match self.options.placeholder_style {
+            PlaceholderStyle::QuestionMark => "?".into(),
+            PlaceholderStyle::Numbered => format!("${}", number),
+            PlaceholderStyle::Named => format!(":p{}", number),
+        }
+    }
+
+    /// Whether a value of a list is to be replaced with a placeholder, checked before visiting the list.
+    /// Placeholders already in SQL are never replaced when they are kept, whatever the placeholder style.
+    fn is_replaced(&self, expr: &Expr) -> bool {
+        match expr {
+            Expr::Value(Value::Placeholder(_)) => !self.options.keep_placeholders,
+            Expr::Value(_) => true,
+            _ => false,
+        }
+    }
+
     pub fn with_options(mut self, options: NormalizerOptions) -> Self {
         self.options = options;
         self
@@ -136,13 +279,80 @@ impl Normalizer {
         sql: &str,
         options: NormalizerOptions,
     ) -> Result<Vec<String>, Error> {
-        let mut statements = Parser::parse_sql(dialect, sql)?;
-        statements.visit(&mut Self::new().with_options(options));
+        let mut statements = instrument::parse_sql(dialect, sql)?;
+        let mut normalizer = Self::new().with_options(options);
         Ok(statements
-            .into_iter()
-            .map(|statement| statement.to_string())
+            .iter_mut()
+            .map(|statement| {
+                instrument::analyze("normalize", || {
+                    let _ = statement.visit(&mut normalizer);
+                    statement.to_string()
+                })
+            })
             .collect::<Vec<String>>())
     }
+
+    /// Normalize SQL, collecting the literal values replaced in each statement.
+    pub fn normalize_with_literals(
+        dialect: &dyn Dialect,
+        sql: &str,
+        options: NormalizerOptions,
+    ) -> Result<Vec<(String, Vec<ExtractedLiteral>)>, Error> {
+        let mut statements = instrument::parse_sql(dialect, sql)?;
+        let mut normalizer = Self::new().with_options(options);
+        normalizer.literals = Some(vec![]);
+        Ok(statements
+            .iter_mut()
+            .map(|statement| {
+                instrument::analyze("normalize", || {
+                    let _ = statement.visit(&mut normalizer);
+                    let literals = normalizer.literals.as_mut().map(std::mem::take);
+                    (statement.to_string(), literals.unwrap_or_default())
+                })
+            })
+            .collect())
+    }
+
+    /// Normalize SQL, appending the normalized statements to `buf` one after another without separators,
+    /// and return the range of each statement in `buf`.
+    ///
+    /// Statements are written into `buf` directly instead of being allocated one by one,
+    /// so clearing and reusing the same buffer across batches avoids allocations once it has grown large enough.
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use sql_insight::sqlparser::dialect::GenericDialect;
+    /// use sql_insight::{Normalizer, NormalizerOptions};
+    ///
+    /// let dialect = GenericDialect {};
+    /// let mut buf = String::new();
+    /// for sql in ["SELECT a FROM t1 WHERE b = 1; DELETE FROM t2 WHERE c = 2", "SELECT 'x'"] {
+    ///     buf.clear();
+    ///     let ranges = Normalizer::normalize_into(&dialect, sql, NormalizerOptions::new(), &mut buf).unwrap();
+    ///     for range in ranges {
+    ///         println!("{}", &buf[range]);
+    ///     }
+    /// }
+    /// assert_eq!(buf, "SELECT ?");
+    /// ```
+    pub fn normalize_into(
+        dialect: &dyn Dialect,
+        sql: &str,
+        options: NormalizerOptions,
+        buf: &mut String,
+    ) -> Result<Vec<Range<usize>>, Error> {
+        let mut statements = instrument::parse_sql(dialect, sql)?;
+        let _ = statements.visit(&mut Self::new().with_options(options));
+        let mut ranges = Vec::with_capacity(statements.len());
+        for statement in &statements {
+            let start = buf.len();
+            // Writing into a String never fails.
+            let _ = write!(buf, "{}", statement);
+            ranges.push(start..buf.len());
+        }
+        Ok(ranges)
+    }
 }
 
 #[cfg(test)]
@@ -162,6 +372,229 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_normalize_into() {
+        let sql = "INSERT INTO t2 (a) VALUES (4); UPDATE t1 SET a = 1 WHERE b = 2";
+        for dialect in all_dialects() {
+            let mut buf = String::from("prefix");
+            let ranges = Normalizer::normalize_into(
+                dialect.as_ref(),
+                sql,
+                NormalizerOptions::new(),
+                &mut buf,
+            )
+            .unwrap();
+            let result = ranges
+                .into_iter()
+                .map(|range| &buf[range])
+                .collect::<Vec<_>>();
+            assert_eq!(
+                result,
+                [
+                    "INSERT INTO t2 (a) VALUES (?)",
+                    "UPDATE t1 SET a = ? WHERE b = ?"
+                ],
+                "Failed for dialect: {dialect:?}"
+            );
+            assert!(buf.starts_with("prefix"), "Failed for dialect: {dialect:?}");
+        }
+    }
+
+    #[test]
+    fn test_placeholder_style() {
+        let sql = "SELECT a FROM t1 WHERE b = 1 AND c IN (2, 3) AND d = 'x'; UPDATE t1 SET a = 1 WHERE b = 2";
+        let options = NormalizerOptions::new().with_placeholder_style(PlaceholderStyle::Numbered);
+        let expected = vec![
+            "SELECT a FROM t1 WHERE b = $1 AND c IN ($2, $3) AND d = $4".into(),
+            "UPDATE t1 SET a = $1 WHERE b = $2".into(),
+        ];
+        assert_normalize(sql, expected, all_dialects(), options);
+        let options = NormalizerOptions::new()
+            .with_placeholder_style(PlaceholderStyle::Named)
+            .with_unify_in_list(true);
+        let expected = vec![
+            "SELECT a FROM t1 WHERE b = :p1 AND c IN (...) AND d = :p2".into(),
+            "UPDATE t1 SET a = :p1 WHERE b = :p2".into(),
+        ];
+        assert_normalize(sql, expected, all_dialects(), options);
+    }
+
+    #[test]
+    fn test_placeholder_style_with_unified_values() {
+        let sql = "INSERT INTO t1 (a, b) VALUES (1, 2), (3, 4) ON CONFLICT DO NOTHING RETURNING a";
+        let options = NormalizerOptions::new()
+            .with_placeholder_style(PlaceholderStyle::Numbered)
+            .with_unify_values(true);
+        let expected =
+            vec!["INSERT INTO t1 (a, b) VALUES (...) ON CONFLICT DO NOTHING RETURNING a".into()];
+        assert_normalize(
+            sql,
+            expected,
+            vec![Box::new(sqlparser::dialect::PostgreSqlDialect {})],
+            options,
+        );
+        let sql =
+            "SELECT a FROM t1 WHERE b IN (SELECT c FROM (VALUES (1), (2)) AS v (c)) AND d = 3";
+        let options = NormalizerOptions::new()
+            .with_placeholder_style(PlaceholderStyle::Numbered)
+            .with_unify_values(true);
+        let expected = vec![
+            "SELECT a FROM t1 WHERE b IN (SELECT c FROM (VALUES (...)) AS v (c)) AND d = $1".into(),
+        ];
+        assert_normalize(
+            sql,
+            expected,
+            vec![Box::new(sqlparser::dialect::PostgreSqlDialect {})],
+            options,
+        );
+    }
+
+    #[test]
+    fn test_keep_placeholders() {
+        let sql = "SELECT a FROM t1 WHERE b = ? AND c = 1 AND d IN (?, 2)";
+        let options = NormalizerOptions::new().with_keep_placeholders(true);
+        let expected = vec!["SELECT a FROM t1 WHERE b = ? AND c = ? AND d IN (?, ?)".into()];
+        assert_normalize(
+            sql,
+            expected,
+            vec![
+                Box::new(sqlparser::dialect::GenericDialect {}),
+                Box::new(sqlparser::dialect::MySqlDialect {}),
+            ],
+            options,
+        );
+        let sql = "SELECT a FROM t1 WHERE b = $1 AND c = :name AND d = 'x' AND e IN (1, 2)";
+        let options = NormalizerOptions::new()
+            .with_keep_placeholders(true)
+            .with_placeholder_style(PlaceholderStyle::Numbered);
+        let expected =
+            vec!["SELECT a FROM t1 WHERE b = $1 AND c = :name AND d = $2 AND e IN ($3, $4)".into()];
+        assert_normalize(
+            sql,
+            expected,
+            vec![
+                Box::new(sqlparser::dialect::GenericDialect {}),
+                Box::new(sqlparser::dialect::PostgreSqlDialect {}),
+            ],
+            options,
+        );
+        let options = NormalizerOptions::new().with_placeholder_style(PlaceholderStyle::Numbered);
+        let expected =
+            vec!["SELECT a FROM t1 WHERE b = $1 AND c = $2 AND d = $3 AND e IN ($4, $5)".into()];
+        assert_normalize(
+            sql,
+            expected,
+            vec![Box::new(sqlparser::dialect::GenericDialect {})],
+            options,
+        );
+    }
+
+    #[test]
+    fn test_keep_placeholders_numbered_after_kept_ones() {
+        let sql =
+            "SELECT a FROM t1 WHERE a = 1 AND b = $1; SELECT a FROM t1 WHERE a = $2 AND b = 5; \
+            SELECT a FROM t1 WHERE a IN ($3, 1, 2) AND b = 3";
+        let dialects = || -> Vec<Box<dyn Dialect>> {
+            vec![
+                Box::new(sqlparser::dialect::GenericDialect {}),
+                Box::new(sqlparser::dialect::PostgreSqlDialect {}),
+            ]
+        };
+        let options = NormalizerOptions::new()
+            .with_keep_placeholders(true)
+            .with_placeholder_style(PlaceholderStyle::Numbered);
+        let expected = vec![
+            "SELECT a FROM t1 WHERE a = $2 AND b = $1".into(),
+            "SELECT a FROM t1 WHERE a = $2 AND b = $3".into(),
+            "SELECT a FROM t1 WHERE a IN ($3, $4, $5) AND b = $6".into(),
+        ];
+        assert_normalize(sql, expected, dialects(), options.clone());
+        let expected = vec![
+            "SELECT a FROM t1 WHERE a = $2 AND b = $1".into(),
+            "SELECT a FROM t1 WHERE a = $2 AND b = $3".into(),
+            "SELECT a FROM t1 WHERE a IN (...) AND b = $4".into(),
+        ];
+        assert_normalize(sql, expected, dialects(), options.with_unify_in_list(true));
+        let sql = "SELECT a FROM t1 WHERE a = :p2 AND b = 'x'";
+        let options = NormalizerOptions::new()
+            .with_keep_placeholders(true)
+            .with_placeholder_style(PlaceholderStyle::Named);
+        let expected = vec!["SELECT a FROM t1 WHERE a = :p2 AND b = :p3".into()];
+        assert_normalize(sql, expected, dialects(), options);
+    }
+
+    #[test]
+    fn test_keep_placeholders_with_unified_lists() {
+        let dialects = || -> Vec<Box<dyn Dialect>> {
+            vec![
+                Box::new(sqlparser::dialect::GenericDialect {}),
+                Box::new(sqlparser::dialect::PostgreSqlDialect {}),
+            ]
+        };
+        for (style, placeholder) in [
+            (PlaceholderStyle::QuestionMark, "?"),
+            (PlaceholderStyle::Numbered, "$2"),
+            (PlaceholderStyle::Named, ":p2"),
+        ] {
+            let options = NormalizerOptions::new()
+                .with_keep_placeholders(true)
+                .with_placeholder_style(style);
+            let sql = "INSERT INTO t (a, b) VALUES ($1, $2); \
+                INSERT INTO t (a, b) VALUES ($1, 1) RETURNING a + 2";
+            let expected = vec![
+                "INSERT INTO t (a, b) VALUES (...)".into(),
+                format!("INSERT INTO t (a, b) VALUES (...) RETURNING a + {placeholder}"),
+            ];
+            assert_normalize(
+                sql,
+                expected,
+                dialects(),
+                options.clone().with_unify_values(true),
+            );
+            let sql = "SELECT a FROM t WHERE b IN ($1, $2); \
+                SELECT a FROM t WHERE b IN ($1, 1) AND c = 2";
+            let expected = vec![
+                "SELECT a FROM t WHERE b IN (...)".into(),
+                format!("SELECT a FROM t WHERE b IN (...) AND c = {placeholder}"),
+            ];
+            assert_normalize(sql, expected, dialects(), options.with_unify_in_list(true));
+        }
+    }
+
+    #[test]
+    fn test_normalize_with_literals() {
+        let sql = "SELECT a FROM t1 WHERE b = 1 AND c IN (2.5, 'x') AND d IS NOT NULL AND e = ?; DELETE FROM t2 WHERE f = TRUE";
+        let literal = |position, literal_type, text: &str| ExtractedLiteral {
+            position,
+            literal_type,
+            text: text.into(),
+        };
+        let expected = vec![
+            (
+                "SELECT a FROM t1 WHERE b = ? AND c IN (...) AND d IS NOT NULL AND e = ?"
+                    .to_string(),
+                vec![
+                    literal(1, ParamType::Integer, "1"),
+                    literal(2, ParamType::Float, "2.5"),
+                    literal(3, ParamType::String, "'x'"),
+                ],
+            ),
+            (
+                "DELETE FROM t2 WHERE f = ?".to_string(),
+                vec![literal(1, ParamType::Boolean, "true")],
+            ),
+        ];
+        for dialect in all_dialects() {
+            let result = Normalizer::normalize_with_literals(
+                dialect.as_ref(),
+                sql,
+                NormalizerOptions::new().with_unify_in_list(true),
+            )
+            .unwrap();
+            assert_eq!(result, expected, "Failed for dialect: {dialect:?}");
+        }
+    }
+
     #[test]
     fn test_single_sql() {
         let sql = "SELECT a FROM t1 WHERE b = 1 AND c in (2, (select * from b)) AND d LIKE '%foo'";
@@ -0,0 +1,149 @@
+//! Parallel processing of statements with `rayon`, enabled by the `rayon` feature.
+//!
+//! SQL is split into statements as described in [`split_statements`](crate::split_statements()),
+//! then statements are parsed and analyzed concurrently on the current `rayon` thread pool.
+//! Results are in order of statements as with the sequential functions, while errors of statements failing to parse
+//! are located within the statement rather than the whole input, and `DELIMITER` commands of MySQL are supported.
+//! To limit the number of threads, run the functions within `rayon::ThreadPool::install`.
+
+use rayon::prelude::*;
+
+use crate::error::Error;
+use crate::extractor::table_extractor::{TableExtractor, Tables};
+use crate::instrument;
+use crate::normalizer::{Normalizer, NormalizerOptions};
+use crate::splitter::StatementSplitter;
+use sqlparser::ast::{Statement, VisitMut};
+use sqlparser::dialect::Dialect;
+
+/// Convenience function to extract tables from SQL, parsing and analyzing statements in parallel.
+/// Results are the same as [`extract_tables`](crate::extract_tables()).
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+///
+/// let dialect = GenericDialect {};
+/// let result = sql_insight::extract_tables_par(&dialect, "SELECT a FROM t1; SELECT b FROM t2 JOIN t3").unwrap();
+/// assert_eq!(result[0].as_ref().unwrap().to_string(), "t1");
+/// assert_eq!(result[1].as_ref().unwrap().to_string(), "t2, t3");
+/// ```
+pub fn extract_tables_par(
+    dialect: &(dyn Dialect + Sync),
+    sql: &str,
+) -> Result<Vec<Result<Tables, Error>>, Error> {
+    let statements = parse_sql_par(dialect, sql)?;
+    Ok(statements
+        .par_iter()
+        .map(TableExtractor::extract_from_statement)
+        .collect())
+}
+
+/// Convenience function to normalize SQL with options, parsing and normalizing statements in parallel.
+/// Results are the same as [`normalize_with_options`](crate::normalize_with_options()).
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+/// use sql_insight::NormalizerOptions;
+///
+/// let dialect = GenericDialect {};
+/// let sql = "SELECT a FROM t1 WHERE b = 1; SELECT a FROM t1 WHERE b IN (1, 2)";
+/// let result = sql_insight::normalize_par(&dialect, sql, NormalizerOptions::new().with_unify_in_list(true)).unwrap();
+/// assert_eq!(result, ["SELECT a FROM t1 WHERE b = ?", "SELECT a FROM t1 WHERE b IN (...)"]);
+/// ```
+pub fn normalize_par(
+    dialect: &(dyn Dialect + Sync),
+    sql: &str,
+    options: NormalizerOptions,
+) -> Result<Vec<String>, Error> {
+    let statements = parse_sql_par(dialect, sql)?;
+    Ok(statements
+        .into_par_iter()
+        .map_init(
+            || Normalizer::new().with_options(options.clone()),
+            |normalizer, mut statement| {
+                instrument::analyze("normalize", || {
+                    let _ = statement.visit(normalizer);
+                    statement.to_string()
+                })
+            },
+        )
+        .collect())
+}
+
+/// Parse statements of SQL in parallel, failing with the error of the first statement failing to parse.
+fn parse_sql_par(dialect: &(dyn Dialect + Sync), sql: &str) -> Result<Vec<Statement>, Error> {
+    let texts = StatementSplitter::new(dialect, sql)
+        .map(|(_, text)| text)
+        .collect::<Vec<_>>();
+    let parsed = texts
+        .par_iter()
+        .map(|text| instrument::parse_sql(dialect, text))
+        .collect::<Vec<_>>();
+    parsed
+        .into_iter()
+        .collect::<Result<Vec<_>, _>>()
+        .map(|statements| statements.into_iter().flatten().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_sync_dialects;
+    use sqlparser::dialect::{GenericDialect, MySqlDialect};
+
+    const SQL: &str = "SELECT a FROM t1 WHERE b = 1; INSERT INTO t2 (a) VALUES (1, 2), (3, 4); \
+        UPDATE t3 SET a = 'x' WHERE b IN (1, 2); DELETE FROM t4 AS x WHERE x.a = 1; SELECT 2";
+
+    #[test]
+    fn test_extract_tables_par() {
+        for dialect in all_sync_dialects() {
+            assert_eq!(
+                extract_tables_par(dialect.as_ref(), SQL),
+                crate::extract_tables(dialect.as_ref(), SQL),
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_normalize_par() {
+        let options = NormalizerOptions::new()
+            .with_unify_in_list(true)
+            .with_unify_values(true);
+        for dialect in all_sync_dialects() {
+            assert_eq!(
+                normalize_par(dialect.as_ref(), SQL, options.clone()),
+                crate::normalize_with_options(dialect.as_ref(), SQL, options.clone()),
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let result = normalize_par(
+            &GenericDialect {},
+            "SELECT 1; SELEC 2; SELEC 3",
+            NormalizerOptions::new(),
+        );
+        assert_eq!(
+            result,
+            instrument::parse_sql(&GenericDialect {}, "SELEC 2").map(|_| vec![])
+        );
+    }
+
+    #[test]
+    fn test_delimiter_command() {
+        let sql = "SELECT a FROM t1;\nDELIMITER //\nSELECT b FROM t2; SELECT c FROM t3 //\nDELIMITER ;\nSELECT d FROM t4;";
+        let result = extract_tables_par(&MySqlDialect {}, sql)
+            .unwrap()
+            .into_iter()
+            .map(|tables| tables.unwrap().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(result, ["t1", "t2", "t3", "t4"]);
+    }
+}
@@ -0,0 +1,531 @@
+//! Inference of the number and likely types of placeholders in parameterized SQL.
+//!
+//! See [`infer_params`](crate::infer_params()) as the entry point for inferring parameters of SQL,
+//! or [`ParamInferrer`] for using types of an [`InferredSchema`].
+
+use std::collections::HashMap;
+use std::ops::ControlFlow;
+
+use crate::aggregator::column_usage::{as_column, literal_type};
+use crate::aggregator::schema_inference::InferredSchema;
+use crate::error::Error;
+use crate::extractor::table_extractor::{TableExtractor, TableReference};
+use crate::instrument;
+use crate::prepared::ParamType;
+use sqlparser::ast::{
+    BinaryOperator, Expr, Ident, Query, SetExpr, Statement, Value, Visit, Visitor,
+};
+use sqlparser::dialect::Dialect;
+
+/// Convenience function to infer the parameters of each statement of SQL.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+/// use sql_insight::ParamType;
+///
+/// let dialect = GenericDialect {};
+/// let sql = "SELECT a FROM t1 WHERE b IN (?, 'x') AND c = ? LIMIT ?; INSERT INTO t1 (a, b) VALUES ($1, CAST($2 AS INT))";
+/// let result = sql_insight::infer_params(&dialect, sql).unwrap();
+/// let params: Vec<Vec<_>> = result
+///     .iter()
+///     .map(|params| params.iter().map(|p| (p.column.as_ref().map(|c| c.value.as_str()), p.param_type)).collect())
+///     .collect();
+/// assert_eq!(params, [
+///     vec![(Some("b"), Some(ParamType::String)), (Some("c"), None), (None, Some(ParamType::Integer))],
+///     vec![(Some("a"), None), (Some("b"), Some(ParamType::Integer))],
+/// ]);
+/// ```
+pub fn infer_params(dialect: &dyn Dialect, sql: &str) -> Result<Vec<Vec<InferredParam>>, Error> {
+    let statements = instrument::parse_sql(dialect, sql)?;
+    let inferrer = ParamInferrer::new();
+    statements
+        .iter()
+        .map(|statement| inferrer.infer_from_statement(statement))
+        .collect()
+}
+
+/// [`InferredParam`] represents a parameter of a statement and what its context tells about it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InferredParam {
+    /// The placeholder as written, e.g. `?`, `$1` or `:id`.
+    pub placeholder: String,
+    /// Table of the column, if the column is known and its table can be determined.
+    pub table: Option<TableReference>,
+    /// Column the parameter is compared with, assigned to or inserted into.
+    pub column: Option<Ident>,
+    /// Likely type, or `None` if the context doesn't tell.
+    pub param_type: Option<ParamType>,
+}
+
+/// An inferrer of parameters from the context of their placeholders.
+///
+/// Parameters are the `?` placeholders in order of appearance, the same order [`BindParams`](crate::BindParams)
+/// binds them in. When all placeholders are numbered, the N-th parameter is `$N`, and numbers not appearing
+/// in the statement are parameters without column and type. Other named placeholders are parameters in order of
+/// their first appearance. A placeholder appearing several times takes the context of the first appearance telling it.
+///
+/// The type of a parameter is taken from, in order of precedence:
+/// - `CAST(? AS type)`, a `LIKE` pattern, or `LIMIT`, `OFFSET` and `FETCH`, where a cast keeps the column of its context
+/// - literal values of the same `IN` list, `BETWEEN` or column of other `VALUES` rows
+/// - the first type hint of its column in the schema given by [`ParamInferrer::with_schema`]
+///
+/// Unqualified columns are attributed to the table of the statement when it reads only one table.
+#[derive(Clone, Debug, Default)]
+pub struct ParamInferrer {
+    schema: InferredSchema,
+}
+
+impl ParamInferrer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use type hints of columns of a schema, e.g. inferred from the literal SQL of the same workload
+    /// by [`infer_schema`](crate::infer_schema()).
+    pub fn with_schema(mut self, schema: InferredSchema) -> Self {
+        self.schema = schema;
+        self
+    }
+
+    /// Infer the parameters of a statement.
+    pub fn infer_from_statement(&self, statement: &Statement) -> Result<Vec<InferredParam>, Error> {
+        let tables = TableExtractor::extract_from_statement(statement)?.0;
+        let mut visitor = ParamVisitor {
+            tables,
+            hints: HashMap::new(),
+            occurrences: vec![],
+        };
+        if let ControlFlow::Break(e) = statement.visit(&mut visitor) {
+            return Err(e);
+        }
+        let mut params = group(visitor.occurrences);
+        for param in &mut params {
+            if param.param_type.is_none() {
+                if let Some(column) = &param.column {
+                    param.param_type = self.column_type(param.table.as_ref(), column);
+                }
+            }
+        }
+        Ok(params)
+    }
+
+    fn column_type(&self, table: Option<&TableReference>, column: &Ident) -> Option<ParamType> {
+        let columns = match table {
+            Some(table) => {
+                &self
+                    .schema
+                    .tables
+                    .iter()
+                    .find(|t| {
+                        t.table.name == table.name
+                            && t.table.schema == table.schema
+                            && t.table.catalog == table.catalog
+                    })?
+                    .columns
+            }
+            None => &self.schema.unresolved_columns,
+        };
+        columns
+            .iter()
+            .find(|c| c.name == *column)?
+            .type_hints
+            .iter()
+            .find(|hint| **hint != ParamType::Null)
+            .copied()
+    }
+}
+
+/// Merge occurrences of placeholders into parameters.
+fn group(occurrences: Vec<InferredParam>) -> Vec<InferredParam> {
+    let number = |placeholder: &str| {
+        placeholder
+            .strip_prefix('$')
+            .and_then(|n| n.parse::<usize>().ok())
+            .filter(|n| *n > 0)
+    };
+    if !occurrences.is_empty() && occurrences.iter().all(|o| number(&o.placeholder).is_some()) {
+        let count = occurrences
+            .iter()
+            .filter_map(|o| number(&o.placeholder))
+            .max()
+            .unwrap_or_default();
+        let mut params = (1..=count)
+            .map(|n| InferredParam {
+                placeholder: format!("${n}"),
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+        for occurrence in occurrences {
+            if let Some(n) = number(&occurrence.placeholder) {
+                merge(&mut params[n - 1], occurrence);
+            }
+        }
+        return params;
+    }
+    let mut params: Vec<InferredParam> = vec![];
+    for occurrence in occurrences {
+        match params
+            .iter_mut()
+            .find(|p| p.placeholder != "?" && p.placeholder == occurrence.placeholder)
+        {
+            Some(param) => merge(param, occurrence),
+            None => params.push(occurrence),
+        }
+    }
+    params
+}
+
+fn merge(param: &mut InferredParam, occurrence: InferredParam) {
+    if param.column.is_none() && occurrence.column.is_some() {
+        param.table = occurrence.table;
+        param.column = occurrence.column;
+    }
+    if param.param_type.is_none() {
+        param.param_type = occurrence.param_type;
+    }
+}
+
+/// What the context of an expression tells about it if it is a placeholder.
+#[derive(Clone, Default)]
+struct Hint {
+    table: Option<TableReference>,
+    column: Option<Ident>,
+    param_type: Option<ParamType>,
+}
+
+struct ParamVisitor {
+    tables: Vec<TableReference>,
+    /// Hints keyed by the address of the expressions they are for, which stays fixed while visiting.
+    hints: HashMap<*const Expr, Hint>,
+    occurrences: Vec<InferredParam>,
+}
+
+impl ParamVisitor {
+    fn hint(&mut self, expr: &Expr, hint: Hint) {
+        self.hints.entry(expr as *const Expr).or_insert(hint);
+    }
+
+    /// Hint for expressions compared with a column, if `expr` is one.
+    fn column_hint(&self, expr: &Expr, param_type: Option<ParamType>) -> Option<Hint> {
+        let (qualifier, column) = as_column(expr)?;
+        let table = match qualifier {
+            Some(qualifier) => self
+                .tables
+                .iter()
+                .find(|t| t.alias.as_ref().unwrap_or(&t.name).value == qualifier.value)
+                .cloned(),
+            None => match self.tables.as_slice() {
+                [table] => Some(table.clone()),
+                _ => None,
+            },
+        };
+        Some(Hint {
+            table: table.map(|table| TableReference {
+                alias: None,
+                ..table
+            }),
+            column: Some(column),
+            param_type,
+        })
+    }
+}
+
+/// Type of the first non-null literal value among expressions.
+fn sibling_type<'a>(exprs: impl IntoIterator<Item = &'a Expr>) -> Option<ParamType> {
+    exprs
+        .into_iter()
+        .filter_map(literal_type)
+        .find(|value_type| *value_type != ParamType::Null)
+}
+
+impl Visitor for ParamVisitor {
+    type Break = Error;
+
+    fn pre_visit_statement(&mut self, statement: &Statement) -> ControlFlow<Self::Break> {
+        match statement {
+            Statement::Insert {
+                table_name,
+                columns,
+                source: Some(source),
+                ..
+            } => {
+                let SetExpr::Values(values) = &*source.body else {
+                    return ControlFlow::Continue(());
+                };
+                let table = match TableReference::try_from(table_name) {
+                    Ok(table) => table,
+                    Err(e) => return ControlFlow::Break(e),
+                };
+                for row in &values.rows {
+                    for (index, expr) in row.iter().enumerate() {
+                        let param_type =
+                            sibling_type(values.rows.iter().filter_map(|row| row.get(index)));
+                        self.hint(
+                            expr,
+                            Hint {
+                                table: Some(table.clone()),
+                                column: columns.get(index).cloned(),
+                                param_type,
+                            },
+                        );
+                    }
+                }
+            }
+            Statement::Update {
+                table, assignments, ..
+            } => {
+                let target = match TableExtractor::extract_from_table_node(table) {
+                    Ok(tables) => tables.0.into_iter().next(),
+                    Err(e) => return ControlFlow::Break(e),
+                };
+                for assignment in assignments {
+                    self.hint(
+                        &assignment.value,
+                        Hint {
+                            table: target.clone().map(|table| TableReference {
+                                alias: None,
+                                ..table
+                            }),
+                            column: assignment.id.last().cloned(),
+                            param_type: None,
+                        },
+                    );
+                }
+            }
+            _ => {}
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_query(&mut self, query: &Query) -> ControlFlow<Self::Break> {
+        let integer = Hint {
+            param_type: Some(ParamType::Integer),
+            ..Default::default()
+        };
+        let counts = query
+            .limit
+            .iter()
+            .chain(query.offset.iter().map(|offset| &offset.value))
+            .chain(query.fetch.iter().flat_map(|fetch| &fetch.quantity));
+        for expr in counts {
+            self.hint(expr, integer.clone());
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_expr(&mut self, expr: &Expr) -> ControlFlow<Self::Break> {
+        match expr {
+            Expr::Value(Value::Placeholder(placeholder)) => {
+                let hint = self
+                    .hints
+                    .get(&(expr as *const Expr))
+                    .cloned()
+                    .unwrap_or_default();
+                self.occurrences.push(InferredParam {
+                    placeholder: placeholder.clone(),
+                    table: hint.table,
+                    column: hint.column,
+                    param_type: hint.param_type,
+                });
+            }
+            Expr::BinaryOp {
+                left,
+                op:
+                    BinaryOperator::Eq
+                    | BinaryOperator::NotEq
+                    | BinaryOperator::Lt
+                    | BinaryOperator::LtEq
+                    | BinaryOperator::Gt
+                    | BinaryOperator::GtEq,
+                right,
+            } => {
+                if let Some(hint) = self.column_hint(left, None) {
+                    self.hint(right, hint);
+                } else if let Some(hint) = self.column_hint(right, None) {
+                    self.hint(left, hint);
+                }
+            }
+            Expr::InList { expr, list, .. } => {
+                if let Some(hint) = self.column_hint(expr, sibling_type(list)) {
+                    for item in list {
+                        self.hint(item, hint.clone());
+                    }
+                }
+            }
+            Expr::Between {
+                expr, low, high, ..
+            } => {
+                let param_type = sibling_type([&**low, &**high]);
+                if let Some(hint) = self.column_hint(expr, param_type) {
+                    self.hint(low, hint.clone());
+                    self.hint(high, hint);
+                }
+            }
+            Expr::Like { expr, pattern, .. } | Expr::ILike { expr, pattern, .. } => {
+                let hint = self
+                    .column_hint(expr, Some(ParamType::String))
+                    .unwrap_or(Hint {
+                        param_type: Some(ParamType::String),
+                        ..Default::default()
+                    });
+                self.hint(pattern, hint);
+            }
+            // A cast or parentheses pass the hint of their own context to the inner expression.
+            Expr::Cast {
+                expr: inner,
+                data_type,
+                ..
+            } => {
+                let hint = Hint {
+                    param_type: ParamType::of_data_type(data_type),
+                    ..self
+                        .hints
+                        .get(&(expr as *const Expr))
+                        .cloned()
+                        .unwrap_or_default()
+                };
+                self.hint(inner, hint);
+            }
+            Expr::Nested(inner) => {
+                if let Some(hint) = self.hints.get(&(expr as *const Expr)).cloned() {
+                    self.hint(inner, hint);
+                }
+            }
+            _ => {}
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+    use sqlparser::dialect::PostgreSqlDialect;
+    use sqlparser::parser::Parser;
+
+    type Expected<'a> = (&'a str, Option<&'a str>, Option<&'a str>, Option<ParamType>);
+
+    fn assert_params(
+        inferrer: &ParamInferrer,
+        sql: &str,
+        expected: Vec<Vec<Expected>>,
+        dialects: Vec<Box<dyn Dialect>>,
+    ) {
+        for dialect in dialects {
+            let result = Parser::parse_sql(dialect.as_ref(), sql)
+                .unwrap()
+                .iter()
+                .map(|statement| {
+                    inferrer
+                        .infer_from_statement(statement)
+                        .unwrap()
+                        .into_iter()
+                        .map(|p| {
+                            (
+                                p.placeholder,
+                                p.table.map(|t| t.to_string()),
+                                p.column.map(|c| c.value),
+                                p.param_type,
+                            )
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>();
+            let expected = expected
+                .iter()
+                .map(|params| {
+                    params
+                        .iter()
+                        .map(|(placeholder, table, column, param_type)| {
+                            (
+                                placeholder.to_string(),
+                                table.map(String::from),
+                                column.map(String::from),
+                                *param_type,
+                            )
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(result, expected, "Failed for dialect: {dialect:?}");
+        }
+    }
+
+    #[test]
+    fn test_question_marks() {
+        let sql = "SELECT a FROM t1 AS x JOIN t2 ON x.id = t2.id \
+            WHERE x.b = ? AND t2.c BETWEEN ? AND 1.5 AND d LIKE ? LIMIT ? OFFSET ?; \
+            INSERT INTO t1 (a, b) VALUES (?, 'x'), (1, ?); \
+            UPDATE t1 SET a = ? WHERE b IN (?, ?) AND c = CAST(? AS BOOLEAN)";
+        assert_params(
+            &ParamInferrer::new(),
+            sql,
+            vec![
+                vec![
+                    ("?", Some("t1"), Some("b"), None),
+                    ("?", Some("t2"), Some("c"), Some(ParamType::Float)),
+                    ("?", None, Some("d"), Some(ParamType::String)),
+                    ("?", None, None, Some(ParamType::Integer)),
+                    ("?", None, None, Some(ParamType::Integer)),
+                ],
+                vec![
+                    ("?", Some("t1"), Some("a"), Some(ParamType::Integer)),
+                    ("?", Some("t1"), Some("b"), Some(ParamType::String)),
+                ],
+                vec![
+                    ("?", Some("t1"), Some("a"), None),
+                    ("?", Some("t1"), Some("b"), None),
+                    ("?", Some("t1"), Some("b"), None),
+                    ("?", Some("t1"), Some("c"), Some(ParamType::Boolean)),
+                ],
+            ],
+            all_dialects(),
+        );
+    }
+
+    #[test]
+    fn test_numbered() {
+        let sql = "SELECT a FROM t1 WHERE b = $3 AND c = $1 AND d = CAST($1 AS INT)";
+        assert_params(
+            &ParamInferrer::new(),
+            sql,
+            vec![vec![
+                ("$1", Some("t1"), Some("c"), Some(ParamType::Integer)),
+                ("$2", None, None, None),
+                ("$3", Some("t1"), Some("b"), None),
+            ]],
+            vec![Box::new(PostgreSqlDialect {})],
+        );
+    }
+
+    #[test]
+    fn test_with_schema() {
+        let dialect = PostgreSqlDialect {};
+        let schema = crate::infer_schema(
+            &dialect,
+            "SELECT a FROM t1 WHERE b = 'x' AND c = 1; SELECT a FROM t1 JOIN t2 ON t1.id = t2.id WHERE d = 1.5",
+        )
+        .unwrap();
+        let inferrer = ParamInferrer::new().with_schema(schema);
+        assert_params(
+            &inferrer,
+            "UPDATE t1 SET b = $1 WHERE c = $2; \
+            SELECT a FROM t1 JOIN t2 ON t1.id = t2.id WHERE d = $1 AND t1.e = $2",
+            vec![
+                vec![
+                    ("$1", Some("t1"), Some("b"), Some(ParamType::String)),
+                    ("$2", Some("t1"), Some("c"), Some(ParamType::Integer)),
+                ],
+                vec![
+                    ("$1", None, Some("d"), Some(ParamType::Float)),
+                    ("$2", Some("t1"), Some("e"), None),
+                ],
+            ],
+            vec![Box::new(dialect)],
+        );
+    }
+}
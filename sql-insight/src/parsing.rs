@@ -0,0 +1,42 @@
+//! Statement-by-statement parsing for APIs that need control between statements.
+
+use std::ops::ControlFlow;
+
+use crate::error::Error;
+use sqlparser::ast::Statement;
+use sqlparser::keywords::Keyword;
+use sqlparser::parser::Parser;
+use sqlparser::tokenizer::Token;
+
+/// Parse statements one by one, handing each to `on_statement` as soon as it is parsed.
+/// Mirrors `Parser::parse_statements`, but stops early when `on_statement` breaks.
+pub(crate) fn parse_statements<F>(parser: &mut Parser, mut on_statement: F) -> Result<(), Error>
+where
+    F: FnMut(Statement) -> Result<ControlFlow<()>, Error>,
+{
+    let mut expecting_statement_delimiter = false;
+    loop {
+        // ignore empty statements (between successive statement delimiters)
+        while parser.consume_token(&Token::SemiColon) {
+            expecting_statement_delimiter = false;
+        }
+        match parser.peek_token().token {
+            Token::EOF => break,
+            Token::Word(word) if expecting_statement_delimiter && word.keyword == Keyword::END => {
+                break
+            }
+            _ => {}
+        }
+        if expecting_statement_delimiter {
+            return parser
+                .expected("end of statement", parser.peek_token())
+                .map_err(Error::from);
+        }
+        let statement = parser.parse_statement()?;
+        if on_statement(statement)?.is_break() {
+            break;
+        }
+        expecting_statement_delimiter = true;
+    }
+    Ok(())
+}
@@ -0,0 +1,209 @@
+//! Generation of prepared statements from SQL with literal values.
+//!
+//! See [`to_prepared`](crate::to_prepared()) as the entry point.
+
+use std::ops::ControlFlow;
+
+use crate::error::Error;
+use crate::instrument;
+use crate::normalizer::Normalizer;
+use crate::rewriter::{ConvertPlaceholders, Param, PlaceholderStyle, Rewrite};
+use sqlparser::ast::{DataType, Expr, Statement, Value, Visit, VisitMut, Visitor};
+use sqlparser::dialect::Dialect;
+
+/// Convenience function to turn SQL with literal values into prepared statements.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+/// use sql_insight::{Param, ParamType};
+///
+/// let dialect = GenericDialect {};
+/// let sql = "SELECT a FROM t1 WHERE b = 'x' AND c > 1.5 LIMIT 10";
+/// let result = sql_insight::to_prepared(&dialect, sql).unwrap();
+/// assert_eq!(result[0].sql, "SELECT a FROM t1 WHERE b = $1 AND c > $2 LIMIT $3");
+/// assert_eq!(result[0].params, [Param::String("x".into()), Param::Float(1.5), Param::Integer(10)]);
+/// assert_eq!(result[0].types, [ParamType::String, ParamType::Float, ParamType::Integer]);
+/// ```
+pub fn to_prepared(dialect: &dyn Dialect, sql: &str) -> Result<Vec<PreparedStatement>, Error> {
+    instrument::parse_sql(dialect, sql)?
+        .into_iter()
+        .map(PreparedStatement::from_statement)
+        .collect()
+}
+
+/// Type of a parameter, inferred from its literal value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ParamType {
+    Null,
+    Boolean,
+    Integer,
+    Float,
+    String,
+}
+
+impl ParamType {
+    /// Type of a literal value, or `None` for placeholders.
+    pub(crate) fn of_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Placeholder(_) => None,
+            Value::Null => Some(ParamType::Null),
+            Value::Boolean(_) => Some(ParamType::Boolean),
+            Value::Number(number, _) if number.parse::<i64>().is_ok() => Some(ParamType::Integer),
+            Value::Number(number, _) if number.parse::<f64>().is_ok() => Some(ParamType::Float),
+            _ => Some(ParamType::String),
+        }
+    }
+
+    /// Type of values a data type holds by its SQL representation, or `None` for types without a matching parameter type,
+    /// e.g. `BLOB` or `JSON`. Dates and times are strings.
+    pub(crate) fn of_data_type(data_type: &DataType) -> Option<Self> {
+        let data_type = data_type.to_string().to_uppercase();
+        let name = data_type.split('(').next().unwrap_or_default().trim();
+        match name.trim_end_matches(" UNSIGNED") {
+            "BOOLEAN" | "BOOL" => Some(ParamType::Boolean),
+            "TINYINT" | "SMALLINT" | "MEDIUMINT" | "INT" | "INTEGER" | "BIGINT" | "INT2"
+            | "INT4" | "INT8" | "INT64" => Some(ParamType::Integer),
+            "REAL" | "FLOAT" | "FLOAT4" | "FLOAT8" | "FLOAT64" | "DOUBLE" | "DOUBLE PRECISION"
+            | "DECIMAL" | "DEC" | "NUMERIC" => Some(ParamType::Float),
+            "CHAR" | "CHARACTER" | "NCHAR" | "VARCHAR" | "CHARACTER VARYING" | "NVARCHAR"
+            | "STRING" | "TEXT" | "CLOB" | "UUID" | "DATE" | "TIME" | "TIMESTAMP" | "DATETIME" => {
+                Some(ParamType::String)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Param {
+    /// Type of the parameter.
+    pub fn param_type(&self) -> ParamType {
+        match self {
+            Param::Null => ParamType::Null,
+            Param::Boolean(_) => ParamType::Boolean,
+            Param::Integer(_) => ParamType::Integer,
+            Param::Float(_) => ParamType::Float,
+            Param::String(_) => ParamType::String,
+        }
+    }
+}
+
+/// [`PreparedStatement`] represents a statement with its literal values replaced by numbered placeholders,
+/// together with the values in order of the placeholders.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PreparedStatement {
+    /// SQL with placeholders `$1`, `$2`, ...
+    pub sql: String,
+    /// Values of the placeholders, where `params[0]` is the value of `$1`.
+    pub params: Vec<Param>,
+    /// Types of the placeholders, inferred from the values.
+    pub types: Vec<ParamType>,
+}
+
+impl PreparedStatement {
+    /// Turn a statement with literal values into a prepared statement.
+    /// Values are replaced by the [`Normalizer`], so every literal value becomes a parameter.
+    pub fn from_statement(mut statement: Statement) -> Result<Self, Error> {
+        let mut collector = ParamCollector { params: vec![] };
+        if let ControlFlow::Break(e) = Visit::visit(&statement, &mut collector) {
+            return Err(e);
+        }
+        let _ = VisitMut::visit(&mut statement, &mut Normalizer::new());
+        ConvertPlaceholders::new(PlaceholderStyle::Numbered).rewrite(&mut statement)?;
+        let types = collector.params.iter().map(Param::param_type).collect();
+        Ok(Self {
+            sql: statement.to_string(),
+            params: collector.params,
+            types,
+        })
+    }
+}
+
+/// Collects values in the order the [`Normalizer`] replaces them.
+struct ParamCollector {
+    params: Vec<Param>,
+}
+
+impl Visitor for ParamCollector {
+    type Break = Error;
+
+    fn pre_visit_expr(&mut self, expr: &Expr) -> ControlFlow<Self::Break> {
+        let Expr::Value(value) = expr else {
+            return ControlFlow::Continue(());
+        };
+        let param = match value {
+            Value::Null => Param::Null,
+            Value::Boolean(value) => Param::Boolean(*value),
+            Value::Number(number, _) => match number.parse::<i64>() {
+                Ok(integer) => Param::Integer(integer),
+                Err(_) => match number.parse::<f64>() {
+                    Ok(float) => Param::Float(float),
+                    Err(_) => Param::String(number.clone()),
+                },
+            },
+            Value::SingleQuotedString(s)
+            | Value::DoubleQuotedString(s)
+            | Value::NationalStringLiteral(s)
+            | Value::EscapedStringLiteral(s)
+            | Value::HexStringLiteral(s) => Param::String(s.clone()),
+            Value::Placeholder(placeholder) => {
+                return ControlFlow::Break(Error::ArgumentError(format!(
+                    "SQL already has placeholder {placeholder}"
+                )))
+            }
+            value => Param::String(value.to_string()),
+        };
+        self.params.push(param);
+        ControlFlow::Continue(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+
+    #[test]
+    fn test_to_prepared() {
+        let sql = "INSERT INTO t1 (a, b, c) VALUES (1, 'x', NULL); UPDATE t1 SET a = TRUE WHERE b IN ('y', 'z')";
+        for dialect in all_dialects() {
+            let result = to_prepared(dialect.as_ref(), sql).unwrap();
+            assert_eq!(
+                result,
+                vec![
+                    PreparedStatement {
+                        sql: "INSERT INTO t1 (a, b, c) VALUES ($1, $2, $3)".into(),
+                        params: vec![Param::Integer(1), Param::String("x".into()), Param::Null],
+                        types: vec![ParamType::Integer, ParamType::String, ParamType::Null],
+                    },
+                    PreparedStatement {
+                        sql: "UPDATE t1 SET a = $1 WHERE b IN ($2, $3)".into(),
+                        params: vec![
+                            Param::Boolean(true),
+                            Param::String("y".into()),
+                            Param::String("z".into()),
+                        ],
+                        types: vec![ParamType::Boolean, ParamType::String, ParamType::String],
+                    },
+                ],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_to_prepared_with_placeholder() {
+        for dialect in all_dialects() {
+            let result = to_prepared(dialect.as_ref(), "SELECT a FROM t1 WHERE b = ?");
+            assert_eq!(
+                result,
+                Err(Error::ArgumentError("SQL already has placeholder ?".into())),
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+}
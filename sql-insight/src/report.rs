@@ -0,0 +1,240 @@
+//! Typed reports of analysis results.
+//!
+//! A [`Report`] wraps the per-statement results of any analysis together with a batch summary and error records.
+//! With the `serde` feature enabled, reports serialize into a stable JSON schema that is shared by the library and the CLI.
+//!
+//! ## Schema
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "statements": [
+//!     { "index": 0, "result": ..., "error": null },
+//!     { "index": 1, "result": null, "error": { "kind": "analysis", "message": "..." } }
+//!   ],
+//!   "summary": { "statements": 2, "succeeded": 1, "failed": 1 },
+//!   "error": null
+//! }
+//! ```
+//!
+//! `result` is the serialized analysis result of each statement, e.g. a list of table references for table extraction.
+//! The top-level `error` is set when the whole input could not be analyzed, e.g. it failed to parse.
+//! [`REPORT_VERSION`] is bumped whenever a field is removed or its meaning changes.
+
+use crate::error::Error;
+
+/// Version of the report schema.
+pub const REPORT_VERSION: u32 = 1;
+
+/// [`Report`] represents the results of analyzing a batch of statements.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Report<T> {
+    pub version: u32,
+    pub statements: Vec<StatementReport<T>>,
+    pub summary: BatchSummary,
+    pub error: Option<ErrorRecord>,
+}
+
+/// [`StatementReport`] represents the result of analyzing a single statement.
+/// Exactly one of `result` and `error` is set.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StatementReport<T> {
+    /// Zero-based position of the statement in the input.
+    pub index: usize,
+    pub result: Option<T>,
+    pub error: Option<ErrorRecord>,
+}
+
+/// [`BatchSummary`] represents counts over all statements in a [`Report`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BatchSummary {
+    pub statements: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+}
+
+/// [`ErrorRecord`] represents an [`Error`] in a serializable form.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ErrorRecord {
+    pub kind: ErrorKind,
+    pub message: String,
+}
+
+/// [`ErrorKind`] represents the kind of an [`Error`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ErrorKind {
+    Argument,
+    Parser,
+    Analysis,
+    Io,
+    LimitExceeded,
+    Untranslatable,
+}
+
+impl From<&Error> for ErrorKind {
+    fn from(error: &Error) -> Self {
+        match error {
+            Error::ArgumentError(_) => ErrorKind::Argument,
+            Error::ParserError(_) => ErrorKind::Parser,
+            Error::AnalysisError(_) => ErrorKind::Analysis,
+            Error::IOError(_) => ErrorKind::Io,
+            Error::LimitExceeded(_) => ErrorKind::LimitExceeded,
+            Error::Untranslatable(_) => ErrorKind::Untranslatable,
+        }
+    }
+}
+
+impl From<&Error> for ErrorRecord {
+    fn from(error: &Error) -> Self {
+        ErrorRecord {
+            kind: ErrorKind::from(error),
+            message: error.to_string(),
+        }
+    }
+}
+
+impl<T> Report<T> {
+    /// Build a report from per-statement results.
+    pub fn from_results(results: Vec<Result<T, Error>>) -> Self {
+        let mut summary = BatchSummary::default();
+        let statements = results
+            .into_iter()
+            .enumerate()
+            .map(|(index, result)| {
+                summary.statements += 1;
+                match result {
+                    Ok(result) => {
+                        summary.succeeded += 1;
+                        StatementReport {
+                            index,
+                            result: Some(result),
+                            error: None,
+                        }
+                    }
+                    Err(e) => {
+                        summary.failed += 1;
+                        StatementReport {
+                            index,
+                            result: None,
+                            error: Some(ErrorRecord::from(&e)),
+                        }
+                    }
+                }
+            })
+            .collect();
+        Report {
+            version: REPORT_VERSION,
+            statements,
+            summary,
+            error: None,
+        }
+    }
+
+    /// Build a report for an input that could not be analyzed at all.
+    pub fn from_error(error: &Error) -> Self {
+        Report {
+            version: REPORT_VERSION,
+            statements: vec![],
+            summary: BatchSummary::default(),
+            error: Some(ErrorRecord::from(error)),
+        }
+    }
+}
+
+impl<T> From<Result<Vec<Result<T, Error>>, Error>> for Report<T> {
+    fn from(result: Result<Vec<Result<T, Error>>, Error>) -> Self {
+        match result {
+            Ok(results) => Report::from_results(results),
+            Err(e) => Report::from_error(&e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{TableReference, Tables};
+    use sqlparser::dialect::GenericDialect;
+
+    #[test]
+    fn test_report_from_results() {
+        let result = crate::extract_tables(
+            &GenericDialect {},
+            "SELECT a FROM t1; SELECT b FROM catalog.schema.table.extra",
+        );
+        let report = Report::from(result);
+        assert_eq!(
+            report,
+            Report {
+                version: REPORT_VERSION,
+                statements: vec![
+                    StatementReport {
+                        index: 0,
+                        result: Some(Tables(vec![TableReference {
+                            catalog: None,
+                            schema: None,
+                            name: "t1".into(),
+                            alias: None,
+                        }])),
+                        error: None,
+                    },
+                    StatementReport {
+                        index: 1,
+                        result: None,
+                        error: Some(ErrorRecord {
+                            kind: ErrorKind::Analysis,
+                            message: "Too many identifiers provided".into(),
+                        }),
+                    },
+                ],
+                summary: BatchSummary {
+                    statements: 2,
+                    succeeded: 1,
+                    failed: 1,
+                },
+                error: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_report_from_error() {
+        let result: Result<Vec<Result<String, Error>>, Error> =
+            crate::format(&GenericDialect {}, "SELECT * FROM (")
+                .map(|r| r.into_iter().map(Ok).collect());
+        let report = Report::from(result);
+        assert!(report.statements.is_empty());
+        assert_eq!(report.summary, BatchSummary::default());
+        assert_eq!(report.error.map(|e| e.kind), Some(ErrorKind::Parser));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_report_serialization() {
+        let report = Report::from_results(vec![
+            Ok("SELECT a FROM t1".to_string()),
+            Err(Error::AnalysisError("failed".into())),
+        ]);
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "version": 1,
+                "statements": [
+                    { "index": 0, "result": "SELECT a FROM t1", "error": null },
+                    { "index": 1, "result": null, "error": { "kind": "analysis", "message": "failed" } }
+                ],
+                "summary": { "statements": 2, "succeeded": 1, "failed": 1 },
+                "error": null
+            })
+        );
+        let deserialized: Report<String> = serde_json::from_value(json).unwrap();
+        assert_eq!(deserialized, report);
+    }
+}
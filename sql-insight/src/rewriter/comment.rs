@@ -0,0 +1,322 @@
+//! Comment injection and stripping, which work on the source text since the AST does not carry comments.
+
+use std::collections::BTreeMap;
+
+use crate::error::Error;
+use crate::span::Location;
+use crate::splitter::split_statements;
+use sqlparser::dialect::{Dialect, MySqlDialect};
+use sqlparser::parser::ParserError;
+use sqlparser::tokenizer::{Token, TokenWithLocation, Tokenizer, Whitespace};
+
+/// Remove line and block comments from SQL, leaving everything else byte-for-byte as it is.
+/// Line breaks ending line comments are kept, and a space is left where a comment was the only separator of two tokens.
+/// Fails rather than emitting partial SQL when a comment follows a quote that the dialect could not read as a literal.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+///
+/// let dialect = GenericDialect {};
+/// let sql = "SELECT a/* password='x' */FROM t1 -- secret\nWHERE b = '-- not a comment'";
+/// let result = sql_insight::strip_comments(&dialect, sql).unwrap();
+/// assert_eq!(result, "SELECT a FROM t1 \nWHERE b = '-- not a comment'");
+/// ```
+pub fn strip_comments(dialect: &dyn Dialect, sql: &str) -> Result<String, Error> {
+    let tokens = Tokenizer::new(dialect, sql)
+        .tokenize_with_location()
+        .map_err(ParserError::from)?;
+    let mut result = String::with_capacity(sql.len());
+    let mut copied = 0;
+    let mut unterminated_quote = None;
+    for (index, TokenWithLocation { token, location }) in tokens.iter().enumerate() {
+        let is_line_comment = match token {
+            Token::Whitespace(Whitespace::SingleLineComment { .. }) => true,
+            Token::Whitespace(Whitespace::MultiLineComment(_)) => false,
+            Token::Char(c @ ('\'' | '"' | '`')) if unterminated_quote.is_none() => {
+                unterminated_quote = Some((*c, Location::from(*location)));
+                continue;
+            }
+            _ => continue,
+        };
+        // A quote the tokenizer could not read as a literal means the comment may swallow part of it.
+        if let Some((quote, location)) = unterminated_quote {
+            return Err(Error::AnalysisError(format!(
+                "Cannot strip comments after unterminated {quote} at {}:{}",
+                location.line, location.column
+            )));
+        }
+        let start = offset(sql, Location::from(*location))?;
+        let mut end = match tokens.get(index + 1) {
+            Some(next) => offset(sql, Location::from(next.location))?,
+            None => sql.len(),
+        };
+        if is_line_comment && sql[start..end].ends_with('\n') {
+            end -= 1;
+        }
+        result.push_str(&sql[copied..start]);
+        let separates_tokens = !result.ends_with(char::is_whitespace)
+            && !result.is_empty()
+            && sql[end..].starts_with(|c: char| !c.is_whitespace());
+        if separates_tokens {
+            result.push(' ');
+        }
+        copied = end;
+    }
+    result.push_str(&sql[copied..]);
+    Ok(result)
+}
+
+/// [`CommentInjector`] prepends a comment to each statement, leaving the rest of the SQL including
+/// existing comments as it is.
+///
+/// Tags are written in [sqlcommenter](https://google.github.io/sqlcommenter/) style, e.g. `/*key='value'*/`,
+/// with keys sorted and keys and values URL-encoded. Hints are written as an optimizer hint block `/*+ ... */`,
+/// placed right after the first keyword for MySQL and in front of the statement otherwise.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::{GenericDialect, MySqlDialect};
+/// use sql_insight::CommentInjector;
+///
+/// let injector = CommentInjector::new().with_tag("traceparent", "00-abc-01").with_tag("route", "/orders");
+/// let result = injector.inject(&GenericDialect {}, "SELECT a FROM t1; -- note\nDELETE FROM t2").unwrap();
+/// assert_eq!(
+///     result,
+///     "/*route='%2Forders',traceparent='00-abc-01'*/ SELECT a FROM t1; -- note\n/*route='%2Forders',traceparent='00-abc-01'*/ DELETE FROM t2"
+/// );
+///
+/// let injector = CommentInjector::new().with_hint("MAX_EXECUTION_TIME(1000)");
+/// let result = injector.inject(&MySqlDialect {}, "SELECT a FROM t1").unwrap();
+/// assert_eq!(result, "SELECT /*+ MAX_EXECUTION_TIME(1000) */ a FROM t1");
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct CommentInjector {
+    tags: BTreeMap<String, String>,
+    hints: Vec<String>,
+}
+
+impl CommentInjector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a sqlcommenter key-value tag, replacing the value of an existing key.
+    pub fn with_tag(mut self, key: &str, value: &str) -> Self {
+        self.tags.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    /// Add an optimizer hint, e.g. `SeqScan(t1)` or `MAX_EXECUTION_TIME(1000)`.
+    /// Hints are written as they are, so [`inject`](Self::inject) fails on a hint that would end the comment.
+    pub fn with_hint(mut self, hint: &str) -> Self {
+        self.hints.push(hint.to_string());
+        self
+    }
+
+    /// Inject the comments into each statement of SQL.
+    pub fn inject(&self, dialect: &dyn Dialect, sql: &str) -> Result<String, Error> {
+        if let Some(hint) = self
+            .hints
+            .iter()
+            .find(|hint| hint.contains("*/") || hint.contains("/*"))
+        {
+            return Err(Error::ArgumentError(format!(
+                "Hint must not contain comment delimiters: {hint}"
+            )));
+        }
+        let hints_after_keyword = dialect.is::<MySqlDialect>();
+        let mut insertions = Vec::new();
+        for (start, keyword) in statement_starts(dialect, sql)? {
+            let mut leading = String::new();
+            if let Some(tags) = self.tags_comment() {
+                leading.push_str(&tags);
+                leading.push(' ');
+            }
+            if let Some(hints) = self.hints_comment() {
+                if hints_after_keyword {
+                    insertions.push((offset(sql, start.advance(&keyword))?, format!(" {hints}")));
+                } else {
+                    leading.push_str(&hints);
+                    leading.push(' ');
+                }
+            }
+            if !leading.is_empty() {
+                insertions.push((offset(sql, start)?, leading));
+            }
+        }
+        let mut result = sql.to_string();
+        insertions.sort_by_key(|(offset, _)| *offset);
+        for (offset, text) in insertions.into_iter().rev() {
+            result.insert_str(offset, &text);
+        }
+        Ok(result)
+    }
+
+    fn tags_comment(&self) -> Option<String> {
+        if self.tags.is_empty() {
+            return None;
+        }
+        let tags = self
+            .tags
+            .iter()
+            .map(|(key, value)| format!("{}='{}'", url_encode(key), url_encode(value)))
+            .collect::<Vec<_>>()
+            .join(",");
+        Some(format!("/*{tags}*/"))
+    }
+
+    fn hints_comment(&self) -> Option<String> {
+        if self.hints.is_empty() {
+            return None;
+        }
+        Some(format!("/*+ {} */", self.hints.join(" ")))
+    }
+}
+
+/// Location and text of the first token of each statement, delimited as described in
+/// [`split_statements`](crate::split_statements()). Comments leading a statement, e.g. hints, come before it.
+fn statement_starts(dialect: &dyn Dialect, sql: &str) -> Result<Vec<(Location, String)>, Error> {
+    let mut starts = Vec::new();
+    for (statement, span) in split_statements(dialect, sql) {
+        let tokens = Tokenizer::new(dialect, &statement)
+            .tokenize_with_location()
+            .map_err(ParserError::from)?;
+        let first = tokens
+            .into_iter()
+            .find(|token| !matches!(token.token, Token::Whitespace(_) | Token::EOF));
+        if let Some(TokenWithLocation { token, location }) = first {
+            let offset = offset(&statement, Location::from(location))?;
+            starts.push((span.start.advance(&statement[..offset]), token.to_string()));
+        }
+    }
+    Ok(starts)
+}
+
+pub(crate) fn offset(sql: &str, location: Location) -> Result<usize, Error> {
+    location.offset_in(sql).ok_or_else(|| {
+        Error::AnalysisError(format!(
+            "Location {}:{} is out of the SQL",
+            location.line, location.column
+        ))
+    })
+}
+
+fn url_encode(text: &str) -> String {
+    text.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+    use sqlparser::dialect::{PostgreSqlDialect, RedshiftSqlDialect};
+
+    #[test]
+    fn test_inject_tags() {
+        let sql = "/* existing */ SELECT a FROM t1;\n  UPDATE t2 SET b = 'x;y';;";
+        let injector = CommentInjector::new()
+            .with_tag("controller", "index")
+            .with_tag("action", "it's");
+        for dialect in all_dialects() {
+            assert_eq!(
+                injector.inject(dialect.as_ref(), sql).unwrap(),
+                "/* existing */ /*action='it%27s',controller='index'*/ SELECT a FROM t1;\n  \
+                    /*action='it%27s',controller='index'*/ UPDATE t2 SET b = 'x;y';;",
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_inject_hints() {
+        let sql = "SELECT a FROM t1; DELETE FROM t2";
+        let injector = CommentInjector::new()
+            .with_tag("app", "api")
+            .with_hint("SeqScan(t1)")
+            .with_hint("NoIndexScan(t2)");
+        assert_eq!(
+            injector.inject(&PostgreSqlDialect {}, sql).unwrap(),
+            "/*app='api'*/ /*+ SeqScan(t1) NoIndexScan(t2) */ SELECT a FROM t1; \
+                /*app='api'*/ /*+ SeqScan(t1) NoIndexScan(t2) */ DELETE FROM t2"
+        );
+        assert_eq!(
+            injector.inject(&MySqlDialect {}, sql).unwrap(),
+            "/*app='api'*/ SELECT /*+ SeqScan(t1) NoIndexScan(t2) */ a FROM t1; \
+                /*app='api'*/ DELETE /*+ SeqScan(t1) NoIndexScan(t2) */ FROM t2"
+        );
+    }
+
+    #[test]
+    fn test_inject_hints_ending_comment() {
+        let injector = CommentInjector::new().with_hint("BKA(t) */ DELETE FROM users; /*");
+        for dialect in all_dialects() {
+            assert_eq!(
+                injector.inject(dialect.as_ref(), "SELECT a FROM t"),
+                Err(Error::ArgumentError(
+                    "Hint must not contain comment delimiters: BKA(t) */ DELETE FROM users; /*"
+                        .into()
+                )),
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_strip_comments() {
+        let sql = "/* leading */ SELECT a, -- first\n  b/*x*/+/*y*/c FROM t1; -- trailing";
+        for dialect in all_dialects() {
+            assert_eq!(
+                strip_comments(dialect.as_ref(), sql).unwrap(),
+                " SELECT a, \n  b + c FROM t1; ",
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_strip_comments_keeps_literals() {
+        let sql = "SELECT '/* a */', \"-- b\" FROM t1 WHERE c = 'é' /* é */";
+        for dialect in all_dialects() {
+            let result = strip_comments(dialect.as_ref(), sql);
+            // Redshift only reads double quotes starting an identifier as a quoted identifier.
+            if dialect.is::<RedshiftSqlDialect>() {
+                assert_eq!(
+                    result,
+                    Err(Error::AnalysisError(
+                        "Cannot strip comments after unterminated \" at 1:19".into()
+                    ))
+                );
+                continue;
+            }
+            assert_eq!(
+                result.unwrap(),
+                "SELECT '/* a */', \"-- b\" FROM t1 WHERE c = 'é' ",
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_inject_nothing() {
+        let sql = "SELECT a FROM t1 -- comment";
+        for dialect in all_dialects() {
+            assert_eq!(
+                CommentInjector::new()
+                    .inject(dialect.as_ref(), sql)
+                    .unwrap(),
+                sql,
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,205 @@
+//! Hint stripping, which works on the source text since hints are comments or,
+//! in the case of index hints, not carried by the AST for every dialect.
+
+use std::ops::Range;
+
+use crate::error::Error;
+use crate::rewriter::comment::offset;
+use crate::span::Location;
+use sqlparser::dialect::Dialect;
+use sqlparser::parser::ParserError;
+use sqlparser::tokenizer::{Token, TokenWithLocation, Tokenizer, Whitespace};
+
+/// Remove index hints and optimizer hints from SQL, leaving everything else as it is.
+///
+/// Index hints are MySQL's `USE`, `FORCE` and `IGNORE` `INDEX`/`KEY` clauses, including `FOR JOIN`,
+/// `FOR ORDER BY` and `FOR GROUP BY`. Optimizer hints are comments starting with `+`, i.e. `/*+ ... */` and `--+ ...`.
+/// Whitespace separating a removed hint from the rest of the SQL is removed along with it.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::MySqlDialect;
+///
+/// let dialect = MySqlDialect {};
+/// let sql = "SELECT /*+ NO_ICP(t1) */ a FROM t1 FORCE INDEX (i1) IGNORE KEY FOR ORDER BY (i2) WHERE b = 1 /* note */";
+/// let result = sql_insight::strip_hints(&dialect, sql).unwrap();
+/// assert_eq!(result, "SELECT a FROM t1 WHERE b = 1 /* note */");
+/// ```
+pub fn strip_hints(dialect: &dyn Dialect, sql: &str) -> Result<String, Error> {
+    let tokens: Vec<TokenWithLocation> = Tokenizer::new(dialect, sql)
+        .tokenize_with_location()
+        .map_err(ParserError::from)?
+        .into_iter()
+        .filter(|token| token.token != Token::EOF)
+        .collect();
+    let mut result = String::with_capacity(sql.len());
+    let mut copied = 0;
+    for range in hint_ranges(&tokens) {
+        let range = with_surrounding_whitespace(&tokens, range);
+        let start = offset(sql, Location::from(tokens[range.start].location))?;
+        let mut end = match tokens.get(range.end) {
+            Some(token) => offset(sql, Location::from(token.location))?,
+            None => sql.len(),
+        };
+        // A line comment ends with the line break, which still separates the lines around it.
+        if matches!(
+            &tokens[range.end - 1].token,
+            Token::Whitespace(Whitespace::SingleLineComment { comment, .. }) if comment.ends_with('\n')
+        ) {
+            end -= 1;
+        }
+        // Ranges widened over the same whitespace may overlap.
+        if copied < start {
+            result.push_str(&sql[copied..start]);
+        }
+        copied = copied.max(end);
+    }
+    result.push_str(&sql[copied..]);
+    Ok(result)
+}
+
+/// Token ranges of hints, in input order.
+pub(crate) fn hint_ranges(tokens: &[TokenWithLocation]) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        if is_hint_comment(&tokens[i].token) {
+            ranges.push(i..i + 1);
+            i += 1;
+        } else if let Some(end) = index_hint_end(tokens, i) {
+            ranges.push(i..end);
+            i = end;
+        } else {
+            i += 1;
+        }
+    }
+    ranges
+}
+
+pub(crate) fn is_hint_comment(token: &Token) -> bool {
+    match token {
+        Token::Whitespace(Whitespace::MultiLineComment(comment))
+        | Token::Whitespace(Whitespace::SingleLineComment { comment, .. }) => {
+            comment.starts_with('+')
+        }
+        _ => false,
+    }
+}
+
+fn is_blank(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::Whitespace(Whitespace::Space | Whitespace::Tab | Whitespace::Newline)
+    )
+}
+
+/// Extend a range over the whitespace before it, or after it when there is none before.
+fn with_surrounding_whitespace(tokens: &[TokenWithLocation], range: Range<usize>) -> Range<usize> {
+    let mut start = range.start;
+    while start > 0 && is_blank(&tokens[start - 1].token) {
+        start -= 1;
+    }
+    if start < range.start {
+        return start..range.end;
+    }
+    let mut end = range.end;
+    while end < tokens.len() && is_blank(&tokens[end].token) {
+        end += 1;
+    }
+    start..end
+}
+
+/// End of an index hint starting at `start`, exclusive.
+fn index_hint_end(tokens: &[TokenWithLocation], start: usize) -> Option<usize> {
+    let next = |from: usize| {
+        (from..tokens.len()).find(|&i| !matches!(tokens[i].token, Token::Whitespace(_)))
+    };
+    let is_word = |i: usize, words: &[&str]| match &tokens[i].token {
+        Token::Word(word) if word.quote_style.is_none() => {
+            words.iter().any(|w| word.value.eq_ignore_ascii_case(w))
+        }
+        _ => false,
+    };
+    if !is_word(start, &["USE", "FORCE", "IGNORE"]) {
+        return None;
+    }
+    let mut i = next(start + 1)?;
+    if !is_word(i, &["INDEX", "KEY"]) {
+        return None;
+    }
+    i = next(i + 1)?;
+    if is_word(i, &["FOR"]) {
+        i = next(i + 1)?;
+        if is_word(i, &["ORDER", "GROUP"]) {
+            i = next(i + 1)?;
+            if !is_word(i, &["BY"]) {
+                return None;
+            }
+        } else if !is_word(i, &["JOIN"]) {
+            return None;
+        }
+        i = next(i + 1)?;
+    }
+    if tokens[i].token != Token::LParen {
+        return None;
+    }
+    let mut depth = 0;
+    for (j, token) in tokens.iter().enumerate().skip(i) {
+        match token.token {
+            Token::LParen => depth += 1,
+            Token::RParen => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(j + 1);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+
+    #[test]
+    fn test_strip_index_hints() {
+        let sql =
+            "SELECT * FROM t1 USE INDEX (i1, i2) JOIN t2 FORCE KEY FOR JOIN (i3) ON t1.a = t2.a \
+            IGNORE INDEX FOR GROUP BY (i4) GROUP BY t1.b";
+        for dialect in all_dialects() {
+            assert_eq!(
+                strip_hints(dialect.as_ref(), sql).unwrap(),
+                "SELECT * FROM t1 JOIN t2 ON t1.a = t2.a GROUP BY t1.b",
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_strip_hint_comments() {
+        let sql = "/*+ SeqScan(t1) */ SELECT a --+ FULL(t1)\nFROM t1 /* keep */ WHERE b = 'USE INDEX (i1)'";
+        for dialect in all_dialects() {
+            assert_eq!(
+                strip_hints(dialect.as_ref(), sql).unwrap(),
+                "SELECT a\nFROM t1 /* keep */ WHERE b = 'USE INDEX (i1)'",
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_use_without_index_is_kept() {
+        let sql = "USE db1; SELECT use FROM t1";
+        for dialect in all_dialects() {
+            assert_eq!(
+                strip_hints(dialect.as_ref(), sql).unwrap(),
+                sql,
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,164 @@
+//! A Rewriter that transforms SQL by applying a sequence of rewrites to each statement.
+//!
+//! Rewrites implement the [`Rewrite`] trait and a [`Rewriter`] applies them in order.
+//! Built-in rewrites are found in the [`rewrites`] module.
+//! Rewrites of comments and hints, which the AST does not carry, work on the source text instead,
+//! e.g. [`CommentInjector`] and [`strip_hints`].
+//!
+//! See [`rewrite`](crate::rewrite()) as the entry point for rewriting SQL,
+//! and [`transpile`](crate::transpile()) for translating SQL between dialects.
+
+pub mod comment;
+pub mod hints;
+pub mod rewrite;
+pub mod rewrites;
+pub mod transpile;
+
+pub use comment::*;
+pub use hints::*;
+pub use rewrite::*;
+pub use rewrites::*;
+pub use transpile::*;
+
+use crate::error::Error;
+use crate::instrument;
+use sqlparser::ast::Statement;
+use sqlparser::dialect::Dialect;
+
+/// Convenience function to rewrite SQL with rewrites applied in order.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::error::Error;
+/// use sql_insight::sqlparser::ast::Statement;
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+/// use sql_insight::Rewrite;
+///
+/// let dialect = GenericDialect {};
+/// let add_limit = |statement: &mut Statement| -> Result<(), Error> {
+///     if let Statement::Query(query) = statement {
+///         query.limit.get_or_insert(sql_insight::sqlparser::ast::Expr::Value(
+///             sql_insight::sqlparser::ast::Value::Number("10".into(), false),
+///         ));
+///     }
+///     Ok(())
+/// };
+/// let rewrites: Vec<Box<dyn Rewrite>> = vec![Box::new(add_limit)];
+/// let result = sql_insight::rewrite(&dialect, "SELECT a FROM t1", rewrites).unwrap();
+/// assert_eq!(result, ["SELECT a FROM t1 LIMIT 10"]);
+/// ```
+pub fn rewrite(
+    dialect: &dyn Dialect,
+    sql: &str,
+    rewrites: Vec<Box<dyn Rewrite>>,
+) -> Result<Vec<String>, Error> {
+    Rewriter::new(rewrites).rewrite(dialect, sql)
+}
+
+/// [`Rewriter`] applies an ordered list of [`Rewrite`]s to statements.
+/// Each rewrite sees the statement as rewritten by the previous ones.
+#[derive(Default)]
+pub struct Rewriter {
+    rewrites: Vec<Box<dyn Rewrite>>,
+}
+
+impl Rewriter {
+    pub fn new(rewrites: Vec<Box<dyn Rewrite>>) -> Self {
+        Self { rewrites }
+    }
+
+    /// Append a rewrite, applied after the rewrites already added.
+    pub fn with_rewrite(mut self, rewrite: Box<dyn Rewrite>) -> Self {
+        self.rewrites.push(rewrite);
+        self
+    }
+
+    /// Rewrite SQL, returning the rewritten statements.
+    pub fn rewrite(&self, dialect: &dyn Dialect, sql: &str) -> Result<Vec<String>, Error> {
+        let mut statements = instrument::parse_sql(dialect, sql)?;
+        self.rewrite_statements(&mut statements)?;
+        Ok(statements
+            .into_iter()
+            .map(|statement| statement.to_string())
+            .collect())
+    }
+
+    /// Rewrite parsed statements in place.
+    pub fn rewrite_statements(&self, statements: &mut [Statement]) -> Result<(), Error> {
+        statements
+            .iter_mut()
+            .try_for_each(|statement| self.rewrite_statement(statement))
+    }
+
+    /// Rewrite a parsed statement in place.
+    pub fn rewrite_statement(&self, statement: &mut Statement) -> Result<(), Error> {
+        self.rewrites
+            .iter()
+            .try_for_each(|rewrite| rewrite.rewrite(statement))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+    use sqlparser::ast::{Expr, Ident, VisitorMut};
+    use std::ops::ControlFlow;
+
+    struct IdentifierRewriter(fn(&str) -> String);
+
+    impl VisitorMut for IdentifierRewriter {
+        type Break = Error;
+
+        fn pre_visit_expr(&mut self, expr: &mut Expr) -> ControlFlow<Self::Break> {
+            if let Expr::Identifier(Ident { value, .. }) = expr {
+                if value == "forbidden" {
+                    return ControlFlow::Break(Error::AnalysisError("Forbidden identifier".into()));
+                }
+                *value = (self.0)(value);
+            }
+            ControlFlow::Continue(())
+        }
+    }
+
+    fn uppercase(statement: &mut Statement) -> Result<(), Error> {
+        rewrite_with_visitor(statement, &mut IdentifierRewriter(|v| v.to_uppercase()))
+    }
+
+    fn suffix(statement: &mut Statement) -> Result<(), Error> {
+        rewrite_with_visitor(statement, &mut IdentifierRewriter(|v| format!("{}_v", v)))
+    }
+
+    #[test]
+    fn test_rewrites_are_applied_in_order() {
+        let sql = "SELECT a FROM t1 WHERE b = 1; SELECT c FROM t2";
+        for dialect in all_dialects() {
+            let result = rewrite(
+                dialect.as_ref(),
+                sql,
+                vec![Box::new(uppercase), Box::new(suffix)],
+            )
+            .unwrap();
+            assert_eq!(
+                result,
+                vec!["SELECT A_v FROM t1 WHERE B_v = 1", "SELECT C_v FROM t2"],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_rewrite_error() {
+        for dialect in all_dialects() {
+            let result = Rewriter::default()
+                .with_rewrite(Box::new(uppercase))
+                .rewrite(dialect.as_ref(), "SELECT forbidden FROM t1");
+            assert_eq!(
+                result,
+                Err(Error::AnalysisError("Forbidden identifier".into())),
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,35 @@
+//! The [`Rewrite`] trait implemented by every rewrite.
+
+use std::ops::ControlFlow;
+
+use crate::error::Error;
+use sqlparser::ast::{Statement, VisitMut, VisitorMut};
+
+/// [`Rewrite`] transforms a single statement in place.
+///
+/// Most rewrites are implemented with a [`VisitorMut`] breaking with an [`Error`] when the statement
+/// cannot be rewritten; see [`rewrite_with_visitor`]. Closures taking `&mut Statement` are rewrites too.
+pub trait Rewrite {
+    /// Rewrite a statement.
+    fn rewrite(&self, statement: &mut Statement) -> Result<(), Error>;
+}
+
+impl<F> Rewrite for F
+where
+    F: Fn(&mut Statement) -> Result<(), Error>,
+{
+    fn rewrite(&self, statement: &mut Statement) -> Result<(), Error> {
+        self(statement)
+    }
+}
+
+/// Rewrite a statement with a [`VisitorMut`], turning its break value into an error.
+pub fn rewrite_with_visitor<V>(statement: &mut Statement, visitor: &mut V) -> Result<(), Error>
+where
+    V: VisitorMut<Break = Error>,
+{
+    match statement.visit(visitor) {
+        ControlFlow::Break(e) => Err(e),
+        ControlFlow::Continue(()) => Ok(()),
+    }
+}
@@ -0,0 +1,390 @@
+//! Rewrite that binds values to placeholders.
+
+use std::collections::HashMap;
+use std::ops::ControlFlow;
+
+use crate::error::Error;
+use crate::rewriter::{rewrite_with_visitor, Rewrite};
+use sqlparser::ast::{Expr, Statement, Value, VisitorMut};
+use sqlparser::dialect::{
+    BigQueryDialect, ClickHouseDialect, Dialect, GenericDialect, HiveDialect, MySqlDialect,
+    SnowflakeDialect,
+};
+
+/// [`Param`] is a value bound to a placeholder.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Param {
+    Null,
+    Boolean(bool),
+    Integer(i64),
+    Float(f64),
+    String(String),
+}
+
+impl From<bool> for Param {
+    fn from(value: bool) -> Self {
+        Param::Boolean(value)
+    }
+}
+
+impl From<i32> for Param {
+    fn from(value: i32) -> Self {
+        Param::Integer(value.into())
+    }
+}
+
+impl From<i64> for Param {
+    fn from(value: i64) -> Self {
+        Param::Integer(value)
+    }
+}
+
+impl From<f64> for Param {
+    fn from(value: f64) -> Self {
+        Param::Float(value)
+    }
+}
+
+impl From<&str> for Param {
+    fn from(value: &str) -> Self {
+        Param::String(value.to_string())
+    }
+}
+
+impl From<String> for Param {
+    fn from(value: String) -> Self {
+        Param::String(value)
+    }
+}
+
+impl<T: Into<Param>> From<Option<T>> for Param {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Param::Null, Into::into)
+    }
+}
+
+/// [`BindParams`] replaces placeholders with literal values, producing executable statements.
+///
+/// `?` placeholders take positional values in order of appearance, starting over for each statement,
+/// and `$N` placeholders take the N-th positional value. Named placeholders such as `:name`, `@name` and `$name`
+/// take the value bound to `name`. A placeholder without a value is an error.
+///
+/// Strings are quoted by doubling every single quote. Backslashes are also doubled for dialects
+/// treating them as escape characters, i.e. MySQL, BigQuery, Snowflake, Hive and ClickHouse,
+/// and for the generic dialect, whose target is unknown. Floats must be finite.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::MySqlDialect;
+/// use sql_insight::BindParams;
+///
+/// let dialect = MySqlDialect {};
+/// let sql = "SELECT a FROM t1 WHERE b = ? AND c IN (?, ?)";
+/// let bind = BindParams::new(&dialect)
+///     .with_positional(vec!["it's \\ ok".into(), 1.into(), None::<i64>.into()]);
+/// let result = sql_insight::rewrite(&dialect, sql, vec![Box::new(bind)]).unwrap();
+/// assert_eq!(result, ["SELECT a FROM t1 WHERE b = 'it''s \\\\ ok' AND c IN (1, NULL)"]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct BindParams {
+    positional: Vec<Param>,
+    named: HashMap<String, Param>,
+    escape_backslashes: bool,
+}
+
+impl BindParams {
+    /// Bind values for statements of the given dialect, which decides how strings are quoted.
+    pub fn new(dialect: &dyn Dialect) -> Self {
+        Self {
+            positional: Vec::new(),
+            named: HashMap::new(),
+            escape_backslashes: dialect.is::<MySqlDialect>()
+                || dialect.is::<BigQueryDialect>()
+                || dialect.is::<SnowflakeDialect>()
+                || dialect.is::<HiveDialect>()
+                || dialect.is::<ClickHouseDialect>()
+                || dialect.is::<GenericDialect>(),
+        }
+    }
+
+    /// Values for `?` and `$N` placeholders.
+    pub fn with_positional(mut self, params: Vec<Param>) -> Self {
+        self.positional = params;
+        self
+    }
+
+    /// Bind a value to a named placeholder. The name is given without its prefix, e.g. `id` for `:id`.
+    pub fn with_named(mut self, name: &str, param: impl Into<Param>) -> Self {
+        self.named.insert(name.to_string(), param.into());
+        self
+    }
+
+    fn lookup(&self, placeholder: &str, next_position: &mut usize) -> Result<&Param, Error> {
+        let param = if placeholder == "?" {
+            let position = *next_position;
+            *next_position += 1;
+            self.positional.get(position)
+        } else if let Some(position) = placeholder
+            .strip_prefix('$')
+            .and_then(|n| n.parse::<usize>().ok())
+        {
+            position
+                .checked_sub(1)
+                .and_then(|index| self.positional.get(index))
+        } else {
+            placeholder
+                .strip_prefix([':', '@', '$'])
+                .and_then(|name| self.named.get(name))
+        };
+        param.ok_or_else(|| {
+            Error::ArgumentError(format!("No value is bound to placeholder {placeholder}"))
+        })
+    }
+
+    // Strings are rendered here rather than by `Value::SingleQuotedString`,
+    // whose display leaves a quote following a backslash undoubled.
+    fn literal(&self, param: &Param) -> Result<Value, Error> {
+        Ok(match param {
+            Param::Null => Value::Null,
+            Param::Boolean(value) => Value::Boolean(*value),
+            Param::Integer(value) => Value::Number(value.to_string(), false),
+            Param::Float(value) if !value.is_finite() => {
+                return Err(Error::ArgumentError(format!(
+                    "Float value {value} cannot be bound"
+                )))
+            }
+            Param::Float(value) => Value::Number(value.to_string(), false),
+            Param::String(value) => {
+                let mut quoted = String::with_capacity(value.len() + 2);
+                quoted.push('\'');
+                for c in value.chars() {
+                    match c {
+                        '\'' => quoted.push_str("''"),
+                        '\\' if self.escape_backslashes => quoted.push_str("\\\\"),
+                        c => quoted.push(c),
+                    }
+                }
+                quoted.push('\'');
+                Value::UnQuotedString(quoted)
+            }
+        })
+    }
+}
+
+impl Rewrite for BindParams {
+    fn rewrite(&self, statement: &mut Statement) -> Result<(), Error> {
+        rewrite_with_visitor(
+            statement,
+            &mut BindParamsVisitor {
+                params: self,
+                next_position: 0,
+            },
+        )
+    }
+}
+
+struct BindParamsVisitor<'a> {
+    params: &'a BindParams,
+    next_position: usize,
+}
+
+impl VisitorMut for BindParamsVisitor<'_> {
+    type Break = Error;
+
+    fn pre_visit_expr(&mut self, expr: &mut Expr) -> ControlFlow<Self::Break> {
+        if let Expr::Value(Value::Placeholder(placeholder)) = expr {
+            match self
+                .params
+                .lookup(placeholder, &mut self.next_position)
+                .and_then(|param| self.params.literal(param))
+            {
+                Ok(value) => *expr = Expr::Value(value),
+                Err(e) => return ControlFlow::Break(e),
+            }
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+    use sqlparser::ast::{BinaryOperator, SetExpr};
+    use sqlparser::dialect::PostgreSqlDialect;
+    use sqlparser::parser::Parser;
+
+    #[test]
+    fn test_positional() {
+        let sql = "SELECT a FROM t1 WHERE b = ? AND c = ? LIMIT ?; UPDATE t1 SET a = ? WHERE b = ?";
+        for dialect in all_dialects() {
+            let bind = BindParams::new(dialect.as_ref()).with_positional(vec![
+                "x".into(),
+                1.5.into(),
+                10.into(),
+                true.into(),
+                Param::Null,
+            ]);
+            let result = crate::rewrite(dialect.as_ref(), sql, vec![Box::new(bind)]);
+            assert_eq!(
+                result.unwrap(),
+                vec![
+                    "SELECT a FROM t1 WHERE b = 'x' AND c = 1.5 LIMIT 10",
+                    "UPDATE t1 SET a = 'x' WHERE b = 1.5",
+                ],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_numbered_and_named() {
+        let bind = BindParams::new(&PostgreSqlDialect {})
+            .with_positional(vec!["a\\b".into(), (-3).into()]);
+        let result = crate::rewrite(
+            &PostgreSqlDialect {},
+            "SELECT * FROM t1 WHERE a = $2 OR b = $1 OR c = $2",
+            vec![Box::new(bind)],
+        );
+        assert_eq!(
+            result.unwrap(),
+            vec!["SELECT * FROM t1 WHERE a = -3 OR b = 'a\\b' OR c = -3"]
+        );
+
+        let bind = BindParams::new(&GenericDialect {})
+            .with_named("id", 42)
+            .with_named("name", "O'Brien");
+        let result = crate::rewrite(
+            &GenericDialect {},
+            "SELECT * FROM t1 WHERE id = :id AND name = :name",
+            vec![Box::new(bind)],
+        );
+        assert_eq!(
+            result.unwrap(),
+            vec!["SELECT * FROM t1 WHERE id = 42 AND name = 'O''Brien'"]
+        );
+    }
+
+    #[test]
+    fn test_missing_value() {
+        for dialect in all_dialects() {
+            let bind = BindParams::new(dialect.as_ref()).with_positional(vec![1.into()]);
+            let result = crate::rewrite(
+                dialect.as_ref(),
+                "SELECT * FROM t1 WHERE a = ? AND b = ?",
+                vec![Box::new(bind)],
+            );
+            assert_eq!(
+                result,
+                Err(Error::ArgumentError(
+                    "No value is bound to placeholder ?".into()
+                )),
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_injection_payloads() {
+        let payloads = ["\\' OR 1=1 -- ", "'' OR 1=1 -- ", "\\\\' OR 1=1 -- ", "x\\"];
+        for dialect in all_dialects() {
+            for payload in payloads {
+                let bind = BindParams::new(dialect.as_ref()).with_positional(vec![payload.into()]);
+                let result = crate::rewrite(
+                    dialect.as_ref(),
+                    "SELECT a FROM t1 WHERE b = ? AND c = 1",
+                    vec![Box::new(bind)],
+                )
+                .unwrap();
+                // The bound value must stay a single string literal compared with `b`.
+                let statements = Parser::parse_sql(dialect.as_ref(), &result[0]).unwrap();
+                let Statement::Query(query) = &statements[0] else {
+                    panic!("Expected a query for dialect: {dialect:?}");
+                };
+                let SetExpr::Select(select) = query.body.as_ref() else {
+                    panic!("Expected a select for dialect: {dialect:?}");
+                };
+                let Some(Expr::BinaryOp { left, op, .. }) = &select.selection else {
+                    panic!("Expected a binary operation for dialect: {dialect:?}");
+                };
+                assert_eq!(*op, BinaryOperator::And, "Failed for dialect: {dialect:?}");
+                assert!(
+                    matches!(
+                        left.as_ref(),
+                        Expr::BinaryOp { op: BinaryOperator::Eq, right, .. }
+                            if matches!(right.as_ref(), Expr::Value(Value::SingleQuotedString(_)))
+                    ),
+                    "Failed for dialect: {dialect:?}, payload: {payload}, result: {}",
+                    result[0]
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_injection_quoting_per_dialect() {
+        let payload = "\\' OR 1=1 -- ";
+        for (dialect, expected) in [
+            (
+                Box::new(MySqlDialect {}) as Box<dyn Dialect>,
+                "SELECT a FROM t1 WHERE b = '\\\\'' OR 1=1 -- '",
+            ),
+            (
+                Box::new(BigQueryDialect {}),
+                "SELECT a FROM t1 WHERE b = '\\\\'' OR 1=1 -- '",
+            ),
+            (
+                Box::new(SnowflakeDialect {}),
+                "SELECT a FROM t1 WHERE b = '\\\\'' OR 1=1 -- '",
+            ),
+            (
+                Box::new(HiveDialect {}),
+                "SELECT a FROM t1 WHERE b = '\\\\'' OR 1=1 -- '",
+            ),
+            (
+                Box::new(ClickHouseDialect {}),
+                "SELECT a FROM t1 WHERE b = '\\\\'' OR 1=1 -- '",
+            ),
+            (
+                Box::new(GenericDialect {}),
+                "SELECT a FROM t1 WHERE b = '\\\\'' OR 1=1 -- '",
+            ),
+            (
+                Box::new(PostgreSqlDialect {}),
+                "SELECT a FROM t1 WHERE b = '\\'' OR 1=1 -- '",
+            ),
+        ] {
+            let bind = BindParams::new(dialect.as_ref()).with_positional(vec![payload.into()]);
+            let result = crate::rewrite(
+                dialect.as_ref(),
+                "SELECT a FROM t1 WHERE b = ?",
+                vec![Box::new(bind)],
+            );
+            assert_eq!(
+                result.unwrap(),
+                vec![expected],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_non_finite_float() {
+        for value in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            let bind = BindParams::new(&GenericDialect {}).with_positional(vec![value.into()]);
+            let result = crate::rewrite(
+                &GenericDialect {},
+                "SELECT * FROM t1 WHERE a = ?",
+                vec![Box::new(bind)],
+            );
+            assert_eq!(
+                result,
+                Err(Error::ArgumentError(format!(
+                    "Float value {value} cannot be bound"
+                )))
+            );
+        }
+    }
+}
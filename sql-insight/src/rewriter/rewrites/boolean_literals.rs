@@ -0,0 +1,68 @@
+//! Rewrite that writes boolean literals as integers.
+
+use std::ops::ControlFlow;
+
+use crate::error::Error;
+use crate::rewriter::{rewrite_with_visitor, Rewrite};
+use sqlparser::ast::{Expr, Statement, Value, VisitorMut};
+
+/// [`BooleansAsIntegers`] replaces `TRUE` and `FALSE` with `1` and `0`, for dialects without boolean literals such as MsSQL.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+/// use sql_insight::BooleansAsIntegers;
+///
+/// let dialect = GenericDialect {};
+/// let sql = "UPDATE t1 SET active = FALSE WHERE deleted = TRUE";
+/// let result = sql_insight::rewrite(&dialect, sql, vec![Box::new(BooleansAsIntegers::new())]).unwrap();
+/// assert_eq!(result, ["UPDATE t1 SET active = 0 WHERE deleted = 1"]);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct BooleansAsIntegers;
+
+impl BooleansAsIntegers {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Rewrite for BooleansAsIntegers {
+    fn rewrite(&self, statement: &mut Statement) -> Result<(), Error> {
+        rewrite_with_visitor(statement, &mut BooleansAsIntegersVisitor)
+    }
+}
+
+struct BooleansAsIntegersVisitor;
+
+impl VisitorMut for BooleansAsIntegersVisitor {
+    type Break = Error;
+
+    fn pre_visit_expr(&mut self, expr: &mut Expr) -> ControlFlow<Self::Break> {
+        if let Expr::Value(Value::Boolean(value)) = expr {
+            let number = if *value { "1" } else { "0" };
+            *expr = Expr::Value(Value::Number(number.into(), false));
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlparser::dialect::GenericDialect;
+
+    #[test]
+    fn test_booleans_as_integers() {
+        let result = crate::rewrite(
+            &GenericDialect {},
+            "SELECT a FROM t1 WHERE b = TRUE AND c IN (FALSE, 'true')",
+            vec![Box::new(BooleansAsIntegers::new())],
+        );
+        assert_eq!(
+            result.unwrap(),
+            vec!["SELECT a FROM t1 WHERE b = 1 AND c IN (0, 'true')"]
+        );
+    }
+}
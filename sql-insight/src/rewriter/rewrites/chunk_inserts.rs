@@ -0,0 +1,122 @@
+//! Splitting of multi-row INSERTs into INSERTs of bounded size.
+
+use crate::error::Error;
+use crate::instrument;
+use sqlparser::ast::{SetExpr, Statement};
+use sqlparser::dialect::Dialect;
+
+/// Convenience function to split INSERTs with many VALUES rows into INSERTs of at most `max_rows` rows each.
+/// Other statements are returned as they are.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+///
+/// let dialect = GenericDialect {};
+/// let sql = "INSERT INTO t1 (a, b) VALUES (1, 'x'), (2, 'y'), (3, 'z')";
+/// let result = sql_insight::chunk_inserts(&dialect, sql, 2).unwrap();
+/// assert_eq!(
+///     result,
+///     ["INSERT INTO t1 (a, b) VALUES (1, 'x'), (2, 'y')", "INSERT INTO t1 (a, b) VALUES (3, 'z')"]
+/// );
+/// ```
+pub fn chunk_inserts(
+    dialect: &dyn Dialect,
+    sql: &str,
+    max_rows: usize,
+) -> Result<Vec<String>, Error> {
+    let chunker = ChunkInserts::new(max_rows)?;
+    Ok(instrument::parse_sql(dialect, sql)?
+        .into_iter()
+        .flat_map(|statement| chunker.split(statement))
+        .map(|statement| statement.to_string())
+        .collect())
+}
+
+/// [`ChunkInserts`] splits an INSERT with many VALUES rows into INSERTs of at most a number of rows each,
+/// keeping the rest of the statement such as the column list.
+///
+/// Unlike rewrites, which transform a statement in place, this turns a statement into several,
+/// so it is applied with [`ChunkInserts::split`] or [`chunk_inserts`].
+#[derive(Clone, Debug)]
+pub struct ChunkInserts {
+    max_rows: usize,
+}
+
+impl ChunkInserts {
+    /// Create a chunker of at most `max_rows` rows per INSERT, which must be positive.
+    pub fn new(max_rows: usize) -> Result<Self, Error> {
+        if max_rows == 0 {
+            return Err(Error::ArgumentError(
+                "Number of rows per INSERT must be positive".into(),
+            ));
+        }
+        Ok(Self { max_rows })
+    }
+
+    /// Split an INSERT into INSERTs of at most the number of rows each.
+    /// Statements other than INSERTs with VALUES, or with few enough rows, are returned as they are.
+    pub fn split(&self, statement: Statement) -> Vec<Statement> {
+        let rows = match &statement {
+            Statement::Insert {
+                source: Some(source),
+                ..
+            } => match source.body.as_ref() {
+                SetExpr::Values(values) if values.rows.len() > self.max_rows => values.rows.clone(),
+                _ => return vec![statement],
+            },
+            _ => return vec![statement],
+        };
+        rows.chunks(self.max_rows)
+            .map(|chunk| {
+                let mut statement = statement.clone();
+                if let Statement::Insert {
+                    source: Some(source),
+                    ..
+                } = &mut statement
+                {
+                    if let SetExpr::Values(values) = source.body.as_mut() {
+                        values.rows = chunk.to_vec();
+                    }
+                }
+                statement
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+
+    #[test]
+    fn test_chunk_inserts() {
+        let sql = "INSERT INTO t1 (a) VALUES (1), (2), (3), (4), (5); \
+            INSERT INTO t1 (a) VALUES (6); INSERT INTO t1 (a) SELECT b FROM t2; DELETE FROM t1";
+        for dialect in all_dialects() {
+            let result = chunk_inserts(dialect.as_ref(), sql, 2).unwrap();
+            assert_eq!(
+                result,
+                vec![
+                    "INSERT INTO t1 (a) VALUES (1), (2)",
+                    "INSERT INTO t1 (a) VALUES (3), (4)",
+                    "INSERT INTO t1 (a) VALUES (5)",
+                    "INSERT INTO t1 (a) VALUES (6)",
+                    "INSERT INTO t1 (a) SELECT b FROM t2",
+                    "DELETE FROM t1",
+                ],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_zero_rows() {
+        assert_eq!(
+            ChunkInserts::new(0).unwrap_err(),
+            Error::ArgumentError("Number of rows per INSERT must be positive".into())
+        );
+    }
+}
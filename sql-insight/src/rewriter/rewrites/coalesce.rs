@@ -0,0 +1,81 @@
+//! Rewrite that replaces dialect-specific null-coalescing functions with COALESCE.
+
+use std::ops::ControlFlow;
+
+use crate::error::Error;
+use crate::rewriter::{rewrite_with_visitor, Rewrite};
+use sqlparser::ast::{Expr, Ident, ObjectName, Statement, VisitorMut};
+
+/// [`IfNullToCoalesce`] replaces `IFNULL`, `NVL` and two-argument `ISNULL`, which are specific to some dialects,
+/// with the standard `COALESCE`.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::MySqlDialect;
+/// use sql_insight::IfNullToCoalesce;
+///
+/// let dialect = MySqlDialect {};
+/// let sql = "SELECT IFNULL(a, 0) FROM t1";
+/// let result = sql_insight::rewrite(&dialect, sql, vec![Box::new(IfNullToCoalesce::new())]).unwrap();
+/// assert_eq!(result, ["SELECT COALESCE(a, 0) FROM t1"]);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct IfNullToCoalesce;
+
+impl IfNullToCoalesce {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Rewrite for IfNullToCoalesce {
+    fn rewrite(&self, statement: &mut Statement) -> Result<(), Error> {
+        rewrite_with_visitor(statement, &mut IfNullToCoalesceVisitor)
+    }
+}
+
+struct IfNullToCoalesceVisitor;
+
+impl VisitorMut for IfNullToCoalesceVisitor {
+    type Break = Error;
+
+    fn pre_visit_expr(&mut self, expr: &mut Expr) -> ControlFlow<Self::Break> {
+        if let Expr::Function(function) = expr {
+            let is_if_null = match function.name.0.as_slice() {
+                [name] => ["IFNULL", "NVL", "ISNULL"]
+                    .iter()
+                    .any(|f| name.quote_style.is_none() && name.value.eq_ignore_ascii_case(f)),
+                _ => false,
+            };
+            if is_if_null && function.args.len() == 2 {
+                function.name = ObjectName(vec![Ident::new("COALESCE")]);
+            }
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+
+    #[test]
+    fn test_if_null_to_coalesce() {
+        let sql =
+            "SELECT IFNULL(a, 0), nvl(b, 'x'), ISNULL(c, 1), ISNULL(d), COALESCE(e, 2) FROM t1";
+        for dialect in all_dialects() {
+            let result = crate::rewrite(
+                dialect.as_ref(),
+                sql,
+                vec![Box::new(IfNullToCoalesce::new())],
+            );
+            assert_eq!(
+                result.unwrap(),
+                vec!["SELECT COALESCE(a, 0), COALESCE(b, 'x'), COALESCE(c, 1), ISNULL(d), COALESCE(e, 2) FROM t1"],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+}
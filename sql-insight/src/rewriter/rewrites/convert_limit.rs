@@ -0,0 +1,276 @@
+//! Rewrite that converts row limits between LIMIT, TOP and FETCH.
+
+use std::ops::ControlFlow;
+
+use crate::error::Error;
+use crate::rewriter::rewrites::inject_limit::LimitStyle;
+use crate::rewriter::{rewrite_with_visitor, Rewrite, Untranslatable};
+use sqlparser::ast::{
+    Expr, Fetch, Offset, OffsetRows, OrderByExpr, Query, SetExpr, Statement, Top, TopQuantity,
+    Value, VisitorMut,
+};
+use sqlparser::dialect::MsSqlDialect;
+use sqlparser::parser::Parser;
+
+/// [`ConvertLimit`] rewrites the row limits of queries, including subqueries, into the given style.
+///
+/// A limit with an OFFSET is written with `FETCH` instead of `TOP`, which cannot skip rows, as is a limit of a set operation.
+/// Since `FETCH` requires `ORDER BY` and `OFFSET` in MsSQL, `ORDER BY (SELECT NULL)` and `OFFSET 0 ROWS` are added
+/// when missing, keeping the rows in no particular order as with `LIMIT`.
+/// Percentages and `WITH TIES` cannot be written with `LIMIT` and are reported as [`Untranslatable`].
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+/// use sql_insight::{ConvertLimit, LimitStyle};
+///
+/// let dialect = GenericDialect {};
+/// let sql = "SELECT a FROM t1 ORDER BY a LIMIT 10";
+/// let result = sql_insight::rewrite(&dialect, sql, vec![Box::new(ConvertLimit::new(LimitStyle::Top))]).unwrap();
+/// assert_eq!(result, ["SELECT TOP 10 a FROM t1 ORDER BY a"]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct ConvertLimit {
+    style: LimitStyle,
+}
+
+impl ConvertLimit {
+    pub fn new(style: LimitStyle) -> Self {
+        Self { style }
+    }
+}
+
+impl Rewrite for ConvertLimit {
+    fn rewrite(&self, statement: &mut Statement) -> Result<(), Error> {
+        rewrite_with_visitor(statement, &mut ConvertLimitVisitor { style: self.style })
+    }
+}
+
+/// Quantity of `TOP`, as a constant for literal numbers so that it is written without parentheses.
+fn top_quantity(quantity: Expr) -> TopQuantity {
+    match &quantity {
+        Expr::Value(Value::Number(n, false)) => match n.parse() {
+            Ok(n) => TopQuantity::Constant(n),
+            Err(_) => TopQuantity::Expr(quantity),
+        },
+        _ => TopQuantity::Expr(quantity),
+    }
+}
+
+/// A row limit taken out of a query.
+struct RowLimit {
+    quantity: Expr,
+    percent: bool,
+    with_ties: bool,
+}
+
+struct ConvertLimitVisitor {
+    style: LimitStyle,
+}
+
+impl ConvertLimitVisitor {
+    fn take_limit(query: &mut Query) -> Option<RowLimit> {
+        if let Some(quantity) = query.limit.take() {
+            return Some(RowLimit {
+                quantity,
+                percent: false,
+                with_ties: false,
+            });
+        }
+        if let Some(Fetch {
+            with_ties,
+            percent,
+            quantity: Some(quantity),
+        }) = query.fetch.take()
+        {
+            return Some(RowLimit {
+                quantity,
+                percent,
+                with_ties,
+            });
+        }
+        let SetExpr::Select(select) = query.body.as_mut() else {
+            return None;
+        };
+        match select.top.take() {
+            Some(Top {
+                with_ties,
+                percent,
+                quantity: Some(quantity),
+            }) => Some(RowLimit {
+                quantity: match quantity {
+                    TopQuantity::Expr(expr) => expr,
+                    TopQuantity::Constant(n) => Expr::Value(Value::Number(n.to_string(), false)),
+                },
+                percent,
+                with_ties,
+            }),
+            top => {
+                select.top = top;
+                None
+            }
+        }
+    }
+
+    fn convert(&self, query: &mut Query) -> Result<(), Error> {
+        if query
+            .fetch
+            .as_ref()
+            .is_some_and(|fetch| fetch.quantity.is_none())
+        {
+            return Ok(());
+        }
+        let Some(limit) = Self::take_limit(query) else {
+            return Ok(());
+        };
+        let style = match self.style {
+            LimitStyle::Top
+                if query.offset.is_some() || !matches!(query.body.as_ref(), SetExpr::Select(_)) =>
+            {
+                if query.order_by.is_empty() {
+                    let expr = Parser::new(&MsSqlDialect {})
+                        .try_with_sql("(SELECT NULL)")?
+                        .parse_expr()?;
+                    query.order_by = vec![OrderByExpr {
+                        expr,
+                        asc: None,
+                        nulls_first: None,
+                    }];
+                }
+                query.offset.get_or_insert(Offset {
+                    value: Expr::Value(Value::Number("0".into(), false)),
+                    rows: OffsetRows::Rows,
+                });
+                LimitStyle::Fetch
+            }
+            style => style,
+        };
+        match style {
+            LimitStyle::Limit => {
+                if limit.percent || limit.with_ties {
+                    let construct = if limit.percent {
+                        "PERCENT"
+                    } else {
+                        "WITH TIES"
+                    };
+                    return Err(Untranslatable::new(
+                        construct,
+                        "LIMIT only limits the number of rows",
+                    )
+                    .into());
+                }
+                query.limit = Some(limit.quantity);
+                if let Some(offset) = &mut query.offset {
+                    offset.rows = OffsetRows::None;
+                }
+            }
+            LimitStyle::Top => {
+                if let SetExpr::Select(select) = query.body.as_mut() {
+                    select.top = Some(Top {
+                        with_ties: limit.with_ties,
+                        percent: limit.percent,
+                        quantity: Some(top_quantity(limit.quantity)),
+                    });
+                }
+            }
+            LimitStyle::Fetch => {
+                query.fetch = Some(Fetch {
+                    with_ties: limit.with_ties,
+                    percent: limit.percent,
+                    quantity: Some(limit.quantity),
+                });
+                if let Some(offset) = &mut query.offset {
+                    offset.rows = OffsetRows::Rows;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl VisitorMut for ConvertLimitVisitor {
+    type Break = Error;
+
+    fn pre_visit_query(&mut self, query: &mut Query) -> ControlFlow<Self::Break> {
+        match self.convert(query) {
+            Ok(()) => ControlFlow::Continue(()),
+            Err(e) => ControlFlow::Break(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlparser::dialect::{GenericDialect, MsSqlDialect};
+
+    fn assert_rewrite(style: LimitStyle, sql: &str, expected: Vec<&str>) {
+        let result = crate::rewrite(
+            &GenericDialect {},
+            sql,
+            vec![Box::new(ConvertLimit::new(style))],
+        );
+        assert_eq!(result.unwrap(), expected);
+    }
+
+    #[test]
+    fn test_into_limit() {
+        let sql = "SELECT TOP 5 a FROM t1 WHERE b IN (SELECT b FROM t2 ORDER BY b FETCH FIRST 3 ROWS ONLY)";
+        let result = crate::rewrite(
+            &MsSqlDialect {},
+            sql,
+            vec![Box::new(ConvertLimit::new(LimitStyle::Limit))],
+        );
+        assert_eq!(
+            result.unwrap(),
+            vec!["SELECT a FROM t1 WHERE b IN (SELECT b FROM t2 ORDER BY b LIMIT 3) LIMIT 5"]
+        );
+        assert_rewrite(
+            LimitStyle::Limit,
+            "SELECT a FROM t1 ORDER BY a OFFSET 10 ROWS FETCH FIRST 5 ROWS ONLY",
+            vec!["SELECT a FROM t1 ORDER BY a LIMIT 5 OFFSET 10"],
+        );
+    }
+
+    #[test]
+    fn test_into_top_and_fetch() {
+        assert_rewrite(
+            LimitStyle::Top,
+            "SELECT a FROM t1 LIMIT 5; SELECT a FROM t1 ORDER BY a LIMIT 5 OFFSET 10; \
+                SELECT a FROM t1 UNION SELECT a FROM t2 LIMIT 5",
+            vec![
+                "SELECT TOP 5 a FROM t1",
+                "SELECT a FROM t1 ORDER BY a OFFSET 10 ROWS FETCH FIRST 5 ROWS ONLY",
+                "SELECT a FROM t1 UNION SELECT a FROM t2 ORDER BY (SELECT NULL) OFFSET 0 ROWS FETCH FIRST 5 ROWS ONLY",
+            ],
+        );
+        let result = crate::transpile(
+            &sqlparser::dialect::MySqlDialect {},
+            &MsSqlDialect {},
+            "SELECT a FROM t LIMIT 10 OFFSET 5",
+        );
+        assert_eq!(
+            result.unwrap(),
+            vec!["SELECT a FROM t ORDER BY (SELECT NULL) OFFSET 5 ROWS FETCH FIRST 10 ROWS ONLY"]
+        );
+        assert_rewrite(
+            LimitStyle::Fetch,
+            "SELECT a FROM t1 LIMIT 5",
+            vec!["SELECT a FROM t1 FETCH FIRST 5 ROWS ONLY"],
+        );
+    }
+
+    #[test]
+    fn test_untranslatable() {
+        let result = crate::rewrite(
+            &MsSqlDialect {},
+            "SELECT TOP 10 PERCENT a FROM t1",
+            vec![Box::new(ConvertLimit::new(LimitStyle::Limit))],
+        );
+        assert_eq!(
+            result,
+            Err(Untranslatable::new("PERCENT", "LIMIT only limits the number of rows").into())
+        );
+    }
+}
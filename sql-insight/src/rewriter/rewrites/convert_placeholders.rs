@@ -0,0 +1,219 @@
+//! Rewrite that converts placeholders between styles.
+
+use std::collections::HashMap;
+use std::ops::ControlFlow;
+
+use crate::error::Error;
+use crate::rewriter::{rewrite_with_visitor, Rewrite};
+use sqlparser::ast::{visit_expressions, Expr, Statement, Value, VisitorMut};
+
+/// Style of placeholders.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PlaceholderStyle {
+    /// `?`, as used by e.g. MySQL drivers.
+    #[default]
+    QuestionMark,
+    /// `$1`, `$2`, ..., as used by e.g. PostgreSQL drivers.
+    Numbered,
+    /// `:name`
+    Named,
+}
+
+/// [`ConvertPlaceholders`] converts placeholders of every style into the given style.
+///
+/// - Into [`PlaceholderStyle::QuestionMark`], placeholders are replaced in order of appearance,
+///   so values of `$N` and named placeholders have to be supplied in that order, once per occurrence.
+/// - Into [`PlaceholderStyle::Numbered`], `?` and named placeholders are numbered together in order of appearance,
+///   where repeated names share the number of their first appearance. `$N` placeholders are kept,
+///   and the others are numbered after the highest of them so that numbers don't collide.
+/// - Into [`PlaceholderStyle::Named`], `?` placeholders are named by the name prefix followed by their position
+///   and `$N` placeholders by the name prefix followed by N. Named placeholders keep their names.
+///
+/// Numbering starts over for each statement.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+/// use sql_insight::{ConvertPlaceholders, PlaceholderStyle};
+///
+/// let dialect = GenericDialect {};
+/// let sql = "SELECT * FROM t1 WHERE a = ? AND b IN (?, ?)";
+/// let rewrite = ConvertPlaceholders::new(PlaceholderStyle::Numbered);
+/// let result = sql_insight::rewrite(&dialect, sql, vec![Box::new(rewrite)]).unwrap();
+/// assert_eq!(result, ["SELECT * FROM t1 WHERE a = $1 AND b IN ($2, $3)"]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct ConvertPlaceholders {
+    style: PlaceholderStyle,
+    name_prefix: String,
+}
+
+impl ConvertPlaceholders {
+    pub fn new(style: PlaceholderStyle) -> Self {
+        Self {
+            style,
+            name_prefix: "p".into(),
+        }
+    }
+
+    /// Prefix of names generated for positional placeholders converted into named ones. Defaults to `p`.
+    pub fn with_name_prefix(mut self, prefix: &str) -> Self {
+        self.name_prefix = prefix.to_string();
+        self
+    }
+}
+
+impl Rewrite for ConvertPlaceholders {
+    fn rewrite(&self, statement: &mut Statement) -> Result<(), Error> {
+        let mut last_number = 0;
+        let _ = visit_expressions(statement, |expr| {
+            if let Expr::Value(Value::Placeholder(placeholder)) = expr {
+                if let Some(number) = parse_number(placeholder) {
+                    last_number = last_number.max(number);
+                }
+            }
+            ControlFlow::<()>::Continue(())
+        });
+        rewrite_with_visitor(
+            statement,
+            &mut ConvertPlaceholdersVisitor {
+                rewrite: self,
+                position: 0,
+                last_number,
+                numbers: HashMap::new(),
+            },
+        )
+    }
+}
+
+/// Number of a `$N` placeholder.
+fn parse_number(placeholder: &str) -> Option<usize> {
+    placeholder.strip_prefix('$')?.parse().ok()
+}
+
+struct ConvertPlaceholdersVisitor<'a> {
+    rewrite: &'a ConvertPlaceholders,
+    /// Number of placeholders seen so far.
+    position: usize,
+    /// Last number used for numbered placeholders, kept or assigned.
+    last_number: usize,
+    /// Numbers assigned to named placeholders.
+    numbers: HashMap<String, usize>,
+}
+
+impl ConvertPlaceholdersVisitor<'_> {
+    fn convert(&mut self, placeholder: &str) -> String {
+        self.position += 1;
+        let number = parse_number(placeholder);
+        match self.rewrite.style {
+            PlaceholderStyle::QuestionMark => "?".into(),
+            PlaceholderStyle::Numbered => match number {
+                Some(_) => placeholder.into(),
+                None if placeholder == "?" => {
+                    self.last_number += 1;
+                    format!("${}", self.last_number)
+                }
+                None => {
+                    let number = match self.numbers.get(placeholder) {
+                        Some(&number) => number,
+                        None => {
+                            self.last_number += 1;
+                            self.numbers.insert(placeholder.into(), self.last_number);
+                            self.last_number
+                        }
+                    };
+                    format!("${number}")
+                }
+            },
+            PlaceholderStyle::Named => {
+                let prefix = &self.rewrite.name_prefix;
+                match number {
+                    Some(number) => format!(":{prefix}{number}"),
+                    None if placeholder == "?" => format!(":{prefix}{}", self.position),
+                    None => match placeholder.strip_prefix(['@', '$']) {
+                        Some(name) => format!(":{name}"),
+                        None => placeholder.into(),
+                    },
+                }
+            }
+        }
+    }
+}
+
+impl VisitorMut for ConvertPlaceholdersVisitor<'_> {
+    type Break = Error;
+
+    fn pre_visit_expr(&mut self, expr: &mut Expr) -> ControlFlow<Self::Break> {
+        if let Expr::Value(Value::Placeholder(placeholder)) = expr {
+            *placeholder = self.convert(placeholder);
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlparser::dialect::{GenericDialect, PostgreSqlDialect};
+
+    fn assert_rewrite(rewrite: ConvertPlaceholders, sql: &str, expected: Vec<&str>) {
+        for dialect in [
+            Box::new(GenericDialect {}) as Box<dyn sqlparser::dialect::Dialect>,
+            Box::new(PostgreSqlDialect {}),
+        ] {
+            let result = crate::rewrite(dialect.as_ref(), sql, vec![Box::new(rewrite.clone())]);
+            assert_eq!(result.unwrap(), expected, "Failed for dialect: {dialect:?}");
+        }
+    }
+
+    #[test]
+    fn test_into_question_mark() {
+        assert_rewrite(
+            ConvertPlaceholders::new(PlaceholderStyle::QuestionMark),
+            "SELECT * FROM t1 WHERE a = $2 AND b = $1; SELECT * FROM t1 WHERE a = :a AND b = :a",
+            vec![
+                "SELECT * FROM t1 WHERE a = ? AND b = ?",
+                "SELECT * FROM t1 WHERE a = ? AND b = ?",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_into_numbered() {
+        assert_rewrite(
+            ConvertPlaceholders::new(PlaceholderStyle::Numbered),
+            "SELECT * FROM t1 WHERE a = :a AND b = :b AND c = :a; SELECT * FROM t1 WHERE a = ? LIMIT ?",
+            vec![
+                "SELECT * FROM t1 WHERE a = $1 AND b = $2 AND c = $1",
+                "SELECT * FROM t1 WHERE a = $1 LIMIT $2",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_into_numbered_from_mixed_styles() {
+        assert_rewrite(
+            ConvertPlaceholders::new(PlaceholderStyle::Numbered),
+            "SELECT * FROM t1 WHERE a = ? AND b = :x AND c = ? AND d = :x; \
+                SELECT * FROM t1 WHERE a = ? AND b = $2",
+            vec![
+                "SELECT * FROM t1 WHERE a = $1 AND b = $2 AND c = $3 AND d = $2",
+                "SELECT * FROM t1 WHERE a = $3 AND b = $2",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_into_named() {
+        assert_rewrite(
+            ConvertPlaceholders::new(PlaceholderStyle::Named).with_name_prefix("arg"),
+            "SELECT * FROM t1 WHERE a = ? AND b = ?; SELECT * FROM t1 WHERE a = $2 AND b = :b",
+            vec![
+                "SELECT * FROM t1 WHERE a = :arg1 AND b = :arg2",
+                "SELECT * FROM t1 WHERE a = :arg2 AND b = :b",
+            ],
+        );
+    }
+}
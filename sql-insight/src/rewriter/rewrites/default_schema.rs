@@ -0,0 +1,116 @@
+//! Rewrite that qualifies unqualified tables with a default schema.
+
+use crate::error::Error;
+use crate::rewriter::rewrites::relation::RelationVisitor;
+use crate::rewriter::{rewrite_with_visitor, Rewrite};
+use sqlparser::ast::{Ident, Statement};
+
+/// [`DefaultSchema`] qualifies every table reference lacking a schema with the given schema,
+/// and with the given catalog when set. References to CTEs are left as they are, as are aliases.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+/// use sql_insight::DefaultSchema;
+///
+/// let dialect = GenericDialect {};
+/// let sql = "WITH c AS (SELECT a FROM t1) SELECT x.a FROM c JOIN t2 AS x ON c.a = x.a";
+/// let result = sql_insight::rewrite(&dialect, sql, vec![Box::new(DefaultSchema::new("warehouse"))]).unwrap();
+/// assert_eq!(
+///     result,
+///     ["WITH c AS (SELECT a FROM warehouse.t1) SELECT x.a FROM c JOIN warehouse.t2 AS x ON c.a = x.a"]
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct DefaultSchema {
+    catalog: Option<Ident>,
+    schema: Ident,
+}
+
+impl DefaultSchema {
+    pub fn new(schema: &str) -> Self {
+        Self {
+            catalog: None,
+            schema: schema.into(),
+        }
+    }
+
+    /// Also qualify tables lacking a catalog with the given catalog.
+    pub fn with_catalog(mut self, catalog: &str) -> Self {
+        self.catalog = Some(catalog.into());
+        self
+    }
+}
+
+impl Rewrite for DefaultSchema {
+    fn rewrite(&self, statement: &mut Statement) -> Result<(), Error> {
+        let mut visitor = RelationVisitor::new(|relation| {
+            if relation.0.len() == 1 {
+                relation.0.insert(0, self.schema.clone());
+            }
+            if relation.0.len() == 2 {
+                if let Some(catalog) = &self.catalog {
+                    relation.0.insert(0, catalog.clone());
+                }
+            }
+            Ok(())
+        });
+        rewrite_with_visitor(statement, &mut visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+
+    fn assert_rewrite(rewrite: DefaultSchema, sql: &str, expected: Vec<&str>) {
+        for dialect in all_dialects() {
+            let result = crate::rewrite(dialect.as_ref(), sql, vec![Box::new(rewrite.clone())]);
+            assert_eq!(result.unwrap(), expected, "Failed for dialect: {dialect:?}");
+        }
+    }
+
+    #[test]
+    fn test_default_schema() {
+        assert_rewrite(
+            DefaultSchema::new("s"),
+            "SELECT a FROM t1 AS x JOIN s2.t2 ON x.id = t2.id WHERE b IN (SELECT b FROM t3); \
+                INSERT INTO t4 (a) SELECT a FROM t5; UPDATE t6 SET a = 1; DELETE FROM t7",
+            vec![
+                "SELECT a FROM s.t1 AS x JOIN s2.t2 ON x.id = t2.id WHERE b IN (SELECT b FROM s.t3)",
+                "INSERT INTO s.t4 (a) SELECT a FROM s.t5",
+                "UPDATE s.t6 SET a = 1",
+                "DELETE FROM s.t7",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_default_schema_with_catalog() {
+        assert_rewrite(
+            DefaultSchema::new("s").with_catalog("c"),
+            "SELECT a FROM t1 JOIN s2.t2 ON t1.id = t2.id JOIN c3.s3.t3 ON t1.id = t3.id",
+            vec![
+                "SELECT a FROM c.s.t1 JOIN c.s2.t2 ON t1.id = t2.id JOIN c3.s3.t3 ON t1.id = t3.id",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_cte_references_are_skipped() {
+        let sql = "WITH c1 AS (SELECT a FROM t1), c2 AS (SELECT a FROM c1) \
+            SELECT a FROM c2 WHERE a IN (SELECT a FROM c1 JOIN t2 ON c1.a = t2.a)";
+        let expected = "WITH c1 AS (SELECT a FROM s.t1), c2 AS (SELECT a FROM c1) \
+            SELECT a FROM c2 WHERE a IN (SELECT a FROM c1 JOIN s.t2 ON c1.a = t2.a)";
+        assert_rewrite(DefaultSchema::new("s"), sql, vec![expected]);
+    }
+
+    #[test]
+    fn test_cte_scope() {
+        let sql = "WITH t1 AS (SELECT a FROM t1), t2 AS (SELECT a FROM t1) SELECT a FROM t2";
+        let expected = "WITH t1 AS (SELECT a FROM s.t1), t2 AS (SELECT a FROM t1) SELECT a FROM t2";
+        assert_rewrite(DefaultSchema::new("s"), sql, vec![expected]);
+    }
+}
@@ -0,0 +1,249 @@
+//! Rewrite that inlines view definitions into queries as derived tables.
+
+use std::collections::HashMap;
+use std::ops::ControlFlow;
+
+use crate::error::Error;
+use crate::instrument;
+use crate::rewriter::{rewrite_with_visitor, Rewrite};
+use sqlparser::ast::{
+    Ident, ObjectName, Query, Statement, TableAlias, TableFactor, VisitMut, VisitorMut,
+};
+use sqlparser::dialect::Dialect;
+
+/// Convenience function to inline the views defined by `view_defs`, a series of `CREATE VIEW` statements,
+/// into each statement of SQL. See [`ExpandViews`] for details.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+///
+/// let dialect = GenericDialect {};
+/// let view_defs = "CREATE VIEW active_users AS SELECT id, name FROM users WHERE active";
+/// let sql = "SELECT u.name FROM active_users AS u JOIN orders ON orders.user_id = u.id";
+/// let result = sql_insight::expand_views(&dialect, sql, view_defs).unwrap();
+/// assert_eq!(
+///     result,
+///     ["SELECT u.name FROM (SELECT id, name FROM users WHERE active) AS u JOIN orders ON orders.user_id = u.id"]
+/// );
+/// ```
+pub fn expand_views(
+    dialect: &dyn Dialect,
+    sql: &str,
+    view_defs: &str,
+) -> Result<Vec<String>, Error> {
+    let rewrite = ExpandViews::parse(dialect, view_defs)?;
+    crate::rewrite(dialect, sql, vec![Box::new(rewrite)])
+}
+
+/// [`ExpandViews`] replaces references to known views with their queries as derived tables,
+/// so that extracting tables and columns from the rewritten statement reports the physical tables.
+///
+/// A view keeps its alias, or is aliased by its name when not aliased, so qualified columns still resolve.
+/// Views referring to other views are expanded recursively, and a view referring to itself through
+/// other views is an error. View names are matched as written, including qualifiers, case-insensitively unless quoted.
+/// References to CTEs shadowing a view are left as they are.
+/// Column lists of view definitions, as in `CREATE VIEW v (a, b) AS ...`, are not applied to the expanded queries.
+#[derive(Clone, Debug, Default)]
+pub struct ExpandViews {
+    views: HashMap<String, Query>,
+}
+
+impl ExpandViews {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a view, replacing the one of the same name if any.
+    pub fn with_view(mut self, name: &ObjectName, query: Query) -> Self {
+        self.views.insert(key(name), query);
+        self
+    }
+
+    /// Parse views from a series of `CREATE VIEW` statements.
+    pub fn parse(dialect: &dyn Dialect, view_defs: &str) -> Result<Self, Error> {
+        let mut expand_views = Self::new();
+        for statement in instrument::parse_sql(dialect, view_defs)? {
+            let Statement::CreateView { name, query, .. } = statement else {
+                return Err(Error::ArgumentError(format!(
+                    "Expected CREATE VIEW, got: {}",
+                    statement
+                )));
+            };
+            expand_views = expand_views.with_view(&name, *query);
+        }
+        Ok(expand_views)
+    }
+}
+
+impl Rewrite for ExpandViews {
+    fn rewrite(&self, statement: &mut Statement) -> Result<(), Error> {
+        rewrite_with_visitor(statement, &mut ViewExpander::new(&self.views, vec![]))
+    }
+}
+
+/// Key of a view name, lowercasing unquoted identifiers.
+fn key(name: &ObjectName) -> String {
+    name.0
+        .iter()
+        .map(|ident| match ident.quote_style {
+            Some(_) => ident.value.clone(),
+            None => ident.value.to_lowercase(),
+        })
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+struct ViewExpander<'a> {
+    views: &'a HashMap<String, Query>,
+    /// Views being expanded, for detecting views referring to themselves.
+    expanding: Vec<String>,
+    cte_scopes: Vec<Vec<Ident>>,
+}
+
+impl<'a> ViewExpander<'a> {
+    fn new(views: &'a HashMap<String, Query>, expanding: Vec<String>) -> Self {
+        Self {
+            views,
+            expanding,
+            cte_scopes: vec![],
+        }
+    }
+
+    fn is_cte(&self, name: &ObjectName) -> bool {
+        match name.0.as_slice() {
+            [name] => self
+                .cte_scopes
+                .iter()
+                .flatten()
+                .any(|cte| cte.value == name.value),
+            _ => false,
+        }
+    }
+
+    /// The query of a view with the views it refers to expanded.
+    fn expand(&self, key: String) -> Result<Option<Query>, Error> {
+        let Some(query) = self.views.get(&key) else {
+            return Ok(None);
+        };
+        if self.expanding.contains(&key) {
+            return Err(Error::AnalysisError(format!(
+                "View {} refers to itself: {}",
+                key,
+                self.expanding.join(" -> ")
+            )));
+        }
+        let mut query = query.clone();
+        let mut expanding = self.expanding.clone();
+        expanding.push(key);
+        match query.visit(&mut ViewExpander::new(self.views, expanding)) {
+            ControlFlow::Break(e) => Err(e),
+            ControlFlow::Continue(()) => Ok(Some(query)),
+        }
+    }
+}
+
+impl VisitorMut for ViewExpander<'_> {
+    type Break = Error;
+
+    fn pre_visit_query(&mut self, query: &mut Query) -> ControlFlow<Self::Break> {
+        let ctes = query
+            .with
+            .iter()
+            .flat_map(|with| with.cte_tables.iter().map(|cte| cte.alias.name.clone()))
+            .collect();
+        self.cte_scopes.push(ctes);
+        ControlFlow::Continue(())
+    }
+
+    fn post_visit_query(&mut self, _query: &mut Query) -> ControlFlow<Self::Break> {
+        self.cte_scopes.pop();
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_table_factor(
+        &mut self,
+        table_factor: &mut TableFactor,
+    ) -> ControlFlow<Self::Break> {
+        let TableFactor::Table {
+            name,
+            alias,
+            args: None,
+            ..
+        } = table_factor
+        else {
+            return ControlFlow::Continue(());
+        };
+        if self.is_cte(name) {
+            return ControlFlow::Continue(());
+        }
+        let query = match self.expand(key(name)) {
+            Ok(Some(query)) => query,
+            Ok(None) => return ControlFlow::Continue(()),
+            Err(e) => return ControlFlow::Break(e),
+        };
+        let alias = alias.take().or_else(|| {
+            name.0.last().map(|name| TableAlias {
+                name: name.clone(),
+                columns: vec![],
+            })
+        });
+        *table_factor = TableFactor::Derived {
+            lateral: false,
+            subquery: Box::new(query),
+            alias,
+        };
+        ControlFlow::Continue(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+
+    fn assert_expand_views(sql: &str, view_defs: &str, expected: Vec<&str>) {
+        for dialect in all_dialects() {
+            let result = expand_views(dialect.as_ref(), sql, view_defs);
+            assert_eq!(result.unwrap(), expected, "Failed for dialect: {dialect:?}");
+        }
+    }
+
+    #[test]
+    fn test_expand_views() {
+        assert_expand_views(
+            "SELECT v1.a FROM v1 WHERE v1.b IN (SELECT b FROM s1.V2); INSERT INTO t3 SELECT * FROM v1",
+            "CREATE VIEW v1 AS SELECT a, b FROM t1; CREATE VIEW s1.v2 AS SELECT b FROM t2",
+            vec![
+                "SELECT v1.a FROM (SELECT a, b FROM t1) AS v1 WHERE v1.b IN (SELECT b FROM (SELECT b FROM t2) AS V2)",
+                "INSERT INTO t3 SELECT * FROM (SELECT a, b FROM t1) AS v1",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_nested_views_and_ctes() {
+        assert_expand_views(
+            "WITH v1 AS (SELECT 1 AS a) SELECT * FROM v1 JOIN v2 AS x ON v1.a = x.a",
+            "CREATE VIEW v1 AS SELECT a FROM t1; CREATE VIEW v2 AS SELECT a FROM v1",
+            vec!["WITH v1 AS (SELECT 1 AS a) SELECT * FROM v1 JOIN (SELECT a FROM (SELECT a FROM t1) AS v1) AS x ON v1.a = x.a"],
+        );
+    }
+
+    #[test]
+    fn test_self_referring_views() {
+        let dialect = sqlparser::dialect::GenericDialect {};
+        let view_defs = "CREATE VIEW v1 AS SELECT a FROM v2; CREATE VIEW v2 AS SELECT a FROM v1";
+        assert_eq!(
+            expand_views(&dialect, "SELECT a FROM v1", view_defs),
+            Err(Error::AnalysisError(
+                "View v1 refers to itself: v1 -> v2".into()
+            ))
+        );
+        assert_eq!(
+            ExpandViews::parse(&dialect, "CREATE TABLE t1 (a INT)").unwrap_err(),
+            Error::ArgumentError("Expected CREATE VIEW, got: CREATE TABLE t1 (a INT)".into())
+        );
+    }
+}
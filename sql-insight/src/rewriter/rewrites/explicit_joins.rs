@@ -0,0 +1,254 @@
+//! Rewrite that converts comma joins into explicit JOINs.
+
+use std::collections::HashSet;
+use std::ops::ControlFlow;
+
+use crate::error::Error;
+use crate::helper;
+use crate::rewriter::{rewrite_with_visitor, Rewrite};
+use sqlparser::ast::{
+    BinaryOperator, Expr, Join, JoinConstraint, JoinOperator, Query, Select, Statement,
+    TableFactor, TableWithJoins, Visit, Visitor, VisitorMut,
+};
+
+/// [`ExplicitJoins`] converts comma joins into explicit JOINs, moving the WHERE predicates
+/// that correlate a joined table with the tables before it into the ON clause of its `INNER JOIN`.
+/// Tables without such a predicate are joined with `CROSS JOIN`.
+///
+/// Only predicates whose columns are all qualified by tables of the join are moved,
+/// and predicates containing subqueries are left in the WHERE clause.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+/// use sql_insight::ExplicitJoins;
+///
+/// let dialect = GenericDialect {};
+/// let sql = "SELECT o.id, c.name FROM orders AS o, customers AS c WHERE o.customer_id = c.id AND o.status = 'open'";
+/// let result = sql_insight::rewrite(&dialect, sql, vec![Box::new(ExplicitJoins::new())]).unwrap();
+/// assert_eq!(
+///     result,
+///     ["SELECT o.id, c.name FROM orders AS o JOIN customers AS c ON o.customer_id = c.id WHERE o.status = 'open'"]
+/// );
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ExplicitJoins;
+
+impl ExplicitJoins {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Rewrite for ExplicitJoins {
+    fn rewrite(&self, statement: &mut Statement) -> Result<(), Error> {
+        rewrite_with_visitor(statement, &mut ExplicitJoinsVisitor)
+    }
+}
+
+struct ExplicitJoinsVisitor;
+
+impl VisitorMut for ExplicitJoinsVisitor {
+    type Break = Error;
+
+    fn pre_visit_query(&mut self, query: &mut Query) -> ControlFlow<Self::Break> {
+        let mut selects = Vec::new();
+        helper::collect_selects_mut(&mut query.body, &mut selects);
+        for select in selects {
+            make_joins_explicit(select);
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+fn make_joins_explicit(select: &mut Select) {
+    if select.from.len() < 2 {
+        return;
+    }
+    let mut predicates = Vec::new();
+    if let Some(selection) = select.selection.take() {
+        split_conjunction(selection, &mut predicates);
+    }
+    let mut from = std::mem::take(&mut select.from).into_iter();
+    let Some(mut first) = from.next() else {
+        return;
+    };
+    let mut joined = HashSet::new();
+    collect_qualifiers(&first, &mut joined);
+    for table_with_joins in from {
+        let mut qualifiers = HashSet::new();
+        collect_qualifiers(&table_with_joins, &mut qualifiers);
+        let (on, rest): (Vec<_>, Vec<_>) = predicates.into_iter().partition(|predicate| {
+            referenced_qualifiers(predicate).is_some_and(|referenced| {
+                referenced.iter().any(|q| qualifiers.contains(q))
+                    && referenced.iter().any(|q| joined.contains(q))
+                    && referenced
+                        .iter()
+                        .all(|q| qualifiers.contains(q) || joined.contains(q))
+            })
+        });
+        predicates = rest;
+        joined.extend(qualifiers);
+        // Joins following a comma bind tighter than the comma, so they are kept together in a nested join.
+        let relation = if table_with_joins.joins.is_empty() {
+            table_with_joins.relation
+        } else {
+            TableFactor::NestedJoin {
+                table_with_joins: Box::new(table_with_joins),
+                alias: None,
+            }
+        };
+        let join_operator = match conjunction(on) {
+            Some(on) => JoinOperator::Inner(JoinConstraint::On(on)),
+            None => JoinOperator::CrossJoin,
+        };
+        first.joins.push(Join {
+            relation,
+            join_operator,
+        });
+    }
+    select.from = vec![first];
+    select.selection = conjunction(predicates);
+}
+
+fn split_conjunction(expr: Expr, predicates: &mut Vec<Expr>) {
+    match expr {
+        Expr::BinaryOp {
+            left,
+            op: BinaryOperator::And,
+            right,
+        } => {
+            split_conjunction(*left, predicates);
+            split_conjunction(*right, predicates);
+        }
+        Expr::Nested(inner)
+            if matches!(
+                *inner,
+                Expr::BinaryOp {
+                    op: BinaryOperator::And,
+                    ..
+                }
+            ) =>
+        {
+            split_conjunction(*inner, predicates)
+        }
+        expr => predicates.push(expr),
+    }
+}
+
+fn conjunction(predicates: Vec<Expr>) -> Option<Expr> {
+    predicates.into_iter().reduce(|left, right| Expr::BinaryOp {
+        left: Box::new(left),
+        op: BinaryOperator::And,
+        right: Box::new(right),
+    })
+}
+
+/// Names tables of a FROM item are referred to by: aliases, or table names when not aliased.
+fn collect_qualifiers(table_with_joins: &TableWithJoins, qualifiers: &mut HashSet<String>) {
+    let factors = std::iter::once(&table_with_joins.relation)
+        .chain(table_with_joins.joins.iter().map(|join| &join.relation));
+    for factor in factors {
+        match factor {
+            TableFactor::Table {
+                alias: Some(alias), ..
+            }
+            | TableFactor::Derived {
+                alias: Some(alias), ..
+            } => {
+                qualifiers.insert(alias.name.value.clone());
+            }
+            TableFactor::Table { name, .. } => {
+                qualifiers.extend(name.0.last().map(|ident| ident.value.clone()));
+            }
+            TableFactor::NestedJoin {
+                table_with_joins, ..
+            } => collect_qualifiers(table_with_joins, qualifiers),
+            _ => {}
+        }
+    }
+}
+
+/// Qualifiers of the columns a predicate refers to,
+/// or `None` if it has unqualified columns or subqueries and cannot be attributed to tables.
+fn referenced_qualifiers(predicate: &Expr) -> Option<HashSet<String>> {
+    let mut collector = QualifierCollector::default();
+    match predicate.visit(&mut collector) {
+        ControlFlow::Continue(()) => Some(collector.qualifiers),
+        ControlFlow::Break(()) => None,
+    }
+}
+
+#[derive(Default)]
+struct QualifierCollector {
+    qualifiers: HashSet<String>,
+}
+
+impl Visitor for QualifierCollector {
+    type Break = ();
+
+    fn pre_visit_query(&mut self, _query: &Query) -> ControlFlow<Self::Break> {
+        ControlFlow::Break(())
+    }
+
+    fn pre_visit_expr(&mut self, expr: &Expr) -> ControlFlow<Self::Break> {
+        match expr {
+            Expr::Identifier(_) => return ControlFlow::Break(()),
+            Expr::CompoundIdentifier(idents) => {
+                if let [.., qualifier, _] = idents.as_slice() {
+                    self.qualifiers.insert(qualifier.value.clone());
+                }
+            }
+            _ => {}
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+
+    fn assert_rewrite(sql: &str, expected: Vec<&str>) {
+        for dialect in all_dialects() {
+            let result =
+                crate::rewrite(dialect.as_ref(), sql, vec![Box::new(ExplicitJoins::new())]);
+            assert_eq!(result.unwrap(), expected, "Failed for dialect: {dialect:?}");
+        }
+    }
+
+    #[test]
+    fn test_multiple_comma_joins() {
+        assert_rewrite(
+            "SELECT * FROM t1, t2 AS b, t3 WHERE t1.a = b.a AND t3.c = b.c AND t3.d = t1.d AND t1.e > 0",
+            vec!["SELECT * FROM t1 JOIN t2 AS b ON t1.a = b.a JOIN t3 ON t3.c = b.c AND t3.d = t1.d WHERE t1.e > 0"],
+        );
+    }
+
+    #[test]
+    fn test_cross_join_and_unattributable_predicates() {
+        assert_rewrite(
+            "SELECT * FROM t1, t2, t3 WHERE t1.a = t3.a AND b = t1.b AND t2.c IN (SELECT c FROM t4)",
+            vec!["SELECT * FROM t1 CROSS JOIN t2 JOIN t3 ON t1.a = t3.a WHERE b = t1.b AND t2.c IN (SELECT c FROM t4)"],
+        );
+    }
+
+    #[test]
+    fn test_comma_join_with_joins_and_subquery() {
+        assert_rewrite(
+            "SELECT * FROM t1, t2 JOIN t3 ON t2.a = t3.a WHERE t1.b = t3.b AND EXISTS (SELECT 1 FROM t4, t5 WHERE t4.c = t5.c)",
+            vec!["SELECT * FROM t1 JOIN (t2 JOIN t3 ON t2.a = t3.a) ON t1.b = t3.b \
+                WHERE EXISTS (SELECT 1 FROM t4 JOIN t5 ON t4.c = t5.c)"],
+        );
+    }
+
+    #[test]
+    fn test_single_table_is_untouched() {
+        assert_rewrite(
+            "SELECT * FROM t1 WHERE t1.a = 1 AND (t1.b = 2 OR t1.c = 3)",
+            vec!["SELECT * FROM t1 WHERE t1.a = 1 AND (t1.b = 2 OR t1.c = 3)"],
+        );
+    }
+}
@@ -0,0 +1,101 @@
+use std::ops::ControlFlow;
+
+use crate::error::Error;
+use crate::helper;
+use sqlparser::ast::{
+    Expr, Ident, ObjectName, Query, SelectItem, Statement, TableAlias, TableFactor, VisitorMut,
+};
+
+/// A visitor calling a function on every identifier naming a table, column, alias or CTE.
+/// Function names and identifiers in DDL are not visited.
+pub(crate) struct IdentVisitor<F> {
+    f: F,
+}
+
+impl<F> IdentVisitor<F>
+where
+    F: FnMut(&mut Ident),
+{
+    pub(crate) fn new(f: F) -> Self {
+        Self { f }
+    }
+
+    fn visit_alias(&mut self, alias: &mut TableAlias) {
+        (self.f)(&mut alias.name);
+        alias.columns.iter_mut().for_each(&mut self.f);
+    }
+}
+
+impl<F> VisitorMut for IdentVisitor<F>
+where
+    F: FnMut(&mut Ident),
+{
+    type Break = Error;
+
+    fn pre_visit_statement(&mut self, statement: &mut Statement) -> ControlFlow<Self::Break> {
+        match statement {
+            Statement::Insert { columns, .. } => columns.iter_mut().for_each(&mut self.f),
+            Statement::Update { assignments, .. } => assignments
+                .iter_mut()
+                .flat_map(|assignment| assignment.id.iter_mut())
+                .for_each(&mut self.f),
+            _ => {}
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_query(&mut self, query: &mut Query) -> ControlFlow<Self::Break> {
+        if let Some(with) = &mut query.with {
+            for cte in with.cte_tables.iter_mut() {
+                self.visit_alias(&mut cte.alias);
+            }
+        }
+        let mut selects = Vec::new();
+        helper::collect_selects_mut(&mut query.body, &mut selects);
+        for select in selects {
+            for item in select.projection.iter_mut() {
+                match item {
+                    SelectItem::ExprWithAlias { alias, .. } => (self.f)(alias),
+                    SelectItem::QualifiedWildcard(ObjectName(idents), _) => {
+                        idents.iter_mut().for_each(&mut self.f)
+                    }
+                    _ => {}
+                }
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_relation(&mut self, relation: &mut ObjectName) -> ControlFlow<Self::Break> {
+        relation.0.iter_mut().for_each(&mut self.f);
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_table_factor(
+        &mut self,
+        table_factor: &mut TableFactor,
+    ) -> ControlFlow<Self::Break> {
+        match table_factor {
+            TableFactor::Table {
+                alias: Some(alias), ..
+            }
+            | TableFactor::Derived {
+                alias: Some(alias), ..
+            }
+            | TableFactor::NestedJoin {
+                alias: Some(alias), ..
+            } => self.visit_alias(alias),
+            _ => {}
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_expr(&mut self, expr: &mut Expr) -> ControlFlow<Self::Break> {
+        match expr {
+            Expr::Identifier(ident) => (self.f)(ident),
+            Expr::CompoundIdentifier(idents) => idents.iter_mut().for_each(&mut self.f),
+            _ => {}
+        }
+        ControlFlow::Continue(())
+    }
+}
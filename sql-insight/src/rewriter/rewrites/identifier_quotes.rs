@@ -0,0 +1,100 @@
+//! Rewrite that converts the quotes of quoted identifiers.
+
+use crate::error::Error;
+use crate::rewriter::rewrites::ident::IdentVisitor;
+use crate::rewriter::{rewrite_with_visitor, Rewrite};
+use sqlparser::ast::Statement;
+use sqlparser::dialect::{BigQueryDialect, Dialect, HiveDialect, MsSqlDialect, MySqlDialect};
+
+/// [`IdentifierQuotes`] rewrites quoted identifiers of tables, columns, aliases and CTEs to use the given quote,
+/// e.g. backticks into double quotes. Unquoted identifiers are left as they are.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::MySqlDialect;
+/// use sql_insight::IdentifierQuotes;
+///
+/// let dialect = MySqlDialect {};
+/// let sql = "SELECT `a` AS `b` FROM `db`.t1";
+/// let result = sql_insight::rewrite(&dialect, sql, vec![Box::new(IdentifierQuotes::new('"'))]).unwrap();
+/// assert_eq!(result, ["SELECT \"a\" AS \"b\" FROM \"db\".t1"]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct IdentifierQuotes {
+    quote: char,
+}
+
+impl IdentifierQuotes {
+    /// Quote identifiers with `quote`, which is one of `"`, `` ` `` and `[`.
+    pub fn new(quote: char) -> Self {
+        Self { quote }
+    }
+
+    /// Quote identifiers in the way of the given dialect: backticks for MySQL, BigQuery and Hive,
+    /// brackets for MsSQL, and double quotes otherwise.
+    pub fn for_dialect(dialect: &dyn Dialect) -> Self {
+        if dialect.is::<MySqlDialect>()
+            || dialect.is::<BigQueryDialect>()
+            || dialect.is::<HiveDialect>()
+        {
+            Self::new('`')
+        } else if dialect.is::<MsSqlDialect>() {
+            Self::new('[')
+        } else {
+            Self::new('"')
+        }
+    }
+
+    pub(crate) fn quote(&self) -> char {
+        self.quote
+    }
+}
+
+impl Rewrite for IdentifierQuotes {
+    fn rewrite(&self, statement: &mut Statement) -> Result<(), Error> {
+        let mut visitor = IdentVisitor::new(|ident| {
+            if ident.quote_style.is_some() {
+                ident.quote_style = Some(self.quote);
+            }
+        });
+        rewrite_with_visitor(statement, &mut visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlparser::dialect::{GenericDialect, PostgreSqlDialect};
+
+    #[test]
+    fn test_identifier_quotes() {
+        let sql = "WITH \"c\" (\"x\") AS (SELECT 1) SELECT \"c\".\"x\" FROM \"c\"; \
+            INSERT INTO \"t1\" (\"a\", b) SELECT \"t2\".\"a\", \"t2\".* FROM \"t2\" AS \"x\"";
+        let result = crate::rewrite(
+            &PostgreSqlDialect {},
+            sql,
+            vec![Box::new(IdentifierQuotes::for_dialect(&MySqlDialect {}))],
+        );
+        assert_eq!(
+            result.unwrap(),
+            vec![
+                "WITH `c` (`x`) AS (SELECT 1) SELECT `c`.`x` FROM `c`",
+                "INSERT INTO `t1` (`a`, b) SELECT `t2`.`a`, `t2`.* FROM `t2` AS `x`",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_identifier_quotes_for_mssql() {
+        let result = crate::rewrite(
+            &GenericDialect {},
+            "UPDATE \"t1\" SET \"a\" = 1 WHERE \"b\" = 2",
+            vec![Box::new(IdentifierQuotes::for_dialect(&MsSqlDialect {}))],
+        );
+        assert_eq!(
+            result.unwrap(),
+            vec!["UPDATE [t1] SET [a] = 1 WHERE [b] = 2"]
+        );
+    }
+}
@@ -0,0 +1,173 @@
+//! Rewrite that injects a row limit into top-level SELECTs lacking one.
+
+use crate::error::Error;
+use crate::linter::rules::select_without_limit::{is_limited, reads_tables};
+use crate::rewriter::Rewrite;
+use sqlparser::ast::{Expr, Fetch, SetExpr, Statement, Top, TopQuantity, Value};
+use sqlparser::dialect::{AnsiDialect, Dialect, MsSqlDialect};
+
+/// How a row limit is written.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LimitStyle {
+    /// `LIMIT n`
+    Limit,
+    /// `SELECT TOP n`, falling back to `FETCH` for set operations.
+    Top,
+    /// `FETCH FIRST n ROWS ONLY`
+    Fetch,
+}
+
+impl LimitStyle {
+    /// The style a dialect is usually written in: `TOP` for MsSQL, `FETCH` for ANSI and `LIMIT` otherwise.
+    pub fn of(dialect: &dyn Dialect) -> Self {
+        if dialect.is::<MsSqlDialect>() {
+            Self::Top
+        } else if dialect.is::<AnsiDialect>() {
+            Self::Fetch
+        } else {
+            Self::Limit
+        }
+    }
+}
+
+/// [`InjectLimit`] adds a row limit to top-level SELECTs that have no `LIMIT`, `FETCH` or `TOP`.
+/// Subqueries and SELECTs without a FROM clause are left untouched.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+/// use sql_insight::InjectLimit;
+///
+/// let dialect = GenericDialect {};
+/// let sql = "SELECT a FROM t1 WHERE b IN (SELECT b FROM t2); SELECT a FROM t1 LIMIT 10";
+/// let result = sql_insight::rewrite(&dialect, sql, vec![Box::new(InjectLimit::new(100))]).unwrap();
+/// assert_eq!(
+///     result,
+///     ["SELECT a FROM t1 WHERE b IN (SELECT b FROM t2) LIMIT 100", "SELECT a FROM t1 LIMIT 10"]
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct InjectLimit {
+    limit: u64,
+    style: LimitStyle,
+}
+
+impl InjectLimit {
+    pub fn new(limit: u64) -> Self {
+        Self {
+            limit,
+            style: LimitStyle::Limit,
+        }
+    }
+
+    /// Write the limit in the style of the given dialect.
+    pub fn for_dialect(dialect: &dyn Dialect, limit: u64) -> Self {
+        Self::new(limit).with_style(LimitStyle::of(dialect))
+    }
+
+    pub fn with_style(mut self, style: LimitStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    fn quantity(&self) -> Expr {
+        Expr::Value(Value::Number(self.limit.to_string(), false))
+    }
+
+    fn fetch(&self) -> Fetch {
+        Fetch {
+            with_ties: false,
+            percent: false,
+            quantity: Some(self.quantity()),
+        }
+    }
+}
+
+impl Rewrite for InjectLimit {
+    fn rewrite(&self, statement: &mut Statement) -> Result<(), Error> {
+        let Statement::Query(query) = statement else {
+            return Ok(());
+        };
+        if !reads_tables(&query.body) || is_limited(query) {
+            return Ok(());
+        }
+        match (self.style, query.body.as_mut()) {
+            (LimitStyle::Limit, _) => query.limit = Some(self.quantity()),
+            (LimitStyle::Top, SetExpr::Select(select)) => {
+                select.top = Some(Top {
+                    with_ties: false,
+                    percent: false,
+                    quantity: Some(TopQuantity::Constant(self.limit)),
+                })
+            }
+            (LimitStyle::Top, _) | (LimitStyle::Fetch, _) => query.fetch = Some(self.fetch()),
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+
+    fn assert_rewrite(rewrite: InjectLimit, sql: &str, expected: Vec<&str>) {
+        for dialect in all_dialects() {
+            let result = crate::rewrite(dialect.as_ref(), sql, vec![Box::new(rewrite.clone())]);
+            assert_eq!(result.unwrap(), expected, "Failed for dialect: {dialect:?}");
+        }
+    }
+
+    #[test]
+    fn test_limit() {
+        assert_rewrite(
+            InjectLimit::new(100),
+            "SELECT a FROM t1 UNION SELECT a FROM (SELECT a FROM t2) AS d; SELECT 1; INSERT INTO t1 SELECT a FROM t2",
+            vec![
+                "SELECT a FROM t1 UNION SELECT a FROM (SELECT a FROM t2) AS d LIMIT 100",
+                "SELECT 1",
+                "INSERT INTO t1 SELECT a FROM t2",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_already_limited() {
+        assert_rewrite(
+            InjectLimit::new(100),
+            "SELECT a FROM t1 LIMIT 5; SELECT a FROM t1 FETCH FIRST 5 ROWS ONLY",
+            vec![
+                "SELECT a FROM t1 LIMIT 5",
+                "SELECT a FROM t1 FETCH FIRST 5 ROWS ONLY",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_top_and_fetch() {
+        assert_rewrite(
+            InjectLimit::new(100).with_style(LimitStyle::Top),
+            "SELECT a FROM t1; SELECT a FROM t1 UNION SELECT a FROM t2",
+            vec![
+                "SELECT TOP 100 a FROM t1",
+                "SELECT a FROM t1 UNION SELECT a FROM t2 FETCH FIRST 100 ROWS ONLY",
+            ],
+        );
+        assert_rewrite(
+            InjectLimit::new(100).with_style(LimitStyle::Fetch),
+            "SELECT a FROM t1",
+            vec!["SELECT a FROM t1 FETCH FIRST 100 ROWS ONLY"],
+        );
+    }
+
+    #[test]
+    fn test_limit_style_of_dialect() {
+        assert_eq!(LimitStyle::of(&MsSqlDialect {}), LimitStyle::Top);
+        assert_eq!(LimitStyle::of(&AnsiDialect {}), LimitStyle::Fetch);
+        assert_eq!(
+            LimitStyle::of(&sqlparser::dialect::PostgreSqlDialect {}),
+            LimitStyle::Limit
+        );
+    }
+}
@@ -0,0 +1,262 @@
+//! Rewrite that converts OFFSET pagination into keyset pagination.
+
+use crate::error::Error;
+use crate::rewriter::rewrites::mandatory_predicate::and;
+use crate::rewriter::Rewrite;
+use sqlparser::ast::{BinaryOperator, Expr, Ident, OrderByExpr, SetExpr, Statement, Value};
+
+/// [`KeysetParameter`] describes a parameter of a keyset-paginated query.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KeysetParameter {
+    /// Name of the placeholder, without the leading colon.
+    pub name: String,
+    /// Sort key whose value in the last row of the previous page is to be bound.
+    pub column: String,
+    pub descending: bool,
+}
+
+/// [`KeysetPagination`] converts `LIMIT n OFFSET m` pagination into keyset pagination over the given sort keys,
+/// which avoids scanning and discarding the rows skipped by OFFSET.
+///
+/// The OFFSET is removed, the query is ordered by the keys, and a predicate selecting the rows after the last row
+/// of the previous page is added to the WHERE clause, with named placeholders described by [`KeysetPagination::parameters`].
+/// The keys should identify rows uniquely, e.g. by ending with the primary key.
+///
+/// Only top-level SELECTs with an OFFSET are converted. It is an error if such a query is ordered otherwise than by the keys.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+/// use sql_insight::KeysetPagination;
+///
+/// let dialect = GenericDialect {};
+/// let sql = "SELECT * FROM orders WHERE status = 'open' ORDER BY created_at DESC, id DESC LIMIT 20 OFFSET 4000";
+/// let pagination = KeysetPagination::new().with_key("created_at", true).with_key("id", true);
+/// let result = sql_insight::rewrite(&dialect, sql, vec![Box::new(pagination.clone())]).unwrap();
+/// assert_eq!(
+///     result,
+///     ["SELECT * FROM orders WHERE status = 'open' AND (created_at < :after_created_at \
+///         OR (created_at = :after_created_at AND id < :after_id)) ORDER BY created_at DESC, id DESC LIMIT 20"]
+/// );
+/// assert_eq!(pagination.parameters()[1].name, "after_id");
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct KeysetPagination {
+    keys: Vec<(Vec<Ident>, bool)>,
+}
+
+impl KeysetPagination {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a sort key, which may be qualified, e.g. `o.created_at`.
+    pub fn with_key(mut self, column: &str, descending: bool) -> Self {
+        let idents = column.split('.').map(Ident::new).collect();
+        self.keys.push((idents, descending));
+        self
+    }
+
+    /// Parameters of the converted queries, in order of the keys.
+    pub fn parameters(&self) -> Vec<KeysetParameter> {
+        self.keys
+            .iter()
+            .map(|(idents, descending)| KeysetParameter {
+                name: Self::parameter_name(idents),
+                column: idents
+                    .iter()
+                    .map(|ident| ident.value.as_str())
+                    .collect::<Vec<_>>()
+                    .join("."),
+                descending: *descending,
+            })
+            .collect()
+    }
+
+    fn parameter_name(idents: &[Ident]) -> String {
+        let column = idents.last().map_or("", |ident| ident.value.as_str());
+        format!("after_{column}")
+    }
+
+    fn key_expr(idents: &[Ident]) -> Expr {
+        match idents {
+            [ident] => Expr::Identifier(ident.clone()),
+            idents => Expr::CompoundIdentifier(idents.to_vec()),
+        }
+    }
+
+    fn order_by(&self) -> Vec<OrderByExpr> {
+        self.keys
+            .iter()
+            .map(|(idents, descending)| OrderByExpr {
+                expr: Self::key_expr(idents),
+                asc: descending.then_some(false),
+                nulls_first: None,
+            })
+            .collect()
+    }
+
+    /// `k1 > p1 OR (k1 = p1 AND k2 > p2) OR ...`, with `<` for descending keys.
+    fn predicate(&self) -> Option<Expr> {
+        let comparison = |idents: &[Ident], op| Expr::BinaryOp {
+            left: Box::new(Self::key_expr(idents)),
+            op,
+            right: Box::new(Expr::Value(Value::Placeholder(format!(
+                ":{}",
+                Self::parameter_name(idents)
+            )))),
+        };
+        let conjunction = |exprs: Vec<Expr>| {
+            exprs.into_iter().reduce(|left, right| Expr::BinaryOp {
+                left: Box::new(left),
+                op: BinaryOperator::And,
+                right: Box::new(right),
+            })
+        };
+        let disjuncts = self
+            .keys
+            .iter()
+            .enumerate()
+            .filter_map(|(i, (idents, descending))| {
+                let mut exprs: Vec<Expr> = self.keys[..i]
+                    .iter()
+                    .map(|(idents, _)| comparison(idents, BinaryOperator::Eq))
+                    .collect();
+                let op = if *descending {
+                    BinaryOperator::Lt
+                } else {
+                    BinaryOperator::Gt
+                };
+                exprs.push(comparison(idents, op));
+                let disjunct = conjunction(exprs)?;
+                Some(if i == 0 {
+                    disjunct
+                } else {
+                    Expr::Nested(Box::new(disjunct))
+                })
+            });
+        disjuncts.reduce(|left, right| Expr::BinaryOp {
+            left: Box::new(left),
+            op: BinaryOperator::Or,
+            right: Box::new(right),
+        })
+    }
+}
+
+impl Rewrite for KeysetPagination {
+    fn rewrite(&self, statement: &mut Statement) -> Result<(), Error> {
+        let Statement::Query(query) = statement else {
+            return Ok(());
+        };
+        if query.offset.is_none() {
+            return Ok(());
+        }
+        let SetExpr::Select(select) = query.body.as_mut() else {
+            return Ok(());
+        };
+        let Some(predicate) = self.predicate() else {
+            return Err(Error::ArgumentError(
+                "Keyset pagination requires at least one key".into(),
+            ));
+        };
+        let order_by = self.order_by();
+        if !query.order_by.is_empty() && query.order_by != order_by {
+            let actual = query
+                .order_by
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ");
+            return Err(Error::ArgumentError(format!(
+                "ORDER BY {actual} does not match the keyset keys"
+            )));
+        }
+        match &mut select.selection {
+            Some(selection) => and(selection, predicate),
+            None => select.selection = Some(predicate),
+        }
+        query.order_by = order_by;
+        query.offset = None;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+
+    #[test]
+    fn test_keyset_pagination() {
+        let sql = "SELECT * FROM t1 ORDER BY a LIMIT 10 OFFSET 100; \
+            SELECT * FROM t1 LIMIT 10 OFFSET 100; SELECT * FROM t1 LIMIT 10";
+        let pagination = KeysetPagination::new().with_key("a", false);
+        for dialect in all_dialects() {
+            let result = crate::rewrite(dialect.as_ref(), sql, vec![Box::new(pagination.clone())]);
+            assert_eq!(
+                result.unwrap(),
+                vec![
+                    "SELECT * FROM t1 WHERE a > :after_a ORDER BY a LIMIT 10",
+                    "SELECT * FROM t1 WHERE a > :after_a ORDER BY a LIMIT 10",
+                    "SELECT * FROM t1 LIMIT 10",
+                ],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_qualified_keys_and_parameters() {
+        let sql = "SELECT * FROM t1 AS x WHERE b = 1 OR c = 2 ORDER BY x.a, x.id DESC LIMIT 10 OFFSET 100";
+        let pagination = KeysetPagination::new()
+            .with_key("x.a", false)
+            .with_key("x.id", true);
+        for dialect in all_dialects() {
+            let result = crate::rewrite(dialect.as_ref(), sql, vec![Box::new(pagination.clone())]);
+            assert_eq!(
+                result.unwrap(),
+                vec![
+                    "SELECT * FROM t1 AS x WHERE (b = 1 OR c = 2) AND (x.a > :after_a \
+                    OR (x.a = :after_a AND x.id < :after_id)) ORDER BY x.a, x.id DESC LIMIT 10"
+                ],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+        assert_eq!(
+            pagination.parameters(),
+            vec![
+                KeysetParameter {
+                    name: "after_a".into(),
+                    column: "x.a".into(),
+                    descending: false,
+                },
+                KeysetParameter {
+                    name: "after_id".into(),
+                    column: "x.id".into(),
+                    descending: true,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_mismatched_order_by() {
+        let pagination = KeysetPagination::new().with_key("a", false);
+        for dialect in all_dialects() {
+            let result = crate::rewrite(
+                dialect.as_ref(),
+                "SELECT * FROM t1 ORDER BY b DESC LIMIT 10 OFFSET 100",
+                vec![Box::new(pagination.clone())],
+            );
+            assert_eq!(
+                result,
+                Err(Error::ArgumentError(
+                    "ORDER BY b DESC does not match the keyset keys".into()
+                )),
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+}
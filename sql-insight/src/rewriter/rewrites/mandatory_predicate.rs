@@ -0,0 +1,656 @@
+//! Rewrite that appends a mandatory predicate to statements touching targeted tables.
+
+use std::ops::ControlFlow;
+
+use crate::error::Error;
+use crate::helper;
+use crate::rewriter::{rewrite_with_visitor, Rewrite};
+use sqlparser::ast::{
+    BinaryOperator, Expr, GroupByExpr, Ident, JoinConstraint, JoinOperator, MergeClause,
+    ObjectName, Query, Select, SelectItem, SetExpr, Statement, TableAlias, TableFactor,
+    TableWithJoins, VisitMut, VisitorMut, WildcardAdditionalOptions, With,
+};
+use sqlparser::dialect::Dialect;
+use sqlparser::parser::Parser;
+
+/// [`MandatoryPredicate`] adds a predicate, e.g. `deleted_at IS NULL` or `tenant_id = ?`, for every reference
+/// to the targeted tables in SELECT, UPDATE, DELETE and MERGE statements, including subqueries.
+///
+/// Unqualified columns of the predicate are qualified with the alias of each reference, or its table name when not aliased,
+/// and the predicate is combined with an existing WHERE clause by `AND`.
+/// Tables on the right of a `LEFT JOIN ... ON` get the predicate in the ON clause instead, so that the join stays outer,
+/// and other tables on a side of an outer join that may be null-extended, e.g. of a `FULL JOIN`, are replaced by
+/// a derived table filtered by the predicate.
+/// Wrapped tables qualified by a schema, e.g. `s.orders`, are aliased by their table name, and columns qualified by
+/// the schema-qualified name are requalified by the alias.
+/// The target of a MERGE gets the predicate in its `WHEN MATCHED` conditions, so that other rows are neither updated
+/// nor deleted, and a targeted source table is wrapped.
+/// Tables are matched by name, case-insensitively unless quoted, and references to CTEs in scope are left as they are.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+/// use sql_insight::MandatoryPredicate;
+///
+/// let dialect = GenericDialect {};
+/// let sql = "SELECT * FROM orders AS o LEFT JOIN items ON o.id = items.order_id WHERE o.a = 1 OR o.b = 2";
+/// let rewrite = MandatoryPredicate::parse(&dialect, "deleted_at IS NULL")
+///     .unwrap()
+///     .with_table("orders")
+///     .with_table("items");
+/// let result = sql_insight::rewrite(&dialect, sql, vec![Box::new(rewrite)]).unwrap();
+/// assert_eq!(
+///     result,
+///     ["SELECT * FROM orders AS o LEFT JOIN items ON o.id = items.order_id AND items.deleted_at IS NULL \
+///         WHERE (o.a = 1 OR o.b = 2) AND o.deleted_at IS NULL"]
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct MandatoryPredicate {
+    predicate: Expr,
+    tables: Vec<String>,
+}
+
+impl MandatoryPredicate {
+    pub fn new(predicate: Expr) -> Self {
+        Self {
+            predicate,
+            tables: vec![],
+        }
+    }
+
+    /// Parse the predicate from SQL.
+    pub fn parse(dialect: &dyn Dialect, predicate: &str) -> Result<Self, Error> {
+        let predicate = Parser::new(dialect).try_with_sql(predicate)?.parse_expr()?;
+        Ok(Self::new(predicate))
+    }
+
+    /// Target a table by name.
+    pub fn with_table(mut self, table: &str) -> Self {
+        self.tables.push(table.to_string());
+        self
+    }
+
+    /// The predicate for a reference to a targeted table, or `None` if the table is not targeted.
+    fn predicate_for(&self, name: &ObjectName, alias: Option<&Ident>) -> Option<Expr> {
+        let table = name.0.last()?;
+        if !self.tables.iter().any(|target| same_name(table, target)) {
+            return None;
+        }
+        let qualifier = match alias {
+            Some(alias) => vec![alias.clone()],
+            None => name.0.clone(),
+        };
+        let mut predicate = self.predicate.clone();
+        let _ = predicate.visit(&mut Qualifier {
+            qualifier,
+            depth: 0,
+        });
+        Some(predicate)
+    }
+}
+
+impl Rewrite for MandatoryPredicate {
+    fn rewrite(&self, statement: &mut Statement) -> Result<(), Error> {
+        rewrite_with_visitor(
+            statement,
+            &mut MandatoryPredicateVisitor {
+                rewrite: self,
+                cte_scopes: vec![],
+                withs: vec![],
+            },
+        )
+    }
+}
+
+/// Whether an identifier refers to `name`, comparing case-insensitively unless the identifier is quoted.
+fn same_name(ident: &Ident, name: &str) -> bool {
+    match ident.quote_style {
+        Some(_) => ident.value == name,
+        None => ident.value.eq_ignore_ascii_case(name),
+    }
+}
+
+/// Qualifies the columns of the predicate itself, leaving those of its subqueries to their own tables.
+struct Qualifier {
+    qualifier: Vec<Ident>,
+    depth: usize,
+}
+
+impl VisitorMut for Qualifier {
+    type Break = ();
+
+    fn pre_visit_query(&mut self, _query: &mut Query) -> ControlFlow<Self::Break> {
+        self.depth += 1;
+        ControlFlow::Continue(())
+    }
+
+    fn post_visit_query(&mut self, _query: &mut Query) -> ControlFlow<Self::Break> {
+        self.depth -= 1;
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_expr(&mut self, expr: &mut Expr) -> ControlFlow<Self::Break> {
+        if self.depth > 0 {
+            return ControlFlow::Continue(());
+        }
+        if let Expr::Identifier(ident) = expr {
+            let mut idents = self.qualifier.clone();
+            idents.push(ident.clone());
+            *expr = Expr::CompoundIdentifier(idents);
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+struct MandatoryPredicateVisitor<'a> {
+    rewrite: &'a MandatoryPredicate,
+    /// Names of the CTEs in scope of each query being visited.
+    cte_scopes: Vec<Vec<Ident>>,
+    /// WITH clauses taken out of the queries being visited, whose CTEs are visited with their own scopes.
+    withs: Vec<Option<With>>,
+}
+
+impl MandatoryPredicateVisitor<'_> {
+    fn predicate_for(&self, table_factor: &TableFactor) -> Option<Expr> {
+        let TableFactor::Table { name, alias, .. } = table_factor else {
+            return None;
+        };
+        let is_cte = match name.0.as_slice() {
+            [name] => self.cte_scopes.iter().flatten().any(|cte| {
+                match (name.quote_style, cte.quote_style) {
+                    (None, None) => cte.value.eq_ignore_ascii_case(&name.value),
+                    _ => cte.value == name.value,
+                }
+            }),
+            _ => false,
+        };
+        if is_cte {
+            return None;
+        }
+        self.rewrite
+            .predicate_for(name, alias.as_ref().map(|alias| &alias.name))
+    }
+
+    /// Add predicates for the tables of a FROM item to its outer joins, collecting the rest for the WHERE clause.
+    /// Tables which may be null-extended are wrapped instead, unless the predicate can go to the ON clause.
+    fn apply_to_joins(
+        &self,
+        table_with_joins: &mut TableWithJoins,
+        applied: &mut Applied,
+        nullable: bool,
+    ) {
+        // A side preceding a RIGHT or FULL JOIN may be null-extended.
+        let nullable_until = table_with_joins
+            .joins
+            .iter()
+            .rposition(|join| {
+                matches!(
+                    join.join_operator,
+                    JoinOperator::RightOuter(_) | JoinOperator::FullOuter(_)
+                )
+            })
+            .map_or(0, |index| index + 1);
+        self.apply_to_table_factor(
+            &mut table_with_joins.relation,
+            applied,
+            nullable || nullable_until > 0,
+        );
+        for (index, join) in table_with_joins.joins.iter_mut().enumerate() {
+            let nullable = nullable || index + 1 < nullable_until;
+            match &mut join.join_operator {
+                JoinOperator::LeftOuter(JoinConstraint::On(on)) if !nullable => {
+                    let mut join_applied = Applied::default();
+                    self.apply_to_table_factor(&mut join.relation, &mut join_applied, false);
+                    for predicate in join_applied.predicates {
+                        and(on, predicate);
+                    }
+                    applied.requalified.extend(join_applied.requalified);
+                }
+                JoinOperator::LeftOuter(_) | JoinOperator::FullOuter(_) => {
+                    self.apply_to_table_factor(&mut join.relation, applied, true)
+                }
+                _ => self.apply_to_table_factor(&mut join.relation, applied, nullable),
+            }
+        }
+    }
+
+    fn apply_to_table_factor(
+        &self,
+        table_factor: &mut TableFactor,
+        applied: &mut Applied,
+        nullable: bool,
+    ) {
+        match table_factor {
+            TableFactor::NestedJoin {
+                table_with_joins, ..
+            } => self.apply_to_joins(table_with_joins, applied, nullable),
+            table_factor if nullable => {
+                if self.predicate_for(table_factor).is_some() {
+                    applied.requalified.extend(wrap(table_factor));
+                }
+            }
+            table_factor => applied.predicates.extend(self.predicate_for(table_factor)),
+        }
+    }
+
+    /// Apply predicates to the tables, returning the names of wrapped tables whose qualifiers must be replaced.
+    fn apply(
+        &self,
+        tables: Vec<&mut TableWithJoins>,
+        selection: &mut Option<Expr>,
+    ) -> Vec<(ObjectName, Ident)> {
+        let mut applied = Applied::default();
+        for table_with_joins in tables {
+            self.apply_to_joins(table_with_joins, &mut applied, false);
+        }
+        for predicate in applied.predicates {
+            match selection {
+                Some(selection) => and(selection, predicate),
+                None => *selection = Some(predicate),
+            }
+        }
+        applied.requalified
+    }
+
+    /// Restrict the rows a MERGE may change to those satisfying the predicate of its target,
+    /// and wrap a targeted source so that only its rows satisfying the predicate are merged.
+    fn apply_to_merge(
+        &self,
+        table: &TableFactor,
+        source: &mut TableFactor,
+        clauses: &mut [MergeClause],
+    ) -> Vec<(ObjectName, Ident)> {
+        if let Some(predicate) = self.predicate_for(table) {
+            for clause in clauses {
+                if let MergeClause::MatchedUpdate {
+                    predicate: condition,
+                    ..
+                }
+                | MergeClause::MatchedDelete(condition) = clause
+                {
+                    match condition {
+                        Some(condition) => and(condition, predicate.clone()),
+                        None => *condition = Some(predicate.clone()),
+                    }
+                }
+            }
+        }
+        let mut applied = Applied::default();
+        self.apply_to_table_factor(source, &mut applied, true);
+        applied.requalified
+    }
+}
+
+/// Predicates for the WHERE clause and names of wrapped tables, collected from FROM items.
+#[derive(Default)]
+struct Applied {
+    predicates: Vec<Expr>,
+    requalified: Vec<(ObjectName, Ident)>,
+}
+
+/// Replace a table by a derived table selecting all of it, e.g. `(SELECT * FROM orders) AS orders`,
+/// which gets the predicate in its WHERE clause when visited.
+/// Returns the name of the table and the alias replacing it as a qualifier, when the name is qualified by a schema.
+fn wrap(table_factor: &mut TableFactor) -> Option<(ObjectName, Ident)> {
+    let TableFactor::Table { name, alias, .. } = table_factor else {
+        return None;
+    };
+    let requalified = match (alias.as_ref(), name.0.as_slice()) {
+        (None, [.., table]) if name.0.len() > 1 => Some((name.clone(), table.clone())),
+        _ => None,
+    };
+    let alias = alias.take().or_else(|| {
+        name.0.last().map(|name| TableAlias {
+            name: name.clone(),
+            columns: vec![],
+        })
+    });
+    let select = Select {
+        distinct: None,
+        top: None,
+        projection: vec![SelectItem::Wildcard(WildcardAdditionalOptions::default())],
+        into: None,
+        from: vec![TableWithJoins {
+            relation: table_factor.clone(),
+            joins: vec![],
+        }],
+        lateral_views: vec![],
+        selection: None,
+        group_by: GroupByExpr::Expressions(vec![]),
+        cluster_by: vec![],
+        distribute_by: vec![],
+        sort_by: vec![],
+        having: None,
+        named_window: vec![],
+        qualify: None,
+    };
+    let subquery = Query {
+        with: None,
+        body: Box::new(SetExpr::Select(Box::new(select))),
+        order_by: vec![],
+        limit: None,
+        limit_by: vec![],
+        offset: None,
+        fetch: None,
+        locks: vec![],
+        for_clause: None,
+    };
+    *table_factor = TableFactor::Derived {
+        lateral: false,
+        subquery: Box::new(subquery),
+        alias,
+    };
+    requalified
+}
+
+/// Replaces qualifiers naming wrapped tables, e.g. `s.orders.a`, with the aliases of the derived tables, e.g. `orders.a`.
+struct Requalifier<'a> {
+    requalified: &'a [(ObjectName, Ident)],
+}
+
+impl Requalifier<'_> {
+    fn requalify(&self, idents: &mut Vec<Ident>) {
+        for (name, alias) in self.requalified {
+            let matches = idents.len() >= name.0.len()
+                && idents.iter().zip(&name.0).all(|(ident, part)| {
+                    match (ident.quote_style, part.quote_style) {
+                        (None, None) => ident.value.eq_ignore_ascii_case(&part.value),
+                        _ => ident.value == part.value,
+                    }
+                });
+            if matches {
+                idents.splice(..name.0.len(), [alias.clone()]);
+                return;
+            }
+        }
+    }
+
+    fn requalify_select(&self, select: &mut Select) {
+        for item in select.projection.iter_mut() {
+            if let SelectItem::QualifiedWildcard(name, _) = item {
+                self.requalify(&mut name.0);
+            }
+        }
+        let _ = select.visit(&mut Requalifier {
+            requalified: self.requalified,
+        });
+    }
+}
+
+impl VisitorMut for Requalifier<'_> {
+    type Break = ();
+
+    fn pre_visit_expr(&mut self, expr: &mut Expr) -> ControlFlow<Self::Break> {
+        if let Expr::CompoundIdentifier(idents) = expr {
+            // Only qualifiers are replaced, not the whole identifier.
+            if idents.len() > 1 {
+                let column = idents.pop();
+                self.requalify(idents);
+                idents.extend(column);
+            }
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+impl VisitorMut for MandatoryPredicateVisitor<'_> {
+    type Break = Error;
+
+    fn pre_visit_statement(&mut self, statement: &mut Statement) -> ControlFlow<Self::Break> {
+        let requalified = match statement {
+            Statement::Update {
+                table,
+                from,
+                selection,
+                ..
+            } => {
+                let mut tables = vec![table];
+                tables.extend(from.as_mut());
+                self.apply(tables, selection)
+            }
+            Statement::Delete {
+                from,
+                using,
+                selection,
+                ..
+            } => self.apply(
+                from.iter_mut().chain(using.iter_mut().flatten()).collect(),
+                selection,
+            ),
+            Statement::Merge {
+                table,
+                source,
+                clauses,
+                ..
+            } => self.apply_to_merge(table, source, clauses),
+            _ => vec![],
+        };
+        if !requalified.is_empty() {
+            let _ = statement.visit(&mut Requalifier {
+                requalified: &requalified,
+            });
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_query(&mut self, query: &mut Query) -> ControlFlow<Self::Break> {
+        // A CTE sees the CTEs preceding it, and itself too if recursive, while the body sees all of them.
+        let mut ctes = vec![];
+        if let Some(with) = query.with.as_mut() {
+            for cte in with.cte_tables.iter_mut() {
+                if with.recursive {
+                    ctes.push(cte.alias.name.clone());
+                }
+                self.cte_scopes.push(ctes.clone());
+                let result = VisitMut::visit(cte.query.as_mut(), self);
+                self.cte_scopes.pop();
+                result?;
+                if !with.recursive {
+                    ctes.push(cte.alias.name.clone());
+                }
+            }
+        }
+        self.withs.push(query.with.take());
+        self.cte_scopes.push(ctes);
+        let mut selects = Vec::new();
+        helper::collect_selects_mut(&mut query.body, &mut selects);
+        let single_select = selects.len() == 1;
+        let mut requalified = vec![];
+        for select in selects {
+            let renamed = self.apply(select.from.iter_mut().collect(), &mut select.selection);
+            if !renamed.is_empty() {
+                Requalifier {
+                    requalified: &renamed,
+                }
+                .requalify_select(select);
+                requalified.extend(renamed);
+            }
+        }
+        // ORDER BY of a single SELECT refers to its tables.
+        if single_select && !requalified.is_empty() {
+            let mut requalifier = Requalifier {
+                requalified: &requalified,
+            };
+            for order_by in query.order_by.iter_mut() {
+                let _ = order_by.visit(&mut requalifier);
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn post_visit_query(&mut self, query: &mut Query) -> ControlFlow<Self::Break> {
+        query.with = self.withs.pop().flatten();
+        self.cte_scopes.pop();
+        ControlFlow::Continue(())
+    }
+}
+
+/// Combine an expression with a predicate by `AND`, parenthesizing operands that bind looser than `AND`.
+pub(crate) fn and(expr: &mut Expr, predicate: Expr) {
+    fn operand(expr: Expr) -> Expr {
+        match expr {
+            Expr::BinaryOp {
+                op: BinaryOperator::Or | BinaryOperator::Xor,
+                ..
+            } => Expr::Nested(Box::new(expr)),
+            expr => expr,
+        }
+    }
+    let left = std::mem::replace(expr, Expr::Value(sqlparser::ast::Value::Null));
+    *expr = Expr::BinaryOp {
+        left: Box::new(operand(left)),
+        op: BinaryOperator::And,
+        right: Box::new(operand(predicate)),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+
+    fn assert_rewrite(predicate: &str, tables: &[&str], sql: &str, expected: Vec<&str>) {
+        for dialect in all_dialects() {
+            let rewrite = tables.iter().fold(
+                MandatoryPredicate::parse(dialect.as_ref(), predicate).unwrap(),
+                |rewrite, table| rewrite.with_table(table),
+            );
+            let result = crate::rewrite(dialect.as_ref(), sql, vec![Box::new(rewrite)]);
+            assert_eq!(result.unwrap(), expected, "Failed for dialect: {dialect:?}");
+        }
+    }
+
+    #[test]
+    fn test_select() {
+        assert_rewrite(
+            "tenant_id = 42",
+            &["orders", "items"],
+            "SELECT * FROM s.orders, items AS i JOIN users ON i.user_id = users.id \
+                WHERE EXISTS (SELECT 1 FROM orders AS o2 WHERE o2.id = i.order_id)",
+            vec!["SELECT * FROM s.orders, items AS i JOIN users ON i.user_id = users.id \
+                WHERE EXISTS (SELECT 1 FROM orders AS o2 WHERE o2.id = i.order_id AND o2.tenant_id = 42) \
+                AND s.orders.tenant_id = 42 AND i.tenant_id = 42"],
+        );
+    }
+
+    #[test]
+    fn test_update_and_delete() {
+        assert_rewrite(
+            "deleted_at IS NULL OR restored",
+            &["orders"],
+            "UPDATE orders SET a = 1 WHERE id = 1; DELETE FROM orders; DELETE FROM users WHERE id = 1",
+            vec![
+                "UPDATE orders SET a = 1 WHERE id = 1 AND (orders.deleted_at IS NULL OR orders.restored)",
+                "DELETE FROM orders WHERE orders.deleted_at IS NULL OR orders.restored",
+                "DELETE FROM users WHERE id = 1",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_ctes_are_skipped() {
+        assert_rewrite(
+            "deleted_at IS NULL",
+            &["orders"],
+            "WITH orders AS (SELECT * FROM t1) SELECT * FROM orders",
+            vec!["WITH orders AS (SELECT * FROM t1) SELECT * FROM orders"],
+        );
+    }
+
+    #[test]
+    fn test_cte_shadowing_its_own_table() {
+        assert_rewrite(
+            "tenant_id = 42",
+            &["orders"],
+            "WITH orders AS (SELECT * FROM orders), o2 AS (SELECT * FROM orders) SELECT * FROM o2",
+            vec![
+                "WITH orders AS (SELECT * FROM orders WHERE orders.tenant_id = 42), \
+                o2 AS (SELECT * FROM orders) SELECT * FROM o2",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_table_names_are_case_insensitive_unless_quoted() {
+        assert_rewrite(
+            "tenant_id = 42",
+            &["orders"],
+            "SELECT * FROM ORDERS; SELECT * FROM Orders AS o",
+            vec![
+                "SELECT * FROM ORDERS WHERE ORDERS.tenant_id = 42",
+                "SELECT * FROM Orders AS o WHERE o.tenant_id = 42",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_null_extended_sides_are_wrapped() {
+        assert_rewrite(
+            "tenant_id = 42",
+            &["orders", "items"],
+            "SELECT * FROM orders AS o FULL JOIN items ON o.id = items.order_id; \
+                SELECT * FROM orders RIGHT JOIN users ON orders.user_id = users.id",
+            vec![
+                "SELECT * FROM (SELECT * FROM orders WHERE orders.tenant_id = 42) AS o \
+                    FULL JOIN (SELECT * FROM items WHERE items.tenant_id = 42) AS items ON o.id = items.order_id",
+                "SELECT * FROM (SELECT * FROM orders WHERE orders.tenant_id = 42) AS orders \
+                    RIGHT JOIN users ON orders.user_id = users.id",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_subqueries_of_the_predicate_are_not_qualified() {
+        assert_rewrite(
+            "tenant_id IN (SELECT id FROM tenants WHERE active)",
+            &["orders"],
+            "SELECT * FROM orders AS o",
+            vec!["SELECT * FROM orders AS o WHERE o.tenant_id IN (SELECT id FROM tenants WHERE active)"],
+        );
+    }
+
+    #[test]
+    fn test_delete_using() {
+        assert_rewrite(
+            "tenant_id = 42",
+            &["orders"],
+            "DELETE FROM items USING orders WHERE items.order_id = orders.id",
+            vec![
+                "DELETE FROM items USING orders \
+                WHERE items.order_id = orders.id AND orders.tenant_id = 42",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_merge() {
+        assert_rewrite(
+            "tenant_id = 42",
+            &["orders", "staged"],
+            "MERGE INTO orders USING staged ON orders.id = staged.id \
+                WHEN MATCHED AND staged.deleted THEN DELETE \
+                WHEN MATCHED THEN UPDATE SET a = staged.a \
+                WHEN NOT MATCHED THEN INSERT (id, a) VALUES (staged.id, staged.a)",
+            vec!["MERGE INTO orders USING (SELECT * FROM staged WHERE staged.tenant_id = 42) AS staged \
+                ON orders.id = staged.id \
+                WHEN MATCHED AND staged.deleted AND orders.tenant_id = 42 THEN DELETE \
+                WHEN MATCHED AND orders.tenant_id = 42 THEN UPDATE SET a = staged.a \
+                WHEN NOT MATCHED THEN INSERT (id, a) VALUES (staged.id, staged.a)"],
+        );
+    }
+
+    #[test]
+    fn test_wrapped_schema_qualified_tables_are_requalified() {
+        assert_rewrite(
+            "tenant_id = 42",
+            &["orders"],
+            "SELECT s.orders.a, s.orders.* FROM s.orders FULL JOIN users ON s.orders.user_id = users.id \
+                ORDER BY s.orders.b",
+            vec!["SELECT orders.a, orders.* \
+                FROM (SELECT * FROM s.orders WHERE s.orders.tenant_id = 42) AS orders \
+                FULL JOIN users ON orders.user_id = users.id ORDER BY orders.b"],
+        );
+    }
+}
@@ -0,0 +1,41 @@
+//! Built-in rewrites.
+
+pub mod bind_params;
+pub mod boolean_literals;
+pub mod chunk_inserts;
+pub mod coalesce;
+pub mod convert_limit;
+pub mod convert_placeholders;
+pub mod default_schema;
+pub mod expand_views;
+pub mod explicit_joins;
+mod ident;
+pub mod identifier_quotes;
+pub mod inject_limit;
+pub mod keyset_pagination;
+pub mod mandatory_predicate;
+pub mod normalize_identifiers;
+mod relation;
+pub mod rename;
+pub mod strip_order_by;
+pub mod tenant_tables;
+pub mod wrap_query;
+
+pub use bind_params::*;
+pub use boolean_literals::*;
+pub use chunk_inserts::*;
+pub use coalesce::*;
+pub use convert_limit::*;
+pub use convert_placeholders::*;
+pub use default_schema::*;
+pub use expand_views::*;
+pub use explicit_joins::*;
+pub use identifier_quotes::*;
+pub use inject_limit::*;
+pub use keyset_pagination::*;
+pub use mandatory_predicate::*;
+pub use normalize_identifiers::*;
+pub use rename::*;
+pub use strip_order_by::*;
+pub use tenant_tables::*;
+pub use wrap_query::*;
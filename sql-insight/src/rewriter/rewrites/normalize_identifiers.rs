@@ -0,0 +1,185 @@
+//! Rewrite that normalizes the casing and quoting of identifiers.
+
+use crate::error::Error;
+use crate::extractor::table_extractor::{IdentifierFolding, IdentifierRules};
+use crate::rewriter::rewrites::ident::IdentVisitor;
+use crate::rewriter::{rewrite_with_visitor, Rewrite};
+use sqlparser::ast::{Ident, Statement};
+use sqlparser::dialect::{AnsiDialect, Dialect};
+
+/// Casing of unquoted identifiers.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum IdentifierCase {
+    #[default]
+    Lower,
+    Upper,
+    Preserve,
+}
+
+/// Quoting of identifiers.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum IdentifierQuoting {
+    /// Remove quotes that are not needed, keeping them for identifiers that are reserved keywords,
+    /// contain characters other than letters, digits and underscores, or whose casing the dialect would fold.
+    #[default]
+    Minimal,
+    /// Quote every identifier with the given quote.
+    Always(char),
+    Preserve,
+}
+
+/// [`NormalizeIdentifiers`] rewrites identifiers of tables, columns, aliases and CTEs into a canonical style:
+/// unquoted identifiers are cased as configured, which does not change their meaning since they are case-insensitive,
+/// and unnecessary quotes are removed or every identifier is quoted.
+///
+/// Quotes are only removed when the dialect resolves the identifier the same without them, e.g. `"name"` in PostgreSQL,
+/// which folds unquoted identifiers to lowercase, but not in Snowflake, which folds them to uppercase.
+/// Folding and reserved keywords follow the dialect given with [`NormalizeIdentifiers::with_dialect`], ANSI by default.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::PostgreSqlDialect;
+/// use sql_insight::NormalizeIdentifiers;
+///
+/// let dialect = PostgreSqlDialect {};
+/// let sql = "SELECT Id, \"name\", \"Name\", \"user\" FROM \"Users\" AS U";
+/// let rewrite = NormalizeIdentifiers::new().with_dialect(&dialect);
+/// let result = sql_insight::rewrite(&dialect, sql, vec![Box::new(rewrite)]).unwrap();
+/// assert_eq!(result, ["SELECT id, name, \"Name\", \"user\" FROM \"Users\" AS u"]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct NormalizeIdentifiers {
+    case: IdentifierCase,
+    quoting: IdentifierQuoting,
+    rules: IdentifierRules,
+}
+
+impl Default for NormalizeIdentifiers {
+    fn default() -> Self {
+        Self {
+            case: IdentifierCase::default(),
+            quoting: IdentifierQuoting::default(),
+            rules: IdentifierRules::of(&AnsiDialect {}),
+        }
+    }
+}
+
+impl NormalizeIdentifiers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_case(mut self, case: IdentifierCase) -> Self {
+        self.case = case;
+        self
+    }
+
+    pub fn with_quoting(mut self, quoting: IdentifierQuoting) -> Self {
+        self.quoting = quoting;
+        self
+    }
+
+    /// Follow the folding and reserved keywords of the given dialect.
+    pub fn with_dialect(mut self, dialect: &dyn Dialect) -> Self {
+        self.rules = IdentifierRules::of(dialect);
+        self
+    }
+
+    fn apply_case(&self, value: &str) -> String {
+        match self.case {
+            IdentifierCase::Lower => value.to_lowercase(),
+            IdentifierCase::Upper => value.to_uppercase(),
+            IdentifierCase::Preserve => value.to_string(),
+        }
+    }
+
+    /// Whether a quoted identifier would mean something else without quotes.
+    fn is_quote_needed(&self, value: &str) -> bool {
+        let is_plain = value.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && value.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        let is_folded = match self.rules.folding {
+            IdentifierFolding::Lower => value == value.to_lowercase(),
+            IdentifierFolding::Upper => value == value.to_uppercase(),
+            IdentifierFolding::CaseInsensitive | IdentifierFolding::CaseSensitive => true,
+        };
+        !is_plain || !is_folded || self.rules.keywords.is_reserved(value)
+    }
+
+    fn normalize(&self, ident: &mut Ident) {
+        if ident.quote_style.is_none() {
+            ident.value = self.apply_case(&ident.value);
+        }
+        match self.quoting {
+            IdentifierQuoting::Minimal => {
+                if ident.quote_style.is_some() && !self.is_quote_needed(&ident.value) {
+                    ident.quote_style = None;
+                }
+            }
+            IdentifierQuoting::Always(quote) => ident.quote_style = Some(quote),
+            IdentifierQuoting::Preserve => {}
+        }
+    }
+}
+
+impl Rewrite for NormalizeIdentifiers {
+    fn rewrite(&self, statement: &mut Statement) -> Result<(), Error> {
+        rewrite_with_visitor(
+            statement,
+            &mut IdentVisitor::new(|ident| self.normalize(ident)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+    use sqlparser::dialect::{PostgreSqlDialect, SnowflakeDialect};
+
+    fn assert_rewrite(rewrite: NormalizeIdentifiers, sql: &str, expected: Vec<&str>) {
+        for dialect in all_dialects() {
+            let result = crate::rewrite(dialect.as_ref(), sql, vec![Box::new(rewrite.clone())]);
+            assert_eq!(result.unwrap(), expected, "Failed for dialect: {dialect:?}");
+        }
+    }
+
+    #[test]
+    fn test_lowercase_and_minimal_quoting() {
+        assert_rewrite(
+            NormalizeIdentifiers::new(),
+            "SELECT T1.A, COUNT(B) AS Cnt FROM S1.T1 GROUP BY T1.A ORDER BY Cnt; UPDATE T2 SET C = 1",
+            vec![
+                "SELECT t1.a, COUNT(b) AS cnt FROM s1.t1 GROUP BY t1.a ORDER BY cnt",
+                "UPDATE t2 SET c = 1",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_uppercase_and_always_quoting() {
+        assert_rewrite(
+            NormalizeIdentifiers::new()
+                .with_case(IdentifierCase::Upper)
+                .with_quoting(IdentifierQuoting::Always('"')),
+            "INSERT INTO t1 (a, b) SELECT x.a, x.b FROM t2 AS x",
+            vec!["INSERT INTO \"T1\" (\"A\", \"B\") SELECT \"X\".\"A\", \"X\".\"B\" FROM \"T2\" AS \"X\""],
+        );
+    }
+
+    #[test]
+    fn test_quotes_follow_dialect_folding() {
+        let sql = "SELECT \"ID\", \"name\" FROM t1";
+        let dialect = PostgreSqlDialect {};
+        let rewrite = NormalizeIdentifiers::new()
+            .with_case(IdentifierCase::Upper)
+            .with_dialect(&dialect);
+        let result = crate::rewrite(&dialect, sql, vec![Box::new(rewrite)]).unwrap();
+        assert_eq!(result, ["SELECT \"ID\", name FROM T1"]);
+
+        let dialect = SnowflakeDialect {};
+        let rewrite = NormalizeIdentifiers::new().with_dialect(&dialect);
+        let result = crate::rewrite(&dialect, sql, vec![Box::new(rewrite)]).unwrap();
+        assert_eq!(result, ["SELECT ID, \"name\" FROM t1"]);
+    }
+}
@@ -0,0 +1,197 @@
+use std::collections::{HashMap, HashSet};
+use std::ops::ControlFlow;
+
+use crate::error::Error;
+use crate::helper;
+use crate::rewriter::rewrite_with_visitor;
+use sqlparser::ast::{
+    Assignment, Expr, Ident, MergeClause, ObjectName, Query, SelectItem, Statement, TableFactor,
+    Visit, Visitor, VisitorMut,
+};
+
+/// A visitor calling a function on every table name, skipping references to CTEs in scope.
+pub(crate) struct RelationVisitor<F> {
+    cte_scopes: helper::CteScopes,
+    f: F,
+}
+
+impl<F> RelationVisitor<F>
+where
+    F: FnMut(&mut ObjectName) -> Result<(), Error>,
+{
+    pub(crate) fn new(f: F) -> Self {
+        Self {
+            cte_scopes: helper::CteScopes::default(),
+            f,
+        }
+    }
+}
+
+impl<F> VisitorMut for RelationVisitor<F>
+where
+    F: FnMut(&mut ObjectName) -> Result<(), Error>,
+{
+    type Break = Error;
+
+    fn pre_visit_query(&mut self, query: &mut Query) -> ControlFlow<Self::Break> {
+        self.cte_scopes.enter(query);
+        ControlFlow::Continue(())
+    }
+
+    fn post_visit_query(&mut self, _query: &mut Query) -> ControlFlow<Self::Break> {
+        self.cte_scopes.exit();
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_relation(&mut self, relation: &mut ObjectName) -> ControlFlow<Self::Break> {
+        if self.cte_scopes.contains(relation) {
+            return ControlFlow::Continue(());
+        }
+        match (self.f)(relation) {
+            Ok(()) => ControlFlow::Continue(()),
+            Err(e) => ControlFlow::Break(e),
+        }
+    }
+}
+
+/// Rename tables by `rename`, which maps a table name to its new name, leaving references to CTEs as they are.
+/// Column references, wildcards and assignment targets qualified by a renamed table are renamed too,
+/// unless the qualifier is an alias.
+pub(crate) fn rename_tables<F>(statement: &mut Statement, rename: F) -> Result<(), Error>
+where
+    F: Fn(&ObjectName) -> Option<String>,
+{
+    let mut collector = QualifierCollector::default();
+    let _ = statement.visit(&mut collector);
+    if collector.tables.iter().all(|table| rename(table).is_none()) {
+        return Ok(());
+    }
+    let qualifiers: HashMap<String, String> = collector
+        .tables
+        .iter()
+        .filter_map(|table| Some((table.0.last()?.value.clone(), rename(table)?)))
+        .filter(|(name, _)| !collector.aliases.contains(name) && !collector.ctes.contains(name))
+        .collect();
+    let mut relation_visitor = RelationVisitor::new(|relation: &mut ObjectName| {
+        if let Some(renamed) = rename(relation) {
+            if let Some(name) = relation.0.last_mut() {
+                name.value = renamed;
+            }
+        }
+        Ok(())
+    });
+    rewrite_with_visitor(statement, &mut relation_visitor)?;
+    rewrite_with_visitor(statement, &mut QualifierRenamer { qualifiers, rename })
+}
+
+#[derive(Default)]
+struct QualifierCollector {
+    tables: HashSet<ObjectName>,
+    aliases: HashSet<String>,
+    ctes: HashSet<String>,
+}
+
+impl Visitor for QualifierCollector {
+    type Break = ();
+
+    fn pre_visit_query(&mut self, query: &Query) -> ControlFlow<Self::Break> {
+        if let Some(with) = &query.with {
+            self.ctes.extend(
+                with.cte_tables
+                    .iter()
+                    .map(|cte| cte.alias.name.value.clone()),
+            );
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_relation(&mut self, relation: &ObjectName) -> ControlFlow<Self::Break> {
+        self.tables.insert(relation.clone());
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_table_factor(&mut self, table_factor: &TableFactor) -> ControlFlow<Self::Break> {
+        if let TableFactor::Table {
+            alias: Some(alias), ..
+        }
+        | TableFactor::Derived {
+            alias: Some(alias), ..
+        } = table_factor
+        {
+            self.aliases.insert(alias.name.value.clone());
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+/// Renames qualifiers of renamed tables. A bare table name is renamed through `qualifiers`,
+/// which only holds tables that are not shadowed by aliases or CTEs, and a qualified one, e.g. `s.users`, by `rename`.
+struct QualifierRenamer<F> {
+    qualifiers: HashMap<String, String>,
+    rename: F,
+}
+
+impl<F> QualifierRenamer<F>
+where
+    F: Fn(&ObjectName) -> Option<String>,
+{
+    fn rename(&self, qualifier: &mut [Ident]) {
+        let renamed = match qualifier {
+            [] => None,
+            [table] => self.qualifiers.get(&table.value).cloned(),
+            _ => (self.rename)(&ObjectName(qualifier.to_vec())),
+        };
+        if let (Some(renamed), Some(table)) = (renamed, qualifier.last_mut()) {
+            table.value = renamed;
+        }
+    }
+}
+
+impl<F> VisitorMut for QualifierRenamer<F>
+where
+    F: Fn(&ObjectName) -> Option<String>,
+{
+    type Break = Error;
+
+    fn pre_visit_statement(&mut self, statement: &mut Statement) -> ControlFlow<Self::Break> {
+        let assignments: Vec<&mut Assignment> = match statement {
+            Statement::Update { assignments, .. } => assignments.iter_mut().collect(),
+            Statement::Merge { clauses, .. } => clauses
+                .iter_mut()
+                .flat_map(|clause| match clause {
+                    MergeClause::MatchedUpdate { assignments, .. } => assignments.iter_mut(),
+                    _ => [].iter_mut(),
+                })
+                .collect(),
+            _ => vec![],
+        };
+        for assignment in assignments {
+            if let [qualifier @ .., _] = assignment.id.as_mut_slice() {
+                self.rename(qualifier);
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_query(&mut self, query: &mut Query) -> ControlFlow<Self::Break> {
+        let mut selects = Vec::new();
+        helper::collect_selects_mut(&mut query.body, &mut selects);
+        for select in selects {
+            for item in select.projection.iter_mut() {
+                if let SelectItem::QualifiedWildcard(name, _) = item {
+                    self.rename(&mut name.0);
+                }
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_expr(&mut self, expr: &mut Expr) -> ControlFlow<Self::Break> {
+        if let Expr::CompoundIdentifier(idents) = expr {
+            if let [qualifier @ .., _] = idents.as_mut_slice() {
+                self.rename(qualifier);
+            }
+        }
+        ControlFlow::Continue(())
+    }
+}
@@ -0,0 +1,380 @@
+//! Rewrite that renames tables and columns.
+
+use std::collections::HashMap;
+use std::ops::ControlFlow;
+
+use crate::error::Error;
+use crate::helper;
+use crate::rewriter::rewrites::relation::rename_tables;
+use crate::rewriter::{rewrite_with_visitor, Rewrite};
+use sqlparser::ast::{
+    Expr, Ident, ObjectName, Query, Statement, TableFactor, TableWithJoins, VisitorMut,
+};
+
+/// [`Rename`] renames tables and columns by old-to-new name maps, updating every reference.
+///
+/// A table to rename may be qualified, e.g. `public.users`, in which case qualifiers are compared when the table
+/// in SQL has them too, so `public.users` renames `users` and `public.users` but not `other.users`.
+/// Columns are renamed per table. Qualified column references are resolved through table names and aliases,
+/// and unqualified ones are resolved when the query reads a single table. References to CTEs are left as they are.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+/// use sql_insight::Rename;
+///
+/// let dialect = GenericDialect {};
+/// let sql = "SELECT u.name FROM users AS u WHERE u.name LIKE 'a%'; UPDATE users SET name = 'b' WHERE id = 1";
+/// let rename = Rename::new()
+///     .with_table("users", "accounts")
+///     .with_column("users", "name", "full_name");
+/// let result = sql_insight::rewrite(&dialect, sql, vec![Box::new(rename)]).unwrap();
+/// assert_eq!(
+///     result,
+///     [
+///         "SELECT u.full_name FROM accounts AS u WHERE u.full_name LIKE 'a%'",
+///         "UPDATE accounts SET full_name = 'b' WHERE id = 1",
+///     ]
+/// );
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Rename {
+    /// Parts of the old table names, and their new names.
+    tables: Vec<(Vec<String>, String)>,
+    columns: HashMap<String, HashMap<String, String>>,
+}
+
+impl Rename {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rename table `old`, possibly qualified, e.g. `public.users`, to `new`.
+    pub fn with_table(mut self, old: &str, new: &str) -> Self {
+        let old = old.split('.').map(str::to_string).collect();
+        self.tables.push((old, new.to_string()));
+        self
+    }
+
+    /// New name of a table, comparing the parts of the name from the table name outwards while both have them.
+    fn renamed_table(&self, name: &ObjectName) -> Option<String> {
+        self.tables
+            .iter()
+            .find(|(old, _)| {
+                old.iter()
+                    .rev()
+                    .zip(name.0.iter().rev())
+                    .all(|(old, ident)| *old == ident.value)
+            })
+            .map(|(_, new)| new.clone())
+    }
+
+    /// Rename column `old` of `table` to `new`, where `table` is the name before renaming.
+    pub fn with_column(mut self, table: &str, old: &str, new: &str) -> Self {
+        self.columns
+            .entry(table.to_string())
+            .or_default()
+            .insert(old.to_string(), new.to_string());
+        self
+    }
+}
+
+impl Rewrite for Rename {
+    fn rewrite(&self, statement: &mut Statement) -> Result<(), Error> {
+        if !self.columns.is_empty() {
+            rewrite_with_visitor(
+                statement,
+                &mut ColumnRenamer {
+                    columns: &self.columns,
+                    scopes: vec![],
+                    cte_scopes: vec![],
+                },
+            )?;
+        }
+        if self.tables.is_empty() {
+            return Ok(());
+        }
+        rename_tables(statement, |table| self.renamed_table(table))
+    }
+}
+
+/// A table in scope, with the name it is referred to by and the table name if it is not derived.
+struct ScopedTable {
+    qualifier: Option<String>,
+    table: Option<String>,
+}
+
+struct ColumnRenamer<'a> {
+    columns: &'a HashMap<String, HashMap<String, String>>,
+    scopes: Vec<Vec<ScopedTable>>,
+    cte_scopes: Vec<Vec<String>>,
+}
+
+impl ColumnRenamer<'_> {
+    fn scope_of(&self, tables: &[&TableWithJoins]) -> Vec<ScopedTable> {
+        let mut scope = vec![];
+        for table in tables {
+            self.collect_table_factor(&table.relation, &mut scope);
+            for join in &table.joins {
+                self.collect_table_factor(&join.relation, &mut scope);
+            }
+        }
+        scope
+    }
+
+    fn collect_table_factor(&self, table_factor: &TableFactor, scope: &mut Vec<ScopedTable>) {
+        match table_factor {
+            TableFactor::Table { name, alias, .. } => {
+                let is_cte = match name.0.as_slice() {
+                    [name] => self
+                        .cte_scopes
+                        .iter()
+                        .flatten()
+                        .any(|cte| *cte == name.value),
+                    _ => false,
+                };
+                let table = name.0.last().map(|ident| ident.value.clone());
+                let qualifier = alias
+                    .as_ref()
+                    .map(|a| a.name.value.clone())
+                    .or(table.clone());
+                scope.push(ScopedTable {
+                    qualifier,
+                    table: if is_cte { None } else { table },
+                });
+            }
+            TableFactor::NestedJoin {
+                table_with_joins, ..
+            } => scope.extend(self.scope_of(&[table_with_joins])),
+            TableFactor::Derived { alias, .. } | TableFactor::TableFunction { alias, .. } => scope
+                .push(ScopedTable {
+                    qualifier: alias.as_ref().map(|a| a.name.value.clone()),
+                    table: None,
+                }),
+            _ => {}
+        }
+    }
+
+    /// Table a qualifier refers to, searching from the innermost scope outwards for correlated references.
+    fn resolve_qualifier(&self, qualifier: &str) -> Option<&str> {
+        self.scopes
+            .iter()
+            .rev()
+            .flatten()
+            .find(|t| t.qualifier.as_deref() == Some(qualifier))
+            .and_then(|t| t.table.as_deref())
+    }
+
+    /// Table an unqualified column belongs to, known only when the innermost scope has a single table.
+    fn resolve_unqualified(&self) -> Option<&str> {
+        match self.scopes.last().map(Vec::as_slice) {
+            Some([table]) => table.table.as_deref(),
+            _ => None,
+        }
+    }
+
+    fn rename(&self, table: Option<&str>, column: &mut Ident) {
+        if let Some(renamed) = table
+            .and_then(|table| self.columns.get(table))
+            .and_then(|columns| columns.get(&column.value))
+        {
+            column.value = renamed.clone();
+        }
+    }
+}
+
+impl VisitorMut for ColumnRenamer<'_> {
+    type Break = Error;
+
+    fn pre_visit_statement(&mut self, statement: &mut Statement) -> ControlFlow<Self::Break> {
+        let scope = match statement {
+            Statement::Insert {
+                table_name,
+                columns,
+                ..
+            } => {
+                let table = table_name.0.last().map(|ident| ident.value.clone());
+                for column in columns.iter_mut() {
+                    self.rename(table.as_deref(), column);
+                }
+                vec![ScopedTable {
+                    qualifier: table.clone(),
+                    table,
+                }]
+            }
+            Statement::Update {
+                table,
+                assignments,
+                from,
+                ..
+            } => {
+                // Unqualified assignment targets always belong to the updated table.
+                let target = match self.scope_of(&[&*table]).as_slice() {
+                    [target] => target.table.clone(),
+                    _ => None,
+                };
+                let mut tables = vec![&*table];
+                tables.extend(from.iter());
+                let scope = self.scope_of(&tables);
+                self.scopes.push(scope);
+                for assignment in assignments.iter_mut() {
+                    match assignment.id.as_mut_slice() {
+                        [column] => self.rename(target.as_deref(), column),
+                        [.., qualifier, column] => {
+                            let table =
+                                self.resolve_qualifier(&qualifier.value).map(str::to_string);
+                            self.rename(table.as_deref(), column);
+                        }
+                        [] => {}
+                    }
+                }
+                return ControlFlow::Continue(());
+            }
+            Statement::Delete { from, using, .. } => {
+                let tables: Vec<&TableWithJoins> =
+                    from.iter().chain(using.iter().flatten()).collect();
+                self.scope_of(&tables)
+            }
+            _ => vec![],
+        };
+        self.scopes.push(scope);
+        ControlFlow::Continue(())
+    }
+
+    fn post_visit_statement(&mut self, _statement: &mut Statement) -> ControlFlow<Self::Break> {
+        self.scopes.pop();
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_query(&mut self, query: &mut Query) -> ControlFlow<Self::Break> {
+        let ctes = query
+            .with
+            .iter()
+            .flat_map(|with| {
+                with.cte_tables
+                    .iter()
+                    .map(|cte| cte.alias.name.value.clone())
+            })
+            .collect();
+        self.cte_scopes.push(ctes);
+        let mut selects = vec![];
+        helper::collect_selects(&query.body, &mut selects);
+        let tables: Vec<&TableWithJoins> = selects.iter().flat_map(|s| s.from.iter()).collect();
+        let scope = self.scope_of(&tables);
+        self.scopes.push(scope);
+        ControlFlow::Continue(())
+    }
+
+    fn post_visit_query(&mut self, _query: &mut Query) -> ControlFlow<Self::Break> {
+        self.scopes.pop();
+        self.cte_scopes.pop();
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_expr(&mut self, expr: &mut Expr) -> ControlFlow<Self::Break> {
+        match expr {
+            Expr::Identifier(column) => {
+                let table = self.resolve_unqualified().map(str::to_string);
+                self.rename(table.as_deref(), column);
+            }
+            Expr::CompoundIdentifier(idents) => {
+                if let [.., qualifier, column] = idents.as_mut_slice() {
+                    let table = self.resolve_qualifier(&qualifier.value).map(str::to_string);
+                    self.rename(table.as_deref(), column);
+                }
+            }
+            _ => {}
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+
+    fn assert_rewrite(rename: Rename, sql: &str, expected: Vec<&str>) {
+        for dialect in all_dialects() {
+            let result = crate::rewrite(dialect.as_ref(), sql, vec![Box::new(rename.clone())]);
+            assert_eq!(result.unwrap(), expected, "Failed for dialect: {dialect:?}");
+        }
+    }
+
+    fn rename() -> Rename {
+        Rename::new()
+            .with_table("users", "accounts")
+            .with_column("users", "name", "full_name")
+            .with_column("orders", "uid", "user_id")
+    }
+
+    #[test]
+    fn test_select() {
+        assert_rewrite(
+            rename(),
+            "SELECT u.name, users.name, o.uid, name FROM users AS u JOIN orders AS o ON u.id = o.uid, users",
+            vec!["SELECT u.full_name, accounts.full_name, o.user_id, name FROM accounts AS u JOIN orders AS o ON u.id = o.user_id, accounts"],
+        );
+    }
+
+    #[test]
+    fn test_unqualified_columns_of_single_table() {
+        assert_rewrite(
+            rename(),
+            "SELECT name FROM users WHERE name IN (SELECT uid FROM orders WHERE orders.uid = users.id) ORDER BY name",
+            vec!["SELECT full_name FROM accounts WHERE full_name IN (SELECT user_id FROM orders WHERE orders.user_id = accounts.id) ORDER BY full_name"],
+        );
+    }
+
+    #[test]
+    fn test_dml() {
+        assert_rewrite(
+            rename(),
+            "INSERT INTO users (id, name) VALUES (1, 'a'); \
+                UPDATE users SET name = 'b' WHERE name = 'a'; \
+                DELETE FROM orders WHERE uid = 1",
+            vec![
+                "INSERT INTO accounts (id, full_name) VALUES (1, 'a')",
+                "UPDATE accounts SET full_name = 'b' WHERE full_name = 'a'",
+                "DELETE FROM orders WHERE user_id = 1",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_derived_tables_and_ctes_are_skipped() {
+        assert_rewrite(
+            rename(),
+            "WITH users AS (SELECT 1 AS name) SELECT users.name, d.uid FROM users, (SELECT 1 AS uid) AS d",
+            vec!["WITH users AS (SELECT 1 AS name) SELECT users.name, d.uid FROM users, (SELECT 1 AS uid) AS d"],
+        );
+    }
+
+    #[test]
+    fn test_assignment_targets() {
+        assert_rewrite(
+            rename(),
+            "UPDATE users SET users.name = 'x' WHERE users.id = 1",
+            vec!["UPDATE accounts SET accounts.full_name = 'x' WHERE accounts.id = 1"],
+        );
+    }
+
+    #[test]
+    fn test_qualified_tables() {
+        let rename = Rename::new().with_table("public.users", "accounts");
+        assert_rewrite(
+            rename,
+            "SELECT public.users.id, users.name FROM public.users; \
+                SELECT other.users.id FROM other.users; \
+                SELECT users.id FROM users; \
+                UPDATE public.users SET public.users.name = 'x'",
+            vec![
+                "SELECT public.accounts.id, accounts.name FROM public.accounts",
+                "SELECT other.users.id FROM other.users",
+                "SELECT accounts.id FROM accounts",
+                "UPDATE public.accounts SET public.accounts.name = 'x'",
+            ],
+        );
+    }
+}
@@ -0,0 +1,153 @@
+//! Rewrite that removes ORDER BY clauses having no effect.
+
+use std::collections::HashSet;
+use std::ops::ControlFlow;
+
+use crate::error::Error;
+use crate::rewriter::{rewrite_with_visitor, Rewrite};
+use sqlparser::ast::{Distinct, Expr, Query, SetExpr, Statement, VisitorMut};
+
+/// [`StripOrderBy`] removes ORDER BY from derived tables, subqueries in expressions such as `IN (SELECT ...)`,
+/// CTEs and branches of set operations, where it does not affect the result.
+///
+/// ORDER BY is kept when the query limits its rows by `LIMIT`, `OFFSET`, `FETCH` or `TOP`,
+/// when it picks the first row of each group by `DISTINCT ON`, in `ARRAY(SELECT ...)` subqueries, and in the top-level query of SELECT, INSERT, CREATE TABLE and CREATE VIEW statements.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+/// use sql_insight::StripOrderBy;
+///
+/// let dialect = GenericDialect {};
+/// let sql = "SELECT * FROM (SELECT a FROM t1 ORDER BY a) AS d WHERE a IN (SELECT b FROM t2 ORDER BY b LIMIT 5) ORDER BY a";
+/// let result = sql_insight::rewrite(&dialect, sql, vec![Box::new(StripOrderBy::new())]).unwrap();
+/// assert_eq!(
+///     result,
+///     ["SELECT * FROM (SELECT a FROM t1) AS d WHERE a IN (SELECT b FROM t2 ORDER BY b LIMIT 5) ORDER BY a"]
+/// );
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct StripOrderBy;
+
+impl StripOrderBy {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Rewrite for StripOrderBy {
+    fn rewrite(&self, statement: &mut Statement) -> Result<(), Error> {
+        rewrite_with_visitor(
+            statement,
+            &mut StripOrderByVisitor {
+                next_is_top_level: false,
+                kept: HashSet::new(),
+            },
+        )
+    }
+}
+
+struct StripOrderByVisitor {
+    /// Whether the next query visited is the top-level query of the statement.
+    next_is_top_level: bool,
+    /// Queries whose ORDER BY is kept regardless of their position.
+    kept: HashSet<*const Query>,
+}
+
+/// Whether the order of the query decides which rows it returns.
+fn is_order_significant(query: &Query) -> bool {
+    query.limit.is_some()
+        || query.offset.is_some()
+        || query.fetch.is_some()
+        || matches!(
+            query.body.as_ref(),
+            SetExpr::Select(select)
+                if select.top.is_some() || matches!(select.distinct, Some(Distinct::On(_)))
+        )
+}
+
+impl VisitorMut for StripOrderByVisitor {
+    type Break = Error;
+
+    fn pre_visit_statement(&mut self, statement: &mut Statement) -> ControlFlow<Self::Break> {
+        self.next_is_top_level = matches!(
+            statement,
+            Statement::Query(_)
+                | Statement::Insert { .. }
+                | Statement::CreateTable { .. }
+                | Statement::CreateView { .. }
+        );
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_query(&mut self, query: &mut Query) -> ControlFlow<Self::Break> {
+        let is_top_level = std::mem::take(&mut self.next_is_top_level);
+        let is_kept = self.kept.contains(&(query as *const Query));
+        if !is_top_level && !is_kept && !is_order_significant(query) {
+            query.order_by.clear();
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_expr(&mut self, expr: &mut Expr) -> ControlFlow<Self::Break> {
+        if let Expr::ArraySubquery(query) = expr {
+            self.kept.insert(query.as_ref() as *const Query);
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+    use sqlparser::dialect::{Dialect, GenericDialect, PostgreSqlDialect};
+
+    fn assert_rewrite(sql: &str, expected: Vec<&str>) {
+        for dialect in all_dialects() {
+            let result = crate::rewrite(dialect.as_ref(), sql, vec![Box::new(StripOrderBy::new())]);
+            assert_eq!(result.unwrap(), expected, "Failed for dialect: {dialect:?}");
+        }
+    }
+
+    #[test]
+    fn test_subqueries_and_ctes() {
+        assert_rewrite(
+            "WITH c AS (SELECT a FROM t1 ORDER BY a) SELECT a FROM c \
+                WHERE EXISTS (SELECT 1 FROM t2 ORDER BY b) ORDER BY a DESC",
+            vec!["WITH c AS (SELECT a FROM t1) SELECT a FROM c WHERE EXISTS (SELECT 1 FROM t2) ORDER BY a DESC"],
+        );
+    }
+
+    #[test]
+    fn test_set_operations() {
+        assert_rewrite(
+            "(SELECT a FROM t1 ORDER BY a) UNION ALL (SELECT a FROM t2 ORDER BY a LIMIT 1) ORDER BY a",
+            vec!["(SELECT a FROM t1) UNION ALL (SELECT a FROM t2 ORDER BY a LIMIT 1) ORDER BY a"],
+        );
+    }
+
+    #[test]
+    fn test_statements() {
+        assert_rewrite(
+            "INSERT INTO t1 SELECT a FROM t2 ORDER BY a; \
+                UPDATE t1 SET a = 1 WHERE b IN (SELECT b FROM t2 ORDER BY b)",
+            vec![
+                "INSERT INTO t1 SELECT a FROM t2 ORDER BY a",
+                "UPDATE t1 SET a = 1 WHERE b IN (SELECT b FROM t2)",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_distinct_on() {
+        let sql = "SELECT * FROM (SELECT DISTINCT ON (a) a, b FROM t1 ORDER BY a, b DESC) AS d";
+        let dialects: Vec<Box<dyn Dialect>> =
+            vec![Box::new(PostgreSqlDialect {}), Box::new(GenericDialect {})];
+        for dialect in dialects {
+            let result = crate::rewrite(dialect.as_ref(), sql, vec![Box::new(StripOrderBy::new())]);
+            assert_eq!(result.unwrap(), [sql], "Failed for dialect: {dialect:?}");
+        }
+    }
+}
@@ -0,0 +1,159 @@
+//! Rewrite that maps table names for multi-tenancy.
+
+use std::collections::HashMap;
+
+use crate::error::Error;
+use crate::rewriter::rewrites::relation::rename_tables;
+use crate::rewriter::Rewrite;
+use sqlparser::ast::Statement;
+
+/// [`TenantTables`] maps table names to tenant-specific ones, e.g. `orders` to `tenant_42_orders`,
+/// by a prefix and suffix or by an explicit mapping which takes precedence.
+///
+/// Every reference is updated, including the targets of INSERT, UPDATE, DELETE and MERGE,
+/// and column references qualified by a table name. Aliases and references to CTEs are left as they are.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+/// use sql_insight::TenantTables;
+///
+/// let dialect = GenericDialect {};
+/// let sql = "SELECT orders.id, c.name FROM orders JOIN customers AS c ON orders.customer_id = c.id";
+/// let rewrite = TenantTables::new().with_prefix("tenant_42_");
+/// let result = sql_insight::rewrite(&dialect, sql, vec![Box::new(rewrite)]).unwrap();
+/// assert_eq!(
+///     result,
+///     ["SELECT tenant_42_orders.id, c.name FROM tenant_42_orders JOIN tenant_42_customers AS c ON tenant_42_orders.customer_id = c.id"]
+/// );
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct TenantTables {
+    prefix: String,
+    suffix: String,
+    mapping: HashMap<String, String>,
+    tables: Option<Vec<String>>,
+}
+
+impl TenantTables {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_prefix(mut self, prefix: &str) -> Self {
+        self.prefix = prefix.to_string();
+        self
+    }
+
+    pub fn with_suffix(mut self, suffix: &str) -> Self {
+        self.suffix = suffix.to_string();
+        self
+    }
+
+    /// Explicit mapping of table names, taking precedence over the prefix and suffix.
+    pub fn with_mapping(mut self, mapping: HashMap<String, String>) -> Self {
+        self.mapping = mapping;
+        self
+    }
+
+    /// Only apply the prefix and suffix to these tables. Every table gets them unless set.
+    pub fn with_tables(mut self, tables: Vec<String>) -> Self {
+        self.tables = Some(tables);
+        self
+    }
+
+    /// Tenant-specific name of a table, if it is mapped.
+    pub fn map(&self, table: &str) -> Option<String> {
+        if let Some(mapped) = self.mapping.get(table) {
+            return Some(mapped.clone());
+        }
+        if self.prefix.is_empty() && self.suffix.is_empty() {
+            return None;
+        }
+        match &self.tables {
+            Some(tables) if !tables.iter().any(|t| t == table) => None,
+            _ => Some(format!("{}{}{}", self.prefix, table, self.suffix)),
+        }
+    }
+}
+
+impl Rewrite for TenantTables {
+    fn rewrite(&self, statement: &mut Statement) -> Result<(), Error> {
+        rename_tables(statement, |table| {
+            table.0.last().and_then(|name| self.map(&name.value))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+
+    fn assert_rewrite(rewrite: TenantTables, sql: &str, expected: Vec<&str>) {
+        for dialect in all_dialects() {
+            let result = crate::rewrite(dialect.as_ref(), sql, vec![Box::new(rewrite.clone())]);
+            assert_eq!(result.unwrap(), expected, "Failed for dialect: {dialect:?}");
+        }
+    }
+
+    #[test]
+    fn test_prefix_and_suffix() {
+        assert_rewrite(
+            TenantTables::new().with_prefix("t42_").with_suffix("_v1"),
+            "INSERT INTO orders (id) SELECT id FROM s.carts; \
+                UPDATE orders SET a = 1 WHERE orders.id = 1; DELETE FROM orders AS o WHERE o.id = 1",
+            vec![
+                "INSERT INTO t42_orders_v1 (id) SELECT id FROM s.t42_carts_v1",
+                "UPDATE t42_orders_v1 SET a = 1 WHERE t42_orders_v1.id = 1",
+                "DELETE FROM t42_orders_v1 AS o WHERE o.id = 1",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_mapping_and_tables() {
+        let rewrite = TenantTables::new()
+            .with_prefix("t42_")
+            .with_tables(vec!["orders".into()])
+            .with_mapping(HashMap::from([("items".into(), "items_s3".into())]));
+        assert_rewrite(
+            rewrite,
+            "SELECT orders.*, items.name FROM orders JOIN items ON orders.id = items.order_id JOIN users ON users.id = orders.user_id",
+            vec!["SELECT t42_orders.*, items_s3.name FROM t42_orders JOIN items_s3 ON t42_orders.id = items_s3.order_id JOIN users ON users.id = t42_orders.user_id"],
+        );
+    }
+
+    #[test]
+    fn test_assignment_targets() {
+        let rewrite = TenantTables::new().with_prefix("t_");
+        let result = crate::rewrite(
+            &sqlparser::dialect::MySqlDialect {},
+            "UPDATE orders JOIN items ON orders.id = items.order_id SET orders.a = 1, items.b = 2",
+            vec![Box::new(rewrite.clone())],
+        );
+        assert_eq!(
+            result.unwrap(),
+            ["UPDATE t_orders JOIN t_items ON t_orders.id = t_items.order_id SET t_orders.a = 1, t_items.b = 2"]
+        );
+        assert_rewrite(
+            rewrite,
+            "MERGE INTO orders USING staged ON orders.id = staged.id \
+                WHEN MATCHED THEN UPDATE SET orders.a = staged.a",
+            vec![
+                "MERGE INTO t_orders USING t_staged ON t_orders.id = t_staged.id \
+                WHEN MATCHED THEN UPDATE SET t_orders.a = t_staged.a",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_cte_and_alias_are_skipped() {
+        assert_rewrite(
+            TenantTables::new().with_prefix("t42_"),
+            "WITH recent AS (SELECT id FROM orders AS o) SELECT recent.id, o.id FROM recent JOIN orders AS o ON o.id = recent.id",
+            vec!["WITH recent AS (SELECT id FROM t42_orders AS o) SELECT recent.id, o.id FROM recent JOIN t42_orders AS o ON o.id = recent.id"],
+        );
+    }
+}
@@ -0,0 +1,212 @@
+//! Rewrites that wrap a query in an outer query for counting or sampling its rows.
+
+use crate::error::Error;
+use crate::rewriter::rewrites::inject_limit::{InjectLimit, LimitStyle};
+use crate::rewriter::Rewrite;
+use sqlparser::ast::{Query, SetExpr, Statement, TableFactor};
+use sqlparser::dialect::{
+    BigQueryDialect, ClickHouseDialect, Dialect, GenericDialect, HiveDialect, MsSqlDialect,
+    MySqlDialect,
+};
+use sqlparser::parser::Parser;
+
+/// [`CountRows`] wraps a query into `SELECT COUNT(*) FROM (...) AS sub` to count the rows it returns.
+/// ORDER BY of the query is dropped when it has no LIMIT, OFFSET or FETCH, since it does not affect the count
+/// and is not allowed in subqueries by some dialects. Statements other than queries are left as they are.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+/// use sql_insight::CountRows;
+///
+/// let dialect = GenericDialect {};
+/// let sql = "SELECT a, b FROM t1 WHERE c = 1 ORDER BY a";
+/// let result = sql_insight::rewrite(&dialect, sql, vec![Box::new(CountRows::new())]).unwrap();
+/// assert_eq!(result, ["SELECT COUNT(*) FROM (SELECT a, b FROM t1 WHERE c = 1) AS sub"]);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct CountRows;
+
+impl CountRows {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Rewrite for CountRows {
+    fn rewrite(&self, statement: &mut Statement) -> Result<(), Error> {
+        let Statement::Query(query) = statement else {
+            return Ok(());
+        };
+        let mut query = query.as_ref().clone();
+        if query.limit.is_none() && query.offset.is_none() && query.fetch.is_none() {
+            query.order_by.clear();
+        }
+        *statement = wrap("SELECT COUNT(*) FROM (SELECT 1) AS sub", query)?;
+        Ok(())
+    }
+}
+
+/// [`SampleRows`] wraps a query into `SELECT * FROM (...) AS sub ORDER BY RANDOM()` limited to a number of rows,
+/// to take a random sample of the rows it returns. Statements other than queries are left as they are.
+///
+/// The random function and the way the limit is written depend on the dialect when created with
+/// [`SampleRows::for_dialect`], e.g. `SELECT TOP n * FROM (...) AS sub ORDER BY NEWID()` for MsSQL.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::MySqlDialect;
+/// use sql_insight::SampleRows;
+///
+/// let dialect = MySqlDialect {};
+/// let sql = "SELECT a FROM t1";
+/// let result = sql_insight::rewrite(&dialect, sql, vec![Box::new(SampleRows::for_dialect(&dialect, 100))]).unwrap();
+/// assert_eq!(result, ["SELECT * FROM (SELECT a FROM t1) AS sub ORDER BY RAND() LIMIT 100"]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct SampleRows {
+    rows: u64,
+    random_function: String,
+    limit_style: LimitStyle,
+}
+
+impl SampleRows {
+    pub fn new(rows: u64) -> Self {
+        Self {
+            rows,
+            random_function: "RANDOM".into(),
+            limit_style: LimitStyle::Limit,
+        }
+    }
+
+    /// Sample in the way of the given dialect.
+    pub fn for_dialect(dialect: &dyn Dialect, rows: u64) -> Self {
+        let random_function = if dialect.is::<MsSqlDialect>() {
+            "NEWID"
+        } else if dialect.is::<MySqlDialect>()
+            || dialect.is::<BigQueryDialect>()
+            || dialect.is::<HiveDialect>()
+        {
+            "RAND"
+        } else if dialect.is::<ClickHouseDialect>() {
+            "rand"
+        } else {
+            "RANDOM"
+        };
+        Self::new(rows)
+            .with_random_function(random_function)
+            .with_limit_style(LimitStyle::of(dialect))
+    }
+
+    /// Name of the function ordering rows randomly. Defaults to `RANDOM`.
+    pub fn with_random_function(mut self, name: &str) -> Self {
+        self.random_function = name.to_string();
+        self
+    }
+
+    pub fn with_limit_style(mut self, style: LimitStyle) -> Self {
+        self.limit_style = style;
+        self
+    }
+}
+
+impl Rewrite for SampleRows {
+    fn rewrite(&self, statement: &mut Statement) -> Result<(), Error> {
+        let Statement::Query(query) = statement else {
+            return Ok(());
+        };
+        let template = format!(
+            "SELECT * FROM (SELECT 1) AS sub ORDER BY {}()",
+            self.random_function
+        );
+        *statement = wrap(&template, query.as_ref().clone())?;
+        InjectLimit::new(self.rows)
+            .with_style(self.limit_style)
+            .rewrite(statement)
+    }
+}
+
+/// Parse a template selecting from a derived table and put the query in place of the derived table.
+fn wrap(template: &str, query: Query) -> Result<Statement, Error> {
+    let mut statements = Parser::parse_sql(&GenericDialect {}, template)?;
+    if let Some(Statement::Query(outer)) = statements.first_mut() {
+        if let SetExpr::Select(select) = outer.body.as_mut() {
+            if let Some(TableFactor::Derived { subquery, .. }) =
+                select.from.first_mut().map(|table| &mut table.relation)
+            {
+                **subquery = query;
+                return Ok(statements.remove(0));
+            }
+        }
+    }
+    Err(Error::AnalysisError(format!(
+        "Template does not select from a derived table: {template}"
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+
+    #[test]
+    fn test_count_rows() {
+        let sql = "SELECT a FROM t1 UNION SELECT a FROM t2 ORDER BY a; \
+            SELECT a FROM t1 ORDER BY a LIMIT 10; DELETE FROM t1";
+        for dialect in all_dialects() {
+            let result = crate::rewrite(dialect.as_ref(), sql, vec![Box::new(CountRows::new())]);
+            assert_eq!(
+                result.unwrap(),
+                vec![
+                    "SELECT COUNT(*) FROM (SELECT a FROM t1 UNION SELECT a FROM t2) AS sub",
+                    "SELECT COUNT(*) FROM (SELECT a FROM t1 ORDER BY a LIMIT 10) AS sub",
+                    "DELETE FROM t1",
+                ],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_sample_rows() {
+        let sql = "SELECT a FROM t1 WHERE b = 1";
+        for dialect in all_dialects() {
+            let result = crate::rewrite(dialect.as_ref(), sql, vec![Box::new(SampleRows::new(10))]);
+            assert_eq!(
+                result.unwrap(),
+                vec!["SELECT * FROM (SELECT a FROM t1 WHERE b = 1) AS sub ORDER BY RANDOM() LIMIT 10"],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_sample_rows_for_dialect() {
+        let sql = "SELECT a FROM t1";
+        let cases: Vec<(Box<dyn Dialect>, &str)> = vec![
+            (
+                Box::new(MsSqlDialect {}),
+                "SELECT TOP 10 * FROM (SELECT a FROM t1) AS sub ORDER BY NEWID()",
+            ),
+            (
+                Box::new(sqlparser::dialect::AnsiDialect {}),
+                "SELECT * FROM (SELECT a FROM t1) AS sub ORDER BY RANDOM() FETCH FIRST 10 ROWS ONLY",
+            ),
+            (
+                Box::new(ClickHouseDialect {}),
+                "SELECT * FROM (SELECT a FROM t1) AS sub ORDER BY rand() LIMIT 10",
+            ),
+        ];
+        for (dialect, expected) in cases {
+            let rewrite = SampleRows::for_dialect(dialect.as_ref(), 10);
+            let result = crate::rewrite(dialect.as_ref(), sql, vec![Box::new(rewrite)]);
+            assert_eq!(
+                result.unwrap(),
+                vec![expected],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,140 @@
+//! Translation of SQL between dialects, covering the most common portability issues.
+
+use std::ops::ControlFlow;
+
+use crate::error::Error;
+use crate::rewriter::{
+    rewrite, rewrite_with_visitor, BooleansAsIntegers, ConvertLimit, IdentifierQuotes,
+    IfNullToCoalesce, LimitStyle, Rewrite,
+};
+use sqlparser::ast::{Expr, Statement, VisitorMut};
+use sqlparser::dialect::{
+    AnsiDialect, BigQueryDialect, Dialect, HiveDialect, MsSqlDialect, MySqlDialect, SQLiteDialect,
+};
+
+/// [`Untranslatable`] describes a construct that cannot be expressed in the target dialect.
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[error("{construct} cannot be translated: {reason}")]
+pub struct Untranslatable {
+    /// The construct, e.g. `ILIKE`.
+    pub construct: String,
+    /// Why it cannot be translated.
+    pub reason: String,
+}
+
+impl Untranslatable {
+    pub fn new(construct: &str, reason: &str) -> Self {
+        Self {
+            construct: construct.to_string(),
+            reason: reason.to_string(),
+        }
+    }
+}
+
+/// Convenience function to translate SQL written for one dialect into another.
+///
+/// The following constructs are translated for the target dialect:
+/// - Row limits are written with `LIMIT`, `TOP` or `FETCH` by [`ConvertLimit`].
+/// - `IFNULL`, `NVL` and two-argument `ISNULL` become `COALESCE` by [`IfNullToCoalesce`].
+/// - Quoted identifiers use backticks, brackets or double quotes by [`IdentifierQuotes`].
+/// - Boolean literals become integers for MsSQL by [`BooleansAsIntegers`].
+///
+/// Constructs that cannot be expressed in the target dialect, e.g. `ILIKE` for MySQL, are reported as
+/// [`Error::Untranslatable`]. Other dialect differences are not taken care of.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::{MsSqlDialect, MySqlDialect};
+///
+/// let sql = "SELECT `a`, IFNULL(b, 0) FROM t1 WHERE c = TRUE LIMIT 10";
+/// let result = sql_insight::transpile(&MySqlDialect {}, &MsSqlDialect {}, sql).unwrap();
+/// assert_eq!(result, ["SELECT TOP 10 [a], COALESCE(b, 0) FROM t1 WHERE c = 1"]);
+/// ```
+pub fn transpile(from: &dyn Dialect, to: &dyn Dialect, sql: &str) -> Result<Vec<String>, Error> {
+    let mut rewrites: Vec<Box<dyn Rewrite>> = vec![
+        Box::new(ConvertLimit::new(LimitStyle::of(to))),
+        Box::new(IfNullToCoalesce::new()),
+        Box::new(IdentifierQuotes::for_dialect(to)),
+    ];
+    if to.is::<MsSqlDialect>() {
+        rewrites.push(Box::new(BooleansAsIntegers::new()));
+    }
+    if !supports_ilike(to) {
+        rewrites.push(Box::new(|statement: &mut Statement| {
+            rewrite_with_visitor(statement, &mut RejectILike)
+        }));
+    }
+    rewrite(from, sql, rewrites)
+}
+
+fn supports_ilike(dialect: &dyn Dialect) -> bool {
+    !(dialect.is::<MySqlDialect>()
+        || dialect.is::<MsSqlDialect>()
+        || dialect.is::<SQLiteDialect>()
+        || dialect.is::<BigQueryDialect>()
+        || dialect.is::<HiveDialect>()
+        || dialect.is::<AnsiDialect>())
+}
+
+struct RejectILike;
+
+impl VisitorMut for RejectILike {
+    type Break = Error;
+
+    fn pre_visit_expr(&mut self, expr: &mut Expr) -> ControlFlow<Self::Break> {
+        if let Expr::ILike { .. } = expr {
+            return ControlFlow::Break(
+                Untranslatable::new(
+                    "ILIKE",
+                    "case-insensitive matching is not supported by the target dialect",
+                )
+                .into(),
+            );
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlparser::dialect::{PostgreSqlDialect, SnowflakeDialect};
+
+    #[test]
+    fn test_transpile_into_postgres() {
+        let sql = "SELECT TOP 5 [a], ISNULL(b, 0) FROM t1 WHERE c = 1";
+        let result = transpile(&MsSqlDialect {}, &PostgreSqlDialect {}, sql);
+        assert_eq!(
+            result.unwrap(),
+            vec!["SELECT \"a\", COALESCE(b, 0) FROM t1 WHERE c = 1 LIMIT 5"]
+        );
+    }
+
+    #[test]
+    fn test_transpile_into_ansi() {
+        let sql = "SELECT `a` FROM t1 WHERE b = TRUE ORDER BY a LIMIT 5 OFFSET 10";
+        let result = transpile(&MySqlDialect {}, &AnsiDialect {}, sql);
+        assert_eq!(
+            result.unwrap(),
+            vec!["SELECT \"a\" FROM t1 WHERE b = true ORDER BY a OFFSET 10 ROWS FETCH FIRST 5 ROWS ONLY"]
+        );
+    }
+
+    #[test]
+    fn test_untranslatable_ilike() {
+        let sql = "SELECT a FROM t1 WHERE b ILIKE 'x%'";
+        assert_eq!(
+            transpile(&PostgreSqlDialect {}, &SnowflakeDialect {}, sql).unwrap(),
+            vec!["SELECT a FROM t1 WHERE b ILIKE 'x%'"]
+        );
+        assert_eq!(
+            transpile(&PostgreSqlDialect {}, &MySqlDialect {}, sql),
+            Err(Error::Untranslatable(Untranslatable::new(
+                "ILIKE",
+                "case-insensitive matching is not supported by the target dialect"
+            )))
+        );
+    }
+}
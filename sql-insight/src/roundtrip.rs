@@ -0,0 +1,282 @@
+//! A verifier that formatting and normalization of SQL round-trip, i.e. formatted statements reparse into
+//! the same AST and normalizing normalized statements changes nothing.
+//!
+//! See [`verify_roundtrip`](crate::verify_roundtrip()) as the entry point for verifying SQL.
+
+use std::fmt;
+
+use crate::error::Error;
+use crate::instrument;
+use crate::normalizer::Normalizer;
+use crate::report::ErrorRecord;
+use sqlparser::ast::{Statement, VisitMut};
+use sqlparser::dialect::Dialect;
+
+/// Convenience function to verify that each statement of SQL round-trips through formatting and normalization.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+///
+/// let dialect = GenericDialect {};
+/// let reports = sql_insight::verify_roundtrip(&dialect, "SELECT a FROM t1 WHERE b = 1; DELETE FROM t2").unwrap();
+/// assert!(reports.iter().all(|report| report.is_ok()));
+/// ```
+pub fn verify_roundtrip(dialect: &dyn Dialect, sql: &str) -> Result<Vec<RoundtripReport>, Error> {
+    let statements = instrument::parse_sql(dialect, sql)?;
+    Ok(statements
+        .iter()
+        .enumerate()
+        .map(|(index, statement)| RoundtripReport {
+            index,
+            formatted: statement.to_string(),
+            mismatches: RoundtripVerifier::verify_statement(dialect, statement),
+        })
+        .collect())
+}
+
+/// [`RoundtripReport`] represents the result of verifying a statement.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RoundtripReport {
+    /// Zero-based position of the statement in the input.
+    pub index: usize,
+    /// The statement as formatted by the parser.
+    pub formatted: String,
+    pub mismatches: Vec<RoundtripMismatch>,
+}
+
+impl RoundtripReport {
+    pub fn is_ok(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
+
+/// [`RoundtripMismatch`] represents a way a statement fails to round-trip.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum RoundtripMismatch {
+    /// The formatted statement fails to parse.
+    FormatUnparsable {
+        formatted: String,
+        error: ErrorRecord,
+    },
+    /// The formatted statement parses into a different AST.
+    /// `line` is the first line at which the pretty-printed ASTs differ, with the lines of the original and reparsed ASTs.
+    FormatChangesAst {
+        formatted: String,
+        line: usize,
+        expected: String,
+        actual: String,
+    },
+    /// The normalized statement fails to parse.
+    NormalizeUnparsable {
+        normalized: String,
+        error: ErrorRecord,
+    },
+    /// Normalizing the normalized statement changes it.
+    NormalizeNotIdempotent {
+        normalized: String,
+        renormalized: String,
+    },
+}
+
+impl fmt::Display for RoundtripMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RoundtripMismatch::FormatUnparsable { formatted, error } => {
+                write!(f, "Formatted statement fails to parse: {formatted}: {}", error.message)
+            }
+            RoundtripMismatch::FormatChangesAst {
+                formatted,
+                line,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Formatted statement parses into a different AST: {formatted}: at line {line}, expected `{expected}`, got `{actual}`"
+            ),
+            RoundtripMismatch::NormalizeUnparsable { normalized, error } => {
+                write!(f, "Normalized statement fails to parse: {normalized}: {}", error.message)
+            }
+            RoundtripMismatch::NormalizeNotIdempotent {
+                normalized,
+                renormalized,
+            } => write!(
+                f,
+                "Normalization is not idempotent: {normalized} is normalized into {renormalized}"
+            ),
+        }
+    }
+}
+
+/// Verifier of round-trips of statements.
+#[derive(Debug, Default)]
+pub struct RoundtripVerifier;
+
+impl RoundtripVerifier {
+    /// Verify that a statement round-trips through formatting and normalization under the dialect.
+    pub fn verify_statement(
+        dialect: &dyn Dialect,
+        statement: &Statement,
+    ) -> Vec<RoundtripMismatch> {
+        let mut mismatches = vec![];
+        let formatted = statement.to_string();
+        match instrument::parse_sql(dialect, &formatted) {
+            Ok(reparsed) => {
+                if reparsed.len() != 1 || reparsed[0] != *statement {
+                    let (line, expected, actual) = first_difference(
+                        &format!("{:#?}", [statement]),
+                        &format!("{:#?}", reparsed),
+                    );
+                    mismatches.push(RoundtripMismatch::FormatChangesAst {
+                        formatted,
+                        line,
+                        expected,
+                        actual,
+                    });
+                }
+            }
+            Err(e) => mismatches.push(RoundtripMismatch::FormatUnparsable {
+                formatted,
+                error: ErrorRecord::from(&e),
+            }),
+        }
+        let normalized = normalize(statement);
+        match instrument::parse_sql(dialect, &normalized) {
+            Ok(reparsed) => {
+                let renormalized = reparsed
+                    .iter()
+                    .map(normalize)
+                    .collect::<Vec<_>>()
+                    .join("; ");
+                if renormalized != normalized {
+                    mismatches.push(RoundtripMismatch::NormalizeNotIdempotent {
+                        normalized,
+                        renormalized,
+                    });
+                }
+            }
+            Err(e) => mismatches.push(RoundtripMismatch::NormalizeUnparsable {
+                normalized,
+                error: ErrorRecord::from(&e),
+            }),
+        }
+        mismatches
+    }
+}
+
+fn normalize(statement: &Statement) -> String {
+    let mut normalized = statement.clone();
+    let _ = normalized.visit(&mut Normalizer::new());
+    normalized.to_string()
+}
+
+/// The first differing line of two texts, numbered from 1, with the line of each text, empty past its end.
+fn first_difference(expected: &str, actual: &str) -> (usize, String, String) {
+    let mut expected_lines = expected.lines();
+    let mut actual_lines = actual.lines();
+    let mut line = 1;
+    loop {
+        match (expected_lines.next(), actual_lines.next()) {
+            (Some(e), Some(a)) if e == a => line += 1,
+            (e, a) => {
+                return (
+                    line,
+                    e.unwrap_or_default().trim().to_string(),
+                    a.unwrap_or_default().trim().to_string(),
+                )
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::ErrorKind;
+    use crate::test_utils::all_dialects;
+    use sqlparser::ast::{Expr, Ident, SelectItem, SetExpr};
+    use sqlparser::parser::Parser;
+
+    /// Parse `SELECT a FROM t1` and rename the column to `name` without quoting it.
+    fn select_unquoted(dialect: &dyn Dialect, name: &str) -> Statement {
+        let mut statement = Parser::parse_sql(dialect, "SELECT a FROM t1")
+            .unwrap()
+            .remove(0);
+        let Statement::Query(query) = &mut statement else {
+            unreachable!()
+        };
+        let SetExpr::Select(select) = query.body.as_mut() else {
+            unreachable!()
+        };
+        select.projection = vec![SelectItem::UnnamedExpr(Expr::Identifier(Ident::new(name)))];
+        statement
+    }
+
+    #[test]
+    fn test_verify_roundtrip() {
+        let sql = "SELECT a, COUNT(*) FROM t1 AS x JOIN t2 ON x.id = t2.id WHERE b IN (1, 2) GROUP BY a; \
+            INSERT INTO t3 (a, b) VALUES (1, 'x'); \
+            UPDATE t4 SET a = 1 WHERE b LIKE 'x%'; \
+            DELETE FROM t5 WHERE a BETWEEN 1 AND 2";
+        for dialect in all_dialects() {
+            let reports = verify_roundtrip(dialect.as_ref(), sql).unwrap();
+            assert_eq!(reports.len(), 4, "Failed for dialect: {dialect:?}");
+            for report in reports {
+                assert!(
+                    report.is_ok(),
+                    "Failed for dialect: {dialect:?}: {report:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_format_changes_ast() {
+        for dialect in all_dialects() {
+            let statement = select_unquoted(dialect.as_ref(), "a b");
+            let mismatches = RoundtripVerifier::verify_statement(dialect.as_ref(), &statement);
+            assert!(
+                matches!(
+                    &mismatches[0],
+                    RoundtripMismatch::FormatChangesAst { formatted, expected, actual, .. }
+                        if formatted == "SELECT a b FROM t1" && expected != actual
+                ),
+                "Failed for dialect: {dialect:?}: {mismatches:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_format_unparsable() {
+        for dialect in all_dialects() {
+            let statement = select_unquoted(dialect.as_ref(), "(");
+            let mismatches = RoundtripVerifier::verify_statement(dialect.as_ref(), &statement);
+            assert!(
+                matches!(
+                    &mismatches[..],
+                    [
+                        RoundtripMismatch::FormatUnparsable { formatted, error },
+                        RoundtripMismatch::NormalizeUnparsable { .. },
+                    ] if formatted == "SELECT ( FROM t1" && error.kind == ErrorKind::Parser
+                ),
+                "Failed for dialect: {dialect:?}: {mismatches:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_first_difference() {
+        assert_eq!(
+            first_difference("a\n  b\nc", "a\n  d\nc"),
+            (2, "b".to_string(), "d".to_string())
+        );
+        assert_eq!(
+            first_difference("a", "a\nb"),
+            (2, String::new(), "b".to_string())
+        );
+    }
+}
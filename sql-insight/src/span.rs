@@ -0,0 +1,234 @@
+//! Source locations of statements.
+//!
+//! The AST does not carry source positions, so spans are computed by splitting the input into statements.
+
+use crate::splitter;
+use sqlparser::dialect::Dialect;
+
+/// [`Location`] represents a position in the source. Both line and column are one-based.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Location {
+    pub line: u64,
+    pub column: u64,
+}
+
+impl Location {
+    pub fn new(line: u64, column: u64) -> Self {
+        Self { line, column }
+    }
+
+    /// Location right after `text` when it starts at this location.
+    pub fn advance(self, text: &str) -> Self {
+        text.chars().fold(self, |location, c| {
+            if c == '\n' {
+                Location::new(location.line + 1, 1)
+            } else {
+                Location::new(location.line, location.column + 1)
+            }
+        })
+    }
+
+    /// Byte offset of this location in `text`, or `None` when it lies outside of it.
+    /// The location right after the last character maps to the length of `text`.
+    pub fn offset_in(self, text: &str) -> Option<usize> {
+        let mut location = Location::new(1, 1);
+        for (offset, c) in text.char_indices() {
+            if location == self {
+                return Some(offset);
+            }
+            location = location.advance(c.encode_utf8(&mut [0; 4]));
+        }
+        (location == self).then_some(text.len())
+    }
+}
+
+impl From<sqlparser::tokenizer::Location> for Location {
+    fn from(location: sqlparser::tokenizer::Location) -> Self {
+        Location::new(location.line, location.column)
+    }
+}
+
+/// [`Span`] represents a range in the source. `start` is inclusive and `end` is exclusive.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Span {
+    pub start: Location,
+    pub end: Location,
+}
+
+impl Span {
+    pub fn new(start: Location, end: Location) -> Self {
+        Self { start, end }
+    }
+}
+
+/// Compute the span of each statement in SQL, in input order.
+/// Statements are delimited as described in [`split_statements`](crate::split_statements()), so comments leading
+/// a statement, e.g. hints, are part of its span, whitespace around it is not, and empty statements are skipped.
+pub fn statement_spans(dialect: &dyn Dialect, sql: &str) -> Vec<Span> {
+    splitter::split_statements(dialect, sql)
+        .into_iter()
+        .map(|(_, span)| span)
+        .collect()
+}
+
+/// [`Spanned`] represents a per-statement result together with the span of the statement in the source.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Spanned<T> {
+    /// Span of the statement. `None` when spans are not available, see [`with_statement_spans`].
+    pub span: Option<Span>,
+    pub value: T,
+}
+
+/// Attach the span of each statement in SQL to the per-statement results of analyzing the SQL,
+/// e.g. those of [`extract_tables`](crate::extract_tables()), to map them back to positions in the source.
+///
+/// Spans are best effort: they are only attached when the input splits into as many statements as results.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+///
+/// let dialect = GenericDialect {};
+/// let sql = "SELECT a FROM t1;\n\nUPDATE t2\nSET b = 1";
+/// let results = sql_insight::extract_crud_tables(&dialect, sql).unwrap();
+/// let spanned = sql_insight::span::with_statement_spans(&dialect, sql, results);
+/// let span = spanned[1].span.unwrap();
+/// assert_eq!((span.start.line, span.end.line), (3, 4));
+/// assert_eq!(spanned[1].value.as_ref().unwrap().update_tables[0].to_string(), "t2");
+/// ```
+pub fn with_statement_spans<T>(
+    dialect: &dyn Dialect,
+    sql: &str,
+    results: Vec<T>,
+) -> Vec<Spanned<T>> {
+    let spans = Some(statement_spans(dialect, sql)).filter(|spans| spans.len() == results.len());
+    results
+        .into_iter()
+        .enumerate()
+        .map(|(index, value)| Spanned {
+            span: spans.as_ref().map(|spans| spans[index]),
+            value,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+    use sqlparser::dialect::GenericDialect;
+
+    #[test]
+    fn test_statement_spans() {
+        let sql = "SELECT a FROM t1; -- comment\nUPDATE t2\n  SET b = 1;;\n\n/* comment */ DELETE FROM t3";
+        for dialect in all_dialects() {
+            let spans = statement_spans(dialect.as_ref(), sql);
+            assert_eq!(
+                spans,
+                vec![
+                    Span::new(Location::new(1, 1), Location::new(1, 17)),
+                    Span::new(Location::new(2, 1), Location::new(3, 12)),
+                    Span::new(Location::new(5, 1), Location::new(5, 29)),
+                ],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_statement_spans_with_escaped_literals() {
+        let sql = "SELECT 'it''s' FROM t1;\nSELECT \"a\"\"b\" FROM t2";
+        for dialect in all_dialects() {
+            let spans = statement_spans(dialect.as_ref(), sql);
+            assert_eq!(
+                spans,
+                vec![
+                    Span::new(Location::new(1, 1), Location::new(1, 23)),
+                    Span::new(Location::new(2, 1), Location::new(2, 22)),
+                ],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_statement_spans_of_procedural_blocks() {
+        let sql = "CREATE PROCEDURE p() BEGIN SELECT 1; SELECT 2; END; SELECT 3";
+        let spans = statement_spans(&sqlparser::dialect::MySqlDialect {}, sql);
+        assert_eq!(
+            spans,
+            vec![
+                Span::new(Location::new(1, 1), Location::new(1, 51)),
+                Span::new(Location::new(1, 53), Location::new(1, 61)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_with_statement_spans() {
+        let sql = "SELECT a FROM t1;\n-- comment\nSELECT b\n  FROM t2";
+        for dialect in all_dialects() {
+            let results = crate::extract_tables(dialect.as_ref(), sql).unwrap();
+            let spanned = with_statement_spans(dialect.as_ref(), sql, results)
+                .into_iter()
+                .map(|spanned| (spanned.span, spanned.value.unwrap().to_string()))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                spanned,
+                vec![
+                    (
+                        Some(Span::new(Location::new(1, 1), Location::new(1, 17))),
+                        "t1".to_string()
+                    ),
+                    (
+                        Some(Span::new(Location::new(2, 1), Location::new(4, 10))),
+                        "t2".to_string()
+                    ),
+                ],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_with_statement_spans_when_counts_differ() {
+        let spanned = with_statement_spans(&GenericDialect {}, "SELECT 1; SELECT 2", vec!["x"]);
+        assert_eq!(
+            spanned,
+            vec![Spanned {
+                span: None,
+                value: "x"
+            }]
+        );
+    }
+
+    #[test]
+    fn test_offset_in() {
+        let text = "SELECT 'é'\nFROM t1";
+        assert_eq!(Location::new(1, 1).offset_in(text), Some(0));
+        assert_eq!(Location::new(1, 10).offset_in(text), Some(10));
+        assert_eq!(Location::new(2, 1).offset_in(text), Some(12));
+        assert_eq!(Location::new(2, 8).offset_in(text), Some(text.len()));
+        assert_eq!(Location::new(3, 1).offset_in(text), None);
+    }
+
+    #[test]
+    fn test_statement_spans_with_semicolon_in_literal() {
+        let sql = "SELECT 'a;b' FROM t1; SELECT c FROM t2";
+        for dialect in all_dialects() {
+            let spans = statement_spans(dialect.as_ref(), sql);
+            assert_eq!(
+                spans,
+                vec![
+                    Span::new(Location::new(1, 1), Location::new(1, 21)),
+                    Span::new(Location::new(1, 23), Location::new(1, 39)),
+                ],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,756 @@
+//! Lexical splitting of SQL into statements without parsing them.
+//!
+//! See [`split_statements`](crate::split_statements()) as the entry point for splitting SQL.
+
+use crate::span::{Location, Span};
+use sqlparser::dialect::{
+    BigQueryDialect, Dialect, DuckDbDialect, GenericDialect, MySqlDialect, PostgreSqlDialect,
+    RedshiftSqlDialect, SnowflakeDialect,
+};
+
+/// Convenience function to split SQL into the text of each statement with its span, without parsing it.
+///
+/// Statements are split on semicolons outside string literals, quoted identifiers and comments,
+/// so a statement that fails to parse can be isolated from the others. Whitespace around a statement is not part
+/// of it, and empty statements or statements of only comments are skipped. Comments leading a statement are part
+/// of it, except those on the line a preceding statement ends, which belong to that statement.
+/// For MySQL, `DELIMITER` commands of the MySQL client change the delimiter of the following statements.
+///
+/// Semicolons inside procedural blocks are not split on, so a routine stays in one piece:
+/// - `BEGIN ... END` blocks of `CREATE FUNCTION`, `CREATE PROCEDURE`, `CREATE TRIGGER` and `CREATE EVENT`,
+///   and of anonymous blocks starting with `BEGIN` or `DECLARE`. `BEGIN` followed by `TRANSACTION`, `WORK` etc.
+///   or by the delimiter starts a transaction instead.
+/// - Dollar-quoted bodies such as `$$ ... $$` and `$body$ ... $body$`, for PostgreSQL, Redshift, Snowflake,
+///   DuckDB and the generic dialect.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::MySqlDialect;
+/// use sql_insight::span::{Location, Span};
+///
+/// let dialect = MySqlDialect {};
+/// let sql = "SELECT ';' FROM t1;\nDELIMITER //\nSELEC a; b //\nDELIMITER ;\nSELECT 2;";
+/// let statements = sql_insight::split_statements(&dialect, sql);
+/// assert_eq!(statements[0], ("SELECT ';' FROM t1".to_string(), Span::new(Location::new(1, 1), Location::new(1, 19))));
+/// assert_eq!(statements[1].0, "SELEC a; b");
+/// assert_eq!(statements[2].0, "SELECT 2");
+/// ```
+pub fn split_statements(dialect: &dyn Dialect, input: &str) -> Vec<(String, Span)> {
+    let mut location = Location::new(1, 1);
+    let mut offset = 0;
+    StatementSplitter::new(dialect, input)
+        .map(|(start, text)| {
+            location = location.advance(&input[offset..start]);
+            let end = location.advance(text);
+            let statement = (text.to_string(), Span::new(location, end));
+            location = end;
+            offset = start + text.len();
+            statement
+        })
+        .collect()
+}
+
+/// Splits SQL on delimiters outside string literals, quoted identifiers and comments, yielding the text of each
+/// non-blank statement with surrounding whitespace trimmed, together with its byte offset in the input.
+///
+/// Statements are found one at a time as the iterator advances, so only the current statement is held
+/// apart from the input itself.
+pub(crate) struct StatementSplitter<'a> {
+    input: &'a str,
+    position: usize,
+    delimiter: String,
+    backslash_escapes: bool,
+    /// Whether `E'...'` strings escape with backslashes, as in PostgreSQL.
+    escape_strings: bool,
+    hash_comments: bool,
+    /// Whether `DELIMITER` commands of the MySQL client are recognized.
+    delimiter_commands: bool,
+    dollar_quotes: bool,
+    /// Whether the last statement yielded contains a procedural block or body.
+    procedural: bool,
+    /// Whether the last statement yielded is followed by a delimiter rather than the end of input.
+    terminated: bool,
+    /// Scan of a statement to resume rather than starting over, see [`with_scan`](Self::with_scan).
+    resume: Option<Scan>,
+    /// Scan of the last statement yielded if it is not terminated.
+    scan: Option<Scan>,
+}
+
+/// State of scanning a statement for its end, taken before the first token that may continue past the end of input,
+/// e.g. an unterminated string or `BEGIN` whose following word is not read yet.
+/// Scanning resumes from it when more input is appended, so a long statement read in parts is scanned once.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Scan {
+    start: usize,
+    position: usize,
+    depth: usize,
+    first_word: Option<(usize, usize)>,
+    routine: bool,
+    procedural: bool,
+    end_case: bool,
+}
+
+impl Scan {
+    fn new(start: usize) -> Self {
+        Self {
+            start,
+            position: start,
+            depth: 0,
+            first_word: None,
+            routine: false,
+            procedural: false,
+            end_case: false,
+        }
+    }
+
+    /// The scan of the same statement after `offset` bytes have been removed from the start of input.
+    pub(crate) fn shifted(self, offset: usize) -> Self {
+        Self {
+            start: self.start - offset,
+            position: self.position - offset,
+            first_word: self
+                .first_word
+                .map(|(start, end)| (start - offset, end - offset)),
+            ..self
+        }
+    }
+}
+
+impl<'a> StatementSplitter<'a> {
+    pub(crate) fn new(dialect: &dyn Dialect, input: &'a str) -> Self {
+        let mysql = dialect.is::<MySqlDialect>();
+        Self {
+            input,
+            position: 0,
+            delimiter: ";".to_string(),
+            backslash_escapes: mysql || dialect.is::<BigQueryDialect>(),
+            escape_strings: dialect.is::<PostgreSqlDialect>()
+                || dialect.is::<RedshiftSqlDialect>()
+                || dialect.is::<GenericDialect>(),
+            hash_comments: mysql
+                || dialect.is::<BigQueryDialect>()
+                || dialect.is::<SnowflakeDialect>(),
+            delimiter_commands: mysql,
+            dollar_quotes: dialect.is::<PostgreSqlDialect>()
+                || dialect.is::<RedshiftSqlDialect>()
+                || dialect.is::<SnowflakeDialect>()
+                || dialect.is::<DuckDbDialect>()
+                || dialect.is::<GenericDialect>(),
+            procedural: false,
+            terminated: false,
+            resume: None,
+            scan: None,
+        }
+    }
+
+    /// Resume `scan` of the statement starting at its start, e.g. one taken from [`unterminated_scan`](Self::unterminated_scan)
+    /// of a splitter over a prefix of this input.
+    pub(crate) fn with_scan(mut self, scan: Option<Scan>) -> Self {
+        self.resume = scan;
+        self
+    }
+
+    /// Scan of the last statement yielded, if it is not terminated, to resume when more input is appended.
+    pub(crate) fn unterminated_scan(&self) -> Option<&Scan> {
+        self.scan.as_ref()
+    }
+
+    /// Start with `delimiter` instead of `;`, e.g. one set by a `DELIMITER` command in preceding input.
+    pub(crate) fn with_delimiter(mut self, delimiter: String) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// The current delimiter.
+    pub(crate) fn delimiter(&self) -> &str {
+        &self.delimiter
+    }
+
+    /// Byte offset in the input where the next statement is searched from.
+    pub(crate) fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Whether the last statement yielded is followed by a delimiter. Only the last statement of input may not be,
+    /// which may then be incomplete if the input is only a part of SQL.
+    pub(crate) fn is_terminated(&self) -> bool {
+        self.terminated
+    }
+
+    /// Whether the last statement yielded contains a `BEGIN ... END` block, or is a routine or a `DO` command
+    /// with a dollar-quoted body.
+    pub(crate) fn is_procedural(&self) -> bool {
+        self.procedural
+    }
+
+    /// Byte offsets of the end of the statement scanned by `scan` and of the end of its delimiter,
+    /// which are both the end of input for the last statement, and whether the statement is procedural.
+    /// When the end of input is reached, the scan to resume is kept.
+    fn statement_end(&mut self, scan: Scan) -> (usize, usize, bool) {
+        let bytes = self.input.as_bytes();
+        let delimiter = self.delimiter.as_bytes();
+        // With a delimiter of its own, e.g. `DELIMITER //` of MySQL, blocks are left to the delimiter.
+        let blocks = self.delimiter == ";";
+        let Scan {
+            start,
+            position: mut i,
+            mut depth,
+            mut first_word,
+            mut routine,
+            mut procedural,
+            // Whether the current word is `CASE` of `END CASE`, which closes a `CASE` rather than opening one.
+            mut end_case,
+        } = scan;
+        let mut resume = None;
+        while i < bytes.len() {
+            if depth == 0 && bytes[i..].starts_with(delimiter) {
+                self.scan = None;
+                return (i, i + delimiter.len(), procedural);
+            }
+            let state = Scan {
+                start,
+                position: i,
+                depth,
+                first_word,
+                routine,
+                procedural,
+                end_case,
+            };
+            if !self.is_word_start(i) {
+                if routine && self.dollar_quote_tag(i).is_some() {
+                    procedural = true;
+                }
+                i = self.skip_token(i);
+                if i >= bytes.len() {
+                    resume.get_or_insert(state);
+                }
+                continue;
+            }
+            let word_end = self.word_end(i);
+            let word = &self.input[i..word_end];
+            let next = self.next_word(word_end);
+            if self.reaches_end(word_end) {
+                resume.get_or_insert(state);
+            }
+            let is = |keyword: &str| word.eq_ignore_ascii_case(keyword);
+            let is_first = first_word.is_none();
+            let (first_start, first_end) = *first_word.get_or_insert((i, word_end));
+            let first = &self.input[first_start..first_end];
+            if (is_first && (is("DECLARE") || is("DO")))
+                || (first.eq_ignore_ascii_case("CREATE")
+                    && ["FUNCTION", "PROCEDURE", "TRIGGER", "EVENT"]
+                        .iter()
+                        .any(|keyword| is(keyword)))
+            {
+                routine = true;
+            }
+            let block_start = depth > 0 || routine || is_first;
+            if blocks && is("BEGIN") && block_start && opens_block(next) {
+                depth += 1;
+                procedural = true;
+            } else if depth > 0 && is("CASE") && !end_case {
+                depth += 1;
+            } else if depth > 0 && is("END") && !ends_statement(next) {
+                depth -= 1;
+            }
+            end_case = is("END") && next.eq_ignore_ascii_case("CASE");
+            i = word_end;
+        }
+        self.scan = Some(resume.unwrap_or(Scan::new(start)));
+        (bytes.len(), bytes.len(), procedural)
+    }
+
+    fn is_word_start(&self, i: usize) -> bool {
+        let bytes = self.input.as_bytes();
+        (bytes[i].is_ascii_alphabetic() || bytes[i] == b'_')
+            && (i == 0 || !is_word_byte(bytes[i - 1]))
+    }
+
+    /// Byte offset past the word starting at `i`, which ends before the delimiter, e.g. `$$` in `END$$`.
+    fn word_end(&self, i: usize) -> usize {
+        let bytes = self.input.as_bytes();
+        let mut end = i;
+        while end < bytes.len()
+            && is_word_byte(bytes[end])
+            && !bytes[end..].starts_with(self.delimiter.as_bytes())
+        {
+            end += 1;
+        }
+        end
+    }
+
+    /// Whether the word ending at `word_end`, or the word following it, may continue past the end of input.
+    fn reaches_end(&self, word_end: usize) -> bool {
+        let bytes = self.input.as_bytes();
+        let mut start = word_end;
+        while start < bytes.len() && bytes[start].is_ascii_whitespace() {
+            start += 1;
+        }
+        start == bytes.len() || (self.is_word_start(start) && self.word_end(start) == bytes.len())
+    }
+
+    /// The word following whitespace after `i`, which is empty if something else follows.
+    fn next_word(&self, i: usize) -> &str {
+        let bytes = self.input.as_bytes();
+        let mut start = i;
+        while start < bytes.len() && bytes[start].is_ascii_whitespace() {
+            start += 1;
+        }
+        if start < bytes.len() && self.is_word_start(start) {
+            &self.input[start..self.word_end(start)]
+        } else {
+            ""
+        }
+    }
+
+    /// The tag of the dollar quote starting at `i`, e.g. `$$` or `$body$`.
+    fn dollar_quote_tag(&self, i: usize) -> Option<&str> {
+        let bytes = self.input.as_bytes();
+        // `$` within a word is part of an identifier, and `$1` is a placeholder.
+        if !self.dollar_quotes
+            || bytes[i] != b'$'
+            || (i > 0 && is_word_byte(bytes[i - 1]))
+            || bytes.get(i + 1).is_some_and(u8::is_ascii_digit)
+        {
+            return None;
+        }
+        let mut end = i + 1;
+        while end < bytes.len() && (bytes[end].is_ascii_alphanumeric() || bytes[end] == b'_') {
+            end += 1;
+        }
+        (bytes.get(end) == Some(&b'$')).then(|| &self.input[i..=end])
+    }
+
+    /// Byte offset past the quoted string or comment starting at `i`, or past the byte at `i` otherwise.
+    fn skip_token(&self, mut i: usize) -> usize {
+        let bytes = self.input.as_bytes();
+        match bytes[i] {
+            quote @ (b'\'' | b'"' | b'`') => {
+                let backslash_escapes = quote == b'\''
+                    && (self.backslash_escapes
+                        || (self.escape_strings && self.is_escape_string(i)));
+                i += 1;
+                while i < bytes.len() {
+                    if backslash_escapes && bytes[i] == b'\\' {
+                        i += 2;
+                        continue;
+                    }
+                    if bytes[i] == quote {
+                        // A doubled quote is an escaped quote.
+                        if bytes.get(i + 1) == Some(&quote) {
+                            i += 2;
+                            continue;
+                        }
+                        break;
+                    }
+                    i += 1;
+                }
+                (i + 1).min(bytes.len())
+            }
+            b'$' if self.dollar_quote_tag(i).is_some() => {
+                let tag = self.dollar_quote_tag(i).unwrap_or_default();
+                let body = i + tag.len();
+                self.input[body..]
+                    .find(tag)
+                    .map_or(bytes.len(), |end| body + end + tag.len())
+            }
+            b'-' if bytes.get(i + 1) == Some(&b'-') => self.line_end(i),
+            b'#' if self.hash_comments => self.line_end(i),
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i += 2;
+                while i < bytes.len() && !(bytes[i] == b'*' && bytes.get(i + 1) == Some(&b'/')) {
+                    i += 1;
+                }
+                (i + 2).min(bytes.len())
+            }
+            _ => i + 1,
+        }
+    }
+
+    /// Whether the quote at `i` is prefixed by a standalone `E`, e.g. `E'it\'s'`.
+    fn is_escape_string(&self, i: usize) -> bool {
+        let bytes = self.input.as_bytes();
+        i > 0 && matches!(bytes[i - 1], b'E' | b'e') && (i == 1 || !is_word_byte(bytes[i - 2]))
+    }
+
+    fn line_end(&self, i: usize) -> usize {
+        self.input[i..]
+            .find('\n')
+            .map_or(self.input.len(), |end| i + end)
+    }
+
+    /// Byte offset past comments following the delimiter ending at `i` on the same line, when nothing else follows
+    /// them on their line, or `i` otherwise. Such comments belong to the statement the delimiter ends,
+    /// e.g. `-- sql-insight: allow(...)`, rather than to the next statement.
+    fn trailing_comments_end(&self, i: usize) -> usize {
+        let bytes = self.input.as_bytes();
+        let mut end = i;
+        let mut j = i;
+        loop {
+            while j < bytes.len() && matches!(bytes[j], b' ' | b'\t' | b'\r') {
+                j += 1;
+            }
+            if j == bytes.len() || bytes[j] == b'\n' {
+                return end;
+            }
+            if !self.is_comment_start(bytes, j) {
+                return i;
+            }
+            j = self.skip_token(j);
+            end = j;
+        }
+    }
+
+    fn is_comment_start(&self, bytes: &[u8], i: usize) -> bool {
+        matches!(
+            (bytes[i], bytes.get(i + 1)),
+            (b'-', Some(b'-')) | (b'/', Some(b'*'))
+        ) || (bytes[i] == b'#' && self.hash_comments)
+    }
+
+    /// Whether `text` consists only of whitespace and comments.
+    fn is_blank(&self, text: &str) -> bool {
+        let bytes = text.as_bytes();
+        let splitter = StatementSplitter {
+            input: text,
+            position: 0,
+            delimiter: self.delimiter.clone(),
+            resume: None,
+            scan: None,
+            ..*self
+        };
+        let mut i = 0;
+        while i < bytes.len() {
+            if self.is_comment_start(bytes, i) {
+                i = splitter.skip_token(i);
+            } else if bytes[i].is_ascii_whitespace() {
+                i += 1;
+            } else {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// If a `DELIMITER` command starts at `start`, switch to its delimiter and return the offset past its line.
+    fn delimiter_command(&mut self, start: usize) -> Option<usize> {
+        let rest = &self.input[start..];
+        let command = rest.trim_start();
+        let keyword = command.get(..9)?;
+        if !keyword.eq_ignore_ascii_case("DELIMITER") || !command[9..].starts_with([' ', '\t']) {
+            return None;
+        }
+        let line_end = command.find('\n').unwrap_or(command.len());
+        let delimiter = command[9..line_end].trim();
+        if delimiter.is_empty() {
+            return None;
+        }
+        self.delimiter = delimiter.to_string();
+        Some(start + rest.len() - command.len() + line_end)
+    }
+}
+
+impl<'a> Iterator for StatementSplitter<'a> {
+    type Item = (usize, &'a str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.position < self.input.len() {
+            let start = self.position;
+            let scan = match self.resume.take() {
+                Some(scan) if scan.start == start => scan,
+                resume => {
+                    self.resume = resume;
+                    if self.delimiter_commands {
+                        if let Some(end) = self.delimiter_command(start) {
+                            self.position = end;
+                            continue;
+                        }
+                    }
+                    Scan::new(start)
+                }
+            };
+            let (end, next, procedural) = self.statement_end(scan);
+            self.position = self.trailing_comments_end(next);
+            self.procedural = procedural;
+            self.terminated = next > end;
+            let text = &self.input[start..end];
+            let trimmed = text.trim_start();
+            let offset = start + text.len() - trimmed.len();
+            let trimmed = trimmed.trim_end();
+            if !self.is_blank(trimmed) {
+                return Some((offset, trimmed));
+            }
+        }
+        None
+    }
+}
+
+fn is_word_byte(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_' || byte == b'$' || !byte.is_ascii()
+}
+
+/// Whether `BEGIN` followed by `next` starts a block rather than a transaction.
+fn opens_block(next: &str) -> bool {
+    !next.is_empty()
+        && ![
+            "TRANSACTION",
+            "TRAN",
+            "WORK",
+            "ISOLATION",
+            "READ",
+            "DEFERRED",
+            "IMMEDIATE",
+            "EXCLUSIVE",
+            "DISTRIBUTED",
+        ]
+        .iter()
+        .any(|keyword| next.eq_ignore_ascii_case(keyword))
+}
+
+/// Whether `END` followed by `next` ends a statement rather than a block or `CASE`, e.g. `END IF`.
+fn ends_statement(next: &str) -> bool {
+    ["IF", "LOOP", "WHILE", "REPEAT", "FOR"]
+        .iter()
+        .any(|keyword| next.eq_ignore_ascii_case(keyword))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+
+    #[test]
+    fn test_split() {
+        let sql = "SELECT ';' FROM t1; ;\n SELECT \"a;b\" FROM t2 -- c;d\n WHERE e = 'it''s;' /* f;g */ ; SELECT 1";
+        for dialect in all_dialects() {
+            let result = StatementSplitter::new(dialect.as_ref(), sql).collect::<Vec<_>>();
+            assert_eq!(
+                result,
+                vec![
+                    (0, "SELECT ';' FROM t1"),
+                    (
+                        23,
+                        "SELECT \"a;b\" FROM t2 -- c;d\n WHERE e = 'it''s;' /* f;g */"
+                    ),
+                    (83, "SELECT 1"),
+                ],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_backslash_escapes() {
+        let sql = "SELECT 'a\\';b'; SELECT 2";
+        let result = StatementSplitter::new(&MySqlDialect {}, sql).collect::<Vec<_>>();
+        assert_eq!(result, vec![(0, "SELECT 'a\\';b'"), (16, "SELECT 2")]);
+        let dialect = sqlparser::dialect::PostgreSqlDialect {};
+        let result = StatementSplitter::new(&dialect, "SELECT 'a\\'; SELECT 2").collect::<Vec<_>>();
+        assert_eq!(result, vec![(0, "SELECT 'a\\'"), (13, "SELECT 2")]);
+    }
+
+    #[test]
+    fn test_escape_strings() {
+        let sql = "SELECT E'a\\'b;', e'\\\\'; SELECT 2; SELECT type'a\\'; SELECT 3";
+        for dialect in [
+            Box::new(sqlparser::dialect::PostgreSqlDialect {}) as Box<dyn Dialect>,
+            Box::new(RedshiftSqlDialect {}),
+            Box::new(GenericDialect {}),
+        ] {
+            let result = StatementSplitter::new(dialect.as_ref(), sql).collect::<Vec<_>>();
+            assert_eq!(
+                result,
+                vec![
+                    (0, "SELECT E'a\\'b;', e'\\\\'"),
+                    (24, "SELECT 2"),
+                    (34, "SELECT type'a\\'"),
+                    (51, "SELECT 3"),
+                ],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_comment_only_statements() {
+        let sql = "SELECT 1; -- it's the end\n/* ; */";
+        for dialect in all_dialects() {
+            let result = StatementSplitter::new(dialect.as_ref(), sql).collect::<Vec<_>>();
+            assert_eq!(
+                result,
+                vec![(0, "SELECT 1")],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+        let result = StatementSplitter::new(&MySqlDialect {}, "SELECT 1 # it's; \n; # x")
+            .collect::<Vec<_>>();
+        assert_eq!(result, vec![(0, "SELECT 1 # it's;")]);
+    }
+
+    #[test]
+    fn test_resume_scan() {
+        let sql = "CREATE PROCEDURE p() BEGIN\n  SELECT 'a;\nb';\n  CASE WHEN x THEN SELECT 1; END CASE;\nEND; SELECT 2";
+        for dialect in all_dialects() {
+            let mut scan = None;
+            // The scan of the incomplete procedure resumes as lines are appended, as streamed input is.
+            for (end, _) in sql.match_indices('\n') {
+                let mut splitter =
+                    StatementSplitter::new(dialect.as_ref(), &sql[..=end]).with_scan(scan.take());
+                assert_eq!(
+                    splitter.next().map(|(offset, _)| offset),
+                    Some(0),
+                    "Failed for dialect: {dialect:?}"
+                );
+                assert!(!splitter.is_terminated(), "Failed for dialect: {dialect:?}");
+                scan = splitter.unterminated_scan().cloned();
+                assert!(
+                    scan.as_ref().is_some_and(|scan| scan.position > 0),
+                    "Failed for dialect: {dialect:?}"
+                );
+            }
+            assert_eq!(
+                StatementSplitter::new(dialect.as_ref(), sql)
+                    .with_scan(scan)
+                    .collect::<Vec<_>>(),
+                vec![
+                    (
+                        0,
+                        "CREATE PROCEDURE p() BEGIN\n  SELECT 'a;\nb';\n  CASE WHEN x THEN SELECT 1; END CASE;\nEND"
+                    ),
+                    (88, "SELECT 2"),
+                ],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_trailing_comments() {
+        let sql = "SELECT 1; -- first\n/* second */ SELECT 2; /* x */ -- y\nSELECT 3; /* third */ SELECT 4";
+        for dialect in all_dialects() {
+            let result = StatementSplitter::new(dialect.as_ref(), sql).collect::<Vec<_>>();
+            assert_eq!(
+                result,
+                vec![
+                    (0, "SELECT 1"),
+                    (19, "/* second */ SELECT 2"),
+                    (55, "SELECT 3"),
+                    (65, "/* third */ SELECT 4"),
+                ],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_delimiter_command() {
+        let sql = "DELIMITER $$\nCREATE PROCEDURE p() BEGIN SELECT 1; SELECT ';'; END$$\ndelimiter ;\nSELECT 2;";
+        let result = StatementSplitter::new(&MySqlDialect {}, sql).collect::<Vec<_>>();
+        assert_eq!(
+            result,
+            vec![
+                (13, "CREATE PROCEDURE p() BEGIN SELECT 1; SELECT ';'; END"),
+                (80, "SELECT 2"),
+            ]
+        );
+        // Other dialects don't have the command.
+        let dialect = sqlparser::dialect::SQLiteDialect {};
+        let result = StatementSplitter::new(&dialect, sql).collect::<Vec<_>>();
+        assert_eq!(result.len(), 4);
+    }
+
+    fn pieces<'a>(dialect: &dyn Dialect, sql: &'a str) -> Vec<(&'a str, bool)> {
+        let mut splitter = StatementSplitter::new(dialect, sql);
+        let mut pieces = vec![];
+        while let Some((_, text)) = splitter.next() {
+            pieces.push((text, splitter.is_procedural()));
+        }
+        pieces
+    }
+
+    #[test]
+    fn test_procedural_blocks() {
+        let routine = "CREATE PROCEDURE p() BEGIN SELECT 1; IF a THEN SELECT CASE WHEN b THEN 1 END; END IF; END";
+        let anonymous = "BEGIN\n  SELECT 1;\n  SELECT 2;\nEND";
+        let sql = format!(
+            "{routine}; BEGIN; SELECT begin FROM t1; {anonymous}; BEGIN TRANSACTION; COMMIT"
+        );
+        for dialect in all_dialects() {
+            assert_eq!(
+                pieces(dialect.as_ref(), &sql),
+                vec![
+                    (routine, true),
+                    ("BEGIN", false),
+                    ("SELECT begin FROM t1", false),
+                    (anonymous, true),
+                    ("BEGIN TRANSACTION", false),
+                    ("COMMIT", false),
+                ],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_end_of_compound_statements() {
+        let routine = "CREATE PROCEDURE p(x INT) BEGIN \
+            CASE x WHEN 1 THEN SELECT 1; ELSE SELECT 2; END CASE; \
+            IF x > 1 THEN SELECT 3; END IF; \
+            l: LOOP LEAVE l; END LOOP; \
+            SELECT CASE WHEN x THEN 1 END; \
+            END";
+        let sql = format!("{routine}; SELECT 2; SELECT 3");
+        assert_eq!(
+            pieces(&MySqlDialect {}, &sql),
+            vec![(routine, true), ("SELECT 2", false), ("SELECT 3", false)]
+        );
+    }
+
+    #[test]
+    fn test_dollar_quotes() {
+        let function = "CREATE FUNCTION f() RETURNS INT AS $body$ BEGIN RETURN 1; END; $body$ LANGUAGE plpgsql";
+        let sql = format!("{function}; SELECT $$a;b$$, $1; SELECT a$b$c");
+        for dialect in [
+            Box::new(sqlparser::dialect::PostgreSqlDialect {}) as Box<dyn Dialect>,
+            Box::new(sqlparser::dialect::GenericDialect {}),
+        ] {
+            assert_eq!(
+                pieces(dialect.as_ref(), &sql),
+                vec![
+                    (function, true),
+                    ("SELECT $$a;b$$, $1", false),
+                    ("SELECT a$b$c", false),
+                ],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+        let result = pieces(&MySqlDialect {}, "SELECT $$a;b$$");
+        assert_eq!(result, vec![("SELECT $$a", false), ("b$$", false)]);
+    }
+
+    #[test]
+    fn test_split_statements() {
+        let sql = "SELECT a\nFROM t1;\n  SELEC é; SELECT 'x'";
+        for dialect in all_dialects() {
+            assert_eq!(
+                split_statements(dialect.as_ref(), sql),
+                vec![
+                    (
+                        "SELECT a\nFROM t1".to_string(),
+                        Span::new(Location::new(1, 1), Location::new(2, 8))
+                    ),
+                    (
+                        "SELEC é".to_string(),
+                        Span::new(Location::new(3, 3), Location::new(3, 10))
+                    ),
+                    (
+                        "SELECT 'x'".to_string(),
+                        Span::new(Location::new(3, 12), Location::new(3, 22))
+                    ),
+                ],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+}
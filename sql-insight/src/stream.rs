@@ -0,0 +1,245 @@
+//! Streaming processing of SQL read incrementally, such as multi-gigabyte dump files.
+//!
+//! See [`process_stream`](crate::process_stream()) as the entry point for processing SQL from a reader.
+
+use std::io::BufRead;
+
+use crate::cancellation::CancellationToken;
+use crate::error::Error;
+use crate::instrument;
+use crate::splitter::{Scan, StatementSplitter};
+use sqlparser::ast::Statement;
+use sqlparser::dialect::Dialect;
+
+/// Convenience function to parse SQL read from `reader` statement by statement, calling `f` with each statement,
+/// or with the error of a statement failing to parse.
+///
+/// Input is read line by line and split into statements as described in [`split_statements`](crate::split_statements()),
+/// and each statement is parsed as soon as its delimiter has been read. Only the statement being read is buffered,
+/// so memory usage depends on the size of the largest statement rather than the size of the input.
+/// A statement failing to parse doesn't stop processing, while failing to read input does and is returned as an error.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+/// use sql_insight::TableExtractor;
+///
+/// let dialect = GenericDialect {};
+/// let reader = std::io::Cursor::new("SELECT a\nFROM t1;\nSELEC b;\nINSERT INTO t2 (a)\nVALUES (1)");
+/// let mut tables = vec![];
+/// sql_insight::process_stream(&dialect, reader, |statement| {
+///     tables.push(statement.and_then(|statement| Ok(TableExtractor::extract_from_statement(&statement)?.to_string())));
+/// })
+/// .unwrap();
+/// assert_eq!(tables[0], Ok("t1".to_string()));
+/// assert!(tables[1].is_err());
+/// assert_eq!(tables[2], Ok("t2".to_string()));
+/// ```
+pub fn process_stream<R, F>(dialect: &dyn Dialect, reader: R, f: F) -> Result<(), Error>
+where
+    R: BufRead,
+    F: FnMut(Result<Statement, Error>),
+{
+    process(dialect, reader, None, f).map(|_| ())
+}
+
+/// Like [`process_stream`], but stops reading input when `token` is cancelled,
+/// returning whether it was cancelled before reaching the end of the input.
+/// Statements passed to `f` before cancellation are the partial results.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+/// use sql_insight::CancellationToken;
+///
+/// let dialect = GenericDialect {};
+/// let reader = std::io::Cursor::new("SELECT a FROM t1;\nSELECT b FROM t2;\nSELECT c FROM t3;");
+/// let token = CancellationToken::new();
+/// let mut statements = vec![];
+/// let cancelled = sql_insight::process_stream_cancellable(&dialect, reader, &token, |statement| {
+///     statements.push(statement.unwrap().to_string());
+///     token.cancel();
+/// })
+/// .unwrap();
+/// assert!(cancelled);
+/// assert_eq!(statements, ["SELECT a FROM t1"]);
+/// ```
+pub fn process_stream_cancellable<R, F>(
+    dialect: &dyn Dialect,
+    reader: R,
+    token: &CancellationToken,
+    f: F,
+) -> Result<bool, Error>
+where
+    R: BufRead,
+    F: FnMut(Result<Statement, Error>),
+{
+    process(dialect, reader, Some(token), f)
+}
+
+/// Process statements read from `reader`, returning whether `token` cancelled processing.
+fn process<R, F>(
+    dialect: &dyn Dialect,
+    mut reader: R,
+    token: Option<&CancellationToken>,
+    mut f: F,
+) -> Result<bool, Error>
+where
+    R: BufRead,
+    F: FnMut(Result<Statement, Error>),
+{
+    let mut buf = String::new();
+    // Delimiter in effect at the start of `buf`, which `DELIMITER` commands of MySQL change.
+    let mut delimiter = ";".to_string();
+    // Delimiter in effect at the end of `buf`. Statements can only be completed by a line containing it.
+    let mut pending_delimiter = delimiter.clone();
+    // Scan of the incomplete statement in `buf`, resumed rather than started over when more lines are read.
+    let mut scan: Option<Scan> = None;
+    loop {
+        let len = buf.len();
+        let read = reader
+            .read_line(&mut buf)
+            .map_err(|e| Error::IOError(e.to_string()))?;
+        let eof = read == 0;
+        let line = &buf[len..];
+        if !eof && !line.contains(pending_delimiter.as_str()) && !is_delimiter_command(line) {
+            continue;
+        }
+        let mut splitter = StatementSplitter::new(dialect, &buf)
+            .with_delimiter(delimiter.clone())
+            .with_scan(scan.take());
+        let mut consumed = 0;
+        while let Some((_, sql)) = splitter.next() {
+            // The last statement is incomplete until its delimiter is read.
+            if !eof && !splitter.is_terminated() {
+                scan = splitter.unterminated_scan().cloned();
+                break;
+            }
+            if token.is_some_and(CancellationToken::is_cancelled) {
+                return Ok(true);
+            }
+            match instrument::parse_sql(dialect, sql) {
+                Ok(statements) => statements.into_iter().for_each(|s| f(Ok(s))),
+                Err(e) => f(Err(e)),
+            }
+            consumed = splitter.position();
+            delimiter = splitter.delimiter().to_string();
+        }
+        pending_delimiter = splitter.delimiter().to_string();
+        if eof {
+            return Ok(false);
+        }
+        buf.drain(..consumed);
+        scan = scan.map(|scan| scan.shifted(consumed));
+    }
+}
+
+fn is_delimiter_command(line: &str) -> bool {
+    line.trim_start()
+        .get(..9)
+        .is_some_and(|word| word.eq_ignore_ascii_case("DELIMITER"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::all_dialects;
+    use sqlparser::dialect::MySqlDialect;
+    use std::io::{BufReader, Cursor};
+
+    fn process(dialect: &dyn Dialect, sql: &str) -> Vec<Result<String, ()>> {
+        let mut results = vec![];
+        // A small capacity makes lines span several reads of the underlying reader.
+        let reader = BufReader::with_capacity(4, Cursor::new(sql.to_string()));
+        process_stream(dialect, reader, |statement| {
+            results.push(statement.map(|s| s.to_string()).map_err(|_| ()))
+        })
+        .unwrap();
+        results
+    }
+
+    #[test]
+    fn test_process_stream() {
+        let sql = "SELECT a\nFROM t1; SELECT 'x;\ny' FROM t2;\n-- comment;\nSELEC c;\n\nINSERT INTO t3 (a)\nVALUES (1);\nDELETE FROM t4";
+        for dialect in all_dialects() {
+            assert_eq!(
+                process(dialect.as_ref(), sql),
+                vec![
+                    Ok("SELECT a FROM t1".into()),
+                    Ok("SELECT 'x;\ny' FROM t2".into()),
+                    Err(()),
+                    Ok("INSERT INTO t3 (a) VALUES (1)".into()),
+                    Ok("DELETE FROM t4".into()),
+                ],
+                "Failed for dialect: {dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_process_stream_with_delimiter_command() {
+        let sql = "SELECT 1;\nDELIMITER //\nSELECT 2; SELECT\n3 //\nDELIMITER ;\nSELECT 4;\n";
+        assert_eq!(
+            process(&MySqlDialect {}, sql),
+            vec![
+                Ok("SELECT 1".into()),
+                Ok("SELECT 2".into()),
+                Ok("SELECT 3".into()),
+                Ok("SELECT 4".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_process_stream_with_procedural_block() {
+        let sql = "SELECT 1;\nCREATE PROCEDURE p()\nBEGIN\n  SELECT 'a;\nb';\n  SELECT 2;\nEND;\nSELECT 3;\n";
+        let results = process(&MySqlDialect {}, sql);
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0], Ok("SELECT 1".into()));
+        assert_eq!(results[2], Ok("SELECT 3".into()));
+    }
+
+    #[test]
+    fn test_process_stream_read_error() {
+        struct FailingReader;
+        impl std::io::Read for FailingReader {
+            fn read(&mut self, _buf: &mut [u8]) -> std::io::Result<usize> {
+                Err(std::io::Error::other("broken"))
+            }
+        }
+        let result = process_stream(&MySqlDialect {}, BufReader::new(FailingReader), |_| {});
+        assert_eq!(result, Err(Error::IOError("broken".into())));
+    }
+
+    #[test]
+    fn test_process_stream_cancellable() {
+        let sql = "SELECT a FROM t1;\nSELECT b FROM t2; SELECT c FROM t3;\nSELECT d FROM t4";
+        for dialect in all_dialects() {
+            let token = CancellationToken::new();
+            let mut results = vec![];
+            let reader = BufReader::with_capacity(4, Cursor::new(sql.to_string()));
+            let cancelled =
+                process_stream_cancellable(dialect.as_ref(), reader, &token, |statement| {
+                    results.push(statement.unwrap().to_string());
+                    if results.len() == 2 {
+                        token.cancel();
+                    }
+                })
+                .unwrap();
+            assert!(cancelled, "Failed for dialect: {dialect:?}");
+            assert_eq!(
+                results,
+                vec!["SELECT a FROM t1", "SELECT b FROM t2"],
+                "Failed for dialect: {dialect:?}"
+            );
+
+            let token = CancellationToken::new();
+            let reader = BufReader::new(Cursor::new(sql.to_string()));
+            let cancelled =
+                process_stream_cancellable(dialect.as_ref(), reader, &token, |_| {}).unwrap();
+            assert!(!cancelled, "Failed for dialect: {dialect:?}");
+        }
+    }
+}
@@ -2,6 +2,13 @@ use sqlparser::dialect;
 use sqlparser::dialect::Dialect;
 
 pub fn all_dialects() -> Vec<Box<dyn Dialect>> {
+    all_sync_dialects()
+        .into_iter()
+        .map(|dialect| dialect as Box<dyn Dialect>)
+        .collect()
+}
+
+pub fn all_sync_dialects() -> Vec<Box<dyn Dialect + Send + Sync>> {
     vec![
         Box::new(dialect::GenericDialect {}),
         Box::new(dialect::MySqlDialect {}),
@@ -0,0 +1,334 @@
+//! Visitor sets that run several visitors in a single AST traversal.
+//!
+//! See [`VisitorSet`] and [`VisitorMutSet`] for details.
+
+use std::ops::ControlFlow;
+
+use sqlparser::ast::{Expr, ObjectName, Query, Statement, TableFactor, Visitor, VisitorMut};
+
+macro_rules! dispatch {
+    ($self:ident, $method:ident, $node:ident) => {{
+        for (visitor, break_value) in $self.visitors.iter_mut() {
+            if break_value.is_some() {
+                continue;
+            }
+            if let ControlFlow::Break(value) = visitor.$method($node) {
+                *break_value = Some(value);
+            }
+        }
+        $self.control_flow()
+    }};
+}
+
+/// [`VisitorSet`] runs several [`Visitor`]s in a single traversal of the AST.
+///
+/// Each visitor is called in the order it was added. When a visitor breaks, its break value is
+/// recorded and it is no longer called for the rest of the traversal, while the other visitors keep going.
+/// The traversal itself stops only once every visitor has broken.
+///
+/// ## Example
+///
+/// ```rust
+/// use sql_insight::sqlparser::ast::Visit;
+/// use sql_insight::sqlparser::dialect::GenericDialect;
+/// use sql_insight::sqlparser::parser::Parser;
+/// use sql_insight::{TableExtractor, VisitorSet};
+///
+/// let statements = Parser::parse_sql(&GenericDialect {}, "SELECT a FROM t1 JOIN t2 ON t1.id = t2.id").unwrap();
+/// let mut first = TableExtractor::default();
+/// let mut second = TableExtractor::default();
+/// let mut visitor_set = VisitorSet::new().with_visitor(&mut first).with_visitor(&mut second);
+/// let _ = statements[0].visit(&mut visitor_set);
+/// assert!(visitor_set.into_breaks().iter().all(|b| b.is_none()));
+/// assert_eq!(first.into_tables().to_string(), "t1, t2");
+/// ```
+pub struct VisitorSet<'a, B> {
+    visitors: Vec<(&'a mut dyn Visitor<Break = B>, Option<B>)>,
+}
+
+impl<B> Default for VisitorSet<'_, B> {
+    fn default() -> Self {
+        Self {
+            visitors: Vec::new(),
+        }
+    }
+}
+
+impl<'a, B> VisitorSet<'a, B> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_visitor(mut self, visitor: &'a mut dyn Visitor<Break = B>) -> Self {
+        self.add_visitor(visitor);
+        self
+    }
+
+    pub fn add_visitor(&mut self, visitor: &'a mut dyn Visitor<Break = B>) {
+        self.visitors.push((visitor, None));
+    }
+
+    /// Break values recorded for each visitor, in the order the visitors were added.
+    /// `None` means the visitor ran through the whole traversal without breaking.
+    pub fn into_breaks(self) -> Vec<Option<B>> {
+        self.visitors.into_iter().map(|(_, b)| b).collect()
+    }
+
+    fn control_flow(&self) -> ControlFlow<()> {
+        if !self.visitors.is_empty() && self.visitors.iter().all(|(_, b)| b.is_some()) {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    }
+}
+
+impl<B> Visitor for VisitorSet<'_, B> {
+    type Break = ();
+
+    fn pre_visit_query(&mut self, query: &Query) -> ControlFlow<Self::Break> {
+        dispatch!(self, pre_visit_query, query)
+    }
+
+    fn post_visit_query(&mut self, query: &Query) -> ControlFlow<Self::Break> {
+        dispatch!(self, post_visit_query, query)
+    }
+
+    fn pre_visit_relation(&mut self, relation: &ObjectName) -> ControlFlow<Self::Break> {
+        dispatch!(self, pre_visit_relation, relation)
+    }
+
+    fn post_visit_relation(&mut self, relation: &ObjectName) -> ControlFlow<Self::Break> {
+        dispatch!(self, post_visit_relation, relation)
+    }
+
+    fn pre_visit_table_factor(&mut self, table_factor: &TableFactor) -> ControlFlow<Self::Break> {
+        dispatch!(self, pre_visit_table_factor, table_factor)
+    }
+
+    fn post_visit_table_factor(&mut self, table_factor: &TableFactor) -> ControlFlow<Self::Break> {
+        dispatch!(self, post_visit_table_factor, table_factor)
+    }
+
+    fn pre_visit_expr(&mut self, expr: &Expr) -> ControlFlow<Self::Break> {
+        dispatch!(self, pre_visit_expr, expr)
+    }
+
+    fn post_visit_expr(&mut self, expr: &Expr) -> ControlFlow<Self::Break> {
+        dispatch!(self, post_visit_expr, expr)
+    }
+
+    fn pre_visit_statement(&mut self, statement: &Statement) -> ControlFlow<Self::Break> {
+        dispatch!(self, pre_visit_statement, statement)
+    }
+
+    fn post_visit_statement(&mut self, statement: &Statement) -> ControlFlow<Self::Break> {
+        dispatch!(self, post_visit_statement, statement)
+    }
+}
+
+/// [`VisitorMutSet`] runs several [`VisitorMut`]s in a single traversal of the AST.
+///
+/// Visitors are called in the order they were added, so each visitor sees the changes made by the previous ones.
+/// Break handling is the same as [`VisitorSet`].
+pub struct VisitorMutSet<'a, B> {
+    visitors: Vec<(&'a mut dyn VisitorMut<Break = B>, Option<B>)>,
+}
+
+impl<B> Default for VisitorMutSet<'_, B> {
+    fn default() -> Self {
+        Self {
+            visitors: Vec::new(),
+        }
+    }
+}
+
+impl<'a, B> VisitorMutSet<'a, B> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_visitor(mut self, visitor: &'a mut dyn VisitorMut<Break = B>) -> Self {
+        self.add_visitor(visitor);
+        self
+    }
+
+    pub fn add_visitor(&mut self, visitor: &'a mut dyn VisitorMut<Break = B>) {
+        self.visitors.push((visitor, None));
+    }
+
+    /// Break values recorded for each visitor, in the order the visitors were added.
+    /// `None` means the visitor ran through the whole traversal without breaking.
+    pub fn into_breaks(self) -> Vec<Option<B>> {
+        self.visitors.into_iter().map(|(_, b)| b).collect()
+    }
+
+    fn control_flow(&self) -> ControlFlow<()> {
+        if !self.visitors.is_empty() && self.visitors.iter().all(|(_, b)| b.is_some()) {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    }
+}
+
+impl<B> VisitorMut for VisitorMutSet<'_, B> {
+    type Break = ();
+
+    fn pre_visit_query(&mut self, query: &mut Query) -> ControlFlow<Self::Break> {
+        dispatch!(self, pre_visit_query, query)
+    }
+
+    fn post_visit_query(&mut self, query: &mut Query) -> ControlFlow<Self::Break> {
+        dispatch!(self, post_visit_query, query)
+    }
+
+    fn pre_visit_relation(&mut self, relation: &mut ObjectName) -> ControlFlow<Self::Break> {
+        dispatch!(self, pre_visit_relation, relation)
+    }
+
+    fn post_visit_relation(&mut self, relation: &mut ObjectName) -> ControlFlow<Self::Break> {
+        dispatch!(self, post_visit_relation, relation)
+    }
+
+    fn pre_visit_table_factor(
+        &mut self,
+        table_factor: &mut TableFactor,
+    ) -> ControlFlow<Self::Break> {
+        dispatch!(self, pre_visit_table_factor, table_factor)
+    }
+
+    fn post_visit_table_factor(
+        &mut self,
+        table_factor: &mut TableFactor,
+    ) -> ControlFlow<Self::Break> {
+        dispatch!(self, post_visit_table_factor, table_factor)
+    }
+
+    fn pre_visit_expr(&mut self, expr: &mut Expr) -> ControlFlow<Self::Break> {
+        dispatch!(self, pre_visit_expr, expr)
+    }
+
+    fn post_visit_expr(&mut self, expr: &mut Expr) -> ControlFlow<Self::Break> {
+        dispatch!(self, post_visit_expr, expr)
+    }
+
+    fn pre_visit_statement(&mut self, statement: &mut Statement) -> ControlFlow<Self::Break> {
+        dispatch!(self, pre_visit_statement, statement)
+    }
+
+    fn post_visit_statement(&mut self, statement: &mut Statement) -> ControlFlow<Self::Break> {
+        dispatch!(self, post_visit_statement, statement)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+    use crate::{Normalizer, TableExtractor};
+    use sqlparser::ast::{Visit, VisitMut};
+    use sqlparser::dialect::GenericDialect;
+    use sqlparser::parser::Parser;
+
+    #[derive(Default)]
+    struct ExprCounter {
+        count: usize,
+        limit: Option<usize>,
+    }
+
+    impl Visitor for ExprCounter {
+        type Break = Error;
+
+        fn pre_visit_expr(&mut self, _expr: &Expr) -> ControlFlow<Self::Break> {
+            self.count += 1;
+            match self.limit {
+                Some(limit) if self.count >= limit => {
+                    ControlFlow::Break(Error::AnalysisError("limit reached".into()))
+                }
+                _ => ControlFlow::Continue(()),
+            }
+        }
+    }
+
+    fn parse(sql: &str) -> Vec<Statement> {
+        Parser::parse_sql(&GenericDialect {}, sql).unwrap()
+    }
+
+    #[test]
+    fn test_all_visitors_run_in_single_traversal() {
+        let statements = parse("SELECT a FROM t1 INNER JOIN t2 ON t1.id = t2.id WHERE b = 1");
+        let mut tables = TableExtractor::default();
+        let mut counter = ExprCounter::default();
+        let mut visitor_set = VisitorSet::new()
+            .with_visitor(&mut tables)
+            .with_visitor(&mut counter);
+        assert_eq!(
+            statements[0].visit(&mut visitor_set),
+            ControlFlow::Continue(())
+        );
+        assert_eq!(visitor_set.into_breaks(), vec![None, None]);
+        assert_eq!(tables.into_tables().to_string(), "t1, t2");
+        assert_eq!(counter.count, 7);
+    }
+
+    #[test]
+    fn test_broken_visitor_is_skipped_while_others_continue() {
+        let statements = parse("SELECT a FROM t1 WHERE b = 1 AND c = 2");
+        let mut limited = ExprCounter {
+            limit: Some(2),
+            ..Default::default()
+        };
+        let mut unlimited = ExprCounter::default();
+        let mut visitor_set = VisitorSet::new()
+            .with_visitor(&mut limited)
+            .with_visitor(&mut unlimited);
+        assert_eq!(
+            statements[0].visit(&mut visitor_set),
+            ControlFlow::Continue(())
+        );
+        assert_eq!(
+            visitor_set.into_breaks(),
+            vec![Some(Error::AnalysisError("limit reached".into())), None]
+        );
+        assert_eq!(limited.count, 2);
+        assert_eq!(unlimited.count, 8);
+    }
+
+    #[test]
+    fn test_traversal_stops_when_all_visitors_break() {
+        let statements = parse("SELECT a FROM t1 WHERE b = 1 AND c = 2");
+        let mut first = ExprCounter {
+            limit: Some(1),
+            ..Default::default()
+        };
+        let mut second = ExprCounter {
+            limit: Some(3),
+            ..Default::default()
+        };
+        let mut visitor_set = VisitorSet::new()
+            .with_visitor(&mut first)
+            .with_visitor(&mut second);
+        assert_eq!(
+            statements[0].visit(&mut visitor_set),
+            ControlFlow::Break(())
+        );
+        assert_eq!(first.count, 1);
+        assert_eq!(second.count, 3);
+    }
+
+    #[test]
+    fn test_visitor_mut_set() {
+        let mut statements = parse("SELECT a FROM t1 WHERE b = 1 AND c IN (2, 3)");
+        let mut normalizer = Normalizer::new();
+        let mut visitor_set = VisitorMutSet::new().with_visitor(&mut normalizer);
+        assert_eq!(
+            VisitMut::visit(&mut statements[0], &mut visitor_set),
+            ControlFlow::Continue(())
+        );
+        assert_eq!(
+            statements[0].to_string(),
+            "SELECT a FROM t1 WHERE b = ? AND c IN (?, ?)"
+        );
+    }
+}
@@ -1,7 +1,7 @@
 #[cfg(test)]
 mod integration {
     use sql_insight::test_utils::all_dialects;
-    use sql_insight::{CrudTables, NormalizerOptions};
+    use sql_insight::{CrudTables, DdlTables, NormalizerOptions};
     use sql_insight::{TableReference, Tables};
 
     mod format {
@@ -82,6 +82,7 @@ mod integration {
                             }],
                             update_tables: vec![],
                             delete_tables: vec![],
+                            ddl: DdlTables::default(),
                         }),
                         Ok(CrudTables {
                             create_tables: vec![],
@@ -93,6 +94,7 @@ mod integration {
                             }],
                             update_tables: vec![],
                             delete_tables: vec![],
+                            ddl: DdlTables::default(),
                         }),
                     ],
                     "Failed for dialect: {dialect:?}"